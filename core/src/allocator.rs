@@ -1,9 +1,16 @@
 //! Tools for using different allocators with QuickJS.
 
-use crate::qjs;
+use crate::{qjs, util};
+use alloc::boxed::Box;
+use core::panic::AssertUnwindSafe;
 
+mod accounting;
+mod limit;
 mod rust;
 
+pub use accounting::ContextMemoryUsage;
+pub(crate) use accounting::{AccountingAllocator, AccountingScope, MemoryAccounting};
+pub use limit::LimitedAllocator;
 pub use rust::RustAllocator;
 
 /// The allocator interface
@@ -54,8 +61,24 @@ pub unsafe trait Allocator {
 
 type DynAllocator = Box<dyn Allocator>;
 
+/// The allocator of a runtime together with the size of its live allocations.
+struct AllocatorState {
+    allocator: DynAllocator,
+    /// The usable size of the live allocations in bytes.
+    size: usize,
+    /// The highest size reached.
+    peak: usize,
+}
+
+impl AllocatorState {
+    fn allocated(&mut self, size: usize) {
+        self.size += size;
+        self.peak = self.peak.max(self.size);
+    }
+}
+
 #[derive(Debug)]
-pub(crate) struct AllocatorHolder(*mut DynAllocator);
+pub(crate) struct AllocatorHolder(*mut AllocatorState);
 
 impl Drop for AllocatorHolder {
     fn drop(&mut self) {
@@ -81,11 +104,20 @@ impl AllocatorHolder {
     where
         A: Allocator + 'static,
     {
-        Self(Box::into_raw(Box::new(Box::new(allocator))))
+        Self(Box::into_raw(Box::new(AllocatorState {
+            allocator: Box::new(allocator),
+            size: 0,
+            peak: 0,
+        })))
     }
 
-    pub(crate) fn opaque_ptr(&self) -> *mut DynAllocator {
-        self.0
+    pub(crate) fn opaque_ptr(&self) -> *mut qjs::c_void {
+        self.0.cast()
+    }
+
+    /// Returns the highest number of bytes allocated at once.
+    pub(crate) fn peak(&self) -> usize {
+        unsafe { (*self.0).peak }
     }
 
     unsafe extern "C" fn calloc<A>(
@@ -96,19 +128,31 @@ impl AllocatorHolder {
     where
         A: Allocator,
     {
-        let allocator = &mut *(opaque as *mut DynAllocator);
-        let rust_size: usize = size.try_into().expect(qjs::SIZE_T_ERROR);
-        let rust_count: usize = count.try_into().expect(qjs::SIZE_T_ERROR);
-        allocator.calloc(rust_count, rust_size) as *mut qjs::c_void
+        let state = &mut *(opaque as *mut AllocatorState);
+        abort_on_panic(|| {
+            let rust_size: usize = size.try_into().expect(qjs::SIZE_T_ERROR);
+            let rust_count: usize = count.try_into().expect(qjs::SIZE_T_ERROR);
+            let ptr = state.allocator.calloc(rust_count, rust_size);
+            if !ptr.is_null() {
+                state.allocated(A::usable_size(ptr));
+            }
+            ptr as *mut qjs::c_void
+        })
     }
 
     unsafe extern "C" fn malloc<A>(opaque: *mut qjs::c_void, size: qjs::size_t) -> *mut qjs::c_void
     where
         A: Allocator,
     {
-        let allocator = &mut *(opaque as *mut DynAllocator);
-        let rust_size: usize = size.try_into().expect(qjs::SIZE_T_ERROR);
-        allocator.alloc(rust_size) as *mut qjs::c_void
+        let state = &mut *(opaque as *mut AllocatorState);
+        abort_on_panic(|| {
+            let rust_size: usize = size.try_into().expect(qjs::SIZE_T_ERROR);
+            let ptr = state.allocator.alloc(rust_size);
+            if !ptr.is_null() {
+                state.allocated(A::usable_size(ptr));
+            }
+            ptr as *mut qjs::c_void
+        })
     }
 
     unsafe extern "C" fn free<A>(opaque: *mut qjs::c_void, ptr: *mut qjs::c_void)
@@ -121,8 +165,11 @@ impl AllocatorHolder {
             return;
         }
 
-        let allocator = &mut *(opaque as *mut DynAllocator);
-        allocator.dealloc(ptr as _);
+        let state = &mut *(opaque as *mut AllocatorState);
+        abort_on_panic(|| {
+            state.size -= A::usable_size(ptr as _);
+            state.allocator.dealloc(ptr as _)
+        });
     }
 
     unsafe extern "C" fn realloc<A>(
@@ -133,9 +180,21 @@ impl AllocatorHolder {
     where
         A: Allocator,
     {
-        let rust_size: usize = size.try_into().expect(qjs::SIZE_T_ERROR);
-        let allocator = &mut *(opaque as *mut DynAllocator);
-        allocator.realloc(ptr as _, rust_size) as *mut qjs::c_void
+        let state = &mut *(opaque as *mut AllocatorState);
+        abort_on_panic(|| {
+            let rust_size: usize = size.try_into().expect(qjs::SIZE_T_ERROR);
+            let old_size = if ptr.is_null() {
+                0
+            } else {
+                A::usable_size(ptr as _)
+            };
+            let new_ptr = state.allocator.realloc(ptr as _, rust_size);
+            if !new_ptr.is_null() {
+                state.size -= old_size;
+                state.allocated(A::usable_size(new_ptr));
+            }
+            new_ptr as *mut qjs::c_void
+        })
     }
 
     unsafe extern "C" fn malloc_usable_size<A>(ptr: *const qjs::c_void) -> qjs::size_t
@@ -146,6 +205,14 @@ impl AllocatorHolder {
         if ptr.is_null() {
             return 0;
         }
-        A::usable_size(ptr as _).try_into().unwrap()
+        abort_on_panic(|| A::usable_size(ptr as _).try_into().unwrap())
     }
 }
+
+/// Run an allocator function, aborting if it panics.
+///
+/// The allocator is called from anywhere inside QuickJS, which can't be unwound through and has no
+/// way to report the error.
+fn abort_on_panic<R>(f: impl FnOnce() -> R) -> R {
+    util::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| util::abort())
+}
@@ -0,0 +1,319 @@
+use alloc::{collections::BTreeMap, rc::Rc};
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
+
+use super::Allocator;
+
+/// The memory attributed to a single context.
+///
+/// Retrieved with [`Context::memory_usage`](crate::Context::memory_usage) or
+/// [`Ctx::memory_usage`](crate::Ctx::memory_usage) from a runtime created with
+/// [`Runtime::new_with_accounting`](crate::Runtime::new_with_accounting).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContextMemoryUsage {
+    /// Number of live allocations, like objects, strings and shapes, made by the context.
+    pub allocations: usize,
+    /// Number of bytes requested by the live allocations of the context.
+    pub bytes: usize,
+}
+
+/// The limits of the memory of a context, set with
+/// [`Ctx::set_memory_limit`](crate::Ctx::set_memory_limit) and
+/// [`Ctx::set_max_allocation_size`](crate::Ctx::set_max_allocation_size).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ContextMemoryLimits {
+    bytes: Option<usize>,
+    allocation: Option<usize>,
+}
+
+/// Bookkeeping of the memory allocated by each context of a runtime.
+#[derive(Default)]
+pub(crate) struct MemoryAccounting {
+    /// The id of the entered context, `0` while no context is entered.
+    current: Cell<usize>,
+    last_id: Cell<usize>,
+    usage: RefCell<BTreeMap<usize, ContextMemoryUsage>>,
+    limits: RefCell<BTreeMap<usize, ContextMemoryLimits>>,
+}
+
+impl MemoryAccounting {
+    /// Returns a new id for a context.
+    pub fn new_id(&self) -> usize {
+        let id = self.last_id.get() + 1;
+        self.last_id.set(id);
+        id
+    }
+
+    /// Attribute allocations to the context with the given id until the scope is dropped.
+    pub fn enter(self: &Rc<Self>, id: usize) -> AccountingScope {
+        let prev = self.current.replace(id);
+        AccountingScope {
+            accounting: self.clone(),
+            prev,
+        }
+    }
+
+    pub fn usage(&self, id: usize) -> ContextMemoryUsage {
+        self.usage.borrow().get(&id).copied().unwrap_or_default()
+    }
+
+    pub fn set_memory_limit(&self, id: usize, limit: Option<usize>) {
+        self.update_limits(id, |x| x.bytes = limit);
+    }
+
+    pub fn set_max_allocation_size(&self, id: usize, size: Option<usize>) {
+        self.update_limits(id, |x| x.allocation = size);
+    }
+
+    fn update_limits(&self, id: usize, f: impl FnOnce(&mut ContextMemoryLimits)) {
+        let mut limits = self.limits.borrow_mut();
+        let entry = limits.entry(id).or_default();
+        f(entry);
+        if *entry == ContextMemoryLimits::default() {
+            limits.remove(&id);
+        }
+    }
+
+    /// Returns whether the context may grow by `grow` bytes with an allocation of `size` bytes.
+    fn allows(&self, id: usize, size: usize, grow: usize) -> bool {
+        let limits = self.limits.borrow();
+        let Some(limits) = limits.get(&id) else {
+            return true;
+        };
+        if matches!(limits.allocation, Some(max) if size > max) {
+            return false;
+        }
+        match limits.bytes {
+            Some(max) => self.usage(id).bytes.saturating_add(grow) <= max,
+            None => true,
+        }
+    }
+
+    fn add(&self, id: usize, size: usize) {
+        let mut usage = self.usage.borrow_mut();
+        let entry = usage.entry(id).or_default();
+        entry.allocations += 1;
+        entry.bytes += size;
+    }
+
+    fn remove(&self, id: usize, size: usize) {
+        let mut usage = self.usage.borrow_mut();
+        if let Some(entry) = usage.get_mut(&id) {
+            entry.allocations = entry.allocations.saturating_sub(1);
+            entry.bytes = entry.bytes.saturating_sub(size);
+            // Drop the entries of contexts without memory so freed contexts don't accumulate.
+            if entry.allocations == 0 {
+                usage.remove(&id);
+            }
+        }
+    }
+
+    fn resize(&self, id: usize, old_size: usize, new_size: usize) {
+        let mut usage = self.usage.borrow_mut();
+        if let Some(entry) = usage.get_mut(&id) {
+            entry.bytes = entry.bytes.saturating_sub(old_size) + new_size;
+        }
+    }
+}
+
+/// Restores the previously entered context when dropped.
+pub(crate) struct AccountingScope {
+    accounting: Rc<MemoryAccounting>,
+    prev: usize,
+}
+
+impl Drop for AccountingScope {
+    fn drop(&mut self) {
+        self.accounting.current.set(self.prev);
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Header {
+    id: usize,
+    size: usize,
+}
+
+/// Keeps the memory after the header aligned for any value QuickJS stores.
+const HEADER_SIZE: usize = 16;
+
+const _: () = assert!(mem::size_of::<Header>() <= HEADER_SIZE);
+
+/// An allocator which records for each allocation the context which made it.
+pub(crate) struct AccountingAllocator<A> {
+    inner: A,
+    accounting: Rc<MemoryAccounting>,
+}
+
+impl<A> AccountingAllocator<A> {
+    pub fn new(inner: A, accounting: Rc<MemoryAccounting>) -> Self {
+        AccountingAllocator { inner, accounting }
+    }
+
+    unsafe fn finish(&self, ptr: *mut u8, size: usize) -> *mut u8 {
+        if ptr.is_null() {
+            return ptr;
+        }
+        let id = self.accounting.current.get();
+        ptr.cast::<Header>().write(Header { id, size });
+        self.accounting.add(id, size);
+        ptr.add(HEADER_SIZE)
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for AccountingAllocator<A> {
+    fn alloc(&mut self, size: usize) -> *mut u8 {
+        let Some(alloc_size) = size.checked_add(HEADER_SIZE) else {
+            return core::ptr::null_mut();
+        };
+        if !self
+            .accounting
+            .allows(self.accounting.current.get(), size, size)
+        {
+            return core::ptr::null_mut();
+        }
+        let ptr = self.inner.alloc(alloc_size);
+        unsafe { self.finish(ptr, size) }
+    }
+
+    fn calloc(&mut self, count: usize, size: usize) -> *mut u8 {
+        let Some(size) = count.checked_mul(size) else {
+            return core::ptr::null_mut();
+        };
+        let Some(alloc_size) = size.checked_add(HEADER_SIZE) else {
+            return core::ptr::null_mut();
+        };
+        if !self
+            .accounting
+            .allows(self.accounting.current.get(), size, size)
+        {
+            return core::ptr::null_mut();
+        }
+        let ptr = self.inner.calloc(1, alloc_size);
+        unsafe { self.finish(ptr, size) }
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8) {
+        let ptr = ptr.sub(HEADER_SIZE);
+        let header = ptr.cast::<Header>().read();
+        self.accounting.remove(header.id, header.size);
+        self.inner.dealloc(ptr)
+    }
+
+    unsafe fn realloc(&mut self, ptr: *mut u8, new_size: usize) -> *mut u8 {
+        let Some(alloc_size) = new_size.checked_add(HEADER_SIZE) else {
+            return core::ptr::null_mut();
+        };
+        let ptr = ptr.sub(HEADER_SIZE);
+        let header = ptr.cast::<Header>().read();
+        // Shrinking is always allowed so contexts over their limit can still release memory.
+        if new_size > header.size
+            && !self
+                .accounting
+                .allows(header.id, new_size, new_size - header.size)
+        {
+            return core::ptr::null_mut();
+        }
+        let ptr = self.inner.realloc(ptr, alloc_size);
+        if ptr.is_null() {
+            return ptr;
+        }
+        // The allocation stays attributed to the context which originally made it.
+        ptr.cast::<Header>().write(Header {
+            id: header.id,
+            size: new_size,
+        });
+        self.accounting.resize(header.id, header.size, new_size);
+        ptr.add(HEADER_SIZE)
+    }
+
+    unsafe fn usable_size(ptr: *mut u8) -> usize {
+        A::usable_size(ptr.sub(HEADER_SIZE)) - HEADER_SIZE
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{allocator::RustAllocator, Context, Runtime};
+
+    #[test]
+    fn attributes_memory_to_contexts() {
+        let rt = Runtime::new_with_accounting(RustAllocator).unwrap();
+        let small = Context::full(&rt).unwrap();
+        let large = Context::full(&rt).unwrap();
+
+        large.with(|ctx| {
+            ctx.eval::<(), _>("globalThis.data = Array.from({ length: 10000 }, (_, i) => ({ i }))")
+                .unwrap();
+        });
+
+        let small_usage = small.memory_usage().unwrap();
+        let large_usage = large.memory_usage().unwrap();
+        assert!(small_usage.allocations > 0);
+        assert!(large_usage.allocations > small_usage.allocations + 10000);
+        assert!(large_usage.bytes > small_usage.bytes);
+
+        large.with(|ctx| {
+            assert_eq!(
+                ctx.memory_usage().unwrap().allocations,
+                large_usage.allocations
+            );
+            ctx.eval::<(), _>("globalThis.data = null").unwrap();
+        });
+        rt.run_gc();
+        assert!(large.memory_usage().unwrap().bytes < large_usage.bytes);
+    }
+
+    #[test]
+    fn limits_contexts() {
+        let rt = Runtime::new_with_accounting(RustAllocator).unwrap();
+        let limited = Context::full(&rt).unwrap();
+        let other = Context::full(&rt).unwrap();
+        let script = r#"
+            try {
+                globalThis.data = Array.from({ length: 100000 }, (_, i) => ({ i }));
+                true
+            } catch (e) {
+                e instanceof InternalError
+                    ? false
+                    : (() => { throw e })()
+            }
+        "#;
+
+        limited.with(|ctx| {
+            assert!(ctx.set_memory_limit(Some(1024 * 1024)));
+            assert!(!ctx.eval::<bool, _>(script).unwrap());
+            // The context stays usable after running out of memory.
+            assert_eq!(ctx.eval::<i32, _>("1 + 1").unwrap(), 2);
+            assert!(ctx.set_memory_limit(None));
+            assert!(ctx.eval::<bool, _>(script).unwrap());
+
+            assert!(ctx.set_max_allocation_size(Some(64 * 1024)));
+            assert!(ctx
+                .eval::<bool, _>("try { 'x'.repeat(1e6); false } catch { true }")
+                .unwrap());
+            assert_eq!(
+                ctx.eval::<usize, _>("'x'.repeat(1000).length").unwrap(),
+                1000
+            );
+        });
+        other.with(|ctx| {
+            assert!(ctx.eval::<bool, _>(script).unwrap());
+            assert!(ctx
+                .eval::<bool, _>("'x'.repeat(1e6).length == 1e6")
+                .unwrap());
+        });
+    }
+
+    #[test]
+    fn no_accounting_by_default() {
+        let rt = Runtime::new_with_alloc(RustAllocator).unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        assert!(ctx.memory_usage().is_none());
+        ctx.with(|ctx| assert!(!ctx.set_memory_limit(Some(1024))));
+    }
+}
@@ -0,0 +1,112 @@
+use super::{Allocator, RustAllocator};
+use core::ptr;
+
+/// An allocator which refuses any single allocation larger than a given size.
+///
+/// Large strings, arrays and buffers are backed by a single allocation in QuickJS, so limiting the
+/// size of a single allocation guards against scripts trying to create huge values like
+/// `"x".repeat(1e9)`. When an allocation is refused QuickJS throws an `out of memory` error which
+/// can be caught by the script or handled as an [`Error::Exception`](crate::Error::Exception)
+/// from Rust.
+///
+/// Note that the limit applies to the whole runtime as all contexts share the runtime allocator.
+/// Runtimes created with [`Runtime::new_with_accounting`](crate::Runtime::new_with_accounting)
+/// can limit contexts separately with
+/// [`Ctx::set_max_allocation_size`](crate::Ctx::set_max_allocation_size) and
+/// [`Ctx::set_memory_limit`](crate::Ctx::set_memory_limit).
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, allocator::LimitedAllocator};
+/// let rt = Runtime::new_with_alloc(LimitedAllocator::new(1024 * 1024)).unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     assert!(ctx.eval::<(), _>(r#""x".repeat(16 * 1024 * 1024)"#).is_err());
+/// });
+/// ```
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+pub struct LimitedAllocator<A = RustAllocator> {
+    inner: A,
+    max_size: usize,
+}
+
+impl LimitedAllocator {
+    /// Create a limited allocator on top of the Rust global allocator.
+    pub fn new(max_size: usize) -> Self {
+        Self::with_allocator(RustAllocator, max_size)
+    }
+}
+
+impl<A> LimitedAllocator<A> {
+    /// Create a limited allocator on top of the given allocator.
+    pub fn with_allocator(inner: A, max_size: usize) -> Self {
+        LimitedAllocator { inner, max_size }
+    }
+
+    /// Returns the maximum size of a single allocation.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for LimitedAllocator<A> {
+    fn alloc(&mut self, size: usize) -> *mut u8 {
+        if size > self.max_size {
+            return ptr::null_mut();
+        }
+        self.inner.alloc(size)
+    }
+
+    fn calloc(&mut self, count: usize, size: usize) -> *mut u8 {
+        match count.checked_mul(size) {
+            Some(total) if total <= self.max_size => self.inner.calloc(count, size),
+            _ => ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8) {
+        self.inner.dealloc(ptr)
+    }
+
+    unsafe fn realloc(&mut self, ptr: *mut u8, new_size: usize) -> *mut u8 {
+        if new_size > self.max_size {
+            return ptr::null_mut();
+        }
+        self.inner.realloc(ptr, new_size)
+    }
+
+    unsafe fn usable_size(ptr: *mut u8) -> usize {
+        A::usable_size(ptr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LimitedAllocator;
+    use crate::{CatchResultExt, Context, Runtime};
+
+    #[test]
+    fn refuses_large_allocations() {
+        let rt = Runtime::new_with_alloc(LimitedAllocator::new(1024 * 1024)).unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let caught: bool = ctx
+                .eval(
+                    r#"
+                    let caught = false;
+                    try {
+                        "x".repeat(16 * 1024 * 1024);
+                    } catch (e) {
+                        caught = e instanceof InternalError;
+                    }
+                    caught
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert!(caught);
+
+            let len: usize = ctx.eval(r#""x".repeat(1024).length"#).unwrap();
+            assert_eq!(len, 1024);
+        });
+    }
+}
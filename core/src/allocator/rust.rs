@@ -1,7 +1,5 @@
-use std::{
-    alloc::{self, Layout},
-    mem, ptr,
-};
+use alloc::alloc::Layout;
+use core::{mem, ptr};
 
 use super::Allocator;
 
@@ -51,7 +49,7 @@ unsafe impl Allocator for RustAllocator {
             return ptr::null_mut();
         };
 
-        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = unsafe { alloc::alloc::alloc_zeroed(layout) };
 
         if ptr.is_null() {
             return ptr::null_mut();
@@ -73,7 +71,7 @@ unsafe impl Allocator for RustAllocator {
             return ptr::null_mut();
         };
 
-        let ptr = unsafe { alloc::alloc(layout) };
+        let ptr = unsafe { alloc::alloc::alloc(layout) };
 
         if ptr.is_null() {
             return ptr::null_mut();
@@ -90,7 +88,7 @@ unsafe impl Allocator for RustAllocator {
         let alloc_size = ptr.cast::<Header>().read().size + HEADER_SIZE;
         let layout = Layout::from_size_align_unchecked(alloc_size, ALLOC_ALIGN);
 
-        alloc::dealloc(ptr, layout);
+        alloc::alloc::dealloc(ptr, layout);
     }
 
     unsafe fn realloc(&mut self, ptr: *mut u8, new_size: usize) -> *mut u8 {
@@ -103,7 +101,7 @@ unsafe impl Allocator for RustAllocator {
 
         let new_alloc_size = new_size + HEADER_SIZE;
 
-        let ptr = alloc::realloc(ptr, layout, new_alloc_size);
+        let ptr = alloc::alloc::realloc(ptr, layout, new_alloc_size);
 
         if ptr.is_null() {
             return ptr::null_mut();
@@ -123,7 +121,7 @@ unsafe impl Allocator for RustAllocator {
 mod test {
     use super::RustAllocator;
     use crate::{allocator::Allocator, Context, Runtime};
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    use core::sync::atomic::{AtomicUsize, Ordering};
 
     static ALLOC_SIZE: AtomicUsize = AtomicUsize::new(0);
 
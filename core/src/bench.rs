@@ -0,0 +1,464 @@
+//! A small harness for measuring the throughput of bindings.
+//!
+//! [`Bench`] measures closures, script evaluation, function calls and value conversions, reporting
+//! the time per iteration and its spread over the samples. The measurements can be kept as a
+//! [`Baseline`], a plain text file meant to be committed, and later runs compared against it to
+//! find regressions, for example after upgrading rquickjs.
+//!
+//! ```
+//! # use std::time::Duration;
+//! # use rquickjs::{Runtime, Context, Function, bench::{Bench, Baseline}};
+//! let rt = Runtime::new().unwrap();
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     let mut bench = Bench::new()
+//!         .with_warm_up(Duration::from_millis(5))
+//!         .with_samples(5)
+//!         .with_sample_time(Duration::from_millis(1));
+//!     bench.eval(&ctx, "eval", "1 + 1").unwrap();
+//!     let add: Function = ctx.eval("(a, b) => a + b").unwrap();
+//!     bench.call("call", &add, (1, 2)).unwrap();
+//!     let list = ctx.eval("[1, 2, 3]").unwrap();
+//!     bench.convert::<Vec<i32>>(&ctx, "convert", &list).unwrap();
+//!
+//!     // A baseline parsed from a previous run, allowing runs to be 50% slower.
+//!     let baseline: Baseline = "eval\t1000000000\t0\ncall\t1000000000\t0\n".parse().unwrap();
+//!     assert!(bench.compare(&baseline, 0.5).is_empty());
+//! });
+//! ```
+
+use alloc::collections::BTreeMap;
+use core::{fmt, str::FromStr, time::Duration};
+use std::time::Instant;
+
+use crate::{function::IntoArgs, Ctx, FromJs, Function, IntoJs, Result, StdString, Value};
+
+/// The measured time of a benchmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// The name of the benchmark.
+    pub name: StdString,
+    /// The number of measured iterations over all samples.
+    pub iterations: u64,
+    /// The mean time per iteration.
+    pub mean: Duration,
+    /// The median of the time per iteration of the samples.
+    pub median: Duration,
+    /// The time per iteration of the fastest sample.
+    pub min: Duration,
+    /// The time per iteration of the slowest sample.
+    pub max: Duration,
+    /// The standard deviation of the time per iteration of the samples.
+    pub std_dev: Duration,
+}
+
+impl Measurement {
+    /// Returns the number of iterations per second, based on the mean.
+    pub fn per_second(&self) -> f64 {
+        1.0 / self.mean.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:?} ± {:?} per iteration (median {:?}, min {:?}, max {:?}), {:.0}/s",
+            self.name,
+            self.mean,
+            self.std_dev,
+            self.median,
+            self.min,
+            self.max,
+            self.per_second()
+        )
+    }
+}
+
+/// Runs benchmarks and collects their [`Measurement`]s.
+///
+/// Every benchmark is first run for the warm up time, then measured in samples. The number of
+/// iterations of a sample is chosen so that a sample takes at least the sample time.
+#[derive(Debug, Clone)]
+pub struct Bench {
+    warm_up: Duration,
+    samples: usize,
+    sample_time: Duration,
+    measurements: Vec<Measurement>,
+}
+
+impl Default for Bench {
+    fn default() -> Self {
+        Bench::new()
+    }
+}
+
+impl Bench {
+    /// Create a harness with 100ms of warm up and 20 samples of at least 10ms.
+    pub fn new() -> Self {
+        Bench {
+            warm_up: Duration::from_millis(100),
+            samples: 20,
+            sample_time: Duration::from_millis(10),
+            measurements: Vec::new(),
+        }
+    }
+
+    /// Set the time a benchmark is run before it is measured.
+    #[must_use]
+    pub fn with_warm_up(mut self, warm_up: Duration) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Set the number of samples, at least one.
+    #[must_use]
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    /// Set the minimum time of a sample.
+    #[must_use]
+    pub fn with_sample_time(mut self, sample_time: Duration) -> Self {
+        self.sample_time = sample_time;
+        self
+    }
+
+    /// Measure a closure, stopping at the first error it returns.
+    pub fn run<F, R>(&mut self, name: &str, mut f: F) -> Result<&Measurement>
+    where
+        F: FnMut() -> Result<R>,
+    {
+        // Warm up while finding how many iterations fill a sample.
+        let start = Instant::now();
+        let mut iterations = 0u64;
+        loop {
+            f()?;
+            iterations += 1;
+            if start.elapsed() >= self.warm_up {
+                break;
+            }
+        }
+        let per_iteration = start.elapsed().as_secs_f64() / iterations as f64;
+        let batch = (self.sample_time.as_secs_f64() / per_iteration.max(1e-9)).ceil() as u64;
+        let batch = batch.max(1);
+
+        let mut samples = Vec::with_capacity(self.samples);
+        for _ in 0..self.samples {
+            let start = Instant::now();
+            for _ in 0..batch {
+                f()?;
+            }
+            samples.push(Duration::from_secs_f64(
+                start.elapsed().as_secs_f64() / batch as f64,
+            ));
+        }
+        samples.sort();
+        let iterations = batch * self.samples as u64;
+        let mean = samples.iter().sum::<Duration>() / self.samples as u32;
+        let variance = samples
+            .iter()
+            .map(|x| (x.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / (self.samples.max(2) - 1) as f64;
+        self.measurements.push(Measurement {
+            name: name.into(),
+            iterations,
+            mean,
+            median: samples[samples.len() / 2],
+            min: samples[0],
+            max: samples[samples.len() - 1],
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        });
+        Ok(self.measurements.last().unwrap())
+    }
+
+    /// Measure evaluating a script.
+    pub fn eval<'js>(&mut self, ctx: &Ctx<'js>, name: &str, source: &str) -> Result<&Measurement> {
+        self.run(name, || ctx.eval::<(), _>(source))
+    }
+
+    /// Measure calling a function with the given arguments.
+    pub fn call<'js, A>(
+        &mut self,
+        name: &str,
+        function: &Function<'js>,
+        args: A,
+    ) -> Result<&Measurement>
+    where
+        A: IntoArgs<'js> + Clone,
+    {
+        self.run(name, || function.call::<_, Value>(args.clone()))
+    }
+
+    /// Measure converting a value to `T` and back.
+    pub fn convert<'js, T>(
+        &mut self,
+        ctx: &Ctx<'js>,
+        name: &str,
+        value: &Value<'js>,
+    ) -> Result<&Measurement>
+    where
+        T: FromJs<'js> + IntoJs<'js>,
+    {
+        self.run(name, || T::from_js(ctx, value.clone())?.into_js(ctx))
+    }
+
+    /// Returns the measurements in the order the benchmarks ran.
+    pub fn measurements(&self) -> &[Measurement] {
+        &self.measurements
+    }
+
+    /// Returns the mean times and standard deviations of the measurements as a baseline for
+    /// later runs.
+    pub fn baseline(&self) -> Baseline {
+        Baseline(
+            self.measurements
+                .iter()
+                .map(|x| {
+                    let entry = BaselineEntry {
+                        mean: x.mean,
+                        std_dev: x.std_dev,
+                    };
+                    (x.name.clone(), entry)
+                })
+                .collect(),
+        )
+    }
+
+    /// Compare the measurements with a baseline.
+    ///
+    /// Returns the benchmarks whose mean time grew by more than `threshold`, a fraction of the
+    /// baseline time, so `0.1` allows runs to be 10% slower. As both means are noisy, the growth
+    /// must also exceed twice the combined standard deviation of the baseline and the
+    /// measurement, so a regression is only reported when it stands out of the noise of the runs.
+    /// Benchmarks missing from the baseline are ignored.
+    pub fn compare(&self, baseline: &Baseline, threshold: f64) -> Vec<Regression> {
+        self.measurements
+            .iter()
+            .filter_map(|x| {
+                let expected = baseline.0.get(&x.name)?;
+                let (mean, current) = (expected.mean.as_secs_f64(), x.mean.as_secs_f64());
+                let noise = (expected.std_dev.as_secs_f64().powi(2)
+                    + x.std_dev.as_secs_f64().powi(2))
+                .sqrt();
+                let growth = current - mean;
+                let ratio = current / mean.max(1e-12);
+                (growth > mean * threshold && growth > 2.0 * noise).then(|| Regression {
+                    name: x.name.clone(),
+                    baseline: expected.mean,
+                    current: x.mean,
+                    ratio,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The time per iteration of an earlier measurement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BaselineEntry {
+    /// The mean time per iteration.
+    pub mean: Duration,
+    /// The standard deviation of the time per iteration of the samples.
+    pub std_dev: Duration,
+}
+
+/// The times per iteration of earlier measurements by name.
+///
+/// A baseline is written and parsed as lines of a name, the mean and the standard deviation in
+/// nanoseconds, separated by tabs. Backslashes, tabs and line breaks in names are escaped as
+/// `\\`, `\t`, `\n` and `\r`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline(pub BTreeMap<StdString, BaselineEntry>);
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, entry) in &self.0 {
+            for c in name.chars() {
+                match c {
+                    '\\' => f.write_str("\\\\")?,
+                    '\t' => f.write_str("\\t")?,
+                    '\n' => f.write_str("\\n")?,
+                    '\r' => f.write_str("\\r")?,
+                    c => write!(f, "{c}")?,
+                }
+            }
+            writeln!(
+                f,
+                "\t{}\t{}",
+                entry.mean.as_nanos(),
+                entry.std_dev.as_nanos()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn unescape(name: &str) -> Option<StdString> {
+    let mut res = StdString::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        res.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(res)
+}
+
+/// An error parsing a [`Baseline`], with the number of the invalid line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBaselineError {
+    /// The line number, starting at 1.
+    pub line: usize,
+}
+
+impl fmt::Display for ParseBaselineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid baseline entry on line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseBaselineError {}
+
+impl FromStr for Baseline {
+    type Err = ParseBaselineError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        let mut res = BTreeMap::new();
+        for (idx, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = || ParseBaselineError { line: idx + 1 };
+            let mut fields = line.split('\t');
+            let (Some(name), Some(mean), Some(std_dev), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(error());
+            };
+            let nanos = |x: &str| {
+                x.trim()
+                    .parse()
+                    .map(Duration::from_nanos)
+                    .map_err(|_| error())
+            };
+            let entry = BaselineEntry {
+                mean: nanos(mean)?,
+                std_dev: nanos(std_dev)?,
+            };
+            res.insert(unescape(name).ok_or_else(error)?, entry);
+        }
+        Ok(Baseline(res))
+    }
+}
+
+/// A benchmark which got slower than its baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// The name of the benchmark.
+    pub name: StdString,
+    /// The mean time per iteration of the baseline.
+    pub baseline: Duration,
+    /// The mean time per iteration of the measurement.
+    pub current: Duration,
+    /// The current time divided by the baseline time.
+    pub ratio: f64,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:?} per iteration is {:.1}% slower than the baseline of {:?}",
+            self.name,
+            self.current,
+            (self.ratio - 1.0) * 100.0,
+            self.baseline
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{Baseline, BaselineEntry, Bench, Measurement};
+    use crate::*;
+
+    #[test]
+    fn bench() {
+        test_with(|ctx| {
+            let mut bench = Bench::new()
+                .with_warm_up(Duration::ZERO)
+                .with_samples(3)
+                .with_sample_time(Duration::from_micros(100));
+            let measurement = bench
+                .eval(&ctx, "eval", "[1, 2, 3].map((x) => x * 2)")
+                .unwrap();
+            assert!(measurement.iterations >= 3);
+            assert!(measurement.min <= measurement.median && measurement.median <= measurement.max);
+            assert!(measurement.per_second() > 0.0);
+            let f: Function = ctx.eval("(x) => x + 1").unwrap();
+            bench.call("call", &f, (1,)).unwrap();
+            let value = ctx.eval("({ a: 1, b: 2 })").unwrap();
+            bench
+                .convert::<std::collections::HashMap<StdString, i32>>(&ctx, "convert", &value)
+                .unwrap();
+            assert!(bench.eval(&ctx, "throws", "throw 1").is_err());
+            ctx.catch();
+            let names: Vec<_> = bench.measurements().iter().map(|x| &x.name).collect();
+            assert_eq!(names, ["eval", "call", "convert"]);
+
+            let mut baseline = bench.baseline();
+            let entry = BaselineEntry {
+                mean: Duration::from_nanos(5),
+                std_dev: Duration::from_nanos(1),
+            };
+            baseline.0.insert("a\tb\\n\nc\r".into(), entry);
+            let text = baseline.to_string();
+            assert!(text.contains("a\\tb\\\\n\\nc\\r\t5\t1\n"));
+            let parsed: Baseline = text.parse().unwrap();
+            assert_eq!(parsed, baseline);
+
+            // Compare known measurements, the measured ones are too noisy.
+            let mut known = Bench::new();
+            for name in ["eval", "call"] {
+                known.measurements.push(Measurement {
+                    name: name.into(),
+                    iterations: 1,
+                    mean: Duration::from_nanos(1000),
+                    median: Duration::from_nanos(1000),
+                    min: Duration::from_nanos(900),
+                    max: Duration::from_nanos(1100),
+                    std_dev: Duration::from_nanos(100),
+                });
+            }
+            let fast: Baseline = "eval\t500\t50\ncall\t1000000\t0\nmissing\t1\t0\n"
+                .parse()
+                .unwrap();
+            let regressions = known.compare(&fast, 0.1);
+            assert_eq!(regressions.len(), 1);
+            assert_eq!(regressions[0].name, "eval");
+            assert_eq!(regressions[0].ratio, 2.0);
+            assert!(regressions[0].to_string().starts_with("eval: "));
+            // A growth within the noise of the runs isn't a regression.
+            let noisy: Baseline = "eval\t500\t300\n".parse().unwrap();
+            assert!(known.compare(&noisy, 0.1).is_empty());
+
+            assert_eq!(
+                "ok\t1\t0\nbad\t2\n".parse::<Baseline>().unwrap_err().line,
+                2
+            );
+            assert_eq!("a\\x\t1\t0\n".parse::<Baseline>().unwrap_err().line, 1);
+        })
+    }
+}
@@ -0,0 +1,231 @@
+//! Channels for passing values between runtimes.
+//!
+//! JavaScript values can't be shared between runtimes, instead values are serialized with the
+//! structured clone like serialization of QuickJS when sent and reconstructed in the receiving
+//! context. Functions, class instances and other values which can't be serialized are refused
+//! with an exception.
+//!
+//! The type sent over a [`channel`] is a Rust type which is converted from and into JavaScript,
+//! to pass JavaScript values as is send a [`Message`] instead.
+//!
+//! ```
+//! # use std::collections::HashMap;
+//! # use rquickjs::{channel, Context, Runtime};
+//! let (sender, receiver) = channel::channel::<HashMap<String, Vec<i32>>>();
+//!
+//! let worker = std::thread::spawn(move || {
+//!     let rt = Runtime::new().unwrap();
+//!     let ctx = Context::full(&rt).unwrap();
+//!     ctx.with(|ctx| {
+//!         let value = ctx.eval("({ tasks: [1, 2, 3] })").unwrap();
+//!         sender.send(&ctx, value).unwrap();
+//!     });
+//! });
+//! worker.join().unwrap();
+//!
+//! let rt = Runtime::new().unwrap();
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     let value = receiver.recv(&ctx).unwrap();
+//!     assert_eq!(value["tasks"], [1, 2, 3]);
+//! });
+//! ```
+
+use alloc::vec::Vec;
+use core::{marker::PhantomData, mem::MaybeUninit, slice};
+use std::sync::mpsc;
+
+use crate::{qjs, Ctx, Error, FromJs, IntoJs, Result, Value};
+
+/// A JavaScript value serialized for sending to another runtime.
+#[derive(Debug, Clone)]
+pub struct Message(Vec<u8>);
+
+impl Message {
+    /// Serialize a value.
+    ///
+    /// Object references are preserved so objects which are referenced multiple times, including
+    /// cyclic references, are reconstructed as such.
+    pub fn write<'js>(value: &Value<'js>) -> Result<Self> {
+        let ctx = value.ctx();
+        let mut len = MaybeUninit::uninit();
+        let buf = unsafe {
+            qjs::JS_WriteObject(
+                ctx.as_ptr(),
+                len.as_mut_ptr(),
+                value.as_js_value(),
+                qjs::JS_WRITE_OBJ_REFERENCE as i32,
+            )
+        };
+        if buf.is_null() {
+            return Err(ctx.raise_exception());
+        }
+        let len = unsafe { len.assume_init() };
+        let bytes = Vec::from(unsafe { slice::from_raw_parts(buf, len as _) });
+        unsafe { qjs::js_free(ctx.as_ptr(), buf as _) };
+        Ok(Message(bytes))
+    }
+
+    /// Reconstruct the serialized value in the given context.
+    pub fn read<'js>(&self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        unsafe {
+            let value = qjs::JS_ReadObject(
+                ctx.as_ptr(),
+                self.0.as_ptr(),
+                self.0.len() as _,
+                qjs::JS_READ_OBJ_REFERENCE as i32,
+            );
+            let value = ctx.handle_exception(value)?;
+            Ok(Value::from_js_value(ctx.clone(), value))
+        }
+    }
+
+    /// Returns the size of the serialized value in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether the serialized value is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Create a new channel for sending values of type `T` between runtimes.
+///
+/// The sender and receiver can be used from any thread with any runtime.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    (
+        Sender {
+            inner: sender,
+            _marker: PhantomData,
+        },
+        Receiver {
+            inner: receiver,
+            _marker: PhantomData,
+        },
+    )
+}
+
+/// The sending half of a [`channel`].
+pub struct Sender<T> {
+    inner: mpsc::Sender<Message>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender {
+            inner: self.inner.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    /// Serialize a value and send it over the channel.
+    ///
+    /// Returns [`Error::ChannelDisconnected`] if the receiver was dropped.
+    pub fn send<'js>(&self, ctx: &Ctx<'js>, value: T) -> Result<()>
+    where
+        T: IntoJs<'js>,
+    {
+        let value = value.into_js(ctx)?;
+        self.send_message(Message::write(&value)?)
+    }
+
+    /// Send an already serialized value over the channel.
+    pub fn send_message(&self, message: Message) -> Result<()> {
+        self.inner
+            .send(message)
+            .map_err(|_| Error::ChannelDisconnected)
+    }
+}
+
+/// The receiving half of a [`channel`].
+pub struct Receiver<T> {
+    inner: mpsc::Receiver<Message>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Receiver<T> {
+    /// Wait for a value and reconstruct it in the given context.
+    ///
+    /// Returns [`Error::ChannelDisconnected`] if all senders were dropped.
+    pub fn recv<'js>(&self, ctx: &Ctx<'js>) -> Result<T>
+    where
+        T: FromJs<'js>,
+    {
+        let message = self.recv_message()?;
+        T::from_js(ctx, message.read(ctx)?)
+    }
+
+    /// Reconstruct a value in the given context if one is available, without waiting.
+    pub fn try_recv<'js>(&self, ctx: &Ctx<'js>) -> Result<Option<T>>
+    where
+        T: FromJs<'js>,
+    {
+        match self.inner.try_recv() {
+            Ok(message) => T::from_js(ctx, message.read(ctx)?).map(Some),
+            Err(mpsc::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::TryRecvError::Disconnected) => Err(Error::ChannelDisconnected),
+        }
+    }
+
+    /// Wait for a value without reconstructing it.
+    pub fn recv_message(&self) -> Result<Message> {
+        self.inner.recv().map_err(|_| Error::ChannelDisconnected)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{channel, Message};
+    use crate::{Context, Error, Function, Runtime};
+
+    #[test]
+    fn send_between_runtimes() {
+        let (sender, receiver) = channel::<Vec<String>>();
+        let (message_sender, message_receiver) = channel::<()>();
+
+        let rt_a = Runtime::new().unwrap();
+        let ctx_a = Context::full(&rt_a).unwrap();
+        ctx_a.with(|ctx| {
+            sender
+                .send(&ctx, vec!["one".to_string(), "two".to_string()])
+                .unwrap();
+            let value = ctx
+                .eval("const a = { list: [1, 'two'], date: 3 }; a.self = a; a")
+                .unwrap();
+            message_sender
+                .send_message(Message::write(&value).unwrap())
+                .unwrap();
+            let func: Function = ctx.eval("() => {}").unwrap();
+            assert!(Message::write(&func.into_value()).is_err());
+            ctx.catch();
+        });
+
+        let rt_b = Runtime::new().unwrap();
+        let ctx_b = Context::full(&rt_b).unwrap();
+        ctx_b.with(|ctx| {
+            assert_eq!(receiver.recv(&ctx).unwrap(), ["one", "two"]);
+            assert!(receiver.try_recv(&ctx).unwrap().is_none());
+
+            let value = message_receiver.recv_message().unwrap().read(&ctx).unwrap();
+            ctx.globals().set("value", value).unwrap();
+            let ok: bool = ctx
+                .eval("value.list[1] === 'two' && value.date === 3 && value.self === value")
+                .unwrap();
+            assert!(ok);
+        });
+
+        drop(sender);
+        ctx_b.with(|ctx| {
+            assert!(matches!(
+                receiver.recv(&ctx),
+                Err(Error::ChannelDisconnected)
+            ));
+        });
+    }
+}
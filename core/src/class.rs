@@ -1,21 +1,46 @@
 //! JavaScript classes defined from Rust.
+//!
+//! # Class identity
+//!
+//! Rust classes don't need to be registered with a runtime. All Rust classes share a single
+//! QuickJS class id which is allocated when the runtime is created, and the Rust type of an
+//! instance is identified by the [`TypeId`](std::any::TypeId) of the class. Prototypes are
+//! created lazily on first use and cached per runtime under the same type id.
+//!
+//! As a result instances can be freely passed between crates which use the same class type.
+//! This includes native modules loaded from a dynamic library, as long as the library is built
+//! with the same compiler and the same version of this crate as the host, since type ids are
+//! only stable under those conditions. An instance of a type which is unknown to the code
+//! checking it is never mistaken for a different class, [`Object::instance_of`] will return false.
+//!
+//! To detect such a mismatch early a class can be registered with
+//! [`Runtime::register_class`](crate::Runtime::register_class), which reserves its name in the
+//! runtime. Registering or using a different type under a registered name, for example the same
+//! class from a module built with another version of this crate, returns
+//! [`Error::ClassConflict`] instead of creating instances the other side doesn't recognize.
 
 use crate::{
     function::Params,
     qjs::{self},
+    runtime::{opaque::Opaque, UserDataGuard},
     value::Constructor,
     Ctx, Error, FromJs, IntoJs, JsLifetime, Object, Result, Value,
 };
-use std::{hash::Hash, marker::PhantomData, mem, ops::Deref, ptr::NonNull};
+use alloc::{boxed::Box, sync::Arc};
+use core::{any::Any, hash::Hash, marker::PhantomData, mem, ops::Deref, ptr::NonNull};
 
 mod cell;
+mod external;
 mod trace;
 
 pub(crate) mod ffi;
 
 pub use cell::{
-    Borrow, BorrowMut, JsCell, Mutability, OwnedBorrow, OwnedBorrowMut, Readable, Writable,
+    Borrow, BorrowMut, JsCell, Mutability, OwnedBorrow, OwnedBorrowMut, Readable, Shared, Writable,
 };
+pub use external::External;
+#[doc(hidden)]
+pub use external::ExternalData;
 use ffi::{ClassCell, VTable};
 pub use trace::{Trace, Tracer};
 #[doc(hidden)]
@@ -31,7 +56,7 @@ pub trait JsClass<'js>: Trace<'js> + JsLifetime<'js> + Sized {
 
     /// Can the type be mutated while a JavaScript value.
     ///
-    /// This should either be [`Readable`] or [`Writable`].
+    /// This should either be [`Readable`], [`Writable`] or [`Shared`].
     type Mutable: Mutability;
 
     /// Returns the class prototype,
@@ -42,6 +67,20 @@ pub trait JsClass<'js>: Trace<'js> + JsLifetime<'js> + Sized {
     /// Returns a predefined constructor for this specific class type if there is one.
     fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>>;
 
+    /// Called when the garbage collector frees an instance, right before the value is dropped.
+    ///
+    /// Finalization happens while the runtime is locked and outside of any context, so
+    /// JavaScript values can't be created or used here. The handle gives access to runtime level
+    /// state like userdata, for example to return resources held by the instance to a registry.
+    ///
+    /// Every instance is finalized and dropped exactly once: when it becomes unreachable, or at
+    /// the latest when the runtime is freed. The userdata of the runtime is already removed by
+    /// then. An instance which was never turned into a JavaScript object is only dropped. Panics
+    /// in a finalizer are dropped, the value is still dropped afterwards.
+    fn finalize(&mut self, rt: &RuntimeHandle<'_>) {
+        let _ = rt;
+    }
+
     /// The function which will be called if [`Self::CALLABLE`] is true and an an object with this
     /// class is called as if it is a function.
     fn call<'a>(this: &JsCell<'js, Self>, params: Params<'a, 'js>) -> Result<Value<'js>> {
@@ -50,6 +89,50 @@ pub trait JsClass<'js>: Trace<'js> + JsLifetime<'js> + Sized {
     }
 }
 
+/// A handle to the runtime passed to [`JsClass::finalize`].
+pub struct RuntimeHandle<'a> {
+    rt: NonNull<qjs::JSRuntime>,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> RuntimeHandle<'a> {
+    pub(crate) unsafe fn from_ptr(rt: *mut qjs::JSRuntime) -> Self {
+        RuntimeHandle {
+            rt: NonNull::new_unchecked(rt),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the raw runtime pointer.
+    pub fn as_raw(&self) -> NonNull<qjs::JSRuntime> {
+        self.rt
+    }
+
+    /// Retrieves a borrow to the userdata of the given type stored in the runtime.
+    ///
+    /// See [`Runtime::store_userdata`](crate::Runtime::store_userdata).
+    pub fn userdata<U>(&self) -> Option<UserDataGuard<'a, U>>
+    where
+        U: JsLifetime<'static>,
+        U::Changed<'static>: Any,
+    {
+        unsafe { Opaque::from_runtime_ptr(self.rt.as_ptr()).get_userdata() }
+    }
+}
+
+/// The id of a class registered with [`Runtime::register_class`](crate::Runtime::register_class).
+///
+/// Ids are assigned in order of registration and are unique within a runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClassId(pub(crate) u32);
+
+impl ClassId {
+    /// Returns the id as an integer.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
 /// A object which is instance of a Rust class.
 #[repr(transparent)]
 pub struct Class<'js, C: JsClass<'js>>(pub(crate) Object<'js>, PhantomData<C>);
@@ -69,7 +152,7 @@ impl<'js, C: JsClass<'js>> PartialEq for Class<'js, C> {
 impl<'js, C: JsClass<'js>> Eq for Class<'js, C> {}
 
 impl<'js, C: JsClass<'js>> Hash for Class<'js, C> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.0.hash(state)
     }
 }
@@ -93,6 +176,52 @@ impl<'js, C: JsClass<'js>> Deref for Class<'js, C> {
 impl<'js, C: JsClass<'js>> Class<'js, C> {
     /// Create a class from a Rust object.
     pub fn instance(ctx: Ctx<'js>, value: C) -> Result<Class<'js, C>> {
+        Self::instance_cell(ctx, JsCell::new(value))
+    }
+
+    /// Create a class from a Rust object which is shared with Rust code.
+    ///
+    /// The object keeps a handle to the value, so the same value can be used from JavaScript and
+    /// from Rust at the same time. Only classes with [`Shared`] as their mutability can be created
+    /// this way.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Class, class::{JsClass, Shared, Trace, Tracer}, function::Constructor, Ctx, JsLifetime, Result};
+    /// # use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+    /// struct Counter(AtomicU32);
+    /// # impl<'js> Trace<'js> for Counter {
+    /// #     fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+    /// # }
+    /// # unsafe impl<'js> JsLifetime<'js> for Counter {
+    /// #     type Changed<'to> = Counter;
+    /// # }
+    ///
+    /// impl<'js> JsClass<'js> for Counter {
+    ///     const NAME: &'static str = "Counter";
+    ///     type Mutable = Shared;
+    ///     fn constructor(_ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+    ///         Ok(None)
+    ///     }
+    /// }
+    ///
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// let counter = Arc::new(Counter(AtomicU32::new(0)));
+    /// ctx.with(|ctx| {
+    ///     let instance = Class::instance_arc(ctx.clone(), counter.clone()).unwrap();
+    ///     instance.arc().0.fetch_add(1, Ordering::Relaxed);
+    ///     assert!(Arc::ptr_eq(&instance.arc(), &counter));
+    /// });
+    /// assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn instance_arc(ctx: Ctx<'js>, value: Arc<C>) -> Result<Class<'js, C>>
+    where
+        C: JsClass<'js, Mutable = Shared>,
+    {
+        Self::instance_cell(ctx, JsCell::from_arc(value))
+    }
+
+    fn instance_cell(ctx: Ctx<'js>, value: JsCell<'js, C>) -> Result<Class<'js, C>> {
         let id = unsafe {
             if C::CALLABLE {
                 ctx.get_opaque().get_callable_id()
@@ -108,7 +237,7 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
             ctx.handle_exception(qjs::JS_NewObjectProtoClass(ctx.as_ptr(), prototype, id))?
         };
 
-        let ptr = Box::into_raw(Box::new(ClassCell::new(value)));
+        let ptr = Box::into_raw(Box::new(ClassCell::from_cell(value)));
         unsafe { qjs::JS_SetOpaque(val, ptr.cast()) };
         Ok(Self(
             unsafe { Object::from_js_value(ctx, val) },
@@ -173,6 +302,14 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
         &self.get_class_cell().data
     }
 
+    /// Returns a new handle to the value of a class shared with Rust.
+    pub fn arc(&self) -> Arc<C>
+    where
+        C: JsClass<'js, Mutable = Shared>,
+    {
+        self.get_cell().arc()
+    }
+
     /// Borrow the Rust class type.
     ///
     /// JavaScript classes behave similar to [`Rc`](std::rc::Rc) in Rust, you can essentially think
@@ -203,7 +340,9 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
     /// JavaScript classes behave similar to [`Rc`](std::rc::Rc) in Rust, you can essentially think
     /// of a class object as a `Rc<RefCell<C>>` and with similar borrowing functionality.
     ///
-    /// This returns an error when the class is already borrowed mutably.
+    /// This returns an [`Error::ClassBorrow`] with `BorrowError::AlreadyBorrowedMut` when the
+    /// class is already borrowed mutably, for example when a method taking `&mut self` calls back
+    /// into JavaScript which then calls a method of the same instance.
     #[inline]
     pub fn try_borrow<'a>(&'a self) -> Result<Borrow<'a, 'js, C>> {
         self.get_cell().try_borrow().map_err(Error::ClassBorrow)
@@ -214,8 +353,9 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
     /// JavaScript classes behave similar to [`Rc`](std::rc::Rc) in Rust, you can essentially think
     /// of a class object as a `Rc<RefCell<C>>` and with similar borrowing functionality.
     ///
-    /// This returns an error when the class is already borrowed mutably, immutably or the class
-    /// can't be borrowed mutably.
+    /// This returns an [`Error::ClassBorrow`] with the reason as a [`BorrowError`](crate::BorrowError):
+    /// `AlreadyBorrowedMut` when the class is already borrowed mutably, `AlreadyBorrowed` when it
+    /// is borrowed immutably and `NotWritable` when the class can't be borrowed mutably at all.
     #[inline]
     pub fn try_borrow_mut<'a>(&'a self) -> Result<BorrowMut<'a, 'js, C>> {
         self.get_cell().try_borrow_mut().map_err(Error::ClassBorrow)
@@ -275,6 +415,16 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
     }
 }
 
+impl<'js> Value<'js> {
+    /// Returns if the value is an object of a certain Rust class.
+    pub fn instance_of<C: JsClass<'js>>(&self) -> bool {
+        match self.as_object() {
+            Some(object) => object.instance_of::<C>(),
+            None => false,
+        }
+    }
+}
+
 impl<'js> Object<'js> {
     /// Returns if the object is of a certain Rust class.
     pub fn instance_of<C: JsClass<'js>>(&self) -> bool {
@@ -301,7 +451,7 @@ impl<'js> Object<'js> {
         // duplicated, which is possible when compilation with multiple code-gen units.
         //
         // Doing check avoids a lookup and an dynamic function call in some cases.
-        if std::ptr::eq(v_table, VTable::get::<C>()) {
+        if core::ptr::eq(v_table, VTable::get::<C>()) {
             return true;
         }
 
@@ -309,7 +459,7 @@ impl<'js> Object<'js> {
     }
 
     /// Turn the object into the class if it is an instance of that class.
-    pub fn into_class<C: JsClass<'js>>(&self) -> std::result::Result<Class<'js, C>, &Self> {
+    pub fn into_class<C: JsClass<'js>>(&self) -> core::result::Result<Class<'js, C>, &Self> {
         if self.instance_of::<C>() {
             Ok(Class(self.clone(), PhantomData))
         } else {
@@ -344,16 +494,17 @@ impl<'js, C: JsClass<'js>> IntoJs<'js> for Class<'js, C> {
 #[cfg(test)]
 mod test {
     use std::sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
     };
 
     use crate::{
-        class::{JsClass, Readable, Trace, Tracer, Writable},
+        class::{JsClass, Readable, RuntimeHandle, Shared, Trace, Tracer, Writable},
         function::This,
         test_with,
         value::Constructor,
-        CatchResultExt, Class, Context, FromJs, Function, IntoJs, JsLifetime, Object, Runtime,
+        BorrowError, CatchResultExt, Class, Context, Error, FromJs, Function, IntoJs, JsLifetime,
+        Object, Runtime, Value,
     };
 
     /// Test circular references.
@@ -412,6 +563,8 @@ mod test {
             .unwrap();
 
             assert!(cls.instance_of::<Container>());
+            assert!(cls.as_value().instance_of::<Container>());
+            assert!(!Value::new_int(ctx.clone(), 1).instance_of::<Container>());
 
             let cls_clone = cls.clone();
             cls.borrow_mut().inner.push(cls_clone);
@@ -433,6 +586,105 @@ mod test {
         });
     }
 
+    #[test]
+    fn finalize() {
+        /// The file descriptors which were closed.
+        struct Closed(Mutex<Vec<i32>>);
+
+        unsafe impl<'js> JsLifetime<'js> for Closed {
+            type Changed<'to> = Closed;
+        }
+
+        struct File {
+            fd: i32,
+        }
+
+        impl<'js> Trace<'js> for File {
+            fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+        }
+
+        unsafe impl<'js> JsLifetime<'js> for File {
+            type Changed<'to> = File;
+        }
+
+        impl<'js> JsClass<'js> for File {
+            const NAME: &'static str = "File";
+
+            type Mutable = Readable;
+
+            fn constructor(_ctx: &crate::Ctx<'js>) -> crate::Result<Option<Constructor<'js>>> {
+                Ok(None)
+            }
+
+            fn finalize(&mut self, rt: &RuntimeHandle<'_>) {
+                if let Some(closed) = rt.userdata::<Closed>() {
+                    closed.0.lock().unwrap().push(self.fd);
+                }
+            }
+        }
+
+        let rt = Runtime::new().unwrap();
+        rt.store_userdata(Closed(Mutex::new(Vec::new()))).ok();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let file = Class::instance(ctx.clone(), File { fd: 3 }).unwrap();
+            ctx.globals().set("file", file).unwrap();
+            Class::instance(ctx.clone(), File { fd: 4 }).unwrap();
+        });
+        rt.with_userdata::<Closed, _, _>(|x| {
+            assert_eq!(*x.unwrap().0.lock().unwrap(), [4]);
+        });
+        ctx.with(|ctx| ctx.globals().remove("file").unwrap());
+        rt.run_gc();
+        rt.with_userdata::<Closed, _, _>(|x| {
+            assert_eq!(*x.unwrap().0.lock().unwrap(), [4, 3]);
+        });
+    }
+
+    #[test]
+    fn shared() {
+        struct Counter(AtomicUsize);
+
+        impl<'js> Trace<'js> for Counter {
+            fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+        }
+
+        unsafe impl<'js> JsLifetime<'js> for Counter {
+            type Changed<'to> = Counter;
+        }
+
+        impl<'js> JsClass<'js> for Counter {
+            const NAME: &'static str = "Counter";
+
+            type Mutable = Shared;
+
+            fn constructor(_ctx: &crate::Ctx<'js>) -> crate::Result<Option<Constructor<'js>>> {
+                Ok(None)
+            }
+
+            fn finalize(&mut self, _rt: &RuntimeHandle<'_>) {
+                // Only called once the value is no longer shared with Rust.
+                *self.0.get_mut() += 100;
+            }
+        }
+
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        test_with(|ctx| {
+            let instance = Class::instance_arc(ctx.clone(), counter.clone()).unwrap();
+            assert!(Arc::ptr_eq(&instance.arc(), &counter));
+            instance.borrow().0.fetch_add(1, Ordering::Relaxed);
+            assert!(matches!(
+                instance.try_borrow_mut(),
+                Err(Error::ClassBorrow(BorrowError::NotWritable))
+            ));
+            ctx.globals().set("counter", instance).unwrap();
+            let instance: Class<Counter> = ctx.globals().get("counter").unwrap();
+            assert_eq!(instance.arc().0.load(Ordering::Relaxed), 1);
+        });
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
     #[derive(Clone, Copy)]
     pub struct Vec3 {
         x: f32,
@@ -673,4 +925,134 @@ mod test {
                 .unwrap();
         })
     }
+
+    #[test]
+    fn borrow_conflict_is_catchable() {
+        pub struct Counter(u32);
+
+        impl<'js> Trace<'js> for Counter {
+            fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+        }
+
+        unsafe impl<'js> JsLifetime<'js> for Counter {
+            type Changed<'to> = Counter;
+        }
+
+        impl<'js> JsClass<'js> for Counter {
+            const NAME: &'static str = "Counter";
+
+            type Mutable = Writable;
+
+            fn prototype(ctx: &crate::Ctx<'js>) -> crate::Result<Option<crate::Object<'js>>> {
+                Ok(Some(Object::new(ctx.clone())?))
+            }
+
+            fn constructor(
+                _ctx: &crate::Ctx<'js>,
+            ) -> crate::Result<Option<crate::value::Constructor<'js>>> {
+                Ok(None)
+            }
+        }
+
+        test_with(|ctx| {
+            // Calls back into JavaScript while the counter is borrowed mutably.
+            let increment = Function::new(
+                ctx.clone(),
+                |this: This<Class<Counter>>, callback: Function| {
+                    let mut counter = this.0.try_borrow_mut()?;
+                    counter.0 += 1;
+                    callback.call::<_, ()>(())
+                },
+            )
+            .unwrap();
+            let counter = Class::instance(ctx.clone(), Counter(0)).unwrap();
+            counter
+                .get_prototype()
+                .unwrap()
+                .set("increment", increment)
+                .unwrap();
+            ctx.globals().set("counter", counter.clone()).unwrap();
+
+            let res: String = ctx
+                .eval(
+                    r#"
+                    let res;
+                    counter.increment(() => {
+                        try { counter.increment(() => {}); } catch (e) { res = `${e.name}: ${e.message}`; }
+                    });
+                    res
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(
+                res,
+                "TypeError: Error borrowing class: can't borrow a value as it is already borrowed mutably"
+            );
+            assert_eq!(counter.borrow().0, 1);
+
+            let borrow = counter.borrow();
+            assert!(matches!(
+                counter.try_borrow_mut(),
+                Err(Error::ClassBorrow(BorrowError::AlreadyBorrowed))
+            ));
+            drop(borrow);
+            let borrow = counter.borrow_mut();
+            assert!(matches!(
+                counter.try_borrow(),
+                Err(Error::ClassBorrow(BorrowError::AlreadyBorrowedMut))
+            ));
+            drop(borrow);
+        })
+    }
+
+    #[test]
+    fn register_class() {
+        macro_rules! point {
+            ($name:ident) => {
+                struct $name;
+
+                impl<'js> Trace<'js> for $name {
+                    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+                }
+
+                unsafe impl<'js> JsLifetime<'js> for $name {
+                    type Changed<'to> = $name;
+                }
+
+                impl<'js> JsClass<'js> for $name {
+                    const NAME: &'static str = "Point";
+
+                    type Mutable = Readable;
+
+                    fn constructor(
+                        _ctx: &crate::Ctx<'js>,
+                    ) -> crate::Result<Option<Constructor<'js>>> {
+                        Ok(None)
+                    }
+                }
+            };
+        }
+        point!(Point);
+        point!(OtherPoint);
+
+        let rt = Runtime::new().unwrap();
+        assert_eq!(rt.class_id("Point"), None);
+        let id = rt.register_class::<Point>().unwrap();
+        assert_eq!(rt.register_class::<Point>().unwrap(), id);
+        assert_eq!(rt.class_id("Point"), Some(id));
+        assert!(matches!(
+            rt.register_class::<OtherPoint>(),
+            Err(Error::ClassConflict { name: "Point" })
+        ));
+
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            Class::instance(ctx.clone(), Point).unwrap();
+            assert!(matches!(
+                Class::instance(ctx.clone(), OtherPoint),
+                Err(Error::ClassConflict { name: "Point" })
+            ));
+        });
+    }
 }
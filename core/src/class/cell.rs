@@ -1,6 +1,7 @@
 use super::{Class, JsClass};
 use crate::{result::BorrowError, Ctx, Error, FromJs, IntoJs, Value};
-use std::{
+use alloc::sync::Arc;
+use core::{
     cell::{Cell, UnsafeCell},
     marker::PhantomData,
     mem::ManuallyDrop,
@@ -9,6 +10,12 @@ use std::{
 
 /// A trait to allow classes to choose there borrowing implementation.
 ///
+/// The cells are only accessed while the runtime is locked, so the non-atomic cells are also
+/// sound with the `parallel` feature and don't need a `Mutex` or `RwLock`. Borrowing a cell which
+/// is already borrowed in a conflicting way, like when JavaScript calls back into a method of an
+/// instance which is borrowed mutably, returns [`Error::ClassBorrow`] which is thrown as a
+/// catchable `TypeError`.
+///
 /// # Safety
 /// This trait is not meant to be implemented outside the rquickjs library.
 pub unsafe trait Mutability {
@@ -64,6 +71,17 @@ pub unsafe trait Mutability {
     /// # Safety
     /// [`borrow_mut`] must first be called on the cell and return without error before calling deref.
     unsafe fn deref_mut<'a, T>(cell: &'a Self::Cell<T>) -> &'a mut T;
+
+    #[doc(hidden)]
+    /// Returns a mutable reference to the value of a uniquely owned cell, `None` if the value is
+    /// still shared with Rust.
+    ///
+    /// Defaults to `None` so existing implementations keep compiling, their values are then
+    /// dropped without calling [`JsClass::finalize`](crate::class::JsClass::finalize).
+    fn get_mut<T>(cell: &mut Self::Cell<T>) -> Option<&mut T> {
+        let _ = cell;
+        None
+    }
 }
 
 /// A marker type used for marking the mutability of a class.
@@ -96,6 +114,48 @@ unsafe impl Mutability for Readable {
     unsafe fn deref_mut<'a, T>(_cell: &'a Self::Cell<T>) -> &'a mut T {
         unreachable!()
     }
+
+    fn get_mut<T>(cell: &mut Self::Cell<T>) -> Option<&mut T> {
+        Some(cell)
+    }
+}
+
+/// A marker type used for marking the mutability of a class.
+/// When a class has `Shared` as it Mutable type the value is stored in an [`Arc`], which can be
+/// shared with Rust code, see [`Class::instance_arc`]. Like with [`Readable`] it can only be
+/// borrowed immutable.
+pub enum Shared {}
+
+unsafe impl Mutability for Shared {
+    type Cell<T> = Arc<T>;
+
+    fn new_cell<T>(t: T) -> Self::Cell<T> {
+        Arc::new(t)
+    }
+
+    unsafe fn borrow<'a, T>(_cell: &'a Self::Cell<T>) -> Result<(), BorrowError> {
+        Ok(())
+    }
+
+    unsafe fn unborrow<'a, T>(_cell: &'a Self::Cell<T>) {}
+
+    unsafe fn borrow_mut<'a, T>(_cell: &'a Self::Cell<T>) -> Result<(), BorrowError> {
+        Err(BorrowError::NotWritable)
+    }
+
+    unsafe fn unborrow_mut<'a, T>(_cell: &'a Self::Cell<T>) {}
+
+    unsafe fn deref<'a, T>(cell: &'a Self::Cell<T>) -> &'a T {
+        cell
+    }
+
+    unsafe fn deref_mut<'a, T>(_cell: &'a Self::Cell<T>) -> &'a mut T {
+        unreachable!()
+    }
+
+    fn get_mut<T>(cell: &mut Self::Cell<T>) -> Option<&mut T> {
+        Arc::get_mut(cell)
+    }
 }
 
 /// A marker type used for marking the mutability of a class.
@@ -166,7 +226,7 @@ unsafe impl Mutability for Writable {
     unsafe fn borrow<'a, T>(cell: &'a Self::Cell<T>) -> Result<(), BorrowError> {
         let count = cell.count.get();
         if count == usize::MAX {
-            return Err(BorrowError::AlreadyBorrowed);
+            return Err(BorrowError::AlreadyBorrowedMut);
         }
         cell.count.set(count + 1);
         Ok(())
@@ -178,6 +238,9 @@ unsafe impl Mutability for Writable {
 
     unsafe fn borrow_mut<'a, T>(cell: &'a Self::Cell<T>) -> Result<(), BorrowError> {
         let count = cell.count.get();
+        if count == usize::MAX {
+            return Err(BorrowError::AlreadyBorrowedMut);
+        }
         if count != 0 {
             return Err(BorrowError::AlreadyBorrowed);
         }
@@ -196,6 +259,10 @@ unsafe impl Mutability for Writable {
     unsafe fn deref_mut<'a, T>(cell: &'a Self::Cell<T>) -> &'a mut T {
         &mut *cell.value.get()
     }
+
+    fn get_mut<T>(cell: &mut Self::Cell<T>) -> Option<&mut T> {
+        Some(cell.value.get_mut())
+    }
 }
 
 /// A cell type for Rust classes passed to JavaScript.
@@ -213,6 +280,22 @@ impl<'js, T: JsClass<'js>> JsCell<'js, T> {
         }
     }
 
+    /// Create a `JsCell` from a value shared with Rust.
+    pub fn from_arc(arc: Arc<T>) -> Self
+    where
+        T: JsClass<'js, Mutable = Shared>,
+    {
+        JsCell { cell: arc }
+    }
+
+    /// Returns a new handle to the shared value.
+    pub fn arc(&self) -> Arc<T>
+    where
+        T: JsClass<'js, Mutable = Shared>,
+    {
+        self.cell.clone()
+    }
+
     /// Borrow the contained value immutable.
     ///
     /// # Panic
@@ -316,7 +399,7 @@ impl<'js, T: JsClass<'js> + 'js> OwnedBorrow<'js, T> {
     pub fn into_inner(mut self) -> Class<'js, T> {
         unsafe { <T::Mutable as Mutability>::unborrow(&self.0.get_cell().cell) };
         let res = unsafe { ManuallyDrop::take(&mut self.0) };
-        std::mem::forget(self);
+        core::mem::forget(self);
         res
     }
 }
@@ -374,7 +457,7 @@ impl<'js, T: JsClass<'js> + 'js> OwnedBorrowMut<'js, T> {
     pub fn into_inner(mut self) -> Class<'js, T> {
         unsafe { <T::Mutable as Mutability>::unborrow_mut(&self.0.get_cell().cell) };
         let res = unsafe { ManuallyDrop::take(&mut self.0) };
-        std::mem::forget(self);
+        core::mem::forget(self);
         res
     }
 }
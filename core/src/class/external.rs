@@ -0,0 +1,214 @@
+//! Opaque Rust values stored in JavaScript values.
+
+use super::{JsClass, Readable, Trace, Tracer};
+use crate::{
+    value::Constructor, Class, Ctx, Error, FromJs, IntoJs, JsLifetime, Object, Result, Value,
+};
+use core::{any::type_name, fmt};
+
+/// The class used to store the data of an [`External`].
+#[doc(hidden)]
+pub struct ExternalData<T>(T);
+
+impl<'js, T> Trace<'js> for ExternalData<T> {
+    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+}
+
+unsafe impl<'js, T: 'static> JsLifetime<'js> for ExternalData<T> {
+    type Changed<'to> = ExternalData<T>;
+}
+
+impl<'js, T: 'static> JsClass<'js> for ExternalData<T> {
+    const NAME: &'static str = "External";
+
+    type Mutable = Readable;
+
+    fn prototype(_ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        Ok(None)
+    }
+
+    fn constructor(_ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Ok(None)
+    }
+}
+
+/// A JavaScript value holding an arbitrary Rust value.
+///
+/// Unlike a [`Class`] an external has no prototype, methods or constructor, it is an opaque
+/// object which JavaScript can only pass around. This makes it a cheap way to thread host handles
+/// through JavaScript code and back into Rust callbacks. Converting a value back into an external
+/// checks that it holds a value of type `T`.
+///
+/// The contained value is dropped when the object is garbage collected. Values of type `T` can't
+/// be traced so they should not contain JavaScript values, use [`Persistent`](crate::Persistent)
+/// for those instead.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, External};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// struct Handle(u32);
+///
+/// let handle = External::new(ctx.clone(), Handle(42)).unwrap();
+/// ctx.globals().set("handle", handle).unwrap();
+///
+/// let id = Function::new(ctx.clone(), |handle: External<Handle>| handle.get().0).unwrap();
+/// ctx.globals().set("id", id).unwrap();
+///
+/// assert_eq!(ctx.eval::<u32, _>("id(handle)").unwrap(), 42);
+/// assert!(ctx.eval::<u32, _>("id({})").is_err());
+/// # });
+/// ```
+#[repr(transparent)]
+pub struct External<'js, T: 'static>(Class<'js, ExternalData<T>>);
+
+impl<'js, T: 'static> Clone for External<'js, T> {
+    fn clone(&self) -> Self {
+        External(self.0.clone())
+    }
+}
+
+impl<'js, T: 'static> PartialEq for External<'js, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<'js, T: 'static> Eq for External<'js, T> {}
+
+impl<'js, T: fmt::Debug + 'static> fmt::Debug for External<'js, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("External").field(self.get()).finish()
+    }
+}
+
+unsafe impl<'js, T: 'static> JsLifetime<'js> for External<'js, T> {
+    type Changed<'to> = External<'to, T>;
+}
+
+impl<'js, T: 'static> Trace<'js> for External<'js, T> {
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        self.0.trace(tracer)
+    }
+}
+
+impl<'js, T: 'static> External<'js, T> {
+    /// Move a Rust value into a new external.
+    pub fn new(ctx: Ctx<'js>, value: T) -> Result<Self> {
+        Class::instance(ctx, ExternalData(value)).map(External)
+    }
+
+    /// Returns a reference to the contained Rust value.
+    #[inline]
+    pub fn get(&self) -> &T {
+        &self.0.get_cell().cell.0
+    }
+
+    /// Returns if the object is an external holding a value of type `T`.
+    #[inline]
+    pub fn is(object: &Object<'js>) -> bool {
+        object.instance_of::<ExternalData<T>>()
+    }
+
+    /// Converts a generic object into an external if it holds a value of type `T`.
+    #[inline]
+    pub fn from_object(object: &Object<'js>) -> Option<Self> {
+        Class::from_object(object).map(External)
+    }
+
+    /// Convert from value.
+    pub fn from_value(value: &Value<'js>) -> Result<Self> {
+        if let Some(external) = value.as_object().and_then(Self::from_object) {
+            return Ok(external);
+        }
+        Err(Error::FromJs {
+            from: value.type_name(),
+            to: type_name::<T>(),
+            message: Some("value is not an external of this type".into()),
+        })
+    }
+
+    /// Turns the external back into a generic object.
+    #[inline]
+    pub fn into_inner(self) -> Object<'js> {
+        self.0.into_inner()
+    }
+
+    /// Returns a reference to the external as a generic object.
+    #[inline]
+    pub fn as_inner(&self) -> &Object<'js> {
+        self.0.as_inner()
+    }
+
+    /// Turn the external into a value.
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+}
+
+impl<'js, T: 'static> FromJs<'js> for External<'js, T> {
+    fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        Self::from_value(&value)
+    }
+}
+
+impl<'js, T: 'static> IntoJs<'js> for External<'js, T> {
+    fn into_js(self, _ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test_with, External, Object, Value};
+    use std::sync::Arc;
+
+    #[test]
+    fn round_trip() {
+        test_with(|ctx| {
+            let external = External::new(ctx.clone(), String::from("host")).unwrap();
+            ctx.globals().set("ext", external.clone()).unwrap();
+
+            let ty: String = ctx.eval("typeof ext").unwrap();
+            assert_eq!(ty, "object");
+            let proto: Value = ctx.eval("Object.getPrototypeOf(ext)").unwrap();
+            assert!(proto.is_null());
+
+            let back: External<String> = ctx.globals().get("ext").unwrap();
+            assert_eq!(back, external);
+            assert_eq!(back.get(), "host");
+        })
+    }
+
+    #[test]
+    fn type_checked() {
+        test_with(|ctx| {
+            let external = External::new(ctx.clone(), 1u32).unwrap();
+            assert!(External::<u32>::is(external.as_inner()));
+            assert!(!External::<i32>::is(external.as_inner()));
+
+            let value = external.into_value();
+            assert!(value.get::<External<i32>>().is_err());
+            assert!(value.get::<External<u32>>().is_ok());
+
+            let object = Object::new(ctx.clone()).unwrap();
+            assert!(External::<u32>::from_object(&object).is_none());
+            assert!(object.into_value().get::<External<u32>>().is_err());
+        })
+    }
+
+    #[test]
+    fn dropped_on_gc() {
+        let data = Arc::new(());
+        test_with(|ctx| {
+            let external = External::new(ctx.clone(), data.clone()).unwrap();
+            assert_eq!(Arc::strong_count(&data), 2);
+            ctx.globals().set("ext", external).unwrap();
+            ctx.eval::<(), _>("ext = undefined").unwrap();
+            ctx.run_gc();
+        });
+        assert_eq!(Arc::strong_count(&data), 1);
+    }
+}
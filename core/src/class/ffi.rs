@@ -1,13 +1,14 @@
-use super::{JsClass, Tracer};
+use super::{JsClass, Mutability, RuntimeHandle, Tracer};
 use crate::{class::JsCell, function::Params, qjs, runtime::opaque::Opaque, Value};
-use std::{any::TypeId, panic::AssertUnwindSafe, ptr::NonNull};
+use alloc::boxed::Box;
+use core::{any::TypeId, panic::AssertUnwindSafe, ptr::NonNull};
 
 /// FFI finalizer, destroying the object once it is delete by the Gc.
 pub(crate) unsafe extern "C" fn class_finalizer(rt: *mut qjs::JSRuntime, val: qjs::JSValue) {
-    let class_id = Opaque::from_runtime_ptr(rt).get_class_id();
-    let ptr = qjs::JS_GetOpaque(val, class_id);
+    let opaque = Opaque::from_runtime_ptr(rt);
+    let ptr = qjs::JS_GetOpaque(val, opaque.get_class_id());
     let ptr = NonNull::new(ptr).unwrap().cast::<ClassCell<()>>();
-    (ptr.as_ref().v_table.finalizer)(ptr);
+    opaque.catch_silent_panic(|| (ptr.as_ref().v_table.finalizer)(ptr, rt));
 }
 
 /// FFI tracing function for non callable classes.
@@ -16,19 +17,20 @@ pub(crate) unsafe extern "C" fn class_trace(
     val: qjs::JSValue,
     mark_func: qjs::JS_MarkFunc,
 ) {
-    let class_id = Opaque::from_runtime_ptr(rt).get_class_id();
-    let ptr = qjs::JS_GetOpaque(val, class_id);
+    let opaque = Opaque::from_runtime_ptr(rt);
+    let ptr = qjs::JS_GetOpaque(val, opaque.get_class_id());
     let ptr = NonNull::new(ptr).unwrap().cast::<ClassCell<()>>();
     let tracer = Tracer::from_ffi(rt, mark_func);
-    (ptr.as_ref().v_table.trace)(ptr, tracer)
+    // A class which fails to trace its values only keeps them alive for longer.
+    opaque.catch_silent_panic(|| (ptr.as_ref().v_table.trace)(ptr, tracer))
 }
 
 /// FFI finalizer, destroying the object once it is delete by the Gc.
 pub(crate) unsafe extern "C" fn callable_finalizer(rt: *mut qjs::JSRuntime, val: qjs::JSValue) {
-    let class_id = Opaque::from_runtime_ptr(rt).get_callable_id();
-    let ptr = qjs::JS_GetOpaque(val, class_id);
+    let opaque = Opaque::from_runtime_ptr(rt);
+    let ptr = qjs::JS_GetOpaque(val, opaque.get_callable_id());
     let ptr = NonNull::new(ptr).unwrap().cast::<ClassCell<()>>();
-    (ptr.as_ref().v_table.finalizer)(ptr)
+    opaque.catch_silent_panic(|| (ptr.as_ref().v_table.finalizer)(ptr, rt));
 }
 
 /// FFI tracing function for classes of type callable.
@@ -37,11 +39,12 @@ pub(crate) unsafe extern "C" fn callable_trace(
     val: qjs::JSValue,
     mark_func: qjs::JS_MarkFunc,
 ) {
-    let class_id = Opaque::from_runtime_ptr(rt).get_callable_id();
-    let ptr = qjs::JS_GetOpaque(val, class_id);
+    let opaque = Opaque::from_runtime_ptr(rt);
+    let ptr = qjs::JS_GetOpaque(val, opaque.get_callable_id());
     let ptr = NonNull::new(ptr).unwrap().cast::<ClassCell<()>>();
     let tracer = Tracer::from_ffi(rt, mark_func);
-    (ptr.as_ref().v_table.trace)(ptr, tracer)
+    // A class which fails to trace its values only keeps them alive for longer.
+    opaque.catch_silent_panic(|| (ptr.as_ref().v_table.trace)(ptr, tracer))
 }
 
 /// FFI calling function.
@@ -60,7 +63,7 @@ pub(crate) unsafe extern "C" fn call(
     (ptr.as_ref().v_table.call)(ptr, ctx, function, this, argc, argv, flags)
 }
 
-pub(crate) type FinalizerFunc = unsafe fn(this: NonNull<ClassCell<()>>);
+pub(crate) type FinalizerFunc = unsafe fn(this: NonNull<ClassCell<()>>, rt: *mut qjs::JSRuntime);
 pub(crate) type TraceFunc =
     for<'a> unsafe fn(this: NonNull<ClassCell<()>>, tracer: Tracer<'a, 'static>);
 pub(crate) type CallFunc = for<'a> unsafe fn(
@@ -83,9 +86,17 @@ pub(crate) struct VTable {
 }
 
 impl VTable {
-    unsafe fn finalizer_impl<'js, C: JsClass<'js>>(this: NonNull<ClassCell<()>>) {
+    unsafe fn finalizer_impl<'js, C: JsClass<'js>>(
+        this: NonNull<ClassCell<()>>,
+        rt: *mut qjs::JSRuntime,
+    ) {
         let this = this.cast::<ClassCell<JsCell<C>>>();
-        let _ = Box::from_raw(this.as_ptr());
+        let mut this = Box::from_raw(this.as_ptr());
+        // The box still drops the value if finalize panics.
+        // A value which is still shared with Rust outlives the object and isn't finalized.
+        if let Some(value) = C::Mutable::get_mut(&mut this.data.cell) {
+            value.finalize(&RuntimeHandle::from_ptr(rt));
+        }
     }
 
     unsafe fn trace_impl<'js, C: JsClass<'js>>(
@@ -110,6 +121,10 @@ impl VTable {
         let this_ptr = this_ptr.cast::<ClassCell<JsCell<C>>>();
         let params = Params::from_ffi_class(ctx, function, this, argc, argv, flags);
         let ctx = params.ctx().clone();
+        trace_span!(
+            "call_rust",
+            function = %crate::Function::from_js_value_const(ctx.clone(), function).trace_name()
+        );
 
         ctx.handle_panic(AssertUnwindSafe(|| {
             C::call(&this_ptr.as_ref().data, params)
@@ -151,9 +166,13 @@ pub(crate) struct ClassCell<T> {
 
 impl<'js, T: JsClass<'js>> ClassCell<JsCell<'js, T>> {
     pub(crate) fn new(class: T) -> Self {
+        Self::from_cell(JsCell::new(class))
+    }
+
+    pub(crate) fn from_cell(data: JsCell<'js, T>) -> Self {
         ClassCell {
             v_table: VTable::get::<T>(),
-            data: JsCell::new(class),
+            data,
         }
     }
 }
@@ -1,6 +1,7 @@
 use super::JsClass;
 use crate::{markers::Invariant, qjs, Class, Ctx, Module, Value};
-use std::marker::PhantomData;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::marker::PhantomData;
 
 #[cfg(feature = "either")]
 use either::{Either, Left, Right};
@@ -227,8 +228,8 @@ trace_impls! {
 trace_impls! {
     ref:
     Box,
-    std::rc::Rc,
-    std::sync::Arc,
+    alloc::rc::Rc,
+    alloc::sync::Arc,
 }
 
 trace_impls! {
@@ -255,10 +256,12 @@ trace_impls! {
 trace_impls! {
     list:
     Vec,
-    std::collections::VecDeque,
-    std::collections::LinkedList,
+    alloc::collections::VecDeque,
+    alloc::collections::LinkedList,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "classes", feature = "std"))))]
     std::collections::HashSet {S},
-    std::collections::BTreeSet,
+    alloc::collections::BTreeSet,
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "classes", feature = "indexmap"))))]
     indexmap::IndexSet {S},
@@ -266,8 +269,10 @@ trace_impls! {
 
 trace_impls! {
     map:
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "classes", feature = "std"))))]
     std::collections::HashMap {S},
-    std::collections::BTreeMap,
+    alloc::collections::BTreeMap,
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "classes", feature = "indexmap"))))]
     indexmap::IndexMap {S},
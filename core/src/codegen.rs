@@ -0,0 +1,1430 @@
+//! Generating typed Rust bindings from TypeScript declaration files.
+//!
+//! [`Bindings`] reads the declarations of a JS library, as found in its `.d.ts` file, and writes
+//! Rust source wrapping the values of the library in structs with typed methods. Calls go through
+//! [`FromJs`](crate::FromJs) and [`IntoJs`](crate::IntoJs), so Rust callers get checked
+//! conversions instead of handling [`Value`](crate::Value)s.
+//!
+//! The generated source contains:
+//! - A struct for every interface and class, wrapping its [`Object`](crate::Object), with getters
+//!   and setters of the properties and a method for every method.
+//! - A struct named by [`Bindings::with_name`] wrapping the object holding the top level
+//!   declarations, the global object or the namespace of the module. It has a method for every
+//!   function, a getter for every constant, a `new_` method for the constructor of every class
+//!   and methods for the static methods of the classes, prefixed with the name of the class.
+//!
+//! The types are mapped to Rust types as follows:
+//! - `number` to `f64`, `string` to `String`, `boolean` to `bool` and `void` to `()`.
+//! - `T[]` and `Array<T>` to `Vec<T>`, and `Record<string, T>` to `HashMap<String, T>`.
+//! - `T | undefined`, `T | null` and optional parameters and properties to `Option<T>`.
+//! - Unions of literals to the type of the literals.
+//! - Declared interfaces, classes and type aliases to their structs or types.
+//! - `object` and object literal types to `Object`, functions to `Function`, `bigint` to `BigInt`
+//!   and `Promise<T>` to `Promise`.
+//! - Everything else, like generics, tuples and other unions, to `Value`.
+//!
+//! Only the first signature of overloaded functions and methods is used. Namespaces and enums are
+//! skipped, while the declarations of `declare module "name"` and `declare global` blocks are
+//! read as top level declarations.
+//!
+//! ```
+//! # use rquickjs::codegen::Bindings;
+//! let source = Bindings::new()
+//!     .with_name("Geometry")
+//!     .generate(
+//!         r#"
+//!         /** A point in the plane. */
+//!         export interface Point {
+//!             readonly x: number;
+//!             y: number;
+//!             label?: string;
+//!             translate(dx: number, dy?: number): Point;
+//!         }
+//!         export declare function distance(a: Point, b: Point): number;
+//!         export declare const ORIGIN: Point;
+//!         "#,
+//!     )
+//!     .unwrap();
+//! assert!(source.contains("pub struct Point<'js>(pub Object<'js>);"));
+//! assert!(source.contains("pub fn label(&self) -> Result<Option<String>>"));
+//! assert!(source.contains("pub fn translate(&self, dx: f64, dy: Option<f64>) -> Result<Point<'js>>"));
+//! assert!(source.contains("pub fn distance(&self, a: Point<'js>, b: Point<'js>) -> Result<f64>"));
+//! assert!(source.contains("pub fn origin(&self) -> Result<Point<'js>>"));
+//! ```
+//!
+//! The generated source is meant to be written by a build script, or by the `rqjsc` binary, and
+//! included in its own module, as it imports the types of rquickjs it uses.
+
+use core::fmt::{self, Write};
+use std::collections::{HashMap, HashSet};
+
+use crate::StdString;
+
+/// An error reading TypeScript declarations, with the line it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenError {
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// What was wrong.
+    pub message: StdString,
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on line {}", self.message, self.line)
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+/// Generates Rust bindings from TypeScript declarations.
+///
+/// See the [module documentation](self) for the generated source.
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    name: StdString,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings::new()
+    }
+}
+
+impl Bindings {
+    /// Create a generator naming the struct of the top level declarations `Bindings`.
+    pub fn new() -> Self {
+        Bindings {
+            name: "Bindings".into(),
+        }
+    }
+
+    /// Set the name of the struct of the top level declarations.
+    pub fn set_name<N: Into<StdString>>(&mut self, name: N) -> &mut Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set the name of the struct of the top level declarations.
+    #[must_use]
+    pub fn with_name<N: Into<StdString>>(mut self, name: N) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    /// Generate the Rust source of the bindings of the declarations.
+    pub fn generate(&self, source: &str) -> Result<StdString, CodegenError> {
+        let tokens = lex(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            decls: Decls::default(),
+        };
+        parser.items(false)?;
+        let decls = parser.decls;
+        if decls.shapes.iter().any(|x| x.name == self.name) {
+            return Err(CodegenError {
+                line: 1,
+                message: format!("`{}` is also the name of a declaration", self.name),
+            });
+        }
+        Ok(Writer {
+            decls: &decls,
+            res: StdString::new(),
+        }
+        .write(&ident(&self.name)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(StdString),
+    /// A string literal, with its escapes left as they are.
+    Str(StdString),
+    Num,
+    Punct(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    line: usize,
+    /// The doc comment right before the token.
+    doc: Option<StdString>,
+}
+
+const PUNCTS: &[&str] = &[
+    "...", "=>", "{", "}", "(", ")", "[", "]", "<", ">", ":", ";", ",", "?", "|", "&", "=", ".",
+    "!", "*", "+", "-", "@", "#", "~", "/", "%", "^",
+];
+
+fn lex(source: &str) -> Result<Vec<Token>, CodegenError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut doc = None;
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").ok_or(CodegenError {
+                line,
+                message: "Unterminated comment".into(),
+            })?;
+            let text = &comment[..end];
+            line += text.matches('\n').count();
+            if let Some(text) = text.strip_prefix('*') {
+                let lines: Vec<_> = text
+                    .lines()
+                    .map(|x| x.trim().trim_start_matches('*').trim())
+                    .collect();
+                let start = lines.iter().position(|x| !x.is_empty()).unwrap_or(0);
+                let end = lines
+                    .iter()
+                    .rposition(|x| !x.is_empty())
+                    .map_or(0, |x| x + 1);
+                doc = Some(lines[start..end.max(start)].join("\n"));
+            }
+            rest = &comment[end + 2..];
+            continue;
+        }
+        let start_line = line;
+        let (tok, len) = if c == '"' || c == '\'' || c == '`' {
+            let mut escaped = false;
+            let end = rest[1..]
+                .char_indices()
+                .find(|&(_, x)| {
+                    let end = !escaped && x == c;
+                    escaped = !escaped && x == '\\';
+                    end
+                })
+                .ok_or(CodegenError {
+                    line,
+                    message: "Unterminated string".into(),
+                })?
+                .0;
+            line += rest[1..end + 1].matches('\n').count();
+            (Tok::Str(rest[1..end + 1].into()), end + 2)
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|x: char| !(x.is_ascii_alphanumeric() || x == '.' || x == '_'))
+                .unwrap_or(rest.len());
+            (Tok::Num, len)
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let len = rest
+                .find(|x: char| !(x.is_alphanumeric() || x == '_' || x == '$'))
+                .unwrap_or(rest.len());
+            (Tok::Ident(rest[..len].into()), len)
+        } else if let Some(punct) = PUNCTS.iter().find(|x| rest.starts_with(**x)) {
+            (Tok::Punct(punct), punct.len())
+        } else {
+            return Err(CodegenError {
+                line,
+                message: format!("Unexpected character `{c}`"),
+            });
+        };
+        tokens.push(Token {
+            tok,
+            line: start_line,
+            doc: doc.take(),
+        });
+        rest = &rest[len..];
+    }
+    tokens.push(Token {
+        tok: Tok::Eof,
+        line,
+        doc: None,
+    });
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Type {
+    Number,
+    String,
+    Bool,
+    Void,
+    Null,
+    BigInt,
+    Object,
+    Function,
+    Promise,
+    Any,
+    Array(Box<Type>),
+    Record(Box<Type>),
+    Option(Box<Type>),
+    Named(StdString),
+}
+
+#[derive(Debug, Clone)]
+struct Param {
+    name: StdString,
+    ty: Type,
+    optional: bool,
+    rest: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Func {
+    name: StdString,
+    doc: Option<StdString>,
+    params: Vec<Param>,
+    ret: Type,
+}
+
+#[derive(Debug, Clone)]
+struct Prop {
+    name: StdString,
+    doc: Option<StdString>,
+    ty: Type,
+    readonly: bool,
+}
+
+/// An interface or a class.
+#[derive(Debug, Clone, Default)]
+struct Shape {
+    name: StdString,
+    doc: Option<StdString>,
+    extends: Vec<StdString>,
+    props: Vec<Prop>,
+    methods: Vec<Func>,
+    /// The constructor of a declared class.
+    constructor: Option<Func>,
+    statics: Vec<Func>,
+}
+
+#[derive(Debug, Default)]
+struct Decls {
+    functions: Vec<Func>,
+    consts: Vec<Prop>,
+    shapes: Vec<Shape>,
+    aliases: HashMap<StdString, Type>,
+}
+
+impl Decls {
+    fn shape(&mut self, name: &str) -> &mut Shape {
+        // Declarations of interfaces and classes with the same name are merged.
+        let idx = match self.shapes.iter().position(|x| x.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.shapes.push(Shape {
+                    name: name.into(),
+                    ..Default::default()
+                });
+                self.shapes.len() - 1
+            }
+        };
+        &mut self.shapes[idx]
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    decls: Decls,
+}
+
+const MODIFIERS: &[&str] = &[
+    "public",
+    "private",
+    "protected",
+    "readonly",
+    "static",
+    "abstract",
+    "declare",
+    "override",
+    "async",
+];
+
+impl Parser {
+    fn token(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn peek(&self, offset: usize) -> &Tok {
+        let idx = (self.pos + offset).min(self.tokens.len() - 1);
+        &self.tokens[idx].tok
+    }
+
+    fn next(&mut self) -> Tok {
+        let tok = self.token().tok.clone();
+        if tok != Tok::Eof {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn is(&self, punct: &str) -> bool {
+        matches!(self.peek(0), Tok::Punct(x) if *x == punct)
+    }
+
+    fn is_word(&self, offset: usize, word: &str) -> bool {
+        matches!(self.peek(offset), Tok::Ident(x) if x == word)
+    }
+
+    /// Whether the token is on a new line, ending a statement without a semicolon.
+    fn on_new_line(&self) -> bool {
+        self.pos > 0 && self.tokens[self.pos].line > self.tokens[self.pos - 1].line
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let res = self.is(punct);
+        if res {
+            self.pos += 1;
+        }
+        res
+    }
+
+    fn error(&self, message: &str) -> CodegenError {
+        CodegenError {
+            line: self.token().line,
+            message: message.into(),
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), CodegenError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected `{punct}`")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<StdString, CodegenError> {
+        match self.next() {
+            Tok::Ident(name) => Ok(name),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("Expected a name"))
+            }
+        }
+    }
+
+    /// Skip a bracketed group starting at the current token.
+    fn skip_group(&mut self) -> Result<(), CodegenError> {
+        let mut depth = 0usize;
+        loop {
+            match self.next() {
+                Tok::Punct("(" | "[" | "{" | "<") => depth += 1,
+                Tok::Punct(")" | "]" | "}" | ">") => depth = depth.saturating_sub(1),
+                Tok::Eof => return Err(self.error("Unexpected end of the declarations")),
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_generics(&mut self) -> Result<(), CodegenError> {
+        if self.is("<") {
+            self.skip_group()?;
+        }
+        Ok(())
+    }
+
+    /// Skip a statement or member which isn't read, stopping after its semicolon or before the
+    /// next statement.
+    fn skip_statement(&mut self) -> Result<(), CodegenError> {
+        let start = self.pos;
+        loop {
+            match self.peek(0) {
+                Tok::Eof | Tok::Punct("}") => return Ok(()),
+                Tok::Punct(";" | ",") => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Tok::Punct("(" | "[" | "{") => self.skip_group()?,
+                _ if self.pos > start
+                    && self.on_new_line()
+                    && matches!(
+                        self.tokens[self.pos - 1].tok,
+                        Tok::Punct(")" | "]" | "}") | Tok::Ident(_) | Tok::Str(_) | Tok::Num
+                    ) =>
+                {
+                    return Ok(())
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// Read the declarations until the end of the file or of the enclosing block.
+    fn items(&mut self, in_block: bool) -> Result<(), CodegenError> {
+        loop {
+            if self.peek(0) == &Tok::Eof {
+                if in_block {
+                    return Err(self.error("Expected `}`"));
+                }
+                return Ok(());
+            }
+            if in_block && self.eat("}") {
+                return Ok(());
+            }
+            if self.eat(";") {
+                continue;
+            }
+            let doc = self.token().doc.clone();
+            while ["export", "declare", "abstract", "default"]
+                .iter()
+                .any(|x| self.is_word(0, x))
+                && matches!(self.peek(1), Tok::Ident(_))
+            {
+                self.pos += 1;
+            }
+            let Tok::Ident(keyword) = self.peek(0).clone() else {
+                self.skip_statement()?;
+                continue;
+            };
+            match keyword.as_str() {
+                "function" => {
+                    self.pos += 1;
+                    let function = self.function(doc)?;
+                    // Anonymous default exports can't be called by name.
+                    if !function.name.is_empty()
+                        && !self.decls.functions.iter().any(|x| x.name == function.name)
+                    {
+                        self.decls.functions.push(function);
+                    }
+                }
+                "const" | "let" | "var" if !self.is_word(1, "enum") => {
+                    self.pos += 1;
+                    loop {
+                        let name = self.ident()?;
+                        let ty = if self.eat(":") { self.ty()? } else { Type::Any };
+                        self.decls.consts.push(Prop {
+                            name,
+                            doc: doc.clone(),
+                            ty,
+                            readonly: true,
+                        });
+                        if !self.eat(",") {
+                            break;
+                        }
+                    }
+                }
+                "interface" | "class" => {
+                    self.pos += 1;
+                    let name = self.ident()?;
+                    self.shape(name, doc, keyword == "class")?;
+                }
+                "type" if matches!(self.peek(1), Tok::Ident(_)) => {
+                    self.pos += 1;
+                    let name = self.ident()?;
+                    self.skip_generics()?;
+                    self.expect("=")?;
+                    let ty = self.ty()?;
+                    self.decls.aliases.insert(name, ty);
+                }
+                "enum" | "const" => {
+                    self.pos += if keyword == "const" { 2 } else { 1 };
+                    let name = self.ident()?;
+                    self.skip_group()?;
+                    self.decls.aliases.insert(name, Type::Any);
+                }
+                "module" | "namespace" | "global" => {
+                    self.pos += 1;
+                    let global = keyword == "global";
+                    if !global && matches!(self.peek(0), Tok::Ident(_)) {
+                        // Namespaces are skipped, with their dotted names.
+                        while !self.is("{") && self.peek(0) != &Tok::Eof {
+                            self.pos += 1;
+                        }
+                        self.skip_group()?;
+                        continue;
+                    }
+                    if !global {
+                        self.next();
+                    }
+                    if self.eat("{") {
+                        self.items(true)?;
+                    }
+                }
+                _ => self.skip_statement()?,
+            }
+            if !in_block || !self.is("}") {
+                self.eat(";");
+            }
+        }
+    }
+
+    /// Read a function declaration, starting at its name.
+    fn function(&mut self, doc: Option<StdString>) -> Result<Func, CodegenError> {
+        let name = match self.peek(0) {
+            Tok::Ident(_) => self.ident()?,
+            _ => StdString::new(),
+        };
+        self.signature(name, doc)
+    }
+
+    /// Read the type parameters, parameters and return type of a function or method.
+    fn signature(&mut self, name: StdString, doc: Option<StdString>) -> Result<Func, CodegenError> {
+        self.skip_generics()?;
+        let params = self.params()?;
+        let ret = if self.eat(":") {
+            self.return_type()?
+        } else {
+            Type::Any
+        };
+        Ok(Func {
+            name,
+            doc,
+            params,
+            ret,
+        })
+    }
+
+    fn params(&mut self) -> Result<Vec<Param>, CodegenError> {
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while !self.eat(")") {
+            while MODIFIERS.iter().any(|x| self.is_word(0, x))
+                && matches!(self.peek(1), Tok::Ident(_))
+            {
+                self.pos += 1;
+            }
+            let rest = self.eat("...");
+            let name = match self.peek(0) {
+                Tok::Ident(_) => self.ident()?,
+                Tok::Punct("{" | "[") => {
+                    self.skip_group()?;
+                    format!("arg{}", params.len())
+                }
+                _ => return Err(self.error("Expected a parameter")),
+            };
+            let optional = self.eat("?");
+            let mut ty = if self.eat(":") { self.ty()? } else { Type::Any };
+            if self.eat("=") {
+                self.skip_statement_until(&[",", ")"]);
+            }
+            if rest {
+                ty = match ty {
+                    Type::Array(ty) => *ty,
+                    _ => Type::Any,
+                };
+            }
+            if name != "this" {
+                params.push(Param {
+                    name,
+                    ty,
+                    optional,
+                    rest,
+                });
+            }
+            if !self.eat(",") {
+                self.expect(")")?;
+                break;
+            }
+        }
+        Ok(params)
+    }
+
+    /// Skip tokens up to one of the given punctuation at the current depth.
+    fn skip_statement_until(&mut self, puncts: &[&str]) {
+        while !puncts.iter().any(|x| self.is(x)) && self.peek(0) != &Tok::Eof {
+            if self.is("(") || self.is("[") || self.is("{") {
+                if self.skip_group().is_err() {
+                    return;
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn return_type(&mut self) -> Result<Type, CodegenError> {
+        // Type predicates like `x is string` and assertions return booleans or nothing.
+        if self.is_word(0, "asserts") && matches!(self.peek(1), Tok::Ident(_)) {
+            self.pos += 2;
+            if self.is_word(0, "is") {
+                self.pos += 1;
+                self.ty()?;
+            }
+            return Ok(Type::Void);
+        }
+        if matches!(self.peek(0), Tok::Ident(_)) && self.is_word(1, "is") {
+            self.pos += 2;
+            self.ty()?;
+            return Ok(Type::Bool);
+        }
+        self.ty()
+    }
+
+    fn shape(
+        &mut self,
+        name: StdString,
+        doc: Option<StdString>,
+        class: bool,
+    ) -> Result<(), CodegenError> {
+        self.skip_generics()?;
+        let mut extends = Vec::new();
+        while self.is_word(0, "extends") || self.is_word(0, "implements") {
+            self.pos += 1;
+            loop {
+                let mut name = self.ident()?;
+                while self.eat(".") {
+                    name = self.ident()?;
+                }
+                self.skip_generics()?;
+                extends.push(name);
+                if !self.eat(",") {
+                    break;
+                }
+            }
+        }
+        self.expect("{")?;
+        let mut shape = Shape {
+            name: name.clone(),
+            doc,
+            extends,
+            ..Default::default()
+        };
+        while !self.eat("}") {
+            if self.peek(0) == &Tok::Eof {
+                return Err(self.error("Expected `}`"));
+            }
+            self.member(&mut shape, class)?;
+        }
+        let merged = self.decls.shape(&name);
+        merged.doc = merged.doc.take().or(shape.doc);
+        merged.extends.extend(shape.extends);
+        merged.props.extend(shape.props);
+        merged.methods.extend(shape.methods);
+        merged.statics.extend(shape.statics);
+        if merged.constructor.is_none() {
+            merged.constructor = shape.constructor;
+        }
+        if class && merged.constructor.is_none() {
+            merged.constructor = Some(Func {
+                name: "constructor".into(),
+                doc: None,
+                params: Vec::new(),
+                ret: Type::Any,
+            });
+        }
+        Ok(())
+    }
+
+    fn member(&mut self, shape: &mut Shape, class: bool) -> Result<(), CodegenError> {
+        if self.eat(";") || self.eat(",") {
+            return Ok(());
+        }
+        let doc = self.token().doc.clone();
+        let mut readonly = false;
+        let mut is_static = false;
+        let mut private = false;
+        while MODIFIERS.iter().any(|x| self.is_word(0, x))
+            && matches!(self.peek(1), Tok::Ident(_) | Tok::Str(_) | Tok::Punct("["))
+        {
+            readonly |= self.is_word(0, "readonly");
+            is_static |= self.is_word(0, "static");
+            private |= self.is_word(0, "private") || self.is_word(0, "protected");
+            self.pos += 1;
+        }
+        if private {
+            return self.skip_statement();
+        }
+        let accessor = (self.is_word(0, "get") || self.is_word(0, "set"))
+            && matches!(self.peek(1), Tok::Ident(_) | Tok::Str(_));
+        let getter = accessor && self.is_word(0, "get");
+        if accessor {
+            self.pos += 1;
+        }
+        if class && self.is_word(0, "constructor") && matches!(self.peek(1), Tok::Punct("(")) {
+            self.pos += 1;
+            let params = self.params()?;
+            if shape.constructor.is_none() {
+                shape.constructor = Some(Func {
+                    name: "constructor".into(),
+                    doc,
+                    params,
+                    ret: Type::Any,
+                });
+            }
+            return self.end_member();
+        }
+        let name = match self.peek(0) {
+            Tok::Ident(name) | Tok::Str(name) => name.clone(),
+            // Index, call and construct signatures, computed, numeric and private names.
+            _ => return self.skip_statement(),
+        };
+        self.pos += 1;
+        let optional = self.eat("?");
+        if accessor {
+            let params = self.params()?;
+            let ty = if getter {
+                if self.eat(":") {
+                    self.ty()?
+                } else {
+                    Type::Any
+                }
+            } else {
+                params.first().map_or(Type::Any, |x| x.ty.clone())
+            };
+            match shape.props.iter_mut().find(|x| x.name == name) {
+                // A getter and a setter make a writable property.
+                Some(prop) => prop.readonly &= getter,
+                None => shape.props.push(Prop {
+                    name,
+                    doc,
+                    ty,
+                    readonly: getter,
+                }),
+            }
+        } else if self.is("(") || self.is("<") {
+            let method = self.signature(name, doc)?;
+            let methods = if is_static {
+                &mut shape.statics
+            } else {
+                &mut shape.methods
+            };
+            if !methods.iter().any(|x| x.name == method.name) {
+                methods.push(method);
+            }
+        } else if !is_static {
+            let ty = if self.eat(":") { self.ty()? } else { Type::Any };
+            let ty = match ty {
+                Type::Option(_) => ty,
+                ty if optional => Type::Option(Box::new(ty)),
+                ty => ty,
+            };
+            shape.props.push(Prop {
+                name,
+                doc,
+                ty,
+                readonly,
+            });
+        }
+        self.end_member()
+    }
+
+    /// Skip the separator after a member, and anything left of the member before it.
+    fn end_member(&mut self) -> Result<(), CodegenError> {
+        if self.eat(";") || self.eat(",") || self.is("}") || self.on_new_line() {
+            return Ok(());
+        }
+        self.skip_statement()
+    }
+
+    fn ty(&mut self) -> Result<Type, CodegenError> {
+        self.eat("|");
+        self.eat("&");
+        let mut members = vec![self.intersection()?];
+        while self.eat("|") {
+            members.push(self.intersection()?);
+        }
+        if members.len() == 1 {
+            return Ok(members.pop().unwrap());
+        }
+        let nullable = members.iter().any(|x| matches!(x, Type::Null | Type::Void));
+        members.retain(|x| !matches!(x, Type::Null | Type::Void));
+        let ty = match members.first() {
+            None => return Ok(Type::Void),
+            Some(first) if members.iter().all(|x| x == first) => first.clone(),
+            Some(_) => Type::Any,
+        };
+        Ok(if nullable && ty != Type::Any {
+            Type::Option(Box::new(ty))
+        } else {
+            ty
+        })
+    }
+
+    fn intersection(&mut self) -> Result<Type, CodegenError> {
+        let ty = self.postfix()?;
+        if !self.is("&") {
+            return Ok(ty);
+        }
+        while self.eat("&") {
+            self.postfix()?;
+        }
+        Ok(Type::Object)
+    }
+
+    fn postfix(&mut self) -> Result<Type, CodegenError> {
+        let mut ty = self.primary()?;
+        while self.is("[") && !self.on_new_line() {
+            self.pos += 1;
+            if self.eat("]") {
+                ty = Type::Array(Box::new(ty));
+            } else {
+                // Indexed access types.
+                self.ty()?;
+                self.expect("]")?;
+                ty = Type::Any;
+            }
+        }
+        // Conditional types.
+        if self.is_word(0, "extends") && !self.on_new_line() {
+            self.pos += 1;
+            self.postfix()?;
+            self.expect("?")?;
+            self.ty()?;
+            self.expect(":")?;
+            self.ty()?;
+            ty = Type::Any;
+        }
+        Ok(ty)
+    }
+
+    /// Whether a parenthesis starts the parameters of a function type.
+    fn is_function_type(&self) -> bool {
+        let mut depth = 0usize;
+        let mut idx = self.pos;
+        loop {
+            match &self.tokens[idx].tok {
+                Tok::Punct("(" | "[" | "{") => depth += 1,
+                Tok::Punct(")" | "]" | "}") => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return matches!(self.tokens[idx + 1].tok, Tok::Punct("=>"));
+                    }
+                }
+                Tok::Eof => return false,
+                _ => {}
+            }
+            idx += 1;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Type, CodegenError> {
+        let tok = self.peek(0).clone();
+        match tok {
+            Tok::Punct("(") if self.is_function_type() => {
+                self.skip_group()?;
+                self.expect("=>")?;
+                self.return_type()?;
+                Ok(Type::Function)
+            }
+            Tok::Punct("(") => {
+                self.pos += 1;
+                let ty = self.ty()?;
+                self.expect(")")?;
+                Ok(ty)
+            }
+            Tok::Punct("<") => {
+                self.skip_group()?;
+                self.primary()?;
+                Ok(Type::Function)
+            }
+            Tok::Punct("{") => {
+                self.skip_group()?;
+                Ok(Type::Object)
+            }
+            Tok::Punct("[") => {
+                self.skip_group()?;
+                Ok(Type::Any)
+            }
+            Tok::Str(_) => {
+                self.pos += 1;
+                Ok(Type::String)
+            }
+            Tok::Num => {
+                self.pos += 1;
+                Ok(Type::Number)
+            }
+            Tok::Punct("-") => {
+                self.pos += 1;
+                self.primary()
+            }
+            Tok::Ident(name) => {
+                self.pos += 1;
+                match name.as_str() {
+                    "typeof" => {
+                        self.ident()?;
+                        while self.eat(".") {
+                            self.ident()?;
+                        }
+                        return Ok(Type::Any);
+                    }
+                    "keyof" => {
+                        self.postfix()?;
+                        return Ok(Type::String);
+                    }
+                    "readonly" => return self.postfix(),
+                    "unique" | "infer" => {
+                        self.primary()?;
+                        return Ok(Type::Any);
+                    }
+                    "new" | "abstract" => {
+                        self.eat("new");
+                        self.skip_generics()?;
+                        self.skip_group()?;
+                        self.expect("=>")?;
+                        self.ty()?;
+                        return Ok(Type::Function);
+                    }
+                    _ => {}
+                }
+                let mut name = name;
+                while self.eat(".") {
+                    name = format!("{name}.{}", self.ident()?);
+                }
+                let mut args = Vec::new();
+                if self.eat("<") {
+                    while !self.eat(">") {
+                        args.push(self.ty()?);
+                        if !self.eat(",") {
+                            self.expect(">")?;
+                            break;
+                        }
+                    }
+                }
+                Ok(match (name.as_str(), args.len()) {
+                    ("number" | "Number", _) => Type::Number,
+                    ("string" | "String", _) => Type::String,
+                    ("boolean" | "Boolean" | "true" | "false", _) => Type::Bool,
+                    ("void" | "undefined" | "never", _) => Type::Void,
+                    ("null", _) => Type::Null,
+                    ("bigint" | "BigInt", _) => Type::BigInt,
+                    ("object" | "Object", _) => Type::Object,
+                    ("Function", _) => Type::Function,
+                    ("Promise" | "PromiseLike", _) => Type::Promise,
+                    ("Array" | "ReadonlyArray", 1) => Type::Array(Box::new(args.remove(0))),
+                    ("Record", 2) => Type::Record(Box::new(args.remove(1))),
+                    ("Partial" | "Readonly" | "Required", 1) => args.remove(0),
+                    (_, 0) => Type::Named(name),
+                    _ => Type::Any,
+                })
+            }
+            _ => Err(self.error("Expected a type")),
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// A Rust identifier for a JS name, escaping keywords.
+fn ident(name: &str) -> StdString {
+    let mut res: StdString = name
+        .chars()
+        .map(|x| if x.is_alphanumeric() { x } else { '_' })
+        .collect();
+    if res.is_empty() || res.starts_with(|x: char| x.is_ascii_digit()) {
+        res.insert(0, '_');
+    }
+    if matches!(res.as_str(), "self" | "Self" | "super" | "crate") {
+        res.push('_');
+    } else if KEYWORDS.contains(&res.as_str()) {
+        res.insert_str(0, "r#");
+    }
+    res
+}
+
+/// The snake case Rust identifier of a camel case JS name.
+fn snake_ident(name: &str) -> StdString {
+    let chars: Vec<char> = name.chars().collect();
+    let mut res = StdString::new();
+    for (idx, &x) in chars.iter().enumerate() {
+        if x.is_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next_lower = matches!(chars.get(idx + 1), Some(x) if x.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                res.push('_');
+            }
+        }
+        res.extend(x.to_lowercase());
+    }
+    ident(&res)
+}
+
+struct Writer<'a> {
+    decls: &'a Decls,
+    res: StdString,
+}
+
+impl Writer<'_> {
+    fn write(mut self, name: &str) -> StdString {
+        self.res += "// Generated by rquickjs from TypeScript declarations, do not edit.\n\n\
+                     #[allow(unused_imports)]\n\
+                     use rquickjs::{\n    \
+                         function::{Constructor, Opt, Rest, This},\n    \
+                         BigInt, Ctx, FromJs, Function, IntoJs, Object, Promise, Result, Value,\n\
+                     };\n\
+                     #[allow(unused_imports)]\n\
+                     use std::collections::HashMap;\n";
+
+        self.wrapper(
+            name,
+            Some(
+                "The top level declarations, defined on the global object or on the namespace of \
+                 a module.",
+            ),
+        );
+        let _ = writeln!(
+            self.res,
+            "\nimpl<'js> {name}<'js> {{\n    \
+                 /// The declarations defined on the global object.\n    \
+                 pub fn globals(ctx: &Ctx<'js>) -> Self {{\n        \
+                     Self(ctx.globals())\n    \
+                 }}"
+        );
+        let mut names = HashSet::new();
+        for function in &self.decls.functions {
+            self.method(&mut names, &snake_ident(&function.name), function, None);
+        }
+        for constant in &self.decls.consts {
+            self.getter(&mut names, &snake_ident(&constant.name), constant);
+        }
+        for shape in &self.decls.shapes {
+            let Some(constructor) = &shape.constructor else {
+                continue;
+            };
+            let class = snake_ident(&shape.name);
+            let class = class.trim_start_matches("r#");
+            let fallback = format!("Construct a `{}`.", shape.name);
+            let constructor = Func {
+                doc: constructor.doc.clone().or(Some(fallback)),
+                ..constructor.clone()
+            };
+            self.method(
+                &mut names,
+                &format!("new_{class}"),
+                &constructor,
+                Some(shape),
+            );
+            for method in &shape.statics {
+                let rust_name = format!("{class}_{}", snake_ident(&method.name));
+                let rust_name = rust_name.replace("r#", "");
+                let target = format!(
+                    "let class: Object = self.0.get({:?})?;\n        \
+                     let function: Function = class.get({:?})?;\n        \
+                     function.call((This(class)",
+                    shape.name, method.name
+                );
+                self.method_with(&mut names, &rust_name, method, &target);
+            }
+        }
+        self.res += "}\n";
+
+        for shape in &self.decls.shapes {
+            self.shape(shape);
+        }
+        self.res
+    }
+
+    fn doc(&mut self, indent: &str, doc: &str) {
+        for line in doc.lines() {
+            if line.is_empty() {
+                let _ = writeln!(self.res, "{indent}///");
+            } else {
+                let _ = writeln!(self.res, "{indent}/// {line}");
+            }
+        }
+    }
+
+    /// The struct wrapping an object, with its conversions.
+    fn wrapper(&mut self, name: &str, doc: Option<&str>) {
+        self.res.push('\n');
+        let fallback = format!("The `{name}` of the declarations.");
+        self.doc("", doc.unwrap_or(&fallback));
+        let _ = write!(
+            self.res,
+            "#[derive(Clone, Debug, PartialEq)]\n\
+             pub struct {name}<'js>(pub Object<'js>);\n\
+             \n\
+             impl<'js> FromJs<'js> for {name}<'js> {{\n    \
+                 fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {{\n        \
+                     Object::from_js(ctx, value).map(Self)\n    \
+                 }}\n\
+             }}\n\
+             \n\
+             impl<'js> IntoJs<'js> for {name}<'js> {{\n    \
+                 fn into_js(self, _ctx: &Ctx<'js>) -> Result<Value<'js>> {{\n        \
+                     Ok(self.0.into_value())\n    \
+                 }}\n\
+             }}\n"
+        );
+    }
+
+    fn shape(&mut self, shape: &Shape) {
+        let name = ident(&shape.name);
+        self.wrapper(&name, shape.doc.as_deref());
+        let _ = writeln!(self.res, "\nimpl<'js> {name}<'js> {{");
+        let mut names = HashSet::new();
+        // Inherited members come after the own members, which override them.
+        let mut visited = HashSet::new();
+        let mut queue = vec![shape];
+        while let Some(shape) = queue.pop() {
+            if !visited.insert(shape.name.as_str()) {
+                continue;
+            }
+            for prop in &shape.props {
+                let rust_name = snake_ident(&prop.name);
+                self.getter(&mut names, &rust_name, prop);
+                if !prop.readonly {
+                    let setter = format!("set_{}", rust_name.trim_start_matches("r#"));
+                    self.setter(&mut names, &setter, prop);
+                }
+            }
+            for method in &shape.methods {
+                let target = format!(
+                    "let function: Function = self.0.get({:?})?;\n        \
+                     function.call((This(self.0.clone())",
+                    method.name
+                );
+                self.method_with(&mut names, &snake_ident(&method.name), method, &target);
+            }
+            for parent in shape.extends.iter().rev() {
+                if let Some(parent) = self.decls.shapes.iter().find(|x| &x.name == parent) {
+                    queue.push(parent);
+                }
+            }
+        }
+        self.res += "}\n";
+    }
+
+    fn getter(&mut self, names: &mut HashSet<StdString>, rust_name: &str, prop: &Prop) {
+        if !names.insert(rust_name.into()) {
+            return;
+        }
+        self.separate_once();
+        let fallback = format!("Returns `{}`.", prop.name);
+        self.doc("    ", prop.doc.as_deref().unwrap_or(&fallback));
+        let ty = self.rust_type(&prop.ty);
+        let _ = writeln!(
+            self.res,
+            "    pub fn {rust_name}(&self) -> Result<{ty}> {{\n        \
+                 self.0.get({:?})\n    \
+             }}",
+            prop.name
+        );
+    }
+
+    fn setter(&mut self, names: &mut HashSet<StdString>, rust_name: &str, prop: &Prop) {
+        if !names.insert(rust_name.into()) {
+            return;
+        }
+        self.separate_once();
+        let _ = writeln!(self.res, "    /// Set `{}`.", prop.name);
+        let ty = self.rust_type(&prop.ty);
+        let _ = writeln!(
+            self.res,
+            "    pub fn {rust_name}(&self, value: {ty}) -> Result<()> {{\n        \
+                 self.0.set({:?}, value)\n    \
+             }}",
+            prop.name
+        );
+    }
+
+    /// Separate an item from the previous one in an `impl` block.
+    fn separate_once(&mut self) {
+        if !self.res.ends_with("{\n") {
+            self.res.push('\n');
+        }
+    }
+
+    /// A function of the top level declarations, or the constructor of a class.
+    fn method(
+        &mut self,
+        names: &mut HashSet<StdString>,
+        rust_name: &str,
+        function: &Func,
+        class: Option<&Shape>,
+    ) {
+        let target = match class {
+            Some(class) => format!(
+                "let constructor: Constructor = self.0.get({:?})?;\n        \
+                 constructor.construct((",
+                class.name
+            ),
+            None => format!(
+                "let function: Function = self.0.get({:?})?;\n        \
+                 function.call((",
+                function.name
+            ),
+        };
+        let function = match class {
+            Some(class) => Func {
+                ret: Type::Named(class.name.clone()),
+                ..function.clone()
+            },
+            None => function.clone(),
+        };
+        self.method_with(names, rust_name, &function, &target);
+    }
+
+    /// A method calling `target`, the start of a call expression taking the arguments.
+    fn method_with(
+        &mut self,
+        names: &mut HashSet<StdString>,
+        rust_name: &str,
+        function: &Func,
+        target: &str,
+    ) {
+        if !names.insert(rust_name.into()) {
+            return;
+        }
+        self.separate_once();
+        let fallback = format!("Call `{}`.", function.name);
+        self.doc("    ", function.doc.as_deref().unwrap_or(&fallback));
+        let mut params = StdString::new();
+        let mut args = StdString::new();
+        let mut used = HashSet::new();
+        for param in &function.params {
+            let mut name = snake_ident(&param.name);
+            while !used.insert(name.clone()) {
+                name.push('_');
+            }
+            let ty = self.rust_type(&param.ty);
+            let (ty, arg) = if param.rest {
+                (format!("Vec<{ty}>"), format!("Rest({name})"))
+            } else if param.optional {
+                match param.ty {
+                    Type::Option(_) => (ty, format!("Opt({name})")),
+                    _ => (format!("Option<{ty}>"), format!("Opt({name})")),
+                }
+            } else {
+                (ty, name.clone())
+            };
+            let _ = write!(params, ", {name}: {ty}");
+            if target.ends_with("((") && args.is_empty() {
+                args += &arg;
+            } else {
+                let _ = write!(args, ", {arg}");
+            }
+        }
+        // A single argument needs a trailing comma to be a tuple.
+        let single =
+            target.ends_with("((") != function.params.is_empty() && function.params.len() <= 1;
+        let close = if single { ",))" } else { "))" };
+        let ret = self.rust_type(&function.ret);
+        let _ = writeln!(
+            self.res,
+            "    pub fn {rust_name}(&self{params}) -> Result<{ret}> {{\n        \
+                 {target}{args}{close}\n    \
+             }}"
+        );
+    }
+
+    fn rust_type(&self, ty: &Type) -> StdString {
+        self.rust_type_at(ty, 0)
+    }
+
+    fn rust_type_at(&self, ty: &Type, depth: usize) -> StdString {
+        match ty {
+            Type::Number => "f64".into(),
+            Type::String => "String".into(),
+            Type::Bool => "bool".into(),
+            Type::Void => "()".into(),
+            Type::BigInt => "BigInt<'js>".into(),
+            Type::Object => "Object<'js>".into(),
+            Type::Function => "Function<'js>".into(),
+            Type::Promise => "Promise<'js>".into(),
+            Type::Null | Type::Any => "Value<'js>".into(),
+            Type::Array(ty) => format!("Vec<{}>", self.rust_type_at(ty, depth)),
+            Type::Record(ty) => format!("HashMap<String, {}>", self.rust_type_at(ty, depth)),
+            Type::Option(ty) => match self.rust_type_at(ty, depth).as_str() {
+                "Value<'js>" => "Value<'js>".into(),
+                ty => format!("Option<{ty}>"),
+            },
+            Type::Named(name) => {
+                if self.decls.shapes.iter().any(|x| &x.name == name) {
+                    format!("{}<'js>", ident(name))
+                } else {
+                    match self.decls.aliases.get(name) {
+                        // Aliases can be recursive.
+                        Some(ty) if depth < 16 => self.rust_type_at(ty, depth + 1),
+                        _ => "Value<'js>".into(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bindings;
+
+    #[test]
+    fn bindings() {
+        let source = Bindings::new()
+            .generate(
+                r#"
+                import { Other } from "./other";
+                type Color = "red" | "green";
+                interface Named {
+                    name: string
+                    readonly "content-type"?: string;
+                }
+                /** A figure. */
+                export declare class Shape implements Named {
+                    constructor(name: string, color?: Color);
+                    static fromJSON(json: string): Shape;
+                    private secret: number;
+                    get area(): number;
+                    draw(...points: number[][]): void;
+                    draw(): void;
+                    type(): Color | undefined;
+                    [key: string]: unknown;
+                }
+                export declare function make(options: Record<string, boolean>): Shape | null;
+                export declare const VERSION: string;
+                export namespace Internal { function hidden(): void; }
+                declare global {
+                    function isShape(x: unknown): x is Shape;
+                }
+                "#,
+            )
+            .unwrap();
+        let signatures: Vec<_> = source
+            .lines()
+            .filter(|x| x.trim_start().starts_with("pub fn"))
+            .map(|x| x.trim())
+            .collect();
+        assert_eq!(
+            signatures,
+            [
+                "pub fn globals(ctx: &Ctx<'js>) -> Self {",
+                "pub fn make(&self, options: HashMap<String, bool>) -> Result<Option<Shape<'js>>> {",
+                "pub fn is_shape(&self, x: Value<'js>) -> Result<bool> {",
+                "pub fn version(&self) -> Result<String> {",
+                "pub fn new_shape(&self, name: String, color: Option<String>) -> Result<Shape<'js>> {",
+                "pub fn shape_from_json(&self, json: String) -> Result<Shape<'js>> {",
+                "pub fn name(&self) -> Result<String> {",
+                "pub fn set_name(&self, value: String) -> Result<()> {",
+                "pub fn content_type(&self) -> Result<Option<String>> {",
+                "pub fn area(&self) -> Result<f64> {",
+                "pub fn draw(&self, points: Vec<Vec<f64>>) -> Result<()> {",
+                "pub fn r#type(&self) -> Result<Option<String>> {",
+                "pub fn name(&self) -> Result<String> {",
+                "pub fn set_name(&self, value: String) -> Result<()> {",
+                "pub fn content_type(&self) -> Result<Option<String>> {",
+            ]
+        );
+        assert!(
+            source.contains("/// A figure.\n#[derive(Clone, Debug, PartialEq)]\npub struct Shape")
+        );
+        assert!(source.contains("constructor.construct((name, Opt(color)))"));
+        assert!(source.contains("function.call((This(self.0.clone()), Rest(points)))"));
+        assert!(source.contains("function.call((This(self.0.clone()),))"));
+        assert!(source.contains("self.0.get(\"content-type\")"));
+
+        let error = Bindings::new()
+            .generate("interface A {\n  x: number;\n")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Expected `}` on line 3");
+        let error = Bindings::new()
+            .with_name("A")
+            .generate("interface A {}")
+            .unwrap_err();
+        assert_eq!(error.message, "`A` is also the name of a declaration");
+    }
+}
@@ -3,6 +3,9 @@
 mod base;
 mod builder;
 mod ctx;
+mod data;
+#[cfg(not(feature = "system"))]
+mod pool;
 mod r#ref;
 
 #[cfg(feature = "futures")]
@@ -24,7 +27,12 @@ pub trait MultiWith<'js> {
 
 pub use base::Context;
 pub use builder::{intrinsic, ContextBuilder, Intrinsic};
-pub use ctx::{Ctx, EvalOptions};
+pub use ctx::{Ctx, ErrorPolicy, EvalOptions, ResolveOptions};
+#[cfg(not(feature = "system"))]
+pub(crate) use data::regexp_poll;
+pub(crate) use data::{context_data_finalizer, ContextData};
+#[cfg(not(feature = "system"))]
+pub use pool::{ContextPool, PooledContext};
 
 #[cfg(feature = "futures")]
 pub use r#async::AsyncContext;
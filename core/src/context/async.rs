@@ -1,6 +1,12 @@
 use super::{intrinsic, r#ref::ContextRef, ContextBuilder, Intrinsic};
-use crate::{markers::ParallelSend, qjs, runtime::AsyncRuntime, Ctx, Error, Result};
-use std::{future::Future, mem, pin::Pin, ptr::NonNull};
+use crate::{
+    markers::ParallelSend,
+    qjs,
+    runtime::{raw::RawRuntime, AsyncRuntime},
+    Ctx, Error, Result,
+};
+use core::{future::Future, mem, panic::AssertUnwindSafe, pin::Pin, ptr::NonNull};
+use std::panic;
 
 mod future;
 
@@ -76,8 +82,8 @@ macro_rules! async_with{
             /// rquickjs objects are send so the future will never be send.
             /// Since we acquire a lock before running the future and nothing can escape the closure
             /// and future it is safe to recast the future as send.
-            unsafe fn uplift<'a,'b,R>(f: std::pin::Pin<Box<dyn std::future::Future<Output = R> + 'a>>) -> std::pin::Pin<Box<dyn std::future::Future<Output = R> + 'b + Send>>{
-                std::mem::transmute(f)
+            unsafe fn uplift<'a,'b,R>(f: core::pin::Pin<Box<dyn core::future::Future<Output = R> + 'a>>) -> core::pin::Pin<Box<dyn core::future::Future<Output = R> + 'b + Send>>{
+                core::mem::transmute(f)
             }
             unsafe{ uplift(fut) }
         })
@@ -133,6 +139,13 @@ impl Drop for Inner {
         };
         guard.runtime.update_stack_top();
         unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
+        #[cfg(feature = "leak-detection")]
+        unsafe {
+            crate::runtime::raw::RawRuntime::report_leaks(
+                guard.runtime.rt.as_ptr(),
+                self.ctx.as_ptr(),
+            )
+        };
         // Explicitly drop the guard to ensure it is valid during the entire use of runtime
         mem::drop(guard);
     }
@@ -168,9 +181,15 @@ impl AsyncContext {
     /// [`AsyncContext::builder`] or [`AsyncContext::full`].
     pub async fn custom<I: Intrinsic>(runtime: &AsyncRuntime) -> Result<Self> {
         let guard = runtime.inner.lock().await;
+        let scope = guard.runtime.get_opaque().enter_new_context();
         let ctx = NonNull::new(unsafe { qjs::JS_NewContextRaw(guard.runtime.rt.as_ptr()) })
             .ok_or_else(|| Error::Allocation)?;
+        unsafe { scope.assign(ctx.as_ptr()) };
+        // rquickjs assumes the base objects exist, so we allways need to add this.
+        unsafe { qjs::JS_AddIntrinsicBaseObjects(ctx.as_ptr()) };
         unsafe { I::add_intrinsic(ctx) };
+        mem::drop(scope);
+        unsafe { Self::init(&guard.runtime, ctx)? };
         let res = Inner {
             ctx,
             rt: runtime.clone(),
@@ -186,8 +205,12 @@ impl AsyncContext {
     /// [`AsyncContext::custom`] or [`AsyncContext::builder`].
     pub async fn full(runtime: &AsyncRuntime) -> Result<Self> {
         let guard = runtime.inner.lock().await;
+        let scope = guard.runtime.get_opaque().enter_new_context();
         let ctx = NonNull::new(unsafe { qjs::JS_NewContext(guard.runtime.rt.as_ptr()) })
             .ok_or_else(|| Error::Allocation)?;
+        unsafe { scope.assign(ctx.as_ptr()) };
+        mem::drop(scope);
+        unsafe { Self::init(&guard.runtime, ctx)? };
         let res = Inner {
             ctx,
             rt: runtime.clone(),
@@ -199,6 +222,19 @@ impl AsyncContext {
         Ok(AsyncContext(ContextRef::new(res)))
     }
 
+    /// Runs the context created hooks of the runtime, freeing the context if one of them fails
+    /// or panics.
+    unsafe fn init(rt: &RawRuntime, ctx: NonNull<qjs::JSContext>) -> Result<()> {
+        rt.update_stack_top();
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            rt.get_opaque().run_context_created(ctx)
+        }));
+        if !matches!(res, Ok(Ok(()))) {
+            qjs::JS_FreeContext(ctx.as_ptr());
+        }
+        res.unwrap_or_else(|panic| panic::resume_unwind(panic))
+    }
+
     /// Create a context builder for creating a context with a specific set of intrinsics
     pub fn builder() -> ContextBuilder<()> {
         ContextBuilder::default()
@@ -237,11 +273,30 @@ impl AsyncContext {
     {
         let guard = self.0.rt.inner.lock().await;
         guard.runtime.update_stack_top();
+        let scope = unsafe {
+            guard
+                .runtime
+                .get_opaque()
+                .enter_context(self.0.ctx.as_ptr())
+        };
         let ctx = unsafe { Ctx::new_async(self) };
         let res = f(ctx);
+        mem::drop(scope);
         guard.drop_pending();
         res
     }
+
+    /// Returns the memory attributed to this context.
+    ///
+    /// Returns `None` if the runtime wasn't created with
+    /// [`AsyncRuntime::new_with_accounting`](crate::AsyncRuntime::new_with_accounting).
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    #[cfg(feature = "allocator")]
+    pub async fn memory_usage(&self) -> Option<crate::allocator::ContextMemoryUsage> {
+        let guard = self.0.rt.inner.lock().await;
+        let id = unsafe { super::ContextData::accounting_id(self.0.ctx.as_ptr()) };
+        guard.runtime.get_opaque().accounting().map(|x| x.usage(id))
+    }
 }
 
 // Since the reference to runtime is behind a Arc this object is send
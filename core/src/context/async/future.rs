@@ -1,4 +1,4 @@
-use std::{
+use core::{
     future::Future,
     mem::{self, ManuallyDrop},
     pin::Pin,
@@ -88,6 +88,11 @@ where
         };
 
         lock.runtime.update_stack_top();
+        let scope = unsafe {
+            lock.runtime
+                .get_opaque()
+                .enter_context(this.context.0.ctx.as_ptr())
+        };
 
         // At this point we have locked the runtime so we start running the actual future
         // we can move this memory since the future is boxed and thus movable.
@@ -151,6 +156,7 @@ where
         };
 
         // Manually drop the lock so it isn't accidentally moved into somewhere.
+        mem::drop(scope);
         mem::drop(lock);
 
         res
@@ -1,6 +1,68 @@
 use super::{ctx::RefCountHeader, intrinsic, r#ref::ContextRef, ContextBuilder, Intrinsic};
-use crate::{qjs, Ctx, Error, Result, Runtime};
-use std::{mem, ptr::NonNull};
+use crate::{
+    qjs,
+    runtime::{opaque::Opaque, raw::RawRuntime},
+    util, Ctx, Error, Result, Runtime,
+};
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use core::{mem, panic::AssertUnwindSafe, ptr::NonNull};
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// The runtimes locked by a [`Context::with`] which is running on this thread.
+    static ENTERED: RefCell<Vec<*mut qjs::JSRuntime>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a runtime as locked by the current thread while alive.
+struct Entered(*mut qjs::JSRuntime);
+
+#[cfg(feature = "std")]
+impl Entered {
+    fn new(rt: *mut qjs::JSRuntime) -> Self {
+        ENTERED.with(|x| x.borrow_mut().push(rt));
+        Entered(rt)
+    }
+
+    fn contains(rt: *mut qjs::JSRuntime) -> bool {
+        ENTERED.with(|x| x.borrow().contains(&rt))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for Entered {
+    fn drop(&mut self) {
+        ENTERED.with(|x| {
+            let mut entered = x.borrow_mut();
+            if let Some(idx) = entered.iter().rposition(|rt| *rt == self.0) {
+                entered.remove(idx);
+            }
+        })
+    }
+}
+
+// Without threads a runtime can only be locked by the code currently running, so a count kept
+// in the runtime itself suffices.
+#[cfg(not(feature = "std"))]
+impl Entered {
+    fn new(rt: *mut qjs::JSRuntime) -> Self {
+        let entered = unsafe { Opaque::from_runtime_ptr(rt) }.entered();
+        entered.set(entered.get() + 1);
+        Entered(rt)
+    }
+
+    fn contains(rt: *mut qjs::JSRuntime) -> bool {
+        unsafe { Opaque::from_runtime_ptr(rt) }.entered().get() != 0
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Drop for Entered {
+    fn drop(&mut self) {
+        let entered = unsafe { Opaque::from_runtime_ptr(self.0) }.entered();
+        entered.set(entered.get() - 1);
+    }
+}
 
 pub(crate) struct Inner {
     pub(crate) ctx: NonNull<qjs::JSContext>,
@@ -48,11 +110,15 @@ impl Context {
     /// [`Context::builder`] or [`Context::full`].
     pub fn custom<I: Intrinsic>(runtime: &Runtime) -> Result<Self> {
         let guard = runtime.inner.lock();
+        let scope = guard.get_opaque().enter_new_context();
         let ctx = NonNull::new(unsafe { qjs::JS_NewContextRaw(guard.rt.as_ptr()) })
             .ok_or_else(|| Error::Allocation)?;
+        unsafe { scope.assign(ctx.as_ptr()) };
         // rquickjs assumes the base objects exist, so we allways need to add this.
         unsafe { qjs::JS_AddIntrinsicBaseObjects(ctx.as_ptr()) };
         unsafe { I::add_intrinsic(ctx) };
+        mem::drop(scope);
+        unsafe { Self::init(&guard, ctx)? };
         let res = Inner {
             ctx,
             rt: runtime.clone(),
@@ -67,8 +133,12 @@ impl Context {
     /// [`Context::custom`] or [`Context::builder`].
     pub fn full(runtime: &Runtime) -> Result<Self> {
         let guard = runtime.inner.lock();
+        let scope = guard.get_opaque().enter_new_context();
         let ctx = NonNull::new(unsafe { qjs::JS_NewContext(guard.rt.as_ptr()) })
             .ok_or_else(|| Error::Allocation)?;
+        unsafe { scope.assign(ctx.as_ptr()) };
+        mem::drop(scope);
+        unsafe { Self::init(&guard, ctx)? };
         let res = Inner {
             ctx,
             rt: runtime.clone(),
@@ -79,6 +149,20 @@ impl Context {
         Ok(Context(ContextRef::new(res)))
     }
 
+    /// Runs the context created hooks of the runtime, freeing the context if one of them fails
+    /// or panics.
+    unsafe fn init(rt: &RawRuntime, ctx: NonNull<qjs::JSContext>) -> Result<()> {
+        rt.update_stack_top();
+        let _entered = Entered::new(rt.rt.as_ptr());
+        let res = util::catch_unwind(AssertUnwindSafe(|| {
+            rt.get_opaque().run_context_created(ctx)
+        }));
+        if !matches!(res, Ok(Ok(()))) {
+            qjs::JS_FreeContext(ctx.as_ptr());
+        }
+        res.unwrap_or_else(|panic| util::resume_unwind(panic))
+    }
+
     /// Create a context builder for creating a context with a specific set of intrinsics
     pub fn builder() -> ContextBuilder<()> {
         ContextBuilder::default()
@@ -100,39 +184,83 @@ impl Context {
     /// Furthermore, this way it is impossible to use values from different runtimes in this
     /// context which would otherwise be undefined behavior.
     ///
+    /// Calling `with` from within the callback of another `with` on the same thread, for example
+    /// from a Rust function called by JavaScript, reuses the lock which is already held instead of
+    /// deadlocking, as long as both contexts belong to the same runtime. This doesn't apply to the
+    /// methods of [`Runtime`], which still need the runtime to be unlocked.
     ///
     /// This is the only way to get a [`Ctx`] object.
     pub fn with<F, R>(&self, f: F) -> R
     where
         F: FnOnce(Ctx) -> R,
     {
+        let rt = self.get_runtime_ptr();
+        if Entered::contains(rt) {
+            // The runtime is locked by an enclosing call on this thread, which keeps it locked
+            // for at least as long as this call runs.
+            let _scope = unsafe { Opaque::from_runtime_ptr(rt).enter_context(self.0.ctx.as_ptr()) };
+            let ctx = unsafe { Ctx::new(self) };
+            return f(ctx);
+        }
+
         let guard = self.0.rt.inner.lock();
         guard.update_stack_top();
+        let _entered = Entered::new(rt);
+        let _scope = unsafe { guard.get_opaque().enter_context(self.0.ctx.as_ptr()) };
         let ctx = unsafe { Ctx::new(self) };
         f(ctx)
     }
+
+    /// Returns the memory attributed to this context.
+    ///
+    /// This locks the runtime, use [`Ctx::memory_usage`] from within [`Context::with`].
+    ///
+    /// Returns `None` if the runtime wasn't created with
+    /// [`Runtime::new_with_accounting`](crate::Runtime::new_with_accounting).
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    #[cfg(feature = "allocator")]
+    pub fn memory_usage(&self) -> Option<crate::allocator::ContextMemoryUsage> {
+        let guard = self.0.rt.inner.lock();
+        let id = unsafe { super::ContextData::accounting_id(self.0.ctx.as_ptr()) };
+        guard.get_opaque().accounting().map(|x| x.usage(id))
+    }
 }
 
-impl Drop for Context {
+impl Drop for Inner {
     fn drop(&mut self) {
         //TODO
-        let guard = match self.0.rt.inner.try_lock() {
+        let rt = unsafe { qjs::JS_GetRuntime(self.ctx.as_ptr()) };
+        if Entered::contains(rt) {
+            // Dropped within a `with` of the same runtime on this thread which holds the lock.
+            unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
+            #[cfg(feature = "leak-detection")]
+            unsafe {
+                RawRuntime::report_leaks(rt, self.ctx.as_ptr())
+            };
+            return;
+        }
+        let guard = match self.rt.inner.try_lock() {
             Some(x) => x,
             None => {
-                let p = unsafe { &mut *(self.0.ctx.as_ptr() as *mut RefCountHeader) };
+                let p = unsafe { &mut *(self.ctx.as_ptr() as *mut RefCountHeader) };
                 if p.ref_count <= 1 {
                     // Lock was poisoned, this should only happen on a panic.
                     // We should still free the context.
                     // TODO see if there is a way to recover from a panic which could cause the
                     // following assertion to trigger
+                    #[cfg(feature = "std")]
                     assert!(std::thread::panicking());
                 }
-                unsafe { qjs::JS_FreeContext(self.0.ctx.as_ptr()) }
+                unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
                 return;
             }
         };
         guard.update_stack_top();
-        unsafe { qjs::JS_FreeContext(self.0.ctx.as_ptr()) }
+        unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
+        #[cfg(feature = "leak-detection")]
+        unsafe {
+            RawRuntime::report_leaks(rt, self.ctx.as_ptr())
+        };
         // Explicitly drop the guard to ensure it is valid during the entire use of runtime
         mem::drop(guard);
     }
@@ -251,6 +379,29 @@ mod test {
         println!("done");
     }
 
+    #[test]
+    fn nested_with() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let other = Context::full(&rt).unwrap();
+        let unused = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let nested = Function::new(ctx.clone(), move || {
+                other.with(|ctx| {
+                    ctx.globals().set("fromNested", 42)?;
+                    ctx.eval::<i32, _>("fromNested + 1")
+                })
+            })
+            .unwrap();
+            ctx.globals().set("nested", nested).unwrap();
+            let res: i32 = ctx.eval("nested()").unwrap();
+            assert_eq!(res, 43);
+
+            // Dropping the last handle of a context while the runtime is held.
+            mem::drop(unused);
+        });
+    }
+
     // Will be improved by https://github.com/quickjs-ng/quickjs/pull/406
     #[test]
     #[should_panic(
@@ -1,26 +1,72 @@
+use alloc::collections::BTreeSet;
+use alloc::{boxed::Box, ffi::CString, vec::Vec};
 #[cfg(feature = "futures")]
-use std::future::Future;
-use std::{
+use core::future::Future;
+#[cfg(feature = "std")]
+use core::time::Duration;
+use core::{
     any::Any,
-    ffi::{CStr, CString},
-    fs,
+    ffi::CStr,
     mem::{self, MaybeUninit},
-    path::Path,
     ptr::NonNull,
     result::Result as StdResult,
 };
+#[cfg(feature = "std")]
+use std::{fs, path::Path, time::Instant};
 
 #[cfg(feature = "futures")]
 use crate::AsyncContext;
 use crate::{
+    convert::{ConversionMode, NumberConversion},
+    function::This,
     markers::Invariant,
     qjs,
-    runtime::{opaque::Opaque, UserDataError, UserDataGuard},
-    Atom, Error, FromJs, Function, IntoJs, JsLifetime, Object, Promise, Result, String, Value,
+    runtime::{
+        opaque::Opaque, record_metric, ContextMetrics, MetricsCounters, UserDataError,
+        UserDataGuard,
+    },
+    util, Atom, Error, Filter, FromJs, Function, IntoJs, JsLifetime, Object, Promise, Result,
+    StdString, String, Value,
 };
 
 use super::Context;
 
+#[cfg(feature = "std")]
+/// Sets the deadline of the runtime while alive and restores the previous one when dropped.
+struct DeadlineGuard<'a, 'js> {
+    ctx: &'a Ctx<'js>,
+    prev: Option<Instant>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, 'js> DeadlineGuard<'a, 'js> {
+    fn new(ctx: &'a Ctx<'js>, deadline: Option<Instant>) -> Self {
+        unsafe {
+            let opaque = ctx.get_opaque();
+            let prev = opaque.replace_deadline(None);
+            // A nested deadline can't extend the deadline of an enclosing evaluation.
+            let deadline = match (prev, deadline) {
+                (Some(prev), Some(deadline)) => Some(prev.min(deadline)),
+                (prev, deadline) => prev.or(deadline),
+            };
+            opaque.replace_deadline(deadline);
+            opaque.reset_deadline_reached();
+            crate::runtime::raw::update_interrupt_handler(qjs::JS_GetRuntime(ctx.as_ptr()));
+            DeadlineGuard { ctx, prev }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for DeadlineGuard<'_, '_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.ctx.get_opaque().replace_deadline(self.prev);
+            crate::runtime::raw::update_interrupt_handler(qjs::JS_GetRuntime(self.ctx.as_ptr()));
+        }
+    }
+}
+
 /// Eval options.
 #[non_exhaustive]
 pub struct EvalOptions {
@@ -32,6 +78,12 @@ pub struct EvalOptions {
     pub backtrace_barrier: bool,
     /// Support top-level-await.
     pub promise: bool,
+    /// The name of the script, shown in the stack traces and the
+    /// [`position`](crate::Exception::position) of errors thrown by it.
+    ///
+    /// Defaults to `eval_script`, or to the name of the file when evaluating a file. Hosts running
+    /// scripts of different origins can use it to attribute errors to the right script.
+    pub filename: Option<StdString>,
 }
 
 impl EvalOptions {
@@ -65,10 +117,36 @@ impl Default for EvalOptions {
             strict: true,
             backtrace_barrier: false,
             promise: false,
+            filename: None,
         }
     }
 }
 
+/// Options for [`Ctx::resolve_sync`].
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct ResolveOptions {
+    /// The maximum time to wait for the promise to settle, including the time spent running
+    /// jobs. `None` waits until the job queue runs out of jobs.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub timeout: Option<Duration>,
+}
+
+/// How much detail of the errors returned by Rust code is shown to scripts, see
+/// [`Ctx::set_error_policy`].
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Exceptions carry the full message of the [`Error`].
+    #[default]
+    Detailed,
+    /// Exceptions carry a generic message without host details like the names of Rust types, the
+    /// paths of loaded modules, the messages of IO errors or of panics thrown with
+    /// [`PanicAction::Throw`](crate::runtime::PanicAction::Throw).
+    Redacted,
+}
+
 /// Context in use, passed to [`Context::with`].
 #[derive(Debug)]
 pub struct Ctx<'js> {
@@ -136,9 +214,19 @@ impl<'js> Ctx<'js> {
         file_name: &CStr,
         flag: i32,
     ) -> Result<qjs::JSValue> {
-        let src = source.into();
+        trace_span!(
+            "eval",
+            file = ?file_name,
+            compile_only = flag & qjs::JS_EVAL_FLAG_COMPILE_ONLY as i32 != 0
+        );
+        // Source files saved by some editors start with a byte order mark, which would keep
+        // QuickJS from skipping a shebang line.
+        let src = util::strip_bom(source.into());
         let len = src.len();
         let src = CString::new(src)?;
+        if flag & qjs::JS_EVAL_FLAG_COMPILE_ONLY as i32 == 0 {
+            record_metric(self.as_ptr(), MetricsCounters::evaluation);
+        }
         let val = qjs::JS_Eval(
             self.ctx.as_ptr(),
             src.as_ptr(),
@@ -174,32 +262,147 @@ impl<'js> Ctx<'js> {
         source: S,
         options: EvalOptions,
     ) -> Result<V> {
-        let file_name = CStr::from_bytes_with_nul(b"eval_script\0").unwrap();
+        let file_name = CString::new(options.filename.as_deref().unwrap_or("eval_script"))?;
 
         V::from_js(self, unsafe {
-            let val = self.eval_raw(source, file_name, options.to_flag())?;
+            let val = self.eval_raw(source, file_name.as_c_str(), options.to_flag())?;
             Value::from_js_value(self.clone(), val)
         })
     }
 
+    /// Evaluate a script with the properties of an object visible as variables.
+    ///
+    /// The script is evaluated like a direct `eval` within a `with (scope)` statement, so reading
+    /// a variable first looks it up on the scope object and assigning to it updates the property.
+    /// `this` is the scope object. Variables and functions declared by the script are local to
+    /// this evaluation, which keeps template or rule expressions from polluting the global
+    /// object. The script runs in sloppy mode, so assigning to an undeclared variable which isn't
+    /// a property of the scope still creates a global variable.
+    ///
+    /// Requires the `eval` intrinsic. Properties named `eval` and `arguments` on the scope are
+    /// not visible as variables, they would otherwise replace the function evaluating the script.
+    /// The wrapper doing the evaluation is compiled once per context and captures `eval` and
+    /// `Proxy` at that point, so scripts replacing the globals don't affect later evaluations.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let scope = Object::new(ctx.clone()).unwrap();
+    /// scope.set("price", 20).unwrap();
+    /// scope.set("quantity", 3).unwrap();
+    ///
+    /// let total: i32 = ctx.eval_with_scope("var total = price * quantity; total", &scope).unwrap();
+    /// assert_eq!(total, 60);
+    /// assert!(!ctx.globals().contains_key("total").unwrap());
+    /// # });
+    /// ```
+    pub fn eval_with_scope<V: FromJs<'js>>(&self, source: &str, scope: &Object<'js>) -> Result<V> {
+        // `eval` is a parameter so the call is a direct eval of the captured intrinsic, a proxy
+        // hides the names used by the wrapper from the scope.
+        const WRAPPER: &str = r#"
+            (function (eval, Proxy) {
+                return function () {
+                    with (new Proxy(this, {
+                        __proto__: null,
+                        has: function (target, key) {
+                            return key !== "eval" && key !== "arguments" && key in target;
+                        },
+                    })) {
+                        return eval(arguments[0]);
+                    }
+                };
+            })(eval, Proxy)
+        "#;
+        const KEY: &str = "evalWithScope";
+
+        let cache = super::ContextData::object(self);
+        let cached = match &cache {
+            Some(cache) => cache.get::<_, Option<Function>>(KEY)?,
+            None => None,
+        };
+        let wrapper = match cached {
+            Some(x) => x,
+            None => {
+                let wrapper: Function = self.eval_with_options(
+                    WRAPPER,
+                    EvalOptions {
+                        strict: false,
+                        ..Default::default()
+                    },
+                )?;
+                if let Some(cache) = &cache {
+                    cache.set(KEY, wrapper.clone())?;
+                }
+                wrapper
+            }
+        };
+        wrapper.call((This(scope.clone()), source))
+    }
+
+    /// Evaluate a script and capture its console output.
+    ///
+    /// While the script runs, the calls of the `console` installed by
+    /// [`web::install`](crate::web::install) in this context are recorded as lines instead of
+    /// being written to the standard output and error, with the arguments converted to strings
+    /// and separated by spaces. Nothing is replaced on the global object and other contexts are
+    /// unaffected, output of promise jobs or timers running later is not captured.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, web};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// web::install(&ctx).unwrap();
+    /// let (res, output) = ctx
+    ///     .eval_capture::<i32, _>("console.log('sum', 1 + 2); console.error({}); 42")
+    ///     .unwrap();
+    /// assert_eq!(res, 42);
+    /// assert_eq!(output, ["sum 3", "[object Object]"]);
+    /// # });
+    /// ```
+    #[cfg(feature = "web-minimal")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "web-minimal")))]
+    pub fn eval_capture<V: FromJs<'js>, S: Into<Vec<u8>>>(
+        &self,
+        source: S,
+    ) -> Result<(V, Vec<StdString>)> {
+        let data = unsafe { super::ContextData::get(self.as_ptr()) };
+        // Nested captures each collect their own lines.
+        let previous = data.map(|data| data.console_capture.replace(Some(Vec::new())));
+        let res = self.eval(source);
+        let output = data
+            .and_then(|data| data.console_capture.replace(previous.flatten()))
+            .unwrap_or_default();
+        Ok((res?, output))
+    }
+
     /// Evaluate a script directly from a file.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     pub fn eval_file<V: FromJs<'js>, P: AsRef<Path>>(&self, path: P) -> Result<V> {
         self.eval_file_with_options(path, Default::default())
     }
 
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     pub fn eval_file_with_options<V: FromJs<'js>, P: AsRef<Path>>(
         &self,
         path: P,
         options: EvalOptions,
     ) -> Result<V> {
         let buffer = fs::read(path.as_ref())?;
-        let file_name = CString::new(
-            path.as_ref()
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
-                .into_owned(),
-        )?;
+        let file_name = match options.filename {
+            Some(ref x) => CString::new(x.as_str())?,
+            None => CString::new(
+                path.as_ref()
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned(),
+            )?,
+        };
 
         V::from_js(self, unsafe {
             let val = self.eval_raw(buffer, file_name.as_c_str(), options.to_flag())?;
@@ -207,6 +410,45 @@ impl<'js> Ctx<'js> {
         })
     }
 
+    /// Evaluate a script, interrupting it if it runs for longer than the given timeout.
+    ///
+    /// Returns [`Error::Timeout`] if the script was interrupted. An interrupt handler set with
+    /// [`Runtime::set_interrupt_handler`](crate::Runtime::set_interrupt_handler) keeps being
+    /// called during the evaluation and the runtime is returned to its previous state afterwards,
+    /// also when called from within another `eval_timeout`.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use rquickjs::{Runtime, Context, Error};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let res = ctx.eval_timeout::<(), _>("while (true) {}", Duration::from_millis(10));
+    /// assert!(matches!(res, Err(Error::Timeout)));
+    /// # });
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+    pub fn eval_timeout<V: FromJs<'js>, S: Into<Vec<u8>>>(
+        &self,
+        source: S,
+        timeout: Duration,
+    ) -> Result<V> {
+        let res = {
+            let _guard = DeadlineGuard::new(self, Instant::now().checked_add(timeout));
+            self.eval(source)
+        };
+        let reached = unsafe { self.get_opaque().take_deadline_reached() };
+        match res {
+            Err(Error::Exception) if reached => {
+                // Clear the uncatchable interrupt exception.
+                self.catch();
+                Err(Error::Timeout)
+            }
+            res => res,
+        }
+    }
+
     /// Returns the global object of this context.
     pub fn globals(&self) -> Object<'js> {
         unsafe {
@@ -268,6 +510,70 @@ impl<'js> Ctx<'js> {
         }
     }
 
+    /// Evaluate a script and convert its result to a JSON value with `JSON.stringify`.
+    ///
+    /// Unlike [`Ctx::eval_into`] the result is converted like `JSON.stringify` does, so functions and `undefined` members
+    /// are left out and objects with a `toJSON` method are replaced by what it returns. A result
+    /// which has no JSON representation, like `undefined`, becomes `null`. Fails with an
+    /// exception if the result can't be stringified, like values with cycles or `BigInt`s.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let value = ctx.eval_json("({ name: 'rquickjs', tags: ['js', 'rust'] })").unwrap();
+    /// assert_eq!(value["tags"][1], "rust");
+    /// # });
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "serde")))]
+    pub fn eval_json<S: Into<Vec<u8>>>(&self, source: S) -> Result<serde_json::Value> {
+        let value: Value = self.eval(source)?;
+        let json = match self.json_stringify(value)? {
+            Some(json) => json.to_string()?,
+            None => return Ok(serde_json::Value::Null),
+        };
+        serde_json::from_str(&json).map_err(|error| Error::FromJs {
+            from: "string",
+            to: "serde_json::Value",
+            message: Some(error.to_string()),
+        })
+    }
+
+    /// Evaluate a script and deserialize its result into `T`.
+    ///
+    /// The result is deserialized directly, without going through JSON, and structurally like
+    /// the [`FromJs`] of `serde_json::Value`: `toJSON` isn't called, `undefined`, functions and
+    /// symbols are skipped in objects and are `null` elsewhere, like non-finite numbers unless
+    /// [`NumberConversion::reject_non_finite`] is set. Integers stored as floats deserialize as
+    /// integers. `null` and `undefined` deserialize as `None`, enum variants are strings or
+    /// objects with the variant as their only key. Nested values are subject to the same depth
+    /// and cycle checks as other conversions. Fails with [`Error::FromJs`] if the result doesn't
+    /// deserialize into `T`.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context};
+    /// # use std::collections::BTreeMap;
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let sizes: BTreeMap<String, u32> = ctx.eval_into("({ small: 1, large: 10 })").unwrap();
+    /// assert_eq!(sizes["large"], 10);
+    /// assert!(ctx.eval_into::<Vec<u32>, _>("'text'").is_err());
+    /// # });
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "serde")))]
+    pub fn eval_into<T, S>(&self, source: S) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        S: Into<Vec<u8>>,
+    {
+        let value: Value = self.eval(source)?;
+        crate::convert::from_value(self, value)
+    }
+
     /// Stringify a JavaScript value into its JSON representation
     pub fn json_stringify<V>(&self, value: V) -> Result<Option<String<'js>>>
     where
@@ -373,12 +679,217 @@ impl<'js> Ctx<'js> {
     /// Returns wether a job was actually executed.
     /// If this function returned false, no job was pending.
     pub fn execute_pending_job(&self) -> bool {
+        trace_span!("execute_pending_job");
         let mut ptr = MaybeUninit::<*mut qjs::JSContext>::uninit();
         let rt = unsafe { qjs::JS_GetRuntime(self.ctx.as_ptr()) };
         let res = unsafe { qjs::JS_ExecutePendingJob(rt, ptr.as_mut_ptr()) };
+        if res != 0 {
+            unsafe { record_metric(ptr.assume_init(), MetricsCounters::job_executed) };
+        }
         res != 0
     }
 
+    /// Block until the promise settles by running pending jobs, returning its result.
+    ///
+    /// This is meant for command line tools and tests which want to use promise based APIs
+    /// without an async runtime. In an [`AsyncRuntime`](crate::AsyncRuntime) the futures spawned
+    /// in the runtime are driven as well, blocking the thread while they are pending.
+    ///
+    /// Returns [`Error::WouldBlock`] if the job queue runs out of jobs and no spawned future is
+    /// left before the promise settles. This is also the case when called from within a spawned
+    /// future, which can't drive the other futures, use
+    /// [`Promise::into_future`](crate::Promise::into_future) there instead. Returns
+    /// [`Error::Timeout`] if the promise didn't settle within the
+    /// timeout of the options, a job which runs for too long is interrupted. If the promise
+    /// rejects, [`Error::Exception`] is returned with the rejection reason retrievable via
+    /// [`Ctx::catch`].
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use rquickjs::{Runtime, Context, Error, Promise, context::ResolveOptions};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let promise: Promise = ctx.eval("(async () => (await 20) + 22)()").unwrap();
+    /// let res: i32 = ctx.resolve_sync(promise, ResolveOptions::default()).unwrap();
+    /// assert_eq!(res, 42);
+    ///
+    /// let mut options = ResolveOptions::default();
+    /// options.timeout = Some(Duration::from_millis(10));
+    /// let promise: Promise = ctx.eval("Promise.resolve().then(() => { while (true) {} })").unwrap();
+    /// let res = ctx.resolve_sync::<()>(promise, options);
+    /// assert!(matches!(res, Err(Error::Timeout)));
+    /// # });
+    /// ```
+    pub fn resolve_sync<T: FromJs<'js>>(
+        &self,
+        promise: Promise<'js>,
+        options: ResolveOptions,
+    ) -> Result<T> {
+        #[cfg(feature = "std")]
+        let deadline = options.timeout.and_then(|x| Instant::now().checked_add(x));
+        #[cfg(feature = "std")]
+        let _guard = DeadlineGuard::new(self, deadline);
+        #[cfg(not(feature = "std"))]
+        let _ = options;
+        #[cfg(feature = "futures")]
+        let wake = crate::util::ThreadWake::new();
+        #[cfg(feature = "futures")]
+        let waker = core::task::Waker::from(wake.clone());
+        loop {
+            if let Some(res) = promise.result() {
+                return res;
+            }
+            let executed = self.execute_pending_job();
+            let reached = unsafe { self.get_opaque().take_deadline_reached() };
+            #[cfg(feature = "std")]
+            let passed = matches!(deadline, Some(x) if Instant::now() >= x);
+            #[cfg(not(feature = "std"))]
+            let passed = false;
+            if reached || passed {
+                if reached {
+                    // Clear the uncatchable interrupt exception.
+                    self.catch();
+                }
+                return Err(Error::Timeout);
+            }
+            if executed {
+                continue;
+            }
+            #[cfg(feature = "futures")]
+            {
+                use crate::runtime::schedular::SchedularPoll;
+
+                let opaque = unsafe { self.get_opaque() };
+                if opaque.can_poll() {
+                    match opaque.poll(&mut core::task::Context::from_waker(&waker)) {
+                        SchedularPoll::Empty => {}
+                        SchedularPoll::Pending => {
+                            wake.wait(deadline);
+                            continue;
+                        }
+                        SchedularPoll::ShouldYield | SchedularPoll::PendingProgress => continue,
+                    }
+                }
+            }
+            return Err(Error::WouldBlock);
+        }
+    }
+
+    /// Call a closure with the given options for converting numbers to Rust.
+    ///
+    /// The options of the runtime are restored once the closure returns, see [`NumberConversion`]
+    /// for an example.
+    pub fn with_number_conversion<F, R>(&self, options: NumberConversion, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        struct Restore<'a, 'js>(&'a Opaque<'js>, NumberConversion);
+
+        impl Drop for Restore<'_, '_> {
+            fn drop(&mut self) {
+                self.0.replace_number_conversion(self.1);
+            }
+        }
+
+        let opaque = unsafe { self.get_opaque() };
+        let _restore = Restore(opaque, opaque.replace_number_conversion(options));
+        f()
+    }
+
+    /// Set how much detail of the errors returned by Rust functions is shown to scripts running
+    /// in this context.
+    ///
+    /// Only the exceptions thrown into JavaScript are affected, the [`Error`] values seen by Rust
+    /// code keep their details and can be logged by the host. Useful when exposing internal
+    /// bindings to untrusted scripts.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Function, context::ErrorPolicy};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let f = Function::new(ctx.clone(), |_: Vec<u8>| {}).unwrap();
+    /// ctx.globals().set("f", f).unwrap();
+    /// let message = "try { f({}) } catch (e) { e.message }";
+    /// assert_eq!(
+    ///     ctx.eval::<String, _>(message).unwrap(),
+    ///     "argument 1: expected array, got object"
+    /// );
+    ///
+    /// ctx.set_error_policy(ErrorPolicy::Redacted);
+    /// assert_eq!(ctx.eval::<String, _>(message).unwrap(), "argument 1: unexpected object");
+    /// # });
+    /// ```
+    pub fn set_error_policy(&self, policy: ErrorPolicy) {
+        if let Some(data) = unsafe { super::ContextData::get(self.as_ptr()) } {
+            data.error_policy.set(policy);
+        }
+    }
+
+    /// Returns the error policy of this context.
+    pub fn error_policy(&self) -> ErrorPolicy {
+        unsafe { super::ContextData::get(self.as_ptr()) }
+            .map(|data| data.error_policy.get())
+            .unwrap_or_default()
+    }
+
+    /// Set whether [`FromJs`] conversions to strings, booleans and numbers in this context coerce
+    /// values of other types, see [`ConversionMode`].
+    pub fn set_conversion_mode(&self, mode: ConversionMode) {
+        if let Some(data) = unsafe { super::ContextData::get(self.as_ptr()) } {
+            data.conversion_mode.set(mode);
+        }
+    }
+
+    /// Returns the conversion mode of this context.
+    pub fn conversion_mode(&self) -> ConversionMode {
+        unsafe { super::ContextData::get(self.as_ptr()) }
+            .map(|data| data.conversion_mode.get())
+            .unwrap_or_default()
+    }
+
+    /// Limit the number of steps a single regular expression match in this context may take.
+    ///
+    /// A match which takes longer throws an `InternalError`, guarding against patterns with
+    /// catastrophic backtracking like `/(a+)+b/`. The steps are checked every 10000 steps, so a
+    /// match may overshoot the limit by that much. `None`, the default, removes the limit.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// ctx.set_regexp_step_limit(Some(1_000_000));
+    /// let message: String = ctx
+    ///     .eval("try { /(a+)+b/.test('a'.repeat(40)) } catch (e) { e.message }")
+    ///     .unwrap();
+    /// assert_eq!(message, "regular expression step limit exceeded");
+    /// # });
+    /// ```
+    #[cfg(not(feature = "system"))]
+    pub fn set_regexp_step_limit(&self, limit: Option<u64>) {
+        if let Some(data) = unsafe { super::ContextData::get(self.as_ptr()) } {
+            data.regexp_step_limit.set(limit);
+        }
+    }
+
+    /// Returns the regular expression step limit of this context.
+    #[cfg(not(feature = "system"))]
+    pub fn regexp_step_limit(&self) -> Option<u64> {
+        unsafe { super::ContextData::get(self.as_ptr()) }
+            .and_then(|data| data.regexp_step_limit.get())
+    }
+
+    /// Returns an owned handle to this context, `None` if it belongs to an async runtime.
+    pub(crate) fn context(&self) -> Option<Context> {
+        let rt = unsafe { self.get_opaque() }.runtime()?.try_ref()?;
+        unsafe {
+            let ctx = qjs::JS_DupContext(self.ctx.as_ptr());
+            Some(Context::from_raw(NonNull::new_unchecked(ctx), rt))
+        }
+    }
+
     pub(crate) unsafe fn get_opaque(&self) -> &Opaque<'js> {
         Opaque::from_runtime_ptr(qjs::JS_GetRuntime(self.ctx.as_ptr()))
     }
@@ -424,7 +935,7 @@ impl<'js> Ctx<'js> {
     /// name.
     /// Otherwise it will return none.
     pub fn script_or_module_name(&self, stack_level: isize) -> Option<Atom<'js>> {
-        let stack_level = std::os::raw::c_int::try_from(stack_level).unwrap();
+        let stack_level = core::ffi::c_int::try_from(stack_level).unwrap();
         let atom = unsafe { qjs::JS_GetScriptOrModuleName(self.as_ptr(), stack_level) };
         if qjs::__JS_ATOM_NULL as u32 == atom {
             unsafe { qjs::JS_FreeAtom(self.as_ptr(), atom) };
@@ -433,17 +944,233 @@ impl<'js> Ctx<'js> {
         unsafe { Some(Atom::from_atom_val(self.clone(), atom)) }
     }
 
+    /// Returns the handles to reference counted values of this context which are still alive,
+    /// together with where they were created.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let before = ctx.live_values().len();
+    /// let object = Object::new(ctx.clone()).unwrap();
+    /// assert_eq!(ctx.live_values().len(), before + 1);
+    /// drop(object);
+    /// assert_eq!(ctx.live_values().len(), before);
+    /// # });
+    /// ```
+    #[cfg(feature = "leak-detection")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-detection")))]
+    pub fn live_values(&self) -> Vec<crate::leak::LiveValue> {
+        unsafe { self.get_opaque().live_values(self.as_ptr()) }
+    }
+
+    /// Returns the reference count of a value and the handles holding references to it, `None`
+    /// if the value isn't reference counted.
+    ///
+    /// The references which aren't held by handles are held by scripts and the engine.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object, Persistent};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let object: Object = ctx.eval("globalThis.object = {}").unwrap();
+    /// let persistent = Persistent::save(&ctx, object.clone());
+    /// let references = ctx.value_references(&object).unwrap();
+    /// // `object` and the persistent handle, and the global variable.
+    /// assert_eq!(references.handles.len(), 2);
+    /// assert_eq!(references.engine(), 1);
+    /// # drop(persistent);
+    /// # });
+    /// ```
+    #[cfg(feature = "leak-detection")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-detection")))]
+    pub fn value_references(&self, value: &Value<'js>) -> Option<crate::leak::ValueReferences> {
+        unsafe { self.get_opaque().value_references(value.as_js_value()) }
+    }
+
+    /// Start recording every handle to the value being acquired or released, in any context of
+    /// the runtime.
+    ///
+    /// The events are returned by [`Ctx::take_handle_trace`], which should be called while the
+    /// value is alive, as the memory of a freed value can be reused by another one.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object, leak::HandleEventKind};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let object = Object::new(ctx.clone()).unwrap();
+    /// ctx.trace_handles(&object);
+    /// let clone = object.clone();
+    /// drop(clone);
+    /// let kinds: Vec<_> = ctx.take_handle_trace(&object).iter().map(|x| x.kind).collect();
+    /// assert_eq!(kinds, [HandleEventKind::Acquired, HandleEventKind::Released]);
+    /// # });
+    /// ```
+    #[cfg(feature = "leak-detection")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-detection")))]
+    pub fn trace_handles(&self, value: &Value<'js>) {
+        unsafe { self.get_opaque().trace_value(value.as_js_value()) }
+    }
+
+    /// Stop tracing a value and return the handles acquired and released since
+    /// [`Ctx::trace_handles`], in order.
+    #[cfg(feature = "leak-detection")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-detection")))]
+    pub fn take_handle_trace(&self, value: &Value<'js>) -> Vec<crate::leak::HandleEvent> {
+        unsafe { self.get_opaque().take_value_trace(value.as_js_value()) }
+    }
+
     /// Runs the quickjs garbage collector for a cycle.
     ///
     /// Quickjs uses reference counting with a collection cycle for cyclic references.
     /// This runs the cyclic reference collector cycle, types which are not part of a reference cycle
     /// will be freed the momement their reference count becomes zero.
     pub fn run_gc(&self) {
-        unsafe { qjs::JS_RunGC(qjs::JS_GetRuntime(self.ctx.as_ptr())) }
+        unsafe {
+            let rt = qjs::JS_GetRuntime(self.ctx.as_ptr());
+            qjs::JS_RunGC(rt);
+            // The bundled sources count collections themselves, see `metrics::count_gc`.
+            #[cfg(feature = "system")]
+            {
+                let mut usage = MaybeUninit::uninit();
+                qjs::JS_ComputeMemoryUsage(rt, usage.as_mut_ptr());
+                let metrics = self.get_opaque().metrics();
+                metrics.gc_run();
+                metrics.sample_memory(&usage.assume_init());
+            }
+        }
+    }
+
+    /// Returns the counters of the events which happened in this context.
+    ///
+    /// See [`Runtime::metrics`](crate::Runtime::metrics) for the counters of the whole runtime.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # let other = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| {
+    ///     ctx.eval::<(), _>("1 + 1").unwrap();
+    ///     assert_eq!(ctx.metrics().evaluations, 1);
+    /// });
+    /// other.with(|ctx| assert_eq!(ctx.metrics().evaluations, 0));
+    /// ```
+    pub fn metrics(&self) -> ContextMetrics {
+        unsafe { super::ContextData::get(self.as_ptr()) }
+            .map(|x| x.metrics.snapshot_context())
+            .unwrap_or_default()
+    }
+
+    /// Returns the memory attributed to this context.
+    ///
+    /// Unlike [`Context::memory_usage`] this uses the lock which is already held, so it can be
+    /// called from within [`Context::with`] and from Rust functions called by scripts.
+    ///
+    /// Returns `None` if the runtime wasn't created with
+    /// [`Runtime::new_with_accounting`](crate::Runtime::new_with_accounting).
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    #[cfg(feature = "allocator")]
+    pub fn memory_usage(&self) -> Option<crate::allocator::ContextMemoryUsage> {
+        let accounting = unsafe { self.get_opaque() }.accounting()?;
+        Some(accounting.usage(self.accounting_id()))
+    }
+
+    /// Limit the memory attributed to this context, in bytes.
+    ///
+    /// Allocations which would make the context exceed the limit fail, which throws a catchable
+    /// `out of memory` error in the context. Memory the context already holds is kept when the
+    /// limit is lowered below it. Calling with `None` removes the limit.
+    ///
+    /// Returns `false` if the runtime wasn't created with
+    /// [`Runtime::new_with_accounting`](crate::Runtime::new_with_accounting), which is required
+    /// to attribute memory to contexts.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, allocator::RustAllocator};
+    /// let rt = Runtime::new_with_accounting(RustAllocator).unwrap();
+    /// let ctx = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| {
+    ///     assert!(ctx.set_memory_limit(Some(4 * 1024 * 1024)));
+    ///     let caught: bool = ctx
+    ///         .eval("try { Array.from({ length: 1e6 }, String); false } catch { true }")
+    ///         .unwrap();
+    ///     assert!(caught);
+    /// });
+    /// ```
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    #[cfg(feature = "allocator")]
+    pub fn set_memory_limit(&self, limit: Option<usize>) -> bool {
+        let Some(accounting) = (unsafe { self.get_opaque() }).accounting() else {
+            return false;
+        };
+        accounting.set_memory_limit(self.accounting_id(), limit);
+        true
+    }
+
+    /// Limit the size of a single allocation made by this context, in bytes.
+    ///
+    /// Large strings, arrays and buffers are backed by a single allocation, so this guards
+    /// against scripts creating huge values like `"x".repeat(1e9)`. A refused allocation throws a
+    /// catchable `out of memory` error in the context. Calling with `None` removes the limit.
+    ///
+    /// Returns `false` if the runtime wasn't created with
+    /// [`Runtime::new_with_accounting`](crate::Runtime::new_with_accounting). See
+    /// [`LimitedAllocator`](crate::allocator::LimitedAllocator) for a limit of all contexts
+    /// without accounting.
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    #[cfg(feature = "allocator")]
+    pub fn set_max_allocation_size(&self, size: Option<usize>) -> bool {
+        let Some(accounting) = (unsafe { self.get_opaque() }).accounting() else {
+            return false;
+        };
+        accounting.set_max_allocation_size(self.accounting_id(), size);
+        true
+    }
+
+    /// Returns the id the allocations of this context are attributed to.
+    #[cfg(feature = "allocator")]
+    fn accounting_id(&self) -> usize {
+        unsafe { super::ContextData::accounting_id(self.as_ptr()) }
     }
 
     /// Store a type in the runtime which can be retrieved later with `Ctx::userdata`.
     ///
+    /// The storage is a type map, it holds at most one value of every type. It belongs to the
+    /// runtime so all contexts of a runtime share the same userdata, see
+    /// [`Ctx::store_context_userdata`] for state which is separate per context.
+    ///
+    /// This makes host state available inside native callbacks without global statics or
+    /// capturing it in every closure:
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Ctx, Function, JsLifetime};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// struct RequestInfo {
+    ///     path: String,
+    /// }
+    ///
+    /// unsafe impl<'js> JsLifetime<'js> for RequestInfo {
+    ///     type Changed<'to> = RequestInfo;
+    /// }
+    ///
+    /// ctx.with(|ctx| {
+    ///     ctx.store_userdata(RequestInfo { path: "/index".into() }).ok();
+    ///
+    ///     let path = Function::new(ctx.clone(), |ctx: Ctx| {
+    ///         ctx.userdata::<RequestInfo>().map(|x| x.path.clone())
+    ///     })
+    ///     .unwrap();
+    ///     ctx.globals().set("path", path).unwrap();
+    ///
+    ///     assert_eq!(ctx.eval::<String, _>("path()").unwrap(), "/index");
+    /// });
+    /// ```
+    ///
     /// Returns the value from the argument if the userdata is currently being accessed and
     /// insertion is not possible.
     /// Otherwise returns the exising value for this type if it existed.
@@ -478,6 +1205,180 @@ impl<'js> Ctx<'js> {
         unsafe { self.get_opaque().get_userdata() }
     }
 
+    /// Store a type in this context which can be retrieved later with
+    /// [`Ctx::context_userdata`].
+    ///
+    /// Like [`Ctx::store_userdata`] the storage is a type map, but every context has its own so
+    /// contexts of the same runtime can hold different values, like the information of the
+    /// request a context is handling. The userdata is dropped when the context is freed, since
+    /// the context can be freed at any point by the garbage collector, the type can't hold
+    /// JavaScript values.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, JsLifetime};
+    /// # let rt = Runtime::new().unwrap();
+    /// struct RequestInfo {
+    ///     path: String,
+    /// }
+    ///
+    /// unsafe impl<'js> JsLifetime<'js> for RequestInfo {
+    ///     type Changed<'to> = RequestInfo;
+    /// }
+    ///
+    /// let first = Context::full(&rt).unwrap();
+    /// let second = Context::full(&rt).unwrap();
+    /// first.with(|ctx| ctx.store_context_userdata(RequestInfo { path: "/a".into() }).ok());
+    /// second.with(|ctx| ctx.store_context_userdata(RequestInfo { path: "/b".into() }).ok());
+    /// first.with(|ctx| assert_eq!(ctx.context_userdata::<RequestInfo>().unwrap().path, "/a"));
+    /// ```
+    ///
+    /// Returns the value from the argument if the userdata is currently being accessed and
+    /// insertion is not possible, or if the context wasn't created by this crate.
+    /// Otherwise returns the exising value for this type if it existed.
+    pub fn store_context_userdata<U>(&self, data: U) -> StdResult<Option<Box<U>>, UserDataError<U>>
+    where
+        U: JsLifetime<'js> + 'static,
+        U::Changed<'static>: Any,
+    {
+        match unsafe { super::ContextData::get(self.as_ptr()) } {
+            Some(x) => x.userdata.insert(data),
+            None => Err(UserDataError(data)),
+        }
+    }
+
+    /// Remove the userdata of the given type from the userdata storage of this context.
+    ///
+    /// Returns Err(()) if the userdata is currently being accessed and removing isn't possible.
+    /// Returns Ok(None) if userdata of the given type wasn't inserted.
+    pub fn remove_context_userdata<U>(&self) -> StdResult<Option<Box<U>>, UserDataError<()>>
+    where
+        U: JsLifetime<'js> + 'static,
+        U::Changed<'static>: Any,
+    {
+        match unsafe { super::ContextData::get(self.as_ptr()) } {
+            Some(x) => x.userdata.remove(),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieves a borrow to the userdata of the given type from the userdata storage of this
+    /// context.
+    ///
+    /// Returns None if userdata of the given type wasn't inserted.
+    pub fn context_userdata<U>(&self) -> Option<UserDataGuard<'_, U>>
+    where
+        U: JsLifetime<'js> + 'static,
+        U::Changed<'static>: Any,
+    {
+        unsafe { super::ContextData::get(self.as_ptr()) }?
+            .userdata
+            .get()
+    }
+
+    /// Deep-freeze all the standard intrinsics of this context.
+    ///
+    /// Every object reachable from the global object, through its own properties, accessors and
+    /// prototype chain, is frozen and the bindings on the global object are made read-only.
+    /// This prevents untrusted scripts from polluting prototypes like `Object.prototype` or
+    /// `Array.prototype` which are shared with other scripts running in the same context.
+    ///
+    /// The global object itself is left extensible so scripts can still declare new globals.
+    /// Host values which are already present on the global object are frozen as well, so values
+    /// which should stay mutable must be injected after calling this function.
+    pub fn freeze_intrinsics(&self) -> Result<()> {
+        let globals = self.globals();
+        let mut visited = BTreeSet::new();
+        visited.insert(unsafe { globals.0.get_ptr() });
+
+        let mut pending = Vec::new();
+        let filter = Filter::new().string().symbol();
+        for key in globals.own_keys::<Atom>(filter) {
+            let key = key?;
+            let Some(desc) = globals.get_own_property(&key)? else {
+                continue;
+            };
+            let mut flags = (qjs::JS_PROP_THROW | qjs::JS_PROP_HAS_CONFIGURABLE) as qjs::c_int;
+            if !desc.is_accessor() {
+                flags |= qjs::JS_PROP_HAS_WRITABLE as qjs::c_int;
+            }
+            pending.extend(
+                [desc.value, desc.getter, desc.setter]
+                    .into_iter()
+                    .filter_map(Value::into_object),
+            );
+            unsafe {
+                if qjs::JS_DefineProperty(
+                    self.as_ptr(),
+                    globals.0.as_js_value(),
+                    key.atom,
+                    qjs::JS_UNDEFINED,
+                    qjs::JS_UNDEFINED,
+                    qjs::JS_UNDEFINED,
+                    flags,
+                ) < 0
+                {
+                    return Err(self.raise_exception());
+                }
+            }
+        }
+        pending.extend(self.hidden_intrinsics());
+
+        while let Some(obj) = pending.pop() {
+            if !visited.insert(unsafe { obj.0.get_ptr() }) {
+                continue;
+            }
+            obj.freeze()?;
+            pending.extend(obj.get_prototype());
+            for key in obj.own_keys::<Atom>(filter) {
+                if let Some(desc) = obj.get_own_property(&key?)? {
+                    pending.extend(
+                        [desc.value, desc.getter, desc.setter]
+                            .into_iter()
+                            .filter_map(Value::into_object),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect the intrinsics which are only reachable through syntax, like the prototypes of
+    /// generators and iterators.
+    fn hidden_intrinsics(&self) -> Vec<Object<'js>> {
+        const SOURCE: &[u8] = br#"
+            (() => {
+                const intrinsics = [];
+                const add = (f) => { try { intrinsics.push(Object.getPrototypeOf(f())); } catch (_) {} };
+                add(() => function* () {});
+                add(() => async function () {});
+                add(() => async function* () {});
+                add(() => [][Symbol.iterator]());
+                add(() => ""[Symbol.iterator]());
+                add(() => new Map()[Symbol.iterator]());
+                add(() => new Set()[Symbol.iterator]());
+                add(() => /a/[Symbol.matchAll](""));
+                return intrinsics;
+            })()
+        "#;
+        const FILE_NAME: &[u8] = b"freeze_intrinsics\0";
+        let file_name = CStr::from_bytes_with_nul(FILE_NAME).unwrap();
+        let res = unsafe { self.eval_raw(SOURCE, file_name, qjs::JS_EVAL_TYPE_GLOBAL as i32) };
+        match res {
+            Ok(val) => {
+                let val = unsafe { Value::from_js_value(self.clone(), val) };
+                val.into_array()
+                    .map(|array| array.iter::<Object>().filter_map(|x| x.ok()).collect())
+                    .unwrap_or_default()
+            }
+            Err(_) => {
+                // Eval is not available in this context, so neither is the syntax which could
+                // reach these intrinsics.
+                self.catch();
+                Vec::new()
+            }
+        }
+    }
+
     /// Returns the pointer to the C library context.
     pub fn as_raw(&self) -> NonNull<qjs::JSContext> {
         self.ctx
@@ -486,7 +1387,235 @@ impl<'js> Ctx<'js> {
 
 #[cfg(test)]
 mod test {
-    use crate::{CatchResultExt, JsLifetime};
+    use crate::{test_with, CatchResultExt, JsLifetime, StdString};
+
+    #[test]
+    fn eval_with_scope() {
+        use crate::Object;
+
+        test_with(|ctx| {
+            let scope = Object::new(ctx.clone()).unwrap();
+            scope.set("name", "world").unwrap();
+            scope.set("count", 1).unwrap();
+
+            let res: StdString = ctx
+                .eval_with_scope(
+                    "count += 1; function greet() { return 'hello ' + name } greet()",
+                    &scope,
+                )
+                .unwrap();
+            assert_eq!(res, "hello world");
+            assert_eq!(scope.get::<_, i32>("count").unwrap(), 2);
+            assert!(!ctx.globals().contains_key("greet").unwrap());
+
+            let this: Object = ctx.eval_with_scope("this", &scope).unwrap();
+            assert_eq!(this, scope);
+            let global: StdString = ctx.eval_with_scope("typeof Math.max", &scope).unwrap();
+            assert_eq!(global, "function");
+
+            assert!(ctx.eval_with_scope::<()>("missing", &scope).is_err());
+            assert!(ctx.catch().is_error());
+
+            scope.set("eval", "shadowed").unwrap();
+            scope.set("arguments", "shadowed").unwrap();
+            let res: i32 = ctx.eval_with_scope("count + 1", &scope).unwrap();
+            assert_eq!(res, 3);
+
+            // The wrapper captured the intrinsics on first use.
+            ctx.eval::<(), _>("globalThis.eval = () => 0; globalThis.Proxy = undefined")
+                .unwrap();
+            let res: i32 = ctx.eval_with_scope("count + 2", &scope).unwrap();
+            assert_eq!(res, 4);
+        })
+    }
+
+    #[cfg(feature = "web-minimal")]
+    #[test]
+    fn eval_capture() {
+        use crate::{Ctx, Function, Object};
+
+        test_with(|ctx| {
+            crate::web::install(&ctx).unwrap();
+            let console: Object = ctx.globals().get("console").unwrap();
+
+            let ((), output) = ctx
+                .eval_capture::<(), _>(
+                    "console.warn('a', 1, null); console.error(); console.assert(false, 'b')",
+                )
+                .unwrap();
+            assert_eq!(output, ["a 1 null", "", "Assertion failed: b"]);
+            assert_eq!(ctx.globals().get::<_, Object>("console").unwrap(), console);
+
+            ctx.globals()
+                .set(
+                    "nested",
+                    Function::new(ctx.clone(), |ctx: Ctx| {
+                        ctx.eval_capture::<(), _>("console.log('inner')")
+                            .map(|x| x.1)
+                    }),
+                )
+                .unwrap();
+            let (inner, outer) = ctx
+                .eval_capture::<Vec<StdString>, _>(
+                    "console.log('before'); const inner = nested(); console.log('after'); inner",
+                )
+                .unwrap();
+            assert_eq!(inner, ["inner"]);
+            assert_eq!(outer, ["before", "after"]);
+
+            assert!(ctx.eval_capture::<(), _>("throw 1").is_err());
+            ctx.catch();
+            let data = unsafe { super::super::ContextData::get(ctx.as_ptr()) }.unwrap();
+            assert!(data.console_capture.borrow().is_none());
+        })
+    }
+
+    #[test]
+    fn resolve_sync() {
+        use super::ResolveOptions;
+        use crate::{Error, Promise, Value};
+
+        test_with(|ctx| {
+            let promise: Promise = ctx
+                .eval("Promise.resolve().then(() => { throw 'failed' })")
+                .unwrap();
+            let res = ctx.resolve_sync::<()>(promise, ResolveOptions::default());
+            assert!(matches!(res, Err(Error::Exception)));
+            assert_eq!(
+                ctx.catch().as_string().unwrap().to_string().unwrap(),
+                "failed"
+            );
+
+            let (promise, _, _) = Promise::new(&ctx).unwrap();
+            let res = ctx.resolve_sync::<Value>(promise, ResolveOptions::default());
+            assert!(matches!(res, Err(Error::WouldBlock)));
+        })
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn resolve_sync_drives_futures() {
+        use super::ResolveOptions;
+        use crate::{async_with, function::Async, AsyncContext, AsyncRuntime, Function, Promise};
+        use std::{
+            future::Future,
+            pin::Pin,
+            task::{Context, Poll},
+        };
+
+        struct YieldOnce(bool);
+
+        impl Future for YieldOnce {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    return Poll::Ready(());
+                }
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+
+        let rt = AsyncRuntime::new().unwrap();
+        rt.block_on(async {
+            let ctx = AsyncContext::full(&rt).await.unwrap();
+            async_with!(ctx => |ctx| {
+                let later = Function::new(
+                    ctx.clone(),
+                    Async(|| async {
+                        YieldOnce(false).await;
+                        42
+                    }),
+                )
+                .unwrap();
+                ctx.globals().set("later", later).unwrap();
+                let promise: Promise = ctx.eval("later().then((x) => x + 1)").unwrap();
+                let res: i32 = ctx.resolve_sync(promise, ResolveOptions::default()).unwrap();
+                assert_eq!(res, 43);
+            })
+            .await
+        });
+    }
+
+    #[test]
+    fn eval_timeout() {
+        use crate::{Context, Error, Function, Runtime};
+        use std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            time::Duration,
+        };
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = calls.clone();
+        rt.set_interrupt_handler(Some(Box::new(move || {
+            handler_calls.fetch_add(1, Ordering::Relaxed);
+            false
+        })));
+
+        ctx.with(|ctx| {
+            let res = ctx.eval_timeout::<(), _>("while (true) {}", Duration::from_millis(20));
+            assert!(matches!(res, Err(Error::Timeout)));
+            assert!(calls.load(Ordering::Relaxed) > 0);
+
+            let res = ctx.eval_timeout::<i32, _>("1 + 1", Duration::from_secs(10));
+            assert_eq!(res.unwrap(), 2);
+            assert!(matches!(
+                ctx.eval_timeout::<(), _>("throw 1", Duration::from_secs(10)),
+                Err(Error::Exception)
+            ));
+            ctx.catch();
+
+            // A nested timeout can't outlive the enclosing one.
+            let nested = Function::new(ctx.clone(), |ctx: crate::Ctx| {
+                ctx.eval_timeout::<(), _>("while (true) {}", Duration::from_secs(3600))
+            })
+            .unwrap();
+            ctx.globals().set("nested", nested).unwrap();
+            let res = ctx.eval_timeout::<(), _>("nested()", Duration::from_millis(20));
+            assert!(matches!(res, Err(Error::Timeout)));
+
+            // The deadline is removed afterwards while the handler stays in place.
+            let before = calls.load(Ordering::Relaxed);
+            ctx.eval::<(), _>("for (let i = 0; i < 1000000; i++) {}")
+                .unwrap();
+            assert!(calls.load(Ordering::Relaxed) > before);
+        });
+    }
+
+    #[cfg(not(feature = "system"))]
+    #[test]
+    fn regexp_limits() {
+        use crate::{Context, Error, Runtime};
+        use std::time::Duration;
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let script = "/(a+)+b/.test('a'.repeat(40))";
+            let res = ctx.eval_timeout::<bool, _>(script, Duration::from_millis(20));
+            assert!(matches!(res, Err(Error::Timeout)));
+
+            ctx.set_regexp_step_limit(Some(100_000));
+            assert_eq!(ctx.regexp_step_limit(), Some(100_000));
+            assert!(ctx
+                .eval::<bool, _>("/(a+)+b/.test('a'.repeat(10) + 'b')")
+                .unwrap());
+            assert!(matches!(ctx.eval::<bool, _>(script), Err(Error::Exception)));
+            let error = ctx.catch();
+            let message: String = error.as_object().unwrap().get("message").unwrap();
+            assert_eq!(message, "regular expression step limit exceeded");
+
+            ctx.set_regexp_step_limit(None);
+            assert!(ctx.eval::<bool, _>("/(a+)+b/.test('aaab')").unwrap());
+        });
+    }
 
     #[test]
     fn exports() {
@@ -621,6 +1750,27 @@ mod test {
         })
     }
 
+    #[test]
+    fn eval_with_options_filename() {
+        use crate::{context::EvalOptions, Context, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            let options = EvalOptions {
+                filename: Some("tenant-a/main.js".into()),
+                ..Default::default()
+            };
+            let _ = ctx.eval_with_options::<(), _>("\nthrow new Error('oops')", options);
+            let error = ctx.catch().into_exception().unwrap();
+            assert_eq!(
+                error.position().unwrap().to_string(),
+                "tenant-a/main.js:2:7"
+            );
+            assert!(error.stack().unwrap().contains("tenant-a/main.js"));
+        })
+    }
+
     #[test]
     fn json_parse() {
         use crate::{Array, Context, Object, Runtime};
@@ -641,6 +1791,63 @@ mod test {
         })
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn eval_json() {
+        use crate::{Context, Error, Runtime};
+        use std::collections::HashMap;
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let value = ctx
+                .eval_json("({ a: [1, 2.5, null], b: { c: 'x', f() {}, u: undefined }, d: true })")
+                .unwrap();
+            assert_eq!(
+                value,
+                serde_json::json!({ "a": [1, 2.5, null], "b": { "c": "x" }, "d": true })
+            );
+            assert_eq!(ctx.eval_json("undefined").unwrap(), serde_json::Value::Null);
+            assert_eq!(
+                ctx.eval_json("({ toJSON: () => 'custom' })").unwrap(),
+                "custom"
+            );
+            assert!(matches!(ctx.eval_json("10n"), Err(Error::Exception)));
+            ctx.catch();
+
+            let res: HashMap<StdString, (u8, bool)> = ctx
+                .eval_into("({ one: [1, true], two: [2, false] })")
+                .unwrap();
+            assert_eq!(res["two"], (2, false));
+            let err = ctx.eval_into::<u8, _>("[1]").unwrap_err();
+            assert!(matches!(
+                err,
+                Error::FromJs {
+                    from: "array",
+                    to: "u8",
+                    ..
+                }
+            ));
+
+            // Deserialized without a JSON round trip.
+            let res: serde_json::Value = ctx.eval_into("({ toJSON: () => 'custom' })").unwrap();
+            assert_eq!(res, serde_json::json!({}));
+            assert_eq!(ctx.eval_into::<u64, _>("2 ** 40").unwrap(), 1 << 40);
+            assert_eq!(ctx.eval_into::<f64, _>("0.5").unwrap(), 0.5);
+            let res: Vec<Option<u8>> = ctx.eval_into("[1, undefined, () => 1, null]").unwrap();
+            assert_eq!(res, [Some(1), None, None, None]);
+            assert!(matches!(
+                ctx.eval_into::<u8, _>("1n"),
+                Err(Error::FromJs { .. })
+            ));
+            assert!(ctx.eval_into::<(u8, u8), _>("[1, 2, 3]").is_err());
+            let err = ctx
+                .eval_into::<HashMap<StdString, serde_json::Value>, _>("const a = {}; a.a = a; a")
+                .unwrap_err();
+            assert!(err.to_string().contains("reference cycle"));
+        })
+    }
+
     #[test]
     fn json_stringify() {
         use crate::{Array, Context, Object, Runtime};
@@ -671,6 +1878,44 @@ mod test {
         })
     }
 
+    #[test]
+    fn freeze_intrinsics() {
+        use crate::{Context, Object, Runtime};
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            ctx.freeze_intrinsics().unwrap();
+
+            let proto: Object = ctx.eval("Array.prototype").unwrap();
+            assert!(proto.is_frozen().unwrap());
+            assert!(ctx.eval::<(), _>("Object.prototype.polluted = 1").is_err());
+            assert!(ctx.eval::<(), _>("globalThis.Array = null").is_err());
+            assert!(ctx
+                .eval::<(), _>("Object.getPrototypeOf(function* () {}).prototype.next = null")
+                .is_err());
+            assert!(ctx
+                .eval::<(), _>("Object.getPrototypeOf([][Symbol.iterator]()).next = null")
+                .is_err());
+
+            let res: i32 = ctx.eval("globalThis.user = 1; user + 1").unwrap();
+            assert_eq!(res, 2);
+        })
+    }
+
+    #[test]
+    fn freeze_intrinsics_without_eval() {
+        use crate::{context::intrinsic, Context, Object, Runtime};
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::custom::<intrinsic::Json>(&rt).unwrap();
+        ctx.with(|ctx| {
+            ctx.freeze_intrinsics().unwrap();
+            let json: Object = ctx.globals().get("JSON").unwrap();
+            assert!(json.is_frozen().unwrap());
+        })
+    }
+
     #[test]
     fn userdata() {
         use crate::{Context, Function, Runtime};
@@ -704,4 +1949,164 @@ mod test {
             ctx.remove_userdata::<MyUserData>().unwrap().unwrap();
         })
     }
+
+    #[test]
+    fn context_userdata() {
+        use crate::{Context, Runtime};
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        struct Request(usize, Arc<AtomicUsize>);
+
+        unsafe impl<'js> JsLifetime<'js> for Request {
+            type Changed<'to> = Request;
+        }
+
+        impl Drop for Request {
+            fn drop(&mut self) {
+                self.1.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let rt = Runtime::new().unwrap();
+        let first = Context::full(&rt).unwrap();
+        let second = Context::full(&rt).unwrap();
+
+        first.with(|ctx| {
+            let request = Request(1, dropped.clone());
+            assert!(ctx.store_context_userdata(request).unwrap().is_none());
+        });
+        second.with(|ctx| {
+            assert!(ctx.context_userdata::<Request>().is_none());
+            let request = Request(2, dropped.clone());
+            assert!(ctx.store_context_userdata(request).unwrap().is_none());
+            let userdata = ctx.context_userdata::<Request>().unwrap();
+            assert!(ctx.remove_context_userdata::<Request>().is_err());
+            assert_eq!(userdata.0, 2);
+        });
+        first.with(|ctx| assert_eq!(ctx.context_userdata::<Request>().unwrap().0, 1));
+
+        second.with(|ctx| {
+            ctx.remove_context_userdata::<Request>().unwrap().unwrap();
+        });
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+        drop(first);
+        rt.run_gc();
+        assert_eq!(dropped.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn conversion_mode_is_per_context() {
+        use crate::{convert::ConversionMode, Context, Function, Runtime};
+
+        let rt = Runtime::new().unwrap();
+        let lenient = Context::full(&rt).unwrap();
+        let strict = Context::full(&rt).unwrap();
+
+        lenient.with(|ctx| {
+            ctx.set_conversion_mode(ConversionMode::Lenient);
+            assert_eq!(ctx.conversion_mode(), ConversionMode::Lenient);
+            assert_eq!(ctx.eval::<StdString, _>("null").unwrap(), "null");
+            assert_eq!(ctx.eval::<StdString, _>("1.5").unwrap(), "1.5");
+            assert!(ctx.eval::<bool, _>("'false'").unwrap());
+            assert_eq!(ctx.eval::<u8, _>("' 7 '").unwrap(), 7);
+            assert_eq!(ctx.eval::<i32, _>("'x'").unwrap(), 0);
+            assert!(ctx.eval::<u8, _>("'300'").is_err());
+            // Objects aren't coerced, so converting never runs scripts.
+            let called = "globalThis.called = false; ({ toString() { called = true; return '' } })";
+            assert!(ctx.eval::<StdString, _>(called).unwrap_err().is_from_js());
+            assert!(ctx.eval::<f64, _>("[1]").unwrap_err().is_from_js());
+            assert!(!ctx.eval::<bool, _>("called").unwrap());
+            assert!(ctx.eval::<StdString, _>("Symbol()").is_err());
+            // Both variants are tried strictly before coercing.
+            #[cfg(feature = "either")]
+            {
+                use either::{Either, Left, Right};
+                let value = ctx.eval::<Either<i32, StdString>, _>("'x'").unwrap();
+                assert_eq!(value, Right("x".into()));
+                let value = ctx.eval::<Either<StdString, i32>, _>("1").unwrap();
+                assert_eq!(value, Right(1));
+                let value = ctx.eval::<Either<i32, Vec<i32>>, _>("'2'").unwrap();
+                assert_eq!(value, Left(2));
+                assert_eq!(ctx.conversion_mode(), ConversionMode::Lenient);
+            }
+            let f =
+                Function::new(ctx.clone(), |x: f64, flag: bool| if flag { x } else { -x }).unwrap();
+            ctx.globals().set("f", f).unwrap();
+            assert_eq!(ctx.eval::<f64, _>("f('2', 1)").unwrap(), 2.0);
+        });
+        strict.with(|ctx| {
+            assert_eq!(ctx.conversion_mode(), ConversionMode::Strict);
+            assert!(ctx.eval::<StdString, _>("1").is_err());
+            assert!(ctx.eval::<bool, _>("1").is_err());
+            assert!(ctx.eval::<f64, _>("'1'").is_err());
+        });
+        lenient.with(|ctx| {
+            ctx.set_conversion_mode(ConversionMode::Strict);
+            assert!(ctx.eval::<i32, _>("'1'").is_err());
+        });
+    }
+
+    #[test]
+    fn error_policy_is_per_context() {
+        use crate::{
+            context::ErrorPolicy, runtime::PanicAction, Context, Error, Function, Runtime,
+        };
+
+        let rt = Runtime::new().unwrap();
+        let redacted = Context::full(&rt).unwrap();
+        let detailed = Context::full(&rt).unwrap();
+        let script = r#"
+            const error = (f) => { try { f() } catch (e) { return `${e.name}: ${e.message}` } };
+            [error(() => f("x")), error(() => g()), error(() => h())]
+        "#;
+        let define = |ctx: &crate::Ctx| {
+            let f = Function::new(ctx.clone(), |_: i32| {}).unwrap();
+            ctx.globals().set("f", f).unwrap();
+            let g = Function::new(ctx.clone(), || -> crate::Result<()> {
+                Err(std::io::Error::new(std::io::ErrorKind::NotFound, "/etc/secret").into())
+            })
+            .unwrap();
+            ctx.globals().set("g", g).unwrap();
+            let h = Function::new(ctx.clone(), || -> () { panic!("/etc/secret") }).unwrap();
+            ctx.globals().set("h", h).unwrap();
+        };
+        rt.set_panic_hook(Some(Box::new(|_| PanicAction::Throw)));
+
+        redacted.with(|ctx| {
+            define(&ctx);
+            ctx.set_error_policy(ErrorPolicy::Redacted);
+            assert_eq!(ctx.error_policy(), ErrorPolicy::Redacted);
+            let res: Vec<StdString> = ctx.eval(script).unwrap();
+            assert_eq!(
+                res,
+                [
+                    "TypeError: argument 1: unexpected string",
+                    "Error: IO Error",
+                    "InternalError: panicked"
+                ]
+            );
+            let err = Error::from(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "/etc/secret",
+            ));
+            assert!(err.to_string().contains("/etc/secret"));
+        });
+        detailed.with(|ctx| {
+            define(&ctx);
+            assert_eq!(ctx.error_policy(), ErrorPolicy::Detailed);
+            let res: Vec<StdString> = ctx.eval(script).unwrap();
+            assert_eq!(
+                res,
+                [
+                    "TypeError: argument 1: expected i32, got string",
+                    "Error: IO Error: /etc/secret",
+                    "InternalError: panicked: /etc/secret"
+                ]
+            );
+        });
+    }
 }
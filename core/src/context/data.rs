@@ -0,0 +1,108 @@
+use alloc::boxed::Box;
+#[cfg(feature = "web-minimal")]
+use core::cell::RefCell;
+use core::{cell::Cell, ptr::NonNull};
+
+#[cfg(feature = "web-minimal")]
+use crate::StdString;
+use crate::{
+    qjs,
+    runtime::{opaque::Opaque, MetricsCounters, UserDataMap},
+    Ctx, Object, Value,
+};
+
+use super::ErrorPolicy;
+use crate::convert::ConversionMode;
+
+/// Rust book keeping data of a single context.
+///
+/// The data is owned by an object of a private class which is set as the prototype of that class
+/// in the context. QuickJS frees the prototypes together with the context, so the data lives
+/// exactly as long as the context, however many references to it exist.
+#[derive(Default)]
+pub(crate) struct ContextData {
+    /// The id the allocations of the context are attributed to, `0` without memory accounting.
+    #[cfg(feature = "allocator")]
+    pub accounting_id: usize,
+    /// The counters of the events which happened in the context.
+    pub metrics: MetricsCounters,
+    /// The userdata stored with `Ctx::store_context_userdata`.
+    pub userdata: UserDataMap,
+    /// How much detail of Rust errors is shown to scripts, set with `Ctx::set_error_policy`.
+    pub error_policy: Cell<ErrorPolicy>,
+    /// Whether `FromJs` coerces primitives, set with `Ctx::set_conversion_mode`.
+    pub conversion_mode: Cell<ConversionMode>,
+    /// The steps a single regular expression match may take, set with
+    /// `Ctx::set_regexp_step_limit`.
+    #[cfg(not(feature = "system"))]
+    pub regexp_step_limit: Cell<Option<u64>>,
+    /// The lines written by the web console while `Ctx::eval_capture` runs.
+    #[cfg(feature = "web-minimal")]
+    pub console_capture: RefCell<Option<Vec<StdString>>>,
+}
+
+impl ContextData {
+    /// Attach the data to a newly created context.
+    pub unsafe fn install(ctx: *mut qjs::JSContext, data: ContextData) {
+        let rt = qjs::JS_GetRuntime(ctx);
+        let class_id = Opaque::from_runtime_ptr(rt).get_context_data_class_id();
+        let object = qjs::JS_NewObjectClass(ctx, class_id as _);
+        if qjs::JS_IsException(object) {
+            // Out of memory, the context works without its data.
+            qjs::JS_FreeValue(ctx, qjs::JS_GetException(ctx));
+            return;
+        }
+        let data = Box::into_raw(Box::new(data));
+        qjs::JS_SetOpaque(object, data.cast());
+        qjs::JS_SetContextOpaque(ctx, data.cast());
+        // Takes ownership of the object.
+        qjs::JS_SetClassProto(ctx, class_id, object);
+    }
+
+    /// Returns the data of a context, `None` for contexts created outside of rquickjs.
+    pub unsafe fn get<'a>(ctx: *mut qjs::JSContext) -> Option<&'a ContextData> {
+        let data = NonNull::new(qjs::JS_GetContextOpaque(ctx))?;
+        Some(data.cast::<ContextData>().as_ref())
+    }
+
+    /// Returns the object owning the data of a context.
+    ///
+    /// Scripts can't reach the object, so it is used to cache values per context. Values stored
+    /// on it are traced by the garbage collector like the other prototypes of the context.
+    pub fn object<'js>(ctx: &Ctx<'js>) -> Option<Object<'js>> {
+        unsafe {
+            let class_id = ctx.get_opaque().get_context_data_class_id();
+            let proto = qjs::JS_GetClassProto(ctx.as_ptr(), class_id);
+            Value::from_js_value(ctx.clone(), proto).into_object()
+        }
+    }
+
+    /// Returns the id the allocations of a context are attributed to.
+    #[cfg(feature = "allocator")]
+    pub unsafe fn accounting_id(ctx: *mut qjs::JSContext) -> usize {
+        Self::get(ctx).map(|x| x.accounting_id).unwrap_or(0)
+    }
+}
+
+/// Frees the data of a context once its owning object is freed.
+pub(crate) unsafe extern "C" fn context_data_finalizer(rt: *mut qjs::JSRuntime, val: qjs::JSValue) {
+    let class_id = Opaque::from_runtime_ptr(rt).get_context_data_class_id();
+    let data = qjs::JS_GetOpaque(val, class_id);
+    if !data.is_null() {
+        drop(Box::from_raw(data.cast::<ContextData>()));
+    }
+}
+
+/// Aborts a regular expression match which took more steps than the limit of its context.
+#[cfg(not(feature = "system"))]
+pub(crate) unsafe fn regexp_poll(ctx: *mut qjs::JSContext, steps: u64) -> bool {
+    let Some(limit) = ContextData::get(ctx).and_then(|data| data.regexp_step_limit.get()) else {
+        return false;
+    };
+    if steps <= limit {
+        return false;
+    }
+    let ctx = Ctx::from_ptr(ctx);
+    crate::Exception::throw_internal(&ctx, "regular expression step limit exceeded");
+    true
+}
@@ -0,0 +1,383 @@
+use crate::{
+    object::Filter, qjs, Atom, Context, Ctx, JsLifetime, Object, Persistent, Result, Runtime, Value,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{cell::RefCell, ops::Deref};
+
+/// A pool of contexts for running many short, unrelated scripts.
+///
+/// Creating a context with all the intrinsics is expensive compared to running a small script.
+/// A pool keeps contexts around after use: a context is taken out with
+/// [`ContextPool::checkout`] and returned with [`ContextPool::checkin`], or by dropping the
+/// [`PooledContext`].
+///
+/// When a context is returned the own properties of its global object are reset to their state
+/// when the context was created: added properties are removed and changed or removed ones are
+/// restored. A context for which this isn't possible, for example because a script declared a
+/// global `var`, is discarded instead of being reused.
+///
+/// State which isn't stored in own properties of the global object can't be reset. The intrinsics
+/// like `Array.prototype` are frozen with [`Ctx::freeze_intrinsics`] when a context is created,
+/// unless disabled with [`ContextPool::with_frozen_intrinsics`]. A context is discarded if a
+/// global script declared a top-level `let`, `const` or `class`, or if jobs like promise
+/// reactions are still pending in the runtime when it is returned.
+///
+/// Not available with the `system` feature since top-level declarations can only be counted with
+/// the bundled sources.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, context::ContextPool};
+/// let rt = Runtime::new().unwrap();
+/// let pool = ContextPool::new(&rt, |rt| Context::full(rt));
+///
+/// let ctx = pool.checkout().unwrap();
+/// ctx.with(|ctx| ctx.eval::<(), _>("globalThis.secret = 42").unwrap());
+/// pool.checkin(ctx).unwrap();
+///
+/// let ctx = pool.checkout().unwrap();
+/// let secret: Option<i32> = ctx.with(|ctx| ctx.eval("globalThis.secret").unwrap());
+/// assert_eq!(secret, None);
+/// ```
+pub struct ContextPool {
+    idle: RefCell<Vec<Pooled>>,
+    max_idle: usize,
+    freeze_intrinsics: bool,
+    create: CreateContext,
+    runtime: Runtime,
+}
+
+type CreateContext = Box<dyn Fn(&Runtime) -> Result<Context>>;
+
+struct Pooled {
+    context: Context,
+    globals: Persistent<Vec<GlobalProperty<'static>>>,
+    /// The number of global lexical declarations when the context was created.
+    lexicals: qjs::c_int,
+}
+
+/// An own property of the global object as it was when the context was created.
+struct GlobalProperty<'js> {
+    key: Atom<'js>,
+    flags: qjs::c_int,
+    value: Value<'js>,
+    getter: Value<'js>,
+    setter: Value<'js>,
+}
+
+unsafe impl<'js> JsLifetime<'js> for GlobalProperty<'js> {
+    type Changed<'to> = GlobalProperty<'to>;
+}
+
+impl ContextPool {
+    /// The default number of unused contexts kept by a pool.
+    pub const DEFAULT_MAX_IDLE: usize = 16;
+
+    /// Create a new pool of contexts of a runtime.
+    ///
+    /// The given function is used to create new contexts, it can set up the globals which should
+    /// be available to every script.
+    pub fn new<F>(runtime: &Runtime, create: F) -> Self
+    where
+        F: Fn(&Runtime) -> Result<Context> + 'static,
+    {
+        ContextPool {
+            idle: RefCell::new(Vec::new()),
+            max_idle: Self::DEFAULT_MAX_IDLE,
+            freeze_intrinsics: true,
+            create: Box::new(create),
+            runtime: runtime.clone(),
+        }
+    }
+
+    /// Set the maximum number of unused contexts kept by the pool.
+    ///
+    /// Contexts returned to a full pool are dropped.
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self.idle.get_mut().truncate(max_idle);
+        self
+    }
+
+    /// Set whether the intrinsics of new contexts are frozen, which is the default.
+    ///
+    /// Changes scripts make to the intrinsics of a context which isn't frozen are carried over to
+    /// the next user of the context.
+    pub fn with_frozen_intrinsics(mut self, freeze: bool) -> Self {
+        self.freeze_intrinsics = freeze;
+        self
+    }
+
+    /// Returns the runtime the contexts of this pool belong to.
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// Returns the number of unused contexts currently in the pool.
+    pub fn idle(&self) -> usize {
+        self.idle.borrow().len()
+    }
+
+    /// Take a context out of the pool, creating a new one if the pool is empty.
+    pub fn checkout(&self) -> Result<PooledContext<'_>> {
+        let pooled = self.idle.borrow_mut().pop();
+        let pooled = match pooled {
+            Some(x) => x,
+            None => {
+                let context = (self.create)(&self.runtime)?;
+                let (globals, lexicals) = context.with(|ctx| {
+                    if self.freeze_intrinsics {
+                        ctx.freeze_intrinsics()?;
+                    }
+                    let globals = Persistent::save(&ctx, snapshot(&ctx.globals())?);
+                    let lexicals = unsafe { qjs::rquickjs_global_lexical_count(ctx.as_ptr()) };
+                    Ok::<_, crate::Error>((globals, lexicals))
+                })?;
+                Pooled {
+                    context,
+                    globals,
+                    lexicals,
+                }
+            }
+        };
+        Ok(PooledContext {
+            pool: self,
+            pooled: Some(pooled),
+        })
+    }
+
+    /// Reset a context and return it to the pool.
+    ///
+    /// Returns an error if the globals of the context could not be reset. The context is dropped
+    /// in that case, as well as when it holds state which can't be reset or the pool is full.
+    pub fn checkin(&self, mut context: PooledContext<'_>) -> Result<()> {
+        let pooled = context.pooled.take().expect("context was already returned");
+        self.release(pooled)
+    }
+
+    /// Return a context to the pool, every early return drops the context.
+    fn release(&self, pooled: Pooled) -> Result<()> {
+        let Pooled {
+            context,
+            globals,
+            lexicals,
+        } = pooled;
+        if self.runtime.is_job_pending() {
+            // The jobs could still change the context after it was reset.
+            return Ok(());
+        }
+        let globals = context.with(|ctx| {
+            let current = unsafe { qjs::rquickjs_global_lexical_count(ctx.as_ptr()) };
+            if current != lexicals {
+                return Ok(None);
+            }
+            let snapshot = globals.restore(&ctx)?;
+            reset(&ctx, &ctx.globals(), &snapshot)?;
+            Ok::<_, crate::Error>(Some(Persistent::save(&ctx, snapshot)))
+        })?;
+        let Some(globals) = globals else {
+            return Ok(());
+        };
+        let mut idle = self.idle.borrow_mut();
+        if idle.len() < self.max_idle {
+            idle.push(Pooled {
+                context,
+                globals,
+                lexicals,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A context taken out of a [`ContextPool`], returned to the pool when dropped.
+pub struct PooledContext<'a> {
+    pool: &'a ContextPool,
+    pooled: Option<Pooled>,
+}
+
+impl Deref for PooledContext<'_> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        &self.pooled.as_ref().unwrap().context
+    }
+}
+
+impl Drop for PooledContext<'_> {
+    fn drop(&mut self) {
+        if let Some(pooled) = self.pooled.take() {
+            // `release` drops a context which couldn't be reset, only the error is lost here.
+            let _ = self.pool.release(pooled);
+        }
+    }
+}
+
+/// Record all own properties of the global object.
+fn snapshot<'js>(globals: &Object<'js>) -> Result<Vec<GlobalProperty<'js>>> {
+    let mut properties = Vec::new();
+    for key in globals.own_keys::<Atom>(Filter::new().string().symbol()) {
+        let key = key?;
+        if let Some(desc) = globals.get_own_property(&key)? {
+            properties.push(GlobalProperty {
+                key,
+                flags: desc.flags,
+                value: desc.value,
+                getter: desc.getter,
+                setter: desc.setter,
+            });
+        }
+    }
+    Ok(properties)
+}
+
+/// Remove the own properties of the global object which aren't in the snapshot and restore the
+/// ones which are.
+fn reset<'js>(
+    ctx: &Ctx<'js>,
+    globals: &Object<'js>,
+    snapshot: &[GlobalProperty<'js>],
+) -> Result<()> {
+    for key in globals.own_keys::<Atom>(Filter::new().string().symbol()) {
+        let key = key?;
+        if !snapshot.iter().any(|x| x.key == key) {
+            globals.remove(key)?;
+        }
+    }
+    for property in snapshot {
+        let unchanged = match globals.get_own_property(&property.key)? {
+            Some(desc) => {
+                desc.flags == property.flags
+                    && desc.value.same_value(&property.value)
+                    && desc.getter.same_value(&property.getter)
+                    && desc.setter.same_value(&property.setter)
+            }
+            None => false,
+        };
+        if unchanged {
+            continue;
+        }
+        let flags = property.flags as u32
+            | qjs::JS_PROP_HAS_CONFIGURABLE
+            | qjs::JS_PROP_HAS_ENUMERABLE
+            | qjs::JS_PROP_THROW;
+        let flags = if flags & qjs::JS_PROP_GETSET != 0 {
+            flags | qjs::JS_PROP_HAS_GET | qjs::JS_PROP_HAS_SET
+        } else {
+            flags | qjs::JS_PROP_HAS_VALUE | qjs::JS_PROP_HAS_WRITABLE
+        };
+        let res = unsafe {
+            qjs::JS_DefineProperty(
+                ctx.as_ptr(),
+                globals.as_js_value(),
+                property.key.atom,
+                property.value.as_js_value(),
+                property.getter.as_js_value(),
+                property.setter.as_js_value(),
+                flags as _,
+            )
+        };
+        if res < 0 {
+            return Err(ctx.raise_exception());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{context::ContextPool, *};
+
+    #[test]
+    fn reset_globals() {
+        let rt = Runtime::new().unwrap();
+        let pool = ContextPool::new(&rt, |rt| {
+            let context = Context::full(rt)?;
+            context.with(|ctx| ctx.globals().set("config", "default"))?;
+            Ok(context)
+        })
+        .with_max_idle(1)
+        .with_frozen_intrinsics(false);
+
+        let ctx = pool.checkout().unwrap();
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                globalThis.leaked = 1;
+                globalThis[Symbol.for("leaked")] = 2;
+                config = "changed";
+                delete globalThis.JSON;
+                Object.defineProperty(globalThis, "Math", { get() { return 3 } });
+            "#,
+            )
+            .unwrap();
+        });
+        pool.checkin(ctx).unwrap();
+        assert_eq!(pool.idle(), 1);
+
+        let ctx = pool.checkout().unwrap();
+        assert_eq!(pool.idle(), 0);
+        ctx.with(|ctx| {
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"[
+                    typeof leaked,
+                    typeof globalThis[Symbol.for("leaked")],
+                    config,
+                    typeof JSON.parse,
+                    typeof Math.max,
+                ]"#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                ["undefined", "undefined", "default", "function", "function"]
+            );
+
+            // `var` declarations can't be deleted.
+            ctx.eval::<(), _>("var declared = 1").unwrap();
+        });
+        assert!(pool.checkin(ctx).is_err());
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn discard_unresettable() {
+        let rt = Runtime::new().unwrap();
+        let pool = ContextPool::new(&rt, Context::full);
+
+        let ctx = pool.checkout().unwrap();
+        ctx.with(|ctx| {
+            let res = ctx.eval::<(), _>("Array.prototype.push = () => 0");
+            assert!(res.is_err());
+            ctx.catch();
+        });
+        pool.checkin(ctx).unwrap();
+        assert_eq!(pool.idle(), 1);
+
+        let ctx = pool.checkout().unwrap();
+        ctx.with(|ctx| ctx.eval::<(), _>("let lexical = 1").unwrap());
+        pool.checkin(ctx).unwrap();
+        assert_eq!(pool.idle(), 0);
+
+        let ctx = pool.checkout().unwrap();
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>("Promise.resolve().then(() => { globalThis.late = 1 })")
+                .unwrap()
+        });
+        pool.checkin(ctx).unwrap();
+        assert_eq!(pool.idle(), 0);
+        while rt.execute_pending_job().unwrap() {}
+
+        let ctx = pool.checkout().unwrap();
+        ctx.with(|ctx| ctx.eval::<(), _>("var declared = 1").unwrap());
+        drop(ctx);
+        assert_eq!(pool.idle(), 0);
+
+        let ctx = pool.checkout().unwrap();
+        let res: Vec<StdString> = ctx.with(|ctx| {
+            ctx.eval("[typeof lexical, typeof late, typeof declared]")
+                .unwrap()
+        });
+        assert_eq!(res, ["undefined", "undefined", "undefined"]);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{Ctx, IntoJs, Object, Result};
+
+/// A Rust enum without fields which is exposed to JavaScript as a set of named constants.
+///
+/// The variants convert to JavaScript as numbers or strings and [`JsEnum::namespace`] creates a
+/// frozen object with a property for every variant, which can be exported from a module or set as
+/// a static property of a class. For numeric enums the object also maps every value back to its
+/// name, like TypeScript enums do.
+///
+/// This trait, together with [`IntoJs`] and [`FromJs`](crate::FromJs), can be derived with
+/// `#[derive(JsEnum)]`.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Ctx, IntoJs, JsEnum, Result, Value};
+/// #[derive(Clone, Copy)]
+/// enum Color {
+///     Red,
+///     Green,
+/// }
+///
+/// impl<'js> IntoJs<'js> for Color {
+///     fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+///         (self as i32).into_js(ctx)
+///     }
+/// }
+///
+/// impl JsEnum for Color {
+///     const NAME: &'static str = "Color";
+///     const VARIANTS: &'static [(&'static str, Self)] = &[("RED", Color::Red), ("GREEN", Color::Green)];
+/// }
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     ctx.globals().set(Color::NAME, Color::namespace(&ctx).unwrap()).unwrap();
+///     assert!(ctx.eval::<bool, _>("Color.GREEN === 1 && Color[1] === 'GREEN'").unwrap());
+///     assert!(ctx.eval::<bool, _>("Object.isFrozen(Color)").unwrap());
+/// });
+/// ```
+pub trait JsEnum: Copy + for<'js> IntoJs<'js> + 'static {
+    /// The JavaScript name of the enum.
+    const NAME: &'static str;
+
+    /// The JavaScript name of every variant together with the variant.
+    const VARIANTS: &'static [(&'static str, Self)];
+
+    /// Create the frozen object holding the constants of the enum.
+    fn namespace<'js>(ctx: &Ctx<'js>) -> Result<Object<'js>> {
+        let object = Object::new(ctx.clone())?;
+        for (name, variant) in Self::VARIANTS {
+            let value = variant.into_js(ctx)?;
+            if value.is_number() {
+                object.set(value.clone(), *name)?;
+            }
+            object.set(*name, value)?;
+        }
+        object.freeze()?;
+        Ok(object)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Level {
+        Low,
+        High,
+    }
+
+    impl<'js> IntoJs<'js> for Level {
+        fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+            match self {
+                Level::Low => "low",
+                Level::High => "high",
+            }
+            .into_js(ctx)
+        }
+    }
+
+    impl JsEnum for Level {
+        const NAME: &'static str = "Level";
+        const VARIANTS: &'static [(&'static str, Self)] =
+            &[("LOW", Level::Low), ("HIGH", Level::High)];
+    }
+
+    #[test]
+    fn string_namespace() {
+        test_with(|ctx| {
+            ctx.globals()
+                .set(Level::NAME, Level::namespace(&ctx).unwrap())
+                .unwrap();
+            let res: StdString = ctx.eval("Level.HIGH").unwrap();
+            assert_eq!(res, "high");
+            let res: Vec<StdString> = ctx.eval("Object.keys(Level)").unwrap();
+            assert_eq!(res, ["LOW", "HIGH"]);
+            assert!(ctx.eval::<bool, _>("Object.isFrozen(Level)").unwrap());
+        })
+    }
+}
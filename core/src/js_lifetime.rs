@@ -2,6 +2,7 @@ use crate::{
     atom, value::Constructor, Array, Atom, BigInt, Exception, Function, Module, Object, Promise,
     String, Symbol, Value,
 };
+use alloc::{boxed::Box, vec::Vec};
 
 /// The trait which signifies a type using the rquickjs `'js` lifetime trick for maintaining safety around Javascript values.
 ///
@@ -108,8 +109,9 @@ outlive_impls! {
 }
 
 macro_rules! impl_outlive{
-    ($($($ty:ident)::+$(<$($g:ident),+>)*),*$(,)?) => {
+    ($($(#[$meta:meta])* $($ty:ident)::+$(<$($g:ident),+>)*),*$(,)?) => {
         $(
+            $(#[$meta])*
             unsafe impl<'js,$($($g,)*)*> JsLifetime<'js> for $($ty)::*$(<$($g,)*>)*
             where
                   $($($g: JsLifetime<'js>,)*)*
@@ -135,42 +137,55 @@ impl_outlive!(
     isize,
     i128,
     char,
-    std::string::String,
+    alloc::string::String,
     Vec<T>,
     Box<T>,
     Option<T>,
-    std::result::Result<T,E>,
+    core::result::Result<T,E>,
+    #[cfg(feature = "std")]
     std::backtrace::Backtrace,
-    std::cell::Cell<T>,
-    std::cell::RefCell<T>,
-    std::cell::UnsafeCell<T>,
-    std::collections::BTreeMap<K,V>,
-    std::collections::BTreeSet<K>,
-    std::collections::BinaryHeap<K>,
+    core::cell::Cell<T>,
+    core::cell::RefCell<T>,
+    core::cell::UnsafeCell<T>,
+    alloc::collections::BTreeMap<K,V>,
+    alloc::collections::BTreeSet<K>,
+    alloc::collections::BinaryHeap<K>,
+    #[cfg(feature = "std")]
     std::collections::HashMap<K,V>,
+    #[cfg(feature = "std")]
     std::collections::HashSet<K>,
-    std::collections::LinkedList<T>,
-    std::collections::VecDeque<T>,
-    std::ffi::CString,
+    alloc::collections::LinkedList<T>,
+    alloc::collections::VecDeque<T>,
+    alloc::ffi::CString,
+    #[cfg(feature = "std")]
     std::ffi::OsString,
-    std::ops::Range<T>,
-    std::ops::RangeFrom<T>,
-    std::ops::RangeFull,
-    std::ops::RangeInclusive<T>,
-    std::ops::RangeTo<T>,
-    std::ops::RangeToInclusive<T>,
-    std::ops::Bound<T>,
-    std::ops::ControlFlow<B,C>,
+    core::ops::Range<T>,
+    core::ops::RangeFrom<T>,
+    core::ops::RangeFull,
+    core::ops::RangeInclusive<T>,
+    core::ops::RangeTo<T>,
+    core::ops::RangeToInclusive<T>,
+    core::ops::Bound<T>,
+    core::ops::ControlFlow<B,C>,
+    #[cfg(feature = "std")]
     std::process::Child,
+    #[cfg(feature = "std")]
     std::process::Command,
+    #[cfg(feature = "std")]
     std::process::ExitCode,
+    #[cfg(feature = "std")]
     std::process::ExitStatus,
+    #[cfg(feature = "std")]
     std::process::Output,
+    #[cfg(feature = "std")]
     std::process::Stdio,
+    #[cfg(feature = "std")]
     std::path::PathBuf,
-    std::rc::Rc<T>,
-    std::sync::Arc<T>,
+    alloc::rc::Rc<T>,
+    alloc::sync::Arc<T>,
+    #[cfg(feature = "std")]
     std::sync::Mutex<T>,
+    #[cfg(feature = "std")]
     std::sync::RwLock<T>,
     atom::PredefinedAtom,
 );
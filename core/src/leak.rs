@@ -0,0 +1,258 @@
+//! Tracking of value handles to find the ones which are never released.
+//!
+//! With the `leak-detection` feature every handle to a reference counted value records where it
+//! was created. The handles still alive when a [`Context`](crate::Context) is dropped are
+//! reported on the standard error, [`Ctx::live_values`](crate::Ctx::live_values) lists them at any
+//! time.
+//!
+//! To find out what keeps a value alive, [`Value::ref_count`](crate::Value::ref_count) returns
+//! its reference count and [`Ctx::value_references`](crate::Ctx::value_references) splits it into
+//! the handles held by Rust, including the ones in [`Persistent`](crate::Persistent)s and
+//! closures, and the references held by scripts and the engine. Tracing a value with
+//! [`Ctx::trace_handles`](crate::Ctx::trace_handles) records every handle to it being acquired
+//! and released.
+//!
+//! Capturing a backtrace for every handle is slow, only enable the feature while debugging.
+
+use crate::{qjs, Type};
+use alloc::{collections::BTreeMap, sync::Arc};
+use core::fmt;
+use std::{backtrace::Backtrace, collections::HashMap};
+
+/// A handle to a JavaScript value which was not released yet.
+#[derive(Debug, Clone)]
+pub struct LiveValue {
+    /// The type of the value.
+    pub type_: Type,
+    /// Where the handle was created.
+    pub backtrace: Arc<Backtrace>,
+}
+
+impl fmt::Display for LiveValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} handle created at:\n{}", self.type_, self.backtrace)
+    }
+}
+
+/// The references to a value, returned by
+/// [`Ctx::value_references`](crate::Ctx::value_references).
+#[derive(Debug, Clone)]
+pub struct ValueReferences {
+    /// The reference count of the value.
+    pub ref_count: u32,
+    /// The live handles to the value in any context of the runtime, including the one the
+    /// references were requested with.
+    pub handles: Vec<LiveValue>,
+}
+
+impl ValueReferences {
+    /// Returns the number of references which aren't held by handles, but by scripts and the
+    /// engine, like properties of objects, variables captured by functions and pending jobs.
+    pub fn engine(&self) -> u32 {
+        self.ref_count.saturating_sub(self.handles.len() as u32)
+    }
+}
+
+/// Whether a handle was acquired or released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleEventKind {
+    /// A handle was created, by cloning another one or taking a reference from the engine.
+    Acquired,
+    /// A handle was dropped, releasing its reference, or its reference was given to the engine.
+    Released,
+}
+
+/// A handle to a traced value being acquired or released, recorded after
+/// [`Ctx::trace_handles`](crate::Ctx::trace_handles).
+#[derive(Debug, Clone)]
+pub struct HandleEvent {
+    /// Whether the handle was acquired or released.
+    pub kind: HandleEventKind,
+    /// The reference count while the handle held its reference.
+    pub ref_count: u32,
+    /// Where it happened.
+    pub backtrace: Arc<Backtrace>,
+}
+
+impl fmt::Display for HandleEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            HandleEventKind::Acquired => "acquired",
+            HandleEventKind::Released => "released",
+        };
+        write!(
+            f,
+            "handle {kind} with {} references at:\n{}",
+            self.ref_count, self.backtrace
+        )
+    }
+}
+
+/// Returns the reference count of a value, `None` if it isn't reference counted.
+///
+/// # Safety
+/// The value must be alive.
+pub(crate) unsafe fn ref_count(value: qjs::JSValue) -> Option<u32> {
+    if !qjs::JS_VALUE_HAS_REF_COUNT(value) {
+        return None;
+    }
+    Some(read_ref_count(qjs::JS_VALUE_GET_PTR(value) as usize))
+}
+
+/// Every reference counted value starts with its reference count.
+unsafe fn read_ref_count(ptr: usize) -> u32 {
+    *(ptr as *const qjs::c_int) as u32
+}
+
+/// The live handles of a runtime.
+#[derive(Default)]
+pub(crate) struct Tracker {
+    live: BTreeMap<usize, (*mut qjs::JSContext, usize, LiveValue)>,
+    next_id: usize,
+    /// The events of the traced values by pointer.
+    traced: HashMap<usize, Vec<HandleEvent>>,
+}
+
+impl Tracker {
+    /// Start tracking a handle, returns the id of the handle or 0 if it isn't tracked.
+    pub fn track(&mut self, ctx: *mut qjs::JSContext, value: qjs::JSValue) -> usize {
+        if !unsafe { qjs::JS_VALUE_HAS_REF_COUNT(value) } {
+            return 0;
+        }
+        let ptr = unsafe { qjs::JS_VALUE_GET_PTR(value) } as usize;
+        // Only the tag is used, checking for arrays or functions could call into JavaScript.
+        let type_ = match unsafe { qjs::JS_VALUE_GET_NORM_TAG(value) } {
+            qjs::JS_TAG_STRING => Type::String,
+            qjs::JS_TAG_SYMBOL => Type::Symbol,
+            qjs::JS_TAG_BIG_INT => Type::BigInt,
+            qjs::JS_TAG_OBJECT => Type::Object,
+            qjs::JS_TAG_MODULE => Type::Module,
+            _ => Type::Unknown,
+        };
+        self.next_id += 1;
+        let live = LiveValue {
+            type_,
+            backtrace: Arc::new(Backtrace::force_capture()),
+        };
+        if let Some(events) = self.traced.get_mut(&ptr) {
+            events.push(HandleEvent {
+                kind: HandleEventKind::Acquired,
+                ref_count: unsafe { read_ref_count(ptr) },
+                backtrace: live.backtrace.clone(),
+            });
+        }
+        self.live.insert(self.next_id, (ctx, ptr, live));
+        self.next_id
+    }
+
+    /// Stop tracking a handle, before it releases its reference.
+    pub fn untrack(&mut self, id: usize) {
+        let Some((_, ptr, _)) = self.live.remove(&id) else {
+            return;
+        };
+        if let Some(events) = self.traced.get_mut(&ptr) {
+            events.push(HandleEvent {
+                kind: HandleEventKind::Released,
+                ref_count: unsafe { read_ref_count(ptr) },
+                backtrace: Arc::new(Backtrace::force_capture()),
+            });
+        }
+    }
+
+    /// Returns the live handles created from the given context, in the order they were created.
+    pub fn live_values(&self, ctx: *mut qjs::JSContext) -> Vec<LiveValue> {
+        self.live
+            .values()
+            .filter(|(x, _, _)| *x == ctx)
+            .map(|(_, _, live)| live.clone())
+            .collect()
+    }
+
+    /// Returns the references to a reference counted value.
+    pub fn references(&self, value: qjs::JSValue) -> Option<ValueReferences> {
+        let ref_count = unsafe { ref_count(value) }?;
+        let ptr = unsafe { qjs::JS_VALUE_GET_PTR(value) } as usize;
+        let handles = self
+            .live
+            .values()
+            .filter(|(_, x, _)| *x == ptr)
+            .map(|(_, _, live)| live.clone())
+            .collect();
+        Some(ValueReferences { ref_count, handles })
+    }
+
+    pub fn trace(&mut self, value: qjs::JSValue) {
+        if unsafe { qjs::JS_VALUE_HAS_REF_COUNT(value) } {
+            let ptr = unsafe { qjs::JS_VALUE_GET_PTR(value) } as usize;
+            self.traced.entry(ptr).or_default();
+        }
+    }
+
+    pub fn take_trace(&mut self, value: qjs::JSValue) -> Vec<HandleEvent> {
+        if !unsafe { qjs::JS_VALUE_HAS_REF_COUNT(value) } {
+            return Vec::new();
+        }
+        let ptr = unsafe { qjs::JS_VALUE_GET_PTR(value) } as usize;
+        self.traced.remove(&ptr).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn live_values() {
+        test_with(|ctx| {
+            let before = ctx.live_values().len();
+            let object: Object = ctx.eval("({})").unwrap();
+            let _number: Value = ctx.eval("1").unwrap();
+            let live = ctx.live_values();
+            assert_eq!(live.len(), before + 1);
+            let last = live.last().unwrap();
+            assert_eq!(last.type_, Type::Object);
+            assert!(last.to_string().starts_with("object handle created at:\n"));
+
+            drop(object);
+            assert_eq!(ctx.live_values().len(), before);
+        })
+    }
+
+    #[test]
+    fn references() {
+        test_with(|ctx| {
+            let number: Value = ctx.eval("1").unwrap();
+            assert_eq!(number.ref_count(), None);
+            assert!(ctx.value_references(&number).is_none());
+
+            let object: Object = ctx.eval("({})").unwrap();
+            assert_eq!(object.ref_count(), Some(1));
+            ctx.globals().set("object", object.clone()).unwrap();
+            let f: Function = ctx.eval("() => object").unwrap();
+            let references = ctx.value_references(&object).unwrap();
+            assert_eq!(references.ref_count, 2);
+            assert_eq!(references.handles.len(), 1);
+            assert_eq!(references.engine(), 1);
+
+            ctx.trace_handles(&object);
+            let value: Object = f.call(()).unwrap();
+            let persistent = Persistent::save(&ctx, value);
+            assert_eq!(ctx.value_references(&object).unwrap().handles.len(), 2);
+            drop(persistent);
+            let trace = ctx.take_handle_trace(&object);
+            let kinds: Vec<_> = trace.iter().map(|x| x.kind).collect();
+            assert_eq!(
+                kinds,
+                [
+                    leak::HandleEventKind::Acquired,
+                    leak::HandleEventKind::Released
+                ]
+            );
+            assert_eq!(trace[0].ref_count, 3);
+            assert!(trace[1]
+                .to_string()
+                .starts_with("handle released with 3 references at:\n"));
+            assert!(ctx.take_handle_trace(&object).is_empty());
+        })
+    }
+}
@@ -5,11 +5,31 @@
 
 #![allow(clippy::needless_lifetimes)]
 #![cfg_attr(feature = "doc-cfg", feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-pub(crate) use std::{result::Result as StdResult, string::String as StdString};
+#[macro_use]
+extern crate alloc;
 
+pub(crate) use alloc::string::String as StdString;
+pub(crate) use core::result::Result as StdResult;
+
+/// Enters a `tracing` span which lasts until the end of the enclosing scope.
+///
+/// Expands to nothing when the `tracing` feature is disabled.
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!(target: "rquickjs", $($args)*).entered();
+    };
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+pub mod channel;
+mod js_enum;
 mod js_lifetime;
 pub mod markers;
+mod owned;
 mod persistent;
 mod result;
 mod safe_ref;
@@ -20,23 +40,43 @@ pub mod runtime;
 pub use runtime::Runtime;
 pub mod context;
 pub use context::{Context, Ctx};
+#[cfg(feature = "bench")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "bench")))]
+pub mod bench;
 pub mod class;
-pub use class::Class;
+#[cfg(feature = "testing")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "testing")))]
+pub mod testing;
+pub use class::{Class, External};
+pub use js_enum::JsEnum;
 pub use js_lifetime::JsLifetime;
+pub use owned::{Owned, OwnedArray, OwnedFunction, OwnedObject, OwnedValue};
 pub use persistent::Persistent;
-pub use result::{CatchResultExt, CaughtError, CaughtResult, Error, Result, ThrowResultExt};
+pub use result::{
+    BorrowError, CatchResultExt, CaughtError, CaughtResult, Error, Result, ThrowResultExt,
+};
 pub use value::{
     array, atom, convert, function, module, object, promise, Array, Atom, BigInt, Coerced,
-    Exception, Filter, FromAtom, FromIteratorJs, FromJs, Function, IntoAtom, IntoJs, IteratorJs,
-    Module, Null, Object, Promise, String, Symbol, Type, Undefined, Value,
+    ErrorPosition, Exception, Filter, FromAtom, FromIteratorJs, FromJs, Function, IntoAtom, IntoJs,
+    IteratorJs, JsIterator, JsStringBuilder, Module, Null, Object, Promise, RegExp, RegExpMatch,
+    String, StringRef, Symbol, Type, Undefined, Value,
 };
 
 #[cfg(feature = "allocator")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
 pub mod allocator;
+#[cfg(feature = "codegen")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "codegen")))]
+pub mod codegen;
+#[cfg(feature = "leak-detection")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-detection")))]
+pub mod leak;
 #[cfg(feature = "loader")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
 pub mod loader;
+#[cfg(feature = "web-minimal")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "web-minimal")))]
+pub mod web;
 
 #[cfg(feature = "futures")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
@@ -48,7 +88,7 @@ pub use context::MultiWith;
 pub use runtime::AsyncRuntime;
 #[cfg(feature = "array-buffer")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "array-buffer")))]
-pub use value::{ArrayBuffer, TypedArray};
+pub use value::{ArrayBuffer, BufferMut, BufferRef, DataView, TypedArray};
 
 //#[doc(hidden)]
 pub mod qjs {
@@ -70,7 +110,8 @@ pub mod prelude {
         context::Ctx,
         convert::{Coerced, FromAtom, FromIteratorJs, FromJs, IntoAtom, IntoJs, IteratorJs, List},
         function::{
-            Exhaustive, Flat, Func, FuncArg, IntoArg, IntoArgs, MutFn, OnceFn, Opt, Rest, This,
+            Exhaustive, Flat, Func, FuncArg, IntoArg, IntoArgs, MultiReturn, MutFn, Named,
+            NewTarget, OnceFn, Opt, Rest, This,
         },
         result::{CatchResultExt, ThrowResultExt},
         JsLifetime,
@@ -1,15 +1,20 @@
 //! Loaders and resolvers for loading JS modules.
 
-use std::{ffi::CStr, ptr};
+use core::{ffi::CStr, panic::AssertUnwindSafe, ptr};
 
 use crate::{module::Declared, qjs, Ctx, Module, Result};
 
 mod builtin_loader;
 mod builtin_resolver;
 pub mod bundle;
+mod commonjs_loader;
 mod compile;
+mod diagnostics;
 mod file_resolver;
+mod file_system;
+mod json_loader;
 mod module_loader;
+mod rewrite;
 mod script_loader;
 mod util;
 
@@ -18,9 +23,14 @@ mod native_loader;
 
 pub use builtin_loader::BuiltinLoader;
 pub use builtin_resolver::BuiltinResolver;
+pub use commonjs_loader::CommonJsLoader;
 pub use compile::Compile;
+pub use diagnostics::{LoaderTrace, TraceEvent, Traced};
 pub use file_resolver::FileResolver;
+pub use file_system::{FileSystem, MemoryFileSystem, StdFileSystem};
+pub use json_loader::JsonLoader;
 pub use module_loader::ModuleLoader;
+pub use rewrite::{Inspect, Rewrite};
 pub use script_loader::ScriptLoader;
 
 #[cfg(feature = "dyn-load")]
@@ -58,6 +68,40 @@ pub trait Resolver {
     /// # }
     /// ```
     fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String>;
+
+    /// Rewrite module specifiers before they are resolved by this resolver.
+    ///
+    /// The function is called with the name of the importing module and the imported specifier
+    /// and returns the specifier to resolve instead, or `None` to keep it. This allows mapping
+    /// bare specifiers to other locations, like pinning packages to the versions of a lock file.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use rquickjs::loader::{BuiltinResolver, Resolver};
+    /// let lock = HashMap::from([("lodash", "lodash@4.17.21")]);
+    /// let resolver = BuiltinResolver::default()
+    ///     .with_module("lodash@4.17.21")
+    ///     .rewrite(move |_base, name| lock.get(name).map(|x| x.to_string()));
+    /// ```
+    fn rewrite<F>(self, rewrite: F) -> Rewrite<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&str, &str) -> Option<String>,
+    {
+        Rewrite::new(self, rewrite)
+    }
+
+    /// Call a function with the outcome of every resolution of this resolver.
+    ///
+    /// The function is called with the name of the importing module, the imported specifier and
+    /// the result of the resolution.
+    fn inspect<F>(self, inspect: F) -> Inspect<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&str, &str, &Result<String>),
+    {
+        Inspect::new(self, inspect)
+    }
 }
 
 /// Module loader interface
@@ -136,10 +180,15 @@ impl LoaderHolder {
         let name = CStr::from_ptr(name);
         let loader = &mut *(opaque as *mut LoaderOpaque);
 
-        Self::normalize(loader, &ctx, base, name).unwrap_or_else(|error| {
-            error.throw(&ctx);
-            ptr::null_mut()
-        })
+        ctx.catch_panic(
+            AssertUnwindSafe(|| {
+                Self::normalize(loader, &ctx, base, name).unwrap_or_else(|error| {
+                    error.throw(&ctx);
+                    ptr::null_mut()
+                })
+            }),
+            ptr::null_mut(),
+        )
     }
 
     #[inline]
@@ -162,10 +211,15 @@ impl LoaderHolder {
         let name = CStr::from_ptr(name);
         let loader = &mut *(opaque as *mut LoaderOpaque);
 
-        Self::load(loader, &ctx, name).unwrap_or_else(|error| {
-            error.throw(&ctx);
-            ptr::null_mut()
-        })
+        ctx.catch_panic(
+            AssertUnwindSafe(|| {
+                Self::load(loader, &ctx, name).unwrap_or_else(|error| {
+                    error.throw(&ctx);
+                    ptr::null_mut()
+                })
+            }),
+            ptr::null_mut(),
+        )
     }
 }
 
@@ -188,7 +242,7 @@ macro_rules! loader_impls {
                 #[allow(non_snake_case)]
                 #[allow(unused_mut)]
                 fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
-                    let mut messages = Vec::<std::string::String>::new();
+                    let mut messages = Vec::<alloc::string::String>::new();
                     let ($($t,)*) = self;
                     $(
                         match $t.resolve(_ctx, base, name) {
@@ -215,7 +269,7 @@ macro_rules! loader_impls {
                 #[allow(non_snake_case)]
                 #[allow(unused_mut)]
                 fn load<'js>(&mut self, _ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
-                    let mut messages = Vec::<std::string::String>::new();
+                    let mut messages = Vec::<alloc::string::String>::new();
                     let ($($t,)*) = self;
                     $(
                         match $t.load(_ctx, name) {
@@ -240,9 +294,12 @@ loader_impls!(A B C D E F G H);
 
 #[cfg(test)]
 mod test {
-    use crate::{CatchResultExt, Context, Ctx, Error, Module, Result, Runtime};
+    use crate::{CatchResultExt, Context, Ctx, Error, Module, Result, Runtime, StdString};
 
-    use super::{Loader, Resolver};
+    use super::{
+        BuiltinLoader, BuiltinResolver, CommonJsLoader, FileResolver, JsonLoader, Loader,
+        LoaderTrace, MemoryFileSystem, Resolver, ScriptLoader, TraceEvent,
+    };
 
     struct TestResolver;
 
@@ -320,4 +377,302 @@ mod test {
             .expect("Unable to resolve");
         })
     }
+
+    #[test]
+    fn trace_resolution() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let trace = LoaderTrace::new();
+        let fs = MemoryFileSystem::default().with_file("lib/util.js", "export const x = 1;");
+        rt.set_loader(
+            (
+                trace.traced("test", TestResolver),
+                trace.traced(
+                    "files",
+                    FileResolver::default()
+                        .with_path("lib")
+                        .with_file_system(fs.clone()),
+                ),
+            ),
+            trace.traced("scripts", ScriptLoader::default().with_file_system(fs)),
+        );
+
+        ctx.with(|ctx| {
+            Module::evaluate(ctx.clone(), "main", "import 'util'")
+                .unwrap()
+                .finish::<()>()
+                .unwrap();
+            assert!(Module::evaluate(ctx.clone(), "main", "import 'missing'").is_err());
+            ctx.catch();
+        });
+
+        let events = trace.events();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(
+            &events[1],
+            TraceEvent::Resolve { resolver, result: Ok(name), .. }
+                if resolver == "files" && name == "lib/util.js"
+        ));
+        assert!(matches!(
+            &events[2],
+            TraceEvent::Load { loader, result: Ok(()), .. } if loader == "scripts"
+        ));
+        let report = trace.to_string();
+        assert!(
+            report.contains("resolve 'missing' from 'main' with files: failed: Error resolving module 'missing' from 'main': tried lib/missing.js"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn json_modules() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let fs = MemoryFileSystem::default()
+            .with_file(
+                "main.js",
+                "\u{feff}#!/usr/bin/env qjs\nimport data from './data.json';\nexport default data;",
+            )
+            .with_file("data.json", "\u{feff}{\"__proto__\": 1, \"list\": [1, 2]}")
+            .with_file("broken.json", "{ list: [] }");
+        rt.set_loader(
+            (
+                BuiltinResolver::default().with_module("config.json"),
+                FileResolver::default()
+                    .with_path("")
+                    .with_pattern("{}.json")
+                    .with_file_system(fs.clone()),
+            ),
+            (
+                BuiltinLoader::default().with_module("config.json", "[true]"),
+                ScriptLoader::default().with_file_system(fs.clone()),
+                JsonLoader::default().with_file_system(fs),
+            ),
+        );
+
+        ctx.with(|ctx| {
+            let promise = Module::import(&ctx, "main.js").unwrap();
+            let namespace: crate::Object = promise.finish().catch(&ctx).unwrap();
+            let data: crate::Object = namespace.get("default").unwrap();
+            assert_eq!(data.get::<_, Vec<i32>>("list").unwrap(), [1, 2]);
+            assert_eq!(data.get::<_, i32>("__proto__").unwrap(), 1);
+
+            let config: Vec<bool> = ctx
+                .eval::<crate::Promise, _>("import('config.json').then(x => x.default)")
+                .unwrap()
+                .finish()
+                .unwrap();
+            assert_eq!(config, [true]);
+
+            let promise = Module::import(&ctx, "broken.json").unwrap();
+            let err = promise.finish::<()>().catch(&ctx).unwrap_err();
+            assert!(err.to_string().contains("in JSON"), "{err}");
+        });
+    }
+
+    #[test]
+    fn commonjs_modules() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let fs = MemoryFileSystem::default()
+            .with_file(
+                "lib/counter.cjs",
+                "#!/usr/bin/env node\nleaked = __dirname;\nvar count = require('start');\nmodule.exports = { next: function () { return ++count; }, self: this === exports };",
+            )
+            .with_file("lib/broken.cjs", "require('missing');");
+        let resolver = || {
+            FileResolver::default()
+                .with_path("")
+                .with_pattern("{}.cjs")
+                .with_file_system(fs.clone())
+        };
+
+        rt.set_loader(
+            resolver(),
+            CommonJsLoader::new(|ctx, base, name| {
+                assert_eq!(base, "lib/counter.cjs");
+                assert_eq!(name, "start");
+                crate::IntoJs::into_js(41, ctx)
+            })
+            .with_file_system(fs.clone()),
+        );
+        ctx.with(|ctx| {
+            let promise = Module::import(&ctx, "lib/counter.cjs").unwrap();
+            let namespace: crate::Object = promise.finish().catch(&ctx).unwrap();
+            let next: crate::Function = namespace.get("next").unwrap();
+            assert_eq!(next.call::<_, i32>(()).unwrap(), 42);
+            let default: crate::Object = namespace.get("default").unwrap();
+            assert!(default.get::<_, bool>("self").unwrap());
+            assert_eq!(ctx.globals().get::<_, StdString>("leaked").unwrap(), "lib");
+        });
+
+        rt.set_loader(
+            resolver(),
+            CommonJsLoader::default().with_file_system(fs.clone()),
+        );
+        ctx.with(|ctx| {
+            let promise = Module::import(&ctx, "lib/broken.cjs").unwrap();
+            let err = promise.finish::<()>().catch(&ctx).unwrap_err();
+            assert!(
+                err.to_string().contains("require is not supported"),
+                "{err}"
+            );
+        });
+    }
+
+    #[test]
+    fn rewrite_specifiers() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let resolved = Rc::new(RefCell::new(Vec::new()));
+        let log = resolved.clone();
+        rt.set_loader(
+            BuiltinResolver::default()
+                .with_module("lib@2")
+                .rewrite(|_base, name| (name == "lib").then(|| "lib@2".to_string()))
+                .inspect(move |_base, name, result| {
+                    log.borrow_mut()
+                        .push((name.to_string(), result.as_ref().ok().cloned()))
+                }),
+            BuiltinLoader::default().with_module("lib@2", "export default 2"),
+        );
+
+        ctx.with(|ctx| {
+            let res: i32 = ctx
+                .eval::<crate::Promise, _>("import('lib').then(x => x.default)")
+                .unwrap()
+                .finish()
+                .unwrap();
+            assert_eq!(res, 2);
+            assert!(Module::evaluate(ctx.clone(), "main", "import 'other'").is_err());
+            ctx.catch();
+        });
+        assert_eq!(
+            *resolved.borrow(),
+            [
+                ("lib".to_string(), Some("lib@2".to_string())),
+                ("other".to_string(), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn bundle_module_graph() {
+        use super::bundle::{Bundler, ModuleBundle};
+        use crate::{Promise, Value};
+
+        let bundle = Bundler::new(
+            BuiltinResolver::default()
+                .with_module("app")
+                .with_module("lib")
+                .with_module("util")
+                .with_module("host"),
+            BuiltinLoader::default()
+                .with_module(
+                    "app",
+                    "import { x } from 'host'; import { y } from 'lib'; import { z } from 'util'; globalThis.res = x + y + z;",
+                )
+                .with_module("lib", "import { z } from 'util'; export const y = z + 1;")
+                .with_module("util", "export const z = 1;"),
+        )
+        .with_external("host")
+        .bundle("app")
+        .unwrap();
+        assert_eq!(bundle.entry(), "app");
+        let mut names: Vec<_> = bundle.modules().map(|(name, _)| name).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["app", "lib", "util"]);
+
+        let bytes = bundle.to_bytes();
+        let bundle = unsafe { ModuleBundle::from_bytes(&bytes) }.unwrap();
+        assert_eq!(bundle.to_bytes(), bytes);
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(
+            (
+                bundle.clone(),
+                BuiltinResolver::default().with_module("host"),
+            ),
+            (
+                bundle.clone(),
+                BuiltinLoader::default().with_module("host", "export const x = 40;"),
+            ),
+        );
+        ctx.with(|ctx| {
+            bundle
+                .import(&ctx)
+                .unwrap()
+                .finish::<()>()
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(ctx.globals().get::<_, i32>("res").unwrap(), 43);
+        });
+
+        let error = |bytes: &[u8]| {
+            unsafe { ModuleBundle::from_bytes(bytes) }
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error(b"not a bundle").contains("Not a module bundle"));
+        assert!(error(&bytes[..bytes.len() - 1]).contains("invalid"));
+        let tampered = |offset: usize, byte: u8| {
+            let mut other = bytes.clone();
+            other[offset] = byte;
+            error(&other)
+        };
+        assert!(tampered(8, 1).contains("bundle format 1"));
+        // The versions of rquickjs and QuickJS follow as length prefixed strings.
+        assert!(tampered(16, b'x').contains("written by rquickjs x"));
+        let rquickjs_len = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        let quickjs = 16 + rquickjs_len;
+        assert!(tampered(quickjs + 4, b'x').contains("written by QuickJS x"));
+        let quickjs_len =
+            u32::from_le_bytes(bytes[quickjs..quickjs + 4].try_into().unwrap()) as usize;
+        let bytecode = quickjs + 4 + quickjs_len;
+        assert!(tampered(bytecode, 0).contains("bytecode version 0"));
+        let endian = if cfg!(target_endian = "big") { 0 } else { 1 };
+        assert!(tampered(bytecode + 1, endian).contains("endian"));
+
+        // Modules imported dynamically are only bundled if added.
+        let bundle = Bundler::new(
+            BuiltinResolver::default()
+                .with_module("main")
+                .with_module("lazy")
+                .with_module("dir/util")
+                .with_module("missing"),
+            BuiltinLoader::default()
+                .with_module(
+                    "main",
+                    "globalThis.lazy = import('lazy').then((m) => m.v); globalThis.missing = import('missing');",
+                )
+                .with_module("lazy", "export { v } from './dir/util';")
+                .with_module("dir/util", "export const v = 'lazy';")
+                .with_module("missing", ""),
+        )
+        .with_module("lazy")
+        .bundle("main")
+        .unwrap();
+        let mut names: Vec<_> = bundle.modules().map(|(name, _)| name).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["dir/util", "lazy", "main"]);
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(bundle.clone(), bundle.clone());
+        ctx.with(|ctx| {
+            bundle.import(&ctx).unwrap().finish::<()>().unwrap();
+            let lazy: Promise = ctx.globals().get("lazy").unwrap();
+            assert_eq!(lazy.finish::<StdString>().unwrap(), "lazy");
+            let missing: Promise = ctx.globals().get("missing").unwrap();
+            let error = missing
+                .finish::<Value>()
+                .catch(&ctx)
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("missing"), "{error}");
+            assert!(error.contains("Bundler::add_module"), "{error}");
+        });
+    }
 }
@@ -1,8 +1,14 @@
-use crate::{loader::Loader, module::Declared, Ctx, Error, Module, Result};
+use crate::{
+    loader::{util::check_extensions, Loader},
+    module::Declared,
+    Ctx, Error, Module, Result,
+};
 use std::collections::HashMap;
 
 /// The builtin script module loader
 ///
+/// Modules with a `.json` extension are loaded as JSON modules, see [`Module::declare_json`].
+///
 /// This loader can be used as the nested backing loader in user-defined loaders.
 #[derive(Debug, Default)]
 pub struct BuiltinLoader {
@@ -31,6 +37,9 @@ impl BuiltinLoader {
 impl Loader for BuiltinLoader {
     fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js, Declared>> {
         match self.modules.remove(path) {
+            Some(source) if check_extensions(path, &["json".into()]) => {
+                Module::declare_json(ctx.clone(), path, source)
+            }
             Some(source) => Module::declare(ctx.clone(), path, source),
             _ => Err(Error::new_loading(path)),
         }
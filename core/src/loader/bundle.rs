@@ -1,8 +1,20 @@
 //! Utilities for embedding JS modules.
+//!
+//! [`Bundle`] serves modules compiled ahead of time, like the ones of the `embed!` macro. A
+//! [`Bundler`] compiles the whole module graph of an entry module with a resolver and a loader
+//! into a [`ModuleBundle`], a single artifact which can be stored and loaded again without the
+//! sources.
 
 use super::{util::resolve_simple, Loader, Resolver};
-use crate::{Ctx, Error, Module, Result};
-use std::ops::Deref;
+use crate::{qjs, CatchResultExt, Context, Ctx, Error, Module, Promise, Result, Runtime};
+use alloc::{collections::BTreeMap, rc::Rc, sync::Arc};
+use core::{
+    cell::RefCell,
+    ffi::CStr,
+    ops::Deref,
+    sync::atomic::{AtomicU16, Ordering},
+};
+use std::collections::HashSet;
 
 /// The module data which contains bytecode
 ///
@@ -89,3 +101,446 @@ where
         Err(Error::new_loading(name))
     }
 }
+
+/// Compiles the module graph of an entry module into a [`ModuleBundle`].
+///
+/// The entry module and every module it imports, directly or not, are resolved and loaded with
+/// the given resolver and loader, then compiled to bytecode without being evaluated. Modules
+/// provided by the host when the bundle is loaded, like the native modules of a
+/// [`ModuleLoader`](super::ModuleLoader), can't be compiled to bytecode and must be marked as
+/// external with [`Bundler::with_external`].
+///
+/// Only the static imports of the modules are followed. The specifier of a dynamic `import()` is
+/// only known once it runs, so the modules imported dynamically are not bundled unless they are
+/// added with [`Bundler::with_module`]. A bundle resolves a dynamic import to a module it
+/// contains by its path relative to the importing module, and fails to resolve any other with an
+/// error naming the specifier and the importing module.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, loader::{FileResolver, MemoryFileSystem, ScriptLoader}};
+/// use rquickjs::loader::bundle::{Bundler, ModuleBundle};
+///
+/// let fs = MemoryFileSystem::default()
+///     .with_file("app/main.js", "import { add } from './math.js'; globalThis.res = add(1, 2);")
+///     .with_file("app/math.js", "export const add = (a, b) => a + b;");
+/// let bundle = Bundler::new(
+///     FileResolver::default().with_path("app").with_file_system(fs.clone()),
+///     ScriptLoader::default().with_file_system(fs),
+/// )
+/// .bundle("main.js")
+/// .unwrap();
+/// let bytes = bundle.to_bytes();
+///
+/// // Later, possibly in another process using the same version of rquickjs.
+/// let bundle = unsafe { ModuleBundle::from_bytes(&bytes) }.unwrap();
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// rt.set_loader(bundle.clone(), bundle.clone());
+/// ctx.with(|ctx| {
+///     bundle.import(&ctx).unwrap().finish::<()>().unwrap();
+///     assert_eq!(ctx.globals().get::<_, i32>("res").unwrap(), 3);
+/// });
+/// ```
+pub struct Bundler<R, L> {
+    resolver: R,
+    loader: L,
+    externals: HashSet<String>,
+    modules: Vec<String>,
+}
+
+impl<R, L> Bundler<R, L>
+where
+    R: Resolver + 'static,
+    L: Loader + 'static,
+{
+    /// Create a bundler resolving and loading modules with the given resolver and loader.
+    pub fn new(resolver: R, loader: L) -> Self {
+        Bundler {
+            resolver,
+            loader,
+            externals: HashSet::new(),
+            modules: Vec::new(),
+        }
+    }
+
+    /// Also bundle the module graph of the module with the given specifier, for modules which
+    /// are only imported dynamically.
+    ///
+    /// The specifier is resolved like the entry. The module isn't evaluated when the entry is
+    /// imported.
+    pub fn add_module<N: Into<String>>(&mut self, specifier: N) -> &mut Self {
+        self.modules.push(specifier.into());
+        self
+    }
+
+    /// Also bundle the module graph of the module with the given specifier.
+    ///
+    /// See [`Bundler::add_module`].
+    #[must_use]
+    pub fn with_module<N: Into<String>>(mut self, specifier: N) -> Self {
+        self.add_module(specifier);
+        self
+    }
+
+    /// Leave the module with the given specifier or resolved name out of bundles.
+    ///
+    /// Imports of external modules are left to the resolvers and loaders chained after the
+    /// bundle in the runtime it is loaded in. Specifiers of external modules aren't resolved, so
+    /// they don't need to be resolvable when bundling.
+    pub fn add_external<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        self.externals.insert(name.into());
+        self
+    }
+
+    /// Leave the module with the given specifier or resolved name out of bundles.
+    ///
+    /// See [`Bundler::add_external`].
+    #[must_use]
+    pub fn with_external<N: Into<String>>(mut self, name: N) -> Self {
+        self.add_external(name);
+        self
+    }
+
+    /// Compile the module graph of the module with the given specifier.
+    ///
+    /// The specifier is resolved like `import(specifier)` at the top level of a script. The
+    /// modules are compiled in a new runtime and never evaluated.
+    pub fn bundle(self, entry: &str) -> Result<ModuleBundle> {
+        let data = Rc::new(RefCell::new(BundleData {
+            entry: entry.into(),
+            ..Default::default()
+        }));
+        let externals = Rc::new(self.externals);
+        let rt = Runtime::new()?;
+        let ctx = Context::full(&rt)?;
+        rt.set_loader(
+            Recorder {
+                inner: self.resolver,
+                data: data.clone(),
+                externals: externals.clone(),
+            },
+            Recorder {
+                inner: self.loader,
+                data: data.clone(),
+                externals,
+            },
+        );
+        // Declaring a module loads its imports recursively without evaluating them. A module
+        // named like the top level of a script resolves the entry as `import(entry)` would.
+        // Errors of the resolver and loader are thrown, so they are reported as loading errors.
+        let source: String = core::iter::once(entry)
+            .chain(self.modules.iter().map(String::as_str))
+            .map(|specifier| format!("import {specifier:?};"))
+            .collect();
+        ctx.with(|ctx| {
+            Module::declare(ctx.clone(), "", source)
+                .catch(&ctx)
+                .map(|_| ())
+                .map_err(|error| Error::new_loading_message(entry, error.to_string()))
+        })?;
+        let mut data = data.take();
+        data.bytecode_version = ctx.with(|ctx| written_bytecode_version(&ctx))?;
+        Ok(ModuleBundle(Arc::new(data)))
+    }
+}
+
+/// The resolver and loader of a [`Bundler`], recording the resolved imports and the bytecode of
+/// the loaded modules.
+struct Recorder<T> {
+    inner: T,
+    data: Rc<RefCell<BundleData>>,
+    externals: Rc<HashSet<String>>,
+}
+
+impl<R: Resolver> Resolver for Recorder<R> {
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        if self.externals.contains(name) {
+            return Ok(name.into());
+        }
+        let resolved = self.inner.resolve(ctx, base, name)?;
+        if !self.externals.contains(&resolved) {
+            self.data
+                .borrow_mut()
+                .imports
+                .insert((base.into(), name.into()), resolved.clone());
+        }
+        Ok(resolved)
+    }
+}
+
+impl<L: Loader> Loader for Recorder<L> {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js>> {
+        if self.externals.contains(name) {
+            // External modules only need to exist once the bundle is linked.
+            return Module::declare(ctx.clone(), name, "");
+        }
+        let module = self.inner.load(ctx, name)?;
+        let bytecode = module.write(false)?;
+        self.data.borrow_mut().modules.push((name.into(), bytecode));
+        Ok(module)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct BundleData {
+    /// The version of the bytecode format of QuickJS the modules were compiled to.
+    bytecode_version: u8,
+    /// The specifier of the entry module.
+    entry: String,
+    /// The names and bytecode of the modules in the order they were loaded.
+    modules: Vec<(String, Vec<u8>)>,
+    /// The resolved names of the imports by importing module and specifier.
+    imports: BTreeMap<(String, String), String>,
+}
+
+/// A module graph compiled to bytecode by a [`Bundler`].
+///
+/// A bundle is a resolver and a loader for its modules, set with
+/// [`Runtime::set_loader`](crate::Runtime::set_loader) and possibly chained with the resolvers
+/// and loaders of external modules. It is cheap to clone.
+///
+/// The bytecode is only valid for the version of QuickJS and the endianness of the target it was
+/// compiled with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleBundle(Arc<BundleData>);
+
+/// The start of a serialized [`ModuleBundle`], followed by the version of its layout.
+const MAGIC: &[u8; 8] = b"rqjsbndl";
+/// The version of the layout of a serialized [`ModuleBundle`], incremented when it changes.
+///
+/// The layout is followed by a header with the versions of rquickjs and QuickJS, the version of
+/// the bytecode and the endianness the modules were compiled with, which must match the ones
+/// loading the bundle.
+const FORMAT_VERSION: u32 = 2;
+
+/// The endianness of the bytecode written by [`Module::write`] without swapping.
+const NATIVE_ENDIAN: u8 = if cfg!(target_endian = "big") { 1 } else { 0 };
+
+/// Returns the version of QuickJS.
+fn quickjs_version() -> &'static str {
+    unsafe { CStr::from_ptr(qjs::JS_GetVersion()) }
+        .to_str()
+        .unwrap_or_default()
+}
+
+/// Returns the version of the bytecode format, the first byte of written bytecode.
+fn written_bytecode_version(ctx: &Ctx<'_>) -> Result<u8> {
+    let module = Module::declare(ctx.clone(), "<bytecode version>", "")?;
+    Ok(module.write(false)?[0])
+}
+
+/// Returns the version of the bytecode format QuickJS loads, found once in a new runtime.
+fn loaded_bytecode_version() -> Result<u8> {
+    // The version in the low byte, 0x100 while unknown.
+    static VERSION: AtomicU16 = AtomicU16::new(0x100);
+    let version = VERSION.load(Ordering::Relaxed);
+    if version < 0x100 {
+        return Ok(version as u8);
+    }
+    let rt = Runtime::new()?;
+    let ctx = Context::full(&rt)?;
+    let version = ctx.with(|ctx| written_bytecode_version(&ctx))?;
+    VERSION.store(version as u16, Ordering::Relaxed);
+    Ok(version)
+}
+
+impl ModuleBundle {
+    /// Returns the specifier of the entry module.
+    pub fn entry(&self) -> &str {
+        &self.0.entry
+    }
+
+    /// Returns the names and bytecode of the modules in the bundle.
+    pub fn modules(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.0
+            .modules
+            .iter()
+            .map(|(name, bytecode)| (name.as_str(), bytecode.as_slice()))
+    }
+
+    /// Returns the resolved imports of the modules in the bundle as the name of the importing
+    /// module, the imported specifier and the name of the imported module.
+    ///
+    /// The entry and the modules added with [`Bundler::add_module`] are imported by a module with
+    /// an empty name. Imports of external modules are left out.
+    pub fn imports(&self) -> impl Iterator<Item = (&str, &str, &str)> {
+        self.0
+            .imports
+            .iter()
+            .map(|((base, specifier), name)| (base.as_str(), specifier.as_str(), name.as_str()))
+    }
+
+    /// Import and evaluate the entry module, like [`Module::import`].
+    ///
+    /// The bundle must be the resolver and loader of the runtime, or chained in them.
+    pub fn import<'js>(&self, ctx: &Ctx<'js>) -> Result<Promise<'js>> {
+        Module::import(ctx, self.0.entry.as_str())
+    }
+
+    /// Serialize the bundle into a single artifact.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fn put(buf: &mut Vec<u8>, bytes: &[u8]) {
+            buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            buf.extend_from_slice(bytes);
+        }
+
+        let data = &self.0;
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        put(&mut buf, env!("CARGO_PKG_VERSION").as_bytes());
+        put(&mut buf, quickjs_version().as_bytes());
+        buf.extend_from_slice(&[data.bytecode_version, NATIVE_ENDIAN]);
+        put(&mut buf, data.entry.as_bytes());
+        buf.extend_from_slice(&(data.modules.len() as u32).to_le_bytes());
+        for (name, bytecode) in &data.modules {
+            put(&mut buf, name.as_bytes());
+            put(&mut buf, bytecode);
+        }
+        buf.extend_from_slice(&(data.imports.len() as u32).to_le_bytes());
+        for ((base, specifier), name) in &data.imports {
+            put(&mut buf, base.as_bytes());
+            put(&mut buf, specifier.as_bytes());
+            put(&mut buf, name.as_bytes());
+        }
+        buf
+    }
+
+    /// Deserialize a bundle written by [`ModuleBundle::to_bytes`].
+    ///
+    /// Fails with a loading error if the bytes are not a bundle, or if it was written by another
+    /// version of rquickjs or QuickJS, for another version of the bytecode or on a target with
+    /// another endianness.
+    ///
+    /// # Safety
+    /// The bytecode of the modules isn't validated, the bytes must come from a trusted source
+    /// like the ones given to [`Module::load`].
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        fn read(reader: &mut Reader, bytecode_version: u8) -> Option<BundleData> {
+            let mut data = BundleData {
+                bytecode_version,
+                entry: reader.string()?,
+                ..Default::default()
+            };
+            for _ in 0..reader.int()? {
+                let (name, bytecode) = (reader.string()?, reader.bytes()?);
+                if bytecode.first() != Some(&bytecode_version) {
+                    return None;
+                }
+                data.modules.push((name, bytecode.to_vec()));
+            }
+            for _ in 0..reader.int()? {
+                let key = (reader.string()?, reader.string()?);
+                data.imports.insert(key, reader.string()?);
+            }
+            reader.0.is_empty().then_some(data)
+        }
+
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len()) != Some(MAGIC.as_slice()) {
+            return Err(Error::new_loading_message(
+                "<bundle>",
+                "Not a module bundle",
+            ));
+        }
+        let invalid = || Error::new_loading_message("<bundle>", "The module bundle is invalid");
+        let mismatch =
+            |what: &str, bundle: &dyn core::fmt::Display, current: &dyn core::fmt::Display| {
+                Error::new_loading_message(
+                    "<bundle>",
+                    format!("The module bundle was written by {what} {bundle}, not {current}"),
+                )
+            };
+        let format = reader.int().ok_or_else(invalid)?;
+        if format != FORMAT_VERSION {
+            return Err(mismatch("bundle format", &format, &FORMAT_VERSION));
+        }
+        let version = reader.string().ok_or_else(invalid)?;
+        if version != env!("CARGO_PKG_VERSION") {
+            return Err(mismatch("rquickjs", &version, &env!("CARGO_PKG_VERSION")));
+        }
+        let version = reader.string().ok_or_else(invalid)?;
+        if version != quickjs_version() {
+            return Err(mismatch("QuickJS", &version, &quickjs_version()));
+        }
+        let (bytecode_version, endian) = match reader.take(2).ok_or_else(invalid)? {
+            [version, endian] => (*version, *endian),
+            _ => unreachable!(),
+        };
+        let current = loaded_bytecode_version()?;
+        if bytecode_version != current {
+            return Err(mismatch("bytecode version", &bytecode_version, &current));
+        }
+        if endian != NATIVE_ENDIAN {
+            let name = |endian| {
+                if endian == 1 {
+                    "big endian"
+                } else {
+                    "little endian"
+                }
+            };
+            return Err(mismatch(
+                "a target with",
+                &name(endian),
+                &name(NATIVE_ENDIAN),
+            ));
+        }
+        let data = read(&mut reader, bytecode_version).ok_or_else(invalid)?;
+        Ok(ModuleBundle(Arc::new(data)))
+    }
+}
+
+impl Resolver for ModuleBundle {
+    fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        if let Some(resolved) = self.0.imports.get(&(base.into(), name.into())) {
+            return Ok(resolved.clone());
+        }
+        // Dynamic imports weren't recorded when bundling.
+        let path = resolve_simple(base, name);
+        if self.0.modules.iter().any(|(module, _)| *module == path) {
+            return Ok(path);
+        }
+        Err(Error::new_resolving_message(
+            base,
+            name,
+            "The module isn't in the bundle, modules imported dynamically must be added with `Bundler::add_module`",
+        ))
+    }
+}
+
+impl Loader for ModuleBundle {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js>> {
+        match self.0.modules.iter().find(|(module, _)| module == name) {
+            Some((_, bytecode)) => unsafe { Module::load(ctx.clone(), bytecode) },
+            None => Err(Error::new_loading(name)),
+        }
+    }
+}
+
+/// Reads the length prefixed parts of a serialized [`ModuleBundle`].
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn int(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|x| u32::from_le_bytes(x.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.int()?;
+        self.take(len as usize)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        String::from_utf8(self.bytes()?.to_vec()).ok()
+    }
+}
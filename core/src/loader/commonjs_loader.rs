@@ -0,0 +1,180 @@
+use crate::{
+    function::This,
+    loader::{util::check_extensions, FileSystem, Loader, StdFileSystem},
+    qjs, util, Ctx, Error, Function, Module, Object, Result, StdString, Value,
+};
+use alloc::{ffi::CString, rc::Rc};
+use core::fmt;
+use relative_path::RelativePath;
+
+type RequireFn = Rc<dyn for<'js> Fn(&Ctx<'js>, &str, &str) -> Result<Value<'js>>>;
+
+/// The CommonJS module loader
+///
+/// Loads CommonJS sources, which assign their exports to `module.exports` and import other
+/// modules with `require`, as ES modules. The value of `module.exports` becomes the default
+/// export of the module and its own enumerable properties become named exports. The source runs
+/// in sloppy mode, with `exports`, `require`, `module`, `__filename` and `__dirname` in scope, as
+/// soon as the module is loaded, which is before the importing module is evaluated.
+///
+/// Calls to `require` are passed to a host function together with the name of the requiring
+/// module, by default they fail.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, Object, loader::{CommonJsLoader, FileResolver, MemoryFileSystem}};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+///
+/// let fs = MemoryFileSystem::default().with_file(
+///     "math.cjs",
+///     "const config = require('config'); exports.double = (x) => x * config.factor;",
+/// );
+/// rt.set_loader(
+///     FileResolver::default().with_path("").with_pattern("{}.cjs").with_file_system(fs.clone()),
+///     CommonJsLoader::new(|ctx, _base, _name| {
+///         let config = Object::new(ctx.clone())?;
+///         config.set("factor", 2)?;
+///         Ok(config.into_value())
+///     })
+///     .with_file_system(fs),
+/// );
+///
+/// ctx.with(|ctx| {
+///     let res: i32 = ctx
+///         .eval::<rquickjs::Promise, _>("import('math.cjs').then(({ double }) => double(21))")
+///         .unwrap()
+///         .finish()
+///         .unwrap();
+///     assert_eq!(res, 42);
+/// });
+/// ```
+pub struct CommonJsLoader {
+    extensions: Vec<String>,
+    fs: Box<dyn FileSystem>,
+    require: RequireFn,
+}
+
+impl fmt::Debug for CommonJsLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommonJsLoader")
+            .field("extensions", &self.extensions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CommonJsLoader {
+    /// Create a loader which resolves `require` calls with the given function.
+    ///
+    /// The function is called with the name of the requiring module and the specifier passed to
+    /// `require` and returns the value `require` returns.
+    pub fn new<F>(require: F) -> Self
+    where
+        F: for<'js> Fn(&Ctx<'js>, &str, &str) -> Result<Value<'js>> + 'static,
+    {
+        Self {
+            extensions: vec!["cjs".into()],
+            fs: Box::new(StdFileSystem),
+            require: Rc::new(require),
+        }
+    }
+
+    /// Add CommonJS file extension
+    pub fn add_extension<X: Into<String>>(&mut self, extension: X) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Add CommonJS file extension
+    #[must_use]
+    pub fn with_extension<X: Into<String>>(mut self, extension: X) -> Self {
+        self.add_extension(extension);
+        self
+    }
+
+    /// Set the file system from which sources are read, defaults to [`StdFileSystem`].
+    pub fn set_file_system<F: FileSystem + 'static>(&mut self, fs: F) -> &mut Self {
+        self.fs = Box::new(fs);
+        self
+    }
+
+    /// Set the file system from which sources are read, defaults to [`StdFileSystem`].
+    #[must_use]
+    pub fn with_file_system<F: FileSystem + 'static>(mut self, fs: F) -> Self {
+        self.set_file_system(fs);
+        self
+    }
+
+    /// Run the source and return the value of `module.exports`.
+    fn run<'js>(&self, ctx: &Ctx<'js>, path: &str, source: Vec<u8>) -> Result<Value<'js>> {
+        let mut source = util::strip_bom(source);
+        // The shebang line is only skipped at the start of a script, comment it out instead.
+        if source.starts_with(b"#!") {
+            source[..2].copy_from_slice(b"//");
+        }
+        // Keep the prefix on the first line so line numbers stay the same.
+        let mut wrapped = b"(function (exports, require, module, __filename, __dirname) {".to_vec();
+        wrapped.extend(source);
+        wrapped.extend(b"\n})");
+
+        let file_name = CString::new(path)?;
+        let wrapper = unsafe {
+            let val = ctx.eval_raw(wrapped, &file_name, qjs::JS_EVAL_TYPE_GLOBAL as i32)?;
+            Value::from_js_value(ctx.clone(), val)
+        };
+        let wrapper: Function = wrapper.get()?;
+
+        let require = self.require.clone();
+        let base = StdString::from(path);
+        let require = Function::new(ctx.clone(), move |ctx: Ctx<'js>, name: StdString| {
+            require(&ctx, &base, &name)
+        })?
+        .with_name("require")?;
+
+        let exports = Object::new(ctx.clone())?;
+        let module = Object::new(ctx.clone())?;
+        module.set("id", path)?;
+        module.set("exports", exports.clone())?;
+        let dir_name = RelativePath::new(path)
+            .parent()
+            .map(|x| x.as_str())
+            .unwrap_or("");
+
+        wrapper.call::<_, ()>((
+            This(exports.clone()),
+            exports,
+            require,
+            module.clone(),
+            path,
+            dir_name,
+        ))?;
+        module.get("exports")
+    }
+}
+
+impl Default for CommonJsLoader {
+    fn default() -> Self {
+        Self::new(|_ctx, base, name| {
+            Err(Error::new_resolving_message(
+                base,
+                name,
+                "require is not supported",
+            ))
+        })
+    }
+}
+
+impl Loader for CommonJsLoader {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js>> {
+        if !check_extensions(path, &self.extensions) {
+            return Err(Error::new_loading(path));
+        }
+
+        let source = self.fs.read(path)?;
+        let exports = self.run(ctx, path, source)?;
+        let names = match exports.as_object() {
+            Some(object) => object.keys().collect::<Result<_>>()?,
+            None => Vec::new(),
+        };
+        Module::declare_value(ctx.clone(), path, exports, names)
+    }
+}
@@ -2,11 +2,11 @@ use crate::{
     loader::{util::resolve_simple, Loader, Resolver},
     Ctx, Lock, Module, Mut, Ref, Result,
 };
-use std::{
-    collections::{hash_map::Iter as HashMapIter, HashMap},
+use core::{
     iter::FusedIterator,
     ops::{Deref, DerefMut},
 };
+use std::collections::{hash_map::Iter as HashMapIter, HashMap};
 
 /// Modules compiling data
 #[derive(Default, Clone)]
@@ -0,0 +1,192 @@
+use alloc::{borrow::Cow, sync::Arc};
+use core::{any::type_name, fmt};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{module::Declared, Ctx, Module, Result};
+
+use super::{Loader, Resolver};
+
+/// A single resolution or loading attempt recorded by a [`LoaderTrace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// A resolver was asked to resolve a module name.
+    Resolve {
+        /// The name of the resolver.
+        resolver: Cow<'static, str>,
+        /// The name of the importing module.
+        base: String,
+        /// The imported module specifier.
+        name: String,
+        /// The resolved name or the reason the resolver failed.
+        result: core::result::Result<String, String>,
+    },
+    /// A loader was asked to load a resolved module.
+    Load {
+        /// The name of the loader.
+        loader: Cow<'static, str>,
+        /// The resolved name of the module.
+        name: String,
+        /// The reason the loader failed if it did.
+        result: core::result::Result<(), String>,
+    },
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceEvent::Resolve {
+                resolver,
+                base,
+                name,
+                result,
+            } => {
+                write!(f, "resolve '{name}' from '{base}' with {resolver}: ")?;
+                match result {
+                    Ok(x) => write!(f, "resolved to '{x}'"),
+                    Err(e) => write!(f, "failed: {e}"),
+                }
+            }
+            TraceEvent::Load {
+                loader,
+                name,
+                result,
+            } => {
+                write!(f, "load '{name}' with {loader}: ")?;
+                match result {
+                    Ok(()) => "loaded".fmt(f),
+                    Err(e) => write!(f, "failed: {e}"),
+                }
+            }
+        }
+    }
+}
+
+/// A diagnostic log of module resolution and loading.
+///
+/// Wrap resolvers and loaders with [`LoaderTrace::resolver`] and [`LoaderTrace::loader`] to
+/// record every attempt they make, including which one matched or why it failed. When wrapping
+/// the elements of a tuple each of them is recorded separately. The trace is a cheap handle
+/// which can be kept around to inspect the log after an import failed.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, loader::{BuiltinResolver, FileResolver, LoaderTrace, ScriptLoader}};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+///
+/// let trace = LoaderTrace::new();
+/// rt.set_loader(
+///     (
+///         trace.resolver(BuiltinResolver::default()),
+///         trace.resolver(FileResolver::default().with_path("./modules")),
+///     ),
+///     trace.loader(ScriptLoader::default()),
+/// );
+///
+/// ctx.with(|ctx| {
+///     assert!(Module::evaluate(ctx, "main", "import 'missing'").is_err());
+/// });
+/// assert_eq!(trace.events().len(), 2);
+/// println!("{trace}");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LoaderTrace {
+    events: Arc<Mutex<Vec<TraceEvent>>>,
+}
+
+impl LoaderTrace {
+    /// Create a new empty trace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<TraceEvent>> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn push(&self, event: TraceEvent) {
+        self.lock().push(event)
+    }
+
+    /// Wrap a resolver so that its attempts are recorded, named after its type.
+    pub fn resolver<R: Resolver>(&self, resolver: R) -> Traced<R> {
+        self.traced(type_name::<R>(), resolver)
+    }
+
+    /// Wrap a loader so that its attempts are recorded, named after its type.
+    pub fn loader<L: Loader>(&self, loader: L) -> Traced<L> {
+        self.traced(type_name::<L>(), loader)
+    }
+
+    /// Wrap a resolver or loader so that its attempts are recorded under the given name.
+    pub fn traced<T, N: Into<Cow<'static, str>>>(&self, name: N, inner: T) -> Traced<T> {
+        Traced {
+            inner,
+            name: name.into(),
+            trace: self.clone(),
+        }
+    }
+
+    /// Returns all recorded events in order.
+    pub fn events(&self) -> Vec<TraceEvent> {
+        self.lock().clone()
+    }
+
+    /// Remove all recorded events.
+    pub fn clear(&self) {
+        self.lock().clear()
+    }
+}
+
+impl fmt::Display for LoaderTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in self.lock().iter() {
+            writeln!(f, "{event}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A resolver or loader which records its attempts into a [`LoaderTrace`].
+pub struct Traced<T> {
+    inner: T,
+    name: Cow<'static, str>,
+    trace: LoaderTrace,
+}
+
+impl<T> Traced<T> {
+    /// Returns the wrapped resolver or loader.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: Resolver> Resolver for Traced<R> {
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let result = self.inner.resolve(ctx, base, name);
+        self.trace.push(TraceEvent::Resolve {
+            resolver: self.name.clone(),
+            base: base.into(),
+            name: name.into(),
+            result: match &result {
+                Ok(x) => Ok(x.clone()),
+                Err(e) => Err(e.to_string()),
+            },
+        });
+        result
+    }
+}
+
+impl<L: Loader> Loader for Traced<L> {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, name: &str) -> Result<Module<'js, Declared>> {
+        let result = self.inner.load(ctx, name);
+        self.trace.push(TraceEvent::Load {
+            loader: self.name.clone(),
+            name: name.into(),
+            result: match &result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            },
+        });
+        result
+    }
+}
@@ -1,13 +1,26 @@
-use crate::{loader::Resolver, Ctx, Error, Result};
+use crate::{
+    loader::{FileSystem, Resolver, StdFileSystem},
+    Ctx, Error, Result,
+};
+use core::fmt;
 use relative_path::{RelativePath, RelativePathBuf};
 
 /// The file module resolver
 ///
 /// This resolver can be used as the nested backing resolver in user-defined resolvers.
-#[derive(Debug)]
 pub struct FileResolver {
     paths: Vec<RelativePathBuf>,
     patterns: Vec<String>,
+    fs: Box<dyn FileSystem>,
+}
+
+impl fmt::Debug for FileResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileResolver")
+            .field("paths", &self.paths)
+            .field("patterns", &self.patterns)
+            .finish_non_exhaustive()
+    }
 }
 
 impl FileResolver {
@@ -77,9 +90,31 @@ impl FileResolver {
         self
     }
 
-    fn try_patterns(&self, path: &RelativePath) -> Option<RelativePathBuf> {
+    /// Set the file system in which modules are searched, defaults to [`StdFileSystem`].
+    pub fn set_file_system<F: FileSystem + 'static>(&mut self, fs: F) -> &mut Self {
+        self.fs = Box::new(fs);
+        self
+    }
+
+    /// Set the file system in which modules are searched, defaults to [`StdFileSystem`].
+    #[must_use]
+    pub fn with_file_system<F: FileSystem + 'static>(mut self, fs: F) -> Self {
+        self.set_file_system(fs);
+        self
+    }
+
+    fn is_file<P: AsRef<RelativePath>>(&self, path: P) -> bool {
+        self.fs.is_file(path.as_ref().as_str())
+    }
+
+    fn try_patterns(
+        &self,
+        path: &RelativePath,
+        tried: &mut Vec<RelativePathBuf>,
+    ) -> Option<RelativePathBuf> {
         if let Some(extension) = &path.extension() {
-            if !is_file(path) {
+            tried.push(path.to_relative_path_buf());
+            if !self.is_file(path) {
                 return None;
             }
             // check for known extensions
@@ -99,9 +134,10 @@ impl FileResolver {
             self.patterns.iter().find_map(|pattern| {
                 let name = pattern.replace("{}", path.file_name()?);
                 let file = path.with_file_name(name);
-                if is_file(&file) {
+                if self.is_file(&file) {
                     Some(file)
                 } else {
+                    tried.push(file);
                     None
                 }
             })
@@ -114,16 +150,18 @@ impl Default for FileResolver {
         Self {
             paths: vec![],
             patterns: vec!["{}.js".into()],
+            fs: Box::new(StdFileSystem),
         }
     }
 }
 
 impl Resolver for FileResolver {
     fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let mut tried = Vec::new();
         let path = if !name.starts_with('.') {
             self.paths.iter().find_map(|path| {
                 let path = path.join_normalized(name);
-                self.try_patterns(&path)
+                self.try_patterns(&path, &mut tried)
             })
         } else {
             let path = RelativePath::new(base);
@@ -132,14 +170,17 @@ impl Resolver for FileResolver {
             } else {
                 name.into()
             };
-            self.try_patterns(&path)
+            self.try_patterns(&path, &mut tried)
         }
-        .ok_or_else(|| Error::new_resolving(base, name))?;
+        .ok_or_else(|| {
+            if tried.is_empty() {
+                Error::new_resolving(base, name)
+            } else {
+                let tried = tried.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+                Error::new_resolving_message(base, name, format!("tried {}", tried.join(", ")))
+            }
+        })?;
 
         Ok(path.to_string())
     }
 }
-
-fn is_file<P: AsRef<RelativePath>>(path: P) -> bool {
-    path.as_ref().to_path(".").is_file()
-}
@@ -0,0 +1,93 @@
+use core::fmt;
+use std::{collections::HashMap, io};
+
+/// The file system used by the [`FileResolver`](super::FileResolver) and
+/// [`ScriptLoader`](super::ScriptLoader) to find and read modules.
+///
+/// Implementing this trait allows loading modules from places other than the file system of the
+/// host, like an archive or memory, which is useful on targets without a file system like
+/// `wasm32-unknown-unknown`.
+pub trait FileSystem {
+    /// Returns whether the path points to an existing file.
+    fn is_file(&self, path: &str) -> bool;
+
+    /// Read the contents of the file at the path.
+    fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+}
+
+/// The file system of the host, accessed through [`std::fs`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn is_file(&self, path: &str) -> bool {
+        std::path::Path::new(path).is_file()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+}
+
+/// A file system kept in memory.
+///
+/// ```
+/// # use rquickjs::{loader::{FileResolver, MemoryFileSystem, ScriptLoader}, Context, Module, Runtime};
+/// let fs = MemoryFileSystem::default()
+///     .with_file("lib/math.js", "export const two = 2;");
+/// let rt = Runtime::new().unwrap();
+/// rt.set_loader(
+///     FileResolver::default().with_path("lib").with_file_system(fs.clone()),
+///     ScriptLoader::default().with_file_system(fs),
+/// );
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     Module::evaluate(ctx.clone(), "main", "import { two } from 'math'; globalThis.two = two;")
+///         .unwrap()
+///         .finish::<()>()
+///         .unwrap();
+///     assert_eq!(ctx.globals().get::<_, i32>("two").unwrap(), 2);
+/// });
+/// ```
+#[derive(Default, Clone)]
+pub struct MemoryFileSystem {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryFileSystem {
+    /// Add a file
+    pub fn add_file<P: Into<String>, C: Into<Vec<u8>>>(
+        &mut self,
+        path: P,
+        content: C,
+    ) -> &mut Self {
+        self.files.insert(path.into(), content.into());
+        self
+    }
+
+    /// Add a file
+    #[must_use]
+    pub fn with_file<P: Into<String>, C: Into<Vec<u8>>>(mut self, path: P, content: C) -> Self {
+        self.add_file(path, content);
+        self
+    }
+}
+
+impl fmt::Debug for MemoryFileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.files.keys()).finish()
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn is_file(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+}
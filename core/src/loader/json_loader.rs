@@ -0,0 +1,73 @@
+use crate::{
+    loader::{util::check_extensions, FileSystem, Loader, StdFileSystem},
+    Ctx, Error, Module, Result,
+};
+use core::fmt;
+
+/// The JSON module loader
+///
+/// Loads JSON files as modules with the parsed value as their default export, see
+/// [`Module::declare_json`]. The resolver must resolve the files too, for example with
+/// [`FileResolver::with_pattern`](crate::loader::FileResolver::with_pattern)`("{}.json")`.
+///
+/// This loader can be used as the nested backing loader in user-defined loaders.
+pub struct JsonLoader {
+    extensions: Vec<String>,
+    fs: Box<dyn FileSystem>,
+}
+
+impl fmt::Debug for JsonLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLoader")
+            .field("extensions", &self.extensions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JsonLoader {
+    /// Add JSON file extension
+    pub fn add_extension<X: Into<String>>(&mut self, extension: X) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Add JSON file extension
+    #[must_use]
+    pub fn with_extension<X: Into<String>>(mut self, extension: X) -> Self {
+        self.add_extension(extension);
+        self
+    }
+
+    /// Set the file system from which JSON files are read, defaults to [`StdFileSystem`].
+    pub fn set_file_system<F: FileSystem + 'static>(&mut self, fs: F) -> &mut Self {
+        self.fs = Box::new(fs);
+        self
+    }
+
+    /// Set the file system from which JSON files are read, defaults to [`StdFileSystem`].
+    #[must_use]
+    pub fn with_file_system<F: FileSystem + 'static>(mut self, fs: F) -> Self {
+        self.set_file_system(fs);
+        self
+    }
+}
+
+impl Default for JsonLoader {
+    fn default() -> Self {
+        Self {
+            extensions: vec!["json".into()],
+            fs: Box::new(StdFileSystem),
+        }
+    }
+}
+
+impl Loader for JsonLoader {
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<Module<'js>> {
+        if !check_extensions(path, &self.extensions) {
+            return Err(Error::new_loading(path));
+        }
+
+        let source = self.fs.read(path)?;
+        Module::declare_json(ctx.clone(), path, source)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{module::ModuleDef, Ctx, Error, Module, Result};
-use std::{collections::HashMap, fmt::Debug};
+use core::fmt::Debug;
+use std::collections::HashMap;
 
 use super::Loader;
 
@@ -0,0 +1,68 @@
+use crate::{Ctx, Result};
+
+use super::Resolver;
+
+/// A resolver which rewrites module specifiers before passing them to another resolver.
+///
+/// Created with [`Resolver::rewrite`].
+#[derive(Debug)]
+pub struct Rewrite<R, F> {
+    inner: R,
+    rewrite: F,
+}
+
+impl<R, F> Rewrite<R, F> {
+    pub(crate) fn new(inner: R, rewrite: F) -> Self {
+        Self { inner, rewrite }
+    }
+
+    /// Returns the wrapped resolver.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, F> Resolver for Rewrite<R, F>
+where
+    R: Resolver,
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        match (self.rewrite)(base, name) {
+            Some(name) => self.inner.resolve(ctx, base, &name),
+            None => self.inner.resolve(ctx, base, name),
+        }
+    }
+}
+
+/// A resolver which reports the outcome of every resolution of another resolver.
+///
+/// Created with [`Resolver::inspect`].
+#[derive(Debug)]
+pub struct Inspect<R, F> {
+    inner: R,
+    inspect: F,
+}
+
+impl<R, F> Inspect<R, F> {
+    pub(crate) fn new(inner: R, inspect: F) -> Self {
+        Self { inner, inspect }
+    }
+
+    /// Returns the wrapped resolver.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, F> Resolver for Inspect<R, F>
+where
+    R: Resolver,
+    F: FnMut(&str, &str, &Result<String>),
+{
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let result = self.inner.resolve(ctx, base, name);
+        (self.inspect)(base, name, &result);
+        result
+    }
+}
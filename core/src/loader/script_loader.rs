@@ -1,14 +1,23 @@
 use crate::{
-    loader::{util::check_extensions, Loader},
+    loader::{util::check_extensions, FileSystem, Loader, StdFileSystem},
     Ctx, Error, Module, Result,
 };
+use core::fmt;
 
 /// The script module loader
 ///
 /// This loader can be used as the nested backing loader in user-defined loaders.
-#[derive(Debug)]
 pub struct ScriptLoader {
     extensions: Vec<String>,
+    fs: Box<dyn FileSystem>,
+}
+
+impl fmt::Debug for ScriptLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptLoader")
+            .field("extensions", &self.extensions)
+            .finish_non_exhaustive()
+    }
 }
 
 impl ScriptLoader {
@@ -24,12 +33,26 @@ impl ScriptLoader {
         self.add_extension(extension);
         self
     }
+
+    /// Set the file system from which scripts are read, defaults to [`StdFileSystem`].
+    pub fn set_file_system<F: FileSystem + 'static>(&mut self, fs: F) -> &mut Self {
+        self.fs = Box::new(fs);
+        self
+    }
+
+    /// Set the file system from which scripts are read, defaults to [`StdFileSystem`].
+    #[must_use]
+    pub fn with_file_system<F: FileSystem + 'static>(mut self, fs: F) -> Self {
+        self.set_file_system(fs);
+        self
+    }
 }
 
 impl Default for ScriptLoader {
     fn default() -> Self {
         Self {
             extensions: vec!["js".into()],
+            fs: Box::new(StdFileSystem),
         }
     }
 }
@@ -40,7 +63,7 @@ impl Loader for ScriptLoader {
             return Err(Error::new_loading(path));
         }
 
-        let source: Vec<_> = std::fs::read(path)?;
+        let source = self.fs.read(path)?;
         Module::declare(ctx.clone(), path, source)
     }
 }
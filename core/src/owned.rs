@@ -0,0 +1,169 @@
+use core::{fmt, mem::ManuallyDrop};
+
+use crate::{Array, Context, Ctx, Function, JsLifetime, Object, Persistent, Result, Value};
+
+/// An owned handle to a value which can be used outside of [`Context::with`].
+///
+/// Unlike a [`Persistent`] the handle keeps the context and runtime of the value alive and
+/// re-enters the context by itself when used or dropped, so it can be returned from
+/// [`Context::with`] and stored freely.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, OwnedFunction};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// let func = ctx.with(|ctx| {
+///     let func: Function = ctx.eval("a => a + 1").unwrap();
+///     OwnedFunction::new(&ctx, func).unwrap()
+/// });
+///
+/// let res: i32 = func.with(|_ctx, func| func.call((2,)).unwrap());
+/// assert_eq!(res, 3);
+/// ```
+///
+/// Using the handle from within [`Context::with`] of the same runtime on the same thread is
+/// allowed, the lock which is already held is reused.
+pub struct Owned<T> {
+    context: Context,
+    value: ManuallyDrop<Persistent<T>>,
+}
+
+/// An owned handle to a [`Value`].
+pub type OwnedValue = Owned<Value<'static>>;
+
+/// An owned handle to an [`Object`].
+pub type OwnedObject = Owned<Object<'static>>;
+
+/// An owned handle to a [`Function`].
+pub type OwnedFunction = Owned<Function<'static>>;
+
+/// An owned handle to an [`Array`].
+pub type OwnedArray = Owned<Array<'static>>;
+
+impl<T> Owned<T> {
+    /// Create an owned handle to the value.
+    ///
+    /// Returns `None` if the context belongs to an `AsyncRuntime`, which can't be entered
+    /// without awaiting.
+    pub fn new<'js, U>(ctx: &Ctx<'js>, value: U) -> Option<Self>
+    where
+        U: JsLifetime<'js, Changed<'static> = T>,
+    {
+        let context = ctx.context()?;
+        Some(Owned {
+            context,
+            value: ManuallyDrop::new(Persistent::save(ctx, value)),
+        })
+    }
+
+    /// Returns the context the value belongs to.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Enter the context of the value and use it.
+    pub fn with<F, R>(&self, f: F) -> R
+    where
+        T: JsLifetime<'static> + Clone,
+        F: for<'js> FnOnce(Ctx<'js>, T::Changed<'js>) -> R,
+    {
+        self.context.with(|ctx| {
+            let value = Persistent::clone(&self.value)
+                .restore(&ctx)
+                .expect("the context of an owned handle is from the same runtime");
+            f(ctx, value)
+        })
+    }
+
+    /// Get the value in a context of the same runtime, for example while using another handle.
+    ///
+    /// Returns [`Error::UnrelatedRuntime`](crate::Error::UnrelatedRuntime) if the context is of a
+    /// different runtime.
+    pub fn restore<'js>(&self, ctx: &Ctx<'js>) -> Result<T::Changed<'js>>
+    where
+        T: JsLifetime<'static> + Clone,
+    {
+        Persistent::clone(&self.value).restore(ctx)
+    }
+}
+
+impl<T: Clone> Clone for Owned<T> {
+    fn clone(&self) -> Self {
+        let value = self.context.with(|_| Persistent::clone(&self.value));
+        Owned {
+            context: self.context.clone(),
+            value: ManuallyDrop::new(value),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Owned<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Owned")
+            .field("value", &*self.value.value)
+            .finish()
+    }
+}
+
+impl<T> Drop for Owned<T> {
+    fn drop(&mut self) {
+        let value = &mut self.value;
+        // Freeing the value requires the runtime to be locked.
+        self.context.with(|_| unsafe { ManuallyDrop::drop(value) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn outlives_with() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+
+        let (obj, func) = ctx.with(|ctx| {
+            let obj: Object = ctx.eval("({ count: 1 })").unwrap();
+            let func: Function = ctx.eval("o => { o.count += 1; return o.count; }").unwrap();
+            (
+                OwnedObject::new(&ctx, obj).unwrap(),
+                OwnedFunction::new(&ctx, func).unwrap(),
+            )
+        });
+        // The handles keep the context alive.
+        drop(ctx);
+
+        let res: i32 = func.with(|ctx, func| {
+            let obj = obj.restore(&ctx).unwrap();
+            func.call((obj,)).unwrap()
+        });
+        assert_eq!(res, 2);
+
+        let copy = obj.clone();
+        drop(obj);
+        assert_eq!(copy.with(|_, obj| obj.get::<_, i32>("count").unwrap()), 2);
+
+        let value = copy.with(|ctx, obj| OwnedValue::new(&ctx, obj.into_value()).unwrap());
+        drop(copy);
+        assert!(value.with(|_, value| value.is_object()));
+        drop(value);
+        rt.run_gc();
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn async_runtime() {
+        let rt = AsyncRuntime::new().unwrap();
+        rt.block_on(async {
+            let ctx = AsyncContext::full(&rt).await.unwrap();
+            async_with!(ctx => |ctx| {
+                let value = Value::new_undefined(ctx.clone());
+                assert!(OwnedValue::new(&ctx, value).is_none());
+            })
+            .await
+        });
+    }
+}
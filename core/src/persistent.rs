@@ -1,8 +1,14 @@
-use crate::{qjs, Ctx, Error, FromJs, IntoJs, JsLifetime, Result, Value};
+use crate::{
+    qjs,
+    runtime::{opaque::Opaque, OutstandingPersistent},
+    Ctx, Error, FromJs, IntoJs, JsLifetime, Result, Value,
+};
 
-use std::{
-    fmt,
+use core::{
+    any, fmt,
+    hash::{Hash, Hasher},
     mem::{self, ManuallyDrop},
+    panic::Location,
 };
 
 /// The wrapper for JS values to keep it from GC
@@ -30,20 +36,44 @@ use std::{
 /// It is an error (`Error::UnrelatedRuntime`) to restore the `Persistent` in a
 /// context who isn't part of the original `Runtime`.
 ///
-/// NOTE: Be careful and ensure that no persistent links outlives the runtime,
-/// otherwise Runtime will abort the process when dropped.
-///
-#[derive(Eq, PartialEq, Hash)]
+/// NOTE: Be careful and ensure that no persistent links outlives the runtime. Dropping the
+/// runtime while handles are alive leaks it and reports the handles to the
+/// [leak hook](crate::Runtime::set_leak_hook), [`Runtime::shutdown`](crate::Runtime::shutdown)
+/// returns them as an error instead. Where the handles were created is only recorded with the
+/// `leak-detection` feature.
 pub struct Persistent<T> {
     pub(crate) rt: *mut qjs::JSRuntime,
-    pub(crate) value: T,
+    pub(crate) value: ManuallyDrop<T>,
+    id: usize,
 }
 
 impl<T: Clone> Clone for Persistent<T> {
+    #[track_caller]
     fn clone(&self) -> Self {
-        Persistent {
-            rt: self.rt,
-            value: self.value.clone(),
+        Self::new(self.rt, T::clone(&self.value))
+    }
+}
+
+impl<T: PartialEq> PartialEq for Persistent<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rt == other.rt && self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Persistent<T> {}
+
+impl<T: Hash> Hash for Persistent<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rt.hash(state);
+        self.value.hash(state);
+    }
+}
+
+impl<T> Drop for Persistent<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.value);
+            Opaque::from_runtime_ptr(self.rt).unregister_persistent(self.id);
         }
     }
 }
@@ -55,12 +85,27 @@ where
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Persistent")
             .field("rt", &self.rt)
-            .field("value", &self.value)
+            .field("value", &*self.value)
             .finish()
     }
 }
 
 impl<T> Persistent<T> {
+    #[track_caller]
+    fn new(rt: *mut qjs::JSRuntime, value: T) -> Self {
+        let id = unsafe {
+            Opaque::from_runtime_ptr(rt).register_persistent(OutstandingPersistent {
+                type_name: any::type_name::<T>(),
+                location: Location::caller(),
+            })
+        };
+        Persistent {
+            rt,
+            value: ManuallyDrop::new(value),
+            id,
+        }
+    }
+
     unsafe fn outlive_transmute<'from, 'to, U>(t: U) -> U::Changed<'to>
     where
         U: JsLifetime<'from>,
@@ -85,6 +130,7 @@ impl<T> Persistent<T> {
     }
 
     /// Save the value of an arbitrary type
+    #[track_caller]
     pub fn save<'js>(ctx: &Ctx<'js>, val: T) -> Persistent<T::Changed<'static>>
     where
         T: JsLifetime<'js>,
@@ -92,10 +138,7 @@ impl<T> Persistent<T> {
         let outlived: T::Changed<'static> =
             unsafe { Self::outlive_transmute::<'js, 'static, T>(val) };
         let ptr = unsafe { qjs::JS_GetRuntime(ctx.as_ptr()) };
-        Persistent {
-            rt: ptr,
-            value: outlived,
-        }
+        Persistent::new(ptr, outlived)
     }
 
     /// Restore the value of an arbitrary type
@@ -107,7 +150,12 @@ impl<T> Persistent<T> {
         if self.rt != ctx_runtime_ptr {
             return Err(Error::UnrelatedRuntime);
         }
-        Ok(unsafe { Self::outlive_transmute::<'static, 'js, T>(self.value) })
+        let mut this = ManuallyDrop::new(self);
+        unsafe {
+            Opaque::from_runtime_ptr(this.rt).unregister_persistent(this.id);
+            let value = ManuallyDrop::take(&mut this.value);
+            Ok(Self::outlive_transmute::<'static, 'js, T>(value))
+        }
     }
 }
 
@@ -154,6 +202,24 @@ mod test {
         });
     }
 
+    #[test]
+    fn outlives_runtime() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let object = ctx.with(|ctx| Persistent::save(&ctx, Object::new(ctx.clone()).unwrap()));
+        drop(ctx);
+
+        let error = rt.shutdown().unwrap_err();
+        assert_eq!(error.outstanding, 1);
+        #[cfg(feature = "leak-detection")]
+        assert!(error
+            .to_string()
+            .contains("handles:\n  rquickjs_core::value::object::Object"));
+        // Leaks the runtime instead of panicking.
+        drop(error.runtime);
+        drop(object);
+    }
+
     #[test]
     fn different_context() {
         let rt1 = Runtime::new().unwrap();
@@ -1,21 +1,28 @@
-use std::{
-    error::Error as StdError,
-    ffi::{CString, FromBytesWithNulError, NulError},
+use alloc::{
+    ffi::{CString, NulError},
+    string::FromUtf8Error,
+};
+use core::{
+    ffi::FromBytesWithNulError,
     fmt::{self, Display, Formatter, Result as FmtResult},
-    io::Error as IoError,
-    panic,
     panic::UnwindSafe,
     str::{FromStr, Utf8Error},
-    string::FromUtf8Error,
 };
+#[cfg(feature = "std")]
+use std::{error::Error as StdError, io::Error as IoError};
 
 #[cfg(feature = "futures")]
 use crate::context::AsyncContext;
 #[cfg(feature = "array-buffer")]
 use crate::value::array_buffer::AsSliceError;
 use crate::{
-    atom::PredefinedAtom, qjs, runtime::UserDataError, value::exception::ERROR_FORMAT_STR, Context,
-    Ctx, Exception, Object, StdResult, StdString, Type, Value,
+    atom::PredefinedAtom,
+    context::ErrorPolicy,
+    qjs,
+    runtime::{MetricsCounters, PanicAction, UserDataError},
+    util,
+    value::exception::ERROR_FORMAT_STR,
+    Context, Ctx, Exception, Object, StdResult, StdString, Type, Value,
 };
 
 /// Result type used throughout the library.
@@ -24,12 +31,19 @@ pub type Result<T> = StdResult<T, Error>;
 /// Result type containing an the JavaScript exception if there was one.
 pub type CaughtResult<'js, T> = StdResult<T, CaughtError<'js>>;
 
-#[derive(Debug)]
+/// The reason borrowing a class instance or a Rust function failed.
+///
+/// Matches must have a wildcard arm since more reasons may be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum BorrowError {
     /// The object was not writable
     NotWritable,
     /// The object was already borrowed in a way that prevents borrowing again.
     AlreadyBorrowed,
+    /// The object was already borrowed mutably, for example by a method which called back into
+    /// JavaScript which then tried to use the object again.
+    AlreadyBorrowedMut,
     /// The object could only be used once and was used already.
     AlreadyUsed,
 }
@@ -41,6 +55,9 @@ impl fmt::Display for BorrowError {
             BorrowError::AlreadyBorrowed => {
                 write!(f, "can't borrow a value as it is already borrowed")
             }
+            BorrowError::AlreadyBorrowedMut => {
+                write!(f, "can't borrow a value as it is already borrowed mutably")
+            }
             BorrowError::AlreadyUsed => {
                 write!(
                     f,
@@ -51,6 +68,7 @@ impl fmt::Display for BorrowError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for BorrowError {}
 
 /// Error type of the library.
@@ -73,6 +91,7 @@ pub enum Error {
     /// String from rquickjs was not UTF-8
     Utf8(Utf8Error),
     /// An io error
+    #[cfg(feature = "std")]
     Io(IoError),
     /// An error happened while trying to borrow a Rust class object.
     ClassBorrow(BorrowError),
@@ -105,6 +124,16 @@ pub enum Error {
         expected: usize,
         given: usize,
     },
+    /// Error converting an argument of a Rust function called from JavaScript.
+    ///
+    /// The fields are the ones of [`Error::FromJs`] together with the zero based `index` of the
+    /// argument, `from` holds the JavaScript name of the type like `number` or `boolean`.
+    FromJsArg {
+        index: usize,
+        from: &'static str,
+        to: &'static str,
+        message: Option<StdString>,
+    },
     #[cfg(feature = "loader")]
     /// Error when resolving js module
     Resolving {
@@ -122,9 +151,22 @@ pub enum Error {
     AsSlice(AsSliceError),
     /// Error when restoring a Persistent in a runtime other than the original runtime.
     UnrelatedRuntime,
+    /// A class was used or registered under a name which another type registered with
+    /// [`Runtime::register_class`](crate::Runtime::register_class).
+    ClassConflict {
+        name: &'static str,
+    },
     /// An error returned by a blocked on promise if block on the promise would result in a dead
     /// lock.
     WouldBlock,
+    /// The other side of a [`channel`](crate::channel) was dropped.
+    ChannelDisconnected,
+    /// An evaluation was interrupted because it exceeded its time limit, see
+    /// [`Ctx::eval_timeout`].
+    Timeout,
+    /// A long running operation of Rust, like [`Array::iter_chunks`](crate::Array::iter_chunks),
+    /// was stopped by the interrupt handler.
+    Interrupted,
     /// An error related to userdata
     UserData(UserDataError<()>),
     /// An error from QuickJS from which the specifics are unknown.
@@ -242,14 +284,31 @@ impl Error {
         }
     }
 
-    /// Returns whether the error is a from JS conversion error
+    /// Returns whether the error is a from JS conversion error, also of a function argument
     pub fn is_from_js(&self) -> bool {
-        matches!(self, Self::FromJs { .. })
+        matches!(self, Self::FromJs { .. } | Self::FromJsArg { .. })
     }
 
     /// Returns whether the error is a from JS to JS type conversion error
     pub fn is_from_js_to_js(&self) -> bool {
-        matches!(self, Self::FromJs { to, .. } if Type::from_str(to).is_ok())
+        matches!(self, Self::FromJs { to, .. } | Self::FromJsArg { to, .. } if Type::from_str(to).is_ok())
+    }
+
+    /// Attach the index of a function argument to a from JS conversion error.
+    ///
+    /// The type of the argument is reported with the name JavaScript uses for it, like `number`
+    /// instead of `int`, as the error is thrown to the script calling the function. Other errors
+    /// are returned unchanged.
+    pub(crate) fn with_arg_index(self, index: usize) -> Self {
+        match self {
+            Error::FromJs { from, to, message } => Error::FromJsArg {
+                index,
+                from: js_type_name(from),
+                to,
+                message,
+            },
+            error => error,
+        }
     }
 
     /// Returns whether the error is an into JS conversion error
@@ -273,6 +332,36 @@ impl Error {
         unsafe { CString::from_vec_unchecked(message) }
     }
 
+    /// Returns the message without host details used by [`ErrorPolicy::Redacted`], `None` if the
+    /// full message doesn't contain any.
+    fn redacted_message(&self) -> Option<StdString> {
+        Some(match self {
+            Error::FromJs { from, .. } => format!("Error converting from js '{from}'"),
+            Error::FromJsArg { index, from, .. } => {
+                format!("argument {}: unexpected {from}", index + 1)
+            }
+            Error::IntoJs { to, .. } => format!("Error converting into js '{to}'"),
+            #[cfg(feature = "std")]
+            Error::Io(_) => "IO Error".into(),
+            #[cfg(feature = "loader")]
+            Error::Resolving { name, .. } => format!("Error resolving module '{name}'"),
+            #[cfg(feature = "loader")]
+            Error::Loading { .. } => "Error loading module".into(),
+            _ => return None,
+        })
+    }
+
+    /// The message of the exception thrown for this error, following the error policy of the
+    /// context.
+    fn to_js_cstring(&self, ctx: &Ctx) -> CString {
+        if ctx.error_policy() == ErrorPolicy::Redacted {
+            if let Some(message) = self.redacted_message() {
+                return CString::new(message).unwrap_or_default();
+            }
+        }
+        self.to_cstring()
+    }
+
     /// Throw an exception
     pub(crate) fn throw(&self, ctx: &Ctx) -> qjs::JSValue {
         use Error::*;
@@ -282,10 +371,13 @@ impl Error {
             InvalidString(_)
             | Utf8(_)
             | FromJs { .. }
+            | FromJsArg { .. }
             | IntoJs { .. }
             | TooManyArgs { .. }
-            | MissingArgs { .. } => {
-                let message = self.to_cstring();
+            | MissingArgs { .. }
+            | ClassBorrow(_)
+            | FunctionBorrow(_) => {
+                let message = self.to_js_cstring(ctx);
                 unsafe {
                     qjs::JS_ThrowTypeError(
                         ctx.as_ptr(),
@@ -296,7 +388,7 @@ impl Error {
             }
             #[cfg(feature = "array-buffer")]
             AsSlice(_) => {
-                let message = self.to_cstring();
+                let message = self.to_js_cstring(ctx);
                 unsafe {
                     qjs::JS_ThrowReferenceError(
                         ctx.as_ptr(),
@@ -307,7 +399,7 @@ impl Error {
             }
             #[cfg(feature = "loader")]
             Resolving { .. } | Loading { .. } => {
-                let message = self.to_cstring();
+                let message = self.to_js_cstring(ctx);
                 unsafe {
                     qjs::JS_ThrowReferenceError(
                         ctx.as_ptr(),
@@ -317,7 +409,7 @@ impl Error {
                 }
             }
             Unknown => {
-                let message = self.to_cstring();
+                let message = self.to_js_cstring(ctx);
                 unsafe {
                     qjs::JS_ThrowInternalError(
                         ctx.as_ptr(),
@@ -326,7 +418,8 @@ impl Error {
                     )
                 }
             }
-            error => {
+            _ => {
+                let message = self.to_js_cstring(ctx);
                 unsafe {
                     let value = qjs::JS_NewError(ctx.as_ptr());
                     if qjs::JS_VALUE_GET_NORM_TAG(value) == qjs::JS_TAG_EXCEPTION {
@@ -335,7 +428,7 @@ impl Error {
                         return value;
                     }
                     let obj = Object::from_js_value(ctx.clone(), value);
-                    match obj.set(PredefinedAtom::Message, error.to_string()) {
+                    match obj.set(PredefinedAtom::Message, message.to_string_lossy().as_ref()) {
                         Ok(_) => {}
                         Err(Error::Exception) => return qjs::JS_EXCEPTION,
                         Err(e) => {
@@ -350,8 +443,22 @@ impl Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl StdError for Error {}
 
+/// Returns the name of a type like `typeof` reports it, keeping `null` apart from objects.
+fn js_type_name(from: &'static str) -> &'static str {
+    match Type::from_str(from) {
+        Ok(Type::Uninitialized) => "undefined",
+        Ok(Type::Bool) => "boolean",
+        Ok(Type::Int | Type::Float) => "number",
+        Ok(Type::BigInt) => "bigint",
+        Ok(Type::Constructor) => "function",
+        Ok(Type::Array | Type::Promise | Type::Exception) => "object",
+        _ => from,
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
@@ -389,6 +496,20 @@ impl Display for Error {
                     }
                 }
             }
+            Error::FromJsArg {
+                index,
+                from,
+                to,
+                message,
+            } => {
+                write!(f, "argument {}: expected {to}, got {from}", index + 1)?;
+                if let Some(message) = message {
+                    if !message.is_empty() {
+                        ": ".fmt(f)?;
+                        message.fmt(f)?;
+                    }
+                }
+            }
             Error::IntoJs { from, to, message } => {
                 "Error converting from '".fmt(f)?;
                 from.fmt(f)?;
@@ -447,6 +568,7 @@ impl Display for Error {
                     }
                 }
             }
+            #[cfg(feature = "std")]
             Error::Io(error) => {
                 "IO Error: ".fmt(f)?;
                 error.fmt(f)?;
@@ -455,11 +577,21 @@ impl Display for Error {
                 "Error borrowing class: ".fmt(f)?;
                 x.fmt(f)?;
             }
+            Error::FunctionBorrow(BorrowError::AlreadyBorrowed) => {
+                "Function already borrowed: a `MutFn` can't be called again while it is running"
+                    .fmt(f)?
+            }
+            Error::FunctionBorrow(BorrowError::AlreadyUsed) => {
+                "Function already consumed: a `OnceFn` can only be called once".fmt(f)?
+            }
             Error::FunctionBorrow(x) => {
                 "Error borrowing function: ".fmt(f)?;
                 x.fmt(f)?;
             }
             Error::WouldBlock => "Error blocking on a promise resulted in a dead lock".fmt(f)?,
+            Error::ChannelDisconnected => "The other side of the channel was dropped".fmt(f)?,
+            Error::Timeout => "Evaluation exceeded its time limit".fmt(f)?,
+            Error::Interrupted => "Interrupted by the interrupt handler".fmt(f)?,
             Error::UserData(x) => x.fmt(f)?,
             #[cfg(feature = "array-buffer")]
             Error::AsSlice(x) => {
@@ -467,6 +599,11 @@ impl Display for Error {
                 x.fmt(f)?;
             }
             Error::UnrelatedRuntime => "Restoring Persistent in an unrelated runtime".fmt(f)?,
+            Error::ClassConflict { name } => {
+                "Class `".fmt(f)?;
+                name.fmt(f)?;
+                "` was registered by a different type".fmt(f)?;
+            }
         }
         Ok(())
     }
@@ -488,7 +625,13 @@ from_impls! {
     NulError => InvalidString,
     FromBytesWithNulError => InvalidCStr,
     Utf8Error => Utf8,
-    IoError => Io,
+}
+
+#[cfg(feature = "std")]
+impl From<IoError> for Error {
+    fn from(error: IoError) -> Self {
+        Error::Io(error)
+    }
 }
 
 impl From<FromUtf8Error> for Error {
@@ -533,6 +676,7 @@ impl<'js> Display for CaughtError<'js> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'js> StdError for CaughtError<'js> {}
 
 impl<'js> CaughtError<'js> {
@@ -674,13 +818,50 @@ impl<'js> Ctx<'js> {
     where
         F: FnOnce() -> qjs::JSValue + UnwindSafe,
     {
-        unsafe {
-            match panic::catch_unwind(f) {
-                Ok(x) => x,
-                Err(e) => {
-                    self.get_opaque().set_panic(e);
-                    qjs::JS_Throw(self.as_ptr(), qjs::JS_MKVAL(qjs::JS_TAG_EXCEPTION, 0))
+        self.catch_panic(f, qjs::JS_MKVAL(qjs::JS_TAG_EXCEPTION, 0))
+    }
+
+    /// Run a callback called from QuickJS, returning `exception` if it panicked.
+    ///
+    /// The panic is handled as decided by the panic hook of the runtime, by default it is stored
+    /// and resumed once control returns to Rust, see [`Ctx::raise_exception`].
+    pub(crate) fn catch_panic<F, R>(&self, f: F, exception: R) -> R
+    where
+        F: FnOnce() -> R + UnwindSafe,
+    {
+        match util::catch_unwind(f) {
+            Ok(x) => x,
+            Err(e) => {
+                unsafe {
+                    let opaque = self.get_opaque();
+                    match opaque.panic_action(&*e) {
+                        PanicAction::Resume => {
+                            opaque.set_panic(e);
+                            // JavaScript must not be able to catch the error or the panic would
+                            // never reach Rust. Without the bundled sources the error can't be
+                            // made uncatchable, `handle_exception` resumes the panic instead.
+                            let error = qjs::JS_NewError(self.as_ptr());
+                            #[cfg(not(feature = "system"))]
+                            qjs::rquickjs_set_uncatchable_error(self.as_ptr(), error, 1);
+                            qjs::JS_Throw(self.as_ptr(), error);
+                        }
+                        PanicAction::Throw => {
+                            // The panic message can name host details just like error messages.
+                            let message = if self.error_policy() == ErrorPolicy::Redacted {
+                                "panicked".into()
+                            } else if let Some(x) = e.downcast_ref::<&str>() {
+                                format!("panicked: {x}")
+                            } else if let Some(x) = e.downcast_ref::<StdString>() {
+                                format!("panicked: {x}")
+                            } else {
+                                "panicked: Box<dyn Any>".into()
+                            };
+                            Exception::throw_internal(self, &message);
+                        }
+                        PanicAction::Abort => util::abort(),
+                    }
                 }
+                exception
             }
         }
     }
@@ -692,11 +873,19 @@ impl<'js> Ctx<'js> {
     /// Assumes to have ownership of the [`JSValue`]
     pub(crate) unsafe fn handle_exception(&self, js_val: qjs::JSValue) -> Result<qjs::JSValue> {
         if qjs::JS_VALUE_GET_NORM_TAG(js_val) != qjs::JS_TAG_EXCEPTION {
+            // Scripts can catch the error thrown for a panic with the `system` feature, which
+            // mustn't swallow the panic.
+            #[cfg(feature = "system")]
+            if let Some(x) = self.get_opaque().take_panic() {
+                qjs::JS_FreeValue(self.as_ptr(), js_val);
+                util::resume_unwind(x)
+            }
             Ok(js_val)
         } else {
             if let Some(x) = self.get_opaque().take_panic() {
-                panic::resume_unwind(x)
+                util::resume_unwind(x)
             }
+            crate::runtime::record_metric(self.as_ptr(), MetricsCounters::exception);
             Err(Error::Exception)
         }
     }
@@ -707,8 +896,9 @@ impl<'js> Ctx<'js> {
         // Safety
         unsafe {
             if let Some(x) = self.get_opaque().take_panic() {
-                panic::resume_unwind(x)
+                util::resume_unwind(x)
             }
+            crate::runtime::record_metric(self.as_ptr(), MetricsCounters::exception);
             Error::Exception
         }
     }
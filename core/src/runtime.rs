@@ -1,8 +1,17 @@
 //! QuickJS runtime related types.
 
+use crate::{Ctx, Result, Value};
+use alloc::boxed::Box;
+use core::any::Any;
+
 mod base;
+mod builder;
+mod metrics;
 pub(crate) mod opaque;
 pub(crate) mod raw;
+mod shutdown;
+#[cfg(all(feature = "std", not(feature = "system")))]
+mod timezone;
 mod userdata;
 
 #[cfg(feature = "futures")]
@@ -12,7 +21,18 @@ pub(crate) mod schedular;
 #[cfg(feature = "futures")]
 mod spawner;
 
+#[cfg(feature = "parallel")]
+mod pool;
+
 pub use base::{Runtime, WeakRuntime};
+pub use builder::RuntimeBuilder;
+pub(crate) use metrics::{record as record_metric, MetricsCounters};
+pub use metrics::{ContextMetrics, Metrics};
+pub use shutdown::{OutstandingPersistent, ShutdownError};
+#[cfg(all(feature = "std", not(feature = "system")))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
+pub use timezone::{reset_timezone_offset, set_timezone_offset};
+pub(crate) use userdata::UserDataMap;
 pub use userdata::{UserDataError, UserDataGuard};
 
 #[cfg(feature = "futures")]
@@ -20,6 +40,10 @@ pub(crate) use r#async::InnerRuntime;
 #[cfg(feature = "futures")]
 pub use r#async::{AsyncRuntime, AsyncWeakRuntime};
 
+#[cfg(feature = "parallel")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "parallel")))]
+pub use pool::{PoolError, RuntimePool};
+
 /// The type of the interrupt handler.
 #[cfg(not(feature = "parallel"))]
 pub type InterruptHandler = Box<dyn FnMut() -> bool + 'static>;
@@ -27,5 +51,63 @@ pub type InterruptHandler = Box<dyn FnMut() -> bool + 'static>;
 #[cfg(feature = "parallel")]
 pub type InterruptHandler = Box<dyn FnMut() -> bool + Send + 'static>;
 
+/// The type of the hooks called when a context is created.
+#[cfg(not(feature = "parallel"))]
+pub type ContextHook = Box<dyn for<'js> FnMut(&Ctx<'js>) -> Result<()> + 'static>;
+/// The type of the hooks called when a context is created.
+#[cfg(feature = "parallel")]
+pub type ContextHook = Box<dyn for<'js> FnMut(&Ctx<'js>) -> Result<()> + Send + 'static>;
+
+/// The type of the tracker called when a promise is rejected without a handler.
+///
+/// Receives the promise, the rejection reason and whether a handler was attached to a promise
+/// which was previously reported as unhandled.
+#[cfg(not(feature = "parallel"))]
+pub type RejectionTracker =
+    Box<dyn for<'js> FnMut(&Ctx<'js>, Value<'js>, Value<'js>, bool) + 'static>;
+/// The type of the tracker called when a promise is rejected without a handler.
+///
+/// Receives the promise, the rejection reason and whether a handler was attached to a promise
+/// which was previously reported as unhandled.
+#[cfg(feature = "parallel")]
+pub type RejectionTracker =
+    Box<dyn for<'js> FnMut(&Ctx<'js>, Value<'js>, Value<'js>, bool) + Send + 'static>;
+
+/// What happens with a panic caught where Rust code is called from QuickJS.
+///
+/// Returned by the [`PanicHook`] set with [`Runtime::set_panic_hook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PanicAction {
+    /// Raise an uncatchable exception and continue the panic once control returns to the Rust
+    /// code which called into JavaScript. This is the default.
+    ///
+    /// With the `system` feature scripts can catch the exception, the panic then continues as soon
+    /// as the call into JavaScript returns, whether it returns a value or an exception.
+    Resume,
+    /// Drop the panic and throw a catchable `InternalError` with the panic message instead.
+    ///
+    /// In a context with the [`Redacted`](crate::context::ErrorPolicy::Redacted) error policy the
+    /// message is left out.
+    Throw,
+    /// Abort the process.
+    Abort,
+}
+
+/// The type of the hook deciding what happens with a panic caught in a callback.
+#[cfg(not(feature = "parallel"))]
+pub type PanicHook = Box<dyn Fn(&(dyn Any + Send)) -> PanicAction + 'static>;
+/// The type of the hook deciding what happens with a panic caught in a callback.
+#[cfg(feature = "parallel")]
+pub type PanicHook = Box<dyn Fn(&(dyn Any + Send)) -> PanicAction + Send + 'static>;
+
+/// The type of the hook called with the outstanding handles when a runtime which is still in use
+/// is dropped and leaked.
+#[cfg(not(feature = "parallel"))]
+pub type LeakHook = Box<dyn FnOnce(ShutdownError<()>) + 'static>;
+/// The type of the hook called with the outstanding handles when a runtime which is still in use
+/// is dropped and leaked.
+#[cfg(feature = "parallel")]
+pub type LeakHook = Box<dyn FnOnce(ShutdownError<()>) + Send + 'static>;
+
 /// A struct with information about the runtimes memory usage.
 pub type MemoryUsage = crate::qjs::JSMemoryUsage;
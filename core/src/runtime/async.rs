@@ -1,9 +1,13 @@
-use std::{
+use alloc::{
     ffi::CString,
+    sync::{Arc, Weak},
+};
+use core::{
+    any::TypeId,
+    future::Future,
     ptr::NonNull,
     result::Result as StdResult,
-    sync::{Arc, Weak},
-    task::Poll,
+    task::{Context, Poll, Waker},
 };
 
 #[cfg(feature = "parallel")]
@@ -12,15 +16,20 @@ use std::sync::mpsc::{self, Receiver, Sender};
 use async_lock::Mutex;
 
 use super::{
-    opaque::Opaque, raw::RawRuntime, schedular::SchedularPoll, spawner::DriveFuture,
-    InterruptHandler, MemoryUsage,
+    opaque::Opaque, raw::RawRuntime, schedular::SchedularPoll, spawner::DriveFuture, ContextHook,
+    InterruptHandler, MemoryUsage, Metrics, PanicHook, RejectionTracker, ShutdownError,
 };
 #[cfg(feature = "allocator")]
 use crate::allocator::Allocator;
 #[cfg(feature = "loader")]
 use crate::loader::{Loader, Resolver};
 use crate::{
-    context::AsyncContext, result::AsyncJobException, util::ManualPoll, Ctx, Exception, Result,
+    class::{ClassId, JsClass},
+    context::AsyncContext,
+    convert::NumberConversion,
+    result::AsyncJobException,
+    util::{ManualPoll, ThreadWake},
+    Ctx, Exception, Result,
 };
 #[cfg(feature = "parallel")]
 use crate::{
@@ -154,6 +163,34 @@ impl AsyncRuntime {
         })
     }
 
+    /// Create a new runtime using specified allocator which attributes memory to contexts.
+    ///
+    /// See [`Runtime::new_with_accounting`](crate::Runtime::new_with_accounting) for details.
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    #[cfg(feature = "allocator")]
+    // Annoying false positive clippy lint
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new_with_accounting<A>(allocator: A) -> Result<Self>
+    where
+        A: Allocator + 'static,
+    {
+        let opaque = Opaque::with_spawner();
+        let runtime = unsafe { RawRuntime::new_with_accounting(opaque, allocator) }?;
+
+        #[cfg(feature = "parallel")]
+        let (drop_send, drop_recv) = mpsc::channel();
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(InnerRuntime {
+                runtime,
+                #[cfg(feature = "parallel")]
+                drop_recv,
+            })),
+            #[cfg(feature = "parallel")]
+            drop_send,
+        })
+    }
+
     /// Get weak ref to runtime
     pub fn weak(&self) -> AsyncWeakRuntime {
         AsyncWeakRuntime {
@@ -177,6 +214,31 @@ impl AsyncRuntime {
         }
     }
 
+    /// Set a closure which is called when a promise is rejected without a handler.
+    ///
+    /// See [`Runtime::set_rejection_tracker`](crate::Runtime::set_rejection_tracker).
+    pub async fn set_rejection_tracker(&self, tracker: Option<RejectionTracker>) {
+        unsafe {
+            self.inner
+                .lock()
+                .await
+                .runtime
+                .set_rejection_tracker(tracker);
+        }
+    }
+
+    /// Set a hook deciding what happens with a panic in Rust code called from JavaScript.
+    ///
+    /// See [`Runtime::set_panic_hook`](crate::Runtime::set_panic_hook).
+    pub async fn set_panic_hook(&self, hook: Option<PanicHook>) {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .set_panic_hook(hook)
+    }
+
     /// Set the module loader
     #[cfg(feature = "loader")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
@@ -191,6 +253,10 @@ impl AsyncRuntime {
     }
 
     /// Set the info of the runtime
+    ///
+    /// The info is shown in the memory usage dumps of QuickJS, hosts running several runtimes can
+    /// use it to tell them apart. To label the scripts of a runtime use
+    /// [`EvalOptions::filename`](crate::context::EvalOptions::filename).
     pub async fn set_info<S: Into<Vec<u8>>>(&self, info: S) -> Result<()> {
         let string = CString::new(info)?;
         unsafe {
@@ -220,6 +286,30 @@ impl AsyncRuntime {
         }
     }
 
+    /// Set the maximum depth of nested arrays and objects converted by [`FromJs`](crate::FromJs).
+    ///
+    /// See [`Runtime::set_max_conversion_depth`](crate::Runtime::set_max_conversion_depth).
+    pub async fn set_max_conversion_depth(&self, depth: usize) {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .set_max_conversion_depth(depth)
+    }
+
+    /// Set the options for converting JavaScript numbers to Rust numbers.
+    ///
+    /// See [`NumberConversion`](crate::convert::NumberConversion) for the default behavior.
+    pub async fn set_number_conversion(&self, options: NumberConversion) {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .replace_number_conversion(options);
+    }
+
     /// Set a memory threshold for garbage collection.
     pub async fn set_gc_threshold(&self, threshold: usize) {
         unsafe {
@@ -246,6 +336,47 @@ impl AsyncRuntime {
         unsafe { self.inner.lock().await.runtime.memory_usage() }
     }
 
+    /// Get the metrics collected by the runtime
+    pub async fn metrics(&self) -> Metrics {
+        unsafe { self.inner.lock().await.runtime.metrics() }
+    }
+
+    /// Register a class with the runtime, reserving its name for the type.
+    ///
+    /// See [`Runtime::register_class`](crate::Runtime::register_class) for details.
+    pub async fn register_class<C>(&self) -> Result<ClassId>
+    where
+        C: JsClass<'static>,
+    {
+        let id = TypeId::of::<C::Changed<'static>>();
+        let lock = self.inner.lock().await;
+        lock.runtime
+            .get_opaque()
+            .register_class(C::NAME, id)
+            .map(ClassId)
+    }
+
+    /// Returns the id of the class registered under the name, if any.
+    pub async fn class_id(&self, name: &str) -> Option<ClassId> {
+        let lock = self.inner.lock().await;
+        lock.runtime
+            .get_opaque()
+            .registered_class(name)
+            .map(ClassId)
+    }
+
+    /// Register a hook which is called with every context created in this runtime.
+    ///
+    /// See [`Runtime::on_context_created`](crate::Runtime::on_context_created) for details.
+    pub async fn on_context_created(&self, hook: ContextHook) {
+        self.inner
+            .lock()
+            .await
+            .runtime
+            .get_opaque()
+            .add_context_created_hook(hook)
+    }
+
     /// Test for pending jobs
     ///
     /// Returns true when at least one job is pending.
@@ -290,6 +421,27 @@ impl AsyncRuntime {
         f.await
     }
 
+    /// Wait for the spawned futures to finish and shut down the runtime, failing if its values
+    /// are still kept alive by [`Persistent`](crate::Persistent) handles.
+    ///
+    /// See [`Runtime::shutdown`](crate::Runtime::shutdown).
+    pub async fn shutdown(self) -> StdResult<(), ShutdownError<AsyncRuntime>> {
+        self.idle().await;
+        let lock = self.inner.lock().await;
+        let opaque = lock.runtime.get_opaque();
+        let outstanding = opaque.persistent_count();
+        if outstanding == 0 {
+            return Ok(());
+        }
+        let persistents = opaque.outstanding_persistents();
+        drop(lock);
+        Err(ShutdownError {
+            runtime: self,
+            outstanding,
+            persistents,
+        })
+    }
+
     /// Run all futures and jobs in the runtime until all are finished.
     #[inline]
     pub async fn idle(&self) {
@@ -342,6 +494,29 @@ impl AsyncRuntime {
     pub fn drive(&self) -> DriveFuture {
         DriveFuture::new(self.weak())
     }
+
+    /// Run a future to completion on the current thread, driving the futures spawned inside the
+    /// runtime while it waits.
+    ///
+    /// This is a minimal single threaded executor for hosts without an async runtime, like a
+    /// plugin sandbox on WebAssembly. It blocks the current thread until the future completes, so
+    /// it must not be called from inside another executor.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let wake = ThreadWake::new();
+        let waker = Waker::from(wake.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut future = Box::pin(future);
+        let mut drive = Box::pin(self.drive());
+        loop {
+            if let Poll::Ready(x) = future.as_mut().poll(&mut cx) {
+                return x;
+            }
+            // Never finishes while `self` is alive, it only makes progress.
+            let _ = drive.as_mut().poll(&mut cx);
+            wake.wait(None);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -390,6 +565,22 @@ mod test {
 
     use self::context::EvalOptions;
 
+    #[test]
+    fn block_on() {
+        let rt = AsyncRuntime::new().unwrap();
+        let res = rt.block_on(async {
+            let ctx = AsyncContext::full(&rt).await.unwrap();
+            async_with!(ctx => |ctx| {
+                let promise: Promise = ctx
+                    .eval("new Promise((resolve) => Promise.resolve().then(() => resolve(42)))")
+                    .unwrap();
+                promise.into_future::<i32>().await.unwrap()
+            })
+            .await
+        });
+        assert_eq!(res, 42);
+    }
+
     async_test_case!(basic => (_rt,ctx){
         async_with!(&ctx => |ctx|{
             let res: i32 = ctx.eval("1 + 1").unwrap();
@@ -397,6 +588,24 @@ mod test {
         }).await;
     });
 
+    #[tokio::test]
+    async fn context_created_hook() {
+        let rt = AsyncRuntime::new().unwrap();
+        rt.on_context_created(Box::new(|ctx: &Ctx| ctx.globals().set("platform", "host")))
+            .await;
+
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(&ctx => |ctx|{
+            let res: StdString = ctx.eval("platform").unwrap();
+            assert_eq!(res, "host");
+        })
+        .await;
+
+        rt.on_context_created(Box::new(|_: &Ctx| Err(Error::Unknown)))
+            .await;
+        assert!(AsyncContext::base(&rt).await.is_err());
+    }
+
     async_test_case!(sleep_closure => (_rt,ctx){
 
         let mut a = 1;
@@ -474,6 +683,30 @@ mod test {
 
     });
 
+    #[tokio::test]
+    async fn shutdown() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        let done = Arc::new(AtomicBool::new(false));
+        let done_clone = done.clone();
+        async_with!(&ctx => |ctx|{
+            ctx.spawn(async move {
+                tokio::task::yield_now().await;
+                done_clone.store(true, Ordering::SeqCst);
+            });
+        })
+        .await;
+        drop(ctx);
+
+        rt.shutdown().await.unwrap();
+        assert!(done.load(Ordering::SeqCst));
+    }
+
     async_test_case!(recursive_spawn => (rt,ctx){
         use tokio::sync::oneshot;
 
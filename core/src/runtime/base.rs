@@ -1,12 +1,25 @@
 //! QuickJS runtime related types.
 
-use super::{opaque::Opaque, raw::RawRuntime, InterruptHandler, MemoryUsage};
+use super::{
+    opaque::Opaque, raw::RawRuntime, ContextHook, InterruptHandler, LeakHook, MemoryUsage, Metrics,
+    PanicHook, RejectionTracker, RuntimeBuilder, ShutdownError, UserDataError,
+};
 #[cfg(feature = "allocator")]
 use crate::allocator::Allocator;
 #[cfg(feature = "loader")]
 use crate::loader::{Loader, Resolver};
-use crate::{result::JobException, Context, Mut, Ref, Result, Weak};
-use std::{ffi::CString, ptr::NonNull, result::Result as StdResult};
+use crate::{
+    class::{ClassId, JsClass},
+    convert::NumberConversion,
+    result::JobException,
+    Context, JsLifetime, Mut, Ref, Result, Weak,
+};
+use alloc::{boxed::Box, ffi::CString, vec::Vec};
+use core::{
+    any::{Any, TypeId},
+    ptr::NonNull,
+    result::Result as StdResult,
+};
 
 /// A weak handle to the runtime.
 ///
@@ -38,9 +51,7 @@ impl Runtime {
     pub fn new() -> Result<Self> {
         let opaque = Opaque::new();
         let rt = unsafe { RawRuntime::new(opaque)? };
-        Ok(Self {
-            inner: Ref::new(Mut::new(rt)),
-        })
+        Ok(Self::from_raw(rt))
     }
 
     /// Create a new runtime using specified allocator
@@ -54,9 +65,46 @@ impl Runtime {
     {
         let opaque = Opaque::new();
         let rt = unsafe { RawRuntime::new_with_allocator(opaque, allocator)? };
-        Ok(Self {
+        Ok(Self::from_raw(rt))
+    }
+
+    /// Create a new runtime using specified allocator which attributes memory to contexts.
+    ///
+    /// Every allocation is recorded against the context which was entered when it was made, the
+    /// usage of a context can then be retrieved with [`Context::memory_usage`]. This allows hosts
+    /// running many contexts in a single runtime to find the heaviest one. Allocations made
+    /// outside of a context, like when executing pending jobs from the runtime, are not
+    /// attributed to any context.
+    ///
+    /// The memory of a context can also be limited with
+    /// [`Ctx::set_memory_limit`](crate::Ctx::set_memory_limit) and
+    /// [`Ctx::set_max_allocation_size`](crate::Ctx::set_max_allocation_size).
+    ///
+    /// The bookkeeping adds a small header to every allocation.
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    #[cfg(feature = "allocator")]
+    pub fn new_with_accounting<A>(allocator: A) -> Result<Self>
+    where
+        A: Allocator + 'static,
+    {
+        let opaque = Opaque::new();
+        let rt = unsafe { RawRuntime::new_with_accounting(opaque, allocator)? };
+        Ok(Self::from_raw(rt))
+    }
+
+    fn from_raw(rt: RawRuntime) -> Self {
+        let res = Self {
             inner: Ref::new(Mut::new(rt)),
-        })
+        };
+        res.inner.lock().get_opaque().set_runtime(res.weak());
+        res
+    }
+
+    /// Create a builder for configuring a runtime before it is created.
+    ///
+    /// See [`RuntimeBuilder`] for the available options.
+    pub fn builder() -> RuntimeBuilder {
+        RuntimeBuilder::default()
     }
 
     /// Get weak ref to runtime
@@ -74,6 +122,79 @@ impl Runtime {
         }
     }
 
+    /// Set a closure which is called when a promise is rejected without a handler.
+    ///
+    /// The closure is called again with `is_handled` set to `true` if a handler is attached to
+    /// such a promise later on.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Value};
+    /// # use std::sync::{Arc, Mutex};
+    /// let rt = Runtime::new().unwrap();
+    /// let unhandled = Arc::new(Mutex::new(Vec::new()));
+    /// let tracked = unhandled.clone();
+    /// rt.set_rejection_tracker(Some(Box::new(move |_ctx, _promise, reason: Value, is_handled| {
+    ///     if !is_handled {
+    ///         tracked.lock().unwrap().push(reason.get::<String>().unwrap());
+    ///     }
+    /// })));
+    /// let ctx = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| ctx.eval::<(), _>("Promise.reject('oops')").unwrap());
+    /// assert_eq!(*unhandled.lock().unwrap(), ["oops"]);
+    /// ```
+    pub fn set_rejection_tracker(&self, tracker: Option<RejectionTracker>) {
+        unsafe {
+            self.inner.lock().set_rejection_tracker(tracker);
+        }
+    }
+
+    /// Set a hook deciding what happens with a panic in Rust code called from JavaScript.
+    ///
+    /// Panics in functions, loaders, modules and the interrupt handler are always caught before
+    /// they reach QuickJS. By default the panic is continued once control returns to the Rust code
+    /// which called into JavaScript, the hook can instead turn it into a catchable exception or
+    /// abort the process, see [`PanicAction`](super::PanicAction). Panics in class finalizers and
+    /// trace functions can't be reported and are dropped unless the hook aborts.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Function, runtime::PanicAction};
+    /// let rt = Runtime::new().unwrap();
+    /// rt.set_panic_hook(Some(Box::new(|_| PanicAction::Throw)));
+    /// let ctx = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| {
+    ///     let f = Function::new(ctx.clone(), || -> () { panic!("oops") }).unwrap();
+    ///     ctx.globals().set("f", f).unwrap();
+    ///     let res: String = ctx.eval("try { f() } catch (e) { e.message }").unwrap();
+    ///     assert_eq!(res, "panicked: oops");
+    /// });
+    /// ```
+    pub fn set_panic_hook(&self, hook: Option<PanicHook>) {
+        self.inner.lock().get_opaque().set_panic_hook(hook)
+    }
+
+    /// Set a hook called when the runtime is dropped while its values are still kept alive by
+    /// [`Persistent`](crate::Persistent) handles.
+    ///
+    /// Freeing the runtime then would leave the handles dangling, so it is leaked together with
+    /// its allocator and loader, and the hook receives the outstanding handles. Without a hook the
+    /// leak is reported as a `tracing` event with the `tracing` feature and goes unnoticed
+    /// otherwise. Use [`Runtime::shutdown`] to get the handles before the runtime is dropped.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Persistent, Value};
+    /// let rt = Runtime::new().unwrap();
+    /// rt.set_leak_hook(Some(Box::new(|error| {
+    ///     assert_eq!(error.outstanding, 1);
+    /// })));
+    /// let ctx = Context::full(&rt).unwrap();
+    /// let value = ctx.with(|ctx| Persistent::save(&ctx, Value::new_int(ctx.clone(), 1)));
+    /// drop((ctx, rt));
+    /// # std::mem::forget(value);
+    /// ```
+    pub fn set_leak_hook(&self, hook: Option<LeakHook>) {
+        self.inner.lock().get_opaque().set_leak_hook(hook)
+    }
+
     /// Set the module loader
     #[cfg(feature = "loader")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
@@ -88,6 +209,10 @@ impl Runtime {
     }
 
     /// Set the info of the runtime
+    ///
+    /// The info is shown in the memory usage dumps of QuickJS, hosts running several runtimes can
+    /// use it to tell them apart. To label the scripts of a runtime use
+    /// [`EvalOptions::filename`](crate::context::EvalOptions::filename).
     pub fn set_info<S: Into<Vec<u8>>>(&self, info: S) -> Result<()> {
         let string = CString::new(info)?;
         unsafe {
@@ -117,6 +242,30 @@ impl Runtime {
         }
     }
 
+    /// Set the maximum depth of nested arrays and objects converted by [`FromJs`](crate::FromJs).
+    ///
+    /// Collections like `Vec` and `HashMap`, and `serde_json::Value` with the `serde` feature,
+    /// fail to convert values which are nested deeper or contain a reference cycle instead of
+    /// overflowing the stack.
+    ///
+    /// The default value is 128.
+    pub fn set_max_conversion_depth(&self, depth: usize) {
+        self.inner
+            .lock()
+            .get_opaque()
+            .set_max_conversion_depth(depth)
+    }
+
+    /// Set the options for converting JavaScript numbers to Rust numbers.
+    ///
+    /// See [`NumberConversion`](crate::convert::NumberConversion) for the default behavior.
+    pub fn set_number_conversion(&self, options: NumberConversion) {
+        self.inner
+            .lock()
+            .get_opaque()
+            .replace_number_conversion(options);
+    }
+
     /// Set a memory threshold for garbage collection.
     pub fn set_gc_threshold(&self, threshold: usize) {
         unsafe {
@@ -148,6 +297,162 @@ impl Runtime {
         unsafe { self.inner.lock().memory_usage() }
     }
 
+    /// Get the metrics collected by the runtime
+    pub fn metrics(&self) -> Metrics {
+        unsafe { self.inner.lock().metrics() }
+    }
+
+    /// Register a class with the runtime, reserving its name for the type.
+    ///
+    /// Classes can be used without registering them, see the [`class`](crate::class) module
+    /// for how class identity works. Once registered, using or registering a different type with
+    /// the same [`JsClass::NAME`] in this runtime returns
+    /// [`Error::ClassConflict`](crate::Error::ClassConflict), so mismatched
+    /// class types of separately built crates are detected. Registering the same class again
+    /// returns the same id.
+    ///
+    /// ```
+    /// # use rquickjs::{class::{JsClass, Readable, Trace, Tracer}, function::Constructor, Ctx, JsLifetime, Result, Runtime};
+    /// struct Point;
+    /// # impl<'js> Trace<'js> for Point {
+    /// #     fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+    /// # }
+    /// # unsafe impl<'js> JsLifetime<'js> for Point {
+    /// #     type Changed<'to> = Point;
+    /// # }
+    ///
+    /// impl<'js> JsClass<'js> for Point {
+    ///     const NAME: &'static str = "Point";
+    ///     type Mutable = Readable;
+    ///     fn constructor(_ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+    ///         Ok(None)
+    ///     }
+    /// }
+    ///
+    /// let rt = Runtime::new().unwrap();
+    /// let id = rt.register_class::<Point>().unwrap();
+    /// assert_eq!(rt.register_class::<Point>().unwrap(), id);
+    /// assert_eq!(rt.class_id("Point"), Some(id));
+    /// ```
+    pub fn register_class<C>(&self) -> Result<ClassId>
+    where
+        C: JsClass<'static>,
+    {
+        let id = TypeId::of::<C::Changed<'static>>();
+        let lock = self.inner.lock();
+        lock.get_opaque().register_class(C::NAME, id).map(ClassId)
+    }
+
+    /// Returns the id of the class registered under the name, if any.
+    pub fn class_id(&self, name: &str) -> Option<ClassId> {
+        let lock = self.inner.lock();
+        lock.get_opaque().registered_class(name).map(ClassId)
+    }
+
+    /// Store a type in the runtime which can be retrieved later with [`Runtime::with_userdata`]
+    /// or [`Ctx::userdata`](crate::Ctx::userdata).
+    ///
+    /// This is the same storage which is used by
+    /// [`Ctx::store_userdata`](crate::Ctx::store_userdata), it allows attaching the state of a
+    /// subsystem to the runtime before any context is created.
+    ///
+    /// Returns the value from the argument if the userdata is currently being accessed and
+    /// insertion is not possible. Otherwise returns the exising value for this type if it existed.
+    pub fn store_userdata<U>(&self, data: U) -> StdResult<Option<Box<U>>, UserDataError<U>>
+    where
+        U: JsLifetime<'static>,
+        U::Changed<'static>: Any,
+    {
+        self.inner.lock().get_opaque().insert_userdata(data)
+    }
+
+    /// Remove the userdata of the given type from the userdata storage.
+    ///
+    /// Returns Err(()) if the userdata is currently being accessed and removing isn't possible.
+    /// Returns Ok(None) if userdata of the given type wasn't inserted.
+    pub fn remove_userdata<U>(&self) -> StdResult<Option<Box<U>>, UserDataError<()>>
+    where
+        U: JsLifetime<'static>,
+        U::Changed<'static>: Any,
+    {
+        self.inner.lock().get_opaque().remove_userdata()
+    }
+
+    /// Call a closure with a reference to the userdata of the given type, if it was stored.
+    ///
+    /// The runtime stays locked while the closure runs, so it can't be called from inside
+    /// [`Context::with`], use [`Ctx::userdata`](crate::Ctx::userdata) there instead.
+    pub fn with_userdata<U, F, R>(&self, f: F) -> R
+    where
+        U: JsLifetime<'static>,
+        U::Changed<'static>: Any,
+        F: FnOnce(Option<&U>) -> R,
+    {
+        let lock = self.inner.lock();
+        let data = lock.get_opaque().get_userdata::<U>();
+        f(data.as_deref())
+    }
+
+    /// Register a hook which is called with every context created in this runtime.
+    ///
+    /// The hook runs after the intrinsics were added and before the context is returned, which
+    /// makes it the place to install globals and per context state of a subsystem. If a hook
+    /// returns an error, creating the context fails with that error. Hooks are called in the
+    /// order they were registered.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Ctx};
+    /// let rt = Runtime::new().unwrap();
+    /// rt.on_context_created(Box::new(|ctx: &Ctx| ctx.globals().set("platform", "host")));
+    ///
+    /// let ctx = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| {
+    ///     assert_eq!(ctx.eval::<String, _>("platform").unwrap(), "host");
+    /// });
+    /// ```
+    pub fn on_context_created(&self, hook: ContextHook) {
+        self.inner
+            .lock()
+            .get_opaque()
+            .add_context_created_hook(hook)
+    }
+
+    /// Shut down the runtime, failing if its values are still kept alive by
+    /// [`Persistent`](crate::Persistent) handles.
+    ///
+    /// Dropping a runtime whose values are still in use leaks it and passes the handles to the
+    /// hook set with [`Runtime::set_leak_hook`], this returns the runtime together with the number
+    /// of handles instead, and where they were created with the `leak-detection` feature. The runtime is only freed once
+    /// the contexts created from it are dropped as well.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Persistent, Value};
+    /// let rt = Runtime::new().unwrap();
+    /// let ctx = Context::full(&rt).unwrap();
+    /// let value = ctx.with(|ctx| Persistent::save(&ctx, Value::new_int(ctx.clone(), 1)));
+    /// drop(ctx);
+    ///
+    /// let error = rt.shutdown().unwrap_err();
+    /// assert_eq!(error.outstanding, 1);
+    /// drop(value);
+    /// error.runtime.shutdown().unwrap();
+    /// ```
+    pub fn shutdown(self) -> StdResult<(), ShutdownError<Runtime>> {
+        let lock = self.inner.lock();
+        let opaque = lock.get_opaque();
+        let outstanding = opaque.persistent_count();
+        if outstanding == 0 {
+            return Ok(());
+        }
+        let persistents = opaque.outstanding_persistents();
+        drop(lock);
+        Err(ShutdownError {
+            runtime: self,
+            outstanding,
+            persistents,
+        })
+    }
+
     /// Test for pending jobs
     ///
     /// Returns true when at least one job is pending.
@@ -200,4 +505,107 @@ mod test {
         rt.set_gc_threshold(0xFF);
         rt.run_gc();
     }
+
+    #[test]
+    fn userdata() {
+        struct Counter(u32);
+        unsafe impl<'js> JsLifetime<'js> for Counter {
+            type Changed<'to> = Counter;
+        }
+
+        let rt = Runtime::new().unwrap();
+        assert!(rt.with_userdata::<Counter, _, _>(|x| x.is_none()));
+        assert!(rt.store_userdata(Counter(1)).unwrap().is_none());
+        assert_eq!(rt.with_userdata(|x: Option<&Counter>| x.unwrap().0), 1);
+
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| assert_eq!(ctx.userdata::<Counter>().unwrap().0, 1));
+        assert_eq!(rt.remove_userdata::<Counter>().unwrap().unwrap().0, 1);
+    }
+
+    #[test]
+    fn panic_hook() {
+        use crate::{runtime::PanicAction, Ctx, Function, StdString};
+        use std::panic::{self, AssertUnwindSafe};
+
+        let setup = |rt: &Runtime| {
+            let ctx = Context::full(rt).unwrap();
+            ctx.with(|ctx: Ctx| {
+                let f = Function::new(ctx.clone(), || -> () { panic!("oops") }).unwrap();
+                ctx.globals().set("f", f).unwrap();
+            });
+            ctx
+        };
+
+        // JavaScript can't catch the panic.
+        let rt = Runtime::new().unwrap();
+        let ctx = setup(&rt);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            ctx.with(|ctx| ctx.eval::<(), _>("try { f() } catch {}"))
+        }));
+        assert_eq!(*res.unwrap_err().downcast::<&str>().unwrap(), "oops");
+
+        let rt = Runtime::new().unwrap();
+        rt.set_panic_hook(Some(Box::new(|panic| {
+            assert_eq!(panic.downcast_ref::<&str>(), Some(&"oops"));
+            PanicAction::Throw
+        })));
+        let ctx = setup(&rt);
+        ctx.with(|ctx| {
+            let res: StdString = ctx
+                .eval("try { f() } catch (e) { e.name + ': ' + e.message }")
+                .unwrap();
+            assert_eq!(res, "InternalError: panicked: oops");
+        });
+    }
+
+    #[test]
+    fn context_created_hook() {
+        use crate::{Ctx, Error};
+
+        let rt = Runtime::new().unwrap();
+        rt.on_context_created(Box::new(|ctx: &Ctx| ctx.globals().set("hooked", true)));
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| assert!(ctx.eval::<bool, _>("hooked").unwrap()));
+        let ctx = Context::base(&rt).unwrap();
+        ctx.with(|ctx| assert!(ctx.globals().get::<_, bool>("hooked").unwrap()));
+
+        rt.on_context_created(Box::new(|_: &Ctx| Err(Error::Unknown)));
+        assert!(matches!(Context::full(&rt), Err(Error::Unknown)));
+    }
+
+    // A panic poisons the lock with the parallel feature.
+    #[cfg(not(feature = "parallel"))]
+    #[test]
+    fn context_created_hook_panic() {
+        use crate::Ctx;
+        use std::{
+            panic::{self, AssertUnwindSafe},
+            sync::{
+                atomic::{AtomicBool, AtomicUsize, Ordering},
+                Arc,
+            },
+        };
+
+        let rt = Runtime::new().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        rt.on_context_created(Box::new(move |_: &Ctx| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }));
+        let panicked = Arc::new(AtomicBool::new(false));
+        let once = panicked.clone();
+        rt.on_context_created(Box::new(move |_: &Ctx| {
+            if !once.swap(true, Ordering::SeqCst) {
+                panic!("hook failed");
+            }
+            Ok(())
+        }));
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| Context::full(&rt)));
+        assert!(res.is_err());
+        Context::full(&rt).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,245 @@
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "allocator")]
+use crate::allocator::Allocator;
+#[cfg(feature = "loader")]
+use crate::loader::{Loader, Resolver};
+use crate::{convert::NumberConversion, Result, Runtime, StdString};
+
+use super::{ContextHook, InterruptHandler, LeakHook, PanicHook, RejectionTracker};
+
+type CreateRuntime = Box<dyn FnOnce() -> Result<Runtime>>;
+type SetLoader = Box<dyn FnOnce(&Runtime)>;
+
+/// A builder gathering the configuration of a [`Runtime`].
+///
+/// Every option corresponds to one of the setters of [`Runtime`]. The builder applies them in an
+/// order which is always valid, the memory limits are set before anything else is installed.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context};
+/// let rt = Runtime::builder()
+///     .memory_limit(64 * 1024 * 1024)
+///     .max_stack_size(512 * 1024)
+///     .gc_threshold(4 * 1024 * 1024)
+///     .info("worker")
+///     .interrupt_handler(Box::new(|| false))
+///     .build()
+///     .unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| assert_eq!(ctx.eval::<i32, _>("1 + 1").unwrap(), 2));
+/// ```
+#[derive(Default)]
+#[must_use]
+pub struct RuntimeBuilder {
+    create: Option<CreateRuntime>,
+    memory_limit: Option<usize>,
+    max_stack_size: Option<usize>,
+    gc_threshold: Option<usize>,
+    dump_flags: Option<u64>,
+    info: Option<StdString>,
+    panic_hook: Option<PanicHook>,
+    leak_hook: Option<LeakHook>,
+    interrupt_handler: Option<InterruptHandler>,
+    rejection_tracker: Option<RejectionTracker>,
+    loader: Option<SetLoader>,
+    max_conversion_depth: Option<usize>,
+    number_conversion: Option<NumberConversion>,
+    context_created: Vec<ContextHook>,
+}
+
+impl RuntimeBuilder {
+    /// Use the given allocator for the runtime, see [`Runtime::new_with_alloc`].
+    #[cfg(feature = "allocator")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    pub fn allocator<A>(mut self, allocator: A) -> Self
+    where
+        A: Allocator + 'static,
+    {
+        self.create = Some(Box::new(move || Runtime::new_with_alloc(allocator)));
+        self
+    }
+
+    /// Use the given allocator and attribute memory to contexts, see
+    /// [`Runtime::new_with_accounting`].
+    #[cfg(feature = "allocator")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
+    pub fn accounting_allocator<A>(mut self, allocator: A) -> Self
+    where
+        A: Allocator + 'static,
+    {
+        self.create = Some(Box::new(move || Runtime::new_with_accounting(allocator)));
+        self
+    }
+
+    /// Set a limit on the memory the runtime will use, see [`Runtime::set_memory_limit`].
+    pub fn memory_limit(mut self, limit: usize) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+
+    /// Set a limit on the stack size the runtime will use, see [`Runtime::set_max_stack_size`].
+    pub fn max_stack_size(mut self, limit: usize) -> Self {
+        self.max_stack_size = Some(limit);
+        self
+    }
+
+    /// Set the memory threshold for garbage collection, see [`Runtime::set_gc_threshold`].
+    pub fn gc_threshold(mut self, threshold: usize) -> Self {
+        self.gc_threshold = Some(threshold);
+        self
+    }
+
+    /// Set the debug flags for dumping memory, see [`Runtime::set_dump_flags`].
+    pub fn dump_flags(mut self, flags: u64) -> Self {
+        self.dump_flags = Some(flags);
+        self
+    }
+
+    /// Set the info of the runtime, see [`Runtime::set_info`].
+    pub fn info<S: Into<StdString>>(mut self, info: S) -> Self {
+        self.info = Some(info.into());
+        self
+    }
+
+    /// Set the hook deciding what happens with a panic, see [`Runtime::set_panic_hook`].
+    pub fn panic_hook(mut self, hook: PanicHook) -> Self {
+        self.panic_hook = Some(hook);
+        self
+    }
+
+    /// Set the hook called when the runtime is leaked, see [`Runtime::set_leak_hook`].
+    pub fn leak_hook(mut self, hook: LeakHook) -> Self {
+        self.leak_hook = Some(hook);
+        self
+    }
+
+    /// Set the interrupt handler, see [`Runtime::set_interrupt_handler`].
+    pub fn interrupt_handler(mut self, handler: InterruptHandler) -> Self {
+        self.interrupt_handler = Some(handler);
+        self
+    }
+
+    /// Set the promise rejection tracker, see [`Runtime::set_rejection_tracker`].
+    pub fn rejection_tracker(mut self, tracker: RejectionTracker) -> Self {
+        self.rejection_tracker = Some(tracker);
+        self
+    }
+
+    /// Set the module resolver and loader, see [`Runtime::set_loader`].
+    #[cfg(feature = "loader")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+    pub fn loader<R, L>(mut self, resolver: R, loader: L) -> Self
+    where
+        R: Resolver + 'static,
+        L: Loader + 'static,
+    {
+        self.loader = Some(Box::new(move |rt: &Runtime| {
+            rt.set_loader(resolver, loader)
+        }));
+        self
+    }
+
+    /// Set the maximum depth of converted values, see [`Runtime::set_max_conversion_depth`].
+    pub fn max_conversion_depth(mut self, depth: usize) -> Self {
+        self.max_conversion_depth = Some(depth);
+        self
+    }
+
+    /// Set the options for converting numbers, see [`Runtime::set_number_conversion`].
+    pub fn number_conversion(mut self, options: NumberConversion) -> Self {
+        self.number_conversion = Some(options);
+        self
+    }
+
+    /// Add a hook called with every created context, see [`Runtime::on_context_created`].
+    pub fn on_context_created(mut self, hook: ContextHook) -> Self {
+        self.context_created.push(hook);
+        self
+    }
+
+    /// Create the runtime with the configured options.
+    pub fn build(self) -> Result<Runtime> {
+        let rt = match self.create {
+            Some(create) => create()?,
+            None => Runtime::new()?,
+        };
+        if let Some(limit) = self.memory_limit {
+            rt.set_memory_limit(limit);
+        }
+        if let Some(limit) = self.max_stack_size {
+            rt.set_max_stack_size(limit);
+        }
+        if let Some(threshold) = self.gc_threshold {
+            rt.set_gc_threshold(threshold);
+        }
+        if let Some(flags) = self.dump_flags {
+            rt.set_dump_flags(flags);
+        }
+        if let Some(info) = self.info {
+            rt.set_info(info)?;
+        }
+        if let Some(hook) = self.panic_hook {
+            rt.set_panic_hook(Some(hook));
+        }
+        if let Some(hook) = self.leak_hook {
+            rt.set_leak_hook(Some(hook));
+        }
+        if let Some(handler) = self.interrupt_handler {
+            rt.set_interrupt_handler(Some(handler));
+        }
+        if let Some(tracker) = self.rejection_tracker {
+            rt.set_rejection_tracker(Some(tracker));
+        }
+        if let Some(set_loader) = self.loader {
+            set_loader(&rt);
+        }
+        if let Some(depth) = self.max_conversion_depth {
+            rt.set_max_conversion_depth(depth);
+        }
+        if let Some(options) = self.number_conversion {
+            rt.set_number_conversion(options);
+        }
+        for hook in self.context_created {
+            rt.on_context_created(hook);
+        }
+        Ok(rt)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn builder() {
+        let rejected = Arc::new(AtomicUsize::new(0));
+        let tracked = rejected.clone();
+        let rt = Runtime::builder()
+            .max_stack_size(1024 * 1024)
+            .max_conversion_depth(2)
+            .rejection_tracker(Box::new(move |_, _, _, is_handled| {
+                // A handler attached later reports the promise again as handled.
+                if is_handled {
+                    tracked.fetch_sub(1, Ordering::Relaxed);
+                } else {
+                    tracked.fetch_add(1, Ordering::Relaxed);
+                }
+            }))
+            .on_context_created(Box::new(|ctx: &Ctx| ctx.globals().set("ready", true)))
+            .build()
+            .unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            assert!(ctx.eval::<bool, _>("ready").unwrap());
+            assert!(ctx.eval::<Vec<Vec<Vec<i32>>>, _>("[[[1]]]").is_err());
+            ctx.eval::<(), _>("Promise.reject(1); Promise.reject(2).catch(() => {})")
+                .unwrap();
+        });
+        assert_eq!(rejected.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,242 @@
+use core::cell::Cell;
+
+use super::{opaque::Opaque, MemoryUsage};
+use crate::{context::ContextData, qjs};
+
+/// A snapshot of the counters collected by a runtime.
+///
+/// Retrieved with [`Runtime::metrics`](crate::Runtime::metrics), the counters accumulate over the
+/// lifetime of the runtime and are shared by all of its contexts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Metrics {
+    /// Number of scripts and modules evaluated.
+    pub evaluations: u64,
+    /// Number of pending jobs, like promise reactions, executed.
+    pub jobs_executed: u64,
+    /// Number of JavaScript exceptions which propagated into Rust.
+    pub exceptions: u64,
+    /// Number of garbage collection cycles.
+    ///
+    /// Counts the collections QuickJS runs by itself when the allocated memory passes the
+    /// threshold set with [`Runtime::set_gc_threshold`](crate::Runtime::set_gc_threshold) as well
+    /// as the ones run with [`Runtime::run_gc`](crate::Runtime::run_gc). With the `system` feature
+    /// the library isn't patched to report collections, so only the ones run from Rust are
+    /// counted.
+    pub gc_runs: u64,
+    /// The highest amount of memory allocated by the runtime, in bytes.
+    ///
+    /// Runtimes using a Rust [`Allocator`](crate::allocator::Allocator), including the ones
+    /// created with the `rust-alloc` feature, track it on every allocation. Otherwise the memory
+    /// usage is sampled when a garbage collection runs and when the metrics are retrieved, so
+    /// short lived peaks in between can be missed.
+    pub peak_memory: u64,
+}
+
+/// A snapshot of the counters of a single context.
+///
+/// Retrieved with [`Ctx::metrics`](crate::Ctx::metrics), the counters accumulate over the
+/// lifetime of the context. The same events are also counted by the [`Metrics`] of its runtime.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContextMetrics {
+    /// Number of scripts and modules evaluated in the context.
+    pub evaluations: u64,
+    /// Number of pending jobs of the context executed.
+    pub jobs_executed: u64,
+    /// Number of JavaScript exceptions of the context which propagated into Rust.
+    pub exceptions: u64,
+}
+
+impl ContextMetrics {
+    /// Publish the metrics through the [`metrics`](::metrics) crate facade, labelled with the
+    /// given context name.
+    ///
+    /// The counters are recorded under the same names as the ones of [`Metrics::record`], with
+    /// a `context` label.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "metrics")))]
+    pub fn record(&self, context: &str) {
+        let context = context.to_owned();
+        ::metrics::counter!("rquickjs_evaluations_total", "context" => context.clone())
+            .absolute(self.evaluations);
+        ::metrics::counter!("rquickjs_jobs_executed_total", "context" => context.clone())
+            .absolute(self.jobs_executed);
+        ::metrics::counter!("rquickjs_exceptions_total", "context" => context)
+            .absolute(self.exceptions);
+    }
+}
+
+impl Metrics {
+    /// Publish the metrics through the [`metrics`](::metrics) crate facade.
+    ///
+    /// The counters are recorded as absolute values under the `rquickjs_` prefix, for example
+    /// `rquickjs_evaluations_total`, and the peak memory as the `rquickjs_peak_memory_bytes`
+    /// gauge.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "metrics")))]
+    pub fn record(&self) {
+        ::metrics::counter!("rquickjs_evaluations_total").absolute(self.evaluations);
+        ::metrics::counter!("rquickjs_jobs_executed_total").absolute(self.jobs_executed);
+        ::metrics::counter!("rquickjs_exceptions_total").absolute(self.exceptions);
+        ::metrics::counter!("rquickjs_gc_runs_total").absolute(self.gc_runs);
+        ::metrics::gauge!("rquickjs_peak_memory_bytes").set(self.peak_memory as f64);
+    }
+}
+
+/// The live counters stored in the runtime opaque.
+#[derive(Default)]
+pub(crate) struct MetricsCounters {
+    evaluations: Cell<u64>,
+    jobs_executed: Cell<u64>,
+    exceptions: Cell<u64>,
+    gc_runs: Cell<u64>,
+    peak_memory: Cell<u64>,
+}
+
+impl MetricsCounters {
+    fn increment(cell: &Cell<u64>) {
+        cell.set(cell.get().wrapping_add(1));
+    }
+
+    pub fn evaluation(&self) {
+        Self::increment(&self.evaluations)
+    }
+
+    pub fn job_executed(&self) {
+        Self::increment(&self.jobs_executed)
+    }
+
+    pub fn exception(&self) {
+        Self::increment(&self.exceptions)
+    }
+
+    pub fn gc_run(&self) {
+        Self::increment(&self.gc_runs)
+    }
+
+    pub fn sample_memory(&self, usage: &MemoryUsage) {
+        self.record_peak(u64::try_from(usage.malloc_size).unwrap_or(0));
+    }
+
+    pub fn record_peak(&self, used: u64) {
+        if used > self.peak_memory.get() {
+            self.peak_memory.set(used);
+        }
+    }
+
+    pub fn snapshot(&self) -> Metrics {
+        Metrics {
+            evaluations: self.evaluations.get(),
+            jobs_executed: self.jobs_executed.get(),
+            exceptions: self.exceptions.get(),
+            gc_runs: self.gc_runs.get(),
+            peak_memory: self.peak_memory.get(),
+        }
+    }
+
+    pub fn snapshot_context(&self) -> ContextMetrics {
+        ContextMetrics {
+            evaluations: self.evaluations.get(),
+            jobs_executed: self.jobs_executed.get(),
+            exceptions: self.exceptions.get(),
+        }
+    }
+}
+
+/// Count a garbage collection, called by QuickJS before every collection of any runtime.
+#[cfg(not(feature = "system"))]
+pub(crate) unsafe fn count_gc(rt: *mut qjs::JSRuntime, malloc_size: usize) {
+    // Collections can run while the runtime is created, before the opaque is set.
+    let opaque = qjs::JS_GetRuntimeOpaque(rt).cast::<Opaque>();
+    if let Some(opaque) = opaque.as_ref() {
+        let metrics = opaque.metrics();
+        metrics.gc_run();
+        metrics.record_peak(malloc_size as u64);
+    }
+}
+
+/// Count an event for the runtime and for the context it happened in.
+pub(crate) unsafe fn record(ctx: *mut qjs::JSContext, count: fn(&MetricsCounters)) {
+    let rt = qjs::JS_GetRuntime(ctx);
+    count(Opaque::from_runtime_ptr(rt).metrics());
+    if let Some(data) = ContextData::get(ctx) {
+        count(&data.metrics);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Context, Runtime};
+
+    #[test]
+    fn collects_metrics() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>("Promise.resolve().then(() => {})")
+                .unwrap();
+            assert!(ctx.eval::<(), _>("throw new Error('oops')").is_err());
+            ctx.catch();
+        });
+        while rt.execute_pending_job().unwrap() {}
+        let gc_runs = rt.metrics().gc_runs;
+        rt.run_gc();
+
+        let metrics = rt.metrics();
+        assert_eq!(metrics.evaluations, 2);
+        assert_eq!(metrics.jobs_executed, 1);
+        assert_eq!(metrics.exceptions, 1);
+        assert_eq!(metrics.gc_runs, gc_runs + 1);
+        assert!(metrics.peak_memory > 0);
+    }
+
+    #[cfg(not(feature = "system"))]
+    #[test]
+    fn counts_automatic_gc() {
+        let rt = Runtime::new().unwrap();
+        rt.set_gc_threshold(64 * 1024);
+        let ctx = Context::full(&rt).unwrap();
+        let gc_runs = rt.metrics().gc_runs;
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>("const a = []; for (let i = 0; i < 100000; i++) a.push({ i })")
+                .unwrap()
+        });
+        assert!(rt.metrics().gc_runs > gc_runs);
+    }
+
+    #[test]
+    fn collects_context_metrics() {
+        let rt = Runtime::new().unwrap();
+        let first = Context::full(&rt).unwrap();
+        let second = Context::full(&rt).unwrap();
+        first.with(|ctx| {
+            ctx.eval::<(), _>("Promise.resolve().then(() => {})")
+                .unwrap();
+            assert!(ctx.eval::<(), _>("throw new Error('oops')").is_err());
+            ctx.catch();
+        });
+        second.with(|ctx| ctx.eval::<(), _>("1").unwrap());
+        while rt.execute_pending_job().unwrap() {}
+
+        let metrics = first.with(|ctx| ctx.metrics());
+        assert_eq!(metrics.evaluations, 2);
+        assert_eq!(metrics.jobs_executed, 1);
+        assert_eq!(metrics.exceptions, 1);
+        let metrics = second.with(|ctx| ctx.metrics());
+        assert_eq!(metrics.evaluations, 1);
+        assert_eq!(metrics.jobs_executed, 0);
+        assert_eq!(rt.metrics().evaluations, 3);
+    }
+
+    #[cfg(feature = "allocator")]
+    #[test]
+    fn tracks_peak_memory() {
+        let rt = Runtime::new_with_alloc(crate::allocator::RustAllocator).unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        assert!(rt.metrics().peak_memory < 4 * 1024 * 1024);
+        // The string is freed right away, between samples.
+        ctx.with(|ctx| ctx.eval::<(), _>("'x'.repeat(4 * 1024 * 1024), 0").unwrap());
+        assert!(rt.metrics().peak_memory >= 4 * 1024 * 1024);
+    }
+}
@@ -1,48 +1,182 @@
 use crate::{
     class::{self, ffi::VTable, JsClass},
-    qjs, Ctx, Error, JsLifetime, Object,
+    context::{context_data_finalizer, ContextData},
+    convert::NumberConversion,
+    qjs, util, Ctx, Error, JsLifetime, Object, Value,
 };
+use alloc::{boxed::Box, vec::Vec};
 
 use super::{
     userdata::{UserDataGuard, UserDataMap},
-    InterruptHandler, UserDataError,
+    ContextHook, InterruptHandler, LeakHook, MetricsCounters, OutstandingPersistent, PanicAction,
+    PanicHook, RejectionTracker, UserDataError, WeakRuntime,
 };
-use std::{
+use alloc::collections::BTreeMap;
+use core::{
     any::{Any, TypeId},
     cell::{Cell, UnsafeCell},
-    collections::{hash_map::Entry, HashMap},
     marker::PhantomData,
-    ptr,
+    mem,
+    panic::{AssertUnwindSafe, UnwindSafe},
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicUsize, Ordering},
 };
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(feature = "leak-detection")]
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "allocator")]
+use crate::allocator::{AccountingScope, MemoryAccounting};
+#[cfg(feature = "allocator")]
+use alloc::rc::Rc;
+
+#[cfg(feature = "array-buffer")]
+use crate::BorrowError;
+#[cfg(feature = "array-buffer")]
+use core::ops::Range;
 
 #[cfg(feature = "futures")]
 use super::{schedular::SchedularPoll, spawner::Spawner};
+#[cfg(feature = "futures")]
+use crate::util::Defer;
 
 #[cfg(feature = "futures")]
-use std::{
+use core::{
     future::Future,
     task::{Context, Waker},
 };
 
+/// Guard which attributes allocations to a context while alive.
+pub(crate) struct ContextScope {
+    #[cfg(feature = "allocator")]
+    _scope: Option<AccountingScope>,
+    #[cfg(feature = "allocator")]
+    id: usize,
+}
+
+impl ContextScope {
+    /// Attach the data of a context created in a scope from [`Opaque::enter_new_context`],
+    /// including the id its allocations are attributed to.
+    pub unsafe fn assign(&self, ctx: *mut qjs::JSContext) {
+        let data = ContextData {
+            #[cfg(feature = "allocator")]
+            accounting_id: self.id,
+            ..ContextData::default()
+        };
+        ContextData::install(ctx, data);
+    }
+}
+
+const CONTEXT_DATA_CLASS_NAME: &[u8] = b"ContextData\0";
+
+/// The default maximum depth of nested objects converted from JavaScript.
+const DEFAULT_MAX_CONVERSION_DEPTH: usize = 128;
+
 /// Opaque book keeping data for Rust.
+/// The context created hooks while they run, put back when dropped so they survive a panicking
+/// hook.
+struct RunningHooks {
+    hooks: *mut Vec<ContextHook>,
+    running: Vec<ContextHook>,
+}
+
+impl Drop for RunningHooks {
+    fn drop(&mut self) {
+        // Hooks added by a running hook are kept after the existing ones.
+        unsafe {
+            let added = mem::replace(&mut *self.hooks, mem::take(&mut self.running));
+            (*self.hooks).extend(added);
+        }
+    }
+}
+
 pub(crate) struct Opaque<'js> {
     /// Used to carry a panic if a callback triggered one.
     panic: Cell<Option<Box<dyn Any + Send + 'static>>>,
+    /// The user provided hook deciding what to do with a caught panic, if any.
+    panic_hook: UnsafeCell<Option<PanicHook>>,
+    /// The user provided hook called when the runtime is leaked, if any.
+    leak_hook: UnsafeCell<Option<LeakHook>>,
 
     /// The user provided interrupt handler, if any.
     interrupt_handler: UnsafeCell<Option<InterruptHandler>>,
+    /// The deadline of the innermost running [`Ctx::eval_timeout`], if any.
+    #[cfg(feature = "std")]
+    deadline: Cell<Option<Instant>>,
+    /// Set when execution was interrupted because the deadline passed.
+    deadline_reached: Cell<bool>,
+
+    /// The user provided promise rejection tracker, if any.
+    rejection_tracker: UnsafeCell<Option<RejectionTracker>>,
 
     /// The class id for rust classes.
     class_id: qjs::JSClassID,
     /// The class id for rust classes which can be called.
     callable_class_id: qjs::JSClassID,
+    /// The class id of the objects owning the data of contexts.
+    context_data_class_id: qjs::JSClassID,
 
-    prototypes: UnsafeCell<HashMap<TypeId, Option<Object<'js>>>>,
+    prototypes: UnsafeCell<BTreeMap<TypeId, Option<Object<'js>>>>,
+    /// The classes registered with `Runtime::register_class`, indexed by their id.
+    classes: UnsafeCell<Vec<(&'static str, TypeId)>>,
 
     userdata: UserDataMap,
 
+    /// Hooks called after a context is created.
+    context_created: UnsafeCell<Vec<ContextHook>>,
+
+    /// The objects currently being converted by the recursive `FromJs` implementations.
+    converting: UnsafeCell<Vec<*mut qjs::c_void>>,
+    /// The maximum number of nested objects a conversion may enter.
+    max_conversion_depth: Cell<usize>,
+    /// The options for converting numbers to Rust.
+    number_conversion: Cell<NumberConversion>,
+
+    /// The capacity of the byte vectors backing array buffers created from Rust, by data pointer.
+    #[cfg(feature = "array-buffer")]
+    byte_buffers: UnsafeCell<BTreeMap<usize, usize>>,
+
+    /// The byte ranges borrowed by views of array buffers, with whether they are mutable.
+    #[cfg(feature = "array-buffer")]
+    byte_borrows: UnsafeCell<Vec<(Range<usize>, bool)>>,
+
+    /// The number of live persistent handles.
+    ///
+    /// Handles are dropped without holding the lock of the runtime, so this is atomic.
+    persistent_count: AtomicUsize,
+    /// The live persistent handles by id, if leak detection is enabled.
+    #[cfg(feature = "leak-detection")]
+    persistents: Mutex<BTreeMap<usize, OutstandingPersistent>>,
+    #[cfg(feature = "leak-detection")]
+    next_persistent_id: AtomicUsize,
+
+    /// The live value handles, if leak detection is enabled.
+    #[cfg(feature = "leak-detection")]
+    live_values: UnsafeCell<crate::leak::Tracker>,
+
+    metrics: MetricsCounters,
+
+    /// A handle to the runtime if it is a [`Runtime`](crate::Runtime).
+    runtime: UnsafeCell<Option<WeakRuntime>>,
+
+    /// Per context memory bookkeeping, if enabled for the runtime.
+    #[cfg(feature = "allocator")]
+    accounting: Option<Rc<MemoryAccounting>>,
+
     #[cfg(feature = "futures")]
     spawner: Option<UnsafeCell<Spawner>>,
+    /// Whether the spawned futures are currently being polled.
+    #[cfg(feature = "futures")]
+    polling: Cell<bool>,
+
+    /// The number of [`Context::with`](crate::Context::with) calls currently running.
+    ///
+    /// Without the standard library there are no threads, so this replaces the thread local
+    /// tracking used otherwise.
+    #[cfg(not(feature = "std"))]
+    entered: Cell<usize>,
 
     _marker: PhantomData<&'js ()>,
 }
@@ -51,20 +185,61 @@ impl<'js> Opaque<'js> {
     pub fn new() -> Self {
         Opaque {
             panic: Cell::new(None),
+            panic_hook: UnsafeCell::new(None),
+            leak_hook: UnsafeCell::new(None),
 
             interrupt_handler: UnsafeCell::new(None),
+            #[cfg(feature = "std")]
+            deadline: Cell::new(None),
+            deadline_reached: Cell::new(false),
+
+            rejection_tracker: UnsafeCell::new(None),
 
             class_id: qjs::JS_INVALID_CLASS_ID,
             callable_class_id: qjs::JS_INVALID_CLASS_ID,
+            context_data_class_id: qjs::JS_INVALID_CLASS_ID,
 
-            prototypes: UnsafeCell::new(HashMap::new()),
+            prototypes: UnsafeCell::new(BTreeMap::new()),
+            classes: UnsafeCell::new(Vec::new()),
 
             userdata: UserDataMap::default(),
 
+            context_created: UnsafeCell::new(Vec::new()),
+
+            converting: UnsafeCell::new(Vec::new()),
+            max_conversion_depth: Cell::new(DEFAULT_MAX_CONVERSION_DEPTH),
+            number_conversion: Cell::new(NumberConversion::default()),
+
+            #[cfg(feature = "array-buffer")]
+            byte_buffers: UnsafeCell::new(BTreeMap::new()),
+            #[cfg(feature = "array-buffer")]
+            byte_borrows: UnsafeCell::new(Vec::new()),
+
+            persistent_count: AtomicUsize::new(0),
+            #[cfg(feature = "leak-detection")]
+            persistents: Mutex::new(BTreeMap::new()),
+            #[cfg(feature = "leak-detection")]
+            next_persistent_id: AtomicUsize::new(0),
+
+            #[cfg(feature = "leak-detection")]
+            live_values: UnsafeCell::new(Default::default()),
+
+            metrics: MetricsCounters::default(),
+
+            runtime: UnsafeCell::new(None),
+
+            #[cfg(feature = "allocator")]
+            accounting: None,
+
             _marker: PhantomData,
 
             #[cfg(feature = "futures")]
             spawner: None,
+            #[cfg(feature = "futures")]
+            polling: Cell::new(false),
+
+            #[cfg(not(feature = "std"))]
+            entered: Cell::new(0),
         }
     }
 
@@ -76,8 +251,14 @@ impl<'js> Opaque<'js> {
     }
 
     pub unsafe fn initialize(&mut self, rt: *mut qjs::JSRuntime) -> Result<(), Error> {
+        #[cfg(not(feature = "system"))]
+        qjs::set_gc_hook(Some(super::metrics::count_gc));
+        #[cfg(not(feature = "system"))]
+        qjs::set_regexp_hook(Some(crate::context::regexp_poll));
+
         qjs::JS_NewClassID(rt, (&mut self.class_id) as *mut qjs::JSClassID);
         qjs::JS_NewClassID(rt, (&mut self.callable_class_id) as *mut qjs::JSClassID);
+        qjs::JS_NewClassID(rt, (&mut self.context_data_class_id) as *mut qjs::JSClassID);
 
         let class_def = qjs::JSClassDef {
             class_name: b"RustClass\0".as_ptr().cast(),
@@ -103,9 +284,27 @@ impl<'js> Opaque<'js> {
             return Err(Error::Unknown);
         }
 
+        let class_def = qjs::JSClassDef {
+            class_name: CONTEXT_DATA_CLASS_NAME.as_ptr().cast(),
+            finalizer: Some(context_data_finalizer),
+            gc_mark: None,
+            call: None,
+            exotic: ptr::null_mut(),
+        };
+
+        if 0 != qjs::JS_NewClass(rt, self.context_data_class_id, &class_def) {
+            return Err(Error::Unknown);
+        }
+
         Ok(())
     }
 
+    /// Returns the counter of running [`Context::with`](crate::Context::with) calls.
+    #[cfg(not(feature = "std"))]
+    pub fn entered(&self) -> &Cell<usize> {
+        &self.entered
+    }
+
     pub unsafe fn from_runtime_ptr<'a>(rt: *mut qjs::JSRuntime) -> &'a Self {
         &*(qjs::JS_GetRuntimeOpaque(rt).cast::<Self>())
     }
@@ -137,9 +336,18 @@ impl<'js> Opaque<'js> {
 
     #[cfg(feature = "futures")]
     pub fn poll(&self, cx: &mut Context) -> SchedularPoll {
+        self.polling.set(true);
+        let _reset = Defer::new((), |_| self.polling.set(false));
         unsafe { (*self.spawner().get()).poll(cx) }
     }
 
+    /// Returns whether the spawned futures can be polled, which is not the case in a runtime
+    /// without futures or while one of them is being polled.
+    #[cfg(feature = "futures")]
+    pub fn can_poll(&self) -> bool {
+        self.spawner.is_some() && !self.polling.get()
+    }
+
     pub fn insert_userdata<U>(&self, data: U) -> Result<Option<Box<U>>, UserDataError<U>>
     where
         U: JsLifetime<'js>,
@@ -164,12 +372,145 @@ impl<'js> Opaque<'js> {
         self.userdata.get()
     }
 
+    pub fn add_context_created_hook(&self, hook: ContextHook) {
+        unsafe { (*self.context_created.get()).push(hook) }
+    }
+
+    /// Run the hooks for a newly created context in order, stopping at the first error.
+    ///
+    /// # Safety
+    /// The runtime must be locked and the context must belong to it.
+    pub unsafe fn run_context_created(&self, ctx: NonNull<qjs::JSContext>) -> Result<(), Error> {
+        let hooks = self.context_created.get();
+        if (*hooks).is_empty() {
+            return Ok(());
+        }
+        let _scope = self.enter_context(ctx.as_ptr());
+        // Take the hooks out so that they don't alias while calling into JavaScript.
+        let mut running = RunningHooks {
+            hooks,
+            running: mem::take(&mut *hooks),
+        };
+        let ctx = Ctx::from_raw(ctx);
+        running.running.iter_mut().try_for_each(|hook| hook(&ctx))
+    }
+
     pub fn set_interrupt_handler(&self, interupt: Option<InterruptHandler>) {
         unsafe { (*self.interrupt_handler.get()) = interupt }
     }
 
     pub fn run_interrupt_handler(&self) -> bool {
-        unsafe { (*self.interrupt_handler.get()).as_mut().unwrap()() }
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.deadline.get() {
+            if Instant::now() >= deadline {
+                self.deadline_reached.set(true);
+                return true;
+            }
+        }
+        unsafe {
+            match (*self.interrupt_handler.get()).as_mut() {
+                Some(handler) => handler(),
+                None => false,
+            }
+        }
+    }
+
+    pub fn set_rejection_tracker(&self, tracker: Option<RejectionTracker>) {
+        unsafe { (*self.rejection_tracker.get()) = tracker }
+    }
+
+    pub fn has_rejection_tracker(&self) -> bool {
+        unsafe { (*self.rejection_tracker.get()).is_some() }
+    }
+
+    pub fn run_rejection_tracker(
+        &self,
+        ctx: &Ctx<'js>,
+        promise: Value<'js>,
+        reason: Value<'js>,
+        is_handled: bool,
+    ) {
+        unsafe {
+            if let Some(tracker) = (*self.rejection_tracker.get()).as_mut() {
+                tracker(ctx, promise, reason, is_handled)
+            }
+        }
+    }
+
+    /// Returns whether the interrupt handler trampoline needs to be installed.
+    pub fn needs_interrupt_handler(&self) -> bool {
+        #[cfg(feature = "std")]
+        if self.deadline.get().is_some() {
+            return true;
+        }
+        unsafe { (*self.interrupt_handler.get()).is_some() }
+    }
+
+    pub fn set_runtime(&self, runtime: WeakRuntime) {
+        unsafe { (*self.runtime.get()) = Some(runtime) }
+    }
+
+    pub fn runtime(&self) -> Option<&WeakRuntime> {
+        unsafe { (*self.runtime.get()).as_ref() }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn replace_deadline(&self, deadline: Option<Instant>) -> Option<Instant> {
+        self.deadline.replace(deadline)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn reset_deadline_reached(&self) {
+        self.deadline_reached.set(false)
+    }
+
+    /// Returns whether the deadline was reached.
+    ///
+    /// The flag is kept when the now active deadline of an enclosing evaluation has passed as
+    /// well, so that evaluation also reports the timeout.
+    pub fn take_deadline_reached(&self) -> bool {
+        let reached = self.deadline_reached.get();
+        #[cfg(feature = "std")]
+        let passed = matches!(self.deadline.get(), Some(deadline) if Instant::now() >= deadline);
+        #[cfg(not(feature = "std"))]
+        let passed = false;
+        self.deadline_reached.set(reached && passed);
+        reached
+    }
+
+    #[cfg(any(feature = "cbor", feature = "msgpack"))]
+    pub fn max_conversion_depth(&self) -> usize {
+        self.max_conversion_depth.get()
+    }
+
+    pub fn set_max_conversion_depth(&self, depth: usize) {
+        self.max_conversion_depth.set(depth)
+    }
+
+    /// Mark an object as being converted, fails if it is already being converted further up or
+    /// the maximum depth is reached.
+    pub fn enter_conversion(&self, ptr: *mut qjs::c_void) -> Result<(), &'static str> {
+        let converting = unsafe { &mut *self.converting.get() };
+        if converting.contains(&ptr) {
+            return Err("value contains a reference cycle");
+        }
+        if converting.len() >= self.max_conversion_depth.get() {
+            return Err("value exceeds the maximum conversion depth");
+        }
+        converting.push(ptr);
+        Ok(())
+    }
+
+    pub fn leave_conversion(&self) {
+        unsafe { (*self.converting.get()).pop() };
+    }
+
+    pub fn number_conversion(&self) -> NumberConversion {
+        self.number_conversion.get()
+    }
+
+    pub fn replace_number_conversion(&self, options: NumberConversion) -> NumberConversion {
+        self.number_conversion.replace(options)
     }
 
     pub fn set_panic(&self, panic: Box<dyn Any + Send + 'static>) {
@@ -180,14 +521,264 @@ impl<'js> Opaque<'js> {
         self.panic.take()
     }
 
+    pub fn set_panic_hook(&self, hook: Option<PanicHook>) {
+        unsafe { *self.panic_hook.get() = hook }
+    }
+
+    pub fn set_leak_hook(&self, hook: Option<LeakHook>) {
+        unsafe { *self.leak_hook.get() = hook }
+    }
+
+    pub fn take_leak_hook(&mut self) -> Option<LeakHook> {
+        self.leak_hook.get_mut().take()
+    }
+
+    /// Returns what should happen with a panic caught in a callback.
+    pub fn panic_action(&self, panic: &(dyn Any + Send)) -> PanicAction {
+        let Some(hook) = (unsafe { &*self.panic_hook.get() }) else {
+            return PanicAction::Resume;
+        };
+        // A panic in the hook itself can't be handled any further.
+        util::catch_unwind(AssertUnwindSafe(|| hook(panic))).unwrap_or_else(|_| util::abort())
+    }
+
+    /// Run a callback which can't report errors, like a finalizer.
+    ///
+    /// A panic is passed to the panic hook and dropped unless the hook aborts.
+    pub fn catch_silent_panic<F: FnOnce() + UnwindSafe>(&self, f: F) {
+        if let Err(panic) = util::catch_unwind(f) {
+            if self.panic_action(&*panic) == PanicAction::Abort {
+                util::abort()
+            }
+        }
+    }
+
+    #[cfg(feature = "array-buffer")]
+    pub fn register_byte_buffer(&self, ptr: *mut qjs::c_void, capacity: usize) {
+        unsafe { (*self.byte_buffers.get()).insert(ptr as usize, capacity) };
+    }
+
+    /// Returns the capacity of the vector backing the buffer, `None` if it was taken already.
+    #[cfg(feature = "array-buffer")]
+    pub fn unregister_byte_buffer(&self, ptr: *mut qjs::c_void) -> Option<usize> {
+        unsafe { (*self.byte_buffers.get()).remove(&(ptr as usize)) }
+    }
+
+    /// Borrow a range of bytes of an array buffer, failing if it overlaps a conflicting borrow.
+    #[cfg(feature = "array-buffer")]
+    pub fn borrow_bytes(&self, range: Range<usize>, mutable: bool) -> Result<(), BorrowError> {
+        let borrows = unsafe { &mut *self.byte_borrows.get() };
+        for (borrowed, borrowed_mut) in borrows.iter() {
+            if borrowed.start < range.end && range.start < borrowed.end {
+                if *borrowed_mut {
+                    return Err(BorrowError::AlreadyBorrowedMut);
+                }
+                if mutable {
+                    return Err(BorrowError::AlreadyBorrowed);
+                }
+            }
+        }
+        borrows.push((range, mutable));
+        Ok(())
+    }
+
+    #[cfg(feature = "array-buffer")]
+    pub fn release_bytes(&self, range: Range<usize>, mutable: bool) {
+        let borrows = unsafe { &mut *self.byte_borrows.get() };
+        if let Some(idx) = borrows.iter().position(|x| x.0 == range && x.1 == mutable) {
+            borrows.swap_remove(idx);
+        }
+    }
+
+    /// Returns whether any byte of the range is borrowed.
+    #[cfg(feature = "array-buffer")]
+    pub fn is_bytes_borrowed(&self, range: Range<usize>) -> bool {
+        let borrows = unsafe { &*self.byte_borrows.get() };
+        borrows
+            .iter()
+            .any(|(borrowed, _)| borrowed.start < range.end && range.start < borrowed.end)
+    }
+
+    /// Returns whether any byte of the range is borrowed mutably.
+    #[cfg(feature = "array-buffer")]
+    pub fn is_bytes_borrowed_mut(&self, range: Range<usize>) -> bool {
+        let borrows = unsafe { &*self.byte_borrows.get() };
+        borrows.iter().any(|(borrowed, borrowed_mut)| {
+            *borrowed_mut && borrowed.start < range.end && range.start < borrowed.end
+        })
+    }
+
+    /// Count a new persistent handle, recording where it was created with leak detection.
+    ///
+    /// Returns the id to unregister the handle with.
+    #[cfg_attr(not(feature = "leak-detection"), allow(unused_variables))]
+    pub fn register_persistent(&self, persistent: OutstandingPersistent) -> usize {
+        self.persistent_count.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "leak-detection")]
+        {
+            let id = self.next_persistent_id.fetch_add(1, Ordering::Relaxed);
+            self.persistents
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(id, persistent);
+            id
+        }
+        #[cfg(not(feature = "leak-detection"))]
+        0
+    }
+
+    /// Remove a persistent handle, may be called without holding the lock of the runtime.
+    #[cfg_attr(not(feature = "leak-detection"), allow(unused_variables))]
+    pub fn unregister_persistent(&self, id: usize) {
+        #[cfg(feature = "leak-detection")]
+        self.persistents
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id);
+        self.persistent_count.fetch_sub(1, Ordering::Release);
+    }
+
+    /// Returns the number of live persistent handles.
+    pub fn persistent_count(&self) -> usize {
+        self.persistent_count.load(Ordering::Acquire)
+    }
+
+    /// Returns where the live persistent handles were created, empty without leak detection.
+    pub fn outstanding_persistents(&self) -> Vec<OutstandingPersistent> {
+        #[cfg(feature = "leak-detection")]
+        return self
+            .persistents
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .copied()
+            .collect();
+        #[cfg(not(feature = "leak-detection"))]
+        Vec::new()
+    }
+
+    #[cfg(feature = "leak-detection")]
+    pub fn track_value(&self, ctx: *mut qjs::JSContext, value: qjs::JSValue) -> usize {
+        unsafe { (*self.live_values.get()).track(ctx, value) }
+    }
+
+    #[cfg(feature = "leak-detection")]
+    pub fn untrack_value(&self, id: usize) {
+        unsafe { (*self.live_values.get()).untrack(id) }
+    }
+
+    #[cfg(feature = "leak-detection")]
+    pub fn live_values(&self, ctx: *mut qjs::JSContext) -> Vec<crate::leak::LiveValue> {
+        unsafe { (*self.live_values.get()).live_values(ctx) }
+    }
+
+    #[cfg(feature = "leak-detection")]
+    pub fn value_references(&self, value: qjs::JSValue) -> Option<crate::leak::ValueReferences> {
+        unsafe { (*self.live_values.get()).references(value) }
+    }
+
+    #[cfg(feature = "leak-detection")]
+    pub fn trace_value(&self, value: qjs::JSValue) {
+        unsafe { (*self.live_values.get()).trace(value) }
+    }
+
+    #[cfg(feature = "leak-detection")]
+    pub fn take_value_trace(&self, value: qjs::JSValue) -> Vec<crate::leak::HandleEvent> {
+        unsafe { (*self.live_values.get()).take_trace(value) }
+    }
+
+    pub fn metrics(&self) -> &MetricsCounters {
+        &self.metrics
+    }
+
+    #[cfg(feature = "allocator")]
+    pub fn set_accounting(&mut self, accounting: Rc<MemoryAccounting>) {
+        self.accounting = Some(accounting);
+    }
+
+    #[cfg(feature = "allocator")]
+    pub fn accounting(&self) -> Option<&MemoryAccounting> {
+        self.accounting.as_deref()
+    }
+
+    /// Attribute the allocations made until the returned scope is dropped to the given context.
+    pub unsafe fn enter_context(&self, ctx: *mut qjs::JSContext) -> ContextScope {
+        #[cfg(not(feature = "allocator"))]
+        let _ = ctx;
+        ContextScope {
+            #[cfg(feature = "allocator")]
+            _scope: self
+                .accounting
+                .as_ref()
+                .map(|x| x.enter(ContextData::accounting_id(ctx))),
+            #[cfg(feature = "allocator")]
+            id: 0,
+        }
+    }
+
+    /// Attribute the allocations made until the returned scope is dropped to a new context.
+    ///
+    /// The context must be assigned to the scope with [`ContextScope::assign`] once created.
+    pub fn enter_new_context(&self) -> ContextScope {
+        #[cfg(feature = "allocator")]
+        if let Some(accounting) = self.accounting.as_ref() {
+            let id = accounting.new_id();
+            return ContextScope {
+                _scope: Some(accounting.enter(id)),
+                id,
+            };
+        }
+        ContextScope {
+            #[cfg(feature = "allocator")]
+            _scope: None,
+            #[cfg(feature = "allocator")]
+            id: 0,
+        }
+    }
+
     pub fn get_class_id(&self) -> qjs::JSClassID {
         self.class_id
     }
 
+    pub fn get_context_data_class_id(&self) -> qjs::JSClassID {
+        self.context_data_class_id
+    }
+
     pub fn get_callable_id(&self) -> qjs::JSClassID {
         self.callable_class_id
     }
 
+    /// Register a class under its name, returns the index of the class in the registry.
+    pub fn register_class(&self, name: &'static str, id: TypeId) -> Result<u32, Error> {
+        let classes = unsafe { &mut *self.classes.get() };
+        match classes.iter().position(|(x, _)| *x == name) {
+            Some(idx) if classes[idx].1 == id => Ok(idx as u32),
+            Some(_) => Err(Error::ClassConflict { name }),
+            None => {
+                classes.push((name, id));
+                Ok((classes.len() - 1) as u32)
+            }
+        }
+    }
+
+    /// Returns the index of the class registered under the name.
+    pub fn registered_class(&self, name: &str) -> Option<u32> {
+        let classes = unsafe { &*self.classes.get() };
+        classes
+            .iter()
+            .position(|(x, _)| *x == name)
+            .map(|x| x as u32)
+    }
+
+    /// Returns an error if the name of the class was registered by another type.
+    fn check_registered(&self, name: &'static str, id: TypeId) -> Result<(), Error> {
+        let classes = unsafe { &*self.classes.get() };
+        match classes.iter().find(|(x, _)| *x == name) {
+            Some((_, registered)) if *registered != id => Err(Error::ClassConflict { name }),
+            _ => Ok(()),
+        }
+    }
+
     pub fn get_or_insert_prototype<C: JsClass<'js>>(
         &self,
         ctx: &Ctx<'js>,
@@ -195,13 +786,14 @@ impl<'js> Opaque<'js> {
         unsafe {
             let vtable = VTable::get::<C>();
             let id = vtable.id();
-            match (*self.prototypes.get()).entry(id) {
-                Entry::Occupied(x) => Ok(x.get().clone()),
-                Entry::Vacant(x) => {
-                    let proto = C::prototype(ctx)?;
-                    Ok(x.insert(proto).clone())
-                }
+            if let Some(proto) = (*self.prototypes.get()).get(&id) {
+                return Ok(proto.clone());
             }
+            self.check_registered(C::NAME, id)?;
+            // Creating the prototype can create the prototypes of other classes, so the map can't
+            // be borrowed while it runs.
+            let proto = C::prototype(ctx)?;
+            Ok((*self.prototypes.get()).entry(id).or_insert(proto).clone())
         }
     }
 
@@ -211,8 +803,15 @@ impl<'js> Opaque<'js> {
     /// runtime.
     pub fn clear(&mut self) {
         self.interrupt_handler.get_mut().take();
+        #[cfg(feature = "std")]
+        self.deadline.take();
+        self.rejection_tracker.get_mut().take();
+        self.runtime.get_mut().take();
         self.panic.take();
+        self.panic_hook.get_mut().take();
+        self.leak_hook.get_mut().take();
         self.prototypes.get_mut().clear();
+        self.context_created.get_mut().clear();
         #[cfg(feature = "futures")]
         self.spawner.take();
         self.userdata.clear()
@@ -0,0 +1,208 @@
+use alloc::sync::Arc;
+use core::fmt;
+use std::{
+    error::Error as StdError,
+    sync::{mpsc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    CatchResultExt, CaughtError, Context, Error, FromJs, Result, Runtime, StdResult, StdString,
+};
+
+type Job = Box<dyn FnOnce(&Context) + Send>;
+
+/// An error returned from an evaluation by a [`RuntimePool`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PoolError {
+    /// The script threw an exception, contains the formatted exception.
+    Exception(StdString),
+    /// An error which was not thrown by the script, like a failed conversion of the result.
+    Error(Error),
+    /// The worker evaluating the script panicked.
+    Panicked,
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Exception(e) => e.fmt(f),
+            PoolError::Error(e) => e.fmt(f),
+            PoolError::Panicked => "Worker panicked during evaluation".fmt(f),
+        }
+    }
+}
+
+impl StdError for PoolError {}
+
+/// A pool of runtimes living on worker threads for evaluating independent scripts in parallel.
+///
+/// Every worker owns a [`Runtime`] with a single [`Context`] which is created by the template
+/// closure given to [`RuntimePool::new`]. Scripts are distributed over the workers so they can't
+/// share any JavaScript values, results are converted to Rust values before being returned.
+///
+/// ```
+/// # use rquickjs::{Context, runtime::RuntimePool};
+/// let pool = RuntimePool::new(4, |rt| {
+///     let context = Context::full(rt)?;
+///     context.with(|ctx| ctx.globals().set("base", 10))?;
+///     Ok(context)
+/// })
+/// .unwrap();
+///
+/// let results = pool.eval_all::<i32, _, _>((0..8).map(|i| format!("base + {i}")));
+/// assert_eq!(results[7].as_ref().unwrap(), &17);
+/// ```
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "parallel")))]
+pub struct RuntimePool {
+    sender: Option<mpsc::Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl RuntimePool {
+    /// Create a pool of `size` workers, at least one.
+    ///
+    /// The template is called on every worker with a new runtime and should return the context
+    /// in which the scripts are evaluated, it can be used to set limits on the runtime and to
+    /// define globals. Returns the first error returned by the template.
+    pub fn new<F>(size: usize, template: F) -> Result<Self>
+    where
+        F: Fn(&Runtime) -> Result<Context> + Send + Sync + 'static,
+    {
+        let template = Arc::new(template);
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let (ready_send, ready_recv) = mpsc::channel();
+
+        let workers = (0..size.max(1))
+            .map(|_| {
+                let template = template.clone();
+                let receiver = receiver.clone();
+                let ready_send = ready_send.clone();
+                thread::spawn(move || {
+                    let context = match Runtime::new().and_then(|rt| template(&rt)) {
+                        Ok(context) => context,
+                        Err(e) => {
+                            let _ = ready_send.send(Err(e));
+                            return;
+                        }
+                    };
+                    let _ = ready_send.send(Ok(()));
+                    loop {
+                        // The lock must be released before running the job.
+                        let job = receiver.lock().unwrap().recv();
+                        let Ok(job) = job else {
+                            break;
+                        };
+                        job(&context);
+                    }
+                })
+            })
+            .collect();
+        drop(ready_send);
+
+        let pool = RuntimePool {
+            sender: Some(sender),
+            workers,
+        };
+        for _ in 0..pool.workers.len() {
+            // A worker which panicked in the template never reports back.
+            ready_recv.recv().unwrap_or(Err(Error::Unknown))?;
+        }
+        Ok(pool)
+    }
+
+    /// Returns the number of workers in the pool.
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Evaluate all scripts on the workers of the pool.
+    ///
+    /// Blocks until all scripts are evaluated, the results are returned in the order of the
+    /// scripts.
+    pub fn eval_all<T, I, S>(&self, scripts: I) -> Vec<StdResult<T, PoolError>>
+    where
+        T: for<'js> FromJs<'js> + Send + 'static,
+        I: IntoIterator<Item = S>,
+        S: Into<Vec<u8>>,
+    {
+        let sender = self.sender.as_ref().unwrap();
+        let (result_send, result_recv) = mpsc::channel();
+        let mut results = Vec::new();
+
+        for (idx, script) in scripts.into_iter().enumerate() {
+            results.push(None);
+            let source = script.into();
+            let result_send = result_send.clone();
+            let job: Job = Box::new(move |context| {
+                let res = context.with(|ctx| {
+                    ctx.eval::<T, _>(source)
+                        .catch(&ctx)
+                        .map_err(|error| match error {
+                            CaughtError::Error(e) => PoolError::Error(e),
+                            e => PoolError::Exception(e.to_string()),
+                        })
+                });
+                let _ = result_send.send((idx, res));
+            });
+            // If all workers are gone the job is dropped and reported as panicked.
+            let _ = sender.send(job);
+        }
+        drop(result_send);
+
+        for (idx, res) in result_recv {
+            results[idx] = Some(res);
+        }
+        results
+            .into_iter()
+            .map(|res| res.unwrap_or(Err(PoolError::Panicked)))
+            .collect()
+    }
+}
+
+impl Drop for RuntimePool {
+    fn drop(&mut self) {
+        // Closing the channel stops the workers once they are idle.
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PoolError, RuntimePool};
+    use crate::Context;
+
+    #[test]
+    fn eval_all_in_order() {
+        let pool = RuntimePool::new(3, |rt| {
+            let context = Context::full(rt)?;
+            context.with(|ctx| ctx.globals().set("base", 100))?;
+            Ok(context)
+        })
+        .unwrap();
+        assert_eq!(pool.size(), 3);
+
+        let results = pool.eval_all::<i32, _, _>((0..20).map(|i| format!("base + {i}")));
+        let results = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(results, (100..120).collect::<Vec<_>>());
+
+        let results = pool.eval_all::<i32, _, _>(["throw new Error('oops')", "'text'", "1"]);
+        match &results[0] {
+            Err(PoolError::Exception(e)) => assert!(e.contains("oops")),
+            x => panic!("unexpected result {x:?}"),
+        }
+        assert!(matches!(results[1], Err(PoolError::Error(_))));
+        assert_eq!(results[2].as_ref().unwrap(), &1);
+    }
+
+    #[test]
+    fn template_error() {
+        let res = RuntimePool::new(2, |_| Err(crate::Error::Unknown));
+        assert!(res.is_err());
+    }
+}
@@ -1,22 +1,28 @@
 #![allow(dead_code)]
-use std::{
-    ffi::CString,
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use core::{
     mem,
-    panic::{self, AssertUnwindSafe},
-    ptr::NonNull,
+    panic::AssertUnwindSafe,
+    ptr::{self, NonNull},
     result::Result as StdResult,
 };
 
 #[cfg(feature = "allocator")]
-use crate::allocator::{Allocator, AllocatorHolder};
+use alloc::rc::Rc;
+
+#[cfg(feature = "allocator")]
+use crate::allocator::{AccountingAllocator, Allocator, AllocatorHolder, MemoryAccounting};
 #[cfg(feature = "loader")]
 use crate::loader::{Loader, LoaderHolder, Resolver};
 use crate::{
     qjs::{self, size_t},
-    Error, Result,
+    util, Ctx, Error, Result, Value,
 };
 
-use super::{opaque::Opaque, InterruptHandler};
+use super::{
+    opaque::Opaque, InterruptHandler, Metrics, PanicAction, RejectionTracker, ShutdownError,
+};
 
 const DUMP_BYTECODE_FINAL: u64 = 0x01;
 const DUMP_BYTECODE_PASS2: u64 = 0x02;
@@ -130,6 +136,27 @@ impl Drop for RawRuntime {
         unsafe {
             let ptr = qjs::JS_GetRuntimeOpaque(self.rt.as_ptr());
             let mut opaque: Box<Opaque> = Box::from_raw(ptr as *mut _);
+            let outstanding = opaque.persistent_count();
+            if outstanding != 0 {
+                // Freeing the runtime would fail an assertion inside QuickJS and the handles
+                // still refer to it, so leak it instead.
+                let error = ShutdownError {
+                    runtime: (),
+                    outstanding,
+                    persistents: opaque.outstanding_persistents(),
+                };
+                match opaque.take_leak_hook() {
+                    Some(hook) => hook(error),
+                    None => report_leak(&error),
+                }
+                Box::leak(opaque);
+                mem::forget(self.info.take());
+                #[cfg(feature = "allocator")]
+                mem::forget(self.allocator.take());
+                #[cfg(feature = "loader")]
+                mem::forget(self.loader.take());
+                return;
+            }
             opaque.clear();
             qjs::JS_FreeRuntime(self.rt.as_ptr());
             mem::drop(opaque);
@@ -137,6 +164,17 @@ impl Drop for RawRuntime {
     }
 }
 
+/// Report a leaked runtime as a `tracing` event if no leak hook was set.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn report_leak(error: &ShutdownError<()>) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        target: "rquickjs",
+        outstanding = error.outstanding,
+        "leaked a dropped runtime which is still in use, {error}"
+    );
+}
+
 impl RawRuntime {
     pub unsafe fn new(opaque: Opaque<'static>) -> Result<Self> {
         #[cfg(not(feature = "rust-alloc"))]
@@ -198,6 +236,16 @@ impl RawRuntime {
         })
     }
 
+    #[cfg(feature = "allocator")]
+    pub unsafe fn new_with_accounting<A>(mut opaque: Opaque<'static>, allocator: A) -> Result<Self>
+    where
+        A: Allocator + 'static,
+    {
+        let accounting = Rc::new(MemoryAccounting::default());
+        opaque.set_accounting(accounting.clone());
+        Self::new_with_allocator(opaque, AccountingAllocator::new(allocator, accounting))
+    }
+
     pub fn update_stack_top(&self) {
         #[cfg(feature = "parallel")]
         unsafe {
@@ -214,12 +262,16 @@ impl RawRuntime {
     }
 
     pub fn execute_pending_job(&mut self) -> StdResult<bool, *mut qjs::JSContext> {
+        trace_span!("execute_pending_job");
         let mut ctx_ptr = mem::MaybeUninit::<*mut qjs::JSContext>::uninit();
         let result = unsafe { qjs::JS_ExecutePendingJob(self.rt.as_ptr(), ctx_ptr.as_mut_ptr()) };
         if result == 0 {
             // no jobs executed
             return Ok(false);
         }
+        unsafe {
+            super::record_metric(ctx_ptr.assume_init(), super::MetricsCounters::job_executed)
+        };
         if result == 1 {
             // single job executed
             return Ok(true);
@@ -273,6 +325,28 @@ impl RawRuntime {
         qjs::JS_SetDumpFlags(self.rt.as_ptr(), flags);
     }
 
+    /// Report the handles to values of a context which are still alive after it was freed.
+    ///
+    /// Runs the garbage collector first so handles owned by unreachable objects are released.
+    /// Takes the runtime pointer so it can be called by the thread holding the lock without
+    /// access to the `RawRuntime`.
+    #[cfg(feature = "leak-detection")]
+    pub unsafe fn report_leaks(rt: *mut qjs::JSRuntime, ctx: *mut qjs::JSContext) {
+        qjs::JS_RunGC(rt);
+        let live = Opaque::from_runtime_ptr(rt).live_values(ctx);
+        if live.is_empty() {
+            return;
+        }
+        let mut report = format!(
+            "{} handles to values of a dropped context were not released:",
+            live.len()
+        );
+        for value in live {
+            report.push_str(&format!("\n\n{value}"));
+        }
+        eprintln!("{report}");
+    }
+
     /// Manually run the garbage collection.
     ///
     /// Most of QuickJS values are reference counted and
@@ -281,6 +355,26 @@ impl RawRuntime {
     /// cyclic references.
     pub unsafe fn run_gc(&mut self) {
         qjs::JS_RunGC(self.rt.as_ptr());
+        // The bundled sources count collections themselves, see `metrics::count_gc`.
+        #[cfg(feature = "system")]
+        {
+            let usage = self.memory_usage();
+            let metrics = self.get_opaque().metrics();
+            metrics.gc_run();
+            metrics.sample_memory(&usage);
+        }
+    }
+
+    /// Get the collected metrics
+    pub unsafe fn metrics(&mut self) -> Metrics {
+        let usage = self.memory_usage();
+        let metrics = self.get_opaque().metrics();
+        metrics.sample_memory(&usage);
+        #[cfg(feature = "allocator")]
+        if let Some(allocator) = self.allocator.as_ref() {
+            metrics.record_peak(allocator.peak() as u64);
+        }
+        metrics.snapshot()
     }
 
     /// Get memory usage stats
@@ -294,35 +388,22 @@ impl RawRuntime {
     /// If the provided closure returns `true` the interpreter will raise and uncatchable
     /// exception and return control flow to the caller.
     pub unsafe fn set_interrupt_handler(&mut self, handler: Option<InterruptHandler>) {
-        unsafe extern "C" fn interrupt_handler_trampoline(
-            _rt: *mut qjs::JSRuntime,
-            opaque: *mut ::std::os::raw::c_void,
-        ) -> ::std::os::raw::c_int {
-            // This should be safe as the value is set below to a non-null pointer.
-            let opaque = NonNull::new_unchecked(opaque).cast::<Opaque>();
-
-            let catch_unwind = panic::catch_unwind(AssertUnwindSafe(move || {
-                opaque.as_ref().run_interrupt_handler()
-            }));
-            let should_interrupt = match catch_unwind {
-                Ok(should_interrupt) => should_interrupt,
-                Err(panic) => {
-                    opaque.as_ref().set_panic(panic);
-                    // Returning true here will cause the interpreter to raise an un-catchable exception.
-                    // The Rust code that is running the interpreter will see that exception and continue
-                    // the panic handling. See crate::result::{handle_exception, handle_panic} for details.
-                    true
-                }
-            };
-            should_interrupt as _
-        }
+        self.get_opaque().set_interrupt_handler(handler);
+        update_interrupt_handler(self.rt.as_ptr());
+    }
 
-        qjs::JS_SetInterruptHandler(
+    /// Set a closure which is called when a promise is rejected without a handler, or when a
+    /// handler is attached to such a promise later.
+    pub unsafe fn set_rejection_tracker(&mut self, tracker: Option<RejectionTracker>) {
+        let opaque = self.get_opaque();
+        opaque.set_rejection_tracker(tracker);
+        qjs::JS_SetHostPromiseRejectionTracker(
             self.rt.as_ptr(),
-            handler.as_ref().map(|_| interrupt_handler_trampoline as _),
-            qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()),
+            opaque
+                .has_rejection_tracker()
+                .then_some(rejection_tracker_trampoline as _),
+            ptr::null_mut(),
         );
-        self.get_opaque().set_interrupt_handler(handler);
     }
 
     fn add_dump_flags(rt: *mut rquickjs_sys::JSRuntime) {
@@ -331,3 +412,62 @@ impl RawRuntime {
         }
     }
 }
+
+unsafe extern "C" fn interrupt_handler_trampoline(
+    _rt: *mut qjs::JSRuntime,
+    opaque: *mut qjs::c_void,
+) -> qjs::c_int {
+    // This should be safe as the value is set below to a non-null pointer.
+    let opaque = NonNull::new_unchecked(opaque).cast::<Opaque>();
+
+    let catch_unwind = util::catch_unwind(AssertUnwindSafe(move || {
+        opaque.as_ref().run_interrupt_handler()
+    }));
+    let should_interrupt = match catch_unwind {
+        Ok(should_interrupt) => should_interrupt,
+        Err(panic) => {
+            let opaque = opaque.as_ref();
+            match opaque.panic_action(&*panic) {
+                PanicAction::Resume => opaque.set_panic(panic),
+                // There is no context to throw in, the interrupt below is raised instead.
+                PanicAction::Throw => {}
+                PanicAction::Abort => util::abort(),
+            }
+            // Returning true here will cause the interpreter to raise an un-catchable exception.
+            // The Rust code that is running the interpreter will see that exception and continue
+            // the panic handling. See crate::result::{handle_exception, handle_panic} for details.
+            true
+        }
+    };
+    should_interrupt as _
+}
+
+unsafe extern "C" fn rejection_tracker_trampoline(
+    ctx: *mut qjs::JSContext,
+    promise: qjs::JSValue,
+    reason: qjs::JSValue,
+    is_handled: qjs::c_int,
+    _opaque: *mut qjs::c_void,
+) {
+    let ctx = Ctx::from_ptr(ctx);
+    ctx.catch_panic(
+        AssertUnwindSafe(|| {
+            let promise = Value::from_js_value_const(ctx.clone(), promise);
+            let reason = Value::from_js_value_const(ctx.clone(), reason);
+            ctx.get_opaque()
+                .run_rejection_tracker(&ctx, promise, reason, is_handled != 0)
+        }),
+        (),
+    )
+}
+
+/// Install the interrupt handler trampoline if either a handler or a deadline is set in the
+/// opaque of the runtime and remove it otherwise.
+pub(crate) unsafe fn update_interrupt_handler(rt: *mut qjs::JSRuntime) {
+    let needed = Opaque::from_runtime_ptr(rt).needs_interrupt_handler();
+    qjs::JS_SetInterruptHandler(
+        rt,
+        needed.then_some(interrupt_handler_trampoline as _),
+        qjs::JS_GetRuntimeOpaque(rt),
+    );
+}
@@ -1,9 +1,10 @@
-use std::{
+use alloc::sync::Arc;
+use core::{
     cell::Cell,
     future::Future,
     mem::offset_of,
     pin::Pin,
-    sync::{atomic::Ordering, Arc},
+    sync::atomic::Ordering,
     task::{Context, Poll},
 };
 
@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+use core::{fmt, panic::Location};
+#[cfg(feature = "std")]
+use std::error::Error as StdError;
+
+/// A [`Persistent`](crate::Persistent) handle which is still alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutstandingPersistent {
+    /// The type of the saved value.
+    pub type_name: &'static str,
+    /// Where the handle was saved or cloned.
+    pub location: &'static Location<'static>,
+}
+
+impl fmt::Display for OutstandingPersistent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} saved at {}", self.type_name, self.location)
+    }
+}
+
+/// The error returned when shutting down a runtime whose values are still kept alive by
+/// [`Persistent`](crate::Persistent) handles.
+///
+/// The runtime is handed back so it can be shut down again once the handles are dropped.
+pub struct ShutdownError<R> {
+    /// The runtime which was not shut down.
+    pub runtime: R,
+    /// The number of handles which are still alive.
+    pub outstanding: usize,
+    /// Where the handles which are still alive were created, in the order they were created.
+    ///
+    /// Only recorded with the `leak-detection` feature, empty otherwise.
+    pub persistents: Vec<OutstandingPersistent>,
+}
+
+impl<R> fmt::Debug for ShutdownError<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownError")
+            .field("outstanding", &self.outstanding)
+            .field("persistents", &self.persistents)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R> fmt::Display for ShutdownError<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} outstanding persistent handles", self.outstanding)?;
+        if self.persistents.is_empty() {
+            return Ok(());
+        }
+        f.write_str(":")?;
+        for persistent in &self.persistents {
+            write!(f, "\n  {persistent}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> StdError for ShutdownError<R> {}
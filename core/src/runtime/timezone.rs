@@ -0,0 +1,57 @@
+use alloc::boxed::Box;
+use core::panic::AssertUnwindSafe;
+use std::{
+    panic,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::qjs;
+
+type Provider = Box<dyn Fn(i64) -> i32 + Send + Sync>;
+
+static PROVIDER: RwLock<Option<Provider>> = RwLock::new(None);
+
+/// Set the function supplying the local time zone offset used by `Date`.
+///
+/// The provider receives a time in milliseconds since the epoch and returns the difference
+/// between UTC and local time in minutes, positive west of Greenwich like
+/// `Date.prototype.getTimezoneOffset`. When converting local date fields to a time QuickJS passes
+/// the local time instead, so the offset around a transition can be off like with the C library.
+///
+/// The provider replaces the C library, which honors the `TZ` environment variable, for every
+/// runtime in the process and is called from any thread running one. If it panics the offset of
+/// the C library is used for that call.
+///
+/// Not available with the `system` feature since the hook is patched into the bundled sources,
+/// and requires the `std` feature.
+///
+/// ```
+/// # use rquickjs::{runtime, Context, Runtime};
+/// runtime::set_timezone_offset(|_time| -60);
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     let offset: i32 = ctx.eval("new Date(0).getTimezoneOffset()").unwrap();
+///     assert_eq!(offset, -60);
+/// });
+/// runtime::reset_timezone_offset();
+/// ```
+pub fn set_timezone_offset<F>(provider: F)
+where
+    F: Fn(i64) -> i32 + Send + Sync + 'static,
+{
+    *PROVIDER.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(provider));
+    qjs::set_timezone_offset_hook(Some(timezone_offset));
+}
+
+/// Remove the provider set with [`set_timezone_offset`], restoring the offset of the C library.
+pub fn reset_timezone_offset() {
+    qjs::set_timezone_offset_hook(None);
+    *PROVIDER.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+fn timezone_offset(time: i64) -> Option<i32> {
+    let provider = PROVIDER.read().unwrap_or_else(PoisonError::into_inner);
+    let provider = provider.as_ref()?;
+    panic::catch_unwind(AssertUnwindSafe(|| provider(time))).ok()
+}
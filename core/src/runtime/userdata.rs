@@ -1,12 +1,15 @@
+use alloc::boxed::Box;
 use core::fmt;
-use std::{
+#[cfg(feature = "std")]
+use core::hash::{BuildHasherDefault, Hasher};
+use core::{
     any::{Any, TypeId},
     cell::{Cell, UnsafeCell},
-    collections::HashMap,
-    hash::{BuildHasherDefault, Hasher},
     mem::ManuallyDrop,
     ops::Deref,
 };
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
 use crate::JsLifetime;
 
@@ -15,13 +18,13 @@ where
     T: Sized,
 {
     assert_eq!(
-        std::mem::size_of::<T>(),
-        std::mem::size_of::<T::Changed<'static>>(),
+        core::mem::size_of::<T>(),
+        core::mem::size_of::<T::Changed<'static>>(),
         "Invalid implementation of JsLifetime, size_of::<T>() != size_of::<T::Changed<'static>>()"
     );
     assert_eq!(
-        std::mem::align_of::<T>(),
-        std::mem::align_of::<T::Changed<'static>>(),
+        core::mem::align_of::<T>(),
+        core::mem::align_of::<T::Changed<'static>>(),
         "Invalid implementation of JsLifetime, align_of::<T>() != align_of::<T::Changed<'static>>()"
     );
 
@@ -45,13 +48,13 @@ where
     T: Sized,
 {
     assert_eq!(
-        std::mem::size_of::<T>(),
-        std::mem::size_of::<T::Changed<'static>>(),
+        core::mem::size_of::<T>(),
+        core::mem::size_of::<T::Changed<'static>>(),
         "Invalid implementation of JsLifetime, size_of::<T>() != size_of::<T::Changed<'static>>()"
     );
     assert_eq!(
-        std::mem::align_of::<T>(),
-        std::mem::align_of::<T::Changed<'static>>(),
+        core::mem::align_of::<T>(),
+        core::mem::align_of::<T::Changed<'static>>(),
         "Invalid implementation of JsLifetime, align_of::<T>() != align_of::<T::Changed<'static>>()"
     );
 
@@ -62,13 +65,13 @@ unsafe fn from_static_ref<'a, 'js, T: JsLifetime<'js>>(this: &'a T::Changed<'sta
 where
     T: Sized,
 {
-    std::mem::transmute(this)
+    core::mem::transmute(this)
 }
 
 pub struct UserDataError<T>(pub T);
 
 impl<T> fmt::Display for UserDataError<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "tried to mutate the user data store while it was being referenced"
@@ -77,14 +80,16 @@ impl<T> fmt::Display for UserDataError<T> {
 }
 
 impl<T> fmt::Debug for UserDataError<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         fmt::Display::fmt(self, f)
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Default)]
 struct IdHasher(u64);
 
+#[cfg(feature = "std")]
 impl Hasher for IdHasher {
     fn write(&mut self, _: &[u8]) {
         unreachable!("TypeId calls write_u64");
@@ -100,9 +105,14 @@ impl Hasher for IdHasher {
 }
 
 /// Typeid hashmap taken from axum.
+#[cfg(feature = "std")]
+type Map = HashMap<TypeId, Box<dyn Any>, BuildHasherDefault<IdHasher>>;
+#[cfg(not(feature = "std"))]
+type Map = alloc::collections::BTreeMap<TypeId, Box<dyn Any>>;
+
 #[derive(Default)]
 pub(crate) struct UserDataMap {
-    map: UnsafeCell<HashMap<TypeId, Box<dyn Any>, BuildHasherDefault<IdHasher>>>,
+    map: UnsafeCell<Map>,
     count: Cell<usize>,
 }
 
@@ -1,17 +1,18 @@
 #[cfg(not(feature = "parallel"))]
-use std::cell::RefCell as Cell;
+use core::cell::RefCell as Cell;
 
 #[cfg(feature = "parallel")]
 use std::sync::Mutex as Cell;
 
 #[cfg(not(feature = "parallel"))]
-pub use std::{
-    cell::RefMut as Lock,
-    rc::{Rc as Ref, Weak},
-};
+pub use alloc::rc::{Rc as Ref, Weak};
+#[cfg(not(feature = "parallel"))]
+pub use core::cell::RefMut as Lock;
 
 #[cfg(feature = "parallel")]
-pub use std::sync::{Arc as Ref, MutexGuard as Lock, Weak};
+pub use alloc::sync::{Arc as Ref, Weak};
+#[cfg(feature = "parallel")]
+pub use std::sync::MutexGuard as Lock;
 
 #[repr(transparent)]
 pub struct Mut<T: ?Sized>(Cell<T>);
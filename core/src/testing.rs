@@ -0,0 +1,576 @@
+//! Utilities for testing code which produces JavaScript values.
+
+use crate::{Array, Ctx, Error, FromJs, Function, IntoJs, Object, Result, StdString, Type, Value};
+use core::fmt;
+
+/// A difference between two JavaScript values found by [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// The path of the differing value, like `$.items[2].name` where `$` is the compared value.
+    pub path: StdString,
+    /// A description of the actual value.
+    pub actual: StdString,
+    /// A description of the expected value.
+    pub expected: StdString,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} != {}", self.path, self.actual, self.expected)
+    }
+}
+
+/// Compare two values structurally and return where they differ.
+///
+/// Arrays are compared element by element and other objects by their own enumerable string keyed
+/// properties, prototypes are ignored. All other values, and functions, are compared like
+/// `Object.is` does.
+pub fn diff<'js>(actual: &Value<'js>, expected: &Value<'js>) -> Result<Vec<Difference>> {
+    let mut differ = Differ {
+        path: StdString::from("$"),
+        visiting: Vec::new(),
+        differences: Vec::new(),
+    };
+    differ.diff(actual, expected)?;
+    Ok(differ.differences)
+}
+
+/// Assert that a value is structurally equal to the value of a JavaScript expression.
+///
+/// The expression is evaluated in the given context. Panics with every difference found by
+/// [`diff`] if the values are not equal.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, testing::assert_js_eq};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let value = ctx.eval::<rquickjs::Value, _>("({ ok: true, items: [1, 2] })").unwrap();
+/// assert_js_eq(&ctx, value, "{ items: [1, 2], ok: true }");
+/// assert_js_eq(&ctx, vec!["a", "b"], "['a', 'b']");
+/// # });
+/// ```
+#[track_caller]
+pub fn assert_js_eq<'js, V: IntoJs<'js>>(ctx: &Ctx<'js>, actual: V, expected: &str) {
+    let actual = match actual.into_js(ctx) {
+        Ok(x) => x,
+        Err(e) => panic!("failed to convert the actual value: {e}"),
+    };
+    let expected = match ctx.eval::<Value, _>(format!("({expected})")) {
+        Ok(x) => x,
+        Err(e) => panic!("failed to evaluate `{expected}`: {e}"),
+    };
+    let differences = match diff(&actual, &expected) {
+        Ok(x) => x,
+        Err(e) => panic!("failed to compare the values: {e}"),
+    };
+    if !differences.is_empty() {
+        let mut message = StdString::from("values are not equal (actual != expected):");
+        for difference in differences {
+            message.push_str("\n  ");
+            message.push_str(&difference.to_string());
+        }
+        panic!("{message}");
+    }
+}
+
+struct Differ<'js> {
+    path: StdString,
+    /// The pairs of objects currently being compared, to stop at reference cycles.
+    visiting: Vec<(Value<'js>, Value<'js>)>,
+    differences: Vec<Difference>,
+}
+
+impl<'js> Differ<'js> {
+    fn diff(&mut self, actual: &Value<'js>, expected: &Value<'js>) -> Result<()> {
+        if actual.same_value(expected) {
+            return Ok(());
+        }
+        let nested = match (actual.as_array(), expected.as_array()) {
+            (Some(_), Some(_)) => true,
+            (None, None) => actual.type_of() == Type::Object && expected.type_of() == Type::Object,
+            _ => false,
+        };
+        if !nested {
+            self.report(actual, expected);
+            return Ok(());
+        }
+
+        let pair = (actual.clone(), expected.clone());
+        if self.visiting.contains(&pair) {
+            return Ok(());
+        }
+        self.visiting.push(pair);
+        let res = match (actual.as_array(), expected.as_array()) {
+            (Some(actual), Some(expected)) => self.diff_array(actual, expected),
+            _ => self.diff_object(actual.as_object().unwrap(), expected.as_object().unwrap()),
+        };
+        self.visiting.pop();
+        res
+    }
+
+    fn diff_array(&mut self, actual: &Array<'js>, expected: &Array<'js>) -> Result<()> {
+        for idx in 0..actual.len().max(expected.len()) {
+            let len = self.path.len();
+            self.path.push_str(&format!("[{idx}]"));
+            self.diff(&actual.get(idx)?, &expected.get(idx)?)?;
+            self.path.truncate(len);
+        }
+        Ok(())
+    }
+
+    fn diff_object(&mut self, actual: &Object<'js>, expected: &Object<'js>) -> Result<()> {
+        let mut keys = actual.keys().collect::<Result<Vec<StdString>>>()?;
+        for key in expected.keys::<StdString>() {
+            let key = key?;
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        for key in keys {
+            let len = self.path.len();
+            self.path.push('.');
+            self.path.push_str(&key);
+            self.diff(&actual.get(key.as_str())?, &expected.get(key.as_str())?)?;
+            self.path.truncate(len);
+        }
+        Ok(())
+    }
+
+    fn report(&mut self, actual: &Value<'js>, expected: &Value<'js>) {
+        self.differences.push(Difference {
+            path: self.path.clone(),
+            actual: describe(actual),
+            expected: describe(expected),
+        })
+    }
+}
+
+/// Returns a short description of a value.
+fn describe(value: &Value) -> StdString {
+    match value.type_of() {
+        Type::String => format!("{:?}", value.get::<StdString>().unwrap_or_default()),
+        Type::Int | Type::Float => {
+            let num = value.as_number().unwrap_or_default();
+            if num.is_infinite() {
+                if num > 0.0 { "Infinity" } else { "-Infinity" }.into()
+            } else if num == 0.0 && num.is_sign_negative() {
+                "-0".into()
+            } else {
+                num.to_string()
+            }
+        }
+        Type::Bool => value.as_bool().unwrap_or_default().to_string(),
+        Type::Array => format!(
+            "array of length {}",
+            value.as_array().map(|x| x.len()).unwrap_or_default()
+        ),
+        type_ => type_.as_str().into(),
+    }
+}
+
+/// Render a value as canonical, stable text for snapshot tests.
+///
+/// Objects are rendered with their own enumerable properties sorted by key and tagged with the
+/// name of their constructor, so the rendering doesn't depend on the order properties were
+/// defined in. Arrays, `Map`s and `Set`s keep their order. Errors are rendered with their name and
+/// message but without their stack, dates as ISO strings and references back to an object being
+/// rendered as `[Circular]`. Nested values are indented by two spaces with a trailing comma after
+/// every entry.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Value, testing::snapshot};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let value: Value = ctx
+///     .eval("({ list: [1, 'two'], when: new Date(0), error: new TypeError('bad'), a: null })")
+///     .unwrap();
+/// assert_eq!(
+///     snapshot(&value).unwrap(),
+///     r#"{
+///   a: null,
+///   error: TypeError("bad"),
+///   list: [
+///     1,
+///     "two",
+///   ],
+///   when: Date(1970-01-01T00:00:00.000Z),
+/// }"#
+/// );
+/// # });
+/// ```
+pub fn snapshot<'js>(value: &Value<'js>) -> Result<StdString> {
+    let mut snapshot = Snapshot {
+        helpers: value.ctx().eval(SNAPSHOT_HELPERS)?,
+        out: StdString::new(),
+        visiting: Vec::new(),
+    };
+    snapshot.write(value, 0)?;
+    Ok(snapshot.out)
+}
+
+/// Evaluate a script and render its result with [`snapshot`].
+///
+/// If the script throws, the exception is rendered prefixed with `throws `, so tests can snapshot
+/// failures as well:
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, testing::snapshot_eval};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let res = snapshot_eval(&ctx, "throw Object.assign(new RangeError('too big'), { limit: 3 })");
+/// assert_eq!(res.unwrap(), "throws RangeError(\"too big\") {\n  limit: 3,\n}");
+/// # });
+/// ```
+pub fn snapshot_eval<'js>(ctx: &Ctx<'js>, source: &str) -> Result<StdString> {
+    match ctx.eval::<Value, _>(source) {
+        Ok(value) => snapshot(&value),
+        Err(Error::Exception) => Ok(format!("throws {}", snapshot(&ctx.catch())?)),
+        Err(e) => Err(e),
+    }
+}
+
+const SNAPSHOT_HELPERS: &str = r#"({
+    tag: (value) => Object.prototype.toString.call(value).slice(8, -1),
+    constructor: (value) => {
+        const prototype = Object.getPrototypeOf(value);
+        if (prototype === null) return null;
+        const name = prototype.constructor?.name;
+        return typeof name === "string" ? name : "";
+    },
+    isView: (value) => ArrayBuffer.isView(value),
+    list: (value) => Array.from(value instanceof ArrayBuffer ? new Uint8Array(value) : value),
+    string: (value) => String(value),
+    date: (value) => Number.isNaN(value.getTime()) ? "Invalid Date" : value.toISOString(),
+})"#;
+
+/// An entry of a rendered object.
+enum Item<'js> {
+    Value(Value<'js>),
+    Property(StdString, Value<'js>),
+    Pair(Value<'js>, Value<'js>),
+}
+
+struct Snapshot<'js> {
+    helpers: Object<'js>,
+    out: StdString,
+    /// The objects currently being rendered, to stop at reference cycles.
+    visiting: Vec<Value<'js>>,
+}
+
+impl<'js> Snapshot<'js> {
+    fn helper<T: FromJs<'js>>(&self, name: &str, value: &Value<'js>) -> Result<T> {
+        self.helpers
+            .get::<_, Function>(name)?
+            .call((value.clone(),))
+    }
+
+    fn write(&mut self, value: &Value<'js>, depth: usize) -> Result<()> {
+        match value.type_of() {
+            Type::String => {
+                let text = format!("{:?}", value.get::<StdString>()?);
+                self.out.push_str(&text);
+                return Ok(());
+            }
+            Type::Symbol => {
+                let description = value.as_symbol().unwrap().description()?;
+                let description = match description.as_string() {
+                    Some(x) => x.to_string()?,
+                    None => StdString::new(),
+                };
+                self.out.push_str(&format!("Symbol({description})"));
+                return Ok(());
+            }
+            Type::BigInt => {
+                let text: StdString = self.helper("string", value)?;
+                self.out.push_str(&format!("{text}n"));
+                return Ok(());
+            }
+            Type::Function | Type::Constructor => {
+                let name: StdString = value.as_object().unwrap().get("name").unwrap_or_default();
+                match name.as_str() {
+                    "" => self.out.push_str("[Function (anonymous)]"),
+                    name => self.out.push_str(&format!("[Function {name}]")),
+                }
+                return Ok(());
+            }
+            Type::Array | Type::Object | Type::Exception | Type::Promise => {}
+            _ => {
+                self.out.push_str(&describe(value));
+                return Ok(());
+            }
+        }
+
+        if self.visiting.contains(value) {
+            self.out.push_str("[Circular]");
+            return Ok(());
+        }
+        self.visiting.push(value.clone());
+        let res = self.write_object(value, depth);
+        self.visiting.pop();
+        res
+    }
+
+    fn write_object(&mut self, value: &Value<'js>, depth: usize) -> Result<()> {
+        let object = value.as_object().unwrap();
+        if let Some(array) = value.as_array() {
+            let items = array
+                .iter()
+                .map(|x| x.map(Item::Value))
+                .collect::<Result<_>>()?;
+            return self.write_block("", ("[", "]"), items, depth);
+        }
+        if let Some(promise) = value.as_promise() {
+            match promise.result::<Value>() {
+                None => self.out.push_str("Promise <pending>"),
+                Some(Ok(value)) => {
+                    self.out.push_str("Promise <fulfilled> ");
+                    self.write(&value, depth)?;
+                }
+                Some(Err(Error::Exception)) => {
+                    self.out.push_str("Promise <rejected> ");
+                    self.write(&value.ctx().catch(), depth)?;
+                }
+                Some(Err(e)) => return Err(e),
+            }
+            return Ok(());
+        }
+        if value.is_error() {
+            let name: StdString = self.helper("string", &object.get("name")?)?;
+            let message: StdString = self.helper("string", &object.get("message")?)?;
+            let head = format!("{name}({message:?})");
+            let items = self.properties(object)?;
+            if items.is_empty() {
+                self.out.push_str(&head);
+                return Ok(());
+            }
+            return self.write_block(&head, ("{", "}"), items, depth);
+        }
+
+        let tag: StdString = self.helper("tag", value)?;
+        match tag.as_str() {
+            "Date" => {
+                let date: StdString = self.helper("date", value)?;
+                self.out.push_str(&format!("Date({date})"));
+                Ok(())
+            }
+            "RegExp" => {
+                let regexp: StdString = self.helper("string", value)?;
+                self.out.push_str(&regexp);
+                Ok(())
+            }
+            "Map" | "Set" => {
+                let entries: Vec<Value> = self.helper("list", value)?;
+                let items = entries
+                    .into_iter()
+                    .map(|entry| match entry.as_array() {
+                        Some(pair) if tag == "Map" => Ok(Item::Pair(pair.get(0)?, pair.get(1)?)),
+                        _ => Ok(Item::Value(entry)),
+                    })
+                    .collect::<Result<_>>()?;
+                let brackets = if tag == "Map" { ("{", "}") } else { ("[", "]") };
+                self.write_block(&tag, brackets, items, depth)
+            }
+            "ArrayBuffer" | "SharedArrayBuffer" => self.write_bytes(&tag, value),
+            _ if self.helper::<bool>("isView", value)? => self.write_bytes(&tag, value),
+            _ => {
+                let head = match self.helper::<Option<StdString>>("constructor", value)? {
+                    None => StdString::from("[Object: null prototype]"),
+                    Some(name) if name == "Object" => StdString::new(),
+                    Some(name) => name,
+                };
+                let items = self.properties(object)?;
+                self.write_block(&head, ("{", "}"), items, depth)
+            }
+        }
+    }
+
+    /// The own enumerable string keyed properties of an object, sorted by key.
+    fn properties(&self, object: &Object<'js>) -> Result<Vec<Item<'js>>> {
+        let mut keys = object.keys().collect::<Result<Vec<StdString>>>()?;
+        keys.sort();
+        keys.into_iter()
+            .map(|key| {
+                let value = object.get(key.as_str())?;
+                Ok(Item::Property(key, value))
+            })
+            .collect()
+    }
+
+    fn write_bytes(&mut self, tag: &str, value: &Value<'js>) -> Result<()> {
+        let items: Vec<Value> = self.helper("list", value)?;
+        let items: Vec<StdString> = items.iter().map(describe).collect();
+        self.out.push_str(&format!("{tag} [{}]", items.join(", ")));
+        Ok(())
+    }
+
+    fn write_block(
+        &mut self,
+        head: &str,
+        (open, close): (&str, &str),
+        items: Vec<Item<'js>>,
+        depth: usize,
+    ) -> Result<()> {
+        if !head.is_empty() {
+            self.out.push_str(head);
+            self.out.push(' ');
+        }
+        self.out.push_str(open);
+        if items.is_empty() {
+            self.out.push_str(close);
+            return Ok(());
+        }
+        self.out.push('\n');
+        for item in items {
+            self.indent(depth + 1);
+            match item {
+                Item::Value(value) => self.write(&value, depth + 1)?,
+                Item::Property(key, value) => {
+                    let identifier = matches!(
+                        key.chars().next(),
+                        Some(x) if x.is_alphabetic() || x == '_' || x == '$'
+                    ) && key
+                        .chars()
+                        .all(|x| x.is_alphanumeric() || x == '_' || x == '$');
+                    if identifier {
+                        self.out.push_str(&key);
+                    } else {
+                        self.out.push_str(&format!("{key:?}"));
+                    }
+                    self.out.push_str(": ");
+                    self.write(&value, depth + 1)?;
+                }
+                Item::Pair(key, value) => {
+                    self.write(&key, depth + 1)?;
+                    self.out.push_str(" => ");
+                    self.write(&value, depth + 1)?;
+                }
+            }
+            self.out.push_str(",\n");
+        }
+        self.indent(depth);
+        self.out.push_str(close);
+        Ok(())
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff, snapshot, snapshot_eval};
+    use crate::*;
+
+    #[test]
+    fn structural_diff() {
+        test_with(|ctx| {
+            let eval = |source: &str| ctx.eval::<Value, _>(source).unwrap();
+
+            let differences = diff(
+                &eval("({ a: [1, { b: 'x' }], c: NaN, d: () => {} })"),
+                &eval("({ c: NaN, a: [1, { b: 'y' }, 3] })"),
+            )
+            .unwrap();
+            let differences: Vec<StdString> = differences.iter().map(|x| x.to_string()).collect();
+            assert_eq!(
+                differences,
+                [
+                    "$.a[1].b: \"x\" != \"y\"",
+                    "$.a[2]: undefined != 3",
+                    "$.d: function != undefined",
+                ]
+            );
+
+            let cyclic = eval("const a = { n: 1 }; a.self = a; a");
+            assert!(diff(&cyclic, &cyclic.clone()).unwrap().is_empty());
+            let other = eval("const b = { n: 2 }; b.self = b; b");
+            assert_eq!(diff(&cyclic, &other).unwrap().len(), 1);
+
+            assert_eq!(diff(&eval("-0"), &eval("0")).unwrap()[0].actual, "-0");
+        })
+    }
+
+    #[test]
+    #[should_panic(expected = "$.ok: false != true")]
+    fn assert_js_eq_panics() {
+        test_with(|ctx| {
+            let object: Object = ctx.eval("({ ok: false })").unwrap();
+            testing::assert_js_eq(&ctx, object, "{ ok: true }");
+        })
+    }
+
+    #[test]
+    fn canonical_snapshot() {
+        test_with(|ctx| {
+            let value: Value = ctx
+                .eval(
+                    r#"
+                    class Point { constructor() { this.y = 2; this.x = 1; } }
+                    const cyclic = { "two words": Symbol("s") };
+                    cyclic.self = cyclic;
+                    ({
+                        z: [new Point(), Object.create(null), [], {}],
+                        m: new Map([["k", new Set([1n, 0])]]),
+                        f: function named() {},
+                        g: () => {},
+                        r: /a+/gi,
+                        b: new Uint8Array([1, 2]),
+                        n: [NaN, -0, undefined, true],
+                        p: Promise.resolve("done"),
+                        cyclic,
+                    })
+                "#,
+                )
+                .unwrap();
+            assert_eq!(
+                snapshot(&value).unwrap(),
+                r#"{
+  b: Uint8Array [1, 2],
+  cyclic: {
+    self: [Circular],
+    "two words": Symbol(s),
+  },
+  f: [Function named],
+  g: [Function g],
+  m: Map {
+    "k" => Set [
+      1n,
+      0,
+    ],
+  },
+  n: [
+    NaN,
+    -0,
+    undefined,
+    true,
+  ],
+  p: Promise <fulfilled> "done",
+  r: /a+/gi,
+  z: [
+    Point {
+      x: 1,
+      y: 2,
+    },
+    [Object: null prototype] {},
+    [],
+    {},
+  ],
+}"#
+            );
+            assert_eq!(
+                snapshot_eval(&ctx, "null.x").unwrap(),
+                "throws TypeError(\"cannot read property 'x' of null\")"
+            );
+            assert_eq!(
+                snapshot_eval(&ctx, "throw 'raw'").unwrap(),
+                "throws \"raw\""
+            );
+        })
+    }
+}
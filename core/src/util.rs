@@ -1,21 +1,122 @@
 //! Module with some util types.
 
+use alloc::{boxed::Box, vec::Vec};
+use core::{any::Any, panic::UnwindSafe};
+
 /// A trait for preventing implementing traits which should not be implemented outside of rquickjs.
 pub trait Sealed {}
 
+/// Runs a closure, catching a panic if it unwinds.
+///
+/// Without the standard library panics can't be caught, so the closure is just called.
+#[cfg(feature = "std")]
+pub(crate) fn catch_unwind<F: FnOnce() -> R + UnwindSafe, R>(
+    f: F,
+) -> Result<R, Box<dyn Any + Send + 'static>> {
+    std::panic::catch_unwind(f)
+}
+
+/// Runs a closure, catching a panic if it unwinds.
+///
+/// Without the standard library panics can't be caught, so the closure is just called.
+#[cfg(not(feature = "std"))]
+pub(crate) fn catch_unwind<F: FnOnce() -> R + UnwindSafe, R>(
+    f: F,
+) -> Result<R, Box<dyn Any + Send + 'static>> {
+    Ok(f())
+}
+
+/// Continues unwinding a panic caught by [`catch_unwind`].
+#[cfg(feature = "std")]
+pub(crate) fn resume_unwind(panic: Box<dyn Any + Send + 'static>) -> ! {
+    std::panic::resume_unwind(panic)
+}
+
+/// Continues unwinding a panic caught by [`catch_unwind`].
+#[cfg(not(feature = "std"))]
+pub(crate) fn resume_unwind(_panic: Box<dyn Any + Send + 'static>) -> ! {
+    unreachable!("panics are never caught without the standard library")
+}
+
+/// Aborts the process.
+#[cfg(feature = "std")]
+pub(crate) fn abort() -> ! {
+    std::process::abort()
+}
+
+/// Aborts the process.
+///
+/// Without the standard library the panic handler decides what happens, which can't return.
+#[cfg(not(feature = "std"))]
+pub(crate) fn abort() -> ! {
+    panic!("rquickjs aborted")
+}
+
+/// Removes a leading UTF-8 byte order mark from a source text.
+pub(crate) fn strip_bom(mut source: Vec<u8>) -> Vec<u8> {
+    if source.starts_with(b"\xEF\xBB\xBF") {
+        source.drain(..3);
+    }
+    source
+}
+
 #[cfg(feature = "futures")]
 pub use self::futures::*;
 
 #[cfg(feature = "futures")]
 mod futures {
-    use std::{
+    use alloc::{sync::Arc, task::Wake};
+    use core::{
         future::Future,
         marker::PhantomData,
         mem::ManuallyDrop,
         ops::{Deref, DerefMut},
         pin::Pin,
+        sync::atomic::{AtomicBool, Ordering},
         task::{Context, Poll},
     };
+    use std::{
+        thread::{self, Thread},
+        time::Instant,
+    };
+
+    /// A waker which unparks the thread which created it.
+    pub struct ThreadWake {
+        thread: Thread,
+        woken: AtomicBool,
+    }
+
+    impl ThreadWake {
+        pub fn new() -> Arc<Self> {
+            Arc::new(ThreadWake {
+                thread: thread::current(),
+                woken: AtomicBool::new(false),
+            })
+        }
+
+        /// Park the thread until woken or the deadline is reached, returns immediately if it was
+        /// woken since the last wait.
+        pub fn wait(&self, deadline: Option<Instant>) {
+            if self.woken.swap(false, Ordering::AcqRel) {
+                return;
+            }
+            match deadline {
+                Some(x) => thread::park_timeout(x.saturating_duration_since(Instant::now())),
+                None => thread::park(),
+            }
+        }
+    }
+
+    impl Wake for ThreadWake {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref()
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.woken.store(true, Ordering::Release);
+            self.thread.unpark();
+        }
+    }
 
     /// Future which allows one to bail out of a async context, back to manually calling poll.
     pub struct ManualPoll<F, R> {
@@ -1,5 +1,5 @@
 use crate::{qjs, Ctx, Error, Result};
-use std::{fmt, hash::Hash, mem, ops::Deref, result::Result as StdResult, str};
+use core::{fmt, hash::Hash, mem, ops::Deref, result::Result as StdResult, str};
 
 pub mod array;
 pub mod atom;
@@ -7,9 +7,11 @@ mod bigint;
 pub mod convert;
 pub(crate) mod exception;
 pub mod function;
+mod iterator;
 pub mod module;
 pub mod object;
 pub mod promise;
+mod regexp;
 mod string;
 mod symbol;
 
@@ -17,28 +19,59 @@ pub use array::Array;
 pub use atom::Atom;
 pub use bigint::BigInt;
 pub use convert::{Coerced, FromAtom, FromIteratorJs, FromJs, IntoAtom, IntoJs, IteratorJs};
-pub use exception::Exception;
+pub use exception::{ErrorPosition, Exception};
 pub use function::{Constructor, Function};
+pub use iterator::JsIterator;
 pub use module::Module;
 pub use object::{Filter, Object};
 pub use promise::Promise;
-pub use string::String;
+pub use regexp::{RegExp, RegExpMatch};
+pub use string::{JsStringBuilder, String, StringRef};
 pub use symbol::Symbol;
 
 #[cfg(feature = "array-buffer")]
 pub mod array_buffer;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod codec;
+#[cfg(feature = "array-buffer")]
+mod data_view;
 #[cfg(feature = "array-buffer")]
 pub mod typed_array;
 
 #[cfg(feature = "array-buffer")]
-pub use array_buffer::ArrayBuffer;
+pub use array_buffer::{ArrayBuffer, BufferMut, BufferRef};
+#[cfg(feature = "array-buffer")]
+pub use data_view::DataView;
 #[cfg(feature = "array-buffer")]
 pub use typed_array::TypedArray;
 
 /// Any JavaScript value
+///
+/// A value holds a reference to the context it was created in, even `undefined` or a number, so
+/// a `Value` can't be created without a [`Ctx`]. The short constructors [`Value::undefined`],
+/// [`Value::null`], [`Value::bool`], [`Value::int`] and [`Value::float`] borrow one. Code which
+/// doesn't have a context at hand can keep types implementing [`IntoJs`] instead and convert them
+/// later: `()`, `None` and [`Undefined`] become `undefined`, [`Null`] becomes `null`, and Rust
+/// booleans and numbers become their JavaScript counterparts.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, IntoJs, Null, Undefined, Value};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// assert!(Value::undefined(&ctx).is_undefined());
+/// assert_eq!(Value::float(&ctx, 0.5).as_float(), Some(0.5));
+/// assert!(Undefined.into_js(&ctx).unwrap().is_undefined());
+/// assert!(Null.into_js(&ctx).unwrap().is_null());
+/// assert_eq!(1.into_js(&ctx).unwrap().as_int(), Value::int(&ctx, 1).as_int());
+/// # });
+/// ```
 pub struct Value<'js> {
     pub(crate) ctx: Ctx<'js>,
     pub(crate) value: qjs::JSValue,
+    /// The id of the handle in the leak tracker, 0 if it isn't tracked.
+    #[cfg(feature = "leak-detection")]
+    id: usize,
 }
 
 impl<'js> PartialEq for Value<'js> {
@@ -56,7 +89,7 @@ impl<'js> PartialEq for Value<'js> {
 impl<'js> Eq for Value<'js> {}
 
 impl<'js> Hash for Value<'js> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         let tag = unsafe { qjs::JS_VALUE_GET_TAG(self.value) };
         let bits = unsafe { qjs::JS_VALUE_GET_FLOAT64(self.value).to_bits() };
         state.write_i32(tag);
@@ -67,14 +100,18 @@ impl<'js> Hash for Value<'js> {
 impl<'js> Clone for Value<'js> {
     fn clone(&self) -> Self {
         let ctx = self.ctx.clone();
-        let value = unsafe { qjs::JS_DupValue(ctx.as_ptr(), self.value) };
-        Self { ctx, value }
+        unsafe {
+            let value = qjs::JS_DupValue(ctx.as_ptr(), self.value);
+            Self::from_js_value(ctx, value)
+        }
     }
 }
 
 impl<'js> Drop for Value<'js> {
     fn drop(&mut self) {
         unsafe {
+            #[cfg(feature = "leak-detection")]
+            self.ctx.get_opaque().untrack_value(self.id);
             qjs::JS_FreeValue(self.ctx.as_ptr(), self.value);
         }
     }
@@ -126,13 +163,18 @@ impl<'js> Value<'js> {
     // unsafe because the value must belong the context and the lifetime must be constrained by its lifetime
     #[inline]
     pub(crate) unsafe fn from_js_value(ctx: Ctx<'js>, value: qjs::JSValue) -> Self {
-        Self { ctx, value }
+        Self {
+            #[cfg(feature = "leak-detection")]
+            id: ctx.get_opaque().track_value(ctx.as_ptr(), value),
+            ctx,
+            value,
+        }
     }
 
     #[inline]
     pub(crate) unsafe fn from_js_value_const(ctx: Ctx<'js>, value: qjs::JSValueConst) -> Self {
         let value = qjs::JS_DupValue(ctx.as_ptr(), value);
-        Self { ctx, value }
+        Self::from_js_value(ctx, value)
     }
 
     #[inline]
@@ -143,34 +185,81 @@ impl<'js> Value<'js> {
     #[inline]
     pub(crate) fn into_js_value(self) -> qjs::JSValue {
         let value = self.value;
-        unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) };
+        unsafe {
+            #[cfg(feature = "leak-detection")]
+            self.ctx.get_opaque().untrack_value(self.id);
+            qjs::JS_FreeContext(self.ctx.as_ptr())
+        };
         mem::forget(self);
         value
     }
 
+    /// Returns the reference count of the value, `None` for values which aren't reference
+    /// counted like numbers and booleans.
+    ///
+    /// Every handle holds a reference, see [`Ctx::value_references`] to find out which.
+    #[cfg(feature = "leak-detection")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-detection")))]
+    pub fn ref_count(&self) -> Option<u32> {
+        unsafe { crate::leak::ref_count(self.value) }
+    }
+
+    /// Create new uninitialized value
     #[inline]
     pub fn new_uninitialized(ctx: Ctx<'js>) -> Self {
         let value = qjs::JS_UNINITIALIZED;
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
     }
 
+    /// Create new undefined value
     #[inline]
     pub fn new_undefined(ctx: Ctx<'js>) -> Self {
         let value = qjs::JS_UNDEFINED;
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
     }
 
+    /// Create new null value
     #[inline]
     pub fn new_null(ctx: Ctx<'js>) -> Self {
         let value = qjs::JS_NULL;
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
     }
 
     /// Create new boolean value
     #[inline]
     pub fn new_bool(ctx: Ctx<'js>, value: bool) -> Self {
         let value = if value { qjs::JS_TRUE } else { qjs::JS_FALSE };
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
+    }
+
+    /// Create an undefined value, like [`Value::new_undefined`] with a borrowed context.
+    #[inline]
+    pub fn undefined(ctx: &Ctx<'js>) -> Self {
+        Self::new_undefined(ctx.clone())
+    }
+
+    /// Create a null value, like [`Value::new_null`] with a borrowed context.
+    #[inline]
+    pub fn null(ctx: &Ctx<'js>) -> Self {
+        Self::new_null(ctx.clone())
+    }
+
+    /// Create a boolean value, like [`Value::new_bool`] with a borrowed context.
+    #[inline]
+    pub fn bool(ctx: &Ctx<'js>, value: bool) -> Self {
+        Self::new_bool(ctx.clone(), value)
+    }
+
+    /// Create an int value, like [`Value::new_int`] with a borrowed context.
+    #[inline]
+    pub fn int(ctx: &Ctx<'js>, value: i32) -> Self {
+        Self::new_int(ctx.clone(), value)
+    }
+
+    /// Create a float value, like [`Value::new_float`] with a borrowed context.
+    #[inline]
+    pub fn float(ctx: &Ctx<'js>, value: f64) -> Self {
+        Self::new_float(ctx.clone(), value)
     }
 
     /// Returns the Ctx object associated with this value.
@@ -198,7 +287,7 @@ impl<'js> Value<'js> {
     #[inline]
     pub fn new_int(ctx: Ctx<'js>, value: i32) -> Self {
         let value = qjs::JS_MKVAL(qjs::JS_TAG_INT, value);
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
     }
 
     #[inline]
@@ -219,7 +308,7 @@ impl<'js> Value<'js> {
     #[inline]
     pub fn new_float(ctx: Ctx<'js>, value: f64) -> Self {
         let value = qjs::JS_NewFloat64(value);
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
     }
 
     #[inline]
@@ -247,7 +336,7 @@ impl<'js> Value<'js> {
         } else {
             qjs::JS_NewFloat64(value)
         };
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
     }
 
     /// Try get any number from value
@@ -265,14 +354,16 @@ impl<'js> Value<'js> {
     #[inline]
     pub(crate) fn new_ptr(ctx: Ctx<'js>, tag: qjs::c_int, ptr: *mut qjs::c_void) -> Self {
         let value = qjs::JS_MKPTR(tag, ptr);
-        Self { ctx, value }
+        unsafe { Self::from_js_value(ctx, value) }
     }
 
     #[allow(unused)]
     #[inline]
     pub(crate) fn new_ptr_const(ctx: Ctx<'js>, tag: qjs::c_int, ptr: *mut qjs::c_void) -> Self {
-        let value = unsafe { qjs::JS_DupValue(ctx.as_ptr(), qjs::JS_MKPTR(tag, ptr)) };
-        Self { ctx, value }
+        unsafe {
+            let value = qjs::JS_DupValue(ctx.as_ptr(), qjs::JS_MKPTR(tag, ptr));
+            Self::from_js_value(ctx, value)
+        }
     }
 
     #[inline]
@@ -294,6 +385,13 @@ impl<'js> Value<'js> {
         qjs::JS_TAG_UNDEFINED == tag
     }
 
+    /// Returns if the value is the internal uninitialized value.
+    #[inline]
+    pub fn is_uninitialized(&self) -> bool {
+        let tag = unsafe { qjs::JS_VALUE_GET_NORM_TAG(self.value) };
+        qjs::JS_TAG_UNINITIALIZED == tag
+    }
+
     /// Check if the value is a bool
     #[inline]
     pub fn is_bool(&self) -> bool {
@@ -331,6 +429,12 @@ impl<'js> Value<'js> {
         qjs::JS_TAG_SYMBOL == unsafe { qjs::JS_VALUE_GET_TAG(self.value) }
     }
 
+    /// Check if the value is a big int
+    #[inline]
+    pub fn is_big_int(&self) -> bool {
+        qjs::JS_TAG_BIG_INT == unsafe { qjs::JS_VALUE_GET_NORM_TAG(self.value) }
+    }
+
     /// Check if the value is an object
     #[inline]
     pub fn is_object(&self) -> bool {
@@ -364,7 +468,7 @@ impl<'js> Value<'js> {
     /// Check if the value is a promise.
     #[inline]
     pub fn is_promise(&self) -> bool {
-        (unsafe { qjs::JS_PromiseState(self.ctx.as_ptr(), self.value) } as std::os::raw::c_int) >= 0
+        (unsafe { qjs::JS_PromiseState(self.ctx.as_ptr(), self.value) } as core::ffi::c_int) >= 0
     }
 
     /// Check if the value is an exception
@@ -379,6 +483,36 @@ impl<'js> Value<'js> {
         0 != unsafe { qjs::JS_IsError(self.ctx.as_ptr(), self.value) }
     }
 
+    /// Compare two values with the semantics of the JavaScript `===` operator.
+    ///
+    /// Objects are only equal if they are the same object. Unlike the [`PartialEq`]
+    /// implementation, which compares the raw values, equal strings are always equal and `NaN` is
+    /// never equal to itself.
+    pub fn strict_eq(&self, other: &Value<'js>) -> bool {
+        0 != unsafe { qjs::JS_IsStrictEqual(self.ctx.as_ptr(), self.value, other.value) }
+    }
+
+    /// Compare two values with the semantics of the JavaScript `==` operator.
+    ///
+    /// Returns an error if converting one of the values throws, for example from a `valueOf`
+    /// method.
+    pub fn loose_eq(&self, other: &Value<'js>) -> Result<bool> {
+        let res = unsafe { qjs::JS_IsEqual(self.ctx.as_ptr(), self.value, other.value) };
+        if res < 0 {
+            Err(self.ctx.raise_exception())
+        } else {
+            Ok(res != 0)
+        }
+    }
+
+    /// Compare two values with the semantics of the JavaScript `Object.is` function.
+    ///
+    /// This is the same as [`Value::strict_eq`] except that `NaN` is equal to itself and `0` is
+    /// not equal to `-0`.
+    pub fn same_value(&self, other: &Value<'js>) -> bool {
+        0 != unsafe { qjs::JS_IsSameValue(self.ctx.as_ptr(), self.value, other.value) }
+    }
+
     /// Reference as value
     #[inline]
     pub fn as_value(&self) -> &Self {
@@ -631,7 +765,7 @@ macro_rules! sub_types {
                 }
 
                 #[doc = concat!("Try convert into [`",stringify!($head),"`] returning self if the conversion fails.")]
-                pub fn $try_into(self) -> std::result::Result<$head<'js>, Value<'js>> {
+                pub fn $try_into(self) -> core::result::Result<$head<'js>, Value<'js>> {
                     if self.type_of().interpretable_as(Type::$head) {
                         Ok(sub_types!(@wrap $head$(->$sub_type)* self))
                     } else {
@@ -786,4 +920,87 @@ mod test {
 
         assert!(!Type::Bool.interpretable_as(Type::Int));
     }
+
+    #[test]
+    fn void_values() {
+        test_with(|ctx| {
+            assert!(Value::undefined(&ctx).is_undefined());
+            assert!(Value::null(&ctx).is_null());
+            assert_eq!(Value::bool(&ctx, true).as_bool(), Some(true));
+            assert_eq!(Value::int(&ctx, 7).as_int(), Some(7));
+            assert_eq!(Value::float(&ctx, 1.5).as_float(), Some(1.5));
+            assert!(().into_js(&ctx).unwrap().is_undefined());
+            assert!(Undefined.into_js(&ctx).unwrap().is_undefined());
+            assert!(Null.into_js(&ctx).unwrap().is_null());
+            assert!(None::<i32>.into_js(&ctx).unwrap().is_undefined());
+            assert_eq!(ctx.eval::<Null, _>("null").unwrap(), Null);
+            assert!(ctx.eval::<Undefined, _>("null").is_err());
+        })
+    }
+
+    #[test]
+    fn equality() {
+        test_with(|ctx| {
+            let eval = |source: &str| ctx.eval::<Value, _>(source).unwrap();
+
+            let a = eval("'a' + 'b'");
+            let b = eval("'ab'");
+            assert!(a.strict_eq(&b));
+            assert!(a.same_value(&b));
+
+            let one = eval("1");
+            let string_one = eval("'1'");
+            assert!(!one.strict_eq(&string_one));
+            assert!(one.loose_eq(&string_one).unwrap());
+
+            let nan = eval("NaN");
+            assert!(!nan.strict_eq(&nan));
+            assert!(nan.same_value(&nan));
+
+            let zero = eval("0");
+            let neg_zero = eval("-0");
+            assert!(zero.strict_eq(&neg_zero));
+            assert!(!zero.same_value(&neg_zero));
+
+            let object = eval("({})");
+            assert!(object.strict_eq(&object.clone()));
+            assert!(!object.strict_eq(&eval("({})")));
+
+            let throws = eval("({ valueOf() { throw new Error('no') } })");
+            assert!(throws.loose_eq(&one).is_err());
+            assert!(ctx.catch().is_error());
+        })
+    }
+
+    #[test]
+    fn type_of_values() {
+        test_with(|ctx| {
+            let cases = [
+                ("undefined", Type::Undefined),
+                ("null", Type::Null),
+                ("true", Type::Bool),
+                ("1", Type::Int),
+                ("1.5", Type::Float),
+                ("'a'", Type::String),
+                ("Symbol()", Type::Symbol),
+                ("[]", Type::Array),
+                ("(function(){})", Type::Constructor),
+                ("(() => {})", Type::Function),
+                ("Promise.resolve()", Type::Promise),
+                ("new Error()", Type::Exception),
+                ("({})", Type::Object),
+                ("1n", Type::BigInt),
+            ];
+            for (source, ty) in cases {
+                let value: Value = ctx.eval(source).unwrap();
+                assert_eq!(value.type_of(), ty, "{source}");
+            }
+
+            let value: Value = ctx.eval("1n").unwrap();
+            assert!(value.is_big_int());
+            assert!(value.as_big_int().is_some());
+            assert!(!value.is_uninitialized());
+            assert!(!value.is_number());
+        })
+    }
 }
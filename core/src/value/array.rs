@@ -1,7 +1,8 @@
 //! JavaScript array types.
 
-use crate::{atom::PredefinedAtom, qjs, Ctx, FromJs, IntoJs, Object, Result, Value};
-use std::{iter::FusedIterator, marker::PhantomData};
+use crate::{atom::PredefinedAtom, qjs, Ctx, Error, FromJs, IntoJs, Object, Owned, Result, Value};
+use alloc::vec::Vec;
+use core::{iter::FusedIterator, marker::PhantomData};
 
 use super::convert::FromIteratorJs;
 
@@ -78,6 +79,46 @@ impl<'js> Array<'js> {
         }
     }
 
+    /// Get an iterator converting the elements of an array in chunks of `chunk_size` elements,
+    /// the last chunk may be shorter.
+    ///
+    /// Before every chunk the interrupt handler of the runtime is called, like while evaluating
+    /// scripts, so converting huge arrays can be stopped. The iterator then returns
+    /// [`Error::Timeout`] if the deadline of an enclosing [`Ctx::eval_timeout`] was reached, or
+    /// [`Error::Interrupted`] if the handler set with
+    /// [`Runtime::set_interrupt_handler`](crate::Runtime::set_interrupt_handler) returned `true`,
+    /// and ends.
+    ///
+    /// The runtime stays locked while the iterator is used. To let other threads use the runtime
+    /// between chunks, use [`OwnedArray::iter_chunks`](Owned::iter_chunks) instead.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Array};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let array: Array = ctx.eval("Array.from({ length: 10000 }, (_, i) => i)").unwrap();
+    /// let mut sum = 0u64;
+    /// for values in array.iter_chunks::<u64>(1024) {
+    ///     sum += values.unwrap().iter().sum::<u64>();
+    /// }
+    /// assert_eq!(sum, 10000 * 9999 / 2);
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    pub fn iter_chunks<T: FromJs<'js>>(&self, chunk_size: usize) -> ArrayChunks<'js, T> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        ArrayChunks {
+            array: self.clone(),
+            index: 0,
+            count: self.len(),
+            chunk_size,
+            marker: PhantomData,
+        }
+    }
+
     pub fn into_object(self) -> Object<'js> {
         self.0
     }
@@ -143,6 +184,151 @@ where
 
 impl<'js, T> FusedIterator for ArrayIter<'js, T> where T: FromJs<'js> {}
 
+/// The iterator returned by [`Array::iter_chunks`].
+pub struct ArrayChunks<'js, T> {
+    array: Array<'js>,
+    index: usize,
+    count: usize,
+    chunk_size: usize,
+    marker: PhantomData<T>,
+}
+
+impl<'js, T> ArrayChunks<'js, T> {
+    fn check_interrupt(&self) -> Result<()> {
+        let ctx = self.array.ctx();
+        let opaque = unsafe { ctx.get_opaque() };
+        if !opaque.run_interrupt_handler() {
+            return Ok(());
+        }
+        if opaque.take_deadline_reached() {
+            Err(Error::Timeout)
+        } else {
+            Err(Error::Interrupted)
+        }
+    }
+}
+
+impl<'js, T> Iterator for ArrayChunks<'js, T>
+where
+    T: FromJs<'js>,
+{
+    type Item = Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        if let Err(error) = self.check_interrupt() {
+            self.index = self.count;
+            return Some(Err(error));
+        }
+        let end = self.count.min(self.index + self.chunk_size);
+        let res = (self.index..end).map(|idx| self.array.get(idx)).collect();
+        self.index = end;
+        Some(res)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // Skipped chunks aren't converted.
+        let skip = n.saturating_mul(self.chunk_size);
+        self.index = self.index.saturating_add(skip).min(self.count);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<'js, T> ExactSizeIterator for ArrayChunks<'js, T>
+where
+    T: FromJs<'js>,
+{
+    fn len(&self) -> usize {
+        let remaining = self.count - self.index;
+        match remaining {
+            0 => 0,
+            _ => (remaining - 1) / self.chunk_size + 1,
+        }
+    }
+}
+
+impl<'js, T> FusedIterator for ArrayChunks<'js, T> where T: FromJs<'js> {}
+
+impl Owned<Array<'static>> {
+    /// Get an iterator converting the elements of the array in chunks of `chunk_size` elements,
+    /// like [`Array::iter_chunks`], but entering the context of the array for every chunk.
+    ///
+    /// The runtime is unlocked between chunks, so other threads can use it while a huge array
+    /// is converted. Chunks are taken by index, so scripts changing the array between chunks
+    /// can make elements be skipped or converted twice, the iterator ends once the index passes
+    /// the current length of the array.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Array, OwnedArray};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let context = Context::full(&rt).unwrap();
+    /// let array = context.with(|ctx| {
+    ///     let array: Array = ctx.eval("Array.from({ length: 10000 }, (_, i) => i)").unwrap();
+    ///     OwnedArray::new(&ctx, array).unwrap()
+    /// });
+    /// let mut sum = 0u64;
+    /// for values in array.iter_chunks::<u64>(1024) {
+    ///     // The runtime isn't locked here.
+    ///     sum += values.unwrap().iter().sum::<u64>();
+    /// }
+    /// assert_eq!(sum, 10000 * 9999 / 2);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    pub fn iter_chunks<T>(&self, chunk_size: usize) -> OwnedArrayChunks<'_, T>
+    where
+        T: for<'js> FromJs<'js>,
+    {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        OwnedArrayChunks {
+            array: self,
+            chunk: 0,
+            chunk_size,
+            done: false,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// The iterator returned by [`OwnedArray::iter_chunks`](Owned::iter_chunks).
+pub struct OwnedArrayChunks<'a, T> {
+    array: &'a Owned<Array<'static>>,
+    chunk: usize,
+    chunk_size: usize,
+    done: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> Iterator for OwnedArrayChunks<'a, T>
+where
+    T: for<'js> FromJs<'js>,
+{
+    type Item = Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (chunk, chunk_size) = (self.chunk, self.chunk_size);
+        let res = self
+            .array
+            .with(|_, array| array.iter_chunks::<T>(chunk_size).nth(chunk));
+        self.chunk += 1;
+        self.done = !matches!(res, Some(Ok(_)));
+        res
+    }
+}
+
+impl<'a, T> FusedIterator for OwnedArrayChunks<'a, T> where T: for<'js> FromJs<'js> {}
+
 impl<'js> IntoIterator for Array<'js> {
     type Item = Result<Value<'js>>;
     type IntoIter = ArrayIter<'js, Value<'js>>;
@@ -179,6 +365,10 @@ where
 
 #[cfg(test)]
 mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
 
     use crate::*;
     #[test]
@@ -201,6 +391,71 @@ mod test {
         });
     }
 
+    #[test]
+    fn iter_chunks() {
+        test_with(|ctx| {
+            let val: Array = ctx.eval("[1, 2, 3, 4, 5, 6, 7]").unwrap();
+            let chunks = val.iter_chunks::<i32>(3);
+            assert_eq!(chunks.len(), 3);
+            let chunks: Vec<Vec<i32>> = chunks.collect::<Result<_>>().unwrap();
+            assert_eq!(chunks, [vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+            assert_eq!(val.iter_chunks::<i32>(3).nth(2).unwrap().unwrap(), [7]);
+            assert!(val.iter_chunks::<i32>(3).nth(3).is_none());
+            assert!(val.iter_chunks::<i32>(7).nth(1).is_none());
+
+            let val: Array = ctx.eval("[1, 'a']").unwrap();
+            let mut chunks = val.iter_chunks::<i32>(1);
+            assert!(chunks.next().unwrap().is_ok());
+            assert!(chunks.next().unwrap().is_err());
+        });
+    }
+
+    #[test]
+    fn iter_chunks_interrupt() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = calls.clone();
+        rt.set_interrupt_handler(Some(Box::new(move || {
+            handler_calls.fetch_add(1, Ordering::Relaxed) >= 2
+        })));
+        ctx.with(|ctx| {
+            let val: Array = ctx.eval("Array.from({ length: 10 }, (_, i) => i)").unwrap();
+            calls.store(0, Ordering::Relaxed);
+            let mut chunks = val.iter_chunks::<i32>(2);
+            assert!(chunks.next().unwrap().is_ok());
+            assert!(chunks.next().unwrap().is_ok());
+            assert!(matches!(chunks.next(), Some(Err(Error::Interrupted))));
+            assert!(chunks.next().is_none());
+        });
+    }
+
+    #[test]
+    fn owned_iter_chunks() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let array = ctx.with(|ctx| {
+            let val: Array = ctx.eval("Array.from({ length: 5 }, (_, i) => i)").unwrap();
+            OwnedArray::new(&ctx, val).unwrap()
+        });
+        let mut chunks = Vec::new();
+        for chunk in array.iter_chunks::<i32>(2) {
+            // Locking the runtime again would dead lock if the iterator kept it locked.
+            rt.run_gc();
+            chunks.push(chunk.unwrap());
+        }
+        assert_eq!(chunks, [vec![0, 1], vec![2, 3], vec![4]]);
+
+        let array = ctx.with(|ctx| {
+            let val: Array = ctx.eval("[1, 'a', 3]").unwrap();
+            OwnedArray::new(&ctx, val).unwrap()
+        });
+        let mut chunks = array.iter_chunks::<i32>(1);
+        assert!(chunks.next().unwrap().is_ok());
+        assert!(chunks.next().unwrap().is_err());
+        assert!(chunks.next().is_none());
+    }
+
     #[test]
     fn into_object() {
         test_with(|ctx| {
@@ -1,9 +1,13 @@
-use crate::{qjs, Ctx, Error, FromJs, IntoJs, JsLifetime, Object, Result, Value};
+use crate::{
+    qjs, runtime::opaque::Opaque, BorrowError, Ctx, Error, FromJs, IntoJs, JsLifetime, Object,
+    Result, Value,
+};
+use alloc::vec::Vec;
 use core::fmt;
-use std::{
+use core::{
+    ffi::c_void,
     mem::{self, size_of, ManuallyDrop, MaybeUninit},
-    ops::Deref,
-    os::raw::c_void,
+    ops::{Deref, DerefMut, Range},
     ptr::NonNull,
     result::Result as StdResult,
     slice,
@@ -52,9 +56,19 @@ impl<'js> ArrayBuffer<'js> {
         let capacity = src.capacity();
         let size = src.len() * size_of::<T>();
 
-        extern "C" fn drop_raw<T>(_rt: *mut qjs::JSRuntime, opaque: *mut c_void, ptr: *mut c_void) {
-            let ptr = ptr as *mut T;
+        // Buffers of bytes are remembered so `take` can move them back into a vector.
+        let is_bytes = mem::align_of::<T>() == 1 && size_of::<T>() != 0 && capacity != 0;
+
+        extern "C" fn drop_raw<T>(rt: *mut qjs::JSRuntime, opaque: *mut c_void, ptr: *mut c_void) {
             let capacity = opaque as usize;
+            if mem::align_of::<T>() == 1 && size_of::<T>() != 0 && capacity != 0 {
+                let opaque = unsafe { Opaque::from_runtime_ptr(rt) };
+                if opaque.unregister_byte_buffer(ptr).is_none() {
+                    // The data was moved into a vector by `take`.
+                    return;
+                }
+            }
+            let ptr = ptr as *mut T;
             // reconstruct vector in order to free data
             // the length of actual data does not matter for copyable types
             unsafe { Vec::from_raw_parts(ptr, capacity, capacity) };
@@ -74,6 +88,10 @@ impl<'js> ArrayBuffer<'js> {
                 Vec::from_raw_parts(ptr, capacity, capacity);
                 error
             })?;
+            if is_bytes {
+                ctx.get_opaque()
+                    .register_byte_buffer(ptr as _, capacity * size_of::<T>());
+            }
             Value::from_js_value(ctx, val)
         })))
     }
@@ -82,7 +100,7 @@ impl<'js> ArrayBuffer<'js> {
     pub fn new_copy<T: Copy>(ctx: Ctx<'js>, src: impl AsRef<[T]>) -> Result<Self> {
         let src = src.as_ref();
         let ptr = src.as_ptr();
-        let size = std::mem::size_of_val(src);
+        let size = core::mem::size_of_val(src);
 
         Ok(Self(Object(unsafe {
             let val = qjs::JS_NewArrayBufferCopy(ctx.as_ptr(), ptr as _, size as _);
@@ -104,15 +122,23 @@ impl<'js> ArrayBuffer<'js> {
     /// Returns the underlying bytes of the buffer,
     ///
     /// Returns `None` if the array is detached.
+    ///
+    /// # Panics
+    /// Panics if the bytes of the buffer are borrowed mutably by a [`BufferMut`].
     pub fn as_bytes(&self) -> Option<&[u8]> {
         let raw = Self::get_raw(self.as_value())?;
-        Some(unsafe { slice::from_raw_parts_mut(raw.ptr.as_ptr(), raw.len) })
+        RawView::assert_not_borrowed_mut(self.as_value(), raw.ptr, raw.len);
+        Some(unsafe { slice::from_raw_parts(raw.ptr.as_ptr(), raw.len) })
     }
 
     /// Returns a slice if the buffer underlying buffer is properly aligned for the type and the
     /// buffer is not detached.
+    ///
+    /// # Panics
+    /// Panics if the bytes of the buffer are borrowed mutably by a [`BufferMut`].
     pub fn as_slice<T: TypedArrayItem>(&self) -> StdResult<&[T], AsSliceError> {
         let raw = Self::get_raw(&self.0).ok_or(AsSliceError::BufferUsed)?;
+        RawView::assert_not_borrowed_mut(self.as_value(), raw.ptr, raw.len);
         if raw.ptr.as_ptr().align_offset(mem::align_of::<T>()) != 0 {
             return Err(AsSliceError::InvalidAlignment);
         }
@@ -120,11 +146,91 @@ impl<'js> ArrayBuffer<'js> {
         Ok(unsafe { slice::from_raw_parts(raw.ptr.as_ptr().cast(), len) })
     }
 
+    /// Borrow the bytes of the buffer without copying them.
+    ///
+    /// See [`BufferRef`] for how the borrow is checked. Returns [`BorrowError::AlreadyUsed`] if
+    /// the buffer is detached and [`BorrowError::AlreadyBorrowedMut`] if its bytes are borrowed
+    /// mutably.
+    pub fn borrow_bytes(&self) -> StdResult<BufferRef<'_, 'js>, BorrowError> {
+        RawView::new(self.as_value(), locate_buffer, false).map(BufferRef)
+    }
+
+    /// Borrow the bytes of the buffer mutably without copying them.
+    ///
+    /// See [`BufferRef`] for how the borrow is checked. Returns [`BorrowError::AlreadyUsed`] if
+    /// the buffer is detached and [`BorrowError::AlreadyBorrowed`] if its bytes are borrowed.
+    ///
+    /// # Safety
+    /// See [`BufferMut`], no script may run while the view is alive.
+    pub unsafe fn borrow_bytes_mut(&self) -> StdResult<BufferMut<'_, 'js>, BorrowError> {
+        RawView::new(self.as_value(), locate_buffer, true).map(BufferMut)
+    }
+
     /// Detach array buffer
+    ///
+    /// # Panics
+    /// Panics if the bytes of the buffer are borrowed by a [`BufferRef`] or a [`BufferMut`].
     pub fn detach(&mut self) {
+        if let Some(raw) = self.as_raw() {
+            let start = raw.ptr.as_ptr() as usize;
+            let opaque = unsafe { self.0.ctx.get_opaque() };
+            if opaque.is_bytes_borrowed(start..start + raw.len.max(1)) {
+                panic!("can't detach an array buffer while its bytes are borrowed");
+            }
+        }
         unsafe { qjs::JS_DetachArrayBuffer(self.0.ctx.as_ptr(), self.0.as_js_value()) }
     }
 
+    /// Detach the array buffer and return its contents.
+    ///
+    /// Only the bytes of a buffer created with [`ArrayBuffer::new`] from a vector of single byte
+    /// aligned items are moved back into a vector without copying. Buffers allocated by QuickJS,
+    /// like every buffer created by a script, live in memory of the engine allocator which a
+    /// vector can't own, so their bytes are always copied before the buffer is detached. The
+    /// buffer and every view of it are empty afterwards.
+    ///
+    /// Returns `None` if the buffer is already detached, its bytes are borrowed or it can't be
+    /// detached, like a `SharedArrayBuffer`.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, ArrayBuffer};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let bytes = vec![1u8, 2, 3];
+    /// let ptr = bytes.as_ptr();
+    /// let mut buffer = ArrayBuffer::new(ctx.clone(), bytes).unwrap();
+    /// let bytes = buffer.take().unwrap();
+    /// assert_eq!(bytes.as_ptr(), ptr);
+    /// assert!(buffer.take().is_none());
+    ///
+    /// // Copied out of the engine allocation.
+    /// let mut buffer: ArrayBuffer = ctx.eval("new Uint8Array([1, 2, 3]).buffer").unwrap();
+    /// assert_eq!(buffer.take().unwrap(), [1, 2, 3]);
+    /// # });
+    /// ```
+    pub fn take(&mut self) -> Option<Vec<u8>> {
+        // Shared buffers can't be detached.
+        if unsafe { qjs::JS_IsArrayBuffer(self.0.as_js_value()) } == 0 {
+            return None;
+        }
+        let raw = self.as_raw()?;
+        let ptr = raw.ptr.as_ptr();
+        let opaque = unsafe { self.0.ctx.get_opaque() };
+        if opaque.is_bytes_borrowed(ptr as usize..ptr as usize + raw.len.max(1)) {
+            return None;
+        }
+        if let Some(capacity) = opaque.unregister_byte_buffer(ptr as _) {
+            // The buffer was created from a vector, detaching it won't free the data now.
+            self.detach();
+            return Some(unsafe { Vec::from_raw_parts(ptr, raw.len, capacity) });
+        }
+
+        let bytes = unsafe { slice::from_raw_parts(ptr, raw.len) }.to_vec();
+        self.detach();
+        Some(bytes)
+    }
+
     /// Reference to value
     #[inline]
     pub fn as_value(&self) -> &Value<'js> {
@@ -230,6 +336,162 @@ impl<'js> IntoJs<'js> for ArrayBuffer<'js> {
     }
 }
 
+fn locate_buffer(value: &Value<'_>) -> Option<(NonNull<u8>, usize)> {
+    ArrayBuffer::get_raw(value).map(|raw| (raw.ptr, raw.len))
+}
+
+/// Finds the start and length of the bytes of a buffer or an array.
+type Locate<'js> = fn(&Value<'js>) -> Option<(NonNull<u8>, usize)>;
+
+/// The location of the bytes of a view, and how to find it again to check it didn't change.
+pub(crate) struct RawView<'a, 'js> {
+    value: &'a Value<'js>,
+    locate: Locate<'js>,
+    ptr: NonNull<u8>,
+    len: usize,
+    mutable: bool,
+}
+
+impl<'a, 'js> RawView<'a, 'js> {
+    pub(crate) fn new(
+        value: &'a Value<'js>,
+        locate: Locate<'js>,
+        mutable: bool,
+    ) -> StdResult<Self, BorrowError> {
+        let (ptr, len) = locate(value).ok_or(BorrowError::AlreadyUsed)?;
+        let opaque = unsafe { value.ctx.get_opaque() };
+        opaque.borrow_bytes(Self::range(ptr, len), mutable)?;
+        Ok(RawView {
+            value,
+            locate,
+            ptr,
+            len,
+            mutable,
+        })
+    }
+
+    /// The borrowed range, where empty views still borrow a byte to conflict with others.
+    fn range(ptr: NonNull<u8>, len: usize) -> Range<usize> {
+        let start = ptr.as_ptr() as usize;
+        start..start + len.max(1)
+    }
+
+    /// Panics if any of the bytes are borrowed mutably.
+    ///
+    /// Used by the accessors returning plain slices, which can't hold a borrow themselves.
+    pub(crate) fn assert_not_borrowed_mut(value: &Value<'_>, ptr: NonNull<u8>, len: usize) {
+        let opaque = unsafe { value.ctx.get_opaque() };
+        if opaque.is_bytes_borrowed_mut(Self::range(ptr, len)) {
+            panic!("the bytes of an array buffer are already borrowed mutably");
+        }
+    }
+
+    /// Check that the bytes are still where they were borrowed.
+    fn check(&self) {
+        match (self.locate)(self.value) {
+            Some((ptr, len)) if ptr == self.ptr && len == self.len => {}
+            _ => panic!("the bytes of an array buffer were detached or moved while borrowed"),
+        }
+    }
+}
+
+impl Drop for RawView<'_, '_> {
+    fn drop(&mut self) {
+        let opaque = unsafe { self.value.ctx.get_opaque() };
+        opaque.release_bytes(Self::range(self.ptr, self.len), self.mutable);
+    }
+}
+
+/// A borrow of the bytes of an [`ArrayBuffer`] or a [`TypedArray`](crate::TypedArray),
+/// dereferencing to a byte slice.
+///
+/// Views are created by [`ArrayBuffer::borrow_bytes`] and
+/// [`TypedArray::borrow_bytes`](crate::TypedArray::borrow_bytes), and live as long as the borrow
+/// of the buffer or array. They let parsers work on the memory of scripts without copying it.
+///
+/// The bytes are borrowed for the whole runtime like a `RefCell`: overlapping views can be
+/// shared, while a [`BufferMut`] excludes any other view of its bytes, even through other
+/// handles of the same buffer or other arrays on it. The slices returned by `as_bytes`,
+/// `as_slice` and `AsRef` panic while the bytes are borrowed mutably, but they aren't borrows
+/// themselves, so they must not be held across the creation of a [`BufferMut`]. Detaching a
+/// buffer with borrowed bytes from Rust panics.
+///
+/// Only Rust is checked. Scripts can still write to the bytes, so a view must not be
+/// dereferenced across a call into JavaScript. They can also detach, transfer or resize the
+/// buffer, so every dereference checks that the bytes are where they were borrowed, and panics
+/// if they aren't.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, TypedArray, BorrowError};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let array: TypedArray<u8> = ctx.eval("new Uint8Array([1, 2, 3, 4]).subarray(1)").unwrap();
+/// let bytes = array.borrow_bytes().unwrap();
+/// assert_eq!(&*bytes, [2, 3, 4]);
+/// let buffer = array.arraybuffer().unwrap();
+/// assert_eq!(
+///     unsafe { buffer.borrow_bytes_mut() }.unwrap_err(),
+///     BorrowError::AlreadyBorrowed
+/// );
+/// drop(bytes);
+///
+/// unsafe { buffer.borrow_bytes_mut() }.unwrap()[1] = 20;
+/// assert_eq!(array.as_bytes().unwrap(), [20, 3, 4]);
+/// # });
+/// ```
+pub struct BufferRef<'a, 'js>(pub(crate) RawView<'a, 'js>);
+
+impl Deref for BufferRef<'_, '_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.check();
+        unsafe { slice::from_raw_parts(self.0.ptr.as_ptr(), self.0.len) }
+    }
+}
+
+impl fmt::Debug for BufferRef<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BufferRef").field(&&**self).finish()
+    }
+}
+
+/// A mutable borrow of the bytes of an [`ArrayBuffer`] or a [`TypedArray`](crate::TypedArray),
+/// dereferencing to a mutable byte slice.
+///
+/// See [`BufferRef`] for how the borrow is checked.
+///
+/// # Safety
+/// The borrow only excludes other views created from Rust, it can't stop a script from reading
+/// or writing the same bytes while the mutable slice is alive. [`ArrayBuffer::borrow_bytes_mut`]
+/// and [`TypedArray::borrow_bytes_mut`](crate::TypedArray::borrow_bytes_mut) are therefore
+/// unsafe: the caller must not run any script, nor keep a slice from `as_bytes`, `as_slice` or
+/// `AsRef` of the same bytes, while the view is alive.
+pub struct BufferMut<'a, 'js>(pub(crate) RawView<'a, 'js>);
+
+impl Deref for BufferMut<'_, '_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.check();
+        unsafe { slice::from_raw_parts(self.0.ptr.as_ptr(), self.0.len) }
+    }
+}
+
+impl DerefMut for BufferMut<'_, '_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.0.check();
+        unsafe { slice::from_raw_parts_mut(self.0.ptr.as_ptr(), self.0.len) }
+    }
+}
+
+impl fmt::Debug for BufferMut<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BufferMut").field(&&**self).finish()
+    }
+}
+
 impl<'js> Object<'js> {
     /// Returns whether the object is an instance of [`ArrayBuffer`].
     pub fn is_array_buffer(&self) -> bool {
@@ -329,6 +591,33 @@ mod test {
         })
     }
 
+    #[test]
+    fn take() {
+        test_with(|ctx| {
+            let bytes = vec![1u8, 2, 3];
+            let ptr = bytes.as_ptr();
+            let buffer = ArrayBuffer::new(ctx.clone(), bytes).unwrap();
+            ctx.globals().set("buffer", buffer.clone()).unwrap();
+            ctx.eval::<(), _>("globalThis.view = new Uint8Array(buffer)")
+                .unwrap();
+
+            let mut buffer = buffer;
+            let bytes = buffer.take().unwrap();
+            assert_eq!(bytes, [1, 2, 3]);
+            assert_eq!(bytes.as_ptr(), ptr);
+            let res: bool = ctx
+                .eval("buffer.detached && buffer.byteLength === 0 && view.length === 0")
+                .unwrap();
+            assert!(res);
+            assert!(buffer.take().is_none());
+            drop(bytes);
+
+            let mut shared: ArrayBuffer = ctx.eval("new SharedArrayBuffer(4)").unwrap();
+            assert!(shared.take().is_none());
+            assert_eq!(shared.len(), 4);
+        })
+    }
+
     #[test]
     fn as_bytes() {
         test_with(|ctx| {
@@ -348,4 +637,65 @@ mod test {
             assert_eq!(val.as_bytes().unwrap(), &res)
         });
     }
+
+    #[test]
+    fn borrow_bytes() {
+        test_with(|ctx| {
+            let buffer: ArrayBuffer = ctx
+                .eval("globalThis.buffer = new Uint8Array([1, 2, 3, 4]).buffer")
+                .unwrap();
+            let head: TypedArray<u8> = ctx.eval("new Uint8Array(buffer, 0, 2)").unwrap();
+            let tail: TypedArray<u8> = ctx.eval("new Uint8Array(buffer, 2)").unwrap();
+
+            let bytes = buffer.borrow_bytes().unwrap();
+            let other = buffer.clone();
+            let again = other.borrow_bytes().unwrap();
+            assert_eq!(&*bytes, [1, 2, 3, 4]);
+            assert_eq!(&*again, [1, 2, 3, 4]);
+            assert_eq!(
+                unsafe { head.borrow_bytes_mut() }.unwrap_err(),
+                BorrowError::AlreadyBorrowed
+            );
+            assert!(other.clone().take().is_none());
+            drop((bytes, again));
+
+            let mut head_bytes = unsafe { head.borrow_bytes_mut() }.unwrap();
+            let mut tail_bytes = unsafe { tail.borrow_bytes_mut() }.unwrap();
+            head_bytes.copy_from_slice(&[5, 6]);
+            tail_bytes[1] = 8;
+            assert_eq!(
+                buffer.borrow_bytes().unwrap_err(),
+                BorrowError::AlreadyBorrowedMut
+            );
+            let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                buffer.as_bytes().map(|x| x.len())
+            }));
+            assert!(res.is_err());
+            let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                (head.as_ref() as &[u8]).len()
+            }));
+            assert!(res.is_err());
+            drop((head_bytes, tail_bytes));
+            assert_eq!(buffer.as_bytes().unwrap(), [5, 6, 3, 8]);
+
+            let bytes = tail.borrow_bytes().unwrap();
+            ctx.eval::<(), _>("buffer.transfer()").unwrap();
+            let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| bytes.len()));
+            assert!(res.is_err());
+            drop(bytes);
+            assert_eq!(buffer.borrow_bytes().unwrap_err(), BorrowError::AlreadyUsed);
+            ctx.catch();
+
+            let mut buffer = ArrayBuffer::new(ctx.clone(), [1u8, 2]).unwrap();
+            let other = buffer.clone();
+            let bytes = other.borrow_bytes().unwrap();
+            let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| buffer.detach()));
+            assert!(res.is_err());
+            assert_eq!(&*bytes, [1, 2]);
+            drop(bytes);
+            buffer.detach();
+            assert!(other.as_bytes().is_none());
+            ctx.catch();
+        });
+    }
 }
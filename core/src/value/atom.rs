@@ -1,7 +1,8 @@
 //!  QuickJS atom functionality.
 
 use crate::{qjs, Ctx, Error, Result, String, Value};
-use std::{ffi::CStr, hash::Hash, string::String as StdString};
+use alloc::string::{String as StdString, ToString};
+use core::{ffi::CStr, hash::Hash};
 
 mod predefined;
 pub use predefined::PredefinedAtom;
@@ -18,6 +19,31 @@ pub use predefined::PredefinedAtom;
 /// a normal number.
 /// However when the atom represents a string link index like `object["foo"]` or `object.foo`
 /// the atom represents a value in a hashmap.
+///
+/// # Interning keys
+///
+/// Every property access with a string key first has to look up the atom for that string.
+/// Cloning an atom only increments its reference count, so code which accesses the same property
+/// many times can create the atom once and pass a reference to it as the key instead.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Atom, Object};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let count = Atom::from_str(ctx.clone(), "count").unwrap();
+/// let objects: Vec<Object> = (0..100)
+///     .map(|i| {
+///         let object = Object::new(ctx.clone()).unwrap();
+///         object.set(&count, i).unwrap();
+///         object
+///     })
+///     .collect();
+///
+/// let total: i32 = objects.iter().map(|x| x.get::<_, i32>(&count).unwrap()).sum();
+/// assert_eq!(total, 4950);
+/// # });
+/// ```
 #[derive(Debug)]
 pub struct Atom<'js> {
     pub(crate) atom: qjs::JSAtom,
@@ -32,7 +58,7 @@ impl<'js> PartialEq for Atom<'js> {
 impl<'js> Eq for Atom<'js> {}
 
 impl<'js> Hash for Atom<'js> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         state.write_u32(self.atom)
     }
 }
@@ -50,6 +76,9 @@ impl<'js> Atom<'js> {
     }
 
     /// Create an atom from a `u32`
+    ///
+    /// Indices up to `i32::MAX` are stored in the atom itself, creating such an atom doesn't
+    /// allocate and the atom is the same in every context of the runtime.
     pub fn from_u32(ctx: Ctx<'js>, val: u32) -> Result<Atom<'js>> {
         let atom = unsafe { qjs::JS_NewAtomUInt32(ctx.as_ptr(), val) };
         if atom == qjs::JS_ATOM_NULL {
@@ -94,7 +123,7 @@ impl<'js> Atom<'js> {
     /// Create an atom from a Rust string
     pub fn from_str(ctx: Ctx<'js>, name: &str) -> Result<Atom<'js>> {
         unsafe {
-            let ptr = name.as_ptr() as *const std::os::raw::c_char;
+            let ptr = name.as_ptr() as *const core::ffi::c_char;
             let atom = qjs::JS_NewAtomLen(ctx.as_ptr(), ptr, name.len() as _);
             if atom == qjs::JS_ATOM_NULL {
                 // Should never invoke a callback so no panics
@@ -109,6 +138,18 @@ impl<'js> Atom<'js> {
         unsafe { Atom::from_atom_val(ctx, predefined as qjs::JSAtom) }
     }
 
+    /// Returns the array index this atom represents.
+    ///
+    /// Array indices are the canonical numbers from `0` to `2^32 - 2`, so `"12"` is an index
+    /// while `"012"`, `"-1"` and `"4294967295"` are not. Indices stored inline in the atom are
+    /// read without converting the atom to a string.
+    pub fn index(&self) -> Option<u32> {
+        let mut index = 0;
+        let is_index =
+            unsafe { qjs::rquickjs_atom_is_array_index(self.ctx.as_ptr(), &mut index, self.atom) };
+        (is_index != 0).then_some(index)
+    }
+
     /// Convert the atom to a JavaScript string.
     pub fn to_string(&self) -> Result<StdString> {
         unsafe {
@@ -121,7 +162,7 @@ impl<'js> Atom<'js> {
             }
             let bytes = CStr::from_ptr(c_str).to_bytes();
             // Safety: QuickJS should return valid utf8 so this should be safe.
-            let res = std::str::from_utf8_unchecked(bytes).to_string();
+            let res = core::str::from_utf8_unchecked(bytes).to_string();
             qjs::JS_FreeCString(self.ctx.as_ptr(), c_str);
             Ok(res)
         }
@@ -0,0 +1,519 @@
+//! The parts shared by the binary serialization formats.
+//!
+//! [`Encoder`] classifies JavaScript values into the kinds the formats know about and [`Decoder`]
+//! creates the values back, so the formats only deal with their encoding.
+
+use crate::{
+    context::ContextData,
+    qjs,
+    runtime::opaque::Opaque,
+    value::{Constructor, Function},
+    Array, ArrayBuffer, Atom, Ctx, DataView, Error, Object, Result, StdString, Type, Value,
+};
+
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+
+/// The element type of a typed array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Element {
+    U8,
+    U8Clamped,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl Element {
+    const ALL: [Element; 11] = [
+        Element::U8,
+        Element::U8Clamped,
+        Element::I8,
+        Element::U16,
+        Element::I16,
+        Element::U32,
+        Element::I32,
+        Element::U64,
+        Element::I64,
+        Element::F32,
+        Element::F64,
+    ];
+
+    fn class_name(self) -> &'static str {
+        match self {
+            Element::U8 => "Uint8Array",
+            Element::U8Clamped => "Uint8ClampedArray",
+            Element::I8 => "Int8Array",
+            Element::U16 => "Uint16Array",
+            Element::I16 => "Int16Array",
+            Element::U32 => "Uint32Array",
+            Element::I32 => "Int32Array",
+            Element::U64 => "BigUint64Array",
+            Element::I64 => "BigInt64Array",
+            Element::F32 => "Float32Array",
+            Element::F64 => "Float64Array",
+        }
+    }
+
+    /// The tag of RFC 8746 for little endian arrays of the element type.
+    pub fn tag(self) -> u8 {
+        match self {
+            Element::U8 => 64,
+            Element::U8Clamped => 68,
+            Element::I8 => 72,
+            Element::U16 => 69,
+            Element::I16 => 77,
+            Element::U32 => 70,
+            Element::I32 => 78,
+            Element::U64 => 71,
+            Element::I64 => 79,
+            Element::F32 => 85,
+            Element::F64 => 86,
+        }
+    }
+
+    pub fn from_tag(tag: u64) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| u64::from(x.tag()) == tag)
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Element::U8 | Element::U8Clamped | Element::I8 => 1,
+            Element::U16 | Element::I16 => 2,
+            Element::U32 | Element::I32 | Element::F32 => 4,
+            Element::U64 | Element::I64 | Element::F64 => 8,
+        }
+    }
+
+    /// Swap the bytes of every element between little endian and the native byte order.
+    fn swap_to_le(self, bytes: &mut [u8]) {
+        if cfg!(target_endian = "big") {
+            for element in bytes.chunks_mut(self.size()) {
+                element.reverse();
+            }
+        }
+    }
+}
+
+/// A JavaScript value classified for serialization.
+pub(crate) enum Kind<'js> {
+    Undefined,
+    Null,
+    Bool(bool),
+    /// A number which is an integer in the safe range.
+    Int(i64),
+    Float(f64),
+    /// A big integer, negative values store the magnitude of `-1 - value` like CBOR.
+    BigInt {
+        negative: bool,
+        magnitude: Vec<u8>,
+    },
+    String(StdString),
+    Array(Vec<Value<'js>>),
+    Object(Vec<(StdString, Value<'js>)>),
+    Map(Vec<(Value<'js>, Value<'js>)>),
+    Set(Vec<Value<'js>>),
+    /// A date by its milliseconds since the epoch.
+    Date(f64),
+    /// The content of an array buffer or a data view.
+    Bytes(Vec<u8>),
+    /// The little endian elements of a typed array.
+    TypedArray(Element, Vec<u8>),
+}
+
+/// JavaScript helpers for the values which have no native API.
+///
+/// The helpers capture the intrinsics they use and the constructors of the classes when they are
+/// created, so scripts replacing globals or prototype methods afterwards can't change how values
+/// are serialized. A class the context doesn't have is `undefined`.
+const HELPERS: &str = r#"(() => {
+    const global = globalThis;
+    const uncurry = Function.prototype.bind.bind(Function.prototype.call);
+    const own = (name) => typeof global[name] === "function" ? global[name] : undefined;
+    const method = (name, key) => own(name) && uncurry(global[name].prototype[key]);
+    const classes = { __proto__: null };
+    for (const name of [
+        "Date", "Map", "Set", "DataView", "Uint8Array", "Uint8ClampedArray", "Int8Array",
+        "Uint16Array", "Int16Array", "Uint32Array", "Int32Array", "BigUint64Array",
+        "BigInt64Array", "Float32Array", "Float64Array",
+    ]) {
+        classes[name] = own(name);
+    }
+    const { Map, Set } = classes;
+    const BigInt = own("BigInt");
+    const bigintToString = method("BigInt", "toString");
+    const getTime = method("Date", "getTime");
+    const mapEntries = method("Map", "entries");
+    const mapSet = method("Map", "set");
+    const setValues = method("Set", "values");
+    const setAdd = method("Set", "add");
+    const next = (iterator) => uncurry(Object.getPrototypeOf(iterator).next);
+    const mapNext = Map && next(new Map().entries());
+    const setNext = Set && next(new Set().values());
+    const collect = (iterator, next) => {
+        const res = [];
+        for (let item = next(iterator); !item.done; item = next(iterator)) {
+            res[res.length] = item.value;
+        }
+        return res;
+    };
+    return {
+        __proto__: null,
+        classes,
+        bigint: (x) => x < 0n ? [true, bigintToString(-1n - x, 16)] : [false, bigintToString(x, 16)],
+        toBigInt: (negative, hex) => negative ? -1n - BigInt(`0x${hex || "0"}`) : BigInt(`0x${hex || "0"}`),
+        time: (x) => getTime(x),
+        mapEntries: (x) => collect(mapEntries(x), mapNext),
+        setValues: (x) => collect(setValues(x), setNext),
+        map: (entries) => {
+            const map = new Map();
+            for (let i = 0; i < entries.length; i++) {
+                mapSet(map, entries[i][0], entries[i][1]);
+            }
+            return map;
+        },
+        set: (values) => {
+            const set = new Set();
+            for (let i = 0; i < values.length; i++) {
+                setAdd(set, values[i]);
+            }
+            return set;
+        },
+    };
+})()"#;
+
+/// Returns the helpers of a context, creating them on first use.
+fn helpers<'js>(ctx: &Ctx<'js>) -> Result<Object<'js>> {
+    const KEY: &str = "codecHelpers";
+
+    let cache = ContextData::object(ctx);
+    if let Some(cache) = &cache {
+        if let Some(helpers) = cache.get::<_, Option<Object>>(KEY)? {
+            return Ok(helpers);
+        }
+    }
+    let helpers: Object = ctx.eval(HELPERS)?;
+    if let Some(cache) = &cache {
+        cache.set(KEY, helpers.clone())?;
+    }
+    Ok(helpers)
+}
+
+/// The constructor of a class captured by the helpers, if the context has it.
+fn class<'js>(classes: &Object<'js>, name: &str) -> Option<Object<'js>> {
+    classes.get::<_, Option<Object>>(name).ok().flatten()
+}
+
+/// Classifies values for serialization, keeping track of the objects being serialized.
+pub(crate) struct Encoder<'js> {
+    ctx: Ctx<'js>,
+    format: &'static str,
+    helpers: Object<'js>,
+    date: Option<Object<'js>>,
+    map: Option<Object<'js>>,
+    set: Option<Object<'js>>,
+    data_view: Option<Object<'js>>,
+    typed_arrays: Vec<(Element, Object<'js>)>,
+}
+
+/// Keeps an object marked as being serialized until dropped.
+pub(crate) struct NestedGuard<'a, 'js>(&'a Opaque<'js>);
+
+impl Drop for NestedGuard<'_, '_> {
+    fn drop(&mut self) {
+        self.0.leave_conversion()
+    }
+}
+
+impl<'js> Encoder<'js> {
+    pub fn new(ctx: &Ctx<'js>, format: &'static str) -> Result<Self> {
+        let helpers = helpers(ctx)?;
+        let classes: Object = helpers.get("classes")?;
+        Ok(Encoder {
+            ctx: ctx.clone(),
+            format,
+            helpers,
+            date: class(&classes, "Date"),
+            map: class(&classes, "Map"),
+            set: class(&classes, "Set"),
+            data_view: class(&classes, "DataView"),
+            typed_arrays: Element::ALL
+                .into_iter()
+                .filter_map(|x| Some((x, class(&classes, x.class_name())?)))
+                .collect(),
+        })
+    }
+
+    /// Returns the error for a value which can't be serialized.
+    pub fn unsupported(&self, value: &Value<'js>, message: &str) -> Error {
+        Error::new_from_js_message(value.type_name(), self.format, message)
+    }
+
+    /// Mark an object as being serialized, failing on reference cycles and too deeply nested
+    /// values.
+    pub fn enter(&self, value: &Value<'js>) -> Result<Option<NestedGuard<'_, 'js>>> {
+        if !value.is_object() {
+            return Ok(None);
+        }
+        let opaque = unsafe { self.ctx.get_opaque() };
+        opaque
+            .enter_conversion(unsafe { value.get_ptr() })
+            .map_err(|msg| self.unsupported(value, msg))?;
+        Ok(Some(NestedGuard(opaque)))
+    }
+
+    fn call<T: crate::FromJs<'js>>(&self, helper: &str, value: &Value<'js>) -> Result<T> {
+        self.helpers
+            .get::<_, Function>(helper)?
+            .call((value.clone(),))
+    }
+
+    pub fn classify(&self, value: &Value<'js>) -> Result<Kind<'js>> {
+        Ok(match value.type_of() {
+            Type::Uninitialized | Type::Undefined => Kind::Undefined,
+            Type::Null => Kind::Null,
+            Type::Bool => Kind::Bool(value.as_bool().unwrap_or_default()),
+            Type::Int => Kind::Int(value.as_int().unwrap_or_default().into()),
+            Type::Float => {
+                let x = value.as_float().unwrap_or_default();
+                if x.fract() == 0.0
+                    && x.abs() <= 9007199254740991.0
+                    && !(x == 0.0 && x.is_sign_negative())
+                {
+                    Kind::Int(x as i64)
+                } else {
+                    Kind::Float(x)
+                }
+            }
+            Type::BigInt => {
+                let (negative, hex): (bool, StdString) = {
+                    let res: Array = self.call("bigint", value)?;
+                    (res.get(0)?, res.get(1)?)
+                };
+                Kind::BigInt {
+                    negative,
+                    magnitude: hex_to_bytes(&hex),
+                }
+            }
+            Type::String => Kind::String(value.get()?),
+            Type::Array => {
+                let array = value.as_array().expect("the value is an array");
+                Kind::Array(array.iter().collect::<Result<_>>()?)
+            }
+            Type::Object | Type::Exception => {
+                self.classify_object(value.as_object().expect("the value is an object"))?
+            }
+            _ => return Err(self.unsupported(value, "the value can't be serialized")),
+        })
+    }
+
+    fn classify_object(&self, object: &Object<'js>) -> Result<Kind<'js>> {
+        let is = |class: &Option<Object<'js>>| matches!(class, Some(class) if object.is_instance_of(class));
+        let value = object.as_value();
+        if let Some(buffer) = ArrayBuffer::from_object(object.clone()) {
+            let bytes = buffer
+                .as_bytes()
+                .ok_or_else(|| self.unsupported(value, "the array buffer is detached"))?;
+            return Ok(Kind::Bytes(bytes.to_vec()));
+        }
+        if let Some((element, _)) = self
+            .typed_arrays
+            .iter()
+            .find(|(_, class)| object.is_instance_of(class))
+        {
+            let bytes = crate::TypedArray::<u8>::get_raw_bytes(value)
+                .map(|(_, len, ptr)| unsafe { core::slice::from_raw_parts(ptr.as_ptr(), len) })
+                .ok_or_else(|| self.unsupported(value, "the typed array is detached"))?;
+            let mut bytes = bytes.to_vec();
+            element.swap_to_le(&mut bytes);
+            return Ok(Kind::TypedArray(*element, bytes));
+        }
+        if is(&self.data_view) {
+            let view = DataView(object.clone());
+            let (offset, len) = (view.byte_offset()?, view.byte_length()?);
+            let bytes = view
+                .buffer()?
+                .as_bytes()
+                .and_then(|x| x.get(offset..offset + len))
+                .ok_or_else(|| self.unsupported(value, "the data view is detached"))?
+                .to_vec();
+            return Ok(Kind::Bytes(bytes));
+        }
+        if is(&self.date) {
+            return Ok(Kind::Date(self.call("time", value)?));
+        }
+        if is(&self.map) {
+            let entries: Vec<Array> = self.call("mapEntries", value)?;
+            return Ok(Kind::Map(
+                entries
+                    .into_iter()
+                    .map(|x| Ok((x.get(0)?, x.get(1)?)))
+                    .collect::<Result<_>>()?,
+            ));
+        }
+        if is(&self.set) {
+            return Ok(Kind::Set(self.call("setValues", value)?));
+        }
+        Ok(Kind::Object(object.props().collect::<Result<_>>()?))
+    }
+}
+
+/// Parse the hexadecimal digits of a big integer into big endian bytes.
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .bytes()
+        .filter_map(|c| (c as char).to_digit(16).map(|x| x as u8))
+        .collect();
+    let mut bytes = Vec::with_capacity(digits.len() / 2 + 1);
+    if digits.len() % 2 == 1 {
+        bytes.push(digits[0]);
+    }
+    for pair in digits[digits.len() % 2..].chunks(2) {
+        bytes.push(pair[0] << 4 | pair[1]);
+    }
+    // Zero is encoded without any bytes.
+    let zeros = bytes.iter().take_while(|x| **x == 0).count();
+    bytes.drain(..zeros);
+    bytes
+}
+
+/// Creates the values of deserialized data.
+pub(crate) struct Decoder<'js> {
+    pub ctx: Ctx<'js>,
+    format: &'static str,
+    helpers: Object<'js>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'js> Decoder<'js> {
+    pub fn new(ctx: &Ctx<'js>, format: &'static str) -> Result<Self> {
+        Ok(Decoder {
+            ctx: ctx.clone(),
+            format,
+            helpers: helpers(ctx)?,
+            depth: 0,
+            max_depth: unsafe { ctx.get_opaque() }.max_conversion_depth(),
+        })
+    }
+
+    /// Returns the error for malformed data.
+    pub fn invalid(&self, message: &str) -> Error {
+        Error::new_into_js_message(self.format, "value", message)
+    }
+
+    /// Enter a nested value, failing if the data is nested too deeply.
+    pub fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.max_depth {
+            return Err(self.invalid("the data exceeds the maximum conversion depth"));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub fn float(&self, x: f64) -> Value<'js> {
+        // `new_number` would turn negative zero into an integer.
+        if x == 0.0 && x.is_sign_negative() {
+            Value::new_float(self.ctx.clone(), x)
+        } else {
+            Value::new_number(self.ctx.clone(), x)
+        }
+    }
+
+    pub fn string(&self, bytes: &[u8]) -> Result<Value<'js>> {
+        let string = core::str::from_utf8(bytes).map_err(|_| self.invalid("invalid UTF-8"))?;
+        Ok(crate::String::from_str(self.ctx.clone(), string)?.into_value())
+    }
+
+    pub fn bigint(&self, negative: bool, magnitude: &[u8]) -> Result<Value<'js>> {
+        let hex: StdString = magnitude.iter().map(|x| format!("{x:02x}")).collect();
+        self.helpers
+            .get::<_, Function>("toBigInt")?
+            .call((negative, hex))
+    }
+
+    pub fn date(&self, millis: f64) -> Result<Value<'js>> {
+        unsafe {
+            let value = qjs::JS_NewDate(self.ctx.as_ptr(), millis);
+            let value = self.ctx.handle_exception(value)?;
+            Ok(Value::from_js_value(self.ctx.clone(), value))
+        }
+    }
+
+    pub fn array(&self, values: Vec<Value<'js>>) -> Result<Value<'js>> {
+        let array = Array::new(self.ctx.clone())?;
+        for (i, value) in values.into_iter().enumerate() {
+            array.set(i, value)?;
+        }
+        Ok(array.into_value())
+    }
+
+    /// Create a plain object if all keys are strings, a `Map` otherwise.
+    pub fn object_or_map(&self, entries: Vec<(Value<'js>, Value<'js>)>) -> Result<Value<'js>> {
+        if !entries.iter().all(|(key, _)| key.is_string()) {
+            return self.map(entries);
+        }
+        let object = Object::new(self.ctx.clone())?;
+        for (key, value) in entries {
+            // Define the properties so that keys like `__proto__` don't invoke setters.
+            let atom = Atom::from_value(self.ctx.clone(), &key)?;
+            let res = unsafe {
+                qjs::JS_DefinePropertyValue(
+                    self.ctx.as_ptr(),
+                    object.as_js_value(),
+                    atom.atom,
+                    value.into_js_value(),
+                    qjs::JS_PROP_C_W_E as _,
+                )
+            };
+            if res < 0 {
+                return Err(self.ctx.raise_exception());
+            }
+        }
+        Ok(object.into_value())
+    }
+
+    pub fn map(&self, entries: Vec<(Value<'js>, Value<'js>)>) -> Result<Value<'js>> {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| self.array(vec![key, value]))
+            .collect::<Result<Vec<_>>>()?;
+        let entries = self.array(entries)?;
+        self.helpers.get::<_, Function>("map")?.call((entries,))
+    }
+
+    pub fn set(&self, values: Vec<Value<'js>>) -> Result<Value<'js>> {
+        let values = self.array(values)?;
+        self.helpers.get::<_, Function>("set")?.call((values,))
+    }
+
+    pub fn bytes(&self, bytes: &[u8]) -> Result<Value<'js>> {
+        Ok(ArrayBuffer::new_copy(self.ctx.clone(), bytes)?.into_value())
+    }
+
+    pub fn typed_array(&self, element: Element, bytes: &[u8]) -> Result<Value<'js>> {
+        if !bytes.chunks_exact(element.size()).remainder().is_empty() {
+            return Err(
+                self.invalid("the length of the typed array is not a multiple of its element size")
+            );
+        }
+        let mut bytes = bytes.to_vec();
+        element.swap_to_le(&mut bytes);
+        let buffer = ArrayBuffer::new_copy(self.ctx.clone(), bytes)?;
+        let classes: Object = self.helpers.get("classes")?;
+        let class: Constructor = classes.get(element.class_name())?;
+        class.construct((buffer,))
+    }
+}
@@ -0,0 +1,600 @@
+use super::{Decoder, Element, Encoder, Kind};
+use crate::{Ctx, Result, Value};
+
+const FORMAT: &str = "CBOR";
+
+const TAG_DATE: u64 = 1;
+const TAG_POSITIVE_BIGNUM: u64 = 2;
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+const TAG_SET: u64 = 258;
+const TAG_MAP: u64 = 259;
+
+/// The largest integer a number can represent exactly.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+impl<'js> Value<'js> {
+    /// Serialize the value into [CBOR](https://www.rfc-editor.org/rfc/rfc8949).
+    ///
+    /// Besides the values JSON supports, the encoding preserves:
+    ///
+    /// - `undefined` as the simple value `undefined`.
+    /// - `BigInt`s as bignums (tags 2 and 3).
+    /// - `Date`s as epoch based date times (tag 1).
+    /// - `Map`s and `Set`s with the tags 259 and 258, maps can have keys of any type.
+    /// - `ArrayBuffer`s and `DataView`s as byte strings.
+    /// - Typed arrays as little endian typed arrays of RFC 8746 (tags 64 to 86).
+    ///
+    /// Numbers which are integers are encoded as integers and other numbers as floats of the
+    /// smallest precision which doesn't lose information. Objects are encoded by their own
+    /// enumerable string keyed properties. Fails for functions, symbols, reference cycles and
+    /// values nested deeper than the maximum conversion depth of the runtime.
+    ///
+    /// The built-in classes and methods used for maps, sets, dates, big integers and typed arrays
+    /// are captured the first time a value is serialized or deserialized in a context, later
+    /// changes to the globals don't affect the conversion.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Value};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let value: Value = ctx.eval("({ a: [1, 2.5], b: 10n })").unwrap();
+    /// let bytes = value.to_cbor().unwrap();
+    /// assert_eq!(
+    ///     bytes,
+    ///     [0xa2, 0x61, b'a', 0x82, 0x01, 0xfa, 0x40, 0x20, 0x00, 0x00, 0x61, b'b', 0xc2, 0x41, 0x0a]
+    /// );
+    ///
+    /// let copy = Value::from_cbor(ctx.clone(), &bytes).unwrap();
+    /// ctx.globals().set("copy", copy).unwrap();
+    /// assert!(ctx.eval::<bool, _>("copy.a[1] === 2.5 && copy.b === 10n").unwrap());
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "cbor")))]
+    pub fn to_cbor(&self) -> Result<Vec<u8>> {
+        let encoder = Encoder::new(self.ctx(), FORMAT)?;
+        let mut out = Vec::new();
+        encode(&encoder, self, &mut out)?;
+        Ok(out)
+    }
+
+    /// Deserialize a value from [CBOR](https://www.rfc-editor.org/rfc/rfc8949).
+    ///
+    /// Accepts the encoding of [`Value::to_cbor`] and CBOR from other sources, including
+    /// indefinite length items. Maps with only text keys become plain objects unless they are
+    /// tagged as a `Map`. Integers which don't fit a number exactly become `BigInt`s. Unknown tags
+    /// are ignored.
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "cbor")))]
+    pub fn from_cbor(ctx: Ctx<'js>, bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader {
+            decoder: Decoder::new(&ctx, FORMAT)?,
+            bytes,
+            pos: 0,
+        };
+        let value = reader.value()?;
+        if reader.pos != bytes.len() {
+            return Err(reader.decoder.invalid("unexpected data after the value"));
+        }
+        Ok(value)
+    }
+}
+
+fn head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    if arg < 24 {
+        out.push(major | arg as u8);
+    } else if let Ok(arg) = u8::try_from(arg) {
+        out.extend([major | 24, arg]);
+    } else if let Ok(arg) = u16::try_from(arg) {
+        out.push(major | 25);
+        out.extend(arg.to_be_bytes());
+    } else if let Ok(arg) = u32::try_from(arg) {
+        out.push(major | 26);
+        out.extend(arg.to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend(arg.to_be_bytes());
+    }
+}
+
+fn int(out: &mut Vec<u8>, x: i64) {
+    if x >= 0 {
+        head(out, 0, x as u64);
+    } else {
+        head(out, 1, (-1 - x) as u64);
+    }
+}
+
+fn float(out: &mut Vec<u8>, x: f64) {
+    if x as f32 as f64 == x || x.is_nan() {
+        out.push(0xfa);
+        out.extend((x as f32).to_be_bytes());
+    } else {
+        out.push(0xfb);
+        out.extend(x.to_be_bytes());
+    }
+}
+
+fn bytes(out: &mut Vec<u8>, major: u8, bytes: &[u8]) {
+    head(out, major, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn encode<'js>(encoder: &Encoder<'js>, value: &Value<'js>, out: &mut Vec<u8>) -> Result<()> {
+    let _guard = encoder.enter(value)?;
+    match encoder.classify(value)? {
+        Kind::Undefined => out.push(0xf7),
+        Kind::Null => out.push(0xf6),
+        Kind::Bool(x) => out.push(if x { 0xf5 } else { 0xf4 }),
+        Kind::Int(x) => int(out, x),
+        Kind::Float(x) => float(out, x),
+        Kind::BigInt {
+            negative,
+            magnitude,
+        } => {
+            let tag = if negative {
+                TAG_NEGATIVE_BIGNUM
+            } else {
+                TAG_POSITIVE_BIGNUM
+            };
+            head(out, 6, tag);
+            bytes(out, 2, &magnitude);
+        }
+        Kind::String(x) => bytes(out, 3, x.as_bytes()),
+        Kind::Array(values) => {
+            head(out, 4, values.len() as u64);
+            for value in values {
+                encode(encoder, &value, out)?;
+            }
+        }
+        Kind::Object(entries) => {
+            head(out, 5, entries.len() as u64);
+            for (key, value) in entries {
+                bytes(out, 3, key.as_bytes());
+                encode(encoder, &value, out)?;
+            }
+        }
+        Kind::Map(entries) => {
+            head(out, 6, TAG_MAP);
+            head(out, 5, entries.len() as u64);
+            for (key, value) in entries {
+                encode(encoder, &key, out)?;
+                encode(encoder, &value, out)?;
+            }
+        }
+        Kind::Set(values) => {
+            head(out, 6, TAG_SET);
+            head(out, 4, values.len() as u64);
+            for value in values {
+                encode(encoder, &value, out)?;
+            }
+        }
+        Kind::Date(millis) => {
+            head(out, 6, TAG_DATE);
+            if millis % 1000.0 == 0.0 {
+                int(out, (millis / 1000.0) as i64);
+            } else {
+                float(out, millis / 1000.0);
+            }
+        }
+        Kind::Bytes(x) => bytes(out, 2, &x),
+        Kind::TypedArray(element, x) => {
+            head(out, 6, element.tag().into());
+            bytes(out, 2, &x);
+        }
+    }
+    Ok(())
+}
+
+fn f16_to_f64(bits: u16) -> f64 {
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as f64;
+    let value = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2f64.powi(exponent as i32 - 25),
+    };
+    if bits & 0x8000 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+struct Reader<'a, 'js> {
+    decoder: Decoder<'js>,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a, 'js> Reader<'a, 'js> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| self.decoder.invalid("unexpected end of data"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("the slice has the length"))
+    }
+
+    /// Read the argument of a head, `None` for an indefinite length.
+    fn argument(&mut self, info: u8) -> Result<Option<u64>> {
+        Ok(Some(match info {
+            0..=23 => info.into(),
+            24 => self.byte()?.into(),
+            25 => u16::from_be_bytes(self.take_array()?).into(),
+            26 => u32::from_be_bytes(self.take_array()?).into(),
+            27 => u64::from_be_bytes(self.take_array()?),
+            31 => return Ok(None),
+            _ => return Err(self.decoder.invalid("invalid additional information")),
+        }))
+    }
+
+    fn definite(&mut self, info: u8) -> Result<u64> {
+        self.argument(info)?
+            .ok_or_else(|| self.decoder.invalid("unexpected indefinite length"))
+    }
+
+    fn length(&mut self, info: u8) -> Result<Option<usize>> {
+        self.argument(info)?
+            .map(|len| {
+                usize::try_from(len).map_err(|_| self.decoder.invalid("the length is too large"))
+            })
+            .transpose()
+    }
+
+    /// Consume a break if it is the next byte.
+    fn is_break(&mut self) -> bool {
+        let res = self.bytes.get(self.pos) == Some(&0xff);
+        if res {
+            self.pos += 1;
+        }
+        res
+    }
+
+    /// Read the content of a byte or text string with the given major type.
+    fn string(&mut self, major: u8, info: u8) -> Result<Vec<u8>> {
+        if let Some(len) = self.length(info)? {
+            return Ok(self.take(len)?.to_vec());
+        }
+        let mut res = Vec::new();
+        while !self.is_break() {
+            let initial = self.byte()?;
+            if initial >> 5 != major {
+                return Err(self
+                    .decoder
+                    .invalid("invalid chunk of an indefinite length string"));
+            }
+            let len = self
+                .length(initial & 0x1f)?
+                .ok_or_else(|| self.decoder.invalid("nested indefinite length string"))?;
+            res.extend_from_slice(self.take(len)?);
+        }
+        Ok(res)
+    }
+
+    /// Read a byte string.
+    fn byte_string(&mut self) -> Result<Vec<u8>> {
+        let initial = self.byte()?;
+        if initial >> 5 != 2 {
+            return Err(self.decoder.invalid("expected a byte string"));
+        }
+        self.string(2, initial & 0x1f)
+    }
+
+    fn items(&mut self, info: u8) -> Result<Vec<Value<'js>>> {
+        let mut res = Vec::new();
+        match self.length(info)? {
+            Some(len) => {
+                for _ in 0..len {
+                    res.push(self.value()?);
+                }
+            }
+            None => {
+                while !self.is_break() {
+                    res.push(self.value()?);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    fn entries(&mut self, info: u8) -> Result<Vec<(Value<'js>, Value<'js>)>> {
+        let mut res = Vec::new();
+        match self.length(info)? {
+            Some(len) => {
+                for _ in 0..len {
+                    res.push((self.value()?, self.value()?));
+                }
+            }
+            None => {
+                while !self.is_break() {
+                    res.push((self.value()?, self.value()?));
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Read the head of an array or map, returning its additional information.
+    fn container(&mut self, major: u8) -> Result<u8> {
+        let initial = self.byte()?;
+        if initial >> 5 != major {
+            return Err(self.decoder.invalid("unexpected type of a tagged value"));
+        }
+        Ok(initial & 0x1f)
+    }
+
+    fn value(&mut self) -> Result<Value<'js>> {
+        self.decoder.enter()?;
+        let res = self.item();
+        self.decoder.leave();
+        res
+    }
+
+    fn item(&mut self) -> Result<Value<'js>> {
+        let initial = self.byte()?;
+        let info = initial & 0x1f;
+        match initial >> 5 {
+            0 => {
+                let x = self.definite(info)?;
+                if x <= MAX_SAFE_INTEGER {
+                    Ok(self.decoder.float(x as f64))
+                } else {
+                    self.decoder.bigint(false, &x.to_be_bytes())
+                }
+            }
+            1 => {
+                let x = self.definite(info)?;
+                if x < MAX_SAFE_INTEGER {
+                    Ok(self.decoder.float(-1.0 - x as f64))
+                } else {
+                    self.decoder.bigint(true, &x.to_be_bytes())
+                }
+            }
+            2 => {
+                let bytes = self.string(2, info)?;
+                self.decoder.bytes(&bytes)
+            }
+            3 => {
+                let bytes = self.string(3, info)?;
+                self.decoder.string(&bytes)
+            }
+            4 => {
+                let values = self.items(info)?;
+                self.decoder.array(values)
+            }
+            5 => {
+                let entries = self.entries(info)?;
+                self.decoder.object_or_map(entries)
+            }
+            6 => {
+                let tag = self.definite(info)?;
+                self.tagged(tag)
+            }
+            _ => match info {
+                20 => Ok(Value::new_bool(self.decoder.ctx.clone(), false)),
+                21 => Ok(Value::new_bool(self.decoder.ctx.clone(), true)),
+                22 => Ok(Value::new_null(self.decoder.ctx.clone())),
+                23 => Ok(Value::new_undefined(self.decoder.ctx.clone())),
+                25 => {
+                    let x = f16_to_f64(u16::from_be_bytes(self.take_array()?));
+                    Ok(self.decoder.float(x))
+                }
+                26 => {
+                    let x = f32::from_be_bytes(self.take_array()?);
+                    Ok(self.decoder.float(x.into()))
+                }
+                27 => {
+                    let x = f64::from_be_bytes(self.take_array()?);
+                    Ok(self.decoder.float(x))
+                }
+                31 => Err(self.decoder.invalid("unexpected break")),
+                _ => Err(self.decoder.invalid("unsupported simple value")),
+            },
+        }
+    }
+
+    fn tagged(&mut self, tag: u64) -> Result<Value<'js>> {
+        match tag {
+            TAG_DATE => {
+                let seconds = self
+                    .value()?
+                    .as_number()
+                    .ok_or_else(|| self.decoder.invalid("a date must be a number"))?;
+                self.decoder.date((seconds * 1000.0).round())
+            }
+            TAG_POSITIVE_BIGNUM | TAG_NEGATIVE_BIGNUM => {
+                let magnitude = self.byte_string()?;
+                self.decoder.bigint(tag == TAG_NEGATIVE_BIGNUM, &magnitude)
+            }
+            TAG_SET => {
+                self.decoder.enter()?;
+                let info = self.container(4)?;
+                let values = self.items(info);
+                self.decoder.leave();
+                self.decoder.set(values?)
+            }
+            TAG_MAP => {
+                self.decoder.enter()?;
+                let info = self.container(5)?;
+                let entries = self.entries(info);
+                self.decoder.leave();
+                self.decoder.map(entries?)
+            }
+            tag => match Element::from_tag(tag) {
+                Some(element) => {
+                    let bytes = self.byte_string()?;
+                    self.decoder.typed_array(element, &bytes)
+                }
+                None => self.value(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn round_trip<'js>(ctx: &Ctx<'js>, source: &str) -> Value<'js> {
+        let value: Value = ctx.eval(source).unwrap();
+        let bytes = value.to_cbor().unwrap();
+        Value::from_cbor(ctx.clone(), &bytes).unwrap()
+    }
+
+    #[test]
+    fn cbor_round_trip() {
+        test_with(|ctx| {
+            let copy = round_trip(
+                &ctx,
+                r#"({
+                    n: [0, -1, 23, 24, 1000, -100000, 2 ** 40, 1.5, -0, NaN, Infinity, 0.1],
+                    s: "héllo",
+                    u: undefined,
+                    z: null,
+                    b: [true, false],
+                    big: [0n, 1n, -1n, 2n ** 100n, -(2n ** 100n)],
+                    date: new Date(1234567),
+                    map: new Map([[1, "a"], [{}, []]]),
+                    set: new Set([1, "x"]),
+                    buffer: new Uint8Array([1, 2, 3]).buffer,
+                    view: new DataView(new Uint8Array([4, 5, 6]).buffer, 1),
+                    typed: [new Uint16Array([1, 65535]), new Float64Array([0.5]), new BigInt64Array([-5n]), new Int8Array(0)],
+                    ["__proto__"]: 1,
+                })"#,
+            );
+            ctx.globals().set("copy", copy).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"[
+                    copy.n.map((x) => Object.is(x, -0) ? "-0" : `${x}`).join(),
+                    copy.s,
+                    `${"u" in copy} ${copy.u} ${copy.z} ${copy.b}`,
+                    copy.big.join(),
+                    `${copy.date instanceof Date} ${copy.date.getTime()}`,
+                    `${copy.map instanceof Map} ${[...copy.map.keys()].map((x) => typeof x)}`,
+                    `${copy.set instanceof Set} ${[...copy.set]}`,
+                    `${copy.buffer instanceof ArrayBuffer} ${new Uint8Array(copy.buffer)}`,
+                    `${new Uint8Array(copy.view)}`,
+                    copy.typed.map((x) => `${x.constructor.name}(${x})`).join(),
+                    `${Object.getPrototypeOf(copy) === Object.prototype} ${Object.keys(copy).includes("__proto__")}`,
+                ]"#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "0,-1,23,24,1000,-100000,1099511627776,1.5,-0,NaN,Infinity,0.1",
+                    "héllo",
+                    "true undefined null true,false",
+                    "0,1,-1,1267650600228229401496703205376,-1267650600228229401496703205376",
+                    "true 1234567",
+                    "true number,object",
+                    "true 1,x",
+                    "true 1,2,3",
+                    "5,6",
+                    "Uint16Array(1,65535),Float64Array(0.5),BigInt64Array(-5),Int8Array()",
+                    "true true",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn cbor_decode() {
+        test_with(|ctx| {
+            let decode = |bytes: &[u8]| Value::from_cbor(ctx.clone(), bytes);
+            // Indefinite length items.
+            let value = decode(&[
+                0xbf, 0x61, b'a', 0x9f, 0x01, 0xff, 0x61, b'b', 0x7f, 0x61, b'x', 0x61, b'y', 0xff,
+                0xff,
+            ])
+            .unwrap();
+            ctx.globals().set("value", value).unwrap();
+            assert!(ctx
+                .eval::<bool, _>("value.a[0] === 1 && value.b === 'xy'")
+                .unwrap());
+            // Half floats, large integers and unknown tags.
+            assert_eq!(decode(&[0xf9, 0x3e, 0x00]).unwrap().as_number(), Some(1.5));
+            assert!(decode(&[0x1b, 0xff, 0, 0, 0, 0, 0, 0, 0])
+                .unwrap()
+                .is_big_int());
+            assert_eq!(decode(&[0xd8, 0x20, 0x05]).unwrap().as_int(), Some(5));
+            // Maps with other keys than strings.
+            assert!(decode(&[0xa1, 0x01, 0x02]).unwrap().is_object());
+
+            for bytes in [
+                &[][..],
+                &[0x82, 0x01],
+                &[0x01, 0x02],
+                &[0x61, 0xff],
+                &[0xff],
+                &[0x1c],
+                &[0xd8, 0x40, 0x01],
+            ] {
+                let err = decode(bytes).unwrap_err();
+                assert!(err.is_into_js(), "{err}");
+            }
+
+            let nested = [0x81; 1000];
+            assert!(decode(&nested).is_err());
+        })
+    }
+
+    #[test]
+    fn cbor_unsupported() {
+        test_with(|ctx| {
+            for source in ["() => {}", "Symbol()", "const a = []; a.push(a); a"] {
+                let value: Value = ctx.eval(source).unwrap();
+                assert!(value.to_cbor().unwrap_err().is_from_js());
+            }
+        })
+    }
+
+    #[test]
+    fn cbor_ignores_replaced_globals() {
+        test_with(|ctx| {
+            // The helpers capture the intrinsics on first use.
+            round_trip(&ctx, "0");
+            let value: Value = ctx
+                .eval(
+                    r#"
+                const value = [new Map([[1, 2]]), new Date(5), new Set([3]), new Uint16Array([4]), 7n];
+                const U16 = Uint16Array;
+                const check = (copy) => [
+                    Map.prototype.get.call(copy[0], 1),
+                    Date.prototype.valueOf.call(copy[1]),
+                    Set.prototype.has.call(copy[2], 3),
+                    copy[3] instanceof U16 && copy[3][0],
+                    copy[4],
+                ].join();
+                Map.prototype.entries = () => [][Symbol.iterator]();
+                Map.prototype.set = () => { throw new Error("set") };
+                Set.prototype.add = () => { throw new Error("add") };
+                Date.prototype.getTime = () => 0;
+                BigInt.prototype.toString = () => "0";
+                globalThis.Uint16Array = function () { throw new Error("Uint16Array") };
+                globalThis.check = check;
+                value
+            "#,
+                )
+                .unwrap();
+            let bytes = value.to_cbor().unwrap();
+            let copy = Value::from_cbor(ctx.clone(), &bytes).unwrap();
+            let check: Function = ctx.globals().get("check").unwrap();
+            let res: StdString = check.call((copy,)).unwrap();
+            assert_eq!(res, "2,5,true,4,7");
+        })
+    }
+}
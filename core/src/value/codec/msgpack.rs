@@ -0,0 +1,605 @@
+use super::{Decoder, Element, Encoder, Kind};
+use crate::{Ctx, Result, Value};
+
+const FORMAT: &str = "MessagePack";
+
+const EXT_TIMESTAMP: i8 = -1;
+const EXT_POSITIVE_BIGINT: i8 = 2;
+const EXT_NEGATIVE_BIGINT: i8 = 3;
+
+/// The largest integer a number can represent exactly.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+impl<'js> Value<'js> {
+    /// Serialize the value into [MessagePack](https://github.com/msgpack/msgpack/blob/master/spec.md).
+    ///
+    /// Besides the values JSON supports, the encoding preserves:
+    ///
+    /// - `Date`s as the timestamp extension type -1.
+    /// - `BigInt`s as the extension types 2 and 3 with the big endian magnitude of the value, or
+    ///   of `-1 - value` for negative values.
+    /// - `Map`s as maps, which can have keys of any type.
+    /// - `ArrayBuffer`s and `DataView`s as binary data.
+    /// - Typed arrays as extension types with the little endian elements, the type is the tag of
+    ///   RFC 8746 for the element type, for example 64 for `Uint8Array`.
+    ///
+    /// `undefined` is encoded as nil and `Set`s as arrays. Numbers which are integers are encoded
+    /// as integers and other numbers as floats of the smallest precision which doesn't lose
+    /// information. Objects are encoded by their own enumerable string keyed properties. Fails
+    /// for functions, symbols, invalid dates, reference cycles and values nested deeper than the
+    /// maximum conversion depth of the runtime.
+    ///
+    /// The built-in classes and methods used for maps, sets, dates, big integers and typed arrays
+    /// are captured the first time a value is serialized or deserialized in a context, later
+    /// changes to the globals don't affect the conversion.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Value};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let value: Value = ctx.eval("({ a: [1, -1], b: new Date(0) })").unwrap();
+    /// let bytes = value.to_msgpack().unwrap();
+    /// assert_eq!(
+    ///     bytes,
+    ///     [0x82, 0xa1, b'a', 0x92, 0x01, 0xff, 0xa1, b'b', 0xd6, 0xff, 0x00, 0x00, 0x00, 0x00]
+    /// );
+    ///
+    /// let copy = Value::from_msgpack(ctx.clone(), &bytes).unwrap();
+    /// ctx.globals().set("copy", copy).unwrap();
+    /// assert!(ctx.eval::<bool, _>("copy.a[1] === -1 && copy.b.getTime() === 0").unwrap());
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "msgpack")))]
+    pub fn to_msgpack(&self) -> Result<Vec<u8>> {
+        let encoder = Encoder::new(self.ctx(), FORMAT)?;
+        let mut out = Vec::new();
+        encode(&encoder, self, &mut out)?;
+        Ok(out)
+    }
+
+    /// Deserialize a value from [MessagePack](https://github.com/msgpack/msgpack/blob/master/spec.md).
+    ///
+    /// Accepts the encoding of [`Value::to_msgpack`] and MessagePack from other sources. Maps
+    /// with only string keys become plain objects, other maps become `Map`s. Integers which don't
+    /// fit a number exactly become `BigInt`s. Fails for unknown extension types.
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "msgpack")))]
+    pub fn from_msgpack(ctx: Ctx<'js>, bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader {
+            decoder: Decoder::new(&ctx, FORMAT)?,
+            bytes,
+            pos: 0,
+        };
+        let value = reader.value()?;
+        if reader.pos != bytes.len() {
+            return Err(reader.decoder.invalid("unexpected data after the value"));
+        }
+        Ok(value)
+    }
+}
+
+/// Write the head of a value with a length, `fix` is the marker of the fixed size variant and
+/// its maximum length.
+fn head<'js>(
+    encoder: &Encoder<'js>,
+    value: &Value<'js>,
+    out: &mut Vec<u8>,
+    len: usize,
+    fix: Option<(u8, usize)>,
+    markers: [u8; 3],
+) -> Result<()> {
+    match fix {
+        Some((marker, max)) if len <= max => out.push(marker | len as u8),
+        _ => {
+            if let Ok(len) = u8::try_from(len) {
+                out.extend([markers[0], len]);
+            } else if let Ok(len) = u16::try_from(len) {
+                out.push(markers[1]);
+                out.extend(len.to_be_bytes());
+            } else if let Ok(len) = u32::try_from(len) {
+                out.push(markers[2]);
+                out.extend(len.to_be_bytes());
+            } else {
+                return Err(encoder.unsupported(value, "the value is too large"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn container_head<'js>(
+    encoder: &Encoder<'js>,
+    value: &Value<'js>,
+    out: &mut Vec<u8>,
+    len: usize,
+    fix: u8,
+    markers: [u8; 2],
+) -> Result<()> {
+    if len < 16 {
+        out.push(fix | len as u8);
+    } else if let Ok(len) = u16::try_from(len) {
+        out.push(markers[0]);
+        out.extend(len.to_be_bytes());
+    } else if let Ok(len) = u32::try_from(len) {
+        out.push(markers[1]);
+        out.extend(len.to_be_bytes());
+    } else {
+        return Err(encoder.unsupported(value, "the value is too large"));
+    }
+    Ok(())
+}
+
+fn ext<'js>(
+    encoder: &Encoder<'js>,
+    value: &Value<'js>,
+    out: &mut Vec<u8>,
+    kind: i8,
+    data: &[u8],
+) -> Result<()> {
+    match data.len() {
+        1 => out.push(0xd4),
+        2 => out.push(0xd5),
+        4 => out.push(0xd6),
+        8 => out.push(0xd7),
+        16 => out.push(0xd8),
+        len => head(encoder, value, out, len, None, [0xc7, 0xc8, 0xc9])?,
+    }
+    out.push(kind as u8);
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+fn int(out: &mut Vec<u8>, x: i64) {
+    if x >= 0 {
+        if x < 128 {
+            out.push(x as u8);
+        } else if let Ok(x) = u8::try_from(x) {
+            out.extend([0xcc, x]);
+        } else if let Ok(x) = u16::try_from(x) {
+            out.push(0xcd);
+            out.extend(x.to_be_bytes());
+        } else if let Ok(x) = u32::try_from(x) {
+            out.push(0xce);
+            out.extend(x.to_be_bytes());
+        } else {
+            out.push(0xcf);
+            out.extend((x as u64).to_be_bytes());
+        }
+    } else if x >= -32 {
+        out.push(x as i8 as u8);
+    } else if let Ok(x) = i8::try_from(x) {
+        out.extend([0xd0, x as u8]);
+    } else if let Ok(x) = i16::try_from(x) {
+        out.push(0xd1);
+        out.extend(x.to_be_bytes());
+    } else if let Ok(x) = i32::try_from(x) {
+        out.push(0xd2);
+        out.extend(x.to_be_bytes());
+    } else {
+        out.push(0xd3);
+        out.extend(x.to_be_bytes());
+    }
+}
+
+fn float(out: &mut Vec<u8>, x: f64) {
+    if x as f32 as f64 == x || x.is_nan() {
+        out.push(0xca);
+        out.extend((x as f32).to_be_bytes());
+    } else {
+        out.push(0xcb);
+        out.extend(x.to_be_bytes());
+    }
+}
+
+fn string<'js>(
+    encoder: &Encoder<'js>,
+    value: &Value<'js>,
+    out: &mut Vec<u8>,
+    x: &str,
+) -> Result<()> {
+    head(
+        encoder,
+        value,
+        out,
+        x.len(),
+        Some((0xa0, 31)),
+        [0xd9, 0xda, 0xdb],
+    )?;
+    out.extend_from_slice(x.as_bytes());
+    Ok(())
+}
+
+/// Encode the timestamp extension in its smallest format.
+fn timestamp(millis: f64) -> Vec<u8> {
+    let seconds = (millis / 1000.0).floor() as i64;
+    let nanos = ((millis - seconds as f64 * 1000.0) * 1_000_000.0) as u32;
+    if seconds >> 34 == 0 {
+        let data = (u64::from(nanos) << 34) | seconds as u64;
+        if data >> 32 == 0 {
+            (data as u32).to_be_bytes().to_vec()
+        } else {
+            data.to_be_bytes().to_vec()
+        }
+    } else {
+        let mut data = nanos.to_be_bytes().to_vec();
+        data.extend(seconds.to_be_bytes());
+        data
+    }
+}
+
+fn encode<'js>(encoder: &Encoder<'js>, value: &Value<'js>, out: &mut Vec<u8>) -> Result<()> {
+    let _guard = encoder.enter(value)?;
+    match encoder.classify(value)? {
+        Kind::Undefined | Kind::Null => out.push(0xc0),
+        Kind::Bool(x) => out.push(if x { 0xc3 } else { 0xc2 }),
+        Kind::Int(x) => int(out, x),
+        Kind::Float(x) => float(out, x),
+        Kind::BigInt {
+            negative,
+            magnitude,
+        } => {
+            let kind = if negative {
+                EXT_NEGATIVE_BIGINT
+            } else {
+                EXT_POSITIVE_BIGINT
+            };
+            ext(encoder, value, out, kind, &magnitude)?;
+        }
+        Kind::String(x) => string(encoder, value, out, &x)?,
+        Kind::Array(values) | Kind::Set(values) => {
+            container_head(encoder, value, out, values.len(), 0x90, [0xdc, 0xdd])?;
+            for value in values {
+                encode(encoder, &value, out)?;
+            }
+        }
+        Kind::Object(entries) => {
+            container_head(encoder, value, out, entries.len(), 0x80, [0xde, 0xdf])?;
+            for (key, value) in entries {
+                string(encoder, &value, out, &key)?;
+                encode(encoder, &value, out)?;
+            }
+        }
+        Kind::Map(entries) => {
+            container_head(encoder, value, out, entries.len(), 0x80, [0xde, 0xdf])?;
+            for (key, value) in entries {
+                encode(encoder, &key, out)?;
+                encode(encoder, &value, out)?;
+            }
+        }
+        Kind::Date(millis) => {
+            if !millis.is_finite() {
+                return Err(encoder.unsupported(value, "the date is invalid"));
+            }
+            ext(encoder, value, out, EXT_TIMESTAMP, &timestamp(millis))?;
+        }
+        Kind::Bytes(x) => {
+            head(encoder, value, out, x.len(), None, [0xc4, 0xc5, 0xc6])?;
+            out.extend_from_slice(&x);
+        }
+        Kind::TypedArray(element, x) => ext(encoder, value, out, element.tag() as i8, &x)?,
+    }
+    Ok(())
+}
+
+struct Reader<'a, 'js> {
+    decoder: Decoder<'js>,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a, 'js> Reader<'a, 'js> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| self.decoder.invalid("unexpected end of data"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("the slice has the length"))
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<usize> {
+        Ok(u16::from_be_bytes(self.take_array()?).into())
+    }
+
+    fn u32(&mut self) -> Result<usize> {
+        usize::try_from(u32::from_be_bytes(self.take_array()?))
+            .map_err(|_| self.decoder.invalid("the length is too large"))
+    }
+
+    fn uint(&self, x: u64) -> Result<Value<'js>> {
+        if x <= MAX_SAFE_INTEGER {
+            Ok(self.decoder.float(x as f64))
+        } else {
+            self.decoder.bigint(false, &x.to_be_bytes())
+        }
+    }
+
+    fn int(&self, x: i64) -> Result<Value<'js>> {
+        if x >= 0 {
+            self.uint(x as u64)
+        } else if x >= -(MAX_SAFE_INTEGER as i64) {
+            Ok(self.decoder.float(x as f64))
+        } else {
+            self.decoder.bigint(true, &((-1 - x) as u64).to_be_bytes())
+        }
+    }
+
+    fn items(&mut self, len: usize) -> Result<Vec<Value<'js>>> {
+        (0..len).map(|_| self.value()).collect()
+    }
+
+    fn entries(&mut self, len: usize) -> Result<Vec<(Value<'js>, Value<'js>)>> {
+        (0..len)
+            .map(|_| Ok((self.value()?, self.value()?)))
+            .collect()
+    }
+
+    fn value(&mut self) -> Result<Value<'js>> {
+        self.decoder.enter()?;
+        let res = self.item();
+        self.decoder.leave();
+        res
+    }
+
+    fn item(&mut self) -> Result<Value<'js>> {
+        let ctx = self.decoder.ctx.clone();
+        let marker = self.u8()?;
+        match marker {
+            0x00..=0x7f => self.uint(marker.into()),
+            0x80..=0x8f => {
+                let entries = self.entries((marker & 0x0f).into())?;
+                self.decoder.object_or_map(entries)
+            }
+            0x90..=0x9f => {
+                let values = self.items((marker & 0x0f).into())?;
+                self.decoder.array(values)
+            }
+            0xa0..=0xbf => {
+                let bytes = self.take((marker & 0x1f).into())?;
+                self.decoder.string(bytes)
+            }
+            0xc0 => Ok(Value::new_null(ctx)),
+            0xc2 => Ok(Value::new_bool(ctx, false)),
+            0xc3 => Ok(Value::new_bool(ctx, true)),
+            0xc4..=0xc6 => {
+                let len = match marker {
+                    0xc4 => self.u8()?.into(),
+                    0xc5 => self.u16()?,
+                    _ => self.u32()?,
+                };
+                let bytes = self.take(len)?;
+                self.decoder.bytes(bytes)
+            }
+            0xc7..=0xc9 => {
+                let len = match marker {
+                    0xc7 => self.u8()?.into(),
+                    0xc8 => self.u16()?,
+                    _ => self.u32()?,
+                };
+                self.ext(len)
+            }
+            0xca => {
+                let x = f32::from_be_bytes(self.take_array()?);
+                Ok(self.decoder.float(x.into()))
+            }
+            0xcb => {
+                let x = f64::from_be_bytes(self.take_array()?);
+                Ok(self.decoder.float(x))
+            }
+            0xcc => {
+                let x = self.u8()?;
+                self.uint(x.into())
+            }
+            0xcd => {
+                let x = u16::from_be_bytes(self.take_array()?);
+                self.uint(x.into())
+            }
+            0xce => {
+                let x = u32::from_be_bytes(self.take_array()?);
+                self.uint(x.into())
+            }
+            0xcf => {
+                let x = u64::from_be_bytes(self.take_array()?);
+                self.uint(x)
+            }
+            0xd0 => {
+                let x = self.u8()? as i8;
+                self.int(x.into())
+            }
+            0xd1 => {
+                let x = i16::from_be_bytes(self.take_array()?);
+                self.int(x.into())
+            }
+            0xd2 => {
+                let x = i32::from_be_bytes(self.take_array()?);
+                self.int(x.into())
+            }
+            0xd3 => {
+                let x = i64::from_be_bytes(self.take_array()?);
+                self.int(x)
+            }
+            0xd4..=0xd8 => self.ext(1 << (marker - 0xd4)),
+            0xd9..=0xdb => {
+                let len = match marker {
+                    0xd9 => self.u8()?.into(),
+                    0xda => self.u16()?,
+                    _ => self.u32()?,
+                };
+                let bytes = self.take(len)?;
+                self.decoder.string(bytes)
+            }
+            0xdc | 0xdd => {
+                let len = if marker == 0xdc {
+                    self.u16()?
+                } else {
+                    self.u32()?
+                };
+                let values = self.items(len)?;
+                self.decoder.array(values)
+            }
+            0xde | 0xdf => {
+                let len = if marker == 0xde {
+                    self.u16()?
+                } else {
+                    self.u32()?
+                };
+                let entries = self.entries(len)?;
+                self.decoder.object_or_map(entries)
+            }
+            0xe0..=0xff => self.int((marker as i8).into()),
+            _ => Err(self.decoder.invalid("invalid marker")),
+        }
+    }
+
+    fn ext(&mut self, len: usize) -> Result<Value<'js>> {
+        let kind = self.u8()? as i8;
+        let data = self.take(len)?;
+        match kind {
+            EXT_TIMESTAMP => {
+                let (seconds, nanos) = match data.len() {
+                    4 => (u32::from_be_bytes(data.try_into().unwrap()).into(), 0),
+                    8 => {
+                        let data = u64::from_be_bytes(data.try_into().unwrap());
+                        ((data & ((1 << 34) - 1)) as i64, (data >> 34) as u32)
+                    }
+                    12 => (
+                        i64::from_be_bytes(data[4..].try_into().unwrap()),
+                        u32::from_be_bytes(data[..4].try_into().unwrap()),
+                    ),
+                    _ => return Err(self.decoder.invalid("invalid timestamp")),
+                };
+                if nanos >= 1_000_000_000 {
+                    return Err(self.decoder.invalid("invalid timestamp"));
+                }
+                self.decoder
+                    .date(seconds as f64 * 1000.0 + (nanos / 1_000_000) as f64)
+            }
+            EXT_POSITIVE_BIGINT | EXT_NEGATIVE_BIGINT => {
+                self.decoder.bigint(kind == EXT_NEGATIVE_BIGINT, data)
+            }
+            kind => match u64::try_from(kind).ok().and_then(Element::from_tag) {
+                Some(element) => self.decoder.typed_array(element, data),
+                None => Err(self.decoder.invalid("unsupported extension type")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn msgpack_round_trip() {
+        test_with(|ctx| {
+            let value: Value = ctx
+                .eval(
+                    r#"({
+                    n: [0, 127, 128, 65536, 2 ** 40, -1, -32, -33, -40000, -(2 ** 40), 1.5, -0, NaN, 0.1],
+                    s: ["", "héllo", "x".repeat(40), "y".repeat(300)],
+                    z: [null, undefined],
+                    b: [true, false],
+                    big: [0n, 1n, -1n, 2n ** 100n, -(2n ** 100n)],
+                    date: [new Date(0), new Date(1234567), new Date(2 ** 36 * 1000), new Date(-1)],
+                    map: new Map([[1, "a"], [{}, []]]),
+                    set: new Set([1, "x"]),
+                    list: Array.from({ length: 20 }, (_, i) => i),
+                    buffer: new Uint8Array([1, 2, 3]).buffer,
+                    typed: [new Uint16Array([1, 65535]), new Float64Array([0.5]), new BigInt64Array([-5n]), new Int8Array(0)],
+                    ["__proto__"]: 1,
+                })"#,
+                )
+                .unwrap();
+            let bytes = value.to_msgpack().unwrap();
+            let copy = Value::from_msgpack(ctx.clone(), &bytes).unwrap();
+            ctx.globals().set("copy", copy).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"[
+                    copy.n.map((x) => Object.is(x, -0) ? "-0" : `${x}`).join(),
+                    copy.s.map((x) => x.length).join(),
+                    copy.z.join("|"),
+                    copy.b.join(),
+                    copy.big.join(),
+                    copy.date.map((x) => x.getTime()).join(),
+                    `${copy.map instanceof Map} ${[...copy.map.keys()].map((x) => typeof x)}`,
+                    `${Array.isArray(copy.set)} ${copy.set}`,
+                    `${copy.list.length} ${copy.list[19]}`,
+                    `${copy.buffer instanceof ArrayBuffer} ${new Uint8Array(copy.buffer)}`,
+                    copy.typed.map((x) => `${x.constructor.name}(${x})`).join(),
+                    `${Object.getPrototypeOf(copy) === Object.prototype} ${Object.keys(copy).includes("__proto__")}`,
+                ]"#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "0,127,128,65536,1099511627776,-1,-32,-33,-40000,-1099511627776,1.5,-0,NaN,0.1",
+                    "0,5,40,300",
+                    "|",
+                    "true,false",
+                    "0,1,-1,1267650600228229401496703205376,-1267650600228229401496703205376",
+                    "0,1234567,68719476736000,-1",
+                    "true number,object",
+                    "true 1,x",
+                    "20 19",
+                    "true 1,2,3",
+                    "Uint16Array(1,65535),Float64Array(0.5),BigInt64Array(-5),Int8Array()",
+                    "true true",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn msgpack_decode() {
+        test_with(|ctx| {
+            let decode = |bytes: &[u8]| Value::from_msgpack(ctx.clone(), bytes);
+            assert!(decode(&[0xcf, 0xff, 0, 0, 0, 0, 0, 0, 0])
+                .unwrap()
+                .is_big_int());
+            assert!(decode(&[0x81, 0x01, 0x02]).unwrap().is_object());
+
+            for bytes in [
+                &[][..],
+                &[0x92, 0x01],
+                &[0x01, 0x02],
+                &[0xa1, 0xff],
+                &[0xc1],
+                &[0xd4, 0x10, 0x00],
+                &[0xd5, 0x45, 0x00, 0x00, 0x00][..3],
+                &[0xd4, 0x45, 0x00],
+            ] {
+                let err = decode(bytes).unwrap_err();
+                assert!(err.is_into_js(), "{err}");
+            }
+
+            let nested = [0x91; 1000];
+            assert!(decode(&nested).is_err());
+        })
+    }
+
+    #[test]
+    fn msgpack_unsupported() {
+        test_with(|ctx| {
+            for source in [
+                "() => {}",
+                "Symbol()",
+                "new Date(NaN)",
+                "const a = []; a.push(a); a",
+            ] {
+                let value: Value = ctx.eval(source).unwrap();
+                assert!(value.to_msgpack().unwrap_err().is_from_js());
+            }
+        })
+    }
+}
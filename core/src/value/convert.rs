@@ -2,8 +2,13 @@
 
 use crate::{Atom, Ctx, Result, Value};
 
+#[cfg(feature = "serde")]
+pub(crate) use de::from_value;
+
 mod atom;
 mod coerce;
+#[cfg(feature = "serde")]
+mod de;
 mod from;
 mod into;
 
@@ -94,3 +99,85 @@ where
 /// A helper type for turning a tuple into a JavaScript array.
 /// Implements [`IntoJs`] and [`FromJs`] for tuples of various lengths
 pub struct List<T>(pub T);
+
+/// How [`FromJs`] converts values of a different type to strings, booleans and numbers, set for a
+/// context with [`Ctx::set_conversion_mode`].
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, convert::ConversionMode};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// assert!(ctx.eval::<String, _>("42").is_err());
+///
+/// ctx.set_conversion_mode(ConversionMode::Lenient);
+/// assert_eq!(ctx.eval::<String, _>("42").unwrap(), "42");
+/// assert_eq!(ctx.eval::<f64, _>("'1.5'").unwrap(), 1.5);
+/// assert_eq!(ctx.eval::<i32, _>("true").unwrap(), 1);
+/// assert!(!ctx.eval::<bool, _>("''").unwrap());
+/// # });
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionMode {
+    /// Values must already have the type they are converted to, a number doesn't convert to a
+    /// string and a string doesn't convert to a boolean.
+    #[default]
+    Strict,
+    /// Primitives of other types are coerced like JavaScript does with `String(value)`,
+    /// `Boolean(value)` and `Number(value)`, the same as converting to [`Coerced`].
+    ///
+    /// Objects, arrays, functions, symbols and big integers are not coerced, so converting never
+    /// runs the `toString` or `valueOf` of a script. `Either` first tries both variants strictly
+    /// and only coerces the value if neither matches. The mode applies to every conversion in the
+    /// context, including the options read by the modules of this crate, so `{ clearEnv: 1 }`
+    /// clears the environment of a subprocess in a lenient context.
+    ///
+    /// Coerced numbers are still checked against the [`NumberConversion`] options, so a string
+    /// which isn't a number converts to `NaN` and to `0` for integers unless
+    /// `reject_non_finite` is set.
+    Lenient,
+}
+
+/// Options for converting JavaScript numbers to Rust numbers with [`FromJs`].
+///
+/// By default `NaN` converts to `0` and negative zero to `0` for integers, non-finite numbers
+/// convert to `null` for `serde_json::Value` with the `serde` feature. `i32` saturates values
+/// out of its range while the other integer types fail to convert them. The options can be set for
+/// a runtime with [`Runtime::set_number_conversion`](crate::Runtime::set_number_conversion) or for
+/// a single call with [`Ctx::with_number_conversion`].
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, convert::NumberConversion};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// assert_eq!(ctx.eval::<u32, _>("NaN").unwrap(), 0);
+///
+/// let mut options = NumberConversion::default();
+/// options.reject_non_finite = true;
+/// ctx.with_number_conversion(options, || {
+///     assert!(ctx.eval::<u32, _>("NaN").is_err());
+///     assert!(ctx.eval::<f64, _>("Infinity").is_err());
+/// });
+///
+/// let mut options = NumberConversion::default();
+/// options.clamp_integers = true;
+/// ctx.with_number_conversion(options, || {
+///     assert_eq!(ctx.eval::<u8, _>("300").unwrap(), 255);
+///     assert_eq!(ctx.eval::<u64, _>("-Infinity").unwrap(), 0);
+/// });
+/// # });
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NumberConversion {
+    /// Fail to convert `NaN`, `Infinity` and `-Infinity` to integers, floats and
+    /// `serde_json::Value`.
+    pub reject_non_finite: bool,
+    /// Fail to convert negative zero to integers.
+    pub reject_negative_zero: bool,
+    /// Clamp values out of the range of an integer type to its minimum or maximum instead of
+    /// failing to convert them.
+    pub clamp_integers: bool,
+}
@@ -1,5 +1,6 @@
 use crate::{
-    atom::PredefinedAtom, qjs, Atom, Ctx, FromAtom, IntoAtom, Result, StdString, String, Value,
+    atom::PredefinedAtom, qjs, Atom, Ctx, Error, FromAtom, IntoAtom, Result, StdString, String,
+    Value,
 };
 
 impl<'js> FromAtom<'js> for Atom<'js> {
@@ -20,6 +21,13 @@ impl<'js> FromAtom<'js> for String<'js> {
     }
 }
 
+impl<'js> FromAtom<'js> for u32 {
+    fn from_atom(atom: Atom<'js>) -> Result<Self> {
+        atom.index()
+            .ok_or_else(|| Error::new_from_js("atom", "array index"))
+    }
+}
+
 impl<'js> FromAtom<'js> for StdString {
     fn from_atom(atom: Atom<'js>) -> Result<Self> {
         atom.to_string()
@@ -38,6 +46,12 @@ impl<'js> IntoAtom<'js> for Atom<'js> {
     }
 }
 
+impl<'js> IntoAtom<'js> for &Atom<'js> {
+    fn into_atom(self, _: &Ctx<'js>) -> Result<Atom<'js>> {
+        Ok(self.clone())
+    }
+}
+
 impl<'js> IntoAtom<'js> for Value<'js> {
     fn into_atom(self, ctx: &Ctx<'js>) -> Result<Atom<'js>> {
         Atom::from_value(ctx.clone(), &self)
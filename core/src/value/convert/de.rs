@@ -0,0 +1,301 @@
+//! Deserializing values directly into types implementing `serde::Deserialize`.
+
+use core::fmt;
+
+use serde::de::{
+    self, value::StringDeserializer, DeserializeOwned, DeserializeSeed, Deserializer,
+    IntoDeserializer, Visitor,
+};
+
+use super::from::{check_float, NestedGuard};
+use crate::{Array, Ctx, Error, Object, Result, StdString, String, Type, Value};
+
+/// Deserialize a value into `T`, failing with [`Error::FromJs`] if it doesn't match.
+pub(crate) fn from_value<'js, T: DeserializeOwned>(ctx: &Ctx<'js>, value: Value<'js>) -> Result<T> {
+    let from = value.type_name();
+    T::deserialize(ValueDeserializer { ctx, value }).map_err(|error| match error {
+        DeError::Js(error) => error,
+        DeError::Custom(message) => Error::FromJs {
+            from,
+            to: core::any::type_name::<T>(),
+            message: Some(message),
+        },
+    })
+}
+
+type DeResult<T> = core::result::Result<T, DeError>;
+
+const TO: &str = "Deserialize";
+
+/// The largest integer a float represents exactly, larger ones are deserialized as floats.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+/// Either an error of the runtime, like an exception or a reference cycle, or a value which
+/// doesn't match the deserialized type.
+#[derive(Debug)]
+enum DeError {
+    Js(Error),
+    Custom(StdString),
+}
+
+impl From<Error> for DeError {
+    fn from(error: Error) -> Self {
+        DeError::Js(error)
+    }
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeError::Js(error) => error.fmt(f),
+            DeError::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError::Custom(msg.to_string())
+    }
+}
+
+/// Values which are skipped in objects and become `null` in arrays, like `JSON.stringify` does.
+fn is_skipped(value: &Value<'_>) -> bool {
+    value.is_undefined() || value.is_function() || value.is_symbol()
+}
+
+struct ValueDeserializer<'a, 'js> {
+    ctx: &'a Ctx<'js>,
+    value: Value<'js>,
+}
+
+impl<'de, 'a, 'js> Deserializer<'de> for ValueDeserializer<'a, 'js> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        let ctx = self.ctx;
+        let value = self.value;
+        match value.type_of() {
+            Type::Uninitialized | Type::Undefined | Type::Null => visitor.visit_unit(),
+            Type::Symbol | Type::Function | Type::Constructor => visitor.visit_unit(),
+            Type::Bool => visitor.visit_bool(unsafe { value.get_bool() }),
+            Type::Int => visitor.visit_i32(unsafe { value.get_int() }),
+            Type::Float => {
+                let num = check_float(ctx, unsafe { value.get_float() }, TO)?;
+                if !num.is_finite() {
+                    visitor.visit_unit()
+                } else if num.fract() == 0.0 && num.abs() <= MAX_SAFE_INTEGER {
+                    // Integers which don't fit an `i32` are stored as floats.
+                    visitor.visit_i64(num as i64)
+                } else {
+                    visitor.visit_f64(num)
+                }
+            }
+            Type::String => visitor.visit_string(String::from_value(value)?.to_string()?),
+            Type::Array => {
+                let array = Array::from_value(value)?;
+                let _guard = NestedGuard::enter(ctx, &array, TO)?;
+                let len = array.len();
+                let mut seq = SeqAccess {
+                    ctx,
+                    array,
+                    index: 0,
+                    len,
+                };
+                let res = visitor.visit_seq(&mut seq)?;
+                if seq.index < seq.len {
+                    return Err(de::Error::invalid_length(
+                        seq.len,
+                        &"fewer elements in array",
+                    ));
+                }
+                Ok(res)
+            }
+            Type::Object | Type::Promise | Type::Exception => {
+                let object = Object::from_value(value)?;
+                let _guard = NestedGuard::enter(ctx, &object, TO)?;
+                let props = object
+                    .props::<StdString, Value>()
+                    .collect::<Result<Vec<_>>>()?;
+                visitor.visit_map(MapAccess {
+                    ctx,
+                    props: props.into_iter(),
+                    value: None,
+                })
+            }
+            type_ => Err(Error::new_from_js(type_.as_str(), TO).into()),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        if self.value.type_of().is_void() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        // Unit variants are strings, the others objects with the variant as their only key.
+        if self.value.is_string() {
+            let variant = String::from_value(self.value)?.to_string()?;
+            return visitor.visit_enum(variant.into_deserializer());
+        }
+        let Some(object) = self.value.as_object() else {
+            return Err(de::Error::invalid_type(
+                de::Unexpected::Other(self.value.type_name()),
+                &"a string or an object with a single key",
+            ));
+        };
+        let mut props = object.props::<StdString, Value>();
+        match (props.next().transpose()?, props.next().transpose()?) {
+            (Some((variant, value)), None) => visitor.visit_enum(EnumAccess {
+                ctx: self.ctx,
+                variant,
+                value,
+            }),
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Map,
+                &"an object with a single key",
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+struct SeqAccess<'a, 'js> {
+    ctx: &'a Ctx<'js>,
+    array: Array<'js>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de, 'a, 'js> de::SeqAccess<'de> for SeqAccess<'a, 'js> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> DeResult<Option<T::Value>> {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let mut value: Value = self.array.get(self.index)?;
+        self.index += 1;
+        if is_skipped(&value) {
+            value = Value::null(self.ctx);
+        }
+        seed.deserialize(ValueDeserializer {
+            ctx: self.ctx,
+            value,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+struct MapAccess<'a, 'js> {
+    ctx: &'a Ctx<'js>,
+    props: alloc::vec::IntoIter<(StdString, Value<'js>)>,
+    value: Option<Value<'js>>,
+}
+
+impl<'de, 'a, 'js> de::MapAccess<'de> for MapAccess<'a, 'js> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> DeResult<Option<K::Value>> {
+        for (key, value) in self.props.by_ref() {
+            if !is_skipped(&value) {
+                self.value = Some(value);
+                let key: StringDeserializer<DeError> = key.into_deserializer();
+                return seed.deserialize(key).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> DeResult<V::Value> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(ValueDeserializer {
+            ctx: self.ctx,
+            value,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.props.len())
+    }
+}
+
+struct EnumAccess<'a, 'js> {
+    ctx: &'a Ctx<'js>,
+    variant: StdString,
+    value: Value<'js>,
+}
+
+impl<'de, 'a, 'js> de::EnumAccess<'de> for EnumAccess<'a, 'js> {
+    type Error = DeError;
+    type Variant = ValueDeserializer<'a, 'js>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> DeResult<(V::Value, Self::Variant)> {
+        let variant: StringDeserializer<DeError> = self.variant.into_deserializer();
+        let value = ValueDeserializer {
+            ctx: self.ctx,
+            value: self.value,
+        };
+        Ok((seed.deserialize(variant)?, value))
+    }
+}
+
+impl<'de, 'a, 'js> de::VariantAccess<'de> for ValueDeserializer<'a, 'js> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> DeResult<()> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> DeResult<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        self.deserialize_any(visitor)
+    }
+}
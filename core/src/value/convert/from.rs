@@ -1,14 +1,25 @@
 use crate::{
-    convert::List, Array, Ctx, Error, FromAtom, FromJs, Object, Result, StdString, String, Type,
-    Value,
+    convert::{Coerced, ConversionMode, List},
+    runtime::opaque::Opaque,
+    Array, Ctx, Error, FromAtom, FromJs, Object, Result, StdString, String, Type, Value,
 };
-use std::{
-    cell::{Cell, RefCell},
-    collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque},
-    hash::{BuildHasher, Hash},
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, LinkedList, VecDeque},
     rc::Rc,
-    sync::{Arc, Mutex, RwLock},
-    time::{Duration, SystemTime},
+    sync::Arc,
+    vec::Vec,
+};
+use core::cell::{Cell, RefCell};
+#[cfg(any(feature = "std", feature = "indexmap"))]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Mutex, RwLock},
+    time::SystemTime,
 };
 
 #[cfg(feature = "either")]
@@ -24,7 +35,10 @@ impl<'js> FromJs<'js> for Value<'js> {
 }
 
 impl<'js> FromJs<'js> for StdString {
-    fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        if !value.is_string() && coerces(ctx, &value) {
+            return Coerced::<StdString>::from_js(ctx, value).map(|x| x.0);
+        }
         String::from_value(value).and_then(|string| string.to_string())
     }
 }
@@ -32,7 +46,7 @@ impl<'js> FromJs<'js> for StdString {
 impl<'js> FromJs<'js> for char {
     fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
         let type_name = value.type_name();
-        let s = String::from_value(value)?.to_string()?;
+        let s = String::from_value(value)?.as_str()?;
 
         let mut chars = s.chars();
         let (c, more) = (chars.next(), chars.next());
@@ -95,16 +109,81 @@ where
     R: FromJs<'js>,
 {
     fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
-        L::from_js(ctx, value.clone()).map(Left).or_else(|error| {
-            if error.is_from_js() {
-                R::from_js(ctx, value).map(Right)
-            } else {
-                Err(error)
-            }
-        })
+        let probe = |value: Value<'js>| {
+            L::from_js(ctx, value.clone()).map(Left).or_else(|error| {
+                if error.is_from_js() {
+                    R::from_js(ctx, value).map(Right)
+                } else {
+                    Err(error)
+                }
+            })
+        };
+        if ctx.conversion_mode() != ConversionMode::Lenient {
+            return probe(value);
+        }
+        // Prefer the variant the value already has over coercing it to the first one.
+        let strict = {
+            let _guard = StrictGuard::new(ctx);
+            probe(value.clone())
+        };
+        match strict {
+            Err(error) if error.is_from_js() => probe(value),
+            res => res,
+        }
+    }
+}
+
+/// Makes the conversions of a context strict until dropped.
+#[cfg(feature = "either")]
+struct StrictGuard<'a, 'js>(&'a Ctx<'js>, ConversionMode);
+
+#[cfg(feature = "either")]
+impl<'a, 'js> StrictGuard<'a, 'js> {
+    fn new(ctx: &'a Ctx<'js>) -> Self {
+        let mode = ctx.conversion_mode();
+        ctx.set_conversion_mode(ConversionMode::Strict);
+        StrictGuard(ctx, mode)
     }
 }
 
+#[cfg(feature = "either")]
+impl Drop for StrictGuard<'_, '_> {
+    fn drop(&mut self) {
+        self.0.set_conversion_mode(self.1)
+    }
+}
+
+/// Keeps an object marked as being converted until dropped.
+pub(super) struct NestedGuard<'a, 'js>(&'a Opaque<'js>);
+
+impl<'a, 'js> NestedGuard<'a, 'js> {
+    /// Enter the conversion of an object whose values are converted recursively, failing on
+    /// reference cycles and too deeply nested values instead of overflowing the stack.
+    pub(super) fn enter(ctx: &'a Ctx<'js>, object: &Object<'js>, to: &'static str) -> Result<Self> {
+        let opaque = unsafe { ctx.get_opaque() };
+        opaque
+            .enter_conversion(unsafe { object.as_value().get_ptr() })
+            .map_err(|msg| Error::new_from_js_message(object.type_name(), to, msg))?;
+        Ok(NestedGuard(opaque))
+    }
+}
+
+impl Drop for NestedGuard<'_, '_> {
+    fn drop(&mut self) {
+        self.0.leave_conversion()
+    }
+}
+
+/// Whether a value of another type is coerced in this context, see [`ConversionMode`].
+///
+/// Only primitives whose coercion can't run scripts are coerced.
+fn coerces(ctx: &Ctx<'_>, value: &Value<'_>) -> bool {
+    matches!(
+        value.type_of(),
+        Type::Undefined | Type::Null | Type::Bool | Type::Int | Type::Float | Type::String
+    ) && ctx.conversion_mode() == ConversionMode::Lenient
+}
+
 fn tuple_match_size(actual: usize, expected: usize) -> Result<()> {
     if actual == expected {
         Ok(())
@@ -137,6 +216,40 @@ fn number_match_range<T: PartialOrd>(
     }
 }
 
+/// Check a float converted to Rust against the number conversion options.
+pub(super) fn check_float(ctx: &Ctx<'_>, num: f64, to: &'static str) -> Result<f64> {
+    let options = unsafe { ctx.get_opaque() }.number_conversion();
+    if options.reject_non_finite && !num.is_finite() {
+        return Err(Error::new_from_js_message(
+            "float",
+            to,
+            "Not a finite number",
+        ));
+    }
+    Ok(num)
+}
+
+/// Check a number converted to a Rust integer against the range of the integer and the number
+/// conversion options, returns the number clamped to the range if clamping is enabled.
+fn check_integer(
+    ctx: &Ctx<'_>,
+    num: f64,
+    min: f64,
+    max: f64,
+    from: &'static str,
+    to: &'static str,
+) -> Result<f64> {
+    let options = unsafe { ctx.get_opaque() }.number_conversion();
+    if options.reject_negative_zero && num == 0.0 && num.is_sign_negative() {
+        return Err(Error::new_from_js_message(from, to, "Negative zero"));
+    }
+    if options.clamp_integers {
+        return Ok(num.clamp(min, max));
+    }
+    number_match_range(num, min, max, from, to)?;
+    Ok(num)
+}
+
 macro_rules! from_js_impls {
     // for reference types
     (ref: $($(#[$meta:meta])* $type:ident,)*) => {
@@ -160,8 +273,9 @@ macro_rules! from_js_impls {
             where
                 $($type: FromJs<'js>,)*
             {
-                fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
                     let array = Array::from_value(value)?;
+                    let _guard = NestedGuard::enter(ctx, &array, "tuple")?;
 
                     let tuple_len = 0 $(+ from_js_impls!(@one $type))*;
                     let array_len = array.len();
@@ -184,8 +298,9 @@ macro_rules! from_js_impls {
                 T: FromJs<'js> $(+ $($guard)*)*,
                 $($param: $($pguard)*,)*
             {
-                fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
                     let array = Array::from_value(value)?;
+                    let _guard = NestedGuard::enter(ctx, &array, stringify!($type))?;
                     array.iter().collect::<Result<_>>()
                 }
             }
@@ -202,38 +317,26 @@ macro_rules! from_js_impls {
                 V: FromJs<'js>,
                 $($param: $($pguard)*,)*
             {
-                fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
                     let object = Object::from_value(value)?;
+                    let _guard = NestedGuard::enter(ctx, &object, stringify!($type))?;
                     object.props().collect::<Result<_>>()
                 }
             }
         )*
     };
 
-    // for basic primitive types (int and float)
-    // (ex. f64 => Float as_float Int as_int)
-    (val: $($type:ty => $($jstype:ident $getfn:ident)*,)*) => {
-        $(
-            impl<'js> FromJs<'js> for $type {
-                fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
-                    let type_ = value.type_of();
-                    match type_ {
-                        $(Type::$jstype => Ok(unsafe { value.$getfn() } as _),)*
-                        _ => Err(Error::new_from_js(type_.as_str(), stringify!($type))),
-                    }
-                }
-            }
-        )*
-    };
-
     // for other primitive types
     (val: $($base:ident: $($type:ident)*,)*) => {
         $(
             $(
                 impl<'js> FromJs<'js> for $type {
                     fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                        if let Some(num) = value.as_int().and_then(|x| $type::try_from(x).ok()) {
+                            return Ok(num);
+                        }
                         let num = <$base>::from_js(ctx, value)?;
-                        number_match_range(num, $type::MIN as $base, $type::MAX as $base, stringify!($base), stringify!($type))?;
+                        let num = check_integer(ctx, num as f64, $type::MIN as f64, $type::MAX as f64, stringify!($base), stringify!($type))?;
                         Ok(num as $type)
                     }
                 }
@@ -267,11 +370,46 @@ from_js_impls! {
     f64: u32 u64 i64 usize isize,
 }
 
-from_js_impls! {
-    val:
-    bool => Bool get_bool,
-    i32 => Float get_float Int get_int,
-    f64 => Float get_float Int get_int,
+impl<'js> FromJs<'js> for bool {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        match value.type_of() {
+            Type::Bool => Ok(unsafe { value.get_bool() }),
+            _ if coerces(ctx, &value) => Coerced::<bool>::from_js(ctx, value).map(|x| x.0),
+            type_ => Err(Error::new_from_js(type_.as_str(), "bool")),
+        }
+    }
+}
+
+impl<'js> FromJs<'js> for i32 {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        match value.type_of() {
+            Type::Int => Ok(unsafe { value.get_int() }),
+            Type::Float => float_to_i32(ctx, unsafe { value.get_float() }),
+            _ if coerces(ctx, &value) => float_to_i32(ctx, Coerced::<f64>::from_js(ctx, value)?.0),
+            type_ => Err(Error::new_from_js(type_.as_str(), "i32")),
+        }
+    }
+}
+
+fn float_to_i32(ctx: &Ctx<'_>, num: f64) -> Result<i32> {
+    let (min, max) = (i32::MIN as f64, i32::MAX as f64);
+    let num = check_float(ctx, num, "i32")?;
+    // Out of range floats saturate even if clamping isn't enabled.
+    let num = check_integer(ctx, num.clamp(min, max), min, max, "float", "i32")?;
+    Ok(num as i32)
+}
+
+impl<'js> FromJs<'js> for f64 {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        match value.type_of() {
+            Type::Int => Ok(unsafe { value.get_int() } as f64),
+            Type::Float => check_float(ctx, unsafe { value.get_float() }, "f64"),
+            _ if coerces(ctx, &value) => {
+                check_float(ctx, Coerced::<f64>::from_js(ctx, value)?.0, "f64")
+            }
+            type_ => Err(Error::new_from_js(type_.as_str(), "f64")),
+        }
+    }
 }
 
 from_js_impls! {
@@ -281,7 +419,11 @@ from_js_impls! {
     Arc,
     Cell,
     RefCell,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     Mutex,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     RwLock,
 }
 
@@ -314,6 +456,8 @@ from_js_impls! {
     /// Convert from JS array to Rust linked list
     LinkedList,
     /// Convert from JS array to Rust hash set
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     HashSet {S: Default + BuildHasher} (Eq + Hash),
     /// Convert from JS array to Rust btree set
     BTreeSet (Eq + Ord),
@@ -326,10 +470,17 @@ from_js_impls! {
 from_js_impls! {
     map:
     /// Convert from JS object to Rust hash map
+    ///
+    /// The order of the properties is lost, use an `IndexMap` to keep it.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     HashMap {S: Default + BuildHasher} (Eq + Hash),
     /// Convert from JS object to Rust btree map
     BTreeMap (Eq + Ord),
     /// Convert from JS object to Rust index map
+    ///
+    /// The entries are in the order of the own enumerable properties of the object, integer keys
+    /// ascending first and then the other keys in the order they were added.
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "indexmap")))]
     IndexMap {S: Default + BuildHasher} (Eq + Hash),
@@ -341,6 +492,7 @@ impl<'js> FromJs<'js> for f32 {
     }
 }
 
+#[cfg(any(feature = "std", feature = "chrono"))]
 fn date_to_millis<'js>(ctx: &Ctx<'js>, value: Value<'js>) -> Result<i64> {
     let global = ctx.globals();
     let date_ctor: Object = global.get("Date")?;
@@ -356,6 +508,8 @@ fn date_to_millis<'js>(ctx: &Ctx<'js>, value: Value<'js>) -> Result<i64> {
     get_time_fn.call((crate::function::This(value),))
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
 impl<'js> FromJs<'js> for SystemTime {
     fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<SystemTime> {
         let millis = date_to_millis(ctx, value)?;
@@ -403,8 +557,187 @@ chrono_from_js_impls! {
     Local;
 }
 
+/// Converts values structurally like `JSON.stringify` without calling `toJSON`: `undefined`,
+/// functions and symbols are skipped in objects and become `null` elsewhere, like non-finite
+/// numbers unless [`NumberConversion::reject_non_finite`](crate::convert::NumberConversion) is
+/// set. Nested arrays and objects are subject to the same depth and cycle checks as the
+/// collections.
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "serde")))]
+impl<'js> FromJs<'js> for serde_json::Value {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        use serde_json::{Map, Number, Value as Json};
+
+        const TO: &str = "serde_json::Value";
+
+        fn is_skipped(value: &Value<'_>) -> bool {
+            value.is_undefined() || value.is_function() || value.is_symbol()
+        }
+
+        Ok(match value.type_of() {
+            Type::Uninitialized | Type::Undefined | Type::Null => Json::Null,
+            Type::Symbol | Type::Function | Type::Constructor => Json::Null,
+            Type::Bool => Json::Bool(unsafe { value.get_bool() }),
+            Type::Int => Json::from(unsafe { value.get_int() }),
+            Type::Float => {
+                let num = check_float(ctx, unsafe { value.get_float() }, TO)?;
+                Number::from_f64(num).map_or(Json::Null, Json::Number)
+            }
+            Type::String => Json::String(String::from_value(value)?.to_string()?),
+            Type::Array => {
+                let array = Array::from_value(value)?;
+                let _guard = NestedGuard::enter(ctx, &array, TO)?;
+                let mut items = Vec::with_capacity(array.len());
+                for item in array.iter::<Value>() {
+                    let item = item?;
+                    items.push(if is_skipped(&item) {
+                        Json::Null
+                    } else {
+                        Self::from_js(ctx, item)?
+                    });
+                }
+                Json::Array(items)
+            }
+            Type::Object | Type::Promise | Type::Exception => {
+                let object = Object::from_value(value)?;
+                let _guard = NestedGuard::enter(ctx, &object, TO)?;
+                let mut map = Map::new();
+                for prop in object.props::<StdString, Value>() {
+                    let (key, value) = prop?;
+                    if !is_skipped(&value) {
+                        map.insert(key, Self::from_js(ctx, value)?);
+                    }
+                }
+                Json::Object(map)
+            }
+            type_ => return Err(Error::new_from_js(type_.as_str(), TO)),
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
+    #[test]
+    fn nested_conversion_limits() {
+        use crate::{Context, Ctx, FromJs, Result, Runtime, Value};
+        use std::collections::HashMap;
+
+        struct Tree(Vec<Tree>);
+
+        impl<'js> FromJs<'js> for Tree {
+            fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                Vec::from_js(ctx, value).map(Tree)
+            }
+        }
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        ctx.with(|ctx| {
+            let err = ctx
+                .eval::<Tree, _>("const a = []; a.push(a); a")
+                .err()
+                .unwrap();
+            assert!(err.to_string().contains("reference cycle"));
+            let res: Tree = ctx.eval("const b = []; [b, b]").unwrap();
+            assert_eq!(res.0.len(), 2);
+
+            let err = ctx
+                .eval::<Tree, _>("let c = []; for (let i = 0; i < 5000; i++) c = [c]; c")
+                .err()
+                .unwrap();
+            assert!(err.to_string().contains("maximum conversion depth"));
+
+            let res: HashMap<String, Vec<i32>> = ctx.eval("({ x: [1, 2] })").unwrap();
+            assert_eq!(res["x"], [1, 2]);
+        });
+
+        runtime.set_max_conversion_depth(2);
+        ctx.with(|ctx| {
+            assert!(ctx.eval::<Tree, _>("[[]]").is_ok());
+            assert!(ctx.eval::<Tree, _>("[[[]]]").is_err());
+        });
+    }
+
+    #[test]
+    fn number_conversion() {
+        use crate::{convert::NumberConversion, Context, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        ctx.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>("NaN").unwrap(), 0);
+            assert_eq!(ctx.eval::<i32, _>("1e20").unwrap(), i32::MAX);
+            assert_eq!(ctx.eval::<u32, _>("-0").unwrap(), 0);
+            assert!(ctx.eval::<u32, _>("-1").is_err());
+            assert!(ctx.eval::<f64, _>("-0").unwrap().is_sign_negative());
+        });
+
+        runtime.set_number_conversion(NumberConversion {
+            reject_non_finite: true,
+            reject_negative_zero: true,
+            ..Default::default()
+        });
+        ctx.with(|ctx| {
+            assert!(ctx.eval::<i32, _>("NaN").is_err());
+            assert!(ctx.eval::<i64, _>("Infinity").is_err());
+            assert!(ctx.eval::<u8, _>("-0").is_err());
+            assert!(ctx.eval::<u64, _>("-0").is_err());
+            assert!(ctx.eval::<f64, _>("-0").unwrap().is_sign_negative());
+            assert_eq!(ctx.eval::<i32, _>("1e20").unwrap(), i32::MAX);
+
+            let res = ctx.with_number_conversion(NumberConversion::default(), || {
+                ctx.eval::<i32, _>("NaN").unwrap()
+            });
+            assert_eq!(res, 0);
+            assert!(ctx.eval::<f32, _>("NaN").is_err());
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_value() {
+        use crate::{convert::NumberConversion, Context, Runtime};
+        use serde_json::json;
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        ctx.with(|ctx| {
+            let res: serde_json::Value = ctx
+                .eval("({ a: [1, 2.5, undefined, NaN], b: { s: 'x', f() {}, u: undefined }, c: null })")
+                .unwrap();
+            assert_eq!(
+                res,
+                json!({ "a": [1, 2.5, null, null], "b": { "s": "x" }, "c": null })
+            );
+            assert!(ctx.eval::<serde_json::Value, _>("1n").is_err());
+
+            let options = NumberConversion {
+                reject_non_finite: true,
+                ..Default::default()
+            };
+            ctx.with_number_conversion(options, || {
+                assert!(ctx.eval::<serde_json::Value, _>("[1, Infinity]").is_err());
+                assert_eq!(ctx.eval::<serde_json::Value, _>("-0").unwrap(), json!(-0.0));
+            });
+
+            let err = ctx
+                .eval::<serde_json::Value, _>("const a = { x: [] }; a.x.push(a); a")
+                .unwrap_err();
+            assert!(err.to_string().contains("reference cycle"));
+            let res: serde_json::Value = ctx.eval("const b = {}; [b, b]").unwrap();
+            assert_eq!(res, json!([{}, {}]));
+        });
+
+        runtime.set_max_conversion_depth(2);
+        ctx.with(|ctx| {
+            assert!(ctx.eval::<serde_json::Value, _>("[[1]]").is_ok());
+            assert!(ctx.eval::<serde_json::Value, _>("[[[1]]]").is_err());
+        });
+    }
+
     #[test]
     fn js_to_system_time() {
         use crate::{Context, Runtime};
@@ -456,4 +789,32 @@ mod test {
             assert_eq!(1654309010000, res.timestamp_millis());
         });
     }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_map_keeps_order() {
+        use crate::{Context, Object, Runtime};
+        use indexmap::IndexMap;
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        ctx.with(|ctx| {
+            let res: IndexMap<String, i32> = ctx
+                .eval(
+                    "const columns = { name: 1, age: 2, 10: 3, 2: 4 }; columns.email = 5; columns",
+                )
+                .unwrap();
+            let keys: Vec<_> = res.keys().map(String::as_str).collect();
+            assert_eq!(keys, ["2", "10", "name", "age", "email"]);
+
+            let map: IndexMap<&str, i32> = [("z", 1), ("a", 2), ("m", 3)].into_iter().collect();
+            ctx.globals().set("map", map).unwrap();
+            let keys: String = ctx.eval("Object.keys(map).join()").unwrap();
+            assert_eq!(keys, "z,a,m");
+            let object: Object = ctx.globals().get("map").unwrap();
+            let back: IndexMap<String, i32> = object.into_value().get().unwrap();
+            assert_eq!(back.get_index(0), Some((&"z".to_string(), &1)));
+        });
+    }
 }
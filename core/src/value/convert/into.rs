@@ -1,11 +1,16 @@
 use crate::{
     convert::{IteratorJs, List},
-    value::Constructor,
     Array, Ctx, Error, IntoAtom, IntoJs, Object, Result, StdResult, StdString, String, Value,
 };
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, LinkedList, VecDeque},
+    vec::Vec,
+};
+use core::cell::{Cell, RefCell};
+#[cfg(feature = "std")]
 use std::{
-    cell::{Cell, RefCell},
-    collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque},
+    collections::{HashMap, HashSet},
     sync::{Mutex, RwLock},
     time::SystemTime,
 };
@@ -48,7 +53,7 @@ impl<'js> IntoJs<'js> for &str {
 
 impl<'js> IntoJs<'js> for char {
     fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
-        String::from_str(ctx.clone(), self.to_string().as_str()).map(|String(value)| value)
+        String::from_str(ctx.clone(), self.encode_utf8(&mut [0; 4])).map(|String(value)| value)
     }
 }
 
@@ -190,6 +195,8 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
 impl<'js, T> IntoJs<'js> for Mutex<T>
 where
     T: IntoJs<'js>,
@@ -200,6 +207,8 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
 impl<'js, T> IntoJs<'js> for &Mutex<T>
 where
     for<'r> &'r T: IntoJs<'js>,
@@ -210,6 +219,8 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
 impl<'js, T> IntoJs<'js> for RwLock<T>
 where
     T: IntoJs<'js>,
@@ -220,6 +231,8 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
 impl<'js, T> IntoJs<'js> for &RwLock<T>
 where
     for<'r> &'r T: IntoJs<'js>,
@@ -420,6 +433,8 @@ into_js_impls! {
     /// Convert from Rust linked list to JS array
     LinkedList,
     /// Convert from Rust hash set to JS array
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     HashSet {S},
     /// Convert from Rust btree set to JS array
     BTreeSet,
@@ -432,10 +447,14 @@ into_js_impls! {
 into_js_impls! {
     map:
     /// Convert from Rust hash map to JS object
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
     HashMap {S},
     /// Convert from Rust btree map to JS object
     BTreeMap,
     /// Convert from Rust index map to JS object
+    ///
+    /// The properties are defined in the order of the entries.
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "indexmap")))]
     IndexMap {S},
@@ -453,12 +472,15 @@ into_js_impls! {
     i32 f64 => i64 u32 u64 usize isize,
 }
 
+#[cfg(any(feature = "std", feature = "chrono"))]
 fn millis_to_date<'js>(ctx: &Ctx<'js>, millis: i64) -> Result<Value<'js>> {
-    let date_ctor: Constructor = ctx.globals().get("Date")?;
+    let date_ctor: crate::value::Constructor = ctx.globals().get("Date")?;
 
     date_ctor.construct((millis,))
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "std")))]
 impl<'js> IntoJs<'js> for SystemTime {
     fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
         let millis = match self.duration_since(SystemTime::UNIX_EPOCH) {
@@ -560,7 +582,7 @@ mod test {
             let globs = ctx.globals();
             globs.set("ts", ts.into_js(&ctx).unwrap()).unwrap();
             let res: i64 = ctx.eval("ts.getTime()").unwrap();
-            assert_eq!(-(millis as i64), res as _);
+            assert_eq!(-(millis as i64), res);
         });
     }
 
@@ -0,0 +1,332 @@
+use crate::{
+    value::Constructor, ArrayBuffer, Ctx, Error, Exception, FromJs, IntoJs, JsLifetime, Object,
+    Result, Value,
+};
+use core::{ops::Deref, ptr::NonNull};
+
+/// Rust representation of a JavaScript object of class DataView.
+///
+/// A view which reads and writes numbers of different types and byte orders at arbitrary offsets
+/// of an [`ArrayBuffer`]. Reads and writes access the buffer directly without calling into
+/// JavaScript. Like in JavaScript, accessing a value outside of the view throws a `RangeError`.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, ArrayBuffer, DataView};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let buffer = ArrayBuffer::new_copy(ctx.clone(), [0u8, 0, 1, 2, 3, 4]).unwrap();
+/// let view = DataView::new(buffer).unwrap();
+/// assert_eq!(view.get_u16(2, false).unwrap(), 0x0102);
+/// assert_eq!(view.get_u16(2, true).unwrap(), 0x0201);
+///
+/// view.set_u32(0, 0xdeadbeef, true).unwrap();
+/// assert_eq!(view.get_u8(0).unwrap(), 0xef);
+/// assert!(view.get_u32(4, true).is_err());
+/// # });
+/// ```
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "array-buffer")))]
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct DataView<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for DataView<'js> {
+    type Changed<'to> = DataView<'to>;
+}
+
+macro_rules! data_view_access {
+    ($($ty:ident $get:ident $set:ident,)*) => {
+        $(
+            #[doc = concat!("Read a `", stringify!($ty), "` at the given byte offset of the view.")]
+            pub fn $get(&self, offset: usize, little_endian: bool) -> Result<$ty> {
+                let bytes = self.read(offset)?;
+                Ok(if little_endian {
+                    $ty::from_le_bytes(bytes)
+                } else {
+                    $ty::from_be_bytes(bytes)
+                })
+            }
+
+            #[doc = concat!("Write a `", stringify!($ty), "` at the given byte offset of the view.")]
+            pub fn $set(&self, offset: usize, value: $ty, little_endian: bool) -> Result<()> {
+                self.write(
+                    offset,
+                    if little_endian {
+                        value.to_le_bytes()
+                    } else {
+                        value.to_be_bytes()
+                    },
+                )
+            }
+        )*
+    };
+}
+
+impl<'js> DataView<'js> {
+    /// Create a view over the whole array buffer.
+    pub fn new(buffer: ArrayBuffer<'js>) -> Result<Self> {
+        let constructor = Self::constructor(buffer.ctx())?;
+        constructor.construct((buffer,)).map(DataView)
+    }
+
+    /// Create a view over a part of the array buffer.
+    ///
+    /// If `length` is `None` the view extends to the end of the buffer.
+    pub fn new_range(
+        buffer: ArrayBuffer<'js>,
+        offset: usize,
+        length: Option<usize>,
+    ) -> Result<Self> {
+        let constructor = Self::constructor(buffer.ctx())?;
+        match length {
+            Some(length) => constructor.construct((buffer, offset, length)),
+            None => constructor.construct((buffer, offset)),
+        }
+        .map(DataView)
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Constructor<'js>> {
+        ctx.globals().get("DataView")
+    }
+
+    /// Returns the array buffer which this view accesses.
+    pub fn buffer(&self) -> Result<ArrayBuffer<'js>> {
+        self.0.get("buffer")
+    }
+
+    /// Returns the offset of the view in bytes from the start of its buffer.
+    pub fn byte_offset(&self) -> Result<usize> {
+        self.0.get("byteOffset")
+    }
+
+    /// Returns the length of the view in bytes.
+    pub fn byte_length(&self) -> Result<usize> {
+        self.0.get("byteLength")
+    }
+
+    /// Returns a pointer to the start of the view and its length.
+    fn get_raw(&self) -> Result<(NonNull<u8>, usize)> {
+        let raw = self
+            .buffer()?
+            .as_raw()
+            .ok_or_else(|| Exception::throw_type(self.ctx(), "DataView buffer is detached"))?;
+        let offset = self.byte_offset()?;
+        let len = self.byte_length()?;
+        // A resizable buffer can shrink below the end of the view.
+        if offset + len > raw.len {
+            return Err(self.range_error());
+        }
+        Ok((
+            unsafe { NonNull::new_unchecked(raw.ptr.as_ptr().add(offset)) },
+            len,
+        ))
+    }
+
+    fn range_error(&self) -> Error {
+        Exception::throw_range(self.ctx(), "Offset is outside the bounds of the DataView")
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        let (ptr, len) = self.get_raw()?;
+        if !matches!(offset.checked_add(N), Some(end) if end <= len) {
+            return Err(self.range_error());
+        }
+        let mut bytes = [0u8; N];
+        unsafe {
+            ptr.as_ptr()
+                .add(offset)
+                .copy_to_nonoverlapping(bytes.as_mut_ptr(), N)
+        };
+        Ok(bytes)
+    }
+
+    fn write<const N: usize>(&self, offset: usize, bytes: [u8; N]) -> Result<()> {
+        let (ptr, len) = self.get_raw()?;
+        if !matches!(offset.checked_add(N), Some(end) if end <= len) {
+            return Err(self.range_error());
+        }
+        unsafe {
+            ptr.as_ptr()
+                .add(offset)
+                .copy_from_nonoverlapping(bytes.as_ptr(), N)
+        };
+        Ok(())
+    }
+
+    /// Read a `u8` at the given byte offset of the view.
+    pub fn get_u8(&self, offset: usize) -> Result<u8> {
+        self.read::<1>(offset).map(|[x]| x)
+    }
+
+    /// Write a `u8` at the given byte offset of the view.
+    pub fn set_u8(&self, offset: usize, value: u8) -> Result<()> {
+        self.write(offset, [value])
+    }
+
+    /// Read a `i8` at the given byte offset of the view.
+    pub fn get_i8(&self, offset: usize) -> Result<i8> {
+        self.read::<1>(offset).map(|[x]| x as i8)
+    }
+
+    /// Write a `i8` at the given byte offset of the view.
+    pub fn set_i8(&self, offset: usize, value: i8) -> Result<()> {
+        self.write(offset, [value as u8])
+    }
+
+    data_view_access! {
+        u16 get_u16 set_u16,
+        i16 get_i16 set_i16,
+        u32 get_u32 set_u32,
+        i32 get_i32 set_i32,
+        u64 get_u64 set_u64,
+        i64 get_i64 set_i64,
+        f32 get_f32 set_f32,
+        f64 get_f64 set_f64,
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        if object.is_data_view() {
+            Some(Self(object))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'js> Deref for DataView<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for DataView<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for DataView<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for DataView<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        if let Some(v) = Self::from_value(value) {
+            Ok(v)
+        } else {
+            Err(Error::new_from_js(ty_name, "DataView"))
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for DataView<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of [`DataView`].
+    pub fn is_data_view(&self) -> bool {
+        match DataView::constructor(self.ctx()) {
+            Ok(constructor) => self.is_instance_of(constructor),
+            Err(_) => false,
+        }
+    }
+
+    /// Interpret as [`DataView`]
+    ///
+    /// # Safety
+    /// You should be sure that the object actually is the required type.
+    pub unsafe fn ref_data_view(&self) -> &DataView<'js> {
+        &*(self as *const _ as *const DataView)
+    }
+
+    /// Try interpret as [`DataView`]
+    pub fn as_data_view(&self) -> Option<&DataView<'js>> {
+        if self.is_data_view() {
+            Some(unsafe { self.ref_data_view() })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let view: DataView = ctx
+                .eval(
+                    r#"
+                        const view = new DataView(new ArrayBuffer(16), 4, 8);
+                        view.setFloat32(0, 1.5);
+                        view.setInt16(4, -2, true);
+                        view
+                    "#,
+                )
+                .unwrap();
+            assert_eq!(view.byte_offset().unwrap(), 4);
+            assert_eq!(view.byte_length().unwrap(), 8);
+            assert_eq!(view.get_f32(0, false).unwrap(), 1.5);
+            assert_eq!(view.get_i16(4, true).unwrap(), -2);
+            assert_eq!(view.get_i8(5).unwrap(), -1);
+            assert!(view.get_u32(6, true).is_err());
+            assert!(view.get_u8(usize::MAX).is_err());
+        })
+    }
+
+    #[test]
+    fn into_javascript() {
+        test_with(|ctx| {
+            let buffer = ArrayBuffer::new(ctx.clone(), vec![0u8; 16]).unwrap();
+            let view = DataView::new_range(buffer, 8, None).unwrap();
+            view.set_f64(0, -0.25, true).unwrap();
+            ctx.globals().set("view", view).unwrap();
+
+            let res: f64 = ctx.eval("new Float64Array(view.buffer, 8)[0]").unwrap();
+            assert_eq!(res, -0.25);
+
+            let object: Object = ctx.eval("new Uint8Array(1)").unwrap();
+            assert!(!object.is_data_view());
+        })
+    }
+}
@@ -1,4 +1,7 @@
-use std::{error::Error as ErrorTrait, ffi::CStr, fmt};
+use alloc::string::{String, ToString};
+use core::{ffi::CStr, fmt};
+#[cfg(feature = "std")]
+use std::error::Error as ErrorTrait;
 
 use crate::{atom::PredefinedAtom, convert::Coerced, qjs, Ctx, Error, Object, Result, Value};
 
@@ -9,6 +12,7 @@ use crate::{atom::PredefinedAtom, convert::Coerced, qjs, Ctx, Error, Object, Res
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct Exception<'js>(pub(crate) Object<'js>);
 
+#[cfg(feature = "std")]
 impl<'js> ErrorTrait for Exception<'js> {}
 
 impl fmt::Debug for Exception<'_> {
@@ -16,10 +20,85 @@ impl fmt::Debug for Exception<'_> {
         f.debug_struct("Exception")
             .field("message", &self.message())
             .field("stack", &self.stack())
+            .field("position", &self.position())
             .finish()
     }
 }
 
+/// The position in a script at which an exception was created.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorPosition {
+    /// The name of the script or module.
+    pub file: String,
+    /// The line number, starting at 1.
+    pub line: u32,
+    /// The column number, starting at 1.
+    pub column: u32,
+}
+
+impl ErrorPosition {
+    /// Parse the position from a single frame of an error stack, like
+    /// `    at foo (script.js:3:7)` or `    at script.js:3:7`.
+    fn from_frame(frame: &str) -> Option<Self> {
+        let frame = frame.trim().strip_prefix("at ")?;
+        let location = match frame.strip_suffix(')') {
+            Some(rest) => &rest[Self::opening_paren(rest)? + 1..],
+            None => frame,
+        };
+        let mut parts = location.rsplitn(3, ':');
+        let column = parts.next()?.parse().ok()?;
+        let line = parts.next()?.parse().ok()?;
+        let file = parts.next()?.to_string();
+        Some(ErrorPosition { file, line, column })
+    }
+
+    /// Returns the index of the parenthesis opening the location at the end of a frame.
+    ///
+    /// File and function names can contain parentheses themselves, like `copy (1).js`, so the
+    /// parenthesis balancing the closing one is searched instead of the last one.
+    fn opening_paren(frame: &str) -> Option<usize> {
+        let mut depth = 0usize;
+        for (idx, c) in frame.char_indices().rev() {
+            match c {
+                ')' => depth += 1,
+                '(' if depth == 0 => return Some(idx),
+                '(' => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Render the line of the source at this position with a caret pointing at the column.
+    ///
+    /// Returns `None` if the source doesn't have the line.
+    pub fn excerpt(&self, source: &str) -> Option<String> {
+        let text = source.lines().nth((self.line as usize).checked_sub(1)?)?;
+        let gutter = self.line.to_string();
+        // Keep tabs so the caret lines up with the source when displayed.
+        let indent: String = text
+            .chars()
+            .take((self.column as usize).saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        Some(format!(
+            "{gutter} | {text}\n{:width$} | {indent}^",
+            "",
+            width = gutter.len()
+        ))
+    }
+}
+
+impl fmt::Display for ErrorPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.file.fmt(f)?;
+        ':'.fmt(f)?;
+        self.line.fmt(f)?;
+        ':'.fmt(f)?;
+        self.column.fmt(f)
+    }
+}
+
 pub(crate) static ERROR_FORMAT_STR: &CStr =
     unsafe { CStr::from_bytes_with_nul_unchecked("%s\0".as_bytes()) };
 
@@ -86,6 +165,39 @@ impl<'js> Exception<'js> {
             .map(|x| x.0)
     }
 
+    /// Returns the position at which the error was created.
+    ///
+    /// This is the innermost position in a script recorded in the stack as reported by QuickJS,
+    /// for syntax errors the column can be less precise than the line. Returns `None` if the error was created outside of any
+    /// script, for example by native code called directly from Rust.
+    pub fn position(&self) -> Option<ErrorPosition> {
+        self.stack()?.lines().find_map(ErrorPosition::from_frame)
+    }
+
+    /// Render the line of the given source at which the error was created with a caret pointing
+    /// at the column.
+    ///
+    /// The source should be the script the error originates from, see [`Exception::position`].
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let source = "let a = 1;\nif (a > 0) throw new Error('too large');";
+    /// let _ = ctx.eval::<(), _>(source);
+    /// let error = ctx.catch().into_exception().unwrap();
+    /// assert_eq!(error.position().unwrap().line, 2);
+    /// assert_eq!(
+    ///     error.excerpt(source).unwrap(),
+    ///     "2 | if (a > 0) throw new Error('too large');\n  |                  ^"
+    /// );
+    /// # });
+    /// ```
+    pub fn excerpt(&self, source: &str) -> Option<String> {
+        self.position()?.excerpt(source)
+    }
+
     /// Throws a new generic error.
     ///
     /// Equivalent to:
@@ -111,10 +223,10 @@ impl<'js> Exception<'js> {
         // generate C string inline.
         // QuickJS implementation doesn't allow error strings longer then 256 anyway so truncating
         // here is fine.
-        let mut buffer = std::mem::MaybeUninit::<[u8; 256]>::uninit();
+        let mut buffer = core::mem::MaybeUninit::<[u8; 256]>::uninit();
         let str = truncate_str(255, message.as_bytes());
         unsafe {
-            std::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
+            core::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
             buffer.as_mut_ptr().cast::<u8>().add(str.len()).write(b'\0');
             let res = qjs::JS_ThrowSyntaxError(
                 ctx.as_ptr(),
@@ -131,10 +243,10 @@ impl<'js> Exception<'js> {
         // generate C string inline.
         // QuickJS implementation doesn't allow error strings longer then 256 anyway so truncating
         // here is fine.
-        let mut buffer = std::mem::MaybeUninit::<[u8; 256]>::uninit();
+        let mut buffer = core::mem::MaybeUninit::<[u8; 256]>::uninit();
         let str = truncate_str(255, message.as_bytes());
         unsafe {
-            std::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
+            core::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
             buffer.as_mut_ptr().cast::<u8>().add(str.len()).write(b'\0');
             let res = qjs::JS_ThrowTypeError(
                 ctx.as_ptr(),
@@ -151,10 +263,10 @@ impl<'js> Exception<'js> {
         // generate C string inline.
         // QuickJS implementation doesn't allow error strings longer then 256 anyway so truncating
         // here is fine.
-        let mut buffer = std::mem::MaybeUninit::<[u8; 256]>::uninit();
+        let mut buffer = core::mem::MaybeUninit::<[u8; 256]>::uninit();
         let str = truncate_str(255, message.as_bytes());
         unsafe {
-            std::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
+            core::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
             buffer.as_mut_ptr().cast::<u8>().add(str.len()).write(b'\0');
             let res = qjs::JS_ThrowReferenceError(
                 ctx.as_ptr(),
@@ -171,10 +283,10 @@ impl<'js> Exception<'js> {
         // generate C string inline.
         // QuickJS implementation doesn't allow error strings longer then 256 anyway so truncating
         // here is fine.
-        let mut buffer = std::mem::MaybeUninit::<[u8; 256]>::uninit();
+        let mut buffer = core::mem::MaybeUninit::<[u8; 256]>::uninit();
         let str = truncate_str(255, message.as_bytes());
         unsafe {
-            std::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
+            core::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
             buffer.as_mut_ptr().cast::<u8>().add(str.len()).write(b'\0');
             let res = qjs::JS_ThrowRangeError(
                 ctx.as_ptr(),
@@ -191,10 +303,10 @@ impl<'js> Exception<'js> {
         // generate C string inline.
         // QuickJS implementation doesn't allow error strings longer then 256 anyway so truncating
         // here is fine.
-        let mut buffer = std::mem::MaybeUninit::<[u8; 256]>::uninit();
+        let mut buffer = core::mem::MaybeUninit::<[u8; 256]>::uninit();
         let str = truncate_str(255, message.as_bytes());
         unsafe {
-            std::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
+            core::ptr::copy_nonoverlapping(message.as_ptr(), buffer.as_mut_ptr().cast(), str.len());
             buffer.as_mut_ptr().cast::<u8>().add(str.len()).write(b'\0');
             let res = qjs::JS_ThrowInternalError(
                 ctx.as_ptr(),
@@ -227,3 +339,64 @@ impl fmt::Display for Exception<'_> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ErrorPosition;
+    use crate::{Context, Runtime};
+
+    #[test]
+    fn parse_frames() {
+        assert_eq!(
+            ErrorPosition::from_frame("    at f (dir/my:script.js:12:5)"),
+            Some(ErrorPosition {
+                file: "dir/my:script.js".into(),
+                line: 12,
+                column: 5
+            })
+        );
+        assert_eq!(
+            ErrorPosition::from_frame("    at main.js:1:3")
+                .unwrap()
+                .to_string(),
+            "main.js:1:3"
+        );
+        assert_eq!(
+            ErrorPosition::from_frame("    at f (dir/copy (1).js:2:4)"),
+            Some(ErrorPosition {
+                file: "dir/copy (1).js".into(),
+                line: 2,
+                column: 4
+            })
+        );
+        assert_eq!(
+            ErrorPosition::from_frame("    at <anonymous> (f(x).js:7:1)")
+                .unwrap()
+                .file,
+            "f(x).js"
+        );
+        assert_eq!(ErrorPosition::from_frame("    at push (native)"), None);
+    }
+
+    #[test]
+    fn runtime_error_position() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let source = "\n\n\tthrow new Error('oops')";
+            let _ = ctx.eval::<(), _>(source);
+            let error = ctx.catch().into_exception().unwrap();
+            let position = error.position().unwrap();
+            assert_eq!(position.file, "eval_script");
+            assert_eq!((position.line, position.column), (3, 8));
+            assert_eq!(
+                error.excerpt(source).unwrap(),
+                "3 | \tthrow new Error('oops')\n  | \t      ^"
+            );
+            assert_eq!(error.excerpt("too short"), None);
+
+            let error = crate::Exception::from_message(ctx.clone(), "native").unwrap();
+            assert_eq!(error.position(), None);
+        });
+    }
+}
@@ -6,19 +6,25 @@ use crate::{
     function::ffi::RustFunc,
     qjs, Ctx, Error, FromJs, IntoJs, Object, Result, Value,
 };
+use alloc::{borrow::ToOwned, boxed::Box};
 
 mod args;
+mod cached;
 mod ffi;
 mod into_func;
 mod params;
 mod types;
 
 pub use args::{Args, IntoArg, IntoArgs};
+pub use cached::CachedCall;
 pub use ffi::RustFunction;
 pub use params::{FromParam, FromParams, ParamRequirement, Params, ParamsAccessor};
 #[cfg(feature = "futures")]
 pub use types::Async;
-pub use types::{Exhaustive, Flat, Func, FuncArg, MutFn, Null, OnceFn, Opt, Rest, This};
+pub use types::{
+    Exhaustive, Flat, Func, FuncArg, MultiReturn, MutFn, Named, NewTarget, Null, OnceFn, Opt, Rest,
+    This,
+};
 
 /// A trait for converting a Rust function to a JavaScript function.
 pub trait IntoJsFunc<'js, P> {
@@ -117,6 +123,26 @@ impl<'js> Function<'js> {
         Ok(())
     }
 
+    /// Returns the `name` of this function used to label tracing spans.
+    ///
+    /// Only an own data property is read so no getters are invoked, failures yield an empty name.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn trace_name(&self) -> crate::StdString {
+        let key = crate::Atom::from_predefined(self.0.ctx.clone(), PredefinedAtom::Name);
+        match self.0.get_own_property(&key) {
+            Ok(Some(desc)) if !desc.is_accessor() => desc
+                .value
+                .as_string()
+                .and_then(|name| name.to_string().ok())
+                .unwrap_or_default(),
+            Ok(_) => crate::StdString::new(),
+            Err(_) => {
+                self.0.ctx.catch();
+                crate::StdString::new()
+            }
+        }
+    }
+
     /// Set the `name` property of this function and then return self.
     pub fn with_name<S: AsRef<str>>(self, name: S) -> Result<Self> {
         self.set_name(name)?;
@@ -312,6 +338,62 @@ mod test {
         })
     }
 
+    #[test]
+    fn multi_return() {
+        test_with(|ctx| {
+            let f = Function::new(ctx.clone(), |x: i32| {
+                MultiReturn::new((x, x.to_string(), x > 0))
+            })
+            .unwrap();
+            ctx.globals().set("f", f).unwrap();
+            let res: StdString = ctx
+                .eval("const [n, s, b] = f(3); `${typeof n} ${typeof s} ${b}`")
+                .unwrap();
+            assert_eq!(res, "number string true");
+
+            let named = MultiReturn::named((1, 2), &["x", "y"]);
+            let object: Object = named.into_js(&ctx).unwrap().into_object().unwrap();
+            assert_eq!(object.get::<_, i32>("y").unwrap(), 2);
+            assert!(MultiReturn::named((1, 2), &["x"]).into_js(&ctx).is_err());
+        })
+    }
+
+    #[test]
+    fn named_options() {
+        #[derive(Default)]
+        struct Options {
+            scale: i32,
+        }
+
+        impl<'js> FromJs<'js> for Options {
+            fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                let object = Object::from_js(ctx, value)?;
+                Ok(Options {
+                    scale: object.get::<_, Option<_>>("scale")?.unwrap_or(1),
+                })
+            }
+        }
+
+        test_with(|ctx| {
+            let f = Function::new(ctx.clone(), |x: i32, Named(opts): Named<Options>| {
+                x * opts.scale
+            })
+            .unwrap();
+            assert_eq!(f.get::<_, i32>("length").unwrap(), 1);
+            ctx.globals().set("f", f.clone()).unwrap();
+            let res: Vec<i32> = ctx
+                .eval("[f(2), f(2, undefined), f(2, {}), f(2, { scale: 3 })]")
+                .unwrap();
+            assert_eq!(res, [0, 0, 2, 6]);
+            assert!(ctx.eval::<i32, _>("f(2, 3)").is_err());
+
+            let res: i32 = f
+                .call((4, Named(Object::new(ctx.clone()).unwrap())))
+                .unwrap();
+            assert_eq!(res, 4);
+        })
+    }
+
     #[test]
     fn call_js_fn_with_no_args_and_return() {
         test_with(|ctx| {
@@ -338,6 +420,47 @@ mod test {
         })
     }
 
+    #[test]
+    fn call_with_16_args() {
+        test_with(|ctx| {
+            let f: Function = ctx
+                .eval("(...args) => args.reduce((a, b) => a + b, 0)")
+                .unwrap();
+            let res: i32 = f
+                .call((1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16))
+                .unwrap();
+            assert_eq!(res, 136);
+
+            #[allow(clippy::too_many_arguments)]
+            fn sum(
+                a: i32,
+                b: i32,
+                c: i32,
+                d: i32,
+                e: i32,
+                f: i32,
+                g: i32,
+                h: i32,
+                i: i32,
+                j: i32,
+                k: i32,
+                l: i32,
+                m: i32,
+                n: i32,
+                o: i32,
+                p: i32,
+            ) -> i32 {
+                a + b + c + d + e + f + g + h + i + j + k + l + m + n + o + p
+            }
+            let sum = Function::new(ctx.clone(), sum).unwrap();
+            ctx.globals().set("sum", sum).unwrap();
+            let res: i32 = ctx
+                .eval("sum(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16)")
+                .unwrap();
+            assert_eq!(res, 136);
+        })
+    }
+
     #[test]
     fn call_js_fn_with_2_args_and_return() {
         test_with(|ctx| {
@@ -536,7 +659,7 @@ mod test {
 
     #[test]
     #[should_panic(
-        expected = "Error borrowing function: can't borrow a value as it is already borrowed"
+        expected = "Function already borrowed: a `MutFn` can't be called again while it is running"
     )]
     fn recursively_called_mutable_callback() {
         test_with(|ctx| {
@@ -561,9 +684,7 @@ mod test {
     }
 
     #[test]
-    #[should_panic(
-        expected = "Error borrowing function: tried to use a value, which can only be used once, again."
-    )]
+    #[should_panic(expected = "Function already consumed: a `OnceFn` can only be called once")]
     fn repeatedly_called_once_callback() {
         test_with(|ctx| {
             let mut v = 0;
@@ -581,6 +702,64 @@ mod test {
         })
     }
 
+    #[test]
+    fn borrow_errors_are_type_errors() {
+        test_with(|ctx| {
+            let once = Function::new(ctx.clone(), OnceFn::from(|| 1)).unwrap();
+            ctx.globals().set("once", once).unwrap();
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                    once();
+                    try { once(); } catch (e) { `${e instanceof TypeError}: ${e.message}` }
+                "#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                "true: Function already consumed: a `OnceFn` can only be called once"
+            );
+        })
+    }
+
+    #[test]
+    fn argument_errors_report_index() {
+        test_with(|ctx| {
+            let f = Function::new(
+                ctx.clone(),
+                |_: i32, _: StdString, _: Opt<bool>, _: Rest<i32>| {},
+            )
+            .unwrap();
+            ctx.globals().set("f", f).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const error = (f) => { try { f() } catch (e) { return `${e.name}: ${e.message}` } };
+                    [
+                        error(() => f(1, 2)),
+                        error(() => f("a", "b")),
+                        error(() => f(1, "b", "c")),
+                        error(() => f(1, "b", true, 4, {})),
+                        error(() => f(true, "b")),
+                        error(() => f(1.5, "b", 1)),
+                    ]
+                "#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "TypeError: argument 2: expected string, got number",
+                    "TypeError: argument 1: expected i32, got string",
+                    "TypeError: argument 3: expected bool, got string",
+                    "TypeError: argument 5: expected i32, got object",
+                    "TypeError: argument 1: expected i32, got boolean",
+                    "TypeError: argument 3: expected bool, got number",
+                ]
+            );
+        })
+    }
+
     #[test]
     fn multiple_const_callbacks() {
         test_with(|ctx| {
@@ -821,4 +1000,77 @@ mod test {
             assert_eq!(n, 3);
         });
     }
+
+    #[test]
+    fn new_target() {
+        fn foo<'js>(
+            ctx: Ctx<'js>,
+            target: NewTarget<Function<'js>>,
+            func: FuncArg<Function<'js>>,
+        ) -> Result<Object<'js>> {
+            let obj = Object::new(ctx)?;
+            obj.set("constructed", target.is_some())?;
+            obj.set("same", target.0.map(|t| t == func.0))?;
+            Ok(obj)
+        }
+
+        test_with(|ctx| {
+            let func = Function::new(ctx.clone(), foo)
+                .unwrap()
+                .with_constructor(true);
+            ctx.globals().set("Foo", func).unwrap();
+
+            let res: Vec<Option<bool>> = ctx
+                .eval(
+                    r#"
+                    const a = new Foo();
+                    const b = Foo();
+                    Foo.prototype = {};
+                    class Bar extends Foo {}
+                    const c = new Bar();
+                    [a.constructed, a.same, b.constructed, b.same, c.constructed, c.same]
+                "#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    Some(true),
+                    Some(true),
+                    Some(false),
+                    None,
+                    Some(true),
+                    Some(false)
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn call_with_args() {
+        use crate::function::Args;
+
+        test_with(|ctx| {
+            let func: Function = ctx
+                .eval("(function(...args) { return [this, ...args].join(',') })")
+                .unwrap();
+
+            // More arguments than reserved are moved from the stack to the heap.
+            let mut args = Args::new(ctx.clone(), 2);
+            args.this("this").unwrap();
+            for i in 0..6 {
+                args.push_arg(i).unwrap();
+            }
+            let values: Vec<Value> = ctx.eval("[true, 'x']").unwrap();
+            args.push_args(values).unwrap();
+            assert_eq!(args.num_args(), 8);
+
+            let res: StdString = func.call(args).unwrap();
+            assert_eq!(res, "this,0,1,2,3,4,5,true,x");
+
+            let args = Args::new(ctx.clone(), 0);
+            let res: StdString = func.call(args).unwrap();
+            assert_eq!(res, "");
+        });
+    }
 }
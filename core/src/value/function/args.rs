@@ -1,7 +1,8 @@
 use crate::{
-    function::{Flat, Opt, Rest, This},
+    function::{Flat, Named, Opt, Rest, This},
     qjs, Ctx, FromJs, Function, IntoJs, Result, Value,
 };
+use alloc::vec::Vec;
 
 use super::{ffi::defer_call_job, Constructor};
 
@@ -17,7 +18,29 @@ pub enum ArgsSlice {
 
 /// Argument input for a functions
 ///
-/// Arguments on the Rust side for calling into the JavaScript context.
+/// Arguments on the Rust side for calling into the JavaScript context. Unlike a tuple of
+/// arguments the list can be built incrementally, for argument lists of which the length or
+/// the types are only known at runtime. Args can be passed to any function which accepts
+/// [`IntoArgs`].
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, Value, function::Args};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let func: Function = ctx.eval("(function(...args) { return this.prefix + args.join(',') })").unwrap();
+/// let rest: Vec<Value> = ctx.eval("[3, 'four', null]").unwrap();
+///
+/// let mut args = Args::new_unsized(ctx.clone());
+/// args.this(ctx.eval::<Value, _>("({ prefix: '>' })").unwrap()).unwrap();
+/// args.push_arg(1).unwrap();
+/// args.push_arg("two").unwrap();
+/// args.push_args(rest).unwrap();
+///
+/// let res: String = func.call(args).unwrap();
+/// assert_eq!(res, ">1,two,3,four,");
+/// # });
+/// ```
 pub struct Args<'js> {
     ctx: Ctx<'js>,
     pub(crate) this: qjs::JSValue,
@@ -50,6 +73,25 @@ impl<'js> Args<'js> {
         }
     }
 
+    /// Returns a new args which pushes its arguments into the given buffer.
+    pub(crate) fn with_buffer(ctx: Ctx<'js>, buffer: Vec<qjs::JSValue>) -> Args<'js> {
+        debug_assert!(buffer.is_empty());
+        Args {
+            ctx,
+            this: qjs::JS_UNDEFINED,
+            args: ArgsSlice::Heap(buffer),
+        }
+    }
+
+    /// Free all arguments and return the emptied buffer so it can be used again.
+    pub(crate) fn into_buffer(mut self) -> Vec<qjs::JSValue> {
+        let mut buffer = self.take_raw();
+        for v in buffer.drain(..) {
+            unsafe { qjs::JS_FreeValue(self.ctx.as_ptr(), v) };
+        }
+        buffer
+    }
+
     /// Returns the context associated with these arguments.
     pub fn ctx(&self) -> &Ctx<'js> {
         &self.ctx
@@ -58,22 +100,38 @@ impl<'js> Args<'js> {
     /// Add an argument to the list.
     pub fn push_arg<T: IntoJs<'js>>(&mut self, arg: T) -> Result<()> {
         let v = arg.into_js(&self.ctx)?;
+        self.push_raw(v.into_js_value());
+        Ok(())
+    }
 
+    /// Add an owned raw value to the list, moving the arguments to the heap once the stack
+    /// space is used up.
+    fn push_raw(&mut self, value: qjs::JSValue) {
         match self.args {
             ArgsSlice::Stack {
                 ref mut slice,
                 ref mut offset,
             } => {
-                if *offset >= 8 {
-                    panic!("pushed more arguments than num_args returned");
+                if (*offset as usize) < ARGS_ON_STACK {
+                    slice[*offset as usize] = value;
+                    *offset += 1;
+                    return;
                 }
-                slice[*offset as usize] = v.into_js_value();
-                *offset += 1;
+                let mut heap = Vec::with_capacity(ARGS_ON_STACK * 2);
+                heap.extend_from_slice(slice);
+                heap.push(value);
+                self.args = ArgsSlice::Heap(heap);
             }
-            ArgsSlice::Heap(ref mut h) => h.push(v.into_js_value()),
+            ArgsSlice::Heap(ref mut h) => h.push(value),
         }
+    }
 
-        Ok(())
+    /// Remove all arguments from the list, returning the owned raw values.
+    fn take_raw(&mut self) -> Vec<qjs::JSValue> {
+        match core::mem::replace(&mut self.args, ArgsSlice::Heap(Vec::new())) {
+            ArgsSlice::Stack { slice, offset } => slice[..offset as usize].to_vec(),
+            ArgsSlice::Heap(h) => h,
+        }
     }
 
     /// Add multiple arguments to the list.
@@ -94,18 +152,15 @@ impl<'js> Args<'js> {
         T: IntoJs<'js>,
     {
         let v = this.into_js(&self.ctx)?;
-        let v = std::mem::replace(&mut self.this, v.into_js_value());
+        let v = core::mem::replace(&mut self.this, v.into_js_value());
         unsafe { qjs::JS_FreeValue(self.ctx.as_ptr(), v) };
         Ok(())
     }
 
     /// Replace the this value with 'Undefined' and return the original value.
     pub fn take_this(&mut self) -> Value<'js> {
-        let value = std::mem::replace(&mut self.this, qjs::JS_UNDEFINED);
-        Value {
-            ctx: self.ctx().clone(),
-            value,
-        }
+        let value = core::mem::replace(&mut self.this, qjs::JS_UNDEFINED);
+        unsafe { Value::from_js_value(self.ctx().clone(), value) }
     }
 
     /// The number of arguments currently in the list.
@@ -128,7 +183,14 @@ impl<'js> Args<'js> {
     where
         R: FromJs<'js>,
     {
-        let val = unsafe {
+        let val = self.call_raw(func)?;
+        R::from_js(&self.ctx, val)
+    }
+
+    /// Call a function with the current set of arguments without consuming them.
+    pub(crate) fn call_raw(&self, func: &Function<'js>) -> Result<Value<'js>> {
+        trace_span!("call_js", function = %func.trace_name());
+        unsafe {
             let val = qjs::JS_Call(
                 self.ctx.as_ptr(),
                 func.as_js_value(),
@@ -137,9 +199,8 @@ impl<'js> Args<'js> {
                 self.as_ptr() as _,
             );
             let val = self.ctx.handle_exception(val)?;
-            Value::from_js_value(self.ctx.clone(), val)
-        };
-        R::from_js(&self.ctx, val)
+            Ok(Value::from_js_value(self.ctx.clone(), val))
+        }
     }
 
     pub fn defer(mut self, func: Function<'js>) -> Result<()> {
@@ -165,6 +226,7 @@ impl<'js> Args<'js> {
     where
         R: FromJs<'js>,
     {
+        trace_span!("construct_js", function = %constructor.trace_name());
         let value = if unsafe { qjs::JS_VALUE_GET_TAG(self.this) != qjs::JS_TAG_UNDEFINED } {
             unsafe {
                 qjs::JS_CallConstructor2(
@@ -253,6 +315,22 @@ pub trait IntoArgs<'js> {
     }
 }
 
+impl<'js> IntoArgs<'js> for Args<'js> {
+    fn num_args(&self) -> usize {
+        self.len()
+    }
+
+    fn into_args(mut self, args: &mut Args<'js>) -> Result<()> {
+        if unsafe { qjs::JS_VALUE_GET_TAG(self.this) } != qjs::JS_TAG_UNDEFINED {
+            args.this(self.take_this())?;
+        }
+        for value in self.take_raw() {
+            args.push_raw(value);
+        }
+        Ok(())
+    }
+}
+
 impl<'js, T: IntoJs<'js>> IntoArg<'js> for T {
     fn num_args(&self) -> usize {
         1
@@ -286,6 +364,16 @@ impl<'js, T: IntoJs<'js>> IntoArg<'js> for Opt<T> {
     }
 }
 
+impl<'js, T: IntoJs<'js>> IntoArg<'js> for Named<T> {
+    fn num_args(&self) -> usize {
+        1
+    }
+
+    fn into_arg(self, args: &mut Args<'js>) -> Result<()> {
+        args.push_arg(self.0)
+    }
+}
+
 impl<'js, T: IntoJs<'js>> IntoArg<'js> for Rest<T> {
     fn num_args(&self) -> usize {
         self.0.len()
@@ -335,3 +423,12 @@ impl_into_args!(A, B, C, D);
 impl_into_args!(A, B, C, D, E);
 impl_into_args!(A, B, C, D, E, F);
 impl_into_args!(A, B, C, D, E, F, G);
+impl_into_args!(A, B, C, D, E, F, G, H);
+impl_into_args!(A, B, C, D, E, F, G, H, I);
+impl_into_args!(A, B, C, D, E, F, G, H, I, J);
+impl_into_args!(A, B, C, D, E, F, G, H, I, J, K);
+impl_into_args!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_into_args!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_into_args!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_into_args!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_into_args!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
@@ -0,0 +1,129 @@
+use crate::{qjs, Atom, Ctx, FromJs, Function, IntoAtom, IntoJs, Object, Result, Value};
+use alloc::vec::Vec;
+
+use super::{Args, IntoArgs};
+
+/// A function prepared for being called many times from Rust.
+///
+/// The function, and for methods the object it is called on, are resolved once when the
+/// `CachedCall` is created. The buffer holding the converted arguments is kept between calls, so
+/// repeated calls don't look up any properties and don't allocate once the buffer has grown to
+/// the number of arguments.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Object, function::CachedCall};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let counter: Object = ctx.eval("({ total: 0, add(a, b) { return this.total += a * b } })").unwrap();
+/// let mut add = CachedCall::method(counter, "add").unwrap();
+/// for i in 0..1000 {
+///     add.call::<_, ()>((i, 2)).unwrap();
+/// }
+/// assert_eq!(add.call::<_, i32>((0, 0)).unwrap(), 999_000);
+/// # });
+/// ```
+pub struct CachedCall<'js> {
+    function: Function<'js>,
+    this: Value<'js>,
+    buffer: Vec<qjs::JSValue>,
+}
+
+impl<'js> CachedCall<'js> {
+    /// Prepare calls to the function with `this` set to undefined.
+    pub fn new(function: Function<'js>) -> Self {
+        let this = Value::new_undefined(function.ctx().clone());
+        CachedCall {
+            function,
+            this,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Prepare calls to the method with the given name of an object.
+    ///
+    /// The method is looked up only once, later changes to the property are not observed.
+    pub fn method<K: IntoAtom<'js>>(object: Object<'js>, name: K) -> Result<Self> {
+        let name: Atom = name.into_atom(object.ctx())?;
+        let function: Function = object.get(name)?;
+        Ok(CachedCall {
+            function,
+            this: object.into_value(),
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Set the value used as `this` for the calls.
+    pub fn with_this<T: IntoJs<'js>>(mut self, this: T) -> Result<Self> {
+        self.this = this.into_js(self.function.ctx())?;
+        Ok(self)
+    }
+
+    /// Reserve space for the given number of arguments up front.
+    pub fn with_capacity(mut self, args: usize) -> Self {
+        self.buffer.reserve(args);
+        self
+    }
+
+    /// Returns the context of the function.
+    pub fn ctx(&self) -> &Ctx<'js> {
+        self.function.ctx()
+    }
+
+    /// Returns the function which is called.
+    pub fn function(&self) -> &Function<'js> {
+        &self.function
+    }
+
+    /// Returns the value used as `this` for the calls.
+    pub fn this(&self) -> &Value<'js> {
+        &self.this
+    }
+
+    /// Call the function with the given arguments.
+    pub fn call<A, R>(&mut self, args: A) -> Result<R>
+    where
+        A: IntoArgs<'js>,
+        R: FromJs<'js>,
+    {
+        let ctx = self.function.ctx();
+        let mut accum_args = Args::with_buffer(ctx.clone(), core::mem::take(&mut self.buffer));
+        accum_args.this = unsafe { qjs::JS_DupValue(ctx.as_ptr(), self.this.as_js_value()) };
+        let res = args
+            .into_args(&mut accum_args)
+            .and_then(|_| accum_args.call_raw(&self.function));
+        self.buffer = accum_args.into_buffer();
+        R::from_js(ctx, res?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{function::CachedCall, *};
+
+    #[test]
+    fn cached_call() {
+        test_with(|ctx| {
+            let func: Function = ctx
+                .eval("(function(...args) { return [this, ...args].join() })")
+                .unwrap();
+            let mut call = CachedCall::new(func).with_this("x").unwrap();
+            let res: StdString = call.call((1, 2)).unwrap();
+            assert_eq!(res, "x,1,2");
+            let res: StdString = call.call((1, 2, 3, 4, 5, 6)).unwrap();
+            assert_eq!(res, "x,1,2,3,4,5,6");
+            let res: StdString = call.call(()).unwrap();
+            assert_eq!(res, "x");
+
+            let object: Object = ctx
+                .eval("({ n: 1, inc(a) { if (a < 0) throw new Error('negative'); return this.n += a } })")
+                .unwrap();
+            let mut inc = CachedCall::method(object.clone(), "inc").unwrap();
+            assert_eq!(inc.call::<_, i32>((2,)).unwrap(), 3);
+            assert!(inc.call::<_, i32>((-1,)).is_err());
+            ctx.catch();
+            assert_eq!(inc.call::<_, i32>((4,)).unwrap(), 7);
+            assert_eq!(object.get::<_, i32>("n").unwrap(), 7);
+        })
+    }
+}
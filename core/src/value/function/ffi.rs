@@ -4,6 +4,7 @@ use crate::{
     value::function::Params,
     Ctx, Function, JsLifetime, Object, Result, Value,
 };
+use alloc::boxed::Box;
 
 use super::Constructor;
 
@@ -8,7 +8,7 @@ use crate::{
 #[cfg(feature = "futures")]
 use crate::{function::types::Async, promise::Promised};
 #[cfg(feature = "futures")]
-use std::future::Future;
+use core::future::Future;
 
 macro_rules! impl_to_js_function {
     ($($t:ident),*$(,)?) => {
@@ -154,3 +154,11 @@ impl_to_js_function!(A, B, C, D, E);
 impl_to_js_function!(A, B, C, D, E, F);
 impl_to_js_function!(A, B, C, D, E, F, G);
 impl_to_js_function!(A, B, C, D, E, F, G, H);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I, J);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I, J, K);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_to_js_function!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
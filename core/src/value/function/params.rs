@@ -1,8 +1,9 @@
 use crate::{
-    function::{Exhaustive, Flat, FuncArg, Opt, Rest, This},
+    function::{Exhaustive, Flat, FuncArg, Named, NewTarget, Opt, Rest, This},
     qjs, Ctx, FromJs, Result, Value,
 };
-use std::slice;
+use alloc::vec::Vec;
+use core::slice;
 
 /// A struct which contains the values a callback is called with.
 ///
@@ -23,7 +24,7 @@ impl<'a, 'js> Params<'a, 'js> {
         this: qjs::JSValue,
         argc: qjs::c_int,
         argv: *mut qjs::JSValue,
-        _flags: qjs::c_int,
+        flags: qjs::c_int,
     ) -> Self {
         let args = if argv.is_null() {
             assert_eq!(
@@ -41,7 +42,7 @@ impl<'a, 'js> Params<'a, 'js> {
             function,
             this,
             args,
-            is_constructor: false,
+            is_constructor: flags & qjs::JS_CALL_FLAG_CONSTRUCTOR as qjs::c_int != 0,
         }
     }
 
@@ -101,6 +102,12 @@ impl<'a, 'js> Params<'a, 'js> {
         self.is_constructor
     }
 
+    /// Returns the `new.target` of the call, `None` if the function isn't called as a
+    /// constructor.
+    pub fn new_target(&self) -> Option<Value<'js>> {
+        self.is_constructor.then(|| self.this())
+    }
+
     /// Turns the params into an accessor object for extracting the arguments.
     pub fn access(self) -> ParamsAccessor<'a, 'js> {
         ParamsAccessor {
@@ -132,6 +139,12 @@ impl<'a, 'js> ParamsAccessor<'a, 'js> {
         self.params.function()
     }
 
+    /// Returns the `new.target` of the call, `None` if the function isn't called as a
+    /// constructor.
+    pub fn new_target(&self) -> Option<Value<'js>> {
+        self.params.new_target()
+    }
+
     /// Returns the next arguments.
     ///
     /// Each call to this function returns a different argument
@@ -149,6 +162,13 @@ impl<'a, 'js> ParamsAccessor<'a, 'js> {
         unsafe { Value::from_js_value_const(self.params.ctx.clone(), res) }
     }
 
+    /// Converts the next argument, reporting its index if the conversion fails.
+    fn arg_from_js<T: FromJs<'js>>(&mut self) -> Result<T> {
+        let index = self.offset;
+        let value = self.arg();
+        T::from_js(self.ctx(), value).map_err(|error| error.with_arg_index(index))
+    }
+
     /// returns the number of arguments remaining
     pub fn len(&self) -> usize {
         self.params.args.len() - self.offset
@@ -255,8 +275,7 @@ impl<'js, T: FromJs<'js>> FromParam<'js> for T {
     }
 
     fn from_param<'a>(params: &mut ParamsAccessor<'a, 'js>) -> Result<Self> {
-        let ctx = params.ctx().clone();
-        T::from_js(&ctx, params.arg())
+        params.arg_from_js()
     }
 }
 
@@ -277,14 +296,33 @@ impl<'js, T: FromJs<'js>> FromParam<'js> for Opt<T> {
 
     fn from_param<'a>(params: &mut ParamsAccessor<'a, 'js>) -> Result<Self> {
         if !params.is_empty() {
-            let ctx = params.ctx().clone();
-            Ok(Opt(Some(T::from_js(&ctx, params.arg())?)))
+            Ok(Opt(Some(params.arg_from_js()?)))
         } else {
             Ok(Opt(None))
         }
     }
 }
 
+impl<'js, T: FromJs<'js> + Default> FromParam<'js> for Named<T> {
+    fn param_requirement() -> ParamRequirement {
+        ParamRequirement::optional()
+    }
+
+    fn from_param<'a>(params: &mut ParamsAccessor<'a, 'js>) -> Result<Self> {
+        if params.is_empty() {
+            return Ok(Named(T::default()));
+        }
+        let index = params.offset;
+        let value = params.arg();
+        if value.is_undefined() || value.is_null() {
+            return Ok(Named(T::default()));
+        }
+        T::from_js(params.ctx(), value)
+            .map(Named)
+            .map_err(|error| error.with_arg_index(index))
+    }
+}
+
 impl<'js, T: FromJs<'js>> FromParam<'js> for This<T> {
     fn param_requirement() -> ParamRequirement {
         ParamRequirement::any()
@@ -305,6 +343,20 @@ impl<'js, T: FromJs<'js>> FromParam<'js> for FuncArg<T> {
     }
 }
 
+impl<'js, T: FromJs<'js>> FromParam<'js> for NewTarget<T> {
+    fn param_requirement() -> ParamRequirement {
+        ParamRequirement::any()
+    }
+
+    fn from_param<'a>(params: &mut ParamsAccessor<'a, 'js>) -> Result<Self> {
+        params
+            .new_target()
+            .map(|target| T::from_js(params.ctx(), target))
+            .transpose()
+            .map(NewTarget)
+    }
+}
+
 impl<'js, T: FromJs<'js>> FromParam<'js> for Rest<T> {
     fn param_requirement() -> ParamRequirement {
         ParamRequirement::any()
@@ -313,8 +365,7 @@ impl<'js, T: FromJs<'js>> FromParam<'js> for Rest<T> {
     fn from_param<'a>(params: &mut ParamsAccessor<'a, 'js>) -> Result<Self> {
         let mut res = Vec::with_capacity(params.len());
         for _ in 0..params.len() {
-            let p = params.arg();
-            res.push(T::from_js(params.ctx(), p)?);
+            res.push(params.arg_from_js()?);
         }
         Ok(Rest(res))
     }
@@ -379,3 +430,12 @@ impl_from_params!(A, B, C, D);
 impl_from_params!(A, B, C, D, E);
 impl_from_params!(A, B, C, D, E, F);
 impl_from_params!(A, B, C, D, E, F, G);
+impl_from_params!(A, B, C, D, E, F, G, H);
+impl_from_params!(A, B, C, D, E, F, G, H, I);
+impl_from_params!(A, B, C, D, E, F, G, H, I, J);
+impl_from_params!(A, B, C, D, E, F, G, H, I, J, K);
+impl_from_params!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_from_params!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_from_params!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_from_params!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_from_params!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
@@ -1,20 +1,42 @@
-use std::{
+use alloc::vec::Vec;
+use core::{
     cell::{Cell, RefCell},
     marker::PhantomData,
     ops::{Deref, DerefMut},
 };
 
-use crate::{Ctx, Function, IntoJs, Result, Value};
+use crate::{convert::List, Array, Ctx, Error, Function, IntoJs, Object, Result, Value};
 
 use super::IntoJsFunc;
 
 /// Helper type to implement [`IntoJsFunc`] for closure by constraining arguments.
+///
+/// Wrapping a closure in `Func` makes it [`IntoJs`], so it can be used anywhere a value is
+/// expected, like an object property, a module export or an argument of a call, without creating
+/// a [`Function`] first.
+///
+/// Closures can't implement [`IntoJs`] themselves as a closure can implement [`IntoJsFunc`] for
+/// more than one set of parameters, the `P` type parameter of `Func` selects the set.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, prelude::Func};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// ctx.globals().set("double", Func::from(|x: i32| x * 2)).unwrap();
+///
+/// let apply: Function = ctx.eval("(f, x) => f(x) + double(x)").unwrap();
+/// let res: i32 = apply.call((Func::from(|x: i32| x + 1), 10)).unwrap();
+/// assert_eq!(res, 31);
+/// # });
+/// ```
 pub struct Func<T, P>(T, PhantomData<P>);
 
 impl<'js, T, P> Func<T, P>
 where
     T: IntoJsFunc<'js, P>,
 {
+    /// Wrap a closure.
     pub fn new(t: T) -> Self {
         Func(t, PhantomData)
     }
@@ -45,12 +67,61 @@ pub struct This<T>(pub T);
 /// helper type for retrieving function object on which a function is called..
 pub struct FuncArg<T>(pub T);
 
+/// helper type for retrieving the `new.target` of a call.
+///
+/// Contains `None` if the function isn't called as a constructor, which allows Rust functions to
+/// distinguish `new Foo()` from `Foo()`.
+pub struct NewTarget<T>(pub Option<T>);
+
 /// Helper type for optional parameters.
 pub struct Opt<T>(pub Option<T>);
 
 /// Helper type for rest and spread arguments.
 pub struct Rest<T>(pub Vec<T>);
 
+/// Helper type for an options object passed as the last argument.
+///
+/// Many JavaScript APIs take their optional settings as an object in the last argument, like
+/// `f(x, { indent: 2 })`. `Named` converts such an object into a Rust type, which uses the
+/// [`Default`] value when the argument is missing, `undefined` or `null`. Fields missing from the
+/// object can fall back to their defaults in the [`FromJs`](crate::FromJs) implementation of the
+/// type, which the `FromJs` derive macro of the `macro` feature generates for fields marked with
+/// `#[qjs(default)]`.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Ctx, Object, FromJs, Value, Result, function::{Func, Named}};
+/// #[derive(Default)]
+/// struct Options {
+///     indent: usize,
+///     prefix: String,
+/// }
+///
+/// impl<'js> FromJs<'js> for Options {
+///     fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+///         let object = Object::from_js(ctx, value)?;
+///         let defaults = Options::default();
+///         Ok(Options {
+///             indent: object.get::<_, Option<_>>("indent")?.unwrap_or(defaults.indent),
+///             prefix: object.get::<_, Option<_>>("prefix")?.unwrap_or(defaults.prefix),
+///         })
+///     }
+/// }
+///
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let format = |text: String, Named(opts): Named<Options>| {
+///     format!("{}{}{}", opts.prefix, " ".repeat(opts.indent), text)
+/// };
+/// ctx.globals().set("format", Func::from(format)).unwrap();
+/// let res: Vec<String> = ctx
+///     .eval(r#"[format("a"), format("b", { indent: 2 }), format("c", { prefix: ">" })]"#)
+///     .unwrap();
+/// assert_eq!(res, ["a", "  b", ">c"]);
+/// # });
+/// ```
+pub struct Named<T>(pub T);
+
 /// Helper type for converting an option into null instead of undefined.
 pub struct Null<T>(pub Option<T>);
 
@@ -61,6 +132,94 @@ pub struct Null<T>(pub Option<T>);
 /// arguments recursively.
 pub struct Flat<T>(pub T);
 
+/// Helper type for returning multiple values from a function.
+///
+/// The values of the tuple are returned as an array, which can be destructured in JavaScript
+/// with `const [a, b] = f()`. With [`MultiReturn::named`] they are returned as an object with the
+/// given property names instead, for destructuring with `const { a, b } = f()`.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, function::{Func, MultiReturn}};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// ctx.globals()
+///     .set("divMod", Func::from(|a: i32, b: i32| MultiReturn::new((a / b, a % b))))
+///     .unwrap();
+/// ctx.globals()
+///     .set(
+///         "minMax",
+///         Func::from(|a: i32, b: i32| MultiReturn::named((a.min(b), a.max(b)), &["min", "max"])),
+///     )
+///     .unwrap();
+///
+/// let res: i32 = ctx
+///     .eval("const [div, rem] = divMod(7, 2); const { min, max } = minMax(3, 1); div * 1000 + rem * 100 + min * 10 + max")
+///     .unwrap();
+/// assert_eq!(res, 3113);
+/// # });
+/// ```
+pub struct MultiReturn<T> {
+    values: T,
+    names: Option<&'static [&'static str]>,
+}
+
+impl<T> MultiReturn<T> {
+    /// Return the values as an array.
+    pub fn new(values: T) -> Self {
+        MultiReturn {
+            values,
+            names: None,
+        }
+    }
+
+    /// Return the values as an object with the given property names, in the order of the values.
+    ///
+    /// Converting the values fails if the number of names doesn't match the number of values.
+    pub fn named(values: T, names: &'static [&'static str]) -> Self {
+        MultiReturn {
+            values,
+            names: Some(names),
+        }
+    }
+
+    /// Returns the values.
+    pub fn into_inner(self) -> T {
+        self.values
+    }
+}
+
+impl<T> From<T> for MultiReturn<T> {
+    fn from(values: T) -> Self {
+        MultiReturn::new(values)
+    }
+}
+
+impl<'js, T> IntoJs<'js> for MultiReturn<T>
+where
+    List<T>: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let values = List(self.values).into_js(ctx)?;
+        let Some(names) = self.names else {
+            return Ok(values);
+        };
+        let values = Array::from_value(values)?;
+        if values.len() != names.len() {
+            return Err(Error::new_into_js_message(
+                "tuple",
+                "object",
+                format!("expected {} names, got {}", values.len(), names.len()),
+            ));
+        }
+        let object = Object::new(ctx.clone())?;
+        for (idx, name) in names.iter().enumerate() {
+            object.set(*name, values.get::<Value>(idx)?)?;
+        }
+        Ok(object.into_value())
+    }
+}
+
 /// Helper type for making an parameter set exhaustive.
 pub struct Exhaustive;
 
@@ -70,8 +229,8 @@ pub struct Async<T>(pub T);
 
 /// Helper type for creating a function from a closure which implements [`FnMut`]
 ///
-/// When called will try to borrow the internal [`RefCell`], if this is not
-/// possible it will return an error.
+/// When called will try to borrow the internal [`RefCell`], if this is not possible, because the
+/// function is called recursively, it will throw a `TypeError`.
 pub struct MutFn<T>(pub RefCell<T>);
 
 impl<T> MutFn<T> {
@@ -86,10 +245,10 @@ impl<T> From<T> for MutFn<T> {
     }
 }
 
-/// Helper type for creating a function from a closure which implements [`FnMut`]
+/// Helper type for creating a function from a closure which implements [`FnOnce`]
 ///
 /// When called, will take the internal value leaving it empty. If the internal
-/// value was already empty it will return a error.
+/// value was already empty, because the function was called before, it will throw a `TypeError`.
 pub struct OnceFn<T>(pub Cell<Option<T>>);
 
 impl<T> OnceFn<T> {
@@ -182,7 +341,9 @@ type_impls! {
     OnceFn<F>(Cell<Option<F>>): AsRef Deref;
     This<T>(T): into_inner From AsRef AsMut Deref DerefMut;
     FuncArg<T>(T): into_inner From AsRef AsMut Deref DerefMut;
+    NewTarget<T>(Option<T>): into_inner From AsRef AsMut Deref DerefMut;
     Opt<T>(Option<T>): into_inner From AsRef AsMut Deref DerefMut;
     Rest<T>(Vec<T>): into_inner From AsRef AsMut Deref DerefMut;
     Flat<T>(T): into_inner From AsRef AsMut Deref DerefMut;
+    Named<T>(T): into_inner From AsRef AsMut Deref DerefMut;
 }
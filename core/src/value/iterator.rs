@@ -0,0 +1,247 @@
+//! Consuming JavaScript iterables from Rust.
+
+use crate::{
+    atom::PredefinedAtom, function::This, qjs, Ctx, Exception, FromJs, Function, IntoAtom, Object,
+    Result, Value,
+};
+use core::{iter::FusedIterator, marker::PhantomData};
+
+/// A Rust iterator which drives the JavaScript iteration protocol.
+///
+/// Created from any iterable value, like arrays, strings, maps, sets, generators or objects with a
+/// custom `Symbol.iterator` method. Every call to [`Iterator::next`] calls the `next` method of
+/// the JavaScript iterator and converts the produced value into `T`.
+///
+/// If the iterator is dropped before it is exhausted, the `return` method of the JavaScript
+/// iterator is called, just like when breaking out of a `for of` loop.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Value};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let set: Value = ctx.eval("new Set([1, 2, 3])").unwrap();
+/// let items = set
+///     .into_iterator::<i32>()
+///     .unwrap()
+///     .collect::<rquickjs::Result<Vec<_>>>()
+///     .unwrap();
+/// assert_eq!(items, [1, 2, 3]);
+/// # });
+/// ```
+pub struct JsIterator<'js, T = Value<'js>> {
+    iterator: Object<'js>,
+    next: Function<'js>,
+    done: bool,
+    marker: PhantomData<T>,
+}
+
+fn get_property<'js>(value: &Value<'js>, key: impl IntoAtom<'js>) -> Result<Value<'js>> {
+    let ctx = value.ctx();
+    let atom = key.into_atom(ctx)?;
+    // JS_GetProperty also accepts primitives, which is required for iterating strings.
+    unsafe {
+        let val = qjs::JS_GetProperty(ctx.as_ptr(), value.as_js_value(), atom.atom);
+        let val = ctx.handle_exception(val)?;
+        Ok(Value::from_js_value(ctx.clone(), val))
+    }
+}
+
+impl<'js, T> JsIterator<'js, T> {
+    /// Get an iterator from an iterable value by calling its `Symbol.iterator` method.
+    pub fn from_iterable(value: Value<'js>) -> Result<Self> {
+        let ctx = value.ctx().clone();
+        let Some(method) = get_property(&value, PredefinedAtom::SymbolIterator)?.into_function()
+        else {
+            return Err(Exception::throw_type(&ctx, "value is not iterable"));
+        };
+        let iterator: Value = method.call((This(value),))?;
+        Self::from_iterator(iterator)
+    }
+
+    /// Create from an object which already implements the iterator protocol, such as the result
+    /// of calling a generator function.
+    pub fn from_iterator(iterator: Value<'js>) -> Result<Self> {
+        let ctx = iterator.ctx().clone();
+        let Some(iterator) = iterator.into_object() else {
+            return Err(Exception::throw_type(&ctx, "iterator is not an object"));
+        };
+        let Some(next) = iterator
+            .get::<_, Value>(PredefinedAtom::Next)?
+            .into_function()
+        else {
+            return Err(Exception::throw_type(
+                &ctx,
+                "iterator.next is not a function",
+            ));
+        };
+        Ok(JsIterator {
+            iterator,
+            next,
+            done: false,
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the context the iterator belongs to.
+    pub fn ctx(&self) -> &Ctx<'js> {
+        self.iterator.ctx()
+    }
+
+    /// Returns the underlying JavaScript iterator object.
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.iterator
+    }
+
+    fn step(&mut self) -> Result<Option<Value<'js>>> {
+        let result: Value = self.next.call((This(self.iterator.clone()),))?;
+        let Some(result) = result.into_object() else {
+            return Err(Exception::throw_type(
+                self.ctx(),
+                "iterator result is not an object",
+            ));
+        };
+        if result.get::<_, bool>(PredefinedAtom::Done)? {
+            return Ok(None);
+        }
+        result.get(PredefinedAtom::Value).map(Some)
+    }
+}
+
+impl<'js, T: FromJs<'js>> Iterator for JsIterator<'js, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.step() {
+            Ok(Some(value)) => Some(T::from_js(self.iterator.ctx(), value)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                // A throwing iterator is considered done and must not be closed.
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'js, T: FromJs<'js>> FusedIterator for JsIterator<'js, T> {}
+
+impl<'js, T> Drop for JsIterator<'js, T> {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        let Ok(close) = self.iterator.get::<_, Value>(PredefinedAtom::Return) else {
+            self.ctx().catch();
+            return;
+        };
+        if let Some(close) = close.into_function() {
+            if close
+                .call::<_, Value>((This(self.iterator.clone()),))
+                .is_err()
+            {
+                self.ctx().catch();
+            }
+        }
+    }
+}
+
+impl<'js> Value<'js> {
+    /// Iterate over an iterable value using the JavaScript iteration protocol.
+    ///
+    /// See [`JsIterator`] for more details.
+    pub fn into_iterator<T: FromJs<'js>>(self) -> Result<JsIterator<'js, T>> {
+        JsIterator::from_iterable(self)
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Iterate over an iterable object using the JavaScript iteration protocol.
+    ///
+    /// Unlike [`Object::props`] this calls the `Symbol.iterator` method of the object, so it
+    /// iterates the entries of a map, the items of a set or the values produced by a generator.
+    pub fn js_iter<T: FromJs<'js>>(&self) -> Result<JsIterator<'js, T>> {
+        JsIterator::from_iterable(self.clone().into_value())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn iterate_builtins() {
+        test_with(|ctx| {
+            let map: Object = ctx.eval("new Map([['a', 1], ['b', 2]])").unwrap();
+            let entries = map
+                .js_iter::<Array>()
+                .unwrap()
+                .map(|entry| {
+                    let entry = entry?;
+                    Ok((entry.get::<StdString>(0)?, entry.get::<i32>(1)?))
+                })
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(entries, [("a".into(), 1), ("b".into(), 2)]);
+
+            let string: Value = ctx.eval("'a😀'").unwrap();
+            let chars = string
+                .into_iterator::<StdString>()
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(chars, ["a", "😀"]);
+
+            let not_iterable: Value = ctx.eval("({})").unwrap();
+            assert!(not_iterable.into_iterator::<Value>().is_err());
+            assert!(ctx.catch().is_error());
+        })
+    }
+
+    #[test]
+    fn generator_closed_on_drop() {
+        test_with(|ctx| {
+            let generator: Value = ctx
+                .eval(
+                    r#"
+                    globalThis.closed = false;
+                    (function* () {
+                        try {
+                            yield 1;
+                            yield 2;
+                            throw new Error("unreachable");
+                        } finally {
+                            closed = true;
+                        }
+                    })()
+                "#,
+                )
+                .unwrap();
+
+            let mut iter = generator.into_iterator::<i32>().unwrap();
+            assert_eq!(iter.next().unwrap().unwrap(), 1);
+            drop(iter);
+            assert!(ctx.globals().get::<_, bool>("closed").unwrap());
+        })
+    }
+
+    #[test]
+    fn throwing_iterator() {
+        test_with(|ctx| {
+            let generator: Value = ctx
+                .eval("(function* () { yield 1; throw new Error('failed'); })()")
+                .unwrap();
+            let mut iter = generator.into_iterator::<i32>().unwrap();
+            assert_eq!(iter.next().unwrap().unwrap(), 1);
+            assert!(iter.next().unwrap().is_err());
+            assert!(ctx.catch().is_error());
+            assert!(iter.next().is_none());
+        })
+    }
+}
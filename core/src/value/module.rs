@@ -1,16 +1,21 @@
 //! Types for loading and handling JS modules.
 
-use std::{
-    ffi::{CStr, CString},
+use alloc::{ffi::CString, vec::Vec};
+use core::{
+    ffi::CStr,
     marker::PhantomData,
     mem::MaybeUninit,
+    panic::AssertUnwindSafe,
     ptr::{self, NonNull},
     slice,
 };
 
 use crate::{
-    atom::PredefinedAtom, qjs, Atom, Ctx, Error, FromAtom, FromJs, IntoAtom, IntoJs, Object,
-    Promise, Result, Value,
+    atom::PredefinedAtom,
+    qjs,
+    runtime::{record_metric, MetricsCounters},
+    util, Atom, Ctx, Error, FromAtom, FromJs, IntoAtom, IntoJs, Object, Promise, Result, StdString,
+    Value,
 };
 
 /// Helper macro to provide module init function.
@@ -59,6 +64,40 @@ pub trait ModuleDef {
     }
 }
 
+/// The definition of modules which export a value created from Rust, like JSON modules.
+///
+/// The value and the names of its properties to export are stored in the `import.meta` object of
+/// the module until it is evaluated, which JavaScript can't access for a native module.
+struct ValueModule;
+
+impl ValueModule {
+    const VALUE: &'static str = "value";
+    const NAMES: &'static str = "names";
+}
+
+impl ModuleDef for ValueModule {
+    fn declare<'js>(decl: &Declarations<'js>) -> Result<()> {
+        decl.declare("default")?;
+        Ok(())
+    }
+
+    fn evaluate<'js>(_ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
+        let meta = exports.0.meta()?;
+        let value: Value = meta.get(Self::VALUE)?;
+        let names: Vec<StdString> = meta.get(Self::NAMES)?;
+        meta.remove(Self::VALUE)?;
+        meta.remove(Self::NAMES)?;
+        if let Some(object) = value.as_object() {
+            for name in names {
+                let export: Value = object.get(name.as_str())?;
+                exports.export(name, export)?;
+            }
+        }
+        exports.export("default", value)?;
+        Ok(())
+    }
+}
+
 /// A struct used for setting declarations on a module.
 pub struct Declarations<'js>(Module<'js, Declared>);
 
@@ -153,13 +192,16 @@ impl<'js, T> Module<'js, T> {
         let ptr = NonNull::new(ptr).unwrap();
         let module = unsafe { Module::from_ptr(ctx.clone(), ptr) };
         let exports = Exports(module);
-        match D::evaluate(&ctx, &exports) {
-            Ok(_) => 0,
-            Err(error) => {
-                error.throw(&ctx);
-                -1
-            }
-        }
+        ctx.catch_panic(
+            AssertUnwindSafe(|| match D::evaluate(&ctx, &exports) {
+                Ok(_) => 0,
+                Err(error) => {
+                    error.throw(&ctx);
+                    -1
+                }
+            }),
+            -1,
+        )
     }
 
     /// Returns the name of the module
@@ -185,6 +227,7 @@ impl<'js> Module<'js, Declared> {
         S: Into<Vec<u8>>,
     {
         let name = CString::new(name)?;
+        trace_span!("module_compile", module = ?name);
         let flag =
             qjs::JS_EVAL_TYPE_MODULE | qjs::JS_EVAL_FLAG_STRICT | qjs::JS_EVAL_FLAG_COMPILE_ONLY;
 
@@ -199,6 +242,42 @@ impl<'js> Module<'js, Declared> {
         unsafe { Ok(Module::from_ptr(ctx, module_ptr)) }
     }
 
+    /// Declare a JSON module but don't evaluate it.
+    ///
+    /// The source is parsed as JSON and becomes the default export of the module, like when
+    /// importing a JSON file in browsers or Node. Returns a `SyntaxError` exception if the source
+    /// isn't valid JSON.
+    pub fn declare_json<N, S>(ctx: Ctx<'js>, name: N, source: S) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+        S: Into<Vec<u8>>,
+    {
+        let value = ctx.json_parse(util::strip_bom(source.into()))?;
+        Self::declare_value(ctx, name, value, Vec::new())
+    }
+
+    /// Declare a native module which exports the value as its default export and the given
+    /// properties of the value as named exports.
+    pub(crate) fn declare_value<N>(
+        ctx: Ctx<'js>,
+        name: N,
+        value: Value<'js>,
+        names: Vec<StdString>,
+    ) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let names: Vec<_> = names.into_iter().filter(|x| x != "default").collect();
+        let decl = Declarations(Self::declare_def::<ValueModule, N>(ctx, name)?);
+        for name in &names {
+            decl.declare(name.as_str())?;
+        }
+        let meta = decl.0.meta()?;
+        meta.set(ValueModule::VALUE, value)?;
+        meta.set(ValueModule::NAMES, names)?;
+        Ok(decl.0)
+    }
+
     /// Declare a rust native module but don't evaluate it.
     pub fn declare_def<D, N>(ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
     where
@@ -233,6 +312,7 @@ impl<'js> Module<'js, Declared> {
         S: Into<Vec<u8>>,
     {
         let name = CString::new(name)?;
+        trace_span!("module_evaluate", module = ?name);
         let flag = qjs::JS_EVAL_TYPE_MODULE | qjs::JS_EVAL_FLAG_STRICT;
 
         let module_val = unsafe { ctx.eval_raw(source, name.as_c_str(), flag as i32)? };
@@ -298,10 +378,15 @@ impl<'js> Module<'js, Declared> {
     /// Returns the module as being evaluated and a promise which resolves when the module has finished evaluating.
     /// The return value of the promise is the JavaScript value undefined.
     pub fn eval(self) -> Result<(Module<'js, Evaluated>, Promise<'js>)> {
+        trace_span!(
+            "module_evaluate",
+            module = %self.name::<StdString>().unwrap_or_default()
+        );
         let ret = unsafe {
             // JS_EvalFunction `free's` the module so we should dup first
             let v = qjs::JS_MKPTR(qjs::JS_TAG_MODULE, self.ptr.as_ptr().cast());
             qjs::JS_DupValue(self.ctx.as_ptr(), v);
+            record_metric(self.ctx.as_ptr(), MetricsCounters::evaluation);
             qjs::JS_EvalFunction(self.ctx.as_ptr(), v)
         };
         let ret = unsafe { self.ctx.handle_exception(ret)? };
@@ -330,13 +415,16 @@ impl<'js> Module<'js, Declared> {
     {
         let ctx = Ctx::from_ptr(ctx);
         let name = CStr::from_ptr(name).to_bytes();
-        match Self::declare_def::<D, _>(ctx.clone(), name) {
-            Ok(module) => module.as_ptr(),
-            Err(error) => {
-                error.throw(&ctx);
-                ptr::null_mut()
-            }
-        }
+        ctx.catch_panic(
+            AssertUnwindSafe(|| match Self::declare_def::<D, _>(ctx.clone(), name) {
+                Ok(module) => module.as_ptr(),
+                Err(error) => {
+                    error.throw(&ctx);
+                    ptr::null_mut()
+                }
+            }),
+            ptr::null_mut(),
+        )
     }
 
     /// Import and evaluate a module
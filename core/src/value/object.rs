@@ -1,12 +1,15 @@
 //! Module for types dealing with JS objects.
 
 use crate::{
-    convert::FromIteratorJs, qjs, Array, Atom, Ctx, FromAtom, FromJs, IntoAtom, IntoJs, Result,
-    Value,
+    convert::FromIteratorJs, qjs, Array, Atom, Ctx, Error, FromAtom, FromJs, IntoAtom, IntoJs,
+    Result, Value,
 };
-use std::{iter::FusedIterator, marker::PhantomData, mem};
+use alloc::vec::Vec;
+use core::{iter::FusedIterator, marker::PhantomData, mem};
 
+mod builder;
 mod property;
+pub use builder::ObjectBuilder;
 pub use property::{Accessor, AsProperty, Property, PropertyFlags};
 
 /// Rust representation of a JavaScript object.
@@ -24,6 +27,21 @@ impl<'js> Object<'js> {
         })
     }
 
+    /// Create a new JavaScript object with the given prototype, the equivalent of
+    /// `Object.create(proto)`.
+    ///
+    /// If called with `None` the object will have a null prototype. Objects with properties are
+    /// built with [`ObjectBuilder`].
+    #[doc(alias = "new_with_proto")]
+    pub fn with_prototype(ctx: Ctx<'js>, proto: Option<&Object<'js>>) -> Result<Self> {
+        let proto = proto.map(|x| x.as_js_value()).unwrap_or(qjs::JS_NULL);
+        Ok(unsafe {
+            let val = qjs::JS_NewObjectProto(ctx.as_ptr(), proto);
+            let val = ctx.handle_exception(val)?;
+            Object::from_js_value(ctx, val)
+        })
+    }
+
     /// Get a new value
     pub fn get<K: IntoAtom<'js>, V: FromJs<'js>>(&self, k: K) -> Result<V> {
         let atom = k.into_atom(self.ctx())?;
@@ -34,6 +52,65 @@ impl<'js> Object<'js> {
         })
     }
 
+    /// Get a value, returning `None` if the member is missing or undefined.
+    ///
+    /// Other values, including `null`, are converted to `V` and fail like [`Object::get`] if they
+    /// can't be.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let options: Object = ctx.eval("({ retries: 3, timeout: undefined })").unwrap();
+    /// assert_eq!(options.get_optional::<_, u32>("retries").unwrap(), Some(3));
+    /// assert_eq!(options.get_optional::<_, u32>("timeout").unwrap(), None);
+    /// assert_eq!(options.get_optional::<_, u32>("missing").unwrap(), None);
+    /// assert_eq!(options.get_or("timeout", 30).unwrap(), 30);
+    /// # });
+    /// ```
+    pub fn get_optional<K: IntoAtom<'js>, V: FromJs<'js>>(&self, k: K) -> Result<Option<V>> {
+        let value: Value = self.get(k)?;
+        if value.is_undefined() {
+            return Ok(None);
+        }
+        V::from_js(self.ctx(), value).map(Some)
+    }
+
+    /// Get a value, returning `default` if the member is missing or undefined.
+    ///
+    /// See [`Object::get_optional`].
+    pub fn get_or<K: IntoAtom<'js>, V: FromJs<'js>>(&self, k: K, default: V) -> Result<V> {
+        Ok(self.get_optional(k)?.unwrap_or(default))
+    }
+
+    /// Get the value at an index of the object.
+    ///
+    /// Works on any object, not only on arrays, and doesn't convert the index to a string.
+    pub fn get_index<V: FromJs<'js>>(&self, idx: u32) -> Result<V> {
+        let ctx = self.ctx();
+        let val = unsafe {
+            let val = qjs::JS_GetPropertyUint32(ctx.as_ptr(), self.0.as_js_value(), idx);
+            let val = ctx.handle_exception(val)?;
+            Value::from_js_value(ctx.clone(), val)
+        };
+        V::from_js(ctx, val)
+    }
+
+    /// Set the value at an index of the object.
+    ///
+    /// Works on any object, not only on arrays, and doesn't convert the index to a string.
+    pub fn set_index<V: IntoJs<'js>>(&self, idx: u32, value: V) -> Result<()> {
+        let ctx = self.ctx();
+        let val = value.into_js(ctx)?.into_js_value();
+        unsafe {
+            if 0 > qjs::JS_SetPropertyUint32(ctx.as_ptr(), self.0.as_js_value(), idx, val) {
+                return Err(ctx.raise_exception());
+            }
+        }
+        Ok(())
+    }
+
     /// check whether the object contains a certain key.
     pub fn contains_key<K>(&self, k: K) -> Result<bool>
     where
@@ -67,6 +144,138 @@ impl<'js> Object<'js> {
         Ok(())
     }
 
+    /// Get multiple members of an object at once.
+    ///
+    /// All values have to be of the same type, use [`Value`] as `V` to get members of different
+    /// types. Combined with keys interned once as [`Atom`]s this avoids converting the keys on
+    /// every call when the same properties are read from many objects.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let point: Object = ctx.eval("({ x: 1, y: 2 })").unwrap();
+    /// let [x, y]: [f64; 2] = point.get_many(["x", "y"]).unwrap();
+    /// assert_eq!((x, y), (1.0, 2.0));
+    /// # });
+    /// ```
+    pub fn get_many<K: IntoAtom<'js>, V: FromJs<'js>, const N: usize>(
+        &self,
+        keys: [K; N],
+    ) -> Result<[V; N]> {
+        let mut values = Vec::with_capacity(N);
+        for key in keys {
+            values.push(self.get(key)?);
+        }
+        match values.try_into() {
+            Ok(x) => Ok(x),
+            Err(_) => unreachable!("one value is read for each key"),
+        }
+    }
+
+    /// Set multiple members of an object at once.
+    ///
+    /// Members are set in iteration order, stopping at the first error.
+    pub fn set_many<K, V, I>(&self, members: I) -> Result<()>
+    where
+        K: IntoAtom<'js>,
+        V: IntoJs<'js>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in members {
+            self.set(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Get a member nested in objects by its dotted path, like `"server.tls.port"`.
+    ///
+    /// Fails if a member along the path is not an object. Conversion errors name the path up to
+    /// the member which failed to convert.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let config: Object = ctx.eval("({ server: { tls: { port: 443 }, name: 'main' } })").unwrap();
+    /// assert_eq!(config.get_path::<u16>("server.tls.port").unwrap(), 443);
+    ///
+    /// let err = config.get_path::<u16>("server.name.length").unwrap_err();
+    /// assert!(err.to_string().contains("`server.name` is not an object"));
+    ///
+    /// config.set_path("server.http.port", 80).unwrap();
+    /// assert_eq!(config.get_path::<u16>("server.http.port").unwrap(), 80);
+    /// # });
+    /// ```
+    pub fn get_path<V: FromJs<'js>>(&self, path: &str) -> Result<V> {
+        let (object, key) = self.walk_path(path, false)?;
+        object.get(key).map_err(|error| match error {
+            Error::FromJs { from, to, message } => Error::FromJs {
+                from,
+                to,
+                message: Some(match message {
+                    Some(message) => format!("`{path}`: {message}"),
+                    None => format!("`{path}`"),
+                }),
+            },
+            error => error,
+        })
+    }
+
+    /// Set a member nested in objects by its dotted path, like `"server.tls.port"`.
+    ///
+    /// Members along the path which are undefined are set to new objects. Fails if a member along
+    /// the path is another value which is not an object.
+    pub fn set_path<V: IntoJs<'js>>(&self, path: &str, value: V) -> Result<()> {
+        let (object, key) = self.walk_path(path, true)?;
+        object.set(key, value)
+    }
+
+    /// Returns the object holding the last member of a dotted path and the key of that member.
+    fn walk_path<'a>(&self, path: &'a str, create: bool) -> Result<(Object<'js>, &'a str)> {
+        let mut object = self.clone();
+        let mut segments = path.split('.');
+        let mut key = segments.next().unwrap_or_default();
+        let mut end = key.len();
+        for next in segments {
+            let value: Value<'js> = object.get(key)?;
+            object = match value.try_into_object() {
+                Ok(x) => x,
+                Err(value) if create && value.is_undefined() => {
+                    let new = Object::new(self.ctx().clone())?;
+                    object.set(key, new.clone())?;
+                    new
+                }
+                Err(value) => {
+                    return Err(Error::new_from_js_message(
+                        value.type_name(),
+                        "object",
+                        format!("`{}` is not an object", &path[..end]),
+                    ))
+                }
+            };
+            end += 1 + next.len();
+            key = next;
+        }
+        Ok((object, key))
+    }
+
+    /// Copy all own enumerable properties of the source object onto this object, the equivalent
+    /// of `Object.assign(this, source)`.
+    ///
+    /// Both string and symbol keyed properties are copied. Getters on the source and setters on
+    /// this object are invoked.
+    pub fn assign(&self, source: &Object<'js>) -> Result<()> {
+        let filter = Filter::new().string().symbol().enum_only();
+        for prop in source.own_props::<Atom<'js>, Value<'js>>(filter) {
+            let (key, value) = prop?;
+            self.set(key, value)?;
+        }
+        Ok(())
+    }
+
     /// Remove a member of an object
     pub fn remove<K: IntoAtom<'js>>(&self, key: K) -> Result<()> {
         let atom = key.into_atom(self.ctx())?;
@@ -168,15 +377,123 @@ impl<'js> Object<'js> {
         }
     }
 
-    /// Check instance of object
+    /// Check instance of object, the equivalent of `object instanceof class`.
+    ///
+    /// Returns false if the check throws, for example when `class` is not callable. Use
+    /// [`Object::try_is_instance_of`] to get the thrown error instead.
     pub fn is_instance_of(&self, class: impl AsRef<Value<'js>>) -> bool {
+        match self.try_is_instance_of(class) {
+            Ok(x) => x,
+            Err(_) => {
+                self.0.ctx.catch();
+                false
+            }
+        }
+    }
+
+    /// Check instance of object, the equivalent of `object instanceof class`.
+    ///
+    /// Returns an error if the check throws, for example when `class` is not callable or its
+    /// `Symbol.hasInstance` method throws.
+    pub fn try_is_instance_of(&self, class: impl AsRef<Value<'js>>) -> Result<bool> {
         let class = class.as_ref();
-        0 != unsafe {
+        let res = unsafe {
             qjs::JS_IsInstanceOf(
                 self.0.ctx.as_ptr(),
                 self.0.as_js_value(),
                 class.as_js_value(),
             )
+        };
+        if res < 0 {
+            Err(self.0.ctx.raise_exception())
+        } else {
+            Ok(res != 0)
+        }
+    }
+
+    /// Freeze the object, the equivalent of `Object.freeze`.
+    ///
+    /// Prevents any new properties from being added and makes all own properties
+    /// non-configurable and, for data properties, non-writable.
+    pub fn freeze(&self) -> Result<()> {
+        let ctx = self.ctx();
+        unsafe {
+            if qjs::JS_PreventExtensions(ctx.as_ptr(), self.0.as_js_value()) < 0 {
+                return Err(ctx.raise_exception());
+            }
+        }
+        for key in self.own_keys::<Atom>(Filter::new().string().symbol()) {
+            let key = key?;
+            let mut flags = (qjs::JS_PROP_THROW | qjs::JS_PROP_HAS_CONFIGURABLE) as qjs::c_int;
+            if let Some(desc) = self.get_own_property(&key)? {
+                if !desc.is_accessor() {
+                    flags |= qjs::JS_PROP_HAS_WRITABLE as qjs::c_int;
+                }
+            }
+            unsafe {
+                if qjs::JS_DefineProperty(
+                    ctx.as_ptr(),
+                    self.0.as_js_value(),
+                    key.atom,
+                    qjs::JS_UNDEFINED,
+                    qjs::JS_UNDEFINED,
+                    qjs::JS_UNDEFINED,
+                    flags,
+                ) < 0
+                {
+                    return Err(ctx.raise_exception());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether the object is frozen, the equivalent of `Object.isFrozen`.
+    pub fn is_frozen(&self) -> Result<bool> {
+        let ctx = self.ctx();
+        let res = unsafe { qjs::JS_IsExtensible(ctx.as_ptr(), self.0.as_js_value()) };
+        if res < 0 {
+            return Err(ctx.raise_exception());
+        }
+        if res != 0 {
+            return Ok(false);
+        }
+        for key in self.own_keys::<Atom>(Filter::new().string().symbol()) {
+            if let Some(desc) = self.get_own_property(&key?)? {
+                let writable =
+                    !desc.is_accessor() && desc.flags & qjs::JS_PROP_WRITABLE as qjs::c_int != 0;
+                if desc.flags & qjs::JS_PROP_CONFIGURABLE as qjs::c_int != 0 || writable {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Retrieve the descriptor of an own property, returns `None` if the property doesn't exist.
+    pub(crate) fn get_own_property(&self, key: &Atom<'js>) -> Result<Option<OwnProperty<'js>>> {
+        let ctx = self.ctx();
+        let mut desc = mem::MaybeUninit::<qjs::JSPropertyDescriptor>::uninit();
+        unsafe {
+            let res = qjs::JS_GetOwnProperty(
+                ctx.as_ptr(),
+                desc.as_mut_ptr(),
+                self.0.as_js_value(),
+                key.atom,
+            );
+            if res < 0 {
+                return Err(ctx.raise_exception());
+            }
+            if res == 0 {
+                return Ok(None);
+            }
+            let desc = desc.assume_init();
+            Ok(Some(OwnProperty {
+                flags: desc.flags,
+                value: Value::from_js_value(ctx.clone(), desc.value),
+                getter: Value::from_js_value(ctx.clone(), desc.getter),
+                setter: Value::from_js_value(ctx.clone(), desc.setter),
+            }))
         }
     }
 
@@ -190,6 +507,20 @@ impl<'js> Object<'js> {
     }
 }
 
+/// The descriptor of an own property as returned by QuickJS.
+pub(crate) struct OwnProperty<'js> {
+    pub flags: qjs::c_int,
+    pub value: Value<'js>,
+    pub getter: Value<'js>,
+    pub setter: Value<'js>,
+}
+
+impl<'js> OwnProperty<'js> {
+    pub fn is_accessor(&self) -> bool {
+        self.flags & qjs::JS_PROP_GETSET as qjs::c_int != 0
+    }
+}
+
 /// The property filter
 #[derive(Debug, Clone, Copy)]
 #[repr(transparent)]
@@ -609,6 +940,67 @@ where
 mod test {
     use crate::*;
 
+    #[test]
+    fn get_optional() {
+        test_with(|ctx| {
+            let val: Object = ctx
+                .eval("({ a: 1, b: undefined, c: null, d: 'text' })")
+                .unwrap();
+            assert_eq!(val.get_optional::<_, i32>("a").unwrap(), Some(1));
+            assert_eq!(val.get_optional::<_, i32>("b").unwrap(), None);
+            assert_eq!(val.get_optional::<_, i32>("missing").unwrap(), None);
+            assert_eq!(val.get_optional::<_, Option<i32>>("c").unwrap(), Some(None));
+            assert!(val.get_optional::<_, i32>("d").is_err());
+            assert_eq!(val.get_or("a", 5).unwrap(), 1);
+            assert_eq!(val.get_or("missing", 5).unwrap(), 5);
+            assert!(val.get_or("c", 5).is_err());
+        })
+    }
+
+    #[test]
+    fn builder() {
+        test_with(|ctx| {
+            let proto: Object = ctx
+                .eval("({ greet() { return 'hi ' + this.name } })")
+                .unwrap();
+            let object = object::ObjectBuilder::with_prototype(ctx.clone(), Some(&proto))
+                .prop("name", "bob")
+                .prop(1, 2.5)
+                .method("twice", |x: i32| x * 2)
+                .build()
+                .unwrap();
+            ctx.globals().set("object", object).unwrap();
+            let res: StdString = ctx
+                .eval("[object.greet(), object[1], object.twice(2), Object.keys(object)].join()")
+                .unwrap();
+            assert_eq!(res, "hi bob,2.5,4,1,name,twice");
+
+            // Setters of the prototype aren't called, like for literals.
+            let proto: Object = ctx.eval("({ set a(x) { throw 'set' } })").unwrap();
+            let object = object::ObjectBuilder::with_prototype(ctx.clone(), Some(&proto))
+                .prop("a", 1)
+                .build()
+                .unwrap();
+            assert_eq!(object.get::<_, i32>("a").unwrap(), 1);
+
+            let object = object::ObjectBuilder::with_prototype(ctx.clone(), None)
+                .build()
+                .unwrap();
+            assert!(object.get_prototype().is_none());
+
+            let key: Object = ctx
+                .eval("({ toString() { throw new Error('key') } })")
+                .unwrap();
+            let res = object::ObjectBuilder::new(ctx.clone())
+                .prop("a", 1)
+                .prop(key, 2)
+                .prop("b", 3)
+                .build();
+            assert!(res.is_err());
+            ctx.catch();
+        })
+    }
+
     #[test]
     fn from_javascript() {
         test_with(|ctx| {
@@ -638,6 +1030,37 @@ mod test {
         });
     }
 
+    #[test]
+    fn index_access() {
+        test_with(|ctx| {
+            let obj = Object::new(ctx.clone()).unwrap();
+            obj.set_index(0, "a").unwrap();
+            obj.set_index(u32::MAX - 1, "b").unwrap();
+            obj.set("c", 1).unwrap();
+            assert_eq!(obj.get_index::<StdString>(0).unwrap(), "a");
+            assert_eq!(obj.get::<_, StdString>("4294967294").unwrap(), "b");
+            assert_eq!(obj.get_index::<Option<StdString>>(1).unwrap(), None);
+
+            let keys: Vec<Atom> = obj.keys().collect::<Result<_>>().unwrap();
+            let indices: Vec<Option<u32>> = keys.iter().map(Atom::index).collect();
+            assert_eq!(indices, [Some(0), Some(u32::MAX - 1), None]);
+            assert_eq!(obj.keys::<u32>().nth(1).unwrap().unwrap(), u32::MAX - 1);
+            assert!(obj.keys::<u32>().nth(2).unwrap().is_err());
+
+            let atom = Atom::from_str(ctx.clone(), "12").unwrap();
+            assert_eq!(atom.index(), Some(12));
+            assert_eq!(atom, Atom::from_u32(ctx.clone(), 12).unwrap());
+            for name in ["012", "-1", "4294967295", "1.5", ""] {
+                assert_eq!(Atom::from_str(ctx.clone(), name).unwrap().index(), None);
+            }
+            let symbol: Value = ctx.eval("Symbol('3')").unwrap();
+            assert_eq!(
+                Atom::from_value(ctx.clone(), &symbol).unwrap().index(),
+                None
+            );
+        });
+    }
+
     #[test]
     fn types() {
         test_with(|ctx| {
@@ -801,4 +1224,115 @@ mod test {
             );
         })
     }
+
+    #[test]
+    fn instance_of() {
+        test_with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                globalThis.Base = class {};
+                globalThis.Derived = class extends Base {};
+            "#,
+            )
+            .unwrap();
+            let base: Function = ctx.globals().get("Base").unwrap();
+            let derived: Function = ctx.globals().get("Derived").unwrap();
+            let object: Object = ctx.eval("new Derived()").unwrap();
+
+            assert!(object.is_instance_of(&derived));
+            assert!(object.is_instance_of(&base));
+            assert!(!Object::new(ctx.clone()).unwrap().is_instance_of(&base));
+
+            let proto = object.get_prototype().unwrap();
+            assert_eq!(proto, derived.get::<_, Object>("prototype").unwrap());
+            object.set_prototype(None).unwrap();
+            assert!(!object.is_instance_of(&base));
+
+            let not_callable = Object::new(ctx.clone()).unwrap();
+            assert!(!object.is_instance_of(&not_callable));
+            assert!(ctx.catch().is_uninitialized());
+            let err = object.try_is_instance_of(&not_callable).unwrap_err();
+            assert!(err.is_exception());
+            assert!(ctx.catch().is_error());
+        })
+    }
+
+    #[test]
+    fn get_set_many() {
+        test_with(|ctx| {
+            let object = Object::new(ctx.clone()).unwrap();
+            object.set_many([("a", 1), ("b", 2), ("c", 3)]).unwrap();
+
+            let [a, b, c]: [i32; 3] = object.get_many(["a", "b", "c"]).unwrap();
+            assert_eq!((a, b, c), (1, 2, 3));
+
+            let key_c = Atom::from_str(ctx.clone(), "c").unwrap();
+            let key_d = Atom::from_str(ctx.clone(), "d").unwrap();
+            let [c, d]: [Value; 2] = object.get_many([&key_c, &key_d]).unwrap();
+            assert_eq!(c.as_int(), Some(3));
+            assert!(d.is_undefined());
+
+            assert!(object.get_many::<_, StdString, 2>(["a", "b"]).is_err());
+        })
+    }
+
+    #[test]
+    fn with_prototype_and_assign() {
+        test_with(|ctx| {
+            let proto: Object = ctx
+                .eval("({ greet() { return 'hi ' + this.name } })")
+                .unwrap();
+            let object = Object::with_prototype(ctx.clone(), Some(&proto)).unwrap();
+            assert_eq!(object.get_prototype(), Some(proto));
+
+            let source: Object = ctx
+                .eval(
+                    r#"
+                const source = { name: 'js', [Symbol.for('tag')]: 1 };
+                Object.defineProperty(source, 'hidden', { value: true, enumerable: false });
+                source
+            "#,
+                )
+                .unwrap();
+            object.assign(&source).unwrap();
+            ctx.globals().set("object", object.clone()).unwrap();
+
+            let greeting: StdString = ctx.eval("object.greet()").unwrap();
+            assert_eq!(greeting, "hi js");
+            assert!(ctx
+                .eval::<bool, _>("object[Symbol.for('tag')] === 1")
+                .unwrap());
+            assert!(!object.contains_key("hidden").unwrap());
+
+            let bare = Object::with_prototype(ctx.clone(), None).unwrap();
+            assert!(bare.get_prototype().is_none());
+        })
+    }
+
+    #[test]
+    fn get_set_path() {
+        test_with(|ctx| {
+            let config: Object = ctx
+                .eval("({ a: { b: { c: 1 }, s: 'text', n: null } })")
+                .unwrap();
+            assert_eq!(config.get_path::<i32>("a.b.c").unwrap(), 1);
+            assert!(config.get_path::<Value>("a.b.d").unwrap().is_undefined());
+
+            let err = config.get_path::<i32>("a.s.length").unwrap_err();
+            assert!(err.to_string().contains("`a.s` is not an object"));
+            let err = config.get_path::<i32>("a.x.y").unwrap_err();
+            assert!(err.to_string().contains("`a.x` is not an object"));
+            let err = config.get_path::<i32>("a.s").unwrap_err();
+            assert!(err.to_string().contains("`a.s`"));
+
+            config.set_path("a.b.c", 2).unwrap();
+            config.set_path("a.x.y.z", true).unwrap();
+            assert!(ctx
+                .globals()
+                .set("config", config.clone())
+                .and_then(|_| ctx.eval::<bool, _>("config.a.b.c === 2 && config.a.x.y.z"))
+                .unwrap());
+            assert!(config.set_path("a.n.m", 1).is_err());
+        })
+    }
 }
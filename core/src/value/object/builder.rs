@@ -0,0 +1,113 @@
+use crate::{function::IntoJsFunc, qjs, Ctx, Function, IntoAtom, IntoJs, Object, Result};
+
+/// A builder of objects, the equivalent of an object literal.
+///
+/// Properties are defined on the object as they are added, like the properties of a literal,
+/// so they are writable, enumerable and configurable, and setters of the prototype aren't
+/// called. The first error is returned by [`ObjectBuilder::build`].
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Object, object::ObjectBuilder};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let options = ObjectBuilder::new(ctx.clone())
+///     .prop("retries", 3)
+///     .prop("verbose", true)
+///     .method("log", |message: String| println!("{message}"))
+///     .build()
+///     .unwrap();
+/// ctx.globals().set("options", options).unwrap();
+/// assert_eq!(ctx.eval::<i32, _>("options.retries").unwrap(), 3);
+/// assert_eq!(ctx.eval::<String, _>("options.log.name").unwrap(), "log");
+///
+/// // Dictionaries without a prototype don't inherit `toString` and friends.
+/// let dictionary = ObjectBuilder::with_prototype(ctx.clone(), None)
+///     .prop("toString", 1)
+///     .build()
+///     .unwrap();
+/// assert!(dictionary.get_prototype().is_none());
+/// # });
+/// ```
+#[derive(Debug)]
+#[must_use = "the object is only returned by `build`"]
+pub struct ObjectBuilder<'js> {
+    object: Result<Object<'js>>,
+}
+
+impl<'js> ObjectBuilder<'js> {
+    /// Start building an object with `Object.prototype` as its prototype.
+    pub fn new(ctx: Ctx<'js>) -> Self {
+        ObjectBuilder {
+            object: Object::new(ctx),
+        }
+    }
+
+    /// Start building an object with the given prototype, or a null prototype if called with
+    /// `None`.
+    pub fn with_prototype(ctx: Ctx<'js>, proto: Option<&Object<'js>>) -> Self {
+        ObjectBuilder {
+            object: Object::with_prototype(ctx, proto),
+        }
+    }
+
+    /// Add a property with a value.
+    pub fn prop<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: IntoAtom<'js>,
+        V: IntoJs<'js>,
+    {
+        if let Ok(object) = &self.object {
+            if let Err(error) = define(object, key, value) {
+                self.object = Err(error);
+            }
+        }
+        self
+    }
+
+    /// Add a function created from a closure, named after the property.
+    pub fn method<F, P>(mut self, name: &str, f: F) -> Self
+    where
+        F: IntoJsFunc<'js, P> + 'js,
+    {
+        if let Ok(object) = &self.object {
+            let res = Function::new(object.ctx().clone(), f)
+                .and_then(|function| function.with_name(name))
+                .and_then(|function| define(object, name, function));
+            if let Err(error) = res {
+                self.object = Err(error);
+            }
+        }
+        self
+    }
+
+    /// Returns the object, or the first error which occurred while building it.
+    pub fn build(self) -> Result<Object<'js>> {
+        self.object
+    }
+}
+
+fn define<'js, K, V>(object: &Object<'js>, key: K, value: V) -> Result<()>
+where
+    K: IntoAtom<'js>,
+    V: IntoJs<'js>,
+{
+    let ctx = object.ctx();
+    let key = key.into_atom(ctx)?;
+    let value = value.into_js(ctx)?;
+    let flags = qjs::JS_PROP_C_W_E | qjs::JS_PROP_THROW;
+    unsafe {
+        // Takes ownership of the value.
+        let res = qjs::JS_DefinePropertyValue(
+            ctx.as_ptr(),
+            object.as_js_value(),
+            key.atom,
+            value.into_js_value(),
+            flags as _,
+        );
+        if res < 0 {
+            return Err(ctx.raise_exception());
+        }
+    }
+    Ok(())
+}
@@ -1,16 +1,18 @@
 //! Javascript promises and future integration.
 use crate::{
-    atom::PredefinedAtom, qjs, Ctx, Error, FromJs, Function, IntoJs, Object, Result, Value,
+    atom::PredefinedAtom, convert::FromIteratorJs, function::This, qjs, Array, Ctx, Error, FromJs,
+    Function, IntoJs, Object, Result, Value,
 };
 #[cfg(feature = "futures")]
-use crate::{function::This, CatchResultExt, CaughtError};
+use crate::{CatchResultExt, CaughtError};
 #[cfg(feature = "futures")]
-use std::{
+use alloc::rc::Rc;
+#[cfg(feature = "futures")]
+use core::{
     cell::RefCell,
     future::Future,
     marker::PhantomData,
     pin::Pin,
-    rc::Rc,
     task::{Context as TaskContext, Poll, Waker},
 };
 
@@ -91,6 +93,67 @@ impl<'js> Promise<'js> {
         self.0.get(PredefinedAtom::Catch)
     }
 
+    /// Create a promise which resolves with an array of the results of all given values once all
+    /// of them resolved, or rejects as soon as one of them rejects, like `Promise.all`.
+    ///
+    /// Values which aren't promises are treated as already resolved.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Promise};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let (first, resolve, _) = Promise::new(&ctx).unwrap();
+    /// let second: Promise = ctx.eval("Promise.resolve(2)").unwrap();
+    ///
+    /// let all = Promise::all(&ctx, [first.into_value(), second.into_value()]).unwrap();
+    /// resolve.call::<_, ()>((1,)).unwrap();
+    /// assert_eq!(all.finish::<Vec<i32>>().unwrap(), [1, 2]);
+    /// # });
+    /// ```
+    pub fn all<I, T>(ctx: &Ctx<'js>, values: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoJs<'js>,
+    {
+        Self::combine(ctx, "all", values)
+    }
+
+    /// Create a promise which settles like the first of the given values which settles, like
+    /// `Promise.race`.
+    ///
+    /// The promise stays pending forever if no values are given.
+    pub fn race<I, T>(ctx: &Ctx<'js>, values: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoJs<'js>,
+    {
+        Self::combine(ctx, "race", values)
+    }
+
+    /// Create a promise which resolves once all given values settled, like `Promise.allSettled`.
+    ///
+    /// The promise never rejects, it resolves with an array of objects which either have a
+    /// `status` of `"fulfilled"` and a `value` or a `status` of `"rejected"` and a `reason`.
+    pub fn all_settled<I, T>(ctx: &Ctx<'js>, values: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoJs<'js>,
+    {
+        Self::combine(ctx, "allSettled", values)
+    }
+
+    fn combine<I, T>(ctx: &Ctx<'js>, method: &str, values: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoJs<'js>,
+    {
+        let constructor: Object = ctx.globals().get(PredefinedAtom::Promise)?;
+        let combinator: Function = constructor.get(method)?;
+        let values = Array::from_iter_js(ctx, values)?;
+        combinator.call((This(constructor), values))
+    }
+
     /// Returns the result of the future if there is one.
     ///
     /// Returns None if the promise has not yet been completed, Ok if the promise was resolved, and
@@ -352,12 +415,12 @@ mod test {
     #[cfg(feature = "futures")]
     use crate::{
         async_with, function::Async, promise::Promised, AsyncContext, AsyncRuntime, CaughtError,
-        Result,
     };
     use crate::{
         function::Func, prelude::This, promise::PromiseState, CatchResultExt, Context, Function,
-        Runtime,
+        IntoJs, Object, Result, Runtime,
     };
+    use std::string::String as StdString;
 
     #[cfg(feature = "futures")]
     async fn set_timeout<'js>(cb: Function<'js>, number: f64) -> Result<()> {
@@ -470,6 +533,44 @@ mod test {
         .await
     }
 
+    #[test]
+    fn combinators() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let (pending, resolve, _) = Promise::new(&ctx).unwrap();
+            let (rejected, _, reject) = Promise::new(&ctx).unwrap();
+
+            let all = Promise::all(
+                &ctx,
+                [pending.clone().into_value(), 2.into_js(&ctx).unwrap()],
+            )
+            .unwrap();
+            let race = Promise::race(&ctx, [pending.clone(), rejected.clone()]).unwrap();
+            let settled = Promise::all_settled(&ctx, [pending, rejected]).unwrap();
+            assert_eq!(all.state(), PromiseState::Pending);
+
+            reject.call::<_, ()>(("failed",)).unwrap();
+            resolve.call::<_, ()>((1,)).unwrap();
+            assert_eq!(all.finish::<Vec<i32>>().unwrap(), [1, 2]);
+
+            assert!(race.finish::<()>().is_err());
+            let reason: StdString = ctx.catch().get().unwrap();
+            assert_eq!(reason, "failed");
+
+            let settled = settled.finish::<Vec<Object>>().unwrap();
+            let status = settled
+                .iter()
+                .map(|x| x.get("status"))
+                .collect::<Result<Vec<StdString>>>()
+                .unwrap();
+            assert_eq!(status, ["fulfilled", "rejected"]);
+
+            let empty = Promise::all(&ctx, Vec::<i32>::new()).unwrap();
+            assert_eq!(empty.finish::<Vec<i32>>().unwrap(), Vec::<i32>::new());
+        })
+    }
+
     #[test]
     fn promise_then() {
         static DID_EXECUTE: AtomicBool = AtomicBool::new(false);
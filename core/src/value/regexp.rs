@@ -0,0 +1,288 @@
+//! JavaScript regular expressions.
+
+use crate::{
+    function::This, value::Constructor, Array, Ctx, Error, FromJs, Function, IntoJs, JsLifetime,
+    Object, Result, Value,
+};
+use alloc::{string::String as StdString, vec::Vec};
+use core::ops::Deref;
+
+/// Rust representation of a JavaScript object of class `RegExp`.
+///
+/// The regular expression is compiled and executed by the JavaScript engine, so it follows the
+/// JavaScript syntax and semantics. Offsets reported by the engine are in UTF-16 code units.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, RegExp};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let re = RegExp::new(ctx.clone(), r"(?<key>\w+)=(\d+)", "d").unwrap();
+/// assert!(re.test("answer=42").unwrap());
+///
+/// let found = re.exec("the answer=42").unwrap().unwrap();
+/// assert_eq!(found.index, 4);
+/// assert_eq!(found.captures[2].as_deref(), Some("42"));
+/// assert_eq!(found.group("key"), Some("answer"));
+/// assert_eq!(found.indices.unwrap()[2], Some((11, 13)));
+/// # });
+/// ```
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct RegExp<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> JsLifetime<'js> for RegExp<'js> {
+    type Changed<'to> = RegExp<'to>;
+}
+
+/// The result of a successful [`RegExp::exec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegExpMatch {
+    /// The offset of the match in the input.
+    pub index: usize,
+    /// The matched text followed by the text of every capture group, `None` for groups which
+    /// didn't participate in the match.
+    pub captures: Vec<Option<StdString>>,
+    /// The named capture groups in the order they appear in the pattern.
+    pub groups: Vec<(StdString, Option<StdString>)>,
+    /// The start and end offsets of the match and of every capture group.
+    ///
+    /// Only available if the regular expression has the `d` flag.
+    pub indices: Option<Vec<Option<(usize, usize)>>>,
+}
+
+impl RegExpMatch {
+    /// Returns the text of a named capture group.
+    pub fn group(&self, name: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(x, _)| x == name)
+            .and_then(|(_, x)| x.as_deref())
+    }
+}
+
+impl<'js> RegExp<'js> {
+    /// Compile a new regular expression from a pattern and flags, like `new RegExp(pattern, flags)`.
+    ///
+    /// Returns an exception if the pattern or flags are invalid.
+    pub fn new(ctx: Ctx<'js>, pattern: &str, flags: &str) -> Result<Self> {
+        let constructor = Self::constructor(&ctx)?;
+        constructor.construct((pattern, flags)).map(RegExp)
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Constructor<'js>> {
+        ctx.globals().get("RegExp")
+    }
+
+    /// Returns the source text of the pattern.
+    pub fn source(&self) -> Result<StdString> {
+        self.0.get("source")
+    }
+
+    /// Returns the flags of the regular expression.
+    pub fn flags(&self) -> Result<StdString> {
+        self.0.get("flags")
+    }
+
+    /// Returns the index at which the next match starts for global and sticky expressions.
+    pub fn last_index(&self) -> Result<usize> {
+        self.0.get("lastIndex")
+    }
+
+    /// Set the index at which the next match starts for global and sticky expressions.
+    pub fn set_last_index(&self, index: usize) -> Result<()> {
+        self.0.set("lastIndex", index)
+    }
+
+    /// Returns if the regular expression matches the input, like `RegExp.prototype.test`.
+    pub fn test(&self, input: &str) -> Result<bool> {
+        let test: Function = self.0.get("test")?;
+        test.call((This(self.0.clone()), input))
+    }
+
+    /// Search for a match in the input, like `RegExp.prototype.exec`.
+    ///
+    /// Returns `None` if there is no match.
+    pub fn exec(&self, input: &str) -> Result<Option<RegExpMatch>> {
+        let exec: Function = self.0.get("exec")?;
+        let result: Option<Array> = exec.call((This(self.0.clone()), input))?;
+        let Some(result) = result else {
+            return Ok(None);
+        };
+
+        let captures = result.iter().collect::<Result<_>>()?;
+        let result = result.into_object();
+        let index = result.get("index")?;
+
+        let groups = match result.get::<_, Option<Object>>("groups")? {
+            Some(groups) => groups.props().collect::<Result<_>>()?,
+            None => Vec::new(),
+        };
+
+        let indices = match result.get::<_, Option<Array>>("indices")? {
+            Some(indices) => Some(
+                indices
+                    .iter::<Option<Vec<usize>>>()
+                    .map(|x| {
+                        x.map(|x| {
+                            x.and_then(|x| match x[..] {
+                                [start, end] => Some((start, end)),
+                                _ => None,
+                            })
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            None => None,
+        };
+
+        Ok(Some(RegExpMatch {
+            index,
+            captures,
+            groups,
+            indices,
+        }))
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        if object.is_reg_exp() {
+            Some(Self(object))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'js> Deref for RegExp<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for RegExp<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for RegExp<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for RegExp<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        if let Some(v) = Self::from_value(value) {
+            Ok(v)
+        } else {
+            Err(Error::new_from_js(ty_name, "RegExp"))
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for RegExp<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of [`RegExp`].
+    pub fn is_reg_exp(&self) -> bool {
+        match RegExp::constructor(self.ctx()) {
+            Ok(constructor) => self.is_instance_of(constructor),
+            Err(_) => false,
+        }
+    }
+
+    /// Interpret as [`RegExp`]
+    ///
+    /// # Safety
+    /// You should be sure that the object actually is the required type.
+    pub unsafe fn ref_reg_exp(&self) -> &RegExp<'js> {
+        &*(self as *const _ as *const RegExp)
+    }
+
+    /// Try interpret as [`RegExp`]
+    pub fn as_reg_exp(&self) -> Option<&RegExp<'js>> {
+        if self.is_reg_exp() {
+            Some(unsafe { self.ref_reg_exp() })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let re: RegExp = ctx.eval("/a(b)?/gi").unwrap();
+            assert_eq!(re.source().unwrap(), "a(b)?");
+            assert_eq!(re.flags().unwrap(), "gi");
+
+            let found = re.exec("xAa").unwrap().unwrap();
+            assert_eq!(found.index, 1);
+            assert_eq!(found.captures, vec![Some("A".to_string()), None]);
+            assert!(found.groups.is_empty());
+            assert_eq!(found.indices, None);
+            assert_eq!(re.last_index().unwrap(), 2);
+
+            re.set_last_index(0).unwrap();
+            assert!(re.exec("xyz").unwrap().is_none());
+
+            let object: Object = ctx.eval("({})").unwrap();
+            assert!(!object.is_reg_exp());
+            assert!(object.into_value().get::<RegExp>().is_err());
+        })
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        test_with(|ctx| {
+            let err = RegExp::new(ctx.clone(), "(", "").catch(&ctx).unwrap_err();
+            let CaughtError::Exception(err) = err else {
+                panic!("expected an exception, got {err}")
+            };
+            let name: StdString = err.get("name").unwrap();
+            assert_eq!(name, "SyntaxError");
+        })
+    }
+}
@@ -1,5 +1,6 @@
-use crate::{qjs, Ctx, Error, Result, StdString, Value};
-use std::{mem, slice, str};
+use crate::{qjs, Ctx, Error, IntoJs, Result, StdString, Value};
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::{fmt, mem, ops::Deref, slice, str};
 
 /// Rust representation of a JavaScript string.
 #[derive(Debug, Clone, PartialEq, Hash)]
@@ -9,6 +10,29 @@ pub struct String<'js>(pub(crate) Value<'js>);
 impl<'js> String<'js> {
     /// Convert the JavaScript string to a Rust string.
     pub fn to_string(&self) -> Result<StdString> {
+        self.as_str().map(|s| StdString::from(&*s))
+    }
+
+    /// Borrow the contents of the JavaScript string as a `&str`.
+    ///
+    /// The returned guard dereferences to the UTF-8 contents and releases them when dropped,
+    /// reading the string doesn't require copying it into a Rust [`String`](StdString) first.
+    ///
+    /// Strings of only ASCII characters are borrowed from QuickJS as they are, whatever their
+    /// length. Other strings are stored as Latin-1 or UTF-16 by QuickJS and are converted to UTF-8
+    /// in a temporary allocation of the runtime, there is no separate path for short strings.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, String};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let s: String = ctx.eval("'key:' + 42").unwrap();
+    /// let s = s.as_str().unwrap();
+    /// assert_eq!(s.split_once(':'), Some(("key", "42")));
+    /// # });
+    /// ```
+    pub fn as_str(&self) -> Result<StringRef<'js>> {
         let mut len = mem::MaybeUninit::uninit();
         let ptr = unsafe {
             qjs::JS_ToCStringLen(self.0.ctx.as_ptr(), len.as_mut_ptr(), self.0.as_js_value())
@@ -19,10 +43,14 @@ impl<'js> String<'js> {
             return Err(Error::Unknown);
         }
         let len = unsafe { len.assume_init() };
-        let bytes: &[u8] = unsafe { slice::from_raw_parts(ptr as _, len as _) };
-        let result = str::from_utf8(bytes).map(|s| s.into());
-        unsafe { qjs::JS_FreeCString(self.0.ctx.as_ptr(), ptr) };
-        Ok(result?)
+        let guard = StringRef {
+            ctx: self.0.ctx.clone(),
+            ptr,
+            len: len as _,
+        };
+        let bytes: &[u8] = unsafe { slice::from_raw_parts(ptr as _, guard.len) };
+        str::from_utf8(bytes)?;
+        Ok(guard)
     }
 
     /// Create a new JavaScript string from an Rust string.
@@ -37,6 +65,254 @@ impl<'js> String<'js> {
     }
 }
 
+/// The contents of a JavaScript [`String`] borrowed as a `&str`, see [`String::as_str`].
+pub struct StringRef<'js> {
+    ctx: Ctx<'js>,
+    ptr: *const core::ffi::c_char,
+    len: usize,
+}
+
+impl Deref for StringRef<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        // Safety: the contents were checked to be valid UTF-8 when the guard was created.
+        unsafe { str::from_utf8_unchecked(slice::from_raw_parts(self.ptr as _, self.len)) }
+    }
+}
+
+impl AsRef<str> for StringRef<'_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Debug for StringRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl fmt::Display for StringRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl PartialEq<str> for StringRef<'_> {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&str> for StringRef<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+impl Drop for StringRef<'_> {
+    fn drop(&mut self) {
+        unsafe { qjs::JS_FreeCString(self.ctx.as_ptr(), self.ptr) };
+    }
+}
+
+/// A buffer for building a large JavaScript string out of many pieces.
+///
+/// The pieces are collected in Rust and the JavaScript string is created once by
+/// [`JsStringBuilder::build`], instead of converting and concatenating every piece. The pieces are
+/// written into chunks of a fixed size which are never reallocated, so the text written so far
+/// isn't copied again whenever the buffer grows, and the string is built from the chunks
+/// directly. It implements [`fmt::Write`] so it can be written to with `write!`.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, JsStringBuilder};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// use std::fmt::Write;
+///
+/// let mut html = JsStringBuilder::new();
+/// html.push_str("<ul>");
+/// for item in ["a", "b"] {
+///     write!(html, "<li>{item}</li>").unwrap();
+/// }
+/// html.push_str("</ul>");
+/// let html = html.build(&ctx).unwrap();
+/// assert_eq!(html.to_string().unwrap(), "<ul><li>a</li><li>b</li></ul>");
+/// # });
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct JsStringBuilder {
+    /// Chunks which are full, or were too large to be copied into one.
+    chunks: Vec<StdString>,
+    /// The chunk being written to, it is never grown past its capacity.
+    current: StdString,
+    len: usize,
+}
+
+impl JsStringBuilder {
+    /// The capacity of the chunks the pieces are written into.
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty builder with room for at least `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        StdString::with_capacity(capacity).into()
+    }
+
+    /// Append a string.
+    pub fn push_str(&mut self, s: &str) -> &mut Self {
+        if s.len() >= Self::CHUNK_SIZE && self.remaining() < s.len() {
+            // Large pieces become a chunk of their own instead of being copied into a new one.
+            self.start_chunk(0);
+            self.chunks.push(s.to_owned());
+        } else {
+            self.reserve(s.len());
+            self.current.push_str(s);
+        }
+        self.len += s.len();
+        self
+    }
+
+    /// Append a character.
+    pub fn push(&mut self, c: char) -> &mut Self {
+        self.push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Reserve room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.remaining() < additional {
+            self.start_chunk(additional.max(Self::CHUNK_SIZE));
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.current.capacity() - self.current.len()
+    }
+
+    /// Keep the current chunk and start writing into a new one with room for `capacity` bytes.
+    fn start_chunk(&mut self, capacity: usize) {
+        let current = mem::replace(&mut self.current, StdString::with_capacity(capacity));
+        if !current.is_empty() {
+            self.chunks.push(current);
+        }
+    }
+
+    /// The length of the built string in bytes of UTF-8.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether nothing was appended yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the chunks of the string built so far, in order.
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.chunks
+            .iter()
+            .chain(Some(&self.current))
+            .map(|x| x.as_str())
+    }
+
+    /// Create the JavaScript string.
+    ///
+    /// The chunks are decoded one after another into the JavaScript string. With the `system`
+    /// feature they are joined into a single Rust string first.
+    pub fn build<'js>(&self, ctx: &Ctx<'js>) -> Result<String<'js>> {
+        if self.chunks.is_empty() {
+            return String::from_str(ctx.clone(), &self.current);
+        }
+        #[cfg(not(feature = "system"))]
+        unsafe {
+            let (ptrs, lens): (Vec<_>, Vec<_>) = self
+                .chunks()
+                .map(|x| (x.as_ptr().cast::<qjs::c_char>(), x.len() as qjs::size_t))
+                .unzip();
+            let js_val = qjs::rquickjs_new_string_from_chunks(
+                ctx.as_ptr(),
+                ptrs.as_ptr(),
+                lens.as_ptr(),
+                ptrs.len() as _,
+                self.len as _,
+            );
+            let js_val = ctx.handle_exception(js_val)?;
+            Ok(String::from_js_value(ctx.clone(), js_val))
+        }
+        #[cfg(feature = "system")]
+        {
+            let mut buffer = StdString::with_capacity(self.len);
+            buffer.extend(self.chunks());
+            String::from_str(ctx.clone(), &buffer)
+        }
+    }
+}
+
+impl PartialEq for JsStringBuilder {
+    fn eq(&self, other: &Self) -> bool {
+        let bytes = |x: &Self| x.chunks().flat_map(str::bytes).collect::<Vec<_>>();
+        self.len == other.len && bytes(self) == bytes(other)
+    }
+}
+
+impl Eq for JsStringBuilder {}
+
+impl fmt::Display for JsStringBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|x| f.write_str(x))
+    }
+}
+
+impl fmt::Write for JsStringBuilder {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<'a> Extend<&'a str> for JsStringBuilder {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|x| {
+            self.push_str(x);
+        })
+    }
+}
+
+impl<'a> FromIterator<&'a str> for JsStringBuilder {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut builder = JsStringBuilder::new();
+        builder.extend(iter);
+        builder
+    }
+}
+
+impl From<StdString> for JsStringBuilder {
+    fn from(current: StdString) -> Self {
+        JsStringBuilder {
+            chunks: Vec::new(),
+            len: current.len(),
+            current,
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for JsStringBuilder {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.build(ctx).map(|x| x.into_value())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{prelude::*, *};
@@ -48,6 +324,74 @@ mod test {
         });
     }
 
+    #[test]
+    fn borrow_str() {
+        test_with(|ctx| {
+            let s: String = ctx.eval("'a' + 'ö'.repeat(3)").unwrap();
+            let borrowed = s.as_str().unwrap();
+            assert_eq!(borrowed, "aööö");
+            assert_eq!(borrowed.len(), 7);
+            drop(s);
+            assert_eq!(borrowed.to_string(), "aööö");
+
+            let c: char = ctx.eval("'😀'").unwrap();
+            assert_eq!(c, '😀');
+            let c: StdString = ctx
+                .eval::<Function, _>("c => c + c")
+                .unwrap()
+                .call(('ß',))
+                .unwrap();
+            assert_eq!(c, "ßß");
+        });
+    }
+
+    #[test]
+    fn string_builder() {
+        test_with(|ctx| {
+            let mut builder: JsStringBuilder = ["a", "ö"].into_iter().collect();
+            builder.push('😀').extend(["b"; 3]);
+            assert_eq!(builder.len(), 10);
+            ctx.globals().set("s", builder).unwrap();
+            let res: Vec<StdString> = ctx.eval("[s, String(s.length)]").unwrap();
+            assert_eq!(res, ["aö😀bbb", "7"]);
+
+            let mut builder = JsStringBuilder::with_capacity(4);
+            let large = "x".repeat(100_000);
+            builder
+                .push_str("ab")
+                .push_str("cde")
+                .push_str(&large)
+                .push('f');
+            assert_eq!(builder.chunks().count(), 4);
+            assert_eq!(builder.len(), 100_006);
+            assert_eq!(builder, JsStringBuilder::from(format!("abcde{large}f")));
+            assert_eq!(builder.to_string(), format!("abcde{large}f"));
+            let res = builder.build(&ctx).unwrap().to_string().unwrap();
+            assert_eq!(res, format!("abcde{large}f"));
+        });
+    }
+
+    #[test]
+    fn string_builder_many_pieces() {
+        use std::fmt::Write;
+
+        test_with(|ctx| {
+            let mut builder = JsStringBuilder::new();
+            let mut expected = StdString::new();
+            for i in 0..20_000 {
+                write!(builder, "{i}:ö😀,").unwrap();
+                write!(expected, "{i}:ö😀,").unwrap();
+            }
+            assert!(builder.len() > 2 * JsStringBuilder::CHUNK_SIZE);
+            assert!(builder.chunks().count() > 2);
+            let string = builder.build(&ctx).unwrap();
+            assert_eq!(string.to_string().unwrap(), expected);
+            ctx.globals().set("s", string).unwrap();
+            let length: usize = ctx.eval("s.length").unwrap();
+            assert_eq!(length, expected.encode_utf16().count());
+        });
+    }
+
     #[test]
     fn to_javascript() {
         test_with(|ctx| {
@@ -1,8 +1,9 @@
 use crate::{
-    atom::PredefinedAtom, qjs, ArrayBuffer, Ctx, Error, FromJs, Function, IntoJs, JsLifetime,
-    Object, Result, Value,
+    atom::PredefinedAtom, qjs, ArrayBuffer, BorrowError, Ctx, Error, FromJs, Function, IntoJs,
+    JsLifetime, Object, Result, StdResult, Value,
 };
-use std::{
+use alloc::vec::Vec;
+use core::{
     fmt,
     marker::PhantomData,
     mem::{self, MaybeUninit},
@@ -11,7 +12,10 @@ use std::{
     slice,
 };
 
-use super::{array_buffer::RawArrayBuffer, Constructor};
+use super::{
+    array_buffer::{BufferMut, BufferRef, RawArrayBuffer, RawView},
+    Constructor,
+};
 
 /// The trait which implements types which capable to be TypedArray items
 ///
@@ -166,11 +170,36 @@ impl<'js, T> TypedArray<'js, T> {
     /// Returns the underlying bytes of the buffer,
     ///
     /// Returns `None` if the array is detached.
+    ///
+    /// # Panics
+    /// Panics if the bytes of the array are borrowed mutably by a [`BufferMut`].
     pub fn as_bytes(&self) -> Option<&[u8]> {
         let (_, len, ptr) = Self::get_raw_bytes(self.as_value())?;
+        RawView::assert_not_borrowed_mut(self.as_value(), ptr, len);
         Some(unsafe { slice::from_raw_parts(ptr.as_ptr(), len) })
     }
 
+    /// Borrow the bytes of the array without copying them.
+    ///
+    /// See [`BufferRef`] for how the borrow is checked. Returns [`BorrowError::AlreadyUsed`] if
+    /// the buffer of the array is detached and [`BorrowError::AlreadyBorrowedMut`] if its bytes
+    /// are borrowed mutably.
+    pub fn borrow_bytes(&self) -> StdResult<BufferRef<'_, 'js>, BorrowError> {
+        RawView::new(self.as_value(), locate_array, false).map(BufferRef)
+    }
+
+    /// Borrow the bytes of the array mutably without copying them.
+    ///
+    /// See [`BufferRef`] for how the borrow is checked. Returns [`BorrowError::AlreadyUsed`] if
+    /// the buffer of the array is detached and [`BorrowError::AlreadyBorrowed`] if its bytes are
+    /// borrowed.
+    ///
+    /// # Safety
+    /// See [`BufferMut`], no script may run while the view is alive.
+    pub unsafe fn borrow_bytes_mut(&self) -> StdResult<BufferMut<'_, 'js>, BorrowError> {
+        RawView::new(self.as_value(), locate_array, true).map(BufferMut)
+    }
+
     pub fn as_raw(&self) -> Option<RawArrayBuffer> {
         let (_, len, ptr) = Self::get_raw_bytes(self.as_value())?;
         Some(RawArrayBuffer { len, ptr })
@@ -245,10 +274,15 @@ impl<'js, T> TypedArray<'js, T> {
     }
 }
 
+fn locate_array(value: &Value<'_>) -> Option<(NonNull<u8>, usize)> {
+    TypedArray::<u8>::get_raw_bytes(value).map(|(_, len, ptr)| (ptr, len))
+}
+
 impl<'js, T: TypedArrayItem> AsRef<[T]> for TypedArray<'js, T> {
     fn as_ref(&self) -> &[T] {
         let (len, ptr) =
             Self::get_raw(&self.0).unwrap_or_else(|| panic!("{}", T::CLASS_NAME.to_str()));
+        RawView::assert_not_borrowed_mut(self.as_value(), ptr.cast(), len * mem::size_of::<T>());
         unsafe { slice::from_raw_parts(ptr.as_ptr().cast(), len) }
     }
 }
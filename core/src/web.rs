@@ -0,0 +1,344 @@
+//! A minimal set of web platform globals.
+//!
+//! Scripts written for browsers or other JavaScript runtimes expect a few globals which are not
+//! part of the language itself. [`install`] defines a coherent set of them on the global object
+//! of a context:
+//!
+//! - `console` with `log`, `info`, `debug`, `warn`, `error`, `trace` and `assert`, writing to the
+//!   standard output and error of the process, or to the output collected by
+//!   [`Ctx::eval_capture`](crate::Ctx::eval_capture).
+//! - `setTimeout`, `clearTimeout`, `setInterval` and `clearInterval`, driven by [`run_timers`] or
+//!   [`run`].
+//! - `queueMicrotask`.
+//! - `Event`, `CustomEvent` and `EventTarget`, which objects made in Rust can inherit from by
+//!   prototype. [`dispatch_event`] lets the host dispatch events to them.
+//! - `MessageChannel`, `MessagePort` and `MessageEvent`, delivering structured clones of messages
+//!   by [`run_messages`] or [`run`]. A [`MessagePortHandle`] moves a port to another context or
+//!   thread.
+//! - `performance` with a monotonic `now` of configurable resolution, marks and measures, see
+//!   [`install_performance`].
+//! - `TextEncoder` and `TextDecoder` for UTF-8.
+//! - `ReadableStream` and `WritableStream` with their default readers, writers and controllers,
+//!   a subset of the streams standard. With the `futures` feature [`readable_stream`] and
+//!   [`writable_stream`] expose Rust `AsyncRead`ers and `AsyncWrite`rs to scripts as streams.
+//! - `Blob` and `File`, with `slice`, `arrayBuffer`, `bytes`, `text` and `stream` returning a
+//!   `ReadableStream` of `Uint8Array`s.
+//! - `FormData`, which hosts can serialize as a `multipart/form-data` body with [`Multipart`].
+//! - `atob` and `btoa`, implemented natively.
+//! - With the `crypto` feature, `crypto.subtle` with `digest` for SHA-1 and SHA-2 and raw HMAC
+//!   keys for `importKey`, `exportKey`, `sign` and `verify`, and a constant-time
+//!   `crypto.timingSafeEqual`.
+//! - `structuredClone`, using the serialization of QuickJS also used by [`channel`](crate::channel).
+//!
+//! There is no event loop running in the background, timers only fire while the host calls
+//! [`run_timers`] or [`run`] and messages are only delivered while it calls [`run_messages`] or
+//! [`run`]. The timers of all contexts of a runtime are kept together, like the job queue of the
+//! runtime.
+//!
+//! The [`Encoding`] module with hex and base64url helpers for `Uint8Array`s and the [`Events`]
+//! module with the Node.js `EventEmitter` can be declared separately, as can the [`Fs`] module for
+//! scoped file system access with the `fs` feature and the [`Process`] module for controlled access
+//! to the environment and arguments of the process with the `process` feature. The [`Subprocess`]
+//! module of the `subprocess` feature runs commands on the host allowed by a [`CommandPolicy`] and
+//! the [`Net`] module of the `net` feature opens TCP and UDP sockets to allow-listed addresses,
+//! which the [`WebSocket`] module of the `websocket` feature also uses for its clients. The
+//! [`Storage`] module of the `storage` feature gives scripts a key-value store with quotas and the
+//! [`Compression`] module of the `compression` feature compresses `Uint8Array`s with gzip and
+//! brotli.
+//!
+//! ```
+//! # use rquickjs::{Runtime, Context, web};
+//! let rt = Runtime::new().unwrap();
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     web::install(&ctx).unwrap();
+//!     ctx.eval::<(), _>(
+//!         r#"
+//!         globalThis.events = [];
+//!         setTimeout(() => events.push(btoa("timeout")), 5);
+//!         queueMicrotask(() => events.push("microtask"));
+//!         const bytes = new TextEncoder().encode("héllo");
+//!         events.push(bytes.length, new TextDecoder().decode(structuredClone(bytes)));
+//!     "#,
+//!     )
+//!     .unwrap();
+//!     web::run(&ctx).unwrap();
+//!     let events: Vec<String> = ctx.eval("events.map(String)").unwrap();
+//!     assert_eq!(events, ["6", "héllo", "microtask", "dGltZW91dA=="]);
+//! });
+//! ```
+
+use core::mem::MaybeUninit;
+use std::thread;
+
+use crate::{
+    channel::Message,
+    function::{Func, Opt},
+    qjs,
+    runtime::{record_metric, MetricsCounters},
+    Coerced, Ctx, Exception, Function, Result, StdString, TypedArray, Value,
+};
+
+mod base64;
+mod blob;
+#[cfg(feature = "futures")]
+mod blocking;
+#[cfg(feature = "compression")]
+mod compression;
+mod console;
+#[cfg(feature = "crypto")]
+mod crypto;
+mod encoding;
+mod events;
+mod form_data;
+#[cfg(feature = "fs")]
+mod fs;
+mod messaging;
+#[cfg(feature = "net")]
+mod net;
+mod performance;
+#[cfg(feature = "process")]
+mod process;
+#[cfg(feature = "storage")]
+mod storage;
+mod streams;
+#[cfg(feature = "subprocess")]
+mod subprocess;
+mod text;
+mod timers;
+#[cfg(feature = "websocket")]
+mod websocket;
+
+#[cfg(feature = "compression")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "compression")))]
+pub use compression::{Compression, DEFAULT_MAX_OUTPUT_LENGTH};
+pub use encoding::Encoding;
+pub use events::{dispatch_event, Events};
+pub use form_data::Multipart;
+#[cfg(feature = "fs")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "fs")))]
+pub use fs::{Fs, Permissions};
+pub use messaging::{run_messages, MessagePortHandle};
+#[cfg(feature = "net")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net")))]
+pub use net::Net;
+pub use performance::{install_performance, DEFAULT_RESOLUTION};
+#[cfg(feature = "process")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process")))]
+pub use process::Process;
+#[cfg(feature = "storage")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "storage")))]
+pub use storage::{Change, MemoryBackend, Storage, StorageBackend};
+#[cfg(feature = "futures")]
+pub use streams::{readable_stream, writable_stream};
+#[cfg(feature = "subprocess")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "subprocess")))]
+pub use subprocess::{Command, CommandPolicy, Decision, Subprocess, DEFAULT_OUTPUT_LIMIT};
+pub use timers::run_timers;
+#[cfg(feature = "websocket")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "websocket")))]
+pub use websocket::WebSocket;
+
+/// Define the web platform globals on the global object of the context.
+///
+/// Existing globals with the same names are replaced.
+pub fn install<'js>(ctx: &Ctx<'js>) -> Result<()> {
+    let globals = ctx.globals();
+    globals.set("console", console::console(ctx)?)?;
+    timers::install(ctx, &globals)?;
+    events::install(ctx, &globals)?;
+    messaging::install(ctx, &globals)?;
+    text::install(ctx, &globals)?;
+    streams::install(ctx, &globals)?;
+    let bytes_of = blob::install(ctx, &globals)?;
+    form_data::install(ctx, &globals, bytes_of)?;
+    #[cfg(feature = "crypto")]
+    crypto::install(ctx, &globals)?;
+    install_performance(ctx, DEFAULT_RESOLUTION)?;
+    globals.set("queueMicrotask", Func::from(queue_microtask))?;
+    globals.set("atob", Func::from(atob))?;
+    globals.set("btoa", Func::from(btoa))?;
+    globals.set("structuredClone", Func::from(structured_clone))?;
+    Ok(())
+}
+
+/// Run pending jobs, timers and received messages until there is nothing left to do.
+///
+/// Sleeps the current thread while waiting for the next timer, messages posted from another
+/// thread later are delivered by the next call. Returns the first exception thrown
+/// by a timer or a microtask, the remaining work is kept and can be resumed by calling `run`
+/// again.
+pub fn run<'js>(ctx: &Ctx<'js>) -> Result<()> {
+    loop {
+        run_jobs(ctx)?;
+        if run_messages(ctx)? {
+            continue;
+        }
+        match run_timers(ctx)? {
+            Some(wait) => thread::sleep(wait),
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Run all pending jobs, returning the exception if one of them threw.
+///
+/// Jobs of every context of the runtime are run, an exception thrown by a job of another context
+/// is moved to `ctx` so it can be retrieved with [`Ctx::catch`].
+fn run_jobs<'js>(ctx: &Ctx<'js>) -> Result<()> {
+    let rt = unsafe { qjs::JS_GetRuntime(ctx.as_ptr()) };
+    loop {
+        let mut job_ctx = MaybeUninit::<*mut qjs::JSContext>::uninit();
+        let res = unsafe { qjs::JS_ExecutePendingJob(rt, job_ctx.as_mut_ptr()) };
+        if res == 0 {
+            return Ok(());
+        }
+        let job_ctx = unsafe { job_ctx.assume_init() };
+        unsafe { record_metric(job_ctx, MetricsCounters::job_executed) };
+        if res < 0 {
+            if job_ctx != ctx.as_ptr() {
+                unsafe {
+                    let exception = qjs::JS_GetException(job_ctx);
+                    qjs::JS_Throw(ctx.as_ptr(), exception);
+                }
+            }
+            return Err(ctx.raise_exception());
+        }
+    }
+}
+
+/// Throw an error with the given name, as web APIs throw `DOMException`s with a name.
+fn throw_named<'js>(ctx: &Ctx<'js>, name: &str, message: &str) -> crate::Error {
+    match Exception::from_message(ctx.clone(), message) {
+        Ok(error) => match error.as_object().set("name", name) {
+            Ok(()) => error.throw(),
+            Err(e) => e,
+        },
+        Err(e) => e,
+    }
+}
+
+/// Returns the bytes of an array, throwing a `TypeError` if its buffer was detached.
+///
+/// A detached array can't be told apart from an empty one by its length, so the web APIs taking
+/// bytes reject it instead of silently using no data.
+fn array_bytes<'a>(ctx: &Ctx<'_>, array: &'a TypedArray<'_, u8>) -> Result<&'a [u8]> {
+    array
+        .as_bytes()
+        .ok_or_else(|| Exception::throw_type(ctx, "Cannot read a detached ArrayBuffer"))
+}
+
+fn queue_microtask<'js>(callback: Function<'js>) -> Result<()> {
+    callback.defer(())
+}
+
+fn atob<'js>(ctx: Ctx<'js>, data: Coerced<StdString>) -> Result<StdString> {
+    let bytes = base64::decode(&data.0, &base64::STANDARD).ok_or_else(|| {
+        throw_named(
+            &ctx,
+            "InvalidCharacterError",
+            "The string to be decoded is not correctly encoded.",
+        )
+    })?;
+    Ok(bytes.into_iter().map(char::from).collect())
+}
+
+fn btoa<'js>(ctx: Ctx<'js>, data: Coerced<StdString>) -> Result<StdString> {
+    let bytes = data
+        .0
+        .chars()
+        .map(|c| u8::try_from(u32::from(c)).ok())
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| {
+            throw_named(
+                &ctx,
+                "InvalidCharacterError",
+                "The string to be encoded contains characters outside of the Latin1 range.",
+            )
+        })?;
+    Ok(base64::encode(&bytes, &base64::STANDARD))
+}
+
+fn structured_clone<'js>(
+    ctx: Ctx<'js>,
+    value: Value<'js>,
+    _options: Opt<Value<'js>>,
+) -> Result<Value<'js>> {
+    let message = Message::write(&value).map_err(|e| match e {
+        crate::Error::Exception => {
+            let error = ctx.catch();
+            let message = error
+                .as_exception()
+                .and_then(Exception::message)
+                .unwrap_or_default();
+            throw_named(&ctx, "DataCloneError", &message)
+        }
+        e => e,
+    })?;
+    message.read(&ctx)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{web, *};
+
+    #[test]
+    fn globals() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"[
+                    atob(" aGVs bG8= "),
+                    btoa("\xff\x00"),
+                    (() => { try { btoa("€") } catch (e) { return e.name } })(),
+                    (() => { try { atob("a") } catch (e) { return e.name } })(),
+                    (() => { try { structuredClone(() => {}) } catch (e) { return e.name } })(),
+                    typeof console.log,
+                ]"#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "hello",
+                    "/wA=",
+                    "InvalidCharacterError",
+                    "InvalidCharacterError",
+                    "DataCloneError",
+                    "function"
+                ]
+            );
+
+            let res: bool = ctx
+                .eval(
+                    r#"
+                    const value = { date: new Date(0), list: [1, "a"], bytes: new Uint8Array([1, 2]) };
+                    value.self = value;
+                    const copy = structuredClone(value);
+                    copy !== value && copy.self === copy && copy.date.getTime() === 0
+                        && copy.list[1] === "a" && copy.bytes[1] === 2 && copy.bytes.buffer !== value.bytes.buffer
+                "#,
+                )
+                .unwrap();
+            assert!(res);
+        })
+    }
+
+    #[test]
+    fn run_reports_other_context_exception() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let other = Context::full(&rt).unwrap();
+        other.with(|ctx| {
+            let throw: Function = ctx.eval("() => { throw new Error('other') }").unwrap();
+            throw.defer(()).unwrap();
+        });
+        ctx.with(|ctx| {
+            web::install(&ctx).unwrap();
+            assert!(matches!(web::run(&ctx), Err(Error::Exception)));
+            let error = ctx.catch().into_exception().unwrap();
+            assert_eq!(error.message().as_deref(), Some("other"));
+        });
+    }
+}
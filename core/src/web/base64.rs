@@ -0,0 +1,92 @@
+//! Base64 as used by `atob`, `btoa` and the [`Encoding`](super::Encoding) module.
+
+/// The characters of a base64 variant and whether encoding adds padding.
+pub(super) struct Alphabet {
+    chars: &'static [u8; 64],
+    /// The value of every byte in the alphabet, `INVALID` for the other bytes.
+    values: [u8; 256],
+    padding: bool,
+}
+
+const INVALID: u8 = 0xff;
+
+impl Alphabet {
+    const fn new(chars: &'static [u8; 64], padding: bool) -> Self {
+        let mut values = [INVALID; 256];
+        let mut i = 0;
+        while i < chars.len() {
+            values[chars[i] as usize] = i as u8;
+            i += 1;
+        }
+        Self {
+            chars,
+            values,
+            padding,
+        }
+    }
+}
+
+/// The standard alphabet with padding.
+pub(super) static STANDARD: Alphabet = Alphabet::new(
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+    true,
+);
+
+/// The URL and filename safe alphabet without padding.
+pub(super) static URL: Alphabet = Alphabet::new(
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+    false,
+);
+
+/// Encode bytes as base64.
+pub(super) fn encode(bytes: &[u8], alphabet: &Alphabet) -> String {
+    let mut res = String::with_capacity(bytes.len() / 3 * 4 + 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(alphabet.chars[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else if alphabet.padding {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+/// Decode base64 with the forgiving rules of the HTML standard: ASCII whitespace is ignored and
+/// padding is optional. Returns `None` if the input is not valid base64.
+pub(super) fn decode(input: &str, alphabet: &Alphabet) -> Option<Vec<u8>> {
+    let mut data: Vec<u8> = input
+        .bytes()
+        .filter(|b| !matches!(b, b'\t' | b'\n' | b'\x0c' | b'\r' | b' '))
+        .collect();
+    if data.len() & 3 == 0 {
+        for _ in 0..2 {
+            if data.last() == Some(&b'=') {
+                data.pop();
+            }
+        }
+    }
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let mut res = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut bits = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = alphabet.values[c as usize];
+            if value == INVALID {
+                return None;
+            }
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            res.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(res)
+}
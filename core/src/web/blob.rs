@@ -0,0 +1,184 @@
+use super::text;
+use crate::{function::Func, Ctx, Function, Object, Result};
+
+/// The contents of a `Blob` are kept in a `WeakMap` and never change, so slices share the bytes
+/// of the blob they are taken from. Strings are encoded and decoded with the natives of
+/// `TextEncoder` and `TextDecoder`.
+const CLASSES: &str = r#"(native, ReadableStream) => {
+    const CHUNK_SIZE = 64 * 1024;
+    const contents = new WeakMap();
+    const contentsOf = (blob) => {
+        const res = contents.get(blob);
+        if (res === undefined) throw new TypeError("Illegal invocation");
+        return res;
+    };
+    const normalizeType = (type) => {
+        type = `${type}`;
+        return /^[\x20-\x7e]*$/.test(type) ? type.toLowerCase() : "";
+    };
+    const toBytes = (part, endings) => {
+        const blob = contents.get(part);
+        if (blob !== undefined) return blob.bytes;
+        if (part instanceof ArrayBuffer) return new Uint8Array(part.slice(0));
+        if (ArrayBuffer.isView(part)) return new Uint8Array(part.buffer, part.byteOffset, part.byteLength).slice();
+        part = `${part}`;
+        if (endings === "native") part = part.replace(/\r\n|\r|\n/g, native.lineEnding);
+        return native.encode(part);
+    };
+    const relative = (index, size) => {
+        index = Math.trunc(Number(index)) || 0;
+        return index < 0 ? Math.max(size + index, 0) : Math.min(index, size);
+    };
+
+    class Blob {
+        constructor(parts = [], options = {}) {
+            if (typeof parts !== "object" || parts === null || typeof parts[Symbol.iterator] !== "function") {
+                throw new TypeError("The parts must be a sequence");
+            }
+            options = options ?? {};
+            const endings = options.endings === undefined ? "transparent" : `${options.endings}`;
+            if (endings !== "transparent" && endings !== "native") {
+                throw new TypeError(`The endings must be "transparent" or "native"`);
+            }
+            const chunks = Array.from(parts, (part) => toBytes(part, endings));
+            const bytes = new Uint8Array(chunks.reduce((size, chunk) => size + chunk.length, 0));
+            let offset = 0;
+            for (const chunk of chunks) {
+                bytes.set(chunk, offset);
+                offset += chunk.length;
+            }
+            contents.set(this, { bytes, type: normalizeType(options.type ?? "") });
+        }
+        get size() { return contentsOf(this).bytes.length; }
+        get type() { return contentsOf(this).type; }
+        slice(start = 0, end, contentType = "") {
+            const { bytes } = contentsOf(this);
+            start = relative(start, bytes.length);
+            end = end === undefined ? bytes.length : relative(end, bytes.length);
+            const res = new Blob();
+            contents.set(res, { bytes: bytes.subarray(start, Math.max(start, end)), type: normalizeType(contentType) });
+            return res;
+        }
+        async arrayBuffer() { return contentsOf(this).bytes.slice().buffer; }
+        async bytes() { return contentsOf(this).bytes.slice(); }
+        async text() { return native.decode(contentsOf(this).bytes, false, false); }
+        stream() {
+            const { bytes } = contentsOf(this);
+            let offset = 0;
+            return new ReadableStream({
+                pull(controller) {
+                    if (offset >= bytes.length) return controller.close();
+                    controller.enqueue(bytes.slice(offset, offset + CHUNK_SIZE));
+                    offset += CHUNK_SIZE;
+                },
+            }, { highWaterMark: 0 });
+        }
+        get [Symbol.toStringTag]() { return "Blob"; }
+    }
+
+    class File extends Blob {
+        #name;
+        #lastModified;
+        constructor(bits, name, options = {}) {
+            if (arguments.length < 2) throw new TypeError("The file bits and name are required");
+            super(bits, options);
+            options = options ?? {};
+            this.#name = `${name}`;
+            this.#lastModified = options.lastModified === undefined ? Date.now() : Math.trunc(Number(options.lastModified)) || 0;
+        }
+        get name() { return this.#name; }
+        get lastModified() { return this.#lastModified; }
+        get [Symbol.toStringTag]() { return "File"; }
+    }
+
+    return { Blob, File, bytesOf: (blob) => contentsOf(blob).bytes };
+}"#;
+
+/// The line ending `endings: "native"` converts to.
+const LINE_ENDING: &str = if cfg!(windows) { "\r\n" } else { "\n" };
+
+/// Define `Blob` and `File` on the global object, after `ReadableStream` is defined.
+///
+/// Returns a function giving the bytes of a blob, for other classes to read blobs synchronously.
+pub(super) fn install<'js>(ctx: &Ctx<'js>, globals: &Object<'js>) -> Result<Function<'js>> {
+    let native = Object::new(ctx.clone())?;
+    native.set("encode", Func::from(text::encode))?;
+    native.set("decode", Func::from(text::decode))?;
+    native.set("lineEnding", LINE_ENDING)?;
+    let define: Function = ctx.eval(CLASSES)?;
+    let classes: Object = define.call((native, globals.get::<_, Function>("ReadableStream")?))?;
+    globals.set("Blob", classes.get::<_, Object>("Blob")?)?;
+    globals.set("File", classes.get::<_, Object>("File")?)?;
+    classes.get("bytesOf")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{web, *};
+
+    #[test]
+    fn blob_and_file() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                const error = (f) => { try { f(); return "returned"; } catch (e) { return e.name; } };
+                (async () => {
+                    const blob = new Blob(["héllo ", new Uint8Array([119, 111]).buffer, new Uint8Array([0, 114, 108, 100]).subarray(1)], { type: "Text/Plain" });
+                    const nested = new Blob([blob, "!"]);
+                    const file = new File([nested], "hello.txt", { type: "text/plain", lastModified: 42 });
+                    const chunks = [];
+                    for await (const chunk of new Blob([new Uint8Array(70000)]).stream()) chunks.push(chunk.length);
+                    globalThis.res = [
+                        `${blob.size} ${blob.type} ${nested.type === ""}`,
+                        await blob.text(),
+                        await nested.slice(7, -1).text(),
+                        await nested.slice(-6, 100, "TEXT/X").text(),
+                        nested.slice(5, 2).size,
+                        new Uint8Array(await blob.slice(0, 2).arrayBuffer()).join(),
+                        (await blob.bytes()) instanceof Uint8Array,
+                        `${file.name} ${file.lastModified} ${file.type} ${file.size}`,
+                        await file.text(),
+                        `${file instanceof Blob} ${file.slice() instanceof File} ${Object.prototype.toString.call(file)}`,
+                        typeof new File([], "now").lastModified,
+                        new Blob(["a\r\nb\rc"], { endings: "native" }).size,
+                        new Blob(["x"], { type: "badĀtype" }).type === "",
+                        chunks.join(),
+                        error(() => new Blob("abc")),
+                        error(() => new Blob([], { endings: "other" })),
+                        error(() => new File([])),
+                        error(() => Blob.prototype.slice.call({})),
+                    ].map(String);
+                })();
+            "#,
+            )
+            .unwrap();
+            web::run(&ctx).unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            let native_size = if cfg!(windows) { "7" } else { "5" };
+            assert_eq!(
+                res,
+                [
+                    "12 text/plain true",
+                    "héllo world",
+                    "world",
+                    "world!",
+                    "0",
+                    "104,195",
+                    "true",
+                    "hello.txt 42 text/plain 13",
+                    "héllo world!",
+                    "true false [object File]",
+                    "number",
+                    native_size,
+                    "true",
+                    "65536,4464",
+                    "TypeError",
+                    "TypeError",
+                    "TypeError",
+                    "TypeError",
+                ]
+            );
+        })
+    }
+}
@@ -0,0 +1,54 @@
+//! Blocking work running on its own thread, awaited as a future.
+
+use alloc::sync::Arc;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context as TaskContext, Poll, Waker},
+};
+use std::{sync::Mutex, thread};
+
+struct Completion<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// The result of work running on another thread.
+pub(super) struct Spawned<T>(Arc<Mutex<Completion<T>>>);
+
+impl<T> Future for Spawned<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<T> {
+        let mut completion = self.0.lock().unwrap();
+        match completion.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                completion.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Run `work` on a new thread, the returned future resolves to its result.
+pub(super) fn spawn<T, F>(work: F) -> Spawned<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let completion = Arc::new(Mutex::new(Completion {
+        output: None,
+        waker: None,
+    }));
+    let shared = completion.clone();
+    thread::spawn(move || {
+        let output = work();
+        let mut completion = shared.lock().unwrap();
+        completion.output = Some(output);
+        if let Some(waker) = completion.waker.take() {
+            waker.wake();
+        }
+    });
+    Spawned(completion)
+}
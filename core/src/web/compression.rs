@@ -0,0 +1,453 @@
+use std::io::{self, Read, Write};
+
+#[cfg(feature = "futures")]
+use super::blocking;
+#[cfg(feature = "futures")]
+use crate::promise::Promised;
+use crate::{
+    function::Opt,
+    module::{Declarations, Declared, Exports, ModuleDef},
+    Ctx, Exception, Function, Module, Object, Result, TypedArray,
+};
+
+/// The default limit on the size of decompressed data, which scripts can lower with
+/// `maxOutputLength`.
+pub const DEFAULT_MAX_OUTPUT_LENGTH: usize = 64 * 1024 * 1024;
+
+/// A native module compressing and decompressing `Uint8Array`s with gzip and brotli.
+///
+/// The module exports the following functions, each taking an `Uint8Array` and an optional
+/// options object and returning a new `Uint8Array`:
+///
+/// - `gzipSync` and `gunzipSync`, compressing takes a `level` between 0 and 9, defaulting to 6.
+/// - `brotliCompressSync` and `brotliDecompressSync`, compressing takes a `quality` between 0
+///   and 11, defaulting to 11.
+///
+/// Decompressing takes a `maxOutputLength` in bytes and throws a `RangeError` if the decompressed
+/// data is larger. It defaults to the maximum set by the host with
+/// [`Compression::set_max_output_length`], [`DEFAULT_MAX_OUTPUT_LENGTH`] unless changed, and
+/// scripts can only lower it. Invalid compressed data throws an `Error`.
+///
+/// With the `futures` feature the module also exports `gzip`, `gunzip`, `brotliCompress` and
+/// `brotliDecompress`, which do the work on a separate thread and return a promise.
+///
+/// The module is not registered by [`install`](super::install), it has to be declared under a
+/// name of choice with [`Compression::declare`], or with the default maximum with
+/// [`Module::declare_def`](crate::Module::declare_def) or a module loader.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, web::Compression};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     Module::declare_def::<Compression, _>(ctx.clone(), "compression").unwrap();
+///     Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { gzipSync, gunzipSync } from "compression";
+///         const data = new Uint8Array(1000).fill(42);
+///         const compressed = gzipSync(data, { level: 9 });
+///         globalThis.res = [compressed.length < 100, gunzipSync(compressed).every((x) => x === 42)];
+///     "#,
+///     )
+///     .unwrap()
+///     .finish::<()>()
+///     .unwrap();
+///     assert_eq!(ctx.globals().get::<_, Vec<bool>>("res").unwrap(), [true, true]);
+/// });
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Compression {
+    max_output_length: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression {
+            max_output_length: DEFAULT_MAX_OUTPUT_LENGTH,
+        }
+    }
+}
+
+impl Compression {
+    /// Create a module with the default maximum size of decompressed data.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum size of decompressed data in bytes, which `maxOutputLength` can't exceed.
+    pub fn set_max_output_length(&mut self, length: usize) -> &mut Self {
+        self.max_output_length = length;
+        self
+    }
+
+    /// Set the maximum size of decompressed data in bytes, which `maxOutputLength` can't exceed.
+    #[must_use]
+    pub fn with_max_output_length(mut self, length: usize) -> Self {
+        self.set_max_output_length(length);
+        self
+    }
+
+    /// Declare the module under the given name, for example from a module loader.
+    pub fn declare<'js, N>(&self, ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let exports = Object::new(ctx.clone())?;
+        let mut names = Vec::new();
+        for (export, function) in self.functions(&ctx)? {
+            exports.set(export, function)?;
+            names.push(export.to_string());
+        }
+        Module::declare_value(ctx, name, exports.into_value(), names)
+    }
+
+    fn functions<'js>(&self, ctx: &Ctx<'js>) -> Result<Vec<(&'static str, Function<'js>)>> {
+        let max = self.max_output_length;
+        let mut res = Vec::new();
+        for (name, codec, mode) in SYNC {
+            let function = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'js>, data: TypedArray<'js, u8>, options: Opt<Object<'js>>| {
+                    let job = Job::new(&ctx, codec, mode, max, options.0)?;
+                    let res = job.run(data.as_bytes().unwrap_or_default());
+                    finish(ctx, res)
+                },
+            )?;
+            res.push((name, function));
+        }
+        #[cfg(feature = "futures")]
+        for (name, codec, mode) in ASYNC {
+            let function = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'js>, data: TypedArray<'js, u8>, options: Opt<Object<'js>>| {
+                    let job = Job::new(&ctx, codec, mode, max, options.0);
+                    let data = data.as_bytes().unwrap_or_default().to_vec();
+                    Promised(async move {
+                        let job = job?;
+                        let res = blocking::spawn(move || job.run(&data)).await;
+                        finish(ctx, res)
+                    })
+                },
+            )?;
+            res.push((name, function));
+        }
+        Ok(res)
+    }
+}
+
+const SYNC: [(&str, Codec, Mode); 4] = [
+    ("gzipSync", Codec::Gzip, Mode::Compress),
+    ("gunzipSync", Codec::Gzip, Mode::Decompress),
+    ("brotliCompressSync", Codec::Brotli, Mode::Compress),
+    ("brotliDecompressSync", Codec::Brotli, Mode::Decompress),
+];
+
+#[cfg(feature = "futures")]
+const ASYNC: [(&str, Codec, Mode); 4] = [
+    ("gzip", Codec::Gzip, Mode::Compress),
+    ("gunzip", Codec::Gzip, Mode::Decompress),
+    ("brotliCompress", Codec::Brotli, Mode::Compress),
+    ("brotliDecompress", Codec::Brotli, Mode::Decompress),
+];
+
+impl ModuleDef for Compression {
+    fn declare<'js>(decl: &Declarations<'js>) -> Result<()> {
+        for (name, _, _) in SYNC {
+            decl.declare(name)?;
+        }
+        #[cfg(feature = "futures")]
+        for (name, _, _) in ASYNC {
+            decl.declare(name)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate<'js>(ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
+        for (name, function) in Compression::default().functions(ctx)? {
+            exports.export(name, function)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Codec {
+    Gzip,
+    Brotli,
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Compress,
+    Decompress,
+}
+
+/// Why decompressing failed.
+enum Failure {
+    Invalid(Codec, io::Error),
+    TooLarge(usize),
+}
+
+/// A compression or decompression with its options checked.
+struct Job {
+    codec: Codec,
+    mode: Mode,
+    level: u32,
+    max_output_length: usize,
+}
+
+impl Job {
+    fn new<'js>(
+        ctx: &Ctx<'js>,
+        codec: Codec,
+        mode: Mode,
+        max_output_length: usize,
+        options: Option<Object<'js>>,
+    ) -> Result<Self> {
+        let (name, max_level, default_level) = match codec {
+            Codec::Gzip => ("level", 9, 6),
+            Codec::Brotli => ("quality", 11, 11),
+        };
+        let mut job = Job {
+            codec,
+            mode,
+            level: default_level,
+            max_output_length,
+        };
+        let Some(options) = options else {
+            return Ok(job);
+        };
+        if let Some(level) = options.get::<_, Option<i64>>(name)? {
+            job.level = u32::try_from(level)
+                .ok()
+                .filter(|level| *level <= max_level)
+                .ok_or_else(|| {
+                    Exception::throw_range(
+                        ctx,
+                        &format!("The {name} must be between 0 and {max_level}"),
+                    )
+                })?;
+        }
+        if let Some(length) = options.get::<_, Option<i64>>("maxOutputLength")? {
+            job.max_output_length = usize::try_from(length)
+                .ok()
+                .filter(|length| *length <= max_output_length)
+                .ok_or_else(|| {
+                    Exception::throw_range(
+                        ctx,
+                        &format!("The maxOutputLength must be between 0 and {max_output_length}"),
+                    )
+                })?;
+        }
+        Ok(job)
+    }
+
+    fn run(&self, data: &[u8]) -> core::result::Result<Vec<u8>, Failure> {
+        match self.mode {
+            Mode::Compress => Ok(self.compress(data)),
+            Mode::Decompress => self.decompress(data),
+        }
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        match self.codec {
+            Codec::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(self.level));
+                encoder
+                    .write_all(data)
+                    .expect("writing to a Vec can't fail");
+                encoder.finish().expect("writing to a Vec can't fail")
+            }
+            Codec::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, self.level, 22);
+                encoder
+                    .write_all(data)
+                    .expect("writing to a Vec can't fail");
+                encoder.into_inner()
+            }
+        }
+    }
+
+    fn decompress(&self, data: &[u8]) -> core::result::Result<Vec<u8>, Failure> {
+        let decoder: Box<dyn Read> = match self.codec {
+            Codec::Gzip => Box::new(flate2::read::MultiGzDecoder::new(data)),
+            Codec::Brotli => Box::new(brotli::Decompressor::new(data, 4096)),
+        };
+        // Read one byte past the limit to tell when the output is too large.
+        let mut res = Vec::new();
+        decoder
+            .take(self.max_output_length as u64 + 1)
+            .read_to_end(&mut res)
+            .map_err(|e| Failure::Invalid(self.codec, e))?;
+        if res.len() > self.max_output_length {
+            return Err(Failure::TooLarge(self.max_output_length));
+        }
+        Ok(res)
+    }
+}
+
+fn finish<'js>(
+    ctx: Ctx<'js>,
+    res: core::result::Result<Vec<u8>, Failure>,
+) -> Result<TypedArray<'js, u8>> {
+    match res {
+        Ok(data) => TypedArray::new(ctx, data),
+        Err(Failure::Invalid(codec, error)) => {
+            let name = match codec {
+                Codec::Gzip => "gzip",
+                Codec::Brotli => "brotli",
+            };
+            Err(Exception::throw_message(
+                &ctx,
+                &format!("The {name} data is invalid: {error}"),
+            ))
+        }
+        Err(Failure::TooLarge(limit)) => Err(Exception::throw_range(
+            &ctx,
+            &format!("The decompressed data is larger than {limit} bytes"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        web::{self, Compression},
+        *,
+    };
+
+    #[test]
+    fn compression_module() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            Module::declare_def::<Compression, _>(ctx.clone(), "compression").unwrap();
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import { gzipSync, gunzipSync, brotliCompressSync, brotliDecompressSync } from "compression";
+                const text = new TextEncoder().encode("hello ".repeat(100));
+                const decode = (data) => new TextDecoder().decode(data);
+                const error = (f) => { try { f(); return "returned"; } catch (e) { return `${e.name}: ${e.message}`; } };
+                const gzipped = gzipSync(text);
+                const brotli = brotliCompressSync(text, { quality: 5 });
+                globalThis.res = [
+                    gzipped[0] === 0x1f && gzipped[1] === 0x8b,
+                    gzipped.length < 100,
+                    decode(gunzipSync(gzipped)) === "hello ".repeat(100),
+                    decode(gunzipSync(gzipSync(text, { level: 0 }))) === "hello ".repeat(100),
+                    brotli.length < 100,
+                    decode(brotliDecompressSync(brotli)) === "hello ".repeat(100),
+                    gunzipSync(gzipSync(new Uint8Array())).length,
+                    gunzipSync(gzipped, { maxOutputLength: 600 }).length,
+                    error(() => gunzipSync(gzipped, { maxOutputLength: 599 })),
+                    error(() => brotliDecompressSync(brotli, { maxOutputLength: 10 })),
+                    error(() => gunzipSync(text)).startsWith("Error: The gzip data is invalid"),
+                    error(() => brotliDecompressSync(text)).startsWith("Error: The brotli data is invalid"),
+                    error(() => gzipSync(text, { level: 10 })),
+                    error(() => brotliCompressSync(text, { quality: -1 })),
+                    error(() => gunzipSync(gzipped, { maxOutputLength: -1 })).startsWith("RangeError: The maxOutputLength must be between 0 and "),
+                ].map(String);
+            "#,
+            )
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "true",
+                    "true",
+                    "true",
+                    "true",
+                    "true",
+                    "true",
+                    "0",
+                    "600",
+                    "RangeError: The decompressed data is larger than 599 bytes",
+                    "RangeError: The decompressed data is larger than 10 bytes",
+                    "true",
+                    "true",
+                    "RangeError: The level must be between 0 and 9",
+                    "RangeError: The quality must be between 0 and 11",
+                    "true",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn host_max_output_length() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            Compression::new()
+                .with_max_output_length(100)
+                .declare(ctx.clone(), "compression")
+                .unwrap();
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import { gzipSync, gunzipSync } from "compression";
+                const error = (f) => { try { f(); return "returned"; } catch (e) { return `${e.name}: ${e.message}`; } };
+                const gzipped = gzipSync(new Uint8Array(200));
+                globalThis.res = [
+                    gunzipSync(gzipSync(new Uint8Array(100))).length,
+                    error(() => gunzipSync(gzipped)),
+                    error(() => gunzipSync(gzipped, { maxOutputLength: 1000 })),
+                    error(() => gunzipSync(gzipped, { maxOutputLength: 10 })),
+                ].map(String);
+            "#,
+            )
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "100",
+                    "RangeError: The decompressed data is larger than 100 bytes",
+                    "RangeError: The maxOutputLength must be between 0 and 100",
+                    "RangeError: The decompressed data is larger than 10 bytes",
+                ]
+            );
+        })
+    }
+
+    #[cfg(feature = "futures")]
+    #[tokio::test]
+    async fn async_compression() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            web::install(&ctx).unwrap();
+            Module::declare_def::<Compression, _>(ctx.clone(), "compression").unwrap();
+            let promise: Promise = Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import { gzip, gunzip, brotliCompress, brotliDecompress } from "compression";
+                const text = new TextEncoder().encode("hello ".repeat(100));
+                const decode = (data) => new TextDecoder().decode(data);
+                globalThis.res = [
+                    decode(await gunzip(await gzip(text))) === "hello ".repeat(100),
+                    decode(await brotliDecompress(await brotliCompress(text))) === "hello ".repeat(100),
+                    await gunzip(text).catch((e) => e.message.startsWith("The gzip data is invalid")),
+                    await gunzip(await gzip(text), { maxOutputLength: 1 }).catch((e) => e.name),
+                    await gzip(text, { level: 12 }).catch((e) => e.name),
+                ].map(String);
+            "#,
+            )
+            .unwrap();
+            promise.into_future::<()>().await.unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(res, ["true", "true", "true", "RangeError", "RangeError"]);
+        })
+        .await;
+    }
+}
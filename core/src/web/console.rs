@@ -0,0 +1,57 @@
+use std::io::{self, Write};
+
+use crate::{
+    context::ContextData,
+    function::{Func, Opt, Rest},
+    Coerced, Ctx, Object, Result, StdString,
+};
+
+/// Join the arguments of a console call into a line.
+fn format(args: Rest<Coerced<StdString>>) -> StdString {
+    args.0
+        .into_iter()
+        .map(|x| x.0)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Write a line to the output captured by [`Ctx::eval_capture`] if it is running, otherwise to
+/// `out`.
+fn write_line(ctx: &Ctx<'_>, mut out: impl Write, line: StdString) {
+    if let Some(data) = unsafe { ContextData::get(ctx.as_ptr()) } {
+        if let Some(output) = data.console_capture.borrow_mut().as_mut() {
+            output.push(line);
+            return;
+        }
+    }
+    // Output can't be reported back to the script, a closed stdout is ignored like in browsers.
+    let _ = writeln!(out, "{line}");
+}
+
+fn stdout(ctx: Ctx<'_>, args: Rest<Coerced<StdString>>) {
+    write_line(&ctx, io::stdout(), format(args));
+}
+
+fn stderr(ctx: Ctx<'_>, args: Rest<Coerced<StdString>>) {
+    write_line(&ctx, io::stderr(), format(args));
+}
+
+fn assert(ctx: Ctx<'_>, condition: Opt<Coerced<bool>>, mut args: Rest<Coerced<StdString>>) {
+    if !condition.0.map(|x| x.0).unwrap_or(false) {
+        args.0.insert(0, Coerced("Assertion failed:".into()));
+        stderr(ctx, args);
+    }
+}
+
+/// Create the `console` object.
+pub(super) fn console<'js>(ctx: &Ctx<'js>) -> Result<Object<'js>> {
+    let console = Object::new(ctx.clone())?;
+    for name in ["log", "info", "debug"] {
+        console.set(name, Func::from(stdout))?;
+    }
+    for name in ["warn", "error", "trace"] {
+        console.set(name, Func::from(stderr))?;
+    }
+    console.set("assert", Func::from(assert))?;
+    Ok(console)
+}
@@ -0,0 +1,317 @@
+use hmac::{
+    digest::{core_api::BlockSizeUser, Digest},
+    Mac, SimpleHmac,
+};
+use subtle::ConstantTimeEq;
+
+use super::{array_bytes, throw_named};
+use crate::{function::Func, ArrayBuffer, Ctx, Function, Object, Result, StdString, TypedArray};
+
+/// `crypto.subtle` is defined in JavaScript around native functions doing the hashing. The
+/// material, algorithm and usages of keys are kept in a `WeakMap` so `CryptoKey`s don't expose
+/// them, and they are checked with intrinsics captured when the globals are defined, so scripts
+/// replacing the accessors of `CryptoKey` or methods like `Array.prototype.includes` can't use a
+/// key beyond its usages.
+const CRYPTO: &str = r#"(native) => {
+    const uncurry = Function.prototype.bind.bind(Function.prototype.call);
+    const getRecord = uncurry(WeakMap.prototype.get);
+    const setRecord = uncurry(WeakMap.prototype.set);
+    const includes = uncurry(Array.prototype.includes);
+    const toLowerCase = uncurry(String.prototype.toLowerCase);
+    const slice = uncurry(Uint8Array.prototype.slice);
+    const toString = String;
+    const token = Symbol();
+    const records = new WeakMap();
+    const named = (name, message) => Object.assign(new Error(message), { name });
+    const toBytes = (data) => {
+        if (data instanceof ArrayBuffer) return new Uint8Array(data);
+        if (ArrayBuffer.isView(data)) return new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+        throw new TypeError("The data must be an ArrayBuffer or an ArrayBufferView");
+    };
+    const normalize = (algorithm, names) => {
+        const name = toString(typeof algorithm === "string" ? algorithm : algorithm?.name);
+        for (let i = 0; i < names.length; i++) {
+            if (toLowerCase(names[i]) === toLowerCase(name)) return names[i];
+        }
+        throw named("NotSupportedError", `Unrecognized algorithm name: ${name}`);
+    };
+    const hashes = ["SHA-1", "SHA-256", "SHA-384", "SHA-512"];
+
+    class CryptoKey {
+        #type;
+        #extractable;
+        #algorithm;
+        #usages;
+        constructor(key, type, extractable, algorithm, usages, secret) {
+            if (key !== token) throw new TypeError("Illegal constructor");
+            this.#type = type;
+            this.#extractable = extractable;
+            this.#algorithm = algorithm;
+            this.#usages = usages;
+            setRecord(records, this, {
+                __proto__: null,
+                secret,
+                extractable,
+                name: algorithm.name,
+                hash: algorithm.hash.name,
+                usages: [...usages],
+            });
+        }
+        get type() { return this.#type; }
+        get extractable() { return this.#extractable; }
+        get algorithm() { return { ...this.#algorithm, hash: { ...this.#algorithm.hash } }; }
+        get usages() { return [...this.#usages]; }
+    }
+
+    const recordOf = (key) => {
+        const record = getRecord(records, key);
+        if (record === undefined) throw new TypeError("The key is not a CryptoKey");
+        return record;
+    };
+    const usableRecordOf = (key, algorithm, usage) => {
+        const record = recordOf(key);
+        if (normalize(algorithm, ["HMAC"]) !== record.name) {
+            throw named("InvalidAccessError", "The algorithm doesn't match the key");
+        }
+        if (!includes(record.usages, usage)) throw named("InvalidAccessError", `The key doesn't support ${usage}`);
+        return record;
+    };
+
+    class SubtleCrypto {
+        constructor(key) {
+            if (key !== token) throw new TypeError("Illegal constructor");
+        }
+        async digest(algorithm, data) {
+            return native.digest(normalize(algorithm, hashes), toBytes(data));
+        }
+        async importKey(format, keyData, algorithm, extractable, usages) {
+            if (format !== "raw") throw named("NotSupportedError", `Unsupported key format: ${format}`);
+            normalize(algorithm, ["HMAC"]);
+            const hash = normalize(algorithm.hash, hashes);
+            usages = Array.from(usages, String);
+            if (usages.some((x) => x !== "sign" && x !== "verify")) throw new SyntaxError("HMAC keys only support sign and verify");
+            const secret = toBytes(keyData).slice();
+            const length = algorithm.length ?? secret.length * 8;
+            if (secret.length === 0 || length > secret.length * 8 || length <= (secret.length - 1) * 8) {
+                throw named("DataError", "The key length is invalid");
+            }
+            return new CryptoKey(token, "secret", !!extractable, { name: "HMAC", hash: { name: hash }, length }, usages, secret);
+        }
+        async exportKey(format, key) {
+            const record = recordOf(key);
+            if (format !== "raw") throw named("NotSupportedError", `Unsupported key format: ${format}`);
+            if (!record.extractable) throw named("InvalidAccessError", "The key is not extractable");
+            return slice(record.secret).buffer;
+        }
+        async sign(algorithm, key, data) {
+            const record = usableRecordOf(key, algorithm, "sign");
+            return native.sign(record.hash, record.secret, toBytes(data));
+        }
+        async verify(algorithm, key, signature, data) {
+            const record = usableRecordOf(key, algorithm, "verify");
+            return native.verify(record.hash, record.secret, toBytes(signature), toBytes(data));
+        }
+    }
+
+    const crypto = {
+        subtle: new SubtleCrypto(token),
+        timingSafeEqual(a, b) {
+            a = toBytes(a);
+            b = toBytes(b);
+            if (a.length !== b.length) throw new RangeError("The buffers must have the same length");
+            return native.equal(a, b);
+        },
+    };
+    return { crypto, CryptoKey, SubtleCrypto };
+}"#;
+
+/// The hash functions of `crypto.subtle`.
+#[derive(Clone, Copy)]
+enum Hash {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Hash {
+    fn from_name<'js>(ctx: &Ctx<'js>, name: &str) -> Result<Self> {
+        Ok(match name {
+            "SHA-1" => Hash::Sha1,
+            "SHA-256" => Hash::Sha256,
+            "SHA-384" => Hash::Sha384,
+            "SHA-512" => Hash::Sha512,
+            _ => {
+                return Err(throw_named(
+                    ctx,
+                    "NotSupportedError",
+                    &format!("Unrecognized algorithm name: {name}"),
+                ))
+            }
+        })
+    }
+
+    fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Hash::Sha1 => sha1::Sha1::digest(data).to_vec(),
+            Hash::Sha256 => sha2::Sha256::digest(data).to_vec(),
+            Hash::Sha384 => sha2::Sha384::digest(data).to_vec(),
+            Hash::Sha512 => sha2::Sha512::digest(data).to_vec(),
+        }
+    }
+
+    fn hmac(self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        fn hmac<D: Digest + BlockSizeUser>(key: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut mac = SimpleHmac::<D>::new_from_slice(key).expect("HMAC accepts any key");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        match self {
+            Hash::Sha1 => hmac::<sha1::Sha1>(key, data),
+            Hash::Sha256 => hmac::<sha2::Sha256>(key, data),
+            Hash::Sha384 => hmac::<sha2::Sha384>(key, data),
+            Hash::Sha512 => hmac::<sha2::Sha512>(key, data),
+        }
+    }
+}
+
+fn digest<'js>(
+    ctx: Ctx<'js>,
+    hash: StdString,
+    data: TypedArray<'js, u8>,
+) -> Result<ArrayBuffer<'js>> {
+    let hash = Hash::from_name(&ctx, &hash)?;
+    let digest = hash.digest(array_bytes(&ctx, &data)?);
+    ArrayBuffer::new(ctx, digest)
+}
+
+fn sign<'js>(
+    ctx: Ctx<'js>,
+    hash: StdString,
+    key: TypedArray<'js, u8>,
+    data: TypedArray<'js, u8>,
+) -> Result<ArrayBuffer<'js>> {
+    let hash = Hash::from_name(&ctx, &hash)?;
+    let mac = hash.hmac(array_bytes(&ctx, &key)?, array_bytes(&ctx, &data)?);
+    ArrayBuffer::new(ctx, mac)
+}
+
+fn verify<'js>(
+    ctx: Ctx<'js>,
+    hash: StdString,
+    key: TypedArray<'js, u8>,
+    signature: TypedArray<'js, u8>,
+    data: TypedArray<'js, u8>,
+) -> Result<bool> {
+    let hash = Hash::from_name(&ctx, &hash)?;
+    let expected = hash.hmac(array_bytes(&ctx, &key)?, array_bytes(&ctx, &data)?);
+    Ok(expected.ct_eq(array_bytes(&ctx, &signature)?).into())
+}
+
+fn equal<'js>(ctx: Ctx<'js>, a: TypedArray<'js, u8>, b: TypedArray<'js, u8>) -> Result<bool> {
+    Ok(array_bytes(&ctx, &a)?.ct_eq(array_bytes(&ctx, &b)?).into())
+}
+
+/// Define `crypto`, `CryptoKey` and `SubtleCrypto` on the global object.
+pub(super) fn install<'js>(ctx: &Ctx<'js>, globals: &Object<'js>) -> Result<()> {
+    let native = Object::new(ctx.clone())?;
+    native.set("digest", Func::from(digest))?;
+    native.set("sign", Func::from(sign))?;
+    native.set("verify", Func::from(verify))?;
+    native.set("equal", Func::from(equal))?;
+    let define: Function = ctx.eval(CRYPTO)?;
+    let defined: Object = define.call((native,))?;
+    for name in ["crypto", "CryptoKey", "SubtleCrypto"] {
+        globals.set(name, defined.get::<_, Object>(name)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{web, *};
+
+    #[test]
+    fn subtle_crypto() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                const hex = (buffer) => Array.from(new Uint8Array(buffer), (x) => x.toString(16).padStart(2, "0")).join("");
+                const bytes = (text) => new TextEncoder().encode(text);
+                const error = (promise) => promise.then(() => "resolved", (e) => `${e.name}: ${e.message}`);
+                (async () => {
+                    const key = await crypto.subtle.importKey("raw", bytes("Jefe"), { name: "HMAC", hash: "SHA-256" }, false, ["sign", "verify"]);
+                    const signature = await crypto.subtle.sign("HMAC", key, bytes("what do ya want for nothing?"));
+                    const sha512 = await crypto.subtle.importKey("raw", bytes("Jefe"), { name: "hmac", hash: { name: "sha-512" } }, true, ["verify"]);
+                    const res = [
+                        hex(await crypto.subtle.digest("SHA-256", bytes("abc"))),
+                        hex(await crypto.subtle.digest({ name: "sha-1" }, bytes("abc").buffer)),
+                        (await crypto.subtle.digest("SHA-384", new Uint8Array())).byteLength,
+                        hex(signature),
+                        await crypto.subtle.verify("HMAC", key, signature, bytes("what do ya want for nothing?")),
+                        await crypto.subtle.verify("HMAC", key, signature, bytes("what do ya want for something?")),
+                        await crypto.subtle.verify("HMAC", key, signature.slice(1), bytes("what do ya want for nothing?")),
+                        JSON.stringify([key.type, key.extractable, key.algorithm, key.usages]),
+                        new TextDecoder().decode(await crypto.subtle.exportKey("raw", sha512)),
+                        await error(crypto.subtle.exportKey("raw", key)),
+                        await error(crypto.subtle.sign("HMAC", sha512, bytes(""))),
+                        await error(crypto.subtle.digest("MD5", bytes(""))),
+                        await error(crypto.subtle.importKey("raw", new Uint8Array(), { name: "HMAC", hash: "SHA-1" }, false, ["sign"])),
+                        crypto.timingSafeEqual(bytes("secret"), bytes("secret")),
+                        crypto.timingSafeEqual(bytes("secret"), bytes("Secret")),
+                        await error((async () => crypto.timingSafeEqual(bytes("a"), bytes("ab")))()),
+                        await error((async () => new CryptoKey())()),
+                    ];
+                    // Neither the accessors nor the intrinsics decide how a key can be used.
+                    const includes = Array.prototype.includes;
+                    Array.prototype.includes = () => true;
+                    Object.defineProperty(CryptoKey.prototype, "usages", { get: () => ["sign", "verify"] });
+                    Object.defineProperty(CryptoKey.prototype, "extractable", { get: () => true });
+                    res.push(
+                        await error(crypto.subtle.sign("HMAC", sha512, bytes(""))),
+                        await error(crypto.subtle.exportKey("raw", key)),
+                    );
+                    Array.prototype.includes = includes;
+                    const detached = bytes("abc");
+                    detached.buffer.transfer();
+                    res.push(
+                        await error(crypto.subtle.digest("SHA-256", detached)),
+                        await error((async () => crypto.timingSafeEqual(detached, detached))()),
+                    );
+                    globalThis.res = res.map(String);
+                })();
+            "#,
+            )
+            .unwrap();
+            web::run(&ctx).unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+                    "a9993e364706816aba3e25717850c26c9cd0d89d",
+                    "48",
+                    "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                    "true",
+                    "false",
+                    "false",
+                    r#"["secret",false,{"name":"HMAC","hash":{"name":"SHA-256"},"length":32},["sign","verify"]]"#,
+                    "Jefe",
+                    "InvalidAccessError: The key is not extractable",
+                    "InvalidAccessError: The key doesn't support sign",
+                    "NotSupportedError: Unrecognized algorithm name: MD5",
+                    "DataError: The key length is invalid",
+                    "true",
+                    "false",
+                    "RangeError: The buffers must have the same length",
+                    "TypeError: Illegal constructor",
+                    "InvalidAccessError: The key doesn't support sign",
+                    "InvalidAccessError: The key is not extractable",
+                    "TypeError: ArrayBuffer is detached",
+                    "TypeError: ArrayBuffer is detached",
+                ]
+            );
+        })
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    function::Func,
+    module::{Declarations, Exports, ModuleDef},
+    Ctx, Exception, Result, StdString, TypedArray,
+};
+
+use super::{
+    array_bytes,
+    base64::{self, Alphabet},
+};
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// A native module with helpers for converting between `Uint8Array`s and text encodings.
+///
+/// The module exports the following functions, the encoding functions take an `Uint8Array` and
+/// return a string while the decoding functions take a string and return a new `Uint8Array`:
+///
+/// - `toHex` and `fromHex` for lowercase hexadecimal, decoding accepts both cases.
+/// - `toBase64` and `fromBase64` for padded base64.
+/// - `toBase64Url` and `fromBase64Url` for unpadded base64 with the URL safe alphabet.
+///
+/// Encoding throws a `TypeError` for an array whose buffer was detached, and decoding throws a
+/// `SyntaxError` for invalid input. Base64 decoding ignores ASCII whitespace
+/// and accepts input with or without padding, like `atob`.
+///
+/// The module is not registered by [`install`](super::install), it has to be declared under a
+/// name of choice, for example with [`Module::declare_def`](crate::Module::declare_def) or a
+/// module loader.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, web::Encoding};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     Module::declare_def::<Encoding, _>(ctx.clone(), "encoding").unwrap();
+///     Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { toHex, fromBase64Url } from "encoding";
+///         globalThis.hex = toHex(fromBase64Url("3q2-7w"));
+///     "#,
+///     )
+///     .unwrap()
+///     .finish::<()>()
+///     .unwrap();
+///     assert_eq!(ctx.globals().get::<_, String>("hex").unwrap(), "deadbeef");
+/// });
+/// ```
+pub struct Encoding;
+
+impl ModuleDef for Encoding {
+    fn declare<'js>(decl: &Declarations<'js>) -> Result<()> {
+        for name in [
+            "toHex",
+            "fromHex",
+            "toBase64",
+            "fromBase64",
+            "toBase64Url",
+            "fromBase64Url",
+        ] {
+            decl.declare(name)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate<'js>(_ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
+        exports.export("toHex", Func::from(to_hex))?;
+        exports.export("fromHex", Func::from(from_hex))?;
+        exports.export("toBase64", Func::from(to_base64))?;
+        exports.export("fromBase64", Func::from(from_base64))?;
+        exports.export("toBase64Url", Func::from(to_base64_url))?;
+        exports.export("fromBase64Url", Func::from(from_base64_url))?;
+        Ok(())
+    }
+}
+
+fn to_hex(ctx: Ctx<'_>, bytes: TypedArray<'_, u8>) -> Result<StdString> {
+    let bytes = array_bytes(&ctx, &bytes)?;
+    let mut res = StdString::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        res.push(HEX[(b >> 4) as usize] as char);
+        res.push(HEX[(b & 0xf) as usize] as char);
+    }
+    Ok(res)
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|x| x as u8)
+}
+
+fn from_hex<'js>(ctx: Ctx<'js>, input: StdString) -> Result<TypedArray<'js, u8>> {
+    let bytes = input
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some(hex_digit(*high)? << 4 | hex_digit(*low)?),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| {
+            Exception::throw_syntax(
+                &ctx,
+                "The input must contain an even number of hexadecimal digits",
+            )
+        })?;
+    TypedArray::new(ctx, bytes)
+}
+
+fn decode<'js>(ctx: Ctx<'js>, input: &str, alphabet: &Alphabet) -> Result<TypedArray<'js, u8>> {
+    let bytes = base64::decode(input, alphabet)
+        .ok_or_else(|| Exception::throw_syntax(&ctx, "The input is not valid base64"))?;
+    TypedArray::new(ctx, bytes)
+}
+
+fn to_base64(ctx: Ctx<'_>, bytes: TypedArray<'_, u8>) -> Result<StdString> {
+    Ok(base64::encode(
+        array_bytes(&ctx, &bytes)?,
+        &base64::STANDARD,
+    ))
+}
+
+fn from_base64<'js>(ctx: Ctx<'js>, input: StdString) -> Result<TypedArray<'js, u8>> {
+    decode(ctx, &input, &base64::STANDARD)
+}
+
+fn to_base64_url(ctx: Ctx<'_>, bytes: TypedArray<'_, u8>) -> Result<StdString> {
+    Ok(base64::encode(array_bytes(&ctx, &bytes)?, &base64::URL))
+}
+
+fn from_base64_url<'js>(ctx: Ctx<'js>, input: StdString) -> Result<TypedArray<'js, u8>> {
+    decode(ctx, &input, &base64::URL)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{web::Encoding, *};
+
+    #[test]
+    fn encoding_module() {
+        test_with(|ctx| {
+            Module::declare_def::<Encoding, _>(ctx.clone(), "encoding").unwrap();
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import * as encoding from "encoding";
+                const bytes = new Uint8Array([0, 0x7f, 0xfb, 0xff]);
+                const error = (f) => { try { f() } catch (e) { return e.name } };
+                const detached = new Uint8Array(4);
+                detached.buffer.transfer();
+                globalThis.res = [
+                    encoding.toHex(bytes),
+                    encoding.fromHex("007FfbfF").join(),
+                    encoding.toBase64(bytes),
+                    encoding.fromBase64("AH/7/w==").join(),
+                    encoding.toBase64Url(bytes),
+                    encoding.fromBase64Url("AH_7_w").join(),
+                    encoding.fromBase64Url("AH_7_w==").join(),
+                    encoding.toHex(bytes.subarray(1, 3)),
+                    encoding.toBase64(new Uint8Array(0)),
+                    error(() => encoding.fromHex("abc")),
+                    error(() => encoding.fromHex("zz")),
+                    error(() => encoding.fromBase64Url("AH/7/w")),
+                    error(() => encoding.toHex([1, 2])),
+                    error(() => encoding.toBase64(detached)),
+                    error(() => encoding.toHex(detached)),
+                ];
+            "#,
+            )
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "007ffbff",
+                    "0,127,251,255",
+                    "AH/7/w==",
+                    "0,127,251,255",
+                    "AH_7_w",
+                    "0,127,251,255",
+                    "0,127,251,255",
+                    "7ffb",
+                    "",
+                    "SyntaxError",
+                    "SyntaxError",
+                    "SyntaxError",
+                    "TypeError",
+                    "TypeError",
+                    "TypeError",
+                ]
+            );
+        })
+    }
+}
@@ -0,0 +1,515 @@
+use crate::{
+    function::{Func, This},
+    module::{Declarations, Exports, ModuleDef},
+    value::Constructor,
+    Ctx, Function, Object, Result, Value,
+};
+
+/// Listeners are kept in a `WeakMap` created on first use, so any object with
+/// `EventTarget.prototype` in its prototype chain is an event target, including instances of
+/// classes defined in Rust. Exceptions thrown by listeners don't stop the dispatch, they are
+/// rethrown from a microtask like browsers report them.
+const CLASSES: &str = r#"(report) => {
+    const states = new WeakMap();
+    const stateOf = (event) => {
+        const state = states.get(event);
+        if (state === undefined) throw new TypeError("Illegal invocation");
+        return state;
+    };
+
+    class Event {
+        static NONE = 0;
+        static CAPTURING_PHASE = 1;
+        static AT_TARGET = 2;
+        static BUBBLING_PHASE = 3;
+        constructor(type, options = {}) {
+            if (arguments.length === 0) throw new TypeError("The event type is required");
+            options = options ?? {};
+            states.set(this, {
+                type: `${type}`,
+                bubbles: !!options.bubbles,
+                cancelable: !!options.cancelable,
+                composed: !!options.composed,
+                target: null,
+                currentTarget: null,
+                phase: 0,
+                dispatching: false,
+                canceled: false,
+                passive: false,
+                stop: false,
+                stopImmediate: false,
+                timeStamp: Date.now(),
+            });
+        }
+        get type() { return stateOf(this).type; }
+        get target() { return stateOf(this).target; }
+        get srcElement() { return stateOf(this).target; }
+        get currentTarget() { return stateOf(this).currentTarget; }
+        get eventPhase() { return stateOf(this).phase; }
+        get bubbles() { return stateOf(this).bubbles; }
+        get cancelable() { return stateOf(this).cancelable; }
+        get composed() { return stateOf(this).composed; }
+        get defaultPrevented() { return stateOf(this).canceled; }
+        get isTrusted() { return false; }
+        get timeStamp() { return stateOf(this).timeStamp; }
+        composedPath() {
+            const state = stateOf(this);
+            return state.dispatching ? [state.currentTarget] : [];
+        }
+        preventDefault() {
+            const state = stateOf(this);
+            if (state.cancelable && !state.passive) state.canceled = true;
+        }
+        stopPropagation() { stateOf(this).stop = true; }
+        stopImmediatePropagation() {
+            const state = stateOf(this);
+            state.stop = true;
+            state.stopImmediate = true;
+        }
+        get [Symbol.toStringTag]() { return "Event"; }
+    }
+    for (const [name, value] of [["NONE", 0], ["CAPTURING_PHASE", 1], ["AT_TARGET", 2], ["BUBBLING_PHASE", 3]]) {
+        Object.defineProperty(Event.prototype, name, { value, enumerable: true });
+    }
+
+    class CustomEvent extends Event {
+        #detail;
+        constructor(type, options = {}) {
+            super(type, options);
+            this.#detail = options?.detail ?? null;
+        }
+        get detail() { return this.#detail; }
+        get [Symbol.toStringTag]() { return "CustomEvent"; }
+    }
+
+    const listeners = new WeakMap();
+    const listenersOf = (target, type) => {
+        let types = listeners.get(target);
+        if (types === undefined) {
+            types = new Map();
+            listeners.set(target, types);
+        }
+        let res = types.get(type);
+        if (res === undefined) {
+            res = [];
+            types.set(type, res);
+        }
+        return res;
+    };
+    const flatten = (options) => typeof options === "boolean" ? { capture: options } : (options ?? {});
+
+    class EventTarget {
+        addEventListener(type, callback, options) {
+            if (callback === null || callback === undefined) return;
+            if (typeof callback !== "function" && typeof callback !== "object") {
+                throw new TypeError("The listener must be a function or an object");
+            }
+            options = flatten(options);
+            const list = listenersOf(this, `${type}`);
+            const capture = !!options.capture;
+            if (list.some((x) => x.callback === callback && x.capture === capture)) return;
+            list.push({ callback, capture, once: !!options.once, passive: !!options.passive, removed: false });
+        }
+        removeEventListener(type, callback, options) {
+            const capture = !!flatten(options).capture;
+            const list = listenersOf(this, `${type}`);
+            const index = list.findIndex((x) => x.callback === callback && x.capture === capture);
+            if (index === -1) return;
+            list[index].removed = true;
+            list.splice(index, 1);
+        }
+        dispatchEvent(event) {
+            const state = states.get(event);
+            if (state === undefined) throw new TypeError("The event must be an Event");
+            if (state.dispatching) throw Object.assign(new Error("The event is already being dispatched"), { name: "InvalidStateError" });
+            state.dispatching = true;
+            state.target = this;
+            state.currentTarget = this;
+            state.phase = 2;
+            const list = listenersOf(this, state.type);
+            for (const listener of [...list]) {
+                if (state.stopImmediate) break;
+                if (listener.removed) continue;
+                if (listener.once) this.removeEventListener(state.type, listener.callback, listener);
+                state.passive = listener.passive;
+                try {
+                    if (typeof listener.callback === "function") listener.callback.call(this, event);
+                    else listener.callback.handleEvent(event);
+                } catch (e) {
+                    report(() => { throw e; });
+                }
+                state.passive = false;
+            }
+            state.dispatching = false;
+            state.currentTarget = null;
+            state.phase = 0;
+            state.stop = false;
+            state.stopImmediate = false;
+            return !state.canceled;
+        }
+        get [Symbol.toStringTag]() { return "EventTarget"; }
+    }
+
+    return { Event, CustomEvent, EventTarget };
+}"#;
+
+/// The Node.js `EventEmitter` keeps its listeners in a `WeakMap` as well, so classes defined in
+/// Rust can inherit from it by prototype.
+const EMITTER: &str = r#"() => {
+    const events = new WeakMap();
+    const maxListeners = new WeakMap();
+    const eventsOf = (emitter) => {
+        let res = events.get(emitter);
+        if (res === undefined) {
+            res = new Map();
+            events.set(emitter, res);
+        }
+        return res;
+    };
+    const checkListener = (listener) => {
+        if (typeof listener !== "function") throw new TypeError(`The "listener" argument must be of type function`);
+    };
+    const add = (emitter, name, listener, once, prepend) => {
+        checkListener(listener);
+        const types = eventsOf(emitter);
+        if (types.has("newListener")) emitter.emit("newListener", name, listener);
+        const list = types.get(name) ?? [];
+        const entry = { listener, once };
+        if (prepend) list.unshift(entry);
+        else list.push(entry);
+        types.set(name, list);
+        return emitter;
+    };
+    const unwrap = (entry) => entry.once ? Object.assign((...args) => entry.listener(...args), { listener: entry.listener }) : entry.listener;
+
+    class EventEmitter {
+        static defaultMaxListeners = 10;
+        static once(emitter, name) {
+            return new Promise((resolve, reject) => {
+                const onError = (e) => {
+                    emitter.removeListener(name, onEvent);
+                    reject(e);
+                };
+                const onEvent = (...args) => {
+                    if (name !== "error") emitter.removeListener("error", onError);
+                    resolve(args);
+                };
+                emitter.once(name, onEvent);
+                if (name !== "error") emitter.once("error", onError);
+            });
+        }
+        on(name, listener) { return add(this, name, listener, false, false); }
+        addListener(name, listener) { return add(this, name, listener, false, false); }
+        prependListener(name, listener) { return add(this, name, listener, false, true); }
+        once(name, listener) { return add(this, name, listener, true, false); }
+        prependOnceListener(name, listener) { return add(this, name, listener, true, true); }
+        off(name, listener) { return this.removeListener(name, listener); }
+        removeListener(name, listener) {
+            checkListener(listener);
+            const types = eventsOf(this);
+            const list = types.get(name);
+            if (list === undefined) return this;
+            const index = list.findLastIndex((x) => x.listener === listener);
+            if (index === -1) return this;
+            list.splice(index, 1);
+            if (list.length === 0) types.delete(name);
+            if (types.has("removeListener")) this.emit("removeListener", name, listener);
+            return this;
+        }
+        removeAllListeners(name) {
+            const types = eventsOf(this);
+            const names = arguments.length === 0 ? [...types.keys()].filter((x) => x !== "removeListener") : [name];
+            for (const name of names) {
+                for (const { listener } of [...(types.get(name) ?? [])].reverse()) this.removeListener(name, listener);
+            }
+            if (arguments.length === 0) types.delete("removeListener");
+            return this;
+        }
+        emit(name, ...args) {
+            const list = eventsOf(this).get(name);
+            if (list === undefined) {
+                if (name === "error") {
+                    const error = args[0];
+                    if (error instanceof Error) throw error;
+                    throw new Error(`Unhandled error. (${error})`);
+                }
+                return false;
+            }
+            for (const entry of [...list]) {
+                if (entry.once) this.removeListener(name, entry.listener);
+                entry.listener.apply(this, args);
+            }
+            return true;
+        }
+        listeners(name) { return (eventsOf(this).get(name) ?? []).map((x) => x.listener); }
+        rawListeners(name) { return (eventsOf(this).get(name) ?? []).map(unwrap); }
+        listenerCount(name) { return eventsOf(this).get(name)?.length ?? 0; }
+        eventNames() { return [...eventsOf(this).keys()]; }
+        setMaxListeners(n) {
+            if (typeof n !== "number" || n < 0 || Number.isNaN(n)) throw new RangeError("The maximum number of listeners must be a non-negative number");
+            maxListeners.set(this, n);
+            return this;
+        }
+        getMaxListeners() { return maxListeners.get(this) ?? EventEmitter.defaultMaxListeners; }
+    }
+
+    return EventEmitter;
+}"#;
+
+/// A native module with the Node.js `EventEmitter`.
+///
+/// The module exports `EventEmitter`, also as the default export, and `once` returning a promise
+/// for the arguments of the next event. Emitters support `on`, `once`, `off`, `emit`,
+/// `prependListener`, `removeAllListeners`, `listeners` and `listenerCount` among others, and
+/// emit `newListener` and `removeListener` like Node.js. Emitting `error` without a listener
+/// throws the error.
+///
+/// The listeners of an emitter are created on first use, so a class defined in Rust can inherit
+/// from `EventEmitter.prototype` by setting it as the prototype of its own prototype.
+///
+/// The module is not registered by [`install`](super::install), it has to be declared under a
+/// name of choice, for example with [`Module::declare_def`](crate::Module::declare_def) or a
+/// module loader.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, web::Events};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     Module::declare_def::<Events, _>(ctx.clone(), "events").unwrap();
+///     Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import EventEmitter from "events";
+///         class Door extends EventEmitter {}
+///         const door = new Door();
+///         globalThis.res = [];
+///         door.on("open", (who) => res.push(`${who} opened`));
+///         door.once("open", () => res.push("first"));
+///         door.emit("open", "alice");
+///         door.emit("open", "bob");
+///     "#,
+///     )
+///     .unwrap()
+///     .finish::<()>()
+///     .unwrap();
+///     let res: Vec<String> = ctx.globals().get("res").unwrap();
+///     assert_eq!(res, ["alice opened", "first", "bob opened"]);
+/// });
+/// ```
+pub struct Events;
+
+impl ModuleDef for Events {
+    fn declare<'js>(decl: &Declarations<'js>) -> Result<()> {
+        decl.declare("EventEmitter")?;
+        decl.declare("once")?;
+        decl.declare("default")?;
+        Ok(())
+    }
+
+    fn evaluate<'js>(ctx: &Ctx<'js>, exports: &Exports<'js>) -> Result<()> {
+        let define: Function = ctx.eval(EMITTER)?;
+        let emitter: Function = define.call(())?;
+        exports.export("once", emitter.get::<_, Value>("once")?)?;
+        exports.export("EventEmitter", emitter.clone())?;
+        exports.export("default", emitter)?;
+        Ok(())
+    }
+}
+
+/// Dispatch a `CustomEvent` with the given type and `detail` to an `EventTarget`.
+///
+/// `target` can be any object inheriting from `EventTarget.prototype`, including instances of
+/// classes defined in Rust, and the globals must have been defined with
+/// [`install`](super::install). Returns `false` if a listener canceled the event, which is only
+/// possible with `cancelable`.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Object, Value, web};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     web::install(&ctx).unwrap();
+///     let target: Object = ctx
+///         .eval(
+///             r#"
+///             const target = new EventTarget();
+///             target.addEventListener("ready", (event) => { globalThis.detail = event.detail; });
+///             target
+///         "#,
+///         )
+///         .unwrap();
+///     let detail = Value::new_int(ctx.clone(), 42);
+///     assert!(web::dispatch_event(&ctx, &target, "ready", detail, false).unwrap());
+///     assert_eq!(ctx.globals().get::<_, i32>("detail").unwrap(), 42);
+/// });
+/// ```
+pub fn dispatch_event<'js>(
+    ctx: &Ctx<'js>,
+    target: &Object<'js>,
+    event_type: &str,
+    detail: Value<'js>,
+    cancelable: bool,
+) -> Result<bool> {
+    let class: Constructor = ctx.globals().get("CustomEvent")?;
+    let options = Object::new(ctx.clone())?;
+    options.set("detail", detail)?;
+    options.set("cancelable", cancelable)?;
+    let event: Object = class.construct((event_type, options))?;
+    let dispatch: Function = target.get("dispatchEvent")?;
+    dispatch.call((This(target.clone()), event))
+}
+
+/// Define `Event`, `CustomEvent` and `EventTarget` on the global object.
+pub(super) fn install<'js>(ctx: &Ctx<'js>, globals: &Object<'js>) -> Result<()> {
+    let define: Function = ctx.eval(CLASSES)?;
+    let classes: Object = define.call((Func::from(super::queue_microtask),))?;
+    for name in ["Event", "CustomEvent", "EventTarget"] {
+        globals.set(name, classes.get::<_, Object>(name)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        web::{self, Events},
+        *,
+    };
+
+    #[test]
+    fn event_target() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            // An object made in Rust inheriting from `EventTarget`.
+            let target = Object::new(ctx.clone()).unwrap();
+            let prototype: Object = ctx.eval("EventTarget.prototype").unwrap();
+            target.set_prototype(Some(&prototype)).unwrap();
+            ctx.globals().set("native", target.clone()).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                globalThis.res = [];
+                const error = (f) => { try { f(); return "returned"; } catch (e) { return e.name; } };
+                const listener = (event) => res.push(`fn ${event.type} ${event.eventPhase} ${event.target === native}`);
+                native.addEventListener("ping", listener);
+                native.addEventListener("ping", listener);
+                native.addEventListener("ping", { handleEvent: (event) => res.push(`object ${event.detail}`) }, { once: true });
+                native.addEventListener("ping", () => { throw new Error("reported"); });
+                native.addEventListener("stop", (event) => { event.stopImmediatePropagation(); event.preventDefault(); });
+                native.addEventListener("stop", () => res.push("not called"));
+                native.addEventListener("passive", (event) => event.preventDefault(), { passive: true });
+                class Counter extends EventTarget {}
+                const counter = new Counter();
+                counter.addEventListener("count", (event) => res.push(`${event instanceof CustomEvent} ${Object.prototype.toString.call(event)}`));
+                counter.dispatchEvent(new CustomEvent("count"));
+                const event = new Event("stop", { cancelable: true });
+                res.push(
+                    `${native.dispatchEvent(event)} ${event.defaultPrevented} ${event.eventPhase} ${event.currentTarget}`,
+                    `${native.dispatchEvent(new Event("passive", { cancelable: true }))}`,
+                    error(() => native.dispatchEvent({ type: "ping" })),
+                    error(() => new Event()),
+                    `${Event.AT_TARGET} ${event.AT_TARGET} ${event.isTrusted}`,
+                );
+            "#,
+            )
+            .unwrap();
+            let detail = "hi".into_js(&ctx).unwrap();
+            assert!(web::dispatch_event(&ctx, &target, "ping", detail.clone(), false).unwrap());
+            let error = web::run(&ctx).catch(&ctx).unwrap_err();
+            assert!(error.to_string().contains("reported"), "{error}");
+            ctx.eval::<(), _>(r#"native.removeEventListener("ping", listener);"#)
+                .unwrap();
+            web::dispatch_event(&ctx, &target, "ping", detail, false).unwrap();
+            let error = web::run(&ctx).catch(&ctx).unwrap_err();
+            assert!(error.to_string().contains("reported"), "{error}");
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "true [object CustomEvent]",
+                    "false true 0 null",
+                    "true",
+                    "TypeError",
+                    "TypeError",
+                    "2 2 false",
+                    "fn ping 2 true",
+                    "object hi",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn event_emitter() {
+        test_with(|ctx| {
+            Module::declare_def::<Events, _>(ctx.clone(), "events").unwrap();
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import EventEmitter, { once } from "events";
+                const res = [];
+                const error = (f) => { try { f(); return "returned"; } catch (e) { return e.message; } };
+                const emitter = new EventEmitter();
+                emitter.on("newListener", (name) => res.push(`new ${name}`));
+                emitter.on("removeListener", (name) => res.push(`removed ${name}`));
+                const first = (x) => res.push(`first ${x}`);
+                emitter.on("data", first);
+                emitter.prependOnceListener("data", (x) => res.push(`once ${x}`));
+                res.push(`${emitter.listenerCount("data")} ${emitter.listeners("data")[1] === first} ${emitter.rawListeners("data")[0].listener !== undefined}`);
+                res.push(`${emitter.emit("data", 1)} ${emitter.emit("data", 2)} ${emitter.emit("other")}`);
+                emitter.off("data", first);
+                res.push(emitter.eventNames().join());
+                res.push(error(() => emitter.emit("error", new Error("boom"))));
+                res.push(error(() => emitter.emit("error", "text")));
+                res.push(error(() => emitter.on("data", 1)));
+                const next = once(emitter, "ready");
+                emitter.emit("ready", "a", "b");
+                res.push((await next).join());
+                const failed = once(emitter, "ready");
+                emitter.emit("error", new Error("failed"));
+                res.push(await failed.catch((e) => e.message));
+                emitter.removeAllListeners();
+                res.push(`${emitter.eventNames().length} ${emitter.getMaxListeners()} ${emitter.setMaxListeners(1).getMaxListeners()}`);
+                globalThis.res = res;
+            "#,
+            )
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "new removeListener",
+                    "new data",
+                    "new data",
+                    "2 true true",
+                    "removed data",
+                    "once 1",
+                    "first 1",
+                    "first 2",
+                    "true true false",
+                    "removed data",
+                    "newListener,removeListener",
+                    "boom",
+                    "Unhandled error. (text)",
+                    "The \"listener\" argument must be of type function",
+                    "new ready",
+                    "new error",
+                    "removed ready",
+                    "removed error",
+                    "a,b",
+                    "new ready",
+                    "new error",
+                    "removed error",
+                    "removed ready",
+                    "failed",
+                    "removed newListener",
+                    "0 10 1",
+                ]
+            );
+        })
+    }
+}
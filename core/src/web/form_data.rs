@@ -0,0 +1,319 @@
+use core::hash::{BuildHasher, Hasher};
+use std::collections::hash_map::RandomState;
+
+use super::array_bytes;
+use crate::{Ctx, Function, Object, Result, StdString, TypedArray, Value};
+
+/// `FormData` keeps its entries in a private field. Binary values are stored as `File`s so they
+/// serialize as file parts, reading blobs through `bytesOf` of the `Blob` classes. A method under
+/// a registered symbol returns the headers and bytes of the parts, which [`Multipart::encode`]
+/// joins with a boundary chosen by the host.
+const CLASS: &str = r#"(Blob, File, bytesOf) => {
+    const toEntry = (args, name, value, filename) => {
+        if (args.length < 2) throw new TypeError("The name and value are required");
+        name = `${name}`;
+        if (value instanceof ArrayBuffer || ArrayBuffer.isView(value)) {
+            value = new File([value], filename === undefined ? "blob" : `${filename}`, { type: "application/octet-stream" });
+        } else if (value instanceof Blob) {
+            if (!(value instanceof File) || filename !== undefined) {
+                const name = filename !== undefined ? `${filename}` : value instanceof File ? value.name : "blob";
+                value = new File([value], name, { type: value.type, lastModified: value.lastModified });
+            }
+        } else {
+            if (args.length > 2) throw new TypeError("A filename can only be given for a Blob value");
+            value = `${value}`;
+        }
+        return { name, value };
+    };
+    const escape = (name) => name.replace(/[\n\r"]/g, (c) => ({ "\n": "%0A", "\r": "%0D", '"': "%22" })[c]);
+
+    class FormData {
+        #entries = [];
+        constructor(form) {
+            if (form !== undefined) throw new TypeError("HTML form elements are not supported");
+        }
+        append(name, value, filename) {
+            this.#entries.push(toEntry(arguments, name, value, filename));
+        }
+        set(name, value, filename) {
+            const entry = toEntry(arguments, name, value, filename);
+            const index = this.#entries.findIndex((x) => x.name === entry.name);
+            if (index === -1) return void this.#entries.push(entry);
+            this.#entries[index] = entry;
+            this.#entries = this.#entries.filter((x, i) => i <= index || x.name !== entry.name);
+        }
+        get(name) {
+            name = `${name}`;
+            const entry = this.#entries.find((x) => x.name === name);
+            return entry === undefined ? null : entry.value;
+        }
+        getAll(name) {
+            name = `${name}`;
+            return this.#entries.filter((x) => x.name === name).map((x) => x.value);
+        }
+        has(name) {
+            name = `${name}`;
+            return this.#entries.some((x) => x.name === name);
+        }
+        delete(name) {
+            name = `${name}`;
+            this.#entries = this.#entries.filter((x) => x.name !== name);
+        }
+        *entries() {
+            for (let i = 0; i < this.#entries.length; i++) yield [this.#entries[i].name, this.#entries[i].value];
+        }
+        *keys() {
+            for (const [name] of this.entries()) yield name;
+        }
+        *values() {
+            for (const [, value] of this.entries()) yield value;
+        }
+        forEach(callback, thisArg) {
+            for (const [name, value] of this.entries()) callback.call(thisArg, value, name, this);
+        }
+        [Symbol.iterator]() { return this.entries(); }
+        get [Symbol.toStringTag]() { return "FormData"; }
+
+        [Symbol.for("rquickjs.multipart")]() {
+            const parts = [];
+            for (const { name, value } of this.#entries) {
+                let head = `Content-Disposition: form-data; name="${escape(name)}"`;
+                if (typeof value === "string") {
+                    parts.push({ head: `${head}\r\n\r\n`, body: bytesOf(new Blob([value])) });
+                } else {
+                    head += `; filename="${escape(value.name)}"\r\nContent-Type: ${value.type || "application/octet-stream"}\r\n\r\n`;
+                    parts.push({ head, body: bytesOf(value) });
+                }
+            }
+            return parts;
+        }
+    }
+
+    return FormData;
+}"#;
+
+const ENCODE: &str = r#"(form) => {
+    const encode = form[Symbol.for("rquickjs.multipart")];
+    if (typeof encode !== "function") throw new TypeError("The value is not a FormData");
+    return encode.call(form);
+}"#;
+
+/// A `FormData` serialized as a `multipart/form-data` body, for hosts sending forms of scripts
+/// with their HTTP client.
+///
+/// String values are encoded as UTF-8 text parts. `Blob`s, `File`s, `ArrayBuffer`s and views on
+/// them are file parts, with the filename given to `append` or `set`, the name of the file or
+/// `"blob"`, and the type of the blob or `application/octet-stream`. The boundary is random for
+/// each encoding and chosen again while it occurs in any of the parts.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Object, web::{self, Multipart}};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     web::install(&ctx).unwrap();
+///     let form: Object = ctx
+///         .eval(
+///             r#"
+///             const form = new FormData();
+///             form.append("name", "value");
+///             form.append("upload", new Uint8Array([104, 105]), "hi.bin");
+///             form
+///         "#,
+///         )
+///         .unwrap();
+///     let multipart = Multipart::encode(&ctx, &form).unwrap();
+///     let boundary = multipart.content_type.strip_prefix("multipart/form-data; boundary=").unwrap();
+///     let body = String::from_utf8(multipart.body).unwrap();
+///     assert!(body.starts_with(&format!("--{boundary}\r\n")));
+///     assert!(body.contains("name=\"upload\"; filename=\"hi.bin\"\r\n"));
+///     assert!(body.ends_with(&format!("\r\nhi\r\n--{boundary}--\r\n")));
+/// });
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Multipart {
+    /// The `Content-Type` of the body, including its boundary.
+    pub content_type: StdString,
+    /// The encoded entries.
+    pub body: Vec<u8>,
+}
+
+impl Multipart {
+    /// Encode a `FormData` defined by [`install`](super::install).
+    ///
+    /// Throws a `TypeError` if `form` isn't a `FormData`.
+    pub fn encode<'js>(ctx: &Ctx<'js>, form: &Object<'js>) -> Result<Self> {
+        let encode: Function = ctx.eval(ENCODE)?;
+        let parts: Vec<Object> = encode.call((form.clone(),))?;
+        let parts = parts
+            .into_iter()
+            .map(|part| Ok((part.get::<_, StdString>("head")?, part.get("body")?)))
+            .collect::<Result<Vec<(StdString, TypedArray<u8>)>>>()?;
+        let parts = parts
+            .iter()
+            .map(|(head, body)| Ok((head.as_bytes(), array_bytes(ctx, body)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut boundary = random_boundary();
+        while parts
+            .iter()
+            .any(|(head, body)| contains(head, &boundary) || contains(body, &boundary))
+        {
+            boundary = random_boundary();
+        }
+
+        let mut body = Vec::new();
+        for (head, part) in &parts {
+            body.extend_from_slice(b"--");
+            body.extend_from_slice(boundary.as_bytes());
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(head);
+            body.extend_from_slice(part);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(b"--");
+        body.extend_from_slice(boundary.as_bytes());
+        body.extend_from_slice(b"--\r\n");
+        Ok(Multipart {
+            content_type: format!("multipart/form-data; boundary={boundary}"),
+            body,
+        })
+    }
+}
+
+/// A boundary with 128 bits from the randomly keyed hashers of the standard library.
+fn random_boundary() -> StdString {
+    let mut res = StdString::from("----rquickjsFormBoundary");
+    for _ in 0..2 {
+        let random = RandomState::new().build_hasher().finish();
+        res.push_str(&format!("{random:016x}"));
+    }
+    res
+}
+
+fn contains(bytes: &[u8], boundary: &str) -> bool {
+    bytes
+        .windows(boundary.len())
+        .any(|x| x == boundary.as_bytes())
+}
+
+/// Define `FormData` on the global object, after `Blob` and `File` are defined.
+pub(super) fn install<'js>(
+    ctx: &Ctx<'js>,
+    globals: &Object<'js>,
+    bytes_of: Function<'js>,
+) -> Result<()> {
+    let define: Function = ctx.eval(CLASS)?;
+    let class: Object = define.call((
+        globals.get::<_, Value>("Blob")?,
+        globals.get::<_, Value>("File")?,
+        bytes_of,
+    ))?;
+    globals.set("FormData", class)
+}
+
+#[cfg(test)]
+mod test {
+    use super::contains;
+    use crate::{
+        web::{self, Multipart},
+        *,
+    };
+
+    #[test]
+    fn form_data() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const error = (f) => { try { f(); return "returned"; } catch (e) { return e.name; } };
+                    const form = new FormData();
+                    form.append("a", 1);
+                    form.append("b", new Blob(["x"], { type: "text/plain" }));
+                    form.append("a", "2");
+                    form.append("c", new File(["y"], "y.txt"), "renamed.txt");
+                    const keys = [...form.keys()].join();
+                    form.set("a", "3");
+                    const b = form.get("b");
+                    [
+                        keys,
+                        [...form].map(([name, value]) => `${name}=${typeof value === "string" ? value : value.name}`).join(),
+                        `${b instanceof File} ${b.name} ${b.type} ${b.size}`,
+                        form.get("c").name,
+                        `${form.get("missing")} ${form.getAll("a")} ${form.has("c")}`,
+                        (form.delete("c"), [...form.keys()].join()),
+                        Object.prototype.toString.call(form),
+                        error(() => form.append("a")),
+                        error(() => form.append("a", "text", "name.txt")),
+                        error(() => new FormData({})),
+                    ]
+                "#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "a,b,a,c",
+                    "a=3,b=blob,c=renamed.txt",
+                    "true blob text/plain 1",
+                    "renamed.txt",
+                    "null 3 true",
+                    "a,b",
+                    "[object FormData]",
+                    "TypeError",
+                    "TypeError",
+                    "TypeError",
+                ]
+            );
+
+            let form: Object = ctx
+                .eval(
+                    r#"
+                    const upload = new FormData();
+                    upload.append("field\"name", "héllo\r\nworld");
+                    upload.append("file", new Blob(["<b>"], { type: "text/html" }), "a\nb.html");
+                    upload.append("raw", new Uint8Array([1, 2]));
+                    upload
+                "#,
+                )
+                .unwrap();
+            let multipart = Multipart::encode(&ctx, &form).unwrap();
+            let boundary = multipart
+                .content_type
+                .strip_prefix("multipart/form-data; boundary=")
+                .unwrap();
+            let body = multipart
+                .body
+                .split(|x| *x == 1)
+                .next()
+                .map(|x| StdString::from_utf8(x.to_vec()).unwrap())
+                .unwrap()
+                .replace(boundary, "BOUNDARY");
+            assert_eq!(
+                body,
+                "--BOUNDARY\r\n\
+                 Content-Disposition: form-data; name=\"field%22name\"\r\n\r\n\
+                 héllo\r\nworld\r\n\
+                 --BOUNDARY\r\n\
+                 Content-Disposition: form-data; name=\"file\"; filename=\"a%0Ab.html\"\r\n\
+                 Content-Type: text/html\r\n\r\n\
+                 <b>\r\n\
+                 --BOUNDARY\r\n\
+                 Content-Disposition: form-data; name=\"raw\"; filename=\"blob\"\r\n\
+                 Content-Type: application/octet-stream\r\n\r\n"
+            );
+            assert!(multipart
+                .body
+                .ends_with(format!("\x01\x02\r\n--{boundary}--\r\n").as_bytes()));
+
+            let again = Multipart::encode(&ctx, &form).unwrap();
+            assert_ne!(again.content_type, multipart.content_type);
+            assert!(!contains(b"--a-b", "a-b-"));
+            assert!(contains(b"--a-b-", "a-b-"));
+
+            let error = Multipart::encode(&ctx, &Object::new(ctx.clone()).unwrap()).catch(&ctx);
+            assert!(error.unwrap_err().to_string().contains("not a FormData"));
+        })
+    }
+}
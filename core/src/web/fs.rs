@@ -0,0 +1,722 @@
+use alloc::rc::Rc;
+use core::{cell::Cell, time::Duration};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    function::{Func, Opt},
+    module::Declared,
+    ArrayBuffer, Ctx, Error, Exception, Function, Module, Object, Result, StdString, TypedArray,
+    Value,
+};
+
+use super::timers;
+
+/// The functions exported by the module.
+const EXPORTS: [&str; 5] = ["readFile", "writeFile", "readdir", "stat", "watch"];
+
+/// The default interval in which `watch` checks a path for changes.
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The operations a script is permitted to perform with an [`Fs`] module.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Permissions {
+    /// Permit `readFile`, `readdir` and `stat`.
+    pub read: bool,
+    /// Permit `writeFile`.
+    pub write: bool,
+    /// Permit `watch`.
+    pub watch: bool,
+}
+
+impl Permissions {
+    /// Permit reading and watching but no changes.
+    pub const READ_ONLY: Self = Permissions {
+        read: true,
+        write: false,
+        watch: true,
+    };
+
+    /// Permit every operation.
+    pub const ALL: Self = Permissions {
+        read: true,
+        write: true,
+        watch: true,
+    };
+}
+
+/// A native module giving scripts access to the files below a directory of the host.
+///
+/// The module exports the following functions, paths are resolved relative to the root directory
+/// of the module:
+///
+/// - `readFile(path, encoding)` returns the content of a file as an `Uint8Array`, or as a string
+///   if the encoding is `"utf8"` or an object with such an `encoding` property.
+/// - `writeFile(path, data)` creates or replaces a file with a string, an `ArrayBuffer` or an
+///   `Uint8Array`.
+/// - `readdir(path)` returns the sorted names of the entries of a directory.
+/// - `stat(path)` returns an object with the `size`, the modification time `mtimeMs` and the
+///   booleans `isFile`, `isDirectory` and `isSymbolicLink`. A symbolic link is described itself
+///   instead of its target.
+/// - `watch(path, listener, { interval })` calls `listener(eventType, path)` when the path is
+///   changed, `eventType` is `"rename"` if the path was created or removed and `"change"`
+///   otherwise. The path is polled every `interval` milliseconds, one second by default, with the
+///   timers of [`install`](super::install) which has to be called before. Returns an object with
+///   a `close` method to stop watching.
+///
+/// Every call checks the [`Permissions`] of the module and that the path stays inside the root
+/// directory. Symbolic links could lead out of the root directory, so paths through a link are
+/// rejected, and files are checked again after opening them to catch a link swapped in
+/// meanwhile. On platforms other than Unix and Windows this check isn't possible, so files can't
+/// be read or written there. Only regular files are read or written, pipes and devices could
+/// block the host. Violations and failed operations throw errors with the message of the
+/// underlying [`io::Error`](std::io::Error), which doesn't include the path on the host.
+///
+/// Unlike other native modules the module is configured at runtime, so it is declared with
+/// [`Fs::declare`] instead of being a [`ModuleDef`](crate::module::ModuleDef).
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, web::{Fs, Permissions}};
+/// let root = std::env::temp_dir().join("rquickjs-fs-doc");
+/// std::fs::create_dir_all(&root).unwrap();
+/// std::fs::write(root.join("config.json"), r#"{ "debug": true }"#).unwrap();
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     Fs::new(&root, Permissions::READ_ONLY)
+///         .declare(ctx.clone(), "fs")
+///         .unwrap();
+///     Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { readFile, writeFile } from "fs";
+///         globalThis.debug = JSON.parse(readFile("config.json", "utf8")).debug;
+///         try {
+///             writeFile("config.json", "{}");
+///         } catch (e) {
+///             globalThis.denied = true;
+///         }
+///     "#,
+///     )
+///     .unwrap()
+///     .finish::<()>()
+///     .unwrap();
+///     assert!(ctx.globals().get::<_, bool>("debug").unwrap());
+///     assert!(ctx.globals().get::<_, bool>("denied").unwrap());
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct Fs {
+    root: PathBuf,
+    permissions: Permissions,
+}
+
+impl Fs {
+    /// Create a module for the files below `root` with the given permissions.
+    pub fn new<P: Into<PathBuf>>(root: P, permissions: Permissions) -> Self {
+        Fs {
+            root: root.into(),
+            permissions,
+        }
+    }
+
+    /// Returns the root directory of the module.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the permissions of the module.
+    pub fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    /// Declare the module under the given name, for example from a module loader.
+    ///
+    /// Fails if the root directory doesn't exist.
+    pub fn declare<'js, N>(&self, ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let scope = Rc::new(Scope {
+            root: self.root.canonicalize()?,
+            permissions: self.permissions,
+        });
+        let exports = Object::new(ctx.clone())?;
+        exports.set("readFile", {
+            let scope = scope.clone();
+            Func::from(move |ctx, path, encoding| scope.read_file(ctx, path, encoding))
+        })?;
+        exports.set("writeFile", {
+            let scope = scope.clone();
+            Func::from(move |path, data| scope.write_file(path, data))
+        })?;
+        exports.set("readdir", {
+            let scope = scope.clone();
+            Func::from(move |path| scope.readdir(path))
+        })?;
+        exports.set("stat", {
+            let scope = scope.clone();
+            Func::from(move |ctx, path| scope.stat(ctx, path))
+        })?;
+        exports.set("watch", {
+            Func::from(move |ctx, path, listener, options| {
+                scope.watch(ctx, path, listener, options)
+            })
+        })?;
+        Module::declare_value(
+            ctx,
+            name,
+            exports.into_value(),
+            EXPORTS.iter().map(|x| x.to_string()).collect(),
+        )
+    }
+}
+
+/// The message for paths through symbolic links.
+const LINK_DENIED: &str = "symbolic links are not followed";
+
+fn denied(message: &str) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::PermissionDenied, message))
+}
+
+/// The message for paths to directories, pipes and devices.
+const NOT_FILE: &str = "not a regular file";
+
+/// Returns whether an opened file is the file at a path, without following a link at the path.
+#[cfg(unix)]
+fn same_file(file: &fs::File, path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let (a, b) = (file.metadata()?, fs::symlink_metadata(path)?);
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+/// Returns whether an opened file is the file at a path, without following a link at the path.
+///
+/// The standard library doesn't expose the identity of a file on Windows yet, so it is read with
+/// `GetFileInformationByHandle` for both the file and the reparse point at the path.
+#[cfg(windows)]
+fn same_file(file: &fs::File, path: &Path) -> io::Result<bool> {
+    use core::mem::MaybeUninit;
+    use std::os::windows::{
+        fs::OpenOptionsExt,
+        io::{AsRawHandle, RawHandle},
+    };
+
+    const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+
+    /// `BY_HANDLE_FILE_INFORMATION`
+    #[repr(C)]
+    struct FileInformation {
+        attributes: u32,
+        creation_time: [u32; 2],
+        last_access_time: [u32; 2],
+        last_write_time: [u32; 2],
+        volume_serial_number: u32,
+        size_high: u32,
+        size_low: u32,
+        number_of_links: u32,
+        index_high: u32,
+        index_low: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetFileInformationByHandle(file: RawHandle, info: *mut FileInformation) -> i32;
+    }
+
+    fn identity(file: &fs::File) -> io::Result<(u32, u32, u32)> {
+        let mut info = MaybeUninit::<FileInformation>::uninit();
+        if unsafe { GetFileInformationByHandle(file.as_raw_handle(), info.as_mut_ptr()) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let info = unsafe { info.assume_init() };
+        Ok((info.volume_serial_number, info.index_high, info.index_low))
+    }
+
+    // Opens the link itself instead of its target, without requesting any access.
+    let link = fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)?;
+    Ok(identity(file)? == identity(&link)?)
+}
+
+/// Without a way to compare the identity of files a link created after the check can't be
+/// detected, so no file is opened.
+#[cfg(not(any(unix, windows)))]
+fn same_file(_: &fs::File, _: &Path) -> io::Result<bool> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the identity of files can't be verified on this platform",
+    ))
+}
+
+/// The state of a watched path, `None` if it doesn't exist.
+type Snapshot = Option<(Option<SystemTime>, u64)>;
+
+fn snapshot(path: &Path) -> Snapshot {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+struct Scope {
+    root: PathBuf,
+    permissions: Permissions,
+}
+
+impl Scope {
+    fn check(&self, permitted: bool, operation: &str) -> Result<()> {
+        if permitted {
+            Ok(())
+        } else {
+            Err(denied(&format!("{operation} is not permitted")))
+        }
+    }
+
+    /// Resolve a path of a script to a path on the host inside the root directory.
+    ///
+    /// Symbolic links could point out of the root directory, so paths through them are rejected.
+    /// With `allow_link` the last component may be a link, for operations which don't follow it.
+    fn resolve(&self, path: &str, allow_link: bool) -> Result<PathBuf> {
+        let outside = || denied("the path is outside of the root directory");
+        let mut relative = PathBuf::new();
+        for component in Path::new(path).components() {
+            match component {
+                Component::RootDir | Component::CurDir => {}
+                Component::Normal(x) => relative.push(x),
+                Component::ParentDir => {
+                    if !relative.pop() {
+                        return Err(outside());
+                    }
+                }
+                Component::Prefix(_) => return Err(outside()),
+            }
+        }
+        let full = self.root.join(relative);
+        self.check_links(&full, allow_link)?;
+        Ok(full)
+    }
+
+    /// Fail if a component of a resolved path below the root directory is a symbolic link.
+    fn check_links(&self, full: &Path, allow_link: bool) -> Result<()> {
+        let relative = full.strip_prefix(&self.root).unwrap_or(full);
+        let mut path = self.root.clone();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            path.push(component);
+            if allow_link && components.peek().is_none() {
+                break;
+            }
+            match fs::symlink_metadata(&path) {
+                Ok(x) if x.file_type().is_symlink() => return Err(denied(LINK_DENIED)),
+                Ok(_) => {}
+                // The remaining components don't exist either, the operation fails or creates
+                // the file.
+                Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a regular file at a resolved path, failing if a component was replaced by a symbolic
+    /// link since it was checked.
+    ///
+    /// Opening a pipe or a device could block the host, so only paths which are regular files or
+    /// don't exist yet are opened.
+    fn open(&self, path: &Path, options: &fs::OpenOptions) -> Result<fs::File> {
+        match fs::symlink_metadata(path) {
+            Ok(x) if x.file_type().is_symlink() => return Err(denied(LINK_DENIED)),
+            Ok(x) if !x.is_file() => return Err(denied(NOT_FILE)),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let file = options.open(path)?;
+        self.check_links(path, false)?;
+        if !same_file(&file, path)? {
+            return Err(denied(LINK_DENIED));
+        }
+        if !file.metadata()?.is_file() {
+            return Err(denied(NOT_FILE));
+        }
+        Ok(file)
+    }
+
+    fn read_file<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        path: StdString,
+        encoding: Opt<Value<'js>>,
+    ) -> Result<Value<'js>> {
+        self.check(self.permissions.read, "reading")?;
+        let encoding = match encoding.0 {
+            Some(x) if x.is_object() => x.into_object().unwrap().get("encoding")?,
+            Some(x) if !x.is_undefined() && !x.is_null() => Some(x.get::<StdString>()?),
+            _ => None,
+        };
+        let mut bytes = Vec::new();
+        let path = self.resolve(&path, false)?;
+        self.open(&path, fs::OpenOptions::new().read(true))?
+            .read_to_end(&mut bytes)?;
+        match encoding.as_deref() {
+            None => Ok(TypedArray::new(ctx, bytes)?.into_value()),
+            Some("utf8" | "utf-8") => {
+                let text = String::from_utf8_lossy(&bytes);
+                Ok(crate::String::from_str(ctx, &text)?.into_value())
+            }
+            Some(encoding) => Err(Exception::throw_range(
+                &ctx,
+                &format!("The encoding '{encoding}' is not supported"),
+            )),
+        }
+    }
+
+    fn write_file<'js>(&self, path: StdString, data: Value<'js>) -> Result<()> {
+        self.check(self.permissions.write, "writing")?;
+        let bytes = if let Some(text) = data.as_string() {
+            text.to_string()?.into_bytes()
+        } else if let Some(buffer) = ArrayBuffer::from_value(data.clone()) {
+            buffer.as_bytes().unwrap_or_default().to_vec()
+        } else if let Ok(array) = TypedArray::<u8>::from_value(data.clone()) {
+            array.as_bytes().unwrap_or_default().to_vec()
+        } else {
+            return Err(Exception::throw_type(
+                data.ctx(),
+                "The data must be a string, an ArrayBuffer or an Uint8Array",
+            ));
+        };
+        let path = self.resolve(&path, false)?;
+        // Creating the file exclusively doesn't follow a link created meanwhile, an existing file
+        // is only truncated once it is known to be the checked one.
+        let mut file = match self.open(&path, fs::OpenOptions::new().write(true).create_new(true)) {
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::AlreadyExists => {
+                let file = self.open(&path, fs::OpenOptions::new().write(true))?;
+                file.set_len(0)?;
+                file
+            }
+            res => res?,
+        };
+        file.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn readdir(&self, path: StdString) -> Result<Vec<StdString>> {
+        self.check(self.permissions.read, "reading")?;
+        let mut names = fs::read_dir(self.resolve(&path, false)?)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
+
+    fn stat<'js>(&self, ctx: Ctx<'js>, path: StdString) -> Result<Object<'js>> {
+        self.check(self.permissions.read, "reading")?;
+        let metadata = fs::symlink_metadata(self.resolve(&path, true)?)?;
+        let res = Object::new(ctx)?;
+        res.set("size", metadata.len() as f64)?;
+        res.set(
+            "mtimeMs",
+            metadata
+                .modified()
+                .ok()
+                .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+                .map(|x| x.as_secs_f64() * 1000.0),
+        )?;
+        res.set("isFile", metadata.is_file())?;
+        res.set("isDirectory", metadata.is_dir())?;
+        res.set("isSymbolicLink", metadata.file_type().is_symlink())?;
+        Ok(res)
+    }
+
+    fn watch<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        path: StdString,
+        listener: Function<'js>,
+        options: Opt<Object<'js>>,
+    ) -> Result<Object<'js>> {
+        self.check(self.permissions.watch, "watching")?;
+        if !timers::installed(&ctx) {
+            return Err(Exception::throw_internal(
+                &ctx,
+                "Watching requires the timers of the web globals",
+            ));
+        }
+        let interval = match options.0 {
+            Some(options) => options
+                .get::<_, Option<f64>>("interval")?
+                .filter(|x| *x > 0.0)
+                .map(|x| Duration::from_secs_f64(x / 1000.0))
+                .unwrap_or(DEFAULT_WATCH_INTERVAL),
+            None => DEFAULT_WATCH_INTERVAL,
+        };
+        let resolved = self.resolve(&path, true)?;
+        let state = Cell::new(snapshot(&resolved));
+        let poll = Function::new(ctx.clone(), move || {
+            let current = snapshot(&resolved);
+            let previous = state.replace(current);
+            if previous == current {
+                return Ok(());
+            }
+            let event = if previous.is_some() == current.is_some() {
+                "change"
+            } else {
+                "rename"
+            };
+            listener.call::<_, ()>((event, path.as_str()))
+        })?;
+        let id = timers::schedule(&ctx, poll, interval, true, Vec::new())?;
+        let watcher = Object::new(ctx)?;
+        watcher.set(
+            "close",
+            Func::from(move |ctx: Ctx<'js>| timers::cancel(&ctx, id)),
+        )?;
+        Ok(watcher)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, thread, time::Duration};
+
+    use super::*;
+    use crate::{web, *};
+
+    fn root(name: &str) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("rquickjs-fs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("dir")).unwrap();
+        fs::write(root.join("dir/a.txt"), "héllo").unwrap();
+        fs::write(root.join("b.bin"), [1, 2, 3]).unwrap();
+        root
+    }
+
+    fn evaluate<'js>(ctx: &Ctx<'js>, source: &str) -> Vec<StdString> {
+        Module::evaluate(ctx.clone(), "test", source)
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+        ctx.globals().get("res").unwrap()
+    }
+
+    #[test]
+    fn fs_module() {
+        let root = root("module");
+        test_with(|ctx| {
+            Fs::new(&root, Permissions::ALL)
+                .declare(ctx.clone(), "fs")
+                .unwrap();
+            let res = evaluate(
+                &ctx,
+                r#"
+                import { readFile, writeFile, readdir, stat } from "fs";
+                const error = (f) => { try { f() } catch (e) { return e.message } };
+                writeFile("/dir/../c.txt", "new");
+                writeFile("d.bin", "replaced by a shorter content");
+                writeFile("d.bin", new Uint8Array([4, 5]));
+                const info = stat("dir/a.txt");
+                globalThis.res = [
+                    readFile("dir/a.txt", "utf8"),
+                    readFile("./dir/a.txt", { encoding: "utf-8" }),
+                    `${readFile("b.bin")}`,
+                    readFile("c.txt", "utf8"),
+                    `${readFile("d.bin")}`,
+                    readdir(".").join(),
+                    readdir("/dir").join(),
+                    `${info.size} ${info.isFile} ${info.isDirectory} ${info.mtimeMs > 0}`,
+                    `${stat("dir").isDirectory}`,
+                    error(() => readFile("../secret")),
+                    error(() => readFile("dir/../../secret")),
+                    error(() => readFile("missing")).includes("IO Error"),
+                    error(() => readFile("b.bin", "latin1")),
+                    error(() => writeFile("e.txt", 1)),
+                    error(() => writeFile("missing/e.txt", "")).includes("IO Error"),
+                    error(() => readFile("dir")),
+                    error(() => writeFile("dir", "")),
+                ].map(String);
+            "#,
+            );
+            assert_eq!(
+                res,
+                [
+                    "héllo",
+                    "héllo",
+                    "1,2,3",
+                    "new",
+                    "4,5",
+                    "b.bin,c.txt,d.bin,dir",
+                    "a.txt",
+                    "6 true false true",
+                    "true",
+                    "IO Error: the path is outside of the root directory",
+                    "IO Error: the path is outside of the root directory",
+                    "true",
+                    "The encoding 'latin1' is not supported",
+                    "The data must be a string, an ArrayBuffer or an Uint8Array",
+                    "true",
+                    "IO Error: not a regular file",
+                    "IO Error: not a regular file",
+                ]
+            );
+        });
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fs_symlink_escape() {
+        let root = root("symlink");
+        let outside = root.with_extension("outside");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("created"), root.join("dangling")).unwrap();
+        std::os::unix::fs::symlink(root.join("b.bin"), root.join("inner")).unwrap();
+        test_with(|ctx| {
+            Fs::new(&root, Permissions::ALL)
+                .declare(ctx.clone(), "fs")
+                .unwrap();
+            let res = evaluate(
+                &ctx,
+                r#"
+                import { readFile, writeFile, readdir, stat } from "fs";
+                const error = (f) => { try { f() } catch (e) { return e.message } };
+                globalThis.res = [
+                    error(() => readFile("link/secret")),
+                    error(() => writeFile("link/new", "")),
+                    error(() => readdir("link")),
+                    error(() => stat("link/secret")),
+                    error(() => writeFile("dangling", "escaped")),
+                    error(() => readFile("dangling")),
+                    error(() => readFile("inner")),
+                    `${stat("dangling").isSymbolicLink} ${stat("link").isDirectory}`,
+                ];
+            "#,
+            );
+            let denied = "IO Error: symbolic links are not followed";
+            assert_eq!(
+                res,
+                [
+                    denied,
+                    denied,
+                    denied,
+                    denied,
+                    denied,
+                    denied,
+                    denied,
+                    "true false"
+                ]
+            );
+        });
+        assert!(!outside.join("new").exists());
+        assert!(!outside.join("created").exists());
+        fs::remove_dir_all(&root).unwrap();
+        fs::remove_dir_all(&outside).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fs_fifo() {
+        let root = root("fifo");
+        let status = std::process::Command::new("mkfifo")
+            .arg(root.join("fifo"))
+            .status();
+        if !status.map(|x| x.success()).unwrap_or(false) {
+            return;
+        }
+        test_with(|ctx| {
+            Fs::new(&root, Permissions::ALL)
+                .declare(ctx.clone(), "fs")
+                .unwrap();
+            let res = evaluate(
+                &ctx,
+                r#"
+                import { readFile, writeFile } from "fs";
+                const error = (f) => { try { f() } catch (e) { return e.message } };
+                globalThis.res = [error(() => readFile("fifo")), error(() => writeFile("fifo", ""))];
+            "#,
+            );
+            assert_eq!(res, ["IO Error: not a regular file"; 2]);
+        });
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn fs_permissions() {
+        let root = root("permissions");
+        test_with(|ctx| {
+            Fs::new(&root, Permissions::default())
+                .declare(ctx.clone(), "none")
+                .unwrap();
+            Fs::new(&root, Permissions::READ_ONLY)
+                .declare(ctx.clone(), "read")
+                .unwrap();
+            let res = evaluate(
+                &ctx,
+                r#"
+                import * as none from "none";
+                import * as read from "read";
+                const error = (f) => { try { f(); return "ok" } catch (e) { return e.message } };
+                globalThis.res = [
+                    error(() => none.readFile("b.bin")),
+                    error(() => none.readdir(".")),
+                    error(() => none.stat("b.bin")),
+                    error(() => none.watch("b.bin", () => {})),
+                    error(() => read.readFile("b.bin")),
+                    error(() => read.writeFile("b.bin", "")),
+                ];
+            "#,
+            );
+            assert_eq!(
+                res,
+                [
+                    "IO Error: reading is not permitted",
+                    "IO Error: reading is not permitted",
+                    "IO Error: reading is not permitted",
+                    "IO Error: watching is not permitted",
+                    "ok",
+                    "IO Error: writing is not permitted",
+                ]
+            );
+        });
+        assert_eq!(fs::read(root.join("b.bin")).unwrap(), [1, 2, 3]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn fs_watch() {
+        let root = root("watch");
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            Fs::new(&root, Permissions::ALL)
+                .declare(ctx.clone(), "fs")
+                .unwrap();
+            evaluate(
+                &ctx,
+                r#"
+                import { watch } from "fs";
+                globalThis.res = [];
+                globalThis.watcher = watch("new.txt", (event, path) => res.push(`${event} ${path}`), { interval: 1 });
+            "#,
+            );
+            fs::write(root.join("new.txt"), "a").unwrap();
+            thread::sleep(Duration::from_millis(5));
+            web::run_timers(&ctx).unwrap();
+            fs::write(root.join("new.txt"), "ab").unwrap();
+            thread::sleep(Duration::from_millis(5));
+            web::run_timers(&ctx).unwrap();
+            ctx.eval::<(), _>("watcher.close()").unwrap();
+            assert_eq!(web::run_timers(&ctx).unwrap(), None);
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(res, ["rename new.txt", "change new.txt"]);
+        });
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,511 @@
+use alloc::{collections::VecDeque, rc::Rc, sync::Arc};
+use core::{
+    cell::{Cell, RefCell},
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use std::sync::Mutex;
+
+use super::throw_named;
+use crate::{
+    channel::Message, function::Func, Ctx, Error, Function, JsLifetime, Object, Result, Value,
+};
+
+/// `MessagePort` wraps an object of native functions holding the Rust side of the port.
+/// `MessagePort[Symbol.for("rquickjs.messagePort")]` lets [`MessagePortHandle`] wrap and unwrap
+/// ports.
+const CLASSES: &str = r#"(EventTarget, Event, pair) => {
+    const token = Symbol();
+    const natives = new WeakMap();
+    const nativeOf = (port) => {
+        const res = natives.get(port);
+        if (res === undefined) throw new TypeError("Illegal invocation");
+        return res;
+    };
+    const cloneError = (message) => Object.assign(new Error(message), { name: "DataCloneError" });
+
+    class MessageEvent extends Event {
+        #data;
+        #origin;
+        #lastEventId;
+        #source;
+        #ports;
+        constructor(type, options = {}) {
+            super(type, options);
+            options = options ?? {};
+            this.#data = options.data ?? null;
+            this.#origin = `${options.origin ?? ""}`;
+            this.#lastEventId = `${options.lastEventId ?? ""}`;
+            this.#source = options.source ?? null;
+            this.#ports = Object.freeze([...(options.ports ?? [])]);
+        }
+        get data() { return this.#data; }
+        get origin() { return this.#origin; }
+        get lastEventId() { return this.#lastEventId; }
+        get source() { return this.#source; }
+        get ports() { return this.#ports; }
+        get [Symbol.toStringTag]() { return "MessageEvent"; }
+    }
+
+    const wrap = (native) => new MessagePort(token, native);
+
+    class MessagePort extends EventTarget {
+        #onmessage = null;
+        #onmessageerror = null;
+        constructor(key, native) {
+            if (key !== token) throw new TypeError("Illegal constructor");
+            super();
+            natives.set(this, native);
+        }
+        postMessage(message, transfer = []) {
+            const native = nativeOf(this);
+            if (!Array.isArray(transfer)) transfer = transfer?.transfer ?? [];
+            const ports = [];
+            for (const port of transfer) {
+                if (port === this) throw cloneError("A port can't be transferred through itself");
+                if (!natives.has(port)) throw cloneError("Only message ports can be transferred");
+                if (ports.includes(natives.get(port))) throw cloneError("A port is transferred twice");
+                ports.push(natives.get(port));
+            }
+            native.post(message, ports);
+        }
+        start() {
+            nativeOf(this).start((data, ports, ok) => {
+                const event = ok ? new MessageEvent("message", { data, ports: ports.map(wrap) }) : new MessageEvent("messageerror");
+                this.dispatchEvent(event);
+            });
+        }
+        close() { nativeOf(this).close(); }
+        get onmessage() { return this.#onmessage; }
+        set onmessage(handler) {
+            if (this.#onmessage !== null) this.removeEventListener("message", this.#onmessage);
+            this.#onmessage = typeof handler === "function" ? handler : null;
+            if (this.#onmessage !== null) {
+                this.addEventListener("message", this.#onmessage);
+                this.start();
+            }
+        }
+        get onmessageerror() { return this.#onmessageerror; }
+        set onmessageerror(handler) {
+            if (this.#onmessageerror !== null) this.removeEventListener("messageerror", this.#onmessageerror);
+            this.#onmessageerror = typeof handler === "function" ? handler : null;
+            if (this.#onmessageerror !== null) this.addEventListener("messageerror", this.#onmessageerror);
+        }
+        get [Symbol.toStringTag]() { return "MessagePort"; }
+    }
+    Object.defineProperty(MessagePort, Symbol.for("rquickjs.messagePort"), {
+        value: Object.freeze({ wrap, unwrap: nativeOf }),
+    });
+
+    class MessageChannel {
+        #port1;
+        #port2;
+        constructor() {
+            const [port1, port2] = pair();
+            this.#port1 = wrap(port1);
+            this.#port2 = wrap(port2);
+        }
+        get port1() { return this.#port1; }
+        get port2() { return this.#port2; }
+        get [Symbol.toStringTag]() { return "MessageChannel"; }
+    }
+
+    return { MessageChannel, MessagePort, MessageEvent };
+}"#;
+
+const PORTS: &str = r#"MessagePort[Symbol.for("rquickjs.messagePort")]"#;
+
+/// A message with the ports transferred along with it.
+struct Envelope {
+    message: Message,
+    ports: Vec<MessagePortHandle>,
+}
+
+/// The messages travelling in one direction between two ports.
+#[derive(Default)]
+struct Queue {
+    messages: Mutex<VecDeque<Envelope>>,
+    closed: AtomicBool,
+}
+
+/// A `MessagePort` detached from any context, for passing it to another context or thread.
+///
+/// Messages are serialized like with a [`channel`](crate::channel) and queued for the other port
+/// until its context delivers them with [`run_messages`] or [`run`](super::run). A port only
+/// receives messages after `start` was called or `onmessage` was set, until then they are kept.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Object, web::{self, MessagePortHandle}};
+/// let (port1, port2) = MessagePortHandle::pair();
+///
+/// let worker = std::thread::spawn(move || {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///     ctx.with(|ctx| {
+///         web::install(&ctx).unwrap();
+///         let port = port2.into_port(&ctx).unwrap();
+///         ctx.globals().set("port", port).unwrap();
+///         ctx.eval::<(), _>("port.postMessage({ answer: 42 })").unwrap();
+///     });
+/// });
+/// worker.join().unwrap();
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     web::install(&ctx).unwrap();
+///     let port = port1.into_port(&ctx).unwrap();
+///     ctx.globals().set("port", port).unwrap();
+///     ctx.eval::<(), _>("port.onmessage = (event) => { globalThis.answer = event.data.answer; }")
+///         .unwrap();
+///     web::run(&ctx).unwrap();
+///     assert_eq!(ctx.globals().get::<_, i32>("answer").unwrap(), 42);
+/// });
+/// ```
+pub struct MessagePortHandle {
+    incoming: Arc<Queue>,
+    outgoing: Arc<Queue>,
+}
+
+impl MessagePortHandle {
+    /// Create two entangled ports, messages posted to one are received by the other.
+    pub fn pair() -> (Self, Self) {
+        let (a, b) = (Arc::new(Queue::default()), Arc::new(Queue::default()));
+        (
+            MessagePortHandle {
+                incoming: a.clone(),
+                outgoing: b.clone(),
+            },
+            MessagePortHandle {
+                incoming: b,
+                outgoing: a,
+            },
+        )
+    }
+
+    /// Detach a `MessagePort` from its context, like transferring it with `postMessage`.
+    ///
+    /// The port stops working in the context. Throws a `DataCloneError` if the port was already
+    /// closed or transferred.
+    pub fn from_port<'js>(ctx: &Ctx<'js>, port: &Object<'js>) -> Result<Self> {
+        let ports: Object = ctx.eval(PORTS)?;
+        let native: Object = ports.get::<_, Function>("unwrap")?.call((port.clone(),))?;
+        detach(ctx, &native)
+    }
+
+    /// Create a `MessagePort` in the context, after [`install`](super::install) was called.
+    pub fn into_port<'js>(self, ctx: &Ctx<'js>) -> Result<Object<'js>> {
+        let ports: Object = ctx.eval(PORTS)?;
+        let native = native(ctx, self)?;
+        ports.get::<_, Function>("wrap")?.call((native,))
+    }
+
+    fn close(self) {
+        self.incoming.closed.store(true, Ordering::Release);
+        self.outgoing.closed.store(true, Ordering::Release);
+        self.incoming.messages.lock().unwrap().clear();
+    }
+}
+
+impl fmt::Debug for MessagePortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessagePortHandle").finish_non_exhaustive()
+    }
+}
+
+/// The Rust side of a `MessagePort`, `None` once it is closed or transferred.
+struct Port {
+    handle: RefCell<Option<MessagePortHandle>>,
+    started: Cell<bool>,
+}
+
+/// The started ports of a runtime, stored as userdata.
+#[derive(Default)]
+struct Messaging<'js> {
+    started: RefCell<Vec<(Rc<Port>, Function<'js>)>>,
+    /// Where ports put their handle when they are transferred.
+    detached: RefCell<Option<MessagePortHandle>>,
+}
+
+unsafe impl<'js> JsLifetime<'js> for Messaging<'js> {
+    type Changed<'to> = Messaging<'to>;
+}
+
+/// Create the native functions of a port.
+fn native<'js>(ctx: &Ctx<'js>, handle: MessagePortHandle) -> Result<Object<'js>> {
+    let port = Rc::new(Port {
+        handle: RefCell::new(Some(handle)),
+        started: Cell::new(false),
+    });
+    let res = Object::new(ctx.clone())?;
+    let state = port.clone();
+    res.set(
+        "post",
+        Func::from(
+            move |ctx: Ctx<'js>, message: Value<'js>, ports: Vec<Object<'js>>| {
+                post(&ctx, &state, message, ports)
+            },
+        ),
+    )?;
+    let state = port.clone();
+    res.set(
+        "start",
+        Func::from(move |ctx: Ctx<'js>, dispatch: Function<'js>| {
+            if state.handle.borrow().is_some() && !state.started.replace(true) {
+                if let Some(messaging) = ctx.userdata::<Messaging>() {
+                    messaging
+                        .started
+                        .borrow_mut()
+                        .push((state.clone(), dispatch));
+                }
+            }
+        }),
+    )?;
+    let state = port.clone();
+    res.set(
+        "close",
+        Func::from(move || {
+            if let Some(handle) = state.handle.borrow_mut().take() {
+                handle.close();
+            }
+        }),
+    )?;
+    res.set(
+        "detach",
+        Func::from(move |ctx: Ctx<'js>| {
+            if let Some(messaging) = ctx.userdata::<Messaging>() {
+                *messaging.detached.borrow_mut() = port.handle.borrow_mut().take();
+            }
+        }),
+    )?;
+    Ok(res)
+}
+
+/// Take the handle out of the native functions of a port.
+fn detach<'js>(ctx: &Ctx<'js>, native: &Object<'js>) -> Result<MessagePortHandle> {
+    let messaging = ctx.userdata::<Messaging>();
+    if let Some(messaging) = &messaging {
+        messaging.detached.borrow_mut().take();
+    }
+    native.get::<_, Function>("detach")?.call::<_, ()>(())?;
+    messaging
+        .and_then(|x| x.detached.borrow_mut().take())
+        .ok_or_else(|| throw_named(ctx, "DataCloneError", "The port is closed or transferred"))
+}
+
+fn post<'js>(
+    ctx: &Ctx<'js>,
+    port: &Port,
+    message: Value<'js>,
+    ports: Vec<Object<'js>>,
+) -> Result<()> {
+    let message = Message::write(&message).map_err(|_| {
+        ctx.catch();
+        throw_named(ctx, "DataCloneError", "The message could not be cloned")
+    })?;
+    let ports = ports
+        .iter()
+        .map(|x| detach(ctx, x))
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(handle) = &*port.handle.borrow() {
+        if !handle.outgoing.closed.load(Ordering::Acquire) {
+            let envelope = Envelope { message, ports };
+            handle.outgoing.messages.lock().unwrap().push_back(envelope);
+        }
+    }
+    Ok(())
+}
+
+fn pair<'js>(ctx: Ctx<'js>) -> Result<Vec<Object<'js>>> {
+    let (a, b) = MessagePortHandle::pair();
+    Ok(vec![native(&ctx, a)?, native(&ctx, b)?])
+}
+
+/// Deliver the messages received by the started ports of the runtime.
+///
+/// Pending jobs are run after every message. Returns whether a message was delivered, `false`
+/// also if [`install`](super::install) was not called.
+pub fn run_messages<'js>(ctx: &Ctx<'js>) -> Result<bool> {
+    let Some(messaging) = ctx.userdata::<Messaging>() else {
+        return Ok(false);
+    };
+    messaging
+        .started
+        .borrow_mut()
+        .retain(|(port, _)| port.handle.borrow().is_some());
+    let started = messaging.started.borrow().clone();
+    let mut delivered = false;
+    for (port, dispatch) in started {
+        loop {
+            let envelope = match &*port.handle.borrow() {
+                Some(handle) => handle.incoming.messages.lock().unwrap().pop_front(),
+                None => None,
+            };
+            let Some(Envelope { message, ports }) = envelope else {
+                break;
+            };
+            delivered = true;
+            let ports = ports
+                .into_iter()
+                .map(|x| native(ctx, x))
+                .collect::<Result<Vec<_>>>()?;
+            match message.read(ctx) {
+                Ok(data) => dispatch.call::<_, ()>((data, ports, true))?,
+                Err(Error::Exception) => {
+                    ctx.catch();
+                    dispatch.call::<_, ()>((Value::new_undefined(ctx.clone()), ports, false))?
+                }
+                Err(e) => return Err(e),
+            }
+            super::run_jobs(ctx)?;
+        }
+    }
+    Ok(delivered)
+}
+
+/// Define `MessageChannel`, `MessagePort` and `MessageEvent` on the global object, after
+/// `EventTarget` is defined.
+pub(super) fn install<'js>(ctx: &Ctx<'js>, globals: &Object<'js>) -> Result<()> {
+    if ctx.userdata::<Messaging>().is_none() {
+        ctx.store_userdata(Messaging::default())?;
+    }
+    let define: Function = ctx.eval(CLASSES)?;
+    let classes: Object = define.call((
+        globals.get::<_, Value>("EventTarget")?,
+        globals.get::<_, Value>("Event")?,
+        Func::from(pair),
+    ))?;
+    for name in ["MessageChannel", "MessagePort", "MessageEvent"] {
+        globals.set(name, classes.get::<_, Object>(name)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        web::{self, MessagePortHandle},
+        *,
+    };
+
+    #[test]
+    fn message_channel() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                globalThis.res = [];
+                const error = (f) => { try { f(); return "returned"; } catch (e) { return e.name; } };
+                const { port1, port2 } = new MessageChannel();
+                const inner = new MessageChannel();
+                const data = { list: [1, 2], nested: { text: "hi" } };
+                port1.postMessage(data, [inner.port2]);
+                data.list.push(3);
+                port1.postMessage("second");
+                port2.onmessage = (event) => {
+                    res.push(`${event instanceof MessageEvent} ${JSON.stringify(event.data)} ${event.ports.length}`);
+                    if (event.ports.length > 0) {
+                        const [port] = event.ports;
+                        port.onmessage = (event) => res.push(`inner ${event.data}`);
+                        inner.port1.postMessage("through");
+                        port.postMessage("back");
+                    }
+                };
+                inner.port1.addEventListener("message", (event) => res.push(`outer ${event.data}`));
+                inner.port1.start();
+                res.push(
+                    error(() => port1.postMessage(() => {})),
+                    error(() => port1.postMessage(1, [port1])),
+                    error(() => port1.postMessage(1, [inner.port2])),
+                    error(() => new MessagePort()),
+                    `${Object.prototype.toString.call(port1)} ${port1 instanceof EventTarget}`,
+                );
+            "#,
+            )
+            .unwrap();
+            web::run(&ctx).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                port2.close();
+                port1.postMessage("dropped");
+                inner.port2.postMessage("transferred ports don't send");
+                "#,
+            )
+            .unwrap();
+            assert!(!web::run_messages(&ctx).unwrap());
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "DataCloneError",
+                    "DataCloneError",
+                    "DataCloneError",
+                    "TypeError",
+                    "[object MessagePort] true",
+                    r#"true {"list":[1,2],"nested":{"text":"hi"}} 1"#,
+                    "true \"second\" 0",
+                    "outer back",
+                    "inner through",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn transfer_between_runtimes() {
+        let (port, remote) = MessagePortHandle::pair();
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let other_rt = Runtime::new().unwrap();
+        let other = Context::full(&other_rt).unwrap();
+
+        ctx.with(|ctx| {
+            web::install(&ctx).unwrap();
+            let port = port.into_port(&ctx).unwrap();
+            ctx.globals().set("port", port).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                const { port1, port2 } = new MessageChannel();
+                port1.onmessage = (event) => { globalThis.reply = event.data; };
+                port.postMessage("hello", [port2]);
+                "#,
+            )
+            .unwrap();
+        });
+        let remote = other.with(|ctx| {
+            web::install(&ctx).unwrap();
+            let port = remote.into_port(&ctx).unwrap();
+            ctx.globals().set("port", port.clone()).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                port.onmessage = (event) => event.ports[0].postMessage(`${event.data} back`);
+                "#,
+            )
+            .unwrap();
+            assert!(web::run_messages(&ctx).unwrap());
+            let remote = MessagePortHandle::from_port(&ctx, &port).unwrap();
+            let error = MessagePortHandle::from_port(&ctx, &port).catch(&ctx);
+            assert!(error
+                .unwrap_err()
+                .to_string()
+                .contains("closed or transferred"));
+            remote
+        });
+        ctx.with(|ctx| {
+            web::run(&ctx).unwrap();
+            let reply: StdString = ctx.globals().get("reply").unwrap();
+            assert_eq!(reply, "hello back");
+            ctx.eval::<(), _>(r#"port.postMessage("after moving the port")"#)
+                .unwrap();
+        });
+        other.with(|ctx| {
+            let port = remote.into_port(&ctx).unwrap();
+            ctx.globals().set("moved", port).unwrap();
+            ctx.eval::<(), _>(r#"moved.onmessage = (event) => { globalThis.last = event.data; };"#)
+                .unwrap();
+            web::run(&ctx).unwrap();
+            let last: StdString = ctx.globals().get("last").unwrap();
+            assert_eq!(last, "after moving the port");
+        });
+    }
+}
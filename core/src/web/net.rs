@@ -0,0 +1,637 @@
+use alloc::rc::Rc;
+use core::{
+    cell::{Cell, RefCell},
+    future::{poll_fn, Future},
+    task::{Poll, Waker},
+};
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+};
+
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+
+use crate::{
+    function::{Async, Func},
+    module::Declared,
+    Ctx, Error, FromJs, Function, Module, Object, Result, StdString, TypedArray, Value,
+};
+
+/// The values and functions exported by the module.
+const EXPORTS: [&str; 6] = [
+    "connect",
+    "listen",
+    "bind",
+    "TcpSocket",
+    "TcpListener",
+    "UdpSocket",
+];
+
+/// The socket classes wrap the native handles, which are kept in a `WeakMap` so that the objects
+/// only expose their methods and addresses.
+const CLASSES: &str = r#"(connect, listen, bind) => {
+    const token = Symbol();
+    const handles = new WeakMap();
+    const handleOf = (object, type) => {
+        const handle = handles.get(object);
+        if (!(object instanceof type) || handle === undefined) throw new TypeError(`The receiver is not a ${type.name}`);
+        return handle;
+    };
+    const portOf = (port) => {
+        port = Number(port);
+        if (!Number.isInteger(port) || port < 0 || port > 65535) throw new RangeError("The port must be an integer between 0 and 65535");
+        return port;
+    };
+    const sizeOf = (size) => {
+        size = Number(size);
+        if (!Number.isInteger(size) || size < 1) throw new RangeError("The size must be a positive integer");
+        return size;
+    };
+    const bytesOf = (data) => {
+        if (typeof data === "string") return data;
+        if (data instanceof ArrayBuffer) return new Uint8Array(data);
+        if (ArrayBuffer.isView(data)) return new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+        throw new TypeError("The data must be a string, an ArrayBuffer or an ArrayBufferView");
+    };
+    const construct = (object, key, handle) => {
+        if (key !== token) throw new TypeError("Illegal constructor");
+        handles.set(object, handle);
+    };
+
+    class TcpSocket {
+        constructor(key, handle) {
+            construct(this, key, handle);
+        }
+        get localAddress() { return handleOf(this, TcpSocket).localAddress; }
+        get localPort() { return handleOf(this, TcpSocket).localPort; }
+        get remoteAddress() { return handleOf(this, TcpSocket).remoteAddress; }
+        get remotePort() { return handleOf(this, TcpSocket).remotePort; }
+        async read(size = 65536) {
+            return handleOf(this, TcpSocket).read(sizeOf(size));
+        }
+        async write(data) {
+            return handleOf(this, TcpSocket).write(bytesOf(data));
+        }
+        close() {
+            handleOf(this, TcpSocket).close();
+        }
+    }
+
+    class TcpListener {
+        constructor(key, handle) {
+            construct(this, key, handle);
+        }
+        get localAddress() { return handleOf(this, TcpListener).localAddress; }
+        get localPort() { return handleOf(this, TcpListener).localPort; }
+        async accept() {
+            return new TcpSocket(token, await handleOf(this, TcpListener).accept());
+        }
+        close() {
+            handleOf(this, TcpListener).close();
+        }
+    }
+
+    class UdpSocket {
+        constructor(key, handle) {
+            construct(this, key, handle);
+        }
+        get localAddress() { return handleOf(this, UdpSocket).localAddress; }
+        get localPort() { return handleOf(this, UdpSocket).localPort; }
+        async send(data, host, port) {
+            return handleOf(this, UdpSocket).send(bytesOf(data), String(host), portOf(port));
+        }
+        async recv(size = 65536) {
+            return handleOf(this, UdpSocket).recv(sizeOf(size));
+        }
+        close() {
+            handleOf(this, UdpSocket).close();
+        }
+    }
+
+    return {
+        connect: async (host, port) => new TcpSocket(token, await connect(String(host), portOf(port))),
+        listen: async (host, port) => new TcpListener(token, await listen(String(host), portOf(port))),
+        bind: async (host, port) => new UdpSocket(token, await bind(String(host), portOf(port))),
+        TcpSocket,
+        TcpListener,
+        UdpSocket,
+    };
+}"#;
+
+/// The host of an allow-list rule or of an address used by a script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Host {
+    Any,
+    Ip(IpAddr),
+    Name(StdString),
+}
+
+impl Host {
+    fn parse(host: &str) -> Self {
+        let host = unbracket(host);
+        if host == "*" {
+            Host::Any
+        } else if let Ok(ip) = host.parse() {
+            Host::Ip(ip)
+        } else {
+            Host::Name(host.to_ascii_lowercase())
+        }
+    }
+}
+
+/// Remove the brackets around an IPv6 address.
+fn unbracket(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|x| x.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+#[derive(Clone, Debug)]
+struct Rule {
+    host: Host,
+    port: Option<u16>,
+}
+
+/// A native module giving scripts TCP and UDP sockets, limited to an allow-list of addresses.
+///
+/// The module exports:
+///
+/// - `connect(host, port)`, which resolves to a `TcpSocket` connected to the address.
+/// - `listen(host, port)`, which resolves to a `TcpListener` bound to the address. Its `accept()`
+///   resolves to the `TcpSocket` of the next incoming connection.
+/// - `bind(host, port)`, which resolves to a `UdpSocket` bound to the address. Its
+///   `send(data, host, port)` sends a datagram and `recv(size)` resolves to the next datagram as
+///   `{ data, address, port }`.
+/// - The classes `TcpSocket`, `TcpListener` and `UdpSocket`, which can't be constructed by scripts.
+///
+/// A `TcpSocket` has `read(size)`, resolving to a `Uint8Array` of at most `size` bytes or
+/// `undefined` at the end of the stream, and `write(data)`, resolving once all of the string or
+/// buffer was written. Every socket has its `localAddress` and `localPort` and a `close()` which
+/// rejects its pending operations.
+///
+/// Connecting, listening, binding and sending are only possible for the addresses allowed with
+/// [`Net::allow_address`] and [`Net::allow_host`], everything else rejects with a permission
+/// error. A host name is only allowed by a rule for that name, not by the addresses it resolves
+/// to. Incoming connections and datagrams are not filtered.
+///
+/// The sockets are tokio sockets, so the async runtime must be driven from within a tokio
+/// runtime with I/O enabled. Every context the module is declared in has its own allow-list.
+///
+/// ```
+/// # use rquickjs::{AsyncRuntime, AsyncContext, async_with, Promise, web::Net};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let net = Net::new().with_host("127.0.0.1");
+/// let rt = AsyncRuntime::new().unwrap();
+/// let ctx = AsyncContext::full(&rt).await.unwrap();
+/// async_with!(ctx => |ctx| {
+///     net.declare(ctx.clone(), "net").unwrap();
+///     let promise: Promise = rquickjs::Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { connect, listen } from "net";
+///         const listener = await listen("127.0.0.1", 0);
+///         const client = await connect("127.0.0.1", listener.localPort);
+///         const server = await listener.accept();
+///         await client.write("ping");
+///         globalThis.res = String.fromCharCode(...await server.read());
+///         globalThis.denied = await connect("example.com", 80).catch((e) => e.message);
+///     "#,
+///     )
+///     .unwrap();
+///     promise.into_future::<()>().await.unwrap();
+///     assert_eq!(ctx.globals().get::<_, String>("res").unwrap(), "ping");
+///     assert_eq!(
+///         ctx.globals().get::<_, String>("denied").unwrap(),
+///         "IO Error: connecting to example.com:80 is not allowed"
+///     );
+/// })
+/// .await;
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Net {
+    rules: Vec<Rule>,
+}
+
+impl Net {
+    /// Create a module which doesn't allow any address.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow scripts to use a port of a host.
+    ///
+    /// The host is a name, which is compared ignoring case, an IP address or `*` for every host.
+    /// Port 0 allows listening and binding on a port chosen by the operating system.
+    pub fn allow_address<H: AsRef<str>>(&mut self, host: H, port: u16) -> &mut Self {
+        self.rules.push(Rule {
+            host: Host::parse(host.as_ref()),
+            port: Some(port),
+        });
+        self
+    }
+
+    /// Allow scripts to use a port of a host.
+    #[must_use]
+    pub fn with_address<H: AsRef<str>>(mut self, host: H, port: u16) -> Self {
+        self.allow_address(host, port);
+        self
+    }
+
+    /// Allow scripts to use every port of a host.
+    ///
+    /// The host is a name, which is compared ignoring case, an IP address or `*` for every host.
+    pub fn allow_host<H: AsRef<str>>(&mut self, host: H) -> &mut Self {
+        self.rules.push(Rule {
+            host: Host::parse(host.as_ref()),
+            port: None,
+        });
+        self
+    }
+
+    /// Allow scripts to use every port of a host.
+    #[must_use]
+    pub fn with_host<H: AsRef<str>>(mut self, host: H) -> Self {
+        self.allow_host(host);
+        self
+    }
+
+    /// Declare the module under the given name, for example from a module loader.
+    pub fn declare<'js, N>(&self, ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let net = Rc::new(self.clone());
+        let connect = {
+            let net = net.clone();
+            move |ctx: Ctx<'js>, host: StdString, port: u16| {
+                let net = net.clone();
+                async move {
+                    let host = net.check(&host, port, "connecting to")?;
+                    let stream = TcpStream::connect((host, port)).await?;
+                    tcp_socket(&ctx, stream)
+                }
+            }
+        };
+        let listen = {
+            let net = net.clone();
+            move |ctx: Ctx<'js>, host: StdString, port: u16| {
+                let net = net.clone();
+                async move {
+                    let host = net.check(&host, port, "listening on")?;
+                    let listener = TcpListener::bind((host, port)).await?;
+                    tcp_listener(&ctx, listener)
+                }
+            }
+        };
+        let bind = move |ctx: Ctx<'js>, host: StdString, port: u16| {
+            let net = net.clone();
+            async move {
+                let socket = UdpSocket::bind((net.check(&host, port, "binding")?, port)).await?;
+                udp_socket(&ctx, socket, net)
+            }
+        };
+
+        let define: Function = ctx.eval(CLASSES)?;
+        let exports: Object = define.call((
+            Function::new(ctx.clone(), Async(connect))?,
+            Function::new(ctx.clone(), Async(listen))?,
+            Function::new(ctx.clone(), Async(bind))?,
+        ))?;
+        Module::declare_value(
+            ctx,
+            name,
+            exports.into_value(),
+            EXPORTS.iter().map(|x| x.to_string()).collect(),
+        )
+    }
+
+    /// Check that the address is allowed, returning the host without brackets.
+    pub(super) fn check<'a>(&self, host: &'a str, port: u16, action: &str) -> Result<&'a str> {
+        let target = Host::parse(host);
+        let allowed = self.rules.iter().any(|rule| {
+            (rule.host == Host::Any || rule.host == target) && rule.port.unwrap_or(port) == port
+        });
+        if allowed {
+            return Ok(unbracket(host));
+        }
+        let address = match target {
+            Host::Ip(IpAddr::V6(_)) => format!("[{}]:{port}", unbracket(host)),
+            _ => format!("{host}:{port}"),
+        };
+        Err(Error::Io(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{action} {address} is not allowed"),
+        )))
+    }
+}
+
+/// A socket shared by the handle given to a script and its pending operations.
+///
+/// Closing drops the socket of the handle and wakes the pending operations, which then drop
+/// their references to it and reject.
+struct Resource<T> {
+    socket: RefCell<Option<Rc<T>>>,
+    pending: RefCell<Vec<(usize, Waker)>>,
+    next: Cell<usize>,
+}
+
+impl<T> Resource<T> {
+    fn new(socket: T) -> Rc<Self> {
+        Rc::new(Resource {
+            socket: RefCell::new(Some(Rc::new(socket))),
+            pending: RefCell::new(Vec::new()),
+            next: Cell::new(0),
+        })
+    }
+
+    fn close(&self) {
+        self.socket.borrow_mut().take();
+        for (_, waker) in self.pending.borrow_mut().drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Run an operation on the socket until it completes or the socket is closed.
+    async fn run<F, R>(&self, operation: impl FnOnce(Rc<T>) -> F) -> Result<R>
+    where
+        F: Future<Output = io::Result<R>>,
+    {
+        let socket = self.socket.borrow().clone().ok_or_else(closed)?;
+        let mut operation = Box::pin(operation(socket));
+        let id = self.next.get();
+        self.next.set(id.wrapping_add(1));
+        let _registration = Registration(self, id);
+        poll_fn(|cx| {
+            if self.socket.borrow().is_none() {
+                return Poll::Ready(Err(closed()));
+            }
+            {
+                let mut pending = self.pending.borrow_mut();
+                match pending.iter_mut().find(|x| x.0 == id) {
+                    Some((_, waker)) => waker.clone_from(cx.waker()),
+                    None => pending.push((id, cx.waker().clone())),
+                }
+            }
+            operation.as_mut().poll(cx).map_err(Error::Io)
+        })
+        .await
+    }
+}
+
+/// Removes the waker of an operation once it is done.
+struct Registration<'a, T>(&'a Resource<T>, usize);
+
+impl<T> Drop for Registration<'_, T> {
+    fn drop(&mut self) {
+        self.0.pending.borrow_mut().retain(|x| x.0 != self.1);
+    }
+}
+
+fn closed() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::NotConnected,
+        "the socket is closed",
+    ))
+}
+
+/// The bytes of a string or a `Uint8Array`, the socket classes convert other buffers.
+fn bytes_of<'js>(ctx: &Ctx<'js>, data: Value<'js>) -> Result<Vec<u8>> {
+    if let Some(string) = data.as_string() {
+        return Ok(string.to_string()?.into_bytes());
+    }
+    let array = TypedArray::<u8>::from_js(ctx, data)?;
+    Ok(array.as_bytes().unwrap_or_default().to_vec())
+}
+
+fn set_address(handle: &Object<'_>, prefix: &str, address: SocketAddr) -> Result<()> {
+    handle.set(format!("{prefix}Address"), address.ip().to_string())?;
+    handle.set(format!("{prefix}Port"), address.port())
+}
+
+fn tcp_socket<'js>(ctx: &Ctx<'js>, stream: TcpStream) -> Result<Object<'js>> {
+    let handle = Object::new(ctx.clone())?;
+    set_address(&handle, "local", stream.local_addr()?)?;
+    set_address(&handle, "remote", stream.peer_addr()?)?;
+    let stream = Resource::new(stream);
+
+    let read = {
+        let stream = stream.clone();
+        move |ctx: Ctx<'js>, size: usize| {
+            let stream = stream.clone();
+            async move {
+                let bytes = stream
+                    .run(|stream| async move {
+                        let mut buffer = vec![0; size];
+                        loop {
+                            stream.readable().await?;
+                            match stream.try_read(&mut buffer) {
+                                Ok(len) => {
+                                    buffer.truncate(len);
+                                    return Ok(buffer);
+                                }
+                                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                                Err(e) => return Err(e),
+                            }
+                        }
+                    })
+                    .await?;
+                if bytes.is_empty() {
+                    return Ok(None);
+                }
+                TypedArray::new(ctx, bytes).map(Some)
+            }
+        }
+    };
+    let write = {
+        let stream = stream.clone();
+        move |ctx: Ctx<'js>, data: Value<'js>| {
+            let stream = stream.clone();
+            let bytes = bytes_of(&ctx, data);
+            async move {
+                let bytes = bytes?;
+                stream
+                    .run(|stream| async move {
+                        let mut bytes = &bytes[..];
+                        while !bytes.is_empty() {
+                            stream.writable().await?;
+                            match stream.try_write(bytes) {
+                                Ok(len) => bytes = &bytes[len..],
+                                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                                Err(e) => return Err(e),
+                            }
+                        }
+                        Ok(())
+                    })
+                    .await
+            }
+        }
+    };
+    handle.set("read", Function::new(ctx.clone(), Async(read))?)?;
+    handle.set("write", Function::new(ctx.clone(), Async(write))?)?;
+    handle.set("close", Func::from(move || stream.close()))?;
+    Ok(handle)
+}
+
+fn tcp_listener<'js>(ctx: &Ctx<'js>, listener: TcpListener) -> Result<Object<'js>> {
+    let handle = Object::new(ctx.clone())?;
+    set_address(&handle, "local", listener.local_addr()?)?;
+    let listener = Resource::new(listener);
+
+    let accept = {
+        let listener = listener.clone();
+        move |ctx: Ctx<'js>| {
+            let listener = listener.clone();
+            async move {
+                let (stream, _) = listener
+                    .run(|listener| async move { listener.accept().await })
+                    .await?;
+                tcp_socket(&ctx, stream)
+            }
+        }
+    };
+    handle.set("accept", Function::new(ctx.clone(), Async(accept))?)?;
+    handle.set("close", Func::from(move || listener.close()))?;
+    Ok(handle)
+}
+
+fn udp_socket<'js>(ctx: &Ctx<'js>, socket: UdpSocket, net: Rc<Net>) -> Result<Object<'js>> {
+    let handle = Object::new(ctx.clone())?;
+    set_address(&handle, "local", socket.local_addr()?)?;
+    let socket = Resource::new(socket);
+
+    let send = {
+        let socket = socket.clone();
+        move |ctx: Ctx<'js>, data: Value<'js>, host: StdString, port: u16| {
+            let socket = socket.clone();
+            let net = net.clone();
+            let bytes = bytes_of(&ctx, data);
+            async move {
+                let bytes = bytes?;
+                let host = net.check(&host, port, "sending to")?.to_string();
+                socket
+                    .run(
+                        |socket| async move { socket.send_to(&bytes, (host.as_str(), port)).await },
+                    )
+                    .await
+            }
+        }
+    };
+    let recv = {
+        let socket = socket.clone();
+        move |ctx: Ctx<'js>, size: usize| {
+            let socket = socket.clone();
+            async move {
+                let (bytes, from) = socket
+                    .run(|socket| async move {
+                        let mut buffer = vec![0; size];
+                        let (len, from) = socket.recv_from(&mut buffer).await?;
+                        buffer.truncate(len);
+                        Ok((buffer, from))
+                    })
+                    .await?;
+                let res = Object::new(ctx.clone())?;
+                res.set("data", TypedArray::new(ctx, bytes)?)?;
+                res.set("address", from.ip().to_string())?;
+                res.set("port", from.port())?;
+                Ok::<_, Error>(res)
+            }
+        }
+    };
+    handle.set("send", Function::new(ctx.clone(), Async(send))?)?;
+    handle.set("recv", Function::new(ctx.clone(), Async(recv))?)?;
+    handle.set("close", Func::from(move || socket.close()))?;
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{web::Net, *};
+
+    #[tokio::test]
+    async fn net_module() {
+        let net = Net::new()
+            .with_host("127.0.0.1")
+            .with_address("LocalHost", 1);
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            net.declare(ctx.clone(), "net").unwrap();
+            let promise: Promise = Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import { connect, listen, bind, TcpSocket, UdpSocket } from "net";
+                const text = (bytes) => bytes === undefined ? "end" : String.fromCharCode(...bytes);
+                const error = (promise) => promise.then(() => "resolved", (e) => e.message);
+
+                const listener = await listen("127.0.0.1", 0);
+                const accepted = listener.accept();
+                const client = await connect("127.0.0.1", listener.localPort);
+                const server = await accepted;
+                await client.write("ping");
+                const ping = text(await server.read());
+                await server.write(new Uint16Array([0x6f70, 0x676e]).buffer);
+                const pong = text(await client.read(2)) + text(await client.read());
+                client.close();
+                const end = text(await server.read());
+                const pending = listener.accept();
+                listener.close();
+
+                const a = await bind("127.0.0.1", 0);
+                const b = await bind("127.0.0.1", 0);
+                const sent = await a.send("datagram", "127.0.0.1", b.localPort);
+                const { data, address, port } = await b.recv();
+
+                globalThis.res = [
+                    ping,
+                    pong,
+                    end,
+                    `${client instanceof TcpSocket} ${a instanceof UdpSocket} ${server.remotePort === client.localPort}`,
+                    `${sent} ${text(data)} ${address} ${port === a.localPort}`,
+                    await error(pending),
+                    await error(client.write("closed")),
+                    await error(connect("127.0.0.2", 80)),
+                    await error(connect("localhost", 2)),
+                    await error(connect("[::1]", 80)),
+                    await error(listen("0.0.0.0", 0)),
+                    await error(a.send("x", "example.com", 53)),
+                    await error(connect("LOCALHOST", 1).then((x) => x.close())).then((x) => x.endsWith("not allowed")),
+                    await error(connect("127.0.0.1", 65536)),
+                    await error(client.read(0)),
+                    (() => { try { new TcpSocket() } catch (e) { return e.message } })(),
+                ].map(String);
+                a.close();
+                b.close();
+            "#,
+            )
+            .unwrap();
+            promise.into_future::<()>().await.unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "ping",
+                    "pong",
+                    "end",
+                    "true true true",
+                    "8 datagram 127.0.0.1 true",
+                    "IO Error: the socket is closed",
+                    "IO Error: the socket is closed",
+                    "IO Error: connecting to 127.0.0.2:80 is not allowed",
+                    "IO Error: connecting to localhost:2 is not allowed",
+                    "IO Error: connecting to [::1]:80 is not allowed",
+                    "IO Error: listening on 0.0.0.0:0 is not allowed",
+                    "IO Error: sending to example.com:53 is not allowed",
+                    "false",
+                    "The port must be an integer between 0 and 65535",
+                    "The size must be a positive integer",
+                    "Illegal constructor",
+                ]
+            );
+        })
+        .await;
+    }
+}
@@ -0,0 +1,175 @@
+use core::time::Duration;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{function::Func, Ctx, Function, Object, Result};
+
+/// The marks and measures are kept in JavaScript around the native clock, entries are plain
+/// objects like the `PerformanceEntry`s of browsers.
+const PERFORMANCE: &str = r#"(now, timeOrigin) => {
+    let entries = [];
+    const error = (message) => {
+        const e = new Error(message);
+        e.name = "SyntaxError";
+        return e;
+    };
+    const timeOf = (mark) => {
+        if (typeof mark === "number") return mark;
+        const name = `${mark}`;
+        for (let i = entries.length - 1; i >= 0; i--) {
+            if (entries[i].entryType === "mark" && entries[i].name === name) return entries[i].startTime;
+        }
+        throw error(`The mark '${name}' does not exist.`);
+    };
+    const entry = (name, entryType, startTime, duration, detail) => {
+        const res = Object.freeze({ name: `${name}`, entryType, startTime, duration, detail });
+        entries.push(res);
+        return res;
+    };
+    const clear = (entryType, name) => {
+        entries = entries.filter((e) => e.entryType !== entryType || (name !== undefined && e.name !== `${name}`));
+    };
+
+    return {
+        timeOrigin,
+        now,
+        mark(name, options = {}) {
+            const startTime = options.startTime ?? now();
+            if (startTime < 0) throw new TypeError("The mark start time must not be negative.");
+            return entry(name, "mark", startTime, 0, options.detail ?? null);
+        },
+        measure(name, start, end) {
+            let options = {};
+            if (typeof start === "object" && start !== null) {
+                options = start;
+                start = options.start;
+                end = options.end;
+                if (options.duration !== undefined) {
+                    if (start === undefined) start = timeOf(end) - options.duration;
+                    else end = timeOf(start) + options.duration;
+                }
+            }
+            const startTime = start === undefined ? 0 : timeOf(start);
+            const endTime = end === undefined ? now() : timeOf(end);
+            return entry(name, "measure", startTime, endTime - startTime, options.detail ?? null);
+        },
+        getEntries() { return entries.slice(); },
+        getEntriesByType(type) { return entries.filter((e) => e.entryType === `${type}`); },
+        getEntriesByName(name, type) {
+            return entries.filter((e) => e.name === `${name}` && (type === undefined || e.entryType === `${type}`));
+        },
+        clearMarks(name) { clear("mark", name); },
+        clearMeasures(name) { clear("measure", name); },
+        toJSON() { return { timeOrigin }; },
+    };
+}"#;
+
+/// The default resolution of `performance.now()`, as used by browsers for pages without cross
+/// origin isolation.
+pub const DEFAULT_RESOLUTION: Duration = Duration::from_micros(100);
+
+/// Define the `performance` object on the global object of the context.
+///
+/// `performance.now()` returns the milliseconds elapsed since the object was installed, measured
+/// with the monotonic [`Instant`] clock and rounded down to a multiple of `resolution`. A coarse
+/// resolution reduces the precision available to timing side channels, a zero resolution disables
+/// the rounding.
+///
+/// Besides `now` and `timeOrigin` the object supports `mark`, `measure`, `getEntries`,
+/// `getEntriesByName`, `getEntriesByType`, `clearMarks` and `clearMeasures` with the entries
+/// represented as plain objects. [`install`](super::install) calls this function with
+/// [`DEFAULT_RESOLUTION`].
+///
+/// ```
+/// # use std::time::Duration;
+/// # use rquickjs::{Runtime, Context, web};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     web::install_performance(&ctx, Duration::from_millis(1)).unwrap();
+///     let res: Vec<f64> = ctx
+///         .eval(
+///             r#"
+///             performance.mark("start");
+///             performance.measure("work", "start");
+///             [performance.now(), performance.getEntriesByType("measure").length]
+///         "#,
+///         )
+///         .unwrap();
+///     assert_eq!(res[0].fract(), 0.0);
+///     assert_eq!(res[1], 1.0);
+/// });
+/// ```
+pub fn install_performance<'js>(ctx: &Ctx<'js>, resolution: Duration) -> Result<()> {
+    let origin = Instant::now();
+    let time_origin = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs_f64() * 1000.0)
+        .unwrap_or_default();
+    let resolution = resolution.as_nanos();
+    let now = move || {
+        let mut elapsed = origin.elapsed().as_nanos();
+        if resolution > 0 {
+            elapsed -= elapsed % resolution;
+        }
+        elapsed as f64 / 1_000_000.0
+    };
+    let define: Function = ctx.eval(PERFORMANCE)?;
+    let performance: Object = define.call((Func::from(now), time_origin))?;
+    ctx.globals().set("performance", performance)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{web, *};
+
+    #[test]
+    fn performance() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const a = performance.now();
+                    const b = performance.now();
+                    performance.mark("a", { startTime: 1, detail: "x" });
+                    performance.mark("b", { startTime: 4 });
+                    performance.mark("a", { startTime: 2 });
+                    const measure = performance.measure("m", "a", "b");
+                    const fromOptions = performance.measure("o", { end: "b", duration: 1 });
+                    const error = (f) => { try { f() } catch (e) { return e.name } };
+                    [
+                        `${a >= 0 && b >= a && performance.timeOrigin > 0}`,
+                        `${measure.startTime} ${measure.duration} ${measure.entryType}`,
+                        `${fromOptions.startTime} ${fromOptions.duration}`,
+                        performance.getEntriesByName("a").map((e) => e.startTime).join(),
+                        `${performance.getEntriesByName("a", "mark")[0].detail}`,
+                        error(() => performance.measure("x", "missing")),
+                        (performance.clearMarks("a"), performance.getEntries().map((e) => e.name).join()),
+                        (performance.clearMeasures(), performance.clearMarks(), `${performance.getEntries().length}`),
+                    ]
+                "#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "true",
+                    "2 2 measure",
+                    "3 1",
+                    "1,2",
+                    "x",
+                    "SyntaxError",
+                    "b,m,o",
+                    "0"
+                ]
+            );
+
+            web::install_performance(&ctx, Duration::from_millis(10)).unwrap();
+            let now: f64 = ctx.eval("performance.now()").unwrap();
+            assert_eq!(now % 10.0, 0.0);
+        })
+    }
+}
@@ -0,0 +1,397 @@
+#[cfg(not(feature = "system"))]
+use alloc::rc::Rc;
+use core::{fmt, time::Duration};
+use std::time::Instant;
+
+use crate::{
+    function::{Func, Opt},
+    module::Declared,
+    Array, BigInt, Ctx, Exception, Function, Module, Object, Result, StdString,
+};
+#[cfg(not(feature = "system"))]
+use crate::{qjs, Error};
+
+use super::DEFAULT_RESOLUTION;
+
+/// The values and functions exported by the module.
+const EXPORTS: [&str; 6] = ["env", "argv", "platform", "arch", "hrtime", "exit"];
+
+/// The name of the operating system like Node reports it.
+fn platform() -> &'static str {
+    match std::env::consts::OS {
+        "macos" | "ios" => "darwin",
+        "windows" => "win32",
+        os => os,
+    }
+}
+
+/// The name of the architecture like Node reports it.
+fn arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "x86" => "ia32",
+        "aarch64" => "arm64",
+        "powerpc64" => "ppc64",
+        arch => arch,
+    }
+}
+
+/// A native module giving scripts controlled access to their process, for scripts run like
+/// command line programs.
+///
+/// The module exports:
+///
+/// - `env`, a frozen object with the environment variables which were allowed with
+///   [`Process::allow_env`] and are set when the module is declared.
+/// - `argv`, a frozen array with the arguments set with [`Process::set_args`].
+/// - `platform` and `arch`, the operating system and architecture with the names Node uses, like
+///   `"linux"` and `"x64"`.
+/// - `hrtime(previous)`, the time of a monotonic clock as `[seconds, nanoseconds]`, or the time
+///   elapsed since `previous`. `hrtime.bigint()` returns the time in nanoseconds as a `BigInt`.
+///   Both are rounded down to a multiple of the resolution set with [`Process::set_resolution`],
+///   [`DEFAULT_RESOLUTION`] like `performance.now()` unless changed.
+/// - `exit(code)`, which calls the callback set with [`Process::on_exit`] and then stops the
+///   script with an error it can't catch. Throws an ordinary error if no callback was set, which
+///   is always the case with the `system` feature since the error can only be made uncatchable
+///   with the bundled sources.
+///
+/// Nothing else about the host is exposed, so the module can be given to untrusted scripts.
+///
+/// ```
+/// # use std::{cell::Cell, rc::Rc};
+/// # use rquickjs::{Runtime, Context, Module, web::Process};
+/// std::env::set_var("GREETING", "hello");
+/// let code = Rc::new(Cell::new(None));
+/// let process = Process::new()
+///     .with_env("GREETING")
+///     .with_env("HOME")
+///     .with_args(["script.js", "world"])
+///     .with_exit({
+///         let code = code.clone();
+///         move |x| code.set(Some(x))
+///     });
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     process.declare(ctx.clone(), "process").unwrap();
+///     let res = Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { env, argv, exit } from "process";
+///         globalThis.greeting = `${env.GREETING} ${argv[1]}`;
+///         try {
+///             exit(3);
+///         } finally {
+///             globalThis.unreachable = true;
+///         }
+///     "#,
+///     )
+///     .unwrap()
+///     .finish::<()>();
+///     assert!(res.is_err());
+///     assert_eq!(ctx.globals().get::<_, String>("greeting").unwrap(), "hello world");
+///     assert!(!ctx.globals().contains_key("unreachable").unwrap());
+/// });
+/// assert_eq!(code.get(), Some(3));
+/// ```
+#[derive(Clone)]
+pub struct Process {
+    env: Vec<StdString>,
+    args: Vec<StdString>,
+    #[cfg(not(feature = "system"))]
+    exit: Option<Rc<dyn Fn(i32)>>,
+    resolution: Duration,
+}
+
+impl Default for Process {
+    fn default() -> Self {
+        Self {
+            env: Vec::new(),
+            args: Vec::new(),
+            #[cfg(not(feature = "system"))]
+            exit: None,
+            resolution: DEFAULT_RESOLUTION,
+        }
+    }
+}
+
+impl Process {
+    /// Create a module without environment variables, arguments and exit callback.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the resolution `hrtime` is rounded down to.
+    ///
+    /// A coarse resolution reduces the precision available to timing side channels, a zero
+    /// resolution disables the rounding.
+    pub fn set_resolution(&mut self, resolution: Duration) -> &mut Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Set the resolution `hrtime` is rounded down to.
+    #[must_use]
+    pub fn with_resolution(mut self, resolution: Duration) -> Self {
+        self.set_resolution(resolution);
+        self
+    }
+
+    /// Allow scripts to read an environment variable.
+    pub fn allow_env<N: Into<StdString>>(&mut self, name: N) -> &mut Self {
+        self.env.push(name.into());
+        self
+    }
+
+    /// Allow scripts to read an environment variable.
+    #[must_use]
+    pub fn with_env<N: Into<StdString>>(mut self, name: N) -> Self {
+        self.allow_env(name);
+        self
+    }
+
+    /// Set the arguments exported as `argv`.
+    pub fn set_args<I, A>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<StdString>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the arguments exported as `argv`.
+    #[must_use]
+    pub fn with_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<StdString>,
+    {
+        self.set_args(args);
+        self
+    }
+
+    /// Set the callback called with the exit code when a script calls `exit`.
+    ///
+    /// The callback decides what exiting means for the host, for example terminating the process
+    /// or only dropping the context.
+    #[cfg(not(feature = "system"))]
+    pub fn on_exit<F: Fn(i32) + 'static>(&mut self, callback: F) -> &mut Self {
+        self.exit = Some(Rc::new(callback));
+        self
+    }
+
+    /// Set the callback called with the exit code when a script calls `exit`.
+    #[cfg(not(feature = "system"))]
+    #[must_use]
+    pub fn with_exit<F: Fn(i32) + 'static>(mut self, callback: F) -> Self {
+        self.on_exit(callback);
+        self
+    }
+
+    /// Declare the module under the given name, for example from a module loader.
+    pub fn declare<'js, N>(&self, ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let exports = Object::new(ctx.clone())?;
+
+        let env = Object::new(ctx.clone())?;
+        for name in &self.env {
+            if let Some(value) = std::env::var_os(name) {
+                env.set(name.as_str(), value.to_string_lossy().as_ref())?;
+            }
+        }
+        env.freeze()?;
+        exports.set("env", env)?;
+
+        let argv = Array::new(ctx.clone())?;
+        for (i, arg) in self.args.iter().enumerate() {
+            argv.set(i, arg.as_str())?;
+        }
+        argv.as_object().freeze()?;
+        exports.set("argv", argv)?;
+
+        exports.set("platform", platform())?;
+        exports.set("arch", arch())?;
+
+        let origin = Instant::now();
+        let resolution = self.resolution.as_nanos();
+        let elapsed = move || {
+            let mut elapsed = origin.elapsed().as_nanos();
+            if resolution > 0 {
+                elapsed -= elapsed % resolution;
+            }
+            elapsed
+        };
+        let hrtime = Function::new(ctx.clone(), move |previous: Opt<Vec<f64>>| {
+            let elapsed = Duration::from_nanos(elapsed() as u64);
+            let (mut seconds, mut nanos) =
+                (elapsed.as_secs() as f64, elapsed.subsec_nanos() as f64);
+            if let Some([previous_seconds, previous_nanos]) = previous.0.as_deref() {
+                seconds -= previous_seconds;
+                nanos -= previous_nanos;
+                if nanos < 0.0 {
+                    seconds -= 1.0;
+                    nanos += 1e9;
+                }
+            }
+            vec![seconds, nanos]
+        })?;
+        hrtime.set(
+            "bigint",
+            Func::from(move |ctx: Ctx<'js>| BigInt::from_u64(ctx, elapsed() as u64)),
+        )?;
+        exports.set("hrtime", hrtime)?;
+
+        #[cfg(not(feature = "system"))]
+        let exit = self.exit.clone();
+        exports.set(
+            "exit",
+            Func::from(move |ctx: Ctx<'js>, code: Opt<i32>| -> Result<()> {
+                #[cfg(not(feature = "system"))]
+                if let Some(exit) = &exit {
+                    exit(code.0.unwrap_or(0));
+                    return Err(throw_uncatchable(&ctx));
+                }
+                let _ = code;
+                Err(Exception::throw_message(&ctx, "exit is not supported"))
+            }),
+        )?;
+
+        Module::declare_value(
+            ctx,
+            name,
+            exports.into_value(),
+            EXPORTS.iter().map(|x| x.to_string()).collect(),
+        )
+    }
+}
+
+impl fmt::Debug for Process {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Process");
+        debug.field("env", &self.env).field("args", &self.args);
+        #[cfg(not(feature = "system"))]
+        debug.field("exit", &self.exit.is_some());
+        debug.field("resolution", &self.resolution).finish()
+    }
+}
+
+/// Throw an error which scripts can't catch, unwinding them up to the host.
+#[cfg(not(feature = "system"))]
+fn throw_uncatchable(ctx: &Ctx<'_>) -> Error {
+    unsafe {
+        let error = qjs::JS_NewError(ctx.as_ptr());
+        qjs::rquickjs_set_uncatchable_error(ctx.as_ptr(), error, 1);
+        qjs::JS_Throw(ctx.as_ptr(), error);
+    }
+    Error::Exception
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{web::Process, *};
+
+    #[cfg(not(feature = "system"))]
+    #[test]
+    fn process_module() {
+        use std::{cell::Cell, rc::Rc};
+
+        std::env::set_var("RQUICKJS_PROCESS_ALLOWED", "yes");
+        std::env::set_var("RQUICKJS_PROCESS_DENIED", "no");
+        test_with(|ctx| {
+            let code = Rc::new(Cell::new(None));
+            let mut process = Process::new();
+            process
+                .allow_env("RQUICKJS_PROCESS_ALLOWED")
+                .allow_env("RQUICKJS_PROCESS_MISSING")
+                .set_args(["a", "b"])
+                .on_exit({
+                    let code = code.clone();
+                    move |x| code.set(Some(x))
+                });
+            process.declare(ctx.clone(), "process").unwrap();
+            Process::new().declare(ctx.clone(), "bare").unwrap();
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import * as process from "process";
+                import { exit } from "bare";
+                const start = process.hrtime();
+                const diff = process.hrtime(start);
+                const error = (f) => { try { f() } catch (e) { return e.message } };
+                globalThis.res = [
+                    JSON.stringify(process.env),
+                    `${Object.isFrozen(process.env)} ${Object.isFrozen(process.argv)}`,
+                    process.argv.join(),
+                    `${typeof process.platform} ${typeof process.arch}`,
+                    `${start.length} ${diff[0] >= 0 && diff[1] >= 0 && diff[1] < 1e9}`,
+                    typeof process.hrtime.bigint(),
+                    error(() => exit()),
+                ];
+                globalThis.exit = () => {
+                    try {
+                        process.exit(2);
+                    } catch (e) {
+                        globalThis.caught = true;
+                    }
+                };
+            "#,
+            )
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    r#"{"RQUICKJS_PROCESS_ALLOWED":"yes"}"#,
+                    "true true",
+                    "a,b",
+                    "string string",
+                    "2 true",
+                    "bigint",
+                    "exit is not supported",
+                ]
+            );
+
+            assert!(ctx.eval::<(), _>("exit()").is_err());
+            assert_eq!(code.get(), Some(2));
+            assert!(!ctx.globals().contains_key("caught").unwrap());
+            // The context stays usable after a script exited.
+            assert_eq!(ctx.eval::<i32, _>("1 + 1").unwrap(), 2);
+        })
+    }
+
+    #[test]
+    fn hrtime_resolution() {
+        test_with(|ctx| {
+            Process::new()
+                .with_resolution(Duration::from_millis(1))
+                .declare(ctx.clone(), "process")
+                .unwrap();
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import { hrtime } from "process";
+                const start = Date.now();
+                while (Date.now() - start < 3) {}
+                globalThis.res = [hrtime()[1] % 1e6, hrtime.bigint() % 1000000n, hrtime.bigint() > 0n];
+            "#,
+            )
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+            let res: Vec<Value> = ctx.globals().get("res").unwrap();
+            assert_eq!(res[0].as_number(), Some(0.0));
+            assert!(ctx.eval::<bool, _>("res[1] === 0n && res[2]").unwrap());
+        })
+    }
+}
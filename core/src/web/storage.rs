@@ -0,0 +1,506 @@
+use alloc::{collections::BTreeMap, rc::Rc};
+use core::{
+    cell::{Cell, RefCell},
+    fmt,
+    ops::Bound,
+};
+use std::io;
+
+use crate::{
+    convert::List, function::Func, module::Declared, Ctx, Error, Function, Module, Object, Result,
+    StdString,
+};
+
+/// The functions exported by the module.
+const EXPORTS: [&str; 7] = [
+    "get",
+    "put",
+    "delete",
+    "keys",
+    "entries",
+    "transaction",
+    "usage",
+];
+
+/// The number of entries fetched from the backend at once while iterating.
+const PAGE_SIZE: usize = 100;
+
+/// Values are stored as JSON. Every operation returns a promise so backends can become
+/// asynchronous without changing the interface of the module.
+const API: &str = r#"(native, pageSize) => {
+    const keyOf = (key) => {
+        if (typeof key !== "string") throw new TypeError("The key must be a string");
+        return key;
+    };
+    const serialize = (value) => {
+        const json = JSON.stringify(value);
+        if (json === undefined) throw new TypeError("The value can't be serialized as JSON");
+        return json;
+    };
+    const parse = (json) => json === undefined ? undefined : JSON.parse(json);
+    async function* scan(prefix) {
+        prefix = String(prefix);
+        let after = undefined;
+        for (;;) {
+            const page = native.scan(prefix, after, pageSize);
+            yield* page;
+            if (page.length < pageSize) return;
+            after = page[page.length - 1][0];
+        }
+    }
+
+    return {
+        get: async (key) => parse(native.get(keyOf(key))),
+        put: async (key, value) => native.commit([[keyOf(key), serialize(value)]]),
+        delete: async (key) => native.commit([[keyOf(key), undefined]]),
+        keys: async function* (prefix = "") {
+            for await (const [key] of scan(prefix)) yield key;
+        },
+        entries: async function* (prefix = "") {
+            for await (const [key, value] of scan(prefix)) yield [key, parse(value)];
+        },
+        transaction: async (callback) => {
+            const changes = new Map();
+            let open = true;
+            const check = () => {
+                if (!open) throw new TypeError("The transaction is finished");
+            };
+            const result = callback({
+                get(key) {
+                    check();
+                    key = keyOf(key);
+                    return parse(changes.has(key) ? changes.get(key) : native.get(key));
+                },
+                put(key, value) {
+                    check();
+                    changes.set(keyOf(key), serialize(value));
+                },
+                delete(key) {
+                    check();
+                    changes.set(keyOf(key), undefined);
+                },
+            });
+            open = false;
+            if (result instanceof Promise) throw new TypeError("The transaction callback must not be async");
+            native.commit(Array.from(changes));
+            return result;
+        },
+        usage: async () => native.usage(),
+    };
+}"#;
+
+/// A change of an entry, `None` deletes it.
+pub type Change = (StdString, Option<StdString>);
+
+/// Stores the entries of a [`Storage`] module.
+///
+/// Keys and values are strings, the values are JSON. Implementations decide where the entries are
+/// kept, [`MemoryBackend`] keeps them in memory.
+pub trait StorageBackend {
+    /// Get the value of a key.
+    fn get(&self, key: &str) -> io::Result<Option<StdString>>;
+
+    /// Get at most `limit` entries with keys starting with `prefix`, sorted by key and starting
+    /// after the key `after`.
+    fn scan(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<(StdString, StdString)>>;
+
+    /// Apply all of the changes or none of them.
+    fn apply(&self, changes: &[Change]) -> io::Result<()>;
+
+    /// The number of bytes of all keys and values.
+    fn size(&self) -> io::Result<u64>;
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackend for Rc<B> {
+    fn get(&self, key: &str) -> io::Result<Option<StdString>> {
+        (**self).get(key)
+    }
+
+    fn scan(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<(StdString, StdString)>> {
+        (**self).scan(prefix, after, limit)
+    }
+
+    fn apply(&self, changes: &[Change]) -> io::Result<()> {
+        (**self).apply(changes)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+}
+
+/// A [`StorageBackend`] keeping the entries in memory.
+///
+/// Wrap it in an [`Rc`] to share it between contexts or to inspect the entries from the host.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    entries: RefCell<BTreeMap<StdString, StdString>>,
+    size: Cell<u64>,
+}
+
+impl MemoryBackend {
+    /// Create an empty backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn get(&self, key: &str) -> io::Result<Option<StdString>> {
+        Ok(self.entries.borrow().get(key).cloned())
+    }
+
+    fn scan(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<(StdString, StdString)>> {
+        let start = match after {
+            Some(after) if after >= prefix => Bound::Excluded(after),
+            _ => Bound::Included(prefix),
+        };
+        Ok(self
+            .entries
+            .borrow()
+            .range::<str, _>((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .take(limit)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn apply(&self, changes: &[Change]) -> io::Result<()> {
+        let mut entries = self.entries.borrow_mut();
+        let mut size = self.size.get();
+        for (key, value) in changes {
+            let old = match value {
+                Some(value) => {
+                    size += (key.len() + value.len()) as u64;
+                    entries.insert(key.clone(), value.clone())
+                }
+                None => entries.remove(key),
+            };
+            if let Some(old) = old {
+                size -= (key.len() + old.len()) as u64;
+            }
+        }
+        self.size.set(size);
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.size.get())
+    }
+}
+
+/// A native module giving scripts a key-value store, limited by quotas set by the host.
+///
+/// The module exports the following functions, which all return promises:
+///
+/// - `get(key)` resolves to the value of a key, or `undefined` if there is none.
+/// - `put(key, value)` sets the value of a key.
+/// - `delete(key)` removes a key.
+/// - `keys(prefix)` and `entries(prefix)` are async iterables of the keys or the `[key, value]`
+///   pairs with keys starting with the prefix, sorted by key.
+/// - `transaction(callback)` calls the callback with an object with synchronous `get`, `put` and
+///   `delete` methods. Its changes are applied together once the callback returned and none of
+///   them if it threw. The callback can't be async, so no other script code runs during the
+///   transaction.
+/// - `usage()` resolves to an object with the `size` of the store in bytes and its `quota`, which
+///   is `undefined` if there is none.
+///
+/// Keys are strings, values anything which can be serialized as JSON. The size of an entry is
+/// the length of its key and of the JSON of its value. Changes exceeding the quota or the
+/// maximum size of a value are rejected with an error.
+///
+/// ```
+/// # use std::rc::Rc;
+/// # use rquickjs::{Runtime, Context, Module, web::{Storage, MemoryBackend, StorageBackend}};
+/// let backend = Rc::new(MemoryBackend::new());
+/// let storage = Storage::new(backend.clone()).with_quota(1024);
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     storage.declare(ctx.clone(), "storage").unwrap();
+///     Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { get, put, transaction } from "storage";
+///         await put("visits", 1);
+///         await transaction((tx) => tx.put("visits", tx.get("visits") + 1));
+///         globalThis.visits = await get("visits");
+///         globalThis.full = await put("big", "x".repeat(2048)).catch((e) => e.message);
+///     "#,
+///     )
+///     .unwrap()
+///     .finish::<()>()
+///     .unwrap();
+///     assert_eq!(ctx.globals().get::<_, i32>("visits").unwrap(), 2);
+///     assert_eq!(
+///         ctx.globals().get::<_, String>("full").unwrap(),
+///         "IO Error: the storage quota of 1024 bytes is exceeded"
+///     );
+/// });
+/// assert_eq!(backend.get("visits").unwrap().as_deref(), Some("2"));
+/// ```
+#[derive(Clone)]
+pub struct Storage {
+    backend: Rc<dyn StorageBackend>,
+    quota: Option<u64>,
+    max_value_size: Option<usize>,
+}
+
+impl Storage {
+    /// Create a module storing its entries in the backend, without quota.
+    pub fn new<B: StorageBackend + 'static>(backend: B) -> Self {
+        Storage {
+            backend: Rc::new(backend),
+            quota: None,
+            max_value_size: None,
+        }
+    }
+
+    /// Set the maximum number of bytes of all entries.
+    pub fn set_quota(&mut self, bytes: u64) -> &mut Self {
+        self.quota = Some(bytes);
+        self
+    }
+
+    /// Set the maximum number of bytes of all entries.
+    #[must_use]
+    pub fn with_quota(mut self, bytes: u64) -> Self {
+        self.set_quota(bytes);
+        self
+    }
+
+    /// Set the maximum number of bytes of the JSON of a single value.
+    pub fn set_max_value_size(&mut self, bytes: usize) -> &mut Self {
+        self.max_value_size = Some(bytes);
+        self
+    }
+
+    /// Set the maximum number of bytes of the JSON of a single value.
+    #[must_use]
+    pub fn with_max_value_size(mut self, bytes: usize) -> Self {
+        self.set_max_value_size(bytes);
+        self
+    }
+
+    /// Declare the module under the given name, for example from a module loader.
+    pub fn declare<'js, N>(&self, ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let storage = Rc::new(self.clone());
+        let native = Object::new(ctx.clone())?;
+        native.set("get", {
+            let storage = storage.clone();
+            Func::from(move |key: StdString| Ok::<_, Error>(storage.backend.get(&key)?))
+        })?;
+        native.set("scan", {
+            let storage = storage.clone();
+            Func::from(
+                move |prefix: StdString, after: Option<StdString>, limit: usize| {
+                    let entries = storage.backend.scan(&prefix, after.as_deref(), limit)?;
+                    Ok::<_, Error>(entries.into_iter().map(List).collect::<Vec<_>>())
+                },
+            )
+        })?;
+        native.set("commit", {
+            let storage = storage.clone();
+            Func::from(move |changes: Vec<List<Change>>| {
+                let changes: Vec<_> = changes.into_iter().map(|x| x.0).collect();
+                storage.commit(&changes)
+            })
+        })?;
+        native.set(
+            "usage",
+            Func::from(move |ctx: Ctx<'js>| {
+                let usage = Object::new(ctx)?;
+                usage.set("size", storage.backend.size()?)?;
+                usage.set("quota", storage.quota)?;
+                Ok::<_, Error>(usage)
+            }),
+        )?;
+
+        let define: Function = ctx.eval(API)?;
+        let exports: Object = define.call((native, PAGE_SIZE))?;
+        Module::declare_value(
+            ctx,
+            name,
+            exports.into_value(),
+            EXPORTS.iter().map(|x| x.to_string()).collect(),
+        )
+    }
+
+    /// Check the changes against the limits and apply them.
+    fn commit(&self, changes: &[Change]) -> Result<()> {
+        if let Some(max) = self.max_value_size {
+            if changes
+                .iter()
+                .any(|(_, value)| value.as_ref().map_or(0, |x| x.len()) > max)
+            {
+                return Err(denied(format!(
+                    "the value is larger than the maximum of {max} bytes"
+                )));
+            }
+        }
+        if let Some(quota) = self.quota {
+            let mut size = self.backend.size()? as i64;
+            for (key, value) in changes {
+                if let Some(old) = self.backend.get(key)? {
+                    size -= (key.len() + old.len()) as i64;
+                }
+                if let Some(value) = value {
+                    size += (key.len() + value.len()) as i64;
+                }
+            }
+            // Changes which shrink the store are always allowed.
+            if size > quota as i64 && size > self.backend.size()? as i64 {
+                return Err(denied(format!(
+                    "the storage quota of {quota} bytes is exceeded"
+                )));
+            }
+        }
+        self.backend.apply(changes)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Storage")
+            .field("quota", &self.quota)
+            .field("max_value_size", &self.max_value_size)
+            .finish_non_exhaustive()
+    }
+}
+
+fn denied(message: StdString) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::PermissionDenied, message))
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::{
+        web::{MemoryBackend, Storage, StorageBackend},
+        *,
+    };
+
+    #[test]
+    fn storage_module() {
+        test_with(|ctx| {
+            let backend = Rc::new(MemoryBackend::new());
+            Storage::new(backend.clone())
+                .with_quota(64)
+                .with_max_value_size(16)
+                .declare(ctx.clone(), "storage")
+                .unwrap();
+            Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import * as storage from "storage";
+                const error = (promise) => promise.then(() => "resolved", (e) => e.message);
+                const collect = async (iterable) => {
+                    const res = [];
+                    for await (const x of iterable) res.push(JSON.stringify(x));
+                    return res.join(" ");
+                };
+
+                await storage.put("a", { n: 1 });
+                await storage.put("b/1", [1]);
+                await storage.put("b/2", "two");
+                await storage.put("c", null);
+                const before = await storage.get("a");
+                await storage.delete("a");
+                const tx = await storage.transaction((tx) => {
+                    tx.put("b/1", tx.get("b/1").concat(2));
+                    tx.delete("c");
+                    tx.put("d", tx.get("d") ?? 0);
+                    return tx;
+                });
+                const failed = await error(storage.transaction((tx) => {
+                    tx.put("b/2", "changed");
+                    throw new Error("rolled back");
+                }));
+
+                globalThis.res = [
+                    JSON.stringify(before),
+                    String(await storage.get("a")),
+                    await collect(storage.entries()),
+                    await collect(storage.keys("b/")),
+                    JSON.stringify(await storage.usage()),
+                    failed,
+                    await error((async () => tx.get("d"))()),
+                    await error(storage.put("e", "0123456789abcdef")),
+                    await error(storage.put("k".repeat(40), "0123456789abc")),
+                    await error(storage.put("f", undefined)),
+                    await error(storage.get(1)),
+                    await error(storage.transaction(async () => {})),
+                ];
+            "#,
+            )
+            .unwrap()
+            .finish::<()>()
+            .unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    r#"{"n":1}"#,
+                    "undefined",
+                    r#"["b/1",[1,2]] ["b/2","two"] ["d",0]"#,
+                    r#""b/1" "b/2""#,
+                    r#"{"size":18,"quota":64}"#,
+                    "rolled back",
+                    "The transaction is finished",
+                    "IO Error: the value is larger than the maximum of 16 bytes",
+                    "IO Error: the storage quota of 64 bytes is exceeded",
+                    "The value can't be serialized as JSON",
+                    "The key must be a string",
+                    "The transaction callback must not be async",
+                ]
+            );
+            assert_eq!(backend.get("b/2").unwrap().as_deref(), Some(r#""two""#));
+            assert_eq!(backend.size().unwrap(), 18);
+        })
+    }
+
+    #[test]
+    fn memory_backend_scan() {
+        let backend = MemoryBackend::new();
+        let changes: Vec<_> = (0..5)
+            .map(|x| (format!("k{x}"), Some(x.to_string())))
+            .chain([("j".into(), Some("".into())), ("l".into(), Some("".into()))])
+            .collect();
+        backend.apply(&changes).unwrap();
+        let keys = |after, limit| -> Vec<StdString> {
+            backend
+                .scan("k", after, limit)
+                .unwrap()
+                .into_iter()
+                .map(|x| x.0)
+                .collect()
+        };
+        assert_eq!(keys(None, 2), ["k0", "k1"]);
+        assert_eq!(keys(Some("k1"), 10), ["k2", "k3", "k4"]);
+        assert_eq!(keys(Some("a"), 1), ["k0"]);
+        assert!(keys(Some("k4"), 10).is_empty());
+    }
+}
@@ -0,0 +1,872 @@
+use crate::{Ctx, Function, Object, Result};
+
+#[cfg(feature = "futures")]
+use super::array_bytes;
+#[cfg(feature = "futures")]
+use crate::{
+    function::{Async, Func},
+    promise::Promised,
+    Error, IntoJs, TypedArray,
+};
+#[cfg(feature = "futures")]
+use alloc::rc::Rc;
+#[cfg(feature = "futures")]
+use core::{
+    cell::{Cell, RefCell},
+    future::poll_fn,
+    pin::Pin,
+    task::Poll,
+};
+#[cfg(feature = "futures")]
+use futures_io::{AsyncRead, AsyncWrite};
+#[cfg(feature = "futures")]
+use std::io;
+
+/// A subset of the streams standard: default readable and writable streams with their readers,
+/// writers and controllers, queuing strategies with a high water mark and a size function,
+/// `pipeTo` and async iteration. Byte streams, `tee` and transform streams are not supported.
+///
+/// The state of a stream is kept in a `WeakMap` so that the objects only expose the standard
+/// properties.
+const CLASSES: &str = r#"() => {
+    const token = Symbol();
+    const states = new WeakMap();
+    const deferred = () => {
+        const res = {};
+        res.promise = new Promise((resolve, reject) => {
+            res.resolve = resolve;
+            res.reject = reject;
+        });
+        // The promises of readers and writers are often not observed.
+        res.promise.catch(() => {});
+        return res;
+    };
+    const resolved = () => {
+        const res = deferred();
+        res.resolve();
+        return res;
+    };
+    const rejected = (e) => {
+        const res = deferred();
+        res.reject(e);
+        return res;
+    };
+    const strategyOf = ({ highWaterMark = 1, size = () => 1 } = {}) => {
+        highWaterMark = Number(highWaterMark);
+        if (Number.isNaN(highWaterMark) || highWaterMark < 0) {
+            throw new RangeError("The highWaterMark must be a non-negative number");
+        }
+        if (typeof size !== "function") throw new TypeError("The size must be a function");
+        return { highWaterMark, size };
+    };
+    const call = (object, name, ...args) => {
+        try {
+            const method = object[name];
+            return Promise.resolve(method === undefined ? undefined : method.apply(object, args));
+        } catch (e) {
+            return Promise.reject(e);
+        }
+    };
+    const start = (object, controller) => Promise.resolve(object.start === undefined ? undefined : object.start(controller));
+    const stateOf = (stream, type) => {
+        const state = states.get(stream);
+        if (!(state instanceof type)) throw new TypeError(`The argument is not a ${type.name.replace("State", "Stream")}`);
+        return state;
+    };
+
+    class ReadableState {
+        constructor(source, strategy) {
+            this.source = source;
+            this.strategy = strategyOf(strategy);
+            this.queue = [];
+            this.queueSize = 0;
+            this.state = "readable";
+            this.storedError = undefined;
+            this.closeRequested = false;
+            this.started = false;
+            this.pulling = false;
+            this.pullAgain = false;
+            this.reader = undefined;
+            this.readRequests = [];
+            this.closed = undefined;
+        }
+        desiredSize() {
+            if (this.state === "errored") return null;
+            if (this.state === "closed") return 0;
+            return this.strategy.highWaterMark - this.queueSize;
+        }
+        enqueue(chunk) {
+            if (this.closeRequested || this.state !== "readable") throw new TypeError("The stream is not in a state that permits enqueue");
+            if (this.readRequests.length > 0) {
+                this.readRequests.shift().resolve({ value: chunk, done: false });
+            } else {
+                let size;
+                try {
+                    size = Number(this.strategy.size(chunk));
+                } catch (e) {
+                    this.error(e);
+                    throw e;
+                }
+                this.queue.push([chunk, size]);
+                this.queueSize += size;
+            }
+            this.pull();
+        }
+        close() {
+            if (this.closeRequested || this.state !== "readable") throw new TypeError("The stream is not in a state that permits close");
+            this.closeRequested = true;
+            if (this.queue.length === 0) this.finish();
+        }
+        finish() {
+            this.state = "closed";
+            for (const request of this.readRequests) request.resolve({ value: undefined, done: true });
+            this.readRequests = [];
+            this.closed?.resolve();
+        }
+        error(e) {
+            if (this.state !== "readable") return;
+            this.state = "errored";
+            this.storedError = e;
+            this.queue = [];
+            this.queueSize = 0;
+            for (const request of this.readRequests) request.reject(e);
+            this.readRequests = [];
+            this.closed?.reject(e);
+        }
+        read() {
+            if (this.queue.length > 0) {
+                const [value, size] = this.queue.shift();
+                this.queueSize -= size;
+                if (this.closeRequested && this.queue.length === 0) this.finish();
+                else this.pull();
+                return Promise.resolve({ value, done: false });
+            }
+            if (this.state === "closed") return Promise.resolve({ value: undefined, done: true });
+            if (this.state === "errored") return Promise.reject(this.storedError);
+            const request = deferred();
+            this.readRequests.push(request);
+            this.pull();
+            return request.promise;
+        }
+        pull() {
+            if (!this.started || this.state !== "readable" || this.closeRequested) return;
+            if (this.readRequests.length === 0 && this.desiredSize() <= 0) return;
+            if (this.pulling) {
+                this.pullAgain = true;
+                return;
+            }
+            this.pulling = true;
+            call(this.source, "pull", this.controller).then(() => {
+                this.pulling = false;
+                if (this.pullAgain) {
+                    this.pullAgain = false;
+                    this.pull();
+                }
+            }, (e) => this.error(e));
+        }
+        cancel(reason) {
+            if (this.state === "closed") return Promise.resolve();
+            if (this.state === "errored") return Promise.reject(this.storedError);
+            this.queue = [];
+            this.queueSize = 0;
+            this.finish();
+            return call(this.source, "cancel", reason).then(() => {});
+        }
+    }
+
+    class ReadableStreamDefaultController {
+        #state;
+        constructor(state, key) {
+            if (key !== token) throw new TypeError("Illegal constructor");
+            this.#state = state;
+        }
+        get desiredSize() { return this.#state.desiredSize(); }
+        enqueue(chunk) { this.#state.enqueue(chunk); }
+        close() { this.#state.close(); }
+        error(e) { this.#state.error(e); }
+    }
+
+    class ReadableStreamDefaultReader {
+        #state;
+        #closed;
+        constructor(stream) {
+            const state = stateOf(stream, ReadableState);
+            if (state.reader !== undefined) throw new TypeError("The stream is locked to a reader");
+            this.#state = state;
+            this.#closed = state.state === "readable" ? deferred() : state.state === "closed" ? resolved() : rejected(state.storedError);
+            state.reader = this;
+            state.closed = this.#closed;
+        }
+        get closed() { return this.#closed.promise; }
+        read() {
+            if (this.#state === undefined) return Promise.reject(new TypeError("The reader has been released"));
+            return this.#state.read();
+        }
+        cancel(reason) {
+            if (this.#state === undefined) return Promise.reject(new TypeError("The reader has been released"));
+            return this.#state.cancel(reason);
+        }
+        releaseLock() {
+            const state = this.#state;
+            if (state === undefined) return;
+            const e = new TypeError("The reader has been released");
+            for (const request of state.readRequests) request.reject(e);
+            state.readRequests = [];
+            this.#closed.reject(e);
+            this.#closed = rejected(e);
+            state.reader = undefined;
+            state.closed = undefined;
+            this.#state = undefined;
+        }
+    }
+
+    class ReadableStream {
+        constructor(source = {}, strategy = {}) {
+            if (source.type !== undefined) throw new RangeError("Byte streams are not supported");
+            const state = new ReadableState(source, strategy);
+            state.controller = new ReadableStreamDefaultController(state, token);
+            states.set(this, state);
+            start(source, state.controller).then(() => {
+                state.started = true;
+                state.pull();
+            }, (e) => state.error(e));
+        }
+        get locked() { return stateOf(this, ReadableState).reader !== undefined; }
+        cancel(reason) {
+            if (this.locked) return Promise.reject(new TypeError("The stream is locked to a reader"));
+            return stateOf(this, ReadableState).cancel(reason);
+        }
+        getReader(options = {}) {
+            if (options.mode !== undefined) throw new RangeError("BYOB readers are not supported");
+            return new ReadableStreamDefaultReader(this);
+        }
+        async pipeTo(destination, { preventClose = false, preventAbort = false, preventCancel = false } = {}) {
+            const reader = this.getReader();
+            const writer = destination.getWriter();
+            try {
+                for (;;) {
+                    let chunk;
+                    try {
+                        chunk = await reader.read();
+                    } catch (e) {
+                        if (!preventAbort) await writer.abort(e);
+                        throw e;
+                    }
+                    if (chunk.done) break;
+                    try {
+                        await writer.write(chunk.value);
+                    } catch (e) {
+                        if (!preventCancel) await reader.cancel(e);
+                        throw e;
+                    }
+                }
+                if (!preventClose) await writer.close();
+            } finally {
+                reader.releaseLock();
+                writer.releaseLock();
+            }
+        }
+        async *values({ preventCancel = false } = {}) {
+            const reader = this.getReader();
+            try {
+                for (;;) {
+                    const { value, done } = await reader.read();
+                    if (done) return;
+                    yield value;
+                }
+            } finally {
+                if (!preventCancel) await reader.cancel();
+                reader.releaseLock();
+            }
+        }
+        [Symbol.asyncIterator](options) { return this.values(options); }
+    }
+
+    class WritableState {
+        constructor(sink, strategy) {
+            this.sink = sink;
+            this.strategy = strategyOf(strategy);
+            this.queue = [];
+            this.queueSize = 0;
+            this.state = "writable";
+            this.storedError = undefined;
+            this.closeRequest = undefined;
+            this.started = false;
+            this.writing = false;
+            this.writer = undefined;
+            this.ready = undefined;
+            this.readyPending = false;
+            this.closed = undefined;
+        }
+        desiredSize() {
+            if (this.state === "errored") return null;
+            if (this.state === "closed") return 0;
+            return this.strategy.highWaterMark - this.queueSize;
+        }
+        closing() {
+            return this.closeRequest !== undefined || this.state === "closed";
+        }
+        write(chunk) {
+            if (this.state === "errored") return Promise.reject(this.storedError);
+            if (this.closing()) return Promise.reject(new TypeError("The stream is closing or closed"));
+            let size;
+            try {
+                size = Number(this.strategy.size(chunk));
+            } catch (e) {
+                this.error(e);
+                return Promise.reject(e);
+            }
+            const request = deferred();
+            this.queue.push({ chunk, size, request });
+            this.queueSize += size;
+            this.updateReady();
+            this.advance();
+            return request.promise;
+        }
+        close() {
+            if (this.state === "errored") return Promise.reject(this.storedError);
+            if (this.closing()) return Promise.reject(new TypeError("The stream is closing or closed"));
+            this.closeRequest = deferred();
+            this.advance();
+            return this.closeRequest.promise;
+        }
+        advance() {
+            if (!this.started || this.writing || this.state !== "writable") return;
+            if (this.queue.length > 0) {
+                const { chunk, size, request } = this.queue[0];
+                this.writing = true;
+                call(this.sink, "write", chunk, this.controller).then(() => {
+                    this.writing = false;
+                    this.queue.shift();
+                    this.queueSize -= size;
+                    request.resolve();
+                    this.updateReady();
+                    this.advance();
+                }, (e) => {
+                    this.writing = false;
+                    this.error(e);
+                });
+            } else if (this.closeRequest !== undefined) {
+                this.writing = true;
+                call(this.sink, "close").then(() => {
+                    this.writing = false;
+                    this.state = "closed";
+                    this.closeRequest.resolve();
+                    this.closed?.resolve();
+                }, (e) => {
+                    this.writing = false;
+                    this.error(e);
+                });
+            }
+        }
+        updateReady() {
+            if (this.ready === undefined || this.state !== "writable") return;
+            const backpressure = this.desiredSize() <= 0;
+            if (backpressure && !this.readyPending) {
+                this.ready = deferred();
+                this.readyPending = true;
+            } else if (!backpressure && this.readyPending) {
+                this.ready.resolve();
+                this.readyPending = false;
+            }
+        }
+        error(e) {
+            if (this.state !== "writable") return;
+            this.state = "errored";
+            this.storedError = e;
+            for (const { request } of this.queue) request.reject(e);
+            this.queue = [];
+            this.queueSize = 0;
+            this.closeRequest?.reject(e);
+            if (this.ready !== undefined) {
+                if (this.readyPending) this.ready.reject(e);
+                else this.ready = rejected(e);
+                this.readyPending = false;
+            }
+            this.closed?.reject(e);
+        }
+        abort(reason) {
+            if (this.state !== "writable") return Promise.resolve();
+            this.error(reason);
+            return call(this.sink, "abort", reason).then(() => {});
+        }
+    }
+
+    class WritableStreamDefaultController {
+        #state;
+        constructor(state, key) {
+            if (key !== token) throw new TypeError("Illegal constructor");
+            this.#state = state;
+        }
+        error(e) { this.#state.error(e); }
+    }
+
+    class WritableStreamDefaultWriter {
+        #state;
+        #ready;
+        #closed;
+        constructor(stream) {
+            const state = stateOf(stream, WritableState);
+            if (state.writer !== undefined) throw new TypeError("The stream is locked to a writer");
+            this.#state = state;
+            state.writer = this;
+            if (state.state === "errored") {
+                state.ready = rejected(state.storedError);
+                state.closed = rejected(state.storedError);
+            } else {
+                state.ready = resolved();
+                state.closed = state.state === "closed" ? resolved() : deferred();
+            }
+            state.readyPending = false;
+            state.updateReady();
+        }
+        get closed() { return (this.#state?.closed ?? this.#closed).promise; }
+        get ready() { return (this.#state?.ready ?? this.#ready).promise; }
+        get desiredSize() {
+            if (this.#state === undefined) throw new TypeError("The writer has been released");
+            return this.#state.desiredSize();
+        }
+        write(chunk) {
+            if (this.#state === undefined) return Promise.reject(new TypeError("The writer has been released"));
+            return this.#state.write(chunk);
+        }
+        close() {
+            if (this.#state === undefined) return Promise.reject(new TypeError("The writer has been released"));
+            return this.#state.close();
+        }
+        abort(reason) {
+            if (this.#state === undefined) return Promise.reject(new TypeError("The writer has been released"));
+            return this.#state.abort(reason);
+        }
+        releaseLock() {
+            const state = this.#state;
+            if (state === undefined) return;
+            const e = new TypeError("The writer has been released");
+            state.ready.reject(e);
+            state.closed.reject(e);
+            this.#ready = rejected(e);
+            this.#closed = rejected(e);
+            state.writer = undefined;
+            state.ready = undefined;
+            state.closed = undefined;
+            this.#state = undefined;
+        }
+    }
+
+    class WritableStream {
+        constructor(sink = {}, strategy = {}) {
+            if (sink.type !== undefined) throw new RangeError("The sink type must be undefined");
+            const state = new WritableState(sink, strategy);
+            state.controller = new WritableStreamDefaultController(state, token);
+            states.set(this, state);
+            start(sink, state.controller).then(() => {
+                state.started = true;
+                state.advance();
+            }, (e) => state.error(e));
+        }
+        get locked() { return stateOf(this, WritableState).writer !== undefined; }
+        abort(reason) {
+            if (this.locked) return Promise.reject(new TypeError("The stream is locked to a writer"));
+            return stateOf(this, WritableState).abort(reason);
+        }
+        close() {
+            if (this.locked) return Promise.reject(new TypeError("The stream is locked to a writer"));
+            return stateOf(this, WritableState).close();
+        }
+        getWriter() { return new WritableStreamDefaultWriter(this); }
+    }
+
+    return {
+        ReadableStream,
+        ReadableStreamDefaultController,
+        ReadableStreamDefaultReader,
+        WritableStream,
+        WritableStreamDefaultController,
+        WritableStreamDefaultWriter,
+    };
+}"#;
+
+pub(super) fn install<'js>(ctx: &Ctx<'js>, globals: &Object<'js>) -> Result<()> {
+    let define: Function = ctx.eval(CLASSES)?;
+    let classes: Object = define.call(())?;
+    for name in classes.keys::<String>() {
+        let name = name?;
+        globals.set(&name, classes.get::<_, crate::Value>(&name)?)?;
+    }
+    Ok(())
+}
+
+/// The size of the chunks read by [`readable_stream`].
+#[cfg(feature = "futures")]
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// Readable streams bridged from Rust pull a chunk for every read, so nothing is buffered ahead
+/// of the consumer.
+#[cfg(feature = "futures")]
+const READABLE: &str = r#"(read, cancel) => new ReadableStream({
+    async pull(controller) {
+        const chunk = await read();
+        if (chunk === undefined) controller.close();
+        else controller.enqueue(chunk);
+    },
+    cancel: () => cancel(),
+}, { highWaterMark: 0 })"#;
+
+#[cfg(feature = "futures")]
+const WRITABLE: &str = r#"(write, close, abort) => new WritableStream({
+    write(chunk) {
+        if (chunk instanceof ArrayBuffer) chunk = new Uint8Array(chunk);
+        else if (ArrayBuffer.isView(chunk)) chunk = new Uint8Array(chunk.buffer, chunk.byteOffset, chunk.byteLength);
+        else throw new TypeError("The chunk must be an ArrayBuffer or an ArrayBufferView");
+        return write(chunk);
+    },
+    close: () => close(),
+    abort: () => abort(),
+})"#;
+
+/// Holds the Rust side of a bridged stream between operations.
+///
+/// The reader or writer is taken out while an operation awaits it and dropped when the stream is
+/// closed, cancelled or aborted.
+#[cfg(feature = "futures")]
+struct Slot<T> {
+    inner: RefCell<Option<T>>,
+    closed: Cell<bool>,
+}
+
+#[cfg(feature = "futures")]
+impl<T> Slot<T> {
+    fn new(inner: T) -> Rc<Self> {
+        Rc::new(Slot {
+            inner: RefCell::new(Some(inner)),
+            closed: Cell::new(false),
+        })
+    }
+
+    fn take(&self) -> Result<T> {
+        self.inner.borrow_mut().take().ok_or_else(|| {
+            Error::Io(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "the stream is closed or busy",
+            ))
+        })
+    }
+
+    fn put(&self, inner: T) {
+        if !self.closed.get() {
+            *self.inner.borrow_mut() = Some(inner);
+        }
+    }
+
+    fn close(&self) -> Option<T> {
+        self.closed.set(true);
+        self.inner.borrow_mut().take()
+    }
+}
+
+/// Create a `ReadableStream` reading its chunks from `reader`.
+///
+/// The stream yields `Uint8Array`s of at most 16 KiB. Data is only read from `reader` when the
+/// script reads from the stream, so a slow consumer applies backpressure to the source. The
+/// reader is dropped once the stream ends or is cancelled, I/O errors error the stream.
+///
+/// The stream classes must have been defined on the global object with
+/// [`install`](super::install).
+#[cfg(feature = "futures")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub fn readable_stream<'js, R>(ctx: &Ctx<'js>, reader: R) -> Result<Object<'js>>
+where
+    R: AsyncRead + Unpin + 'js,
+{
+    let slot = Slot::new(reader);
+    let read = {
+        let slot = slot.clone();
+        move |ctx: Ctx<'js>| {
+            let slot = slot.clone();
+            async move {
+                let mut reader = slot.take()?;
+                let mut buffer = vec![0; CHUNK_SIZE];
+                let res = poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buffer)).await;
+                slot.put(reader);
+                let len = res?;
+                if len == 0 {
+                    slot.close();
+                    return Ok(None);
+                }
+                buffer.truncate(len);
+                TypedArray::new(ctx, buffer).map(Some)
+            }
+        }
+    };
+    let cancel = move || {
+        slot.close();
+    };
+    let define: Function = ctx.eval(READABLE)?;
+    define.call((Function::new(ctx.clone(), Async(read))?, Func::from(cancel)))
+}
+
+/// Create a `WritableStream` writing its chunks to `writer`.
+///
+/// The stream accepts `ArrayBuffer`s and views on them, other chunks and chunks whose buffer was
+/// detached error the stream with a `TypeError`. Every write promise resolves once the
+/// chunk was completely written to `writer` and at most one chunk is queued, so
+/// `writer.ready` applies backpressure to the script. Closing the stream flushes and closes
+/// `writer`, aborting drops it.
+///
+/// The stream classes must have been defined on the global object with
+/// [`install`](super::install).
+#[cfg(feature = "futures")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub fn writable_stream<'js, W>(ctx: &Ctx<'js>, writer: W) -> Result<Object<'js>>
+where
+    W: AsyncWrite + Unpin + 'js,
+{
+    let slot = Slot::new(writer);
+    let write = {
+        let slot = slot.clone();
+        move |ctx: Ctx<'js>, chunk: TypedArray<'js, u8>| {
+            let slot = slot.clone();
+            // Copy the chunk before returning, the script may detach or change it meanwhile.
+            let bytes = array_bytes(&ctx, &chunk)?.to_vec();
+            Promised(async move {
+                let mut writer = slot.take()?;
+                let mut bytes = &bytes[..];
+                let res = poll_fn(|cx| {
+                    while !bytes.is_empty() {
+                        match Pin::new(&mut writer).poll_write(cx, bytes) {
+                            Poll::Ready(Ok(0)) => {
+                                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()))
+                            }
+                            Poll::Ready(Ok(len)) => bytes = &bytes[len..],
+                            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                            Poll::Pending => return Poll::Pending,
+                        }
+                    }
+                    Poll::Ready(Ok(()))
+                })
+                .await;
+                slot.put(writer);
+                res.map_err(Error::Io)
+            })
+            .into_js(&ctx)
+        }
+    };
+    let close = {
+        let slot = slot.clone();
+        move || {
+            let slot = slot.clone();
+            async move {
+                let mut writer = slot.take()?;
+                slot.close();
+                poll_fn(|cx| Pin::new(&mut writer).poll_close(cx))
+                    .await
+                    .map_err(Error::Io)
+            }
+        }
+    };
+    let abort = move || {
+        slot.close();
+    };
+    let define: Function = ctx.eval(WRITABLE)?;
+    define.call((
+        Func::from(write),
+        Function::new(ctx.clone(), Async(close))?,
+        Func::from(abort),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{web, *};
+
+    #[test]
+    fn streams() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                globalThis.res = [];
+                const error = (f) => { try { f() } catch (e) { return e.name } };
+                let n = 0;
+                const readable = new ReadableStream({
+                    pull(controller) {
+                        if (n < 3) controller.enqueue(n++);
+                        else controller.close();
+                    },
+                }, { highWaterMark: 2 });
+                const written = [];
+                let resolveWrite;
+                const writable = new WritableStream({
+                    write(chunk) {
+                        written.push(chunk);
+                        return new Promise((resolve) => { resolveWrite = resolve; });
+                    },
+                    close() { written.push("closed"); },
+                });
+                (async () => {
+                    const reader = readable.getReader();
+                    res.push(`${readable.locked} ${error(() => readable.getReader())}`);
+                    const chunks = [];
+                    for (;;) {
+                        const { value, done } = await reader.read();
+                        if (done) break;
+                        chunks.push(value);
+                    }
+                    res.push(chunks.join());
+                    reader.releaseLock();
+                    res.push(`${readable.locked}`);
+
+                    const writer = writable.getWriter();
+                    res.push(`${writer.desiredSize}`);
+                    const write = writer.write("a");
+                    res.push(`${writer.desiredSize}`);
+                    let ready = false;
+                    writer.ready.then(() => { ready = true; });
+                    await null;
+                    await null;
+                    res.push(`${ready} ${written}`);
+                    resolveWrite();
+                    await write;
+                    await writer.ready;
+                    res.push(`${writer.desiredSize}`);
+                    await writer.close();
+                    res.push(`${written}`);
+                    res.push(await writer.write("b").catch((e) => e.name));
+
+                    const iterated = [];
+                    const source = new ReadableStream({
+                        start(controller) {
+                            controller.enqueue("x");
+                            controller.enqueue("y");
+                            controller.close();
+                        },
+                    });
+                    for await (const chunk of source) iterated.push(chunk);
+                    res.push(iterated.join());
+
+                    const sink = [];
+                    await new ReadableStream({ start(c) { c.enqueue(1); c.enqueue(2); c.close(); } })
+                        .pipeTo(new WritableStream({ write(chunk) { sink.push(chunk); } }));
+                    res.push(sink.join());
+
+                    const failing = new ReadableStream({ pull() { throw new RangeError("pull"); } });
+                    res.push(await failing.getReader().read().catch((e) => e.name));
+                })().catch((e) => res.push(`${e}`));
+            "#,
+            )
+            .unwrap();
+            web::run(&ctx).unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "true TypeError",
+                    "0,1,2",
+                    "false",
+                    "1",
+                    "0",
+                    "false a",
+                    "1",
+                    "a,closed",
+                    "TypeError",
+                    "x,y",
+                    "1,2",
+                    "RangeError",
+                ]
+            );
+        })
+    }
+
+    #[cfg(feature = "futures")]
+    #[tokio::test]
+    async fn bridged_streams() {
+        use std::{
+            io,
+            pin::Pin,
+            sync::{Arc, Mutex},
+            task::{Context as TaskContext, Poll},
+        };
+
+        #[derive(Clone, Default)]
+        struct Sink(Arc<Mutex<Vec<u8>>>);
+
+        impl futures_io::AsyncWrite for Sink {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut TaskContext<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                // Write in small parts to exercise partial writes.
+                let len = buf.len().min(3);
+                self.0.lock().unwrap().extend_from_slice(&buf[..len]);
+                Poll::Ready(Ok(len))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+                self.0.lock().unwrap().extend_from_slice(b"|closed");
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        let sink = Sink::default();
+        let source = vec![7u8; 40_000];
+        let writer = sink.clone();
+        async_with!(ctx => |ctx| {
+            web::install(&ctx).unwrap();
+            let readable =
+                web::readable_stream(&ctx, futures_rs::io::Cursor::new(source)).unwrap();
+            let writable = web::writable_stream(&ctx, writer).unwrap();
+            ctx.globals().set("readable", readable).unwrap();
+            ctx.globals().set("writable", writable).unwrap();
+            let done: Promise = ctx
+                .eval(
+                    r#"(async () => {
+                        const sizes = [];
+                        const counter = new WritableStream({ write(chunk) { sizes.push(chunk.length); } });
+                        await readable.pipeTo(counter);
+                        const writer = writable.getWriter();
+                        await writer.write(new TextEncoder().encode("hello"));
+                        const error = await writer.write("text").catch((e) => e.name);
+                        return `${sizes} ${error}`;
+                    })()"#,
+                )
+                .unwrap();
+            let res: StdString = done.into_future().await.unwrap();
+            assert_eq!(res, "16384,16384,7232 TypeError");
+        })
+        .await;
+        assert_eq!(&*sink.0.lock().unwrap(), b"hello");
+
+        let writer = sink.clone();
+        async_with!(ctx => |ctx| {
+            let writable = web::writable_stream(&ctx, writer.clone()).unwrap();
+            ctx.globals().set("writable", writable).unwrap();
+            let done: Promise = ctx
+                .eval(
+                    r#"(async () => {
+                        const chunk = new Uint8Array([1, 2, 3]);
+                        chunk.buffer.transfer();
+                        return await writable.getWriter().write(chunk).catch((e) => e.name);
+                    })()"#,
+                )
+                .unwrap();
+            let res: StdString = done.into_future().await.unwrap();
+            assert_eq!(res, "TypeError");
+
+            let writable = web::writable_stream(&ctx, writer).unwrap();
+            ctx.globals().set("writable", writable).unwrap();
+            let done: Promise = ctx
+                .eval("writable.getWriter().close()")
+                .unwrap();
+            done.into_future::<()>().await.unwrap();
+        })
+        .await;
+        assert_eq!(&*sink.0.lock().unwrap(), b"hello|closed");
+    }
+}
@@ -0,0 +1,417 @@
+use alloc::{rc::Rc, sync::Arc};
+use core::{fmt, time::Duration};
+use std::{
+    io::{self, Read, Write},
+    process::{self, ExitStatus, Stdio},
+    sync::Mutex,
+    thread,
+    time::Instant,
+};
+
+use super::{blocking, throw_named};
+use crate::{
+    function::{Func, Opt},
+    module::Declared,
+    promise::Promised,
+    Ctx, Module, Null, Object, Result, StdString,
+};
+
+/// The default limit of captured output for each of stdout and stderr.
+pub const DEFAULT_OUTPUT_LIMIT: usize = 1024 * 1024;
+
+/// A command a script asked to run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Command {
+    /// The program to run.
+    pub program: StdString,
+    /// The arguments passed to the program.
+    pub args: Vec<StdString>,
+    /// The working directory of the program, the one of the host if `None`.
+    pub current_dir: Option<StdString>,
+    /// The environment variables set for the program, in addition to the inherited ones.
+    pub env: Vec<(StdString, StdString)>,
+    /// Whether the program starts with an empty environment instead of the one of the host.
+    pub clear_env: bool,
+    /// The time after which the program is killed, unlimited if `None`.
+    pub timeout: Option<Duration>,
+    /// The bytes written to the standard input of the program.
+    pub input: Option<Vec<u8>>,
+    /// The maximum number of bytes captured of each of stdout and stderr, the rest is discarded.
+    pub output_limit: usize,
+}
+
+/// The decision of a [`CommandPolicy`] about a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// Run the command.
+    Allow,
+    /// Don't run the command, the promise of the script is rejected with the reason.
+    Deny(StdString),
+}
+
+/// Decides which commands scripts may run.
+pub trait CommandPolicy {
+    /// Decide whether the command may run.
+    ///
+    /// The command can be changed before it runs, for example to resolve the program to an
+    /// absolute path, to rewrite the arguments or to lower the output limit.
+    fn check(&self, command: &mut Command) -> Decision;
+}
+
+impl<F> CommandPolicy for F
+where
+    F: Fn(&mut Command) -> Decision,
+{
+    fn check(&self, command: &mut Command) -> Decision {
+        self(command)
+    }
+}
+
+/// A native module letting scripts run commands on the host, checked by a [`CommandPolicy`].
+///
+/// The module exports `run(program, args, options)` which returns a promise resolving when the
+/// command exited. The options can contain the working directory `cwd`, a string `input` written
+/// to the standard input of the command, an object `env` with environment variables, `clearEnv`
+/// to not inherit the environment of the host and a `timeout` in milliseconds after which the
+/// command is killed. The promise resolves to an object with the exit `status`, `null` if the
+/// command was terminated by a signal, the captured `stdout` and `stderr` as strings, whether any
+/// of them was `truncated` to the output limit and whether the command was killed because it
+/// `timedOut`.
+///
+/// Every command is passed to the policy before it runs, which sees and can change all of the
+/// options. Denied commands reject the promise with an error named `NotAllowedError`, commands
+/// which fail to start with an IO error. The commands run on their own threads, the promises are
+/// settled by the async runtime, so the module requires the `futures` feature.
+///
+/// Only the command itself is killed on timeout. Output written after it by processes the
+/// command started is discarded.
+///
+/// ```
+/// # use rquickjs::{AsyncRuntime, AsyncContext, async_with, Promise, web::{Subprocess, Command, Decision}};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let policy = |command: &mut Command| {
+///     if command.program == "echo" {
+///         Decision::Allow
+///     } else {
+///         Decision::Deny(format!("{} is not allowed", command.program))
+///     }
+/// };
+/// let rt = AsyncRuntime::new().unwrap();
+/// let ctx = AsyncContext::full(&rt).await.unwrap();
+/// async_with!(ctx => |ctx| {
+///     Subprocess::new(policy).declare(ctx.clone(), "subprocess").unwrap();
+///     let promise: Promise = rquickjs::Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { run } from "subprocess";
+///         const { status, stdout } = await run("echo", ["hello"]);
+///         globalThis.res = `${status} ${stdout.trim()}`;
+///         globalThis.denied = await run("rm", ["-rf", "/"]).catch((e) => `${e.name}: ${e.message}`);
+///     "#,
+///     )
+///     .unwrap();
+///     promise.into_future::<()>().await.unwrap();
+///     assert_eq!(ctx.globals().get::<_, String>("res").unwrap(), "0 hello");
+///     assert_eq!(
+///         ctx.globals().get::<_, String>("denied").unwrap(),
+///         "NotAllowedError: rm is not allowed"
+///     );
+/// })
+/// .await;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Subprocess {
+    policy: Rc<dyn CommandPolicy>,
+}
+
+impl Subprocess {
+    /// Create a module running the commands allowed by the policy.
+    pub fn new<P: CommandPolicy + 'static>(policy: P) -> Self {
+        Subprocess {
+            policy: Rc::new(policy),
+        }
+    }
+
+    /// Declare the module under the given name, for example from a module loader.
+    pub fn declare<'js, N>(&self, ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let policy = self.policy.clone();
+        let exports = Object::new(ctx.clone())?;
+        exports.set(
+            "run",
+            Func::from(
+                move |ctx: Ctx<'js>,
+                      program: StdString,
+                      args: Opt<Vec<StdString>>,
+                      options: Opt<Object<'js>>| {
+                    let command = command(program, args.0.unwrap_or_default(), options.0);
+                    let policy = policy.clone();
+                    Promised(async move {
+                        let mut command = command?;
+                        if let Decision::Deny(reason) = policy.check(&mut command) {
+                            return Err(throw_named(&ctx, "NotAllowedError", &reason));
+                        }
+                        let output = blocking::spawn(move || execute(command)).await?;
+                        output.into_object(ctx)
+                    })
+                },
+            ),
+        )?;
+        Module::declare_value(ctx, name, exports.into_value(), vec!["run".into()])
+    }
+}
+
+impl fmt::Debug for Subprocess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subprocess").finish_non_exhaustive()
+    }
+}
+
+fn command<'js>(
+    program: StdString,
+    args: Vec<StdString>,
+    options: Option<Object<'js>>,
+) -> Result<Command> {
+    let mut command = Command {
+        program,
+        args,
+        current_dir: None,
+        env: Vec::new(),
+        clear_env: false,
+        timeout: None,
+        input: None,
+        output_limit: DEFAULT_OUTPUT_LIMIT,
+    };
+    if let Some(options) = options {
+        command.current_dir = options.get("cwd")?;
+        command.input = options
+            .get::<_, Option<StdString>>("input")?
+            .map(StdString::into_bytes);
+        if let Some(env) = options.get::<_, Option<Object>>("env")? {
+            command.env = env.props().collect::<Result<_>>()?;
+        }
+        command.clear_env = options.get::<_, Option<bool>>("clearEnv")?.unwrap_or(false);
+        command.timeout = options
+            .get::<_, Option<u64>>("timeout")?
+            .map(Duration::from_millis);
+    }
+    Ok(command)
+}
+
+/// Captured output and whether some of it was discarded.
+type Captured = (Vec<u8>, bool);
+
+struct Output {
+    status: Option<i32>,
+    stdout: Captured,
+    stderr: Captured,
+    timed_out: bool,
+}
+
+impl Output {
+    fn into_object<'js>(self, ctx: Ctx<'js>) -> Result<Object<'js>> {
+        let res = Object::new(ctx)?;
+        match self.status {
+            Some(status) => res.set("status", status)?,
+            None => res.set("status", Null)?,
+        }
+        res.set(
+            "stdout",
+            StdString::from_utf8_lossy(&self.stdout.0).as_ref(),
+        )?;
+        res.set(
+            "stderr",
+            StdString::from_utf8_lossy(&self.stderr.0).as_ref(),
+        )?;
+        res.set("truncated", self.stdout.1 || self.stderr.1)?;
+        res.set("timedOut", self.timed_out)?;
+        Ok(res)
+    }
+}
+
+/// Output shared with the thread capturing it, so it can be taken before the pipe is closed.
+type Shared = Arc<Mutex<Captured>>;
+
+/// Read all of the output into `captured`, keeping at most `limit` bytes.
+fn capture<R: Read>(mut reader: R, limit: usize, captured: Shared) -> io::Result<()> {
+    let mut buffer = [0; 8192];
+    loop {
+        let len = reader.read(&mut buffer)?;
+        if len == 0 {
+            return Ok(());
+        }
+        let mut captured = captured.lock().unwrap();
+        let keep = len.min(limit - captured.0.len());
+        captured.1 |= keep < len;
+        captured.0.extend_from_slice(&buffer[..keep]);
+    }
+}
+
+/// The thread capturing a pipe, if the pipe exists, and its output.
+type Capturing = (Option<thread::JoinHandle<io::Result<()>>>, Shared);
+
+fn spawn_capture<R: Read + Send + 'static>(reader: Option<R>, limit: usize) -> Capturing {
+    let captured = Shared::default();
+    let handle = reader.map(|x| {
+        let captured = captured.clone();
+        thread::spawn(move || capture(x, limit, captured))
+    });
+    (handle, captured)
+}
+
+/// Wait for the child to exit, killing it when the timeout elapsed. Returns whether it was
+/// killed.
+fn wait(child: &mut process::Child, timeout: Option<Duration>) -> io::Result<(ExitStatus, bool)> {
+    let Some(timeout) = timeout else {
+        return Ok((child.wait()?, false));
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, false));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // Fails if the child exited in the meantime, which `wait` reports.
+            let _ = child.kill();
+            return Ok((child.wait()?, true));
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(10)));
+    }
+}
+
+fn execute(command: Command) -> io::Result<Output> {
+    let mut child = process::Command::new(&command.program);
+    child
+        .args(&command.args)
+        .stdin(if command.input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = &command.current_dir {
+        child.current_dir(dir);
+    }
+    if command.clear_env {
+        child.env_clear();
+    }
+    child.envs(command.env.iter().map(|(name, value)| (name, value)));
+    let mut child = child.spawn()?;
+
+    // Every pipe is served by its own thread so a full pipe can't block the command.
+    let stdin = child
+        .stdin
+        .take()
+        .zip(command.input)
+        .map(|(mut stdin, input)| {
+            thread::spawn(move || {
+                // The command might exit without reading all of its input.
+                let _ = stdin.write_all(&input);
+            })
+        });
+    let limit = command.output_limit;
+    let stdout = spawn_capture(child.stdout.take(), limit);
+    let stderr = spawn_capture(child.stderr.take(), limit);
+    let (status, timed_out) = wait(&mut child, command.timeout)?;
+
+    // Processes started by a killed command can keep the pipes open, so the output is only
+    // waited for if the command exited by itself.
+    let join = |(handle, captured): Capturing| {
+        if let Some(handle) = handle {
+            if !timed_out || handle.is_finished() {
+                handle.join().expect("capturing output doesn't panic")?;
+            }
+        }
+        let captured = core::mem::take(&mut *captured.lock().unwrap());
+        io::Result::Ok(captured)
+    };
+    if let Some(stdin) = stdin {
+        if !timed_out || stdin.is_finished() {
+            let _ = stdin.join();
+        }
+    }
+    Ok(Output {
+        status: status.code(),
+        stdout: join(stdout)?,
+        stderr: join(stderr)?,
+        timed_out,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use crate::{
+        web::{Command, Decision, Subprocess},
+        *,
+    };
+
+    #[tokio::test]
+    async fn subprocess_module() {
+        let policy = |command: &mut Command| match command.program.as_str() {
+            "sh" | "cat" => {
+                command.output_limit = 8;
+                Decision::Allow
+            }
+            // Rewrite the command.
+            "greet" => {
+                command.program = "echo".into();
+                command.args.insert(0, "hello".into());
+                Decision::Allow
+            }
+            program => Decision::Deny(format!("{program} is denied")),
+        };
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            Subprocess::new(policy)
+                .declare(ctx.clone(), "subprocess")
+                .unwrap();
+            let promise: Promise = Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import { run } from "subprocess";
+                const show = ({ status, stdout, stderr, truncated, timedOut }) => `${status} ${JSON.stringify(stdout)} ${JSON.stringify(stderr)} ${truncated} ${timedOut}`;
+                globalThis.res = [
+                    show(await run("greet", ["world"])),
+                    show(await run("cat", [], { input: "piped" })),
+                    show(await run("sh", ["-c", "echo out; echo err >&2; exit 3"])),
+                    show(await run("sh", ["-c", "echo 0123456789"])),
+                    show(await run("sh", ["-c", "pwd"], { cwd: "/" })),
+                    show(await run("sh", ["-c", "echo $A${HOME:-none}"], { env: { A: "x" }, clearEnv: true })),
+                    show(await run("sh", ["-c", "echo $A"], { env: { A: "y" } })),
+                    show(await run("sh", ["-c", "echo start; sleep 10"], { timeout: 50 })),
+                    await run("ls").catch((e) => `${e.name}: ${e.message}`),
+                    await run("sh", ["-c", ""], { cwd: "/missing" }).then(() => "started", () => "failed"),
+                ];
+            "#,
+            )
+            .unwrap();
+            promise.into_future::<()>().await.unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    r#"0 "hello world\n" "" false false"#,
+                    r#"0 "piped" "" false false"#,
+                    r#"3 "out\n" "err\n" false false"#,
+                    r#"0 "01234567" "" true false"#,
+                    r#"0 "/\n" "" false false"#,
+                    r#"0 "xnone\n" "" false false"#,
+                    r#"0 "y\n" "" false false"#,
+                    r#"null "start\n" "" false true"#,
+                    "NotAllowedError: ls is denied",
+                    "failed",
+                ]
+            );
+        })
+        .await;
+    }
+}
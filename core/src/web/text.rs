@@ -0,0 +1,146 @@
+use crate::{function::Func, Ctx, Exception, Function, Object, Result, StdString, TypedArray};
+
+/// The classes are defined in JavaScript around native functions doing the conversion, so they
+/// behave like regular classes for `instanceof`, subclassing and private fields.
+const CLASSES: &str = r#"(native) => {
+    const toBytes = (input) => {
+        if (input === undefined) return new Uint8Array(0);
+        if (input instanceof ArrayBuffer) return new Uint8Array(input);
+        if (ArrayBuffer.isView(input)) return new Uint8Array(input.buffer, input.byteOffset, input.byteLength);
+        throw new TypeError("The input must be an ArrayBuffer or an ArrayBufferView");
+    };
+
+    class TextEncoder {
+        get encoding() { return "utf-8"; }
+        encode(input = "") { return native.encode(`${input}`); }
+        encodeInto(source, destination) {
+            if (!(destination instanceof Uint8Array)) throw new TypeError("The destination must be an Uint8Array");
+            return native.encodeInto(`${source}`, destination);
+        }
+    }
+
+    class TextDecoder {
+        #fatal;
+        #ignoreBOM;
+        constructor(label = "utf-8", options = {}) {
+            const encoding = `${label}`.trim().toLowerCase();
+            if (encoding !== "utf-8" && encoding !== "utf8" && encoding !== "unicode-1-1-utf-8") {
+                throw new RangeError(`The encoding label provided ('${label}') is invalid.`);
+            }
+            this.#fatal = !!options.fatal;
+            this.#ignoreBOM = !!options.ignoreBOM;
+        }
+        get encoding() { return "utf-8"; }
+        get fatal() { return this.#fatal; }
+        get ignoreBOM() { return this.#ignoreBOM; }
+        decode(input) { return native.decode(toBytes(input), this.#fatal, this.#ignoreBOM); }
+    }
+
+    return { TextEncoder, TextDecoder };
+}"#;
+
+pub(super) fn encode<'js>(ctx: Ctx<'js>, input: StdString) -> Result<TypedArray<'js, u8>> {
+    TypedArray::new(ctx, input.into_bytes())
+}
+
+fn encode_into<'js>(
+    ctx: Ctx<'js>,
+    input: StdString,
+    destination: TypedArray<'js, u8>,
+) -> Result<Object<'js>> {
+    let (mut read, mut written) = (0, 0);
+    if let Some(raw) = destination.as_raw() {
+        let buffer = unsafe { core::slice::from_raw_parts_mut(raw.ptr.as_ptr(), raw.len) };
+        for c in input.chars() {
+            let len = c.len_utf8();
+            if written + len > buffer.len() {
+                break;
+            }
+            c.encode_utf8(&mut buffer[written..]);
+            written += len;
+            read += c.len_utf16();
+        }
+    }
+    let res = Object::new(ctx)?;
+    res.set("read", read)?;
+    res.set("written", written)?;
+    Ok(res)
+}
+
+pub(super) fn decode<'js>(
+    ctx: Ctx<'js>,
+    input: TypedArray<'js, u8>,
+    fatal: bool,
+    ignore_bom: bool,
+) -> Result<StdString> {
+    let mut bytes = input.as_bytes().unwrap_or_default();
+    if !ignore_bom {
+        bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    }
+    if fatal {
+        core::str::from_utf8(bytes)
+            .map(StdString::from)
+            .map_err(|_| Exception::throw_type(&ctx, "The encoded data was not valid utf-8"))
+    } else {
+        Ok(StdString::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// Define `TextEncoder` and `TextDecoder` on the global object.
+pub(super) fn install<'js>(ctx: &Ctx<'js>, globals: &Object<'js>) -> Result<()> {
+    let native = Object::new(ctx.clone())?;
+    native.set("encode", Func::from(encode))?;
+    native.set("encodeInto", Func::from(encode_into))?;
+    native.set("decode", Func::from(decode))?;
+    let define: Function = ctx.eval(CLASSES)?;
+    let classes: Object = define.call((native,))?;
+    globals.set("TextEncoder", classes.get::<_, Object>("TextEncoder")?)?;
+    globals.set("TextDecoder", classes.get::<_, Object>("TextDecoder")?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{web, *};
+
+    #[test]
+    fn text_coding() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const encoder = new TextEncoder();
+                    const bytes = encoder.encode("a€");
+                    const target = new Uint8Array(3);
+                    const { read, written } = encoder.encodeInto("ab€", target);
+                    const decoder = new TextDecoder("UTF-8", { fatal: true });
+                    [
+                        bytes.join(),
+                        `${read} ${written} ${target.join()}`,
+                        new TextDecoder().decode(new Uint8Array([0xef, 0xbb, 0xbf, 0x68, 0xff]).buffer),
+                        new TextDecoder("utf8", { ignoreBOM: true }).decode(new Uint8Array([0xef, 0xbb, 0xbf])),
+                        decoder.decode(bytes.subarray(1)),
+                        (() => { try { decoder.decode(bytes.subarray(2)) } catch (e) { return e.name } })(),
+                        (() => { try { new TextDecoder("latin1") } catch (e) { return e.name } })(),
+                        `${encoder instanceof TextEncoder} ${decoder.encoding} ${decoder.fatal}`,
+                    ]
+                "#,
+                )
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "97,226,130,172",
+                    "2 2 97,98,0",
+                    "h\u{fffd}",
+                    "\u{feff}",
+                    "€",
+                    "TypeError",
+                    "RangeError",
+                    "true utf-8 true",
+                ]
+            );
+        })
+    }
+}
@@ -0,0 +1,205 @@
+use alloc::collections::BTreeMap;
+use core::{
+    cell::{Cell, RefCell},
+    time::Duration,
+};
+use std::time::Instant;
+
+use crate::{
+    function::{Func, Opt, Rest},
+    Coerced, Ctx, Function, JsLifetime, Object, Result, Value,
+};
+
+/// The pending timers of a runtime, stored as userdata.
+#[derive(Default)]
+struct Timers<'js> {
+    next_id: Cell<u32>,
+    entries: RefCell<BTreeMap<u32, Timer<'js>>>,
+}
+
+unsafe impl<'js> JsLifetime<'js> for Timers<'js> {
+    type Changed<'to> = Timers<'to>;
+}
+
+struct Timer<'js> {
+    deadline: Instant,
+    interval: Option<Duration>,
+    callback: Function<'js>,
+    args: Vec<Value<'js>>,
+}
+
+/// Convert a delay in milliseconds like browsers do, invalid and negative delays are zero.
+fn delay(delay: Opt<Coerced<f64>>) -> Duration {
+    let millis = delay.0.map(|x| x.0).unwrap_or(0.0);
+    if millis > 0.0 {
+        Duration::from_secs_f64(millis.min(i32::MAX as f64) / 1000.0)
+    } else {
+        Duration::ZERO
+    }
+}
+
+/// Returns whether [`install`] was called for the runtime of the context.
+#[cfg(feature = "fs")]
+pub(super) fn installed<'js>(ctx: &Ctx<'js>) -> bool {
+    ctx.userdata::<Timers>().is_some()
+}
+
+pub(super) fn schedule<'js>(
+    ctx: &Ctx<'js>,
+    callback: Function<'js>,
+    delay: Duration,
+    interval: bool,
+    args: Vec<Value<'js>>,
+) -> Result<u32> {
+    let timers = ctx.userdata::<Timers>().expect("timers were installed");
+    let id = timers.next_id.get().wrapping_add(1).max(1);
+    timers.next_id.set(id);
+    timers.entries.borrow_mut().insert(
+        id,
+        Timer {
+            deadline: Instant::now() + delay,
+            interval: interval.then_some(delay),
+            callback,
+            args,
+        },
+    );
+    Ok(id)
+}
+
+fn set_timeout<'js>(
+    ctx: Ctx<'js>,
+    callback: Function<'js>,
+    ms: Opt<Coerced<f64>>,
+    args: Rest<Value<'js>>,
+) -> Result<u32> {
+    schedule(&ctx, callback, delay(ms), false, args.0)
+}
+
+fn set_interval<'js>(
+    ctx: Ctx<'js>,
+    callback: Function<'js>,
+    ms: Opt<Coerced<f64>>,
+    args: Rest<Value<'js>>,
+) -> Result<u32> {
+    schedule(&ctx, callback, delay(ms), true, args.0)
+}
+
+fn clear<'js>(ctx: Ctx<'js>, id: Opt<Value<'js>>) {
+    if let Some(id) = id.0.and_then(|x| x.as_number()) {
+        cancel(&ctx, id as u32);
+    }
+}
+
+pub(super) fn cancel<'js>(ctx: &Ctx<'js>, id: u32) {
+    if let Some(timers) = ctx.userdata::<Timers>() {
+        timers.entries.borrow_mut().remove(&id);
+    }
+}
+
+/// Define the timer functions on the global object.
+pub(super) fn install<'js>(ctx: &Ctx<'js>, globals: &Object<'js>) -> Result<()> {
+    if ctx.userdata::<Timers>().is_none() {
+        ctx.store_userdata(Timers::default())?;
+    }
+    globals.set("setTimeout", Func::from(set_timeout))?;
+    globals.set("setInterval", Func::from(set_interval))?;
+    globals.set("clearTimeout", Func::from(clear))?;
+    globals.set("clearInterval", Func::from(clear))?;
+    Ok(())
+}
+
+/// Run the timers which are due, returns the time until the next timer is due.
+///
+/// Pending jobs, like promise reactions, are run after every timer. Timers which become due while
+/// running, for example because a callback scheduled a timer without a delay, are left for the
+/// next call. Returns `None` if there are no timers, also if [`install`](super::install) was not
+/// called.
+pub fn run_timers<'js>(ctx: &Ctx<'js>) -> Result<Option<Duration>> {
+    let Some(timers) = ctx.userdata::<Timers>() else {
+        return Ok(None);
+    };
+    let now = Instant::now();
+    let mut due: Vec<(Instant, u32)> = timers
+        .entries
+        .borrow()
+        .iter()
+        .filter(|(_, x)| x.deadline <= now)
+        .map(|(id, x)| (x.deadline, *id))
+        .collect();
+    due.sort();
+
+    for (_, id) in due {
+        let (callback, args) = {
+            let mut entries = timers.entries.borrow_mut();
+            // The timer might have been cleared by an earlier callback.
+            let Some(timer) = entries.get_mut(&id) else {
+                continue;
+            };
+            let call = (timer.callback.clone(), timer.args.clone());
+            match timer.interval {
+                Some(interval) => timer.deadline = Instant::now() + interval,
+                None => {
+                    entries.remove(&id);
+                }
+            }
+            call
+        };
+        callback.call::<_, ()>((Rest(args),))?;
+        super::run_jobs(ctx)?;
+    }
+
+    let next = timers.entries.borrow().values().map(|x| x.deadline).min();
+    Ok(next.map(|x| x.saturating_duration_since(Instant::now())))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{web, *};
+
+    #[test]
+    fn timers() {
+        test_with(|ctx| {
+            web::install(&ctx).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                globalThis.log = [];
+                setTimeout((a, b) => log.push(`timeout ${a} ${b}`), 10, 1, 2);
+                setTimeout(() => log.push("first"), 0);
+                const cleared = setTimeout(() => log.push("cleared"), 0);
+                clearTimeout(cleared);
+                let count = 0;
+                const interval = setInterval(() => {
+                    log.push(`interval ${++count}`);
+                    Promise.resolve().then(() => log.push("job"));
+                    if (count == 2) clearInterval(interval);
+                }, 1);
+            "#,
+            )
+            .unwrap();
+            let next = web::run_timers(&ctx).unwrap().unwrap();
+            assert!(next <= Duration::from_millis(10));
+            web::run(&ctx).unwrap();
+            assert_eq!(web::run_timers(&ctx).unwrap(), None);
+
+            let log: Vec<StdString> = ctx.eval("log").unwrap();
+            assert_eq!(
+                log,
+                [
+                    "first",
+                    "interval 1",
+                    "job",
+                    "interval 2",
+                    "job",
+                    "timeout 1 2"
+                ]
+            );
+
+            ctx.eval::<(), _>("setTimeout(() => { throw new Error('oops') })")
+                .unwrap();
+            assert!(web::run(&ctx).is_err());
+            ctx.catch();
+        })
+    }
+}
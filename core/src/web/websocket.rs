@@ -0,0 +1,698 @@
+use alloc::rc::Rc;
+#[cfg(feature = "websocket-tls")]
+use alloc::sync::Arc;
+use core::cell::RefCell;
+use std::io;
+
+use async_lock::Mutex;
+use futures_util::{
+    stream::{SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
+#[cfg(feature = "websocket-tls")]
+use rustls::{crypto::ring, ClientConfig, RootCertStore};
+use tokio::net::TcpStream;
+#[cfg(not(feature = "websocket-tls"))]
+use tokio_tungstenite::client_async;
+#[cfg(feature = "websocket-tls")]
+use tokio_tungstenite::{client_async_tls_with_config, Connector, MaybeTlsStream};
+use tokio_tungstenite::{
+    tungstenite::{
+        client::IntoClientRequest,
+        error::ProtocolError,
+        http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderValue},
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Error as WsError, Message,
+    },
+    WebSocketStream,
+};
+
+use super::{array_bytes, throw_named, Net};
+use crate::{
+    function::{Async, Func},
+    module::Declared,
+    promise::Promised,
+    Ctx, Error, FromJs, Function, IntoJs, Module, Object, Result, StdString, TypedArray, Value,
+};
+
+/// The class wraps the native handle of the connection, which is only reachable from its private
+/// fields, so the objects only expose the standard API.
+const CLASSES: &str = r#"(EventTarget, Event, MessageEvent, Blob, connect) => {
+    const [CONNECTING, OPEN, CLOSING, CLOSED] = [0, 1, 2, 3];
+    const named = (name, message) => Object.assign(new Error(message), { name });
+    const utf8Length = (string) => {
+        let res = 0;
+        for (const char of string) {
+            const code = char.codePointAt(0);
+            res += code < 0x80 ? 1 : code < 0x800 ? 2 : code < 0x10000 ? 3 : 4;
+        }
+        return res;
+    };
+    const token = /^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/;
+
+    class CloseEvent extends Event {
+        #wasClean;
+        #code;
+        #reason;
+        constructor(type, options = {}) {
+            super(type, options);
+            options = options ?? {};
+            this.#wasClean = !!options.wasClean;
+            this.#code = Number(options.code ?? 0) & 0xffff;
+            this.#reason = `${options.reason ?? ""}`;
+        }
+        get wasClean() { return this.#wasClean; }
+        get code() { return this.#code; }
+        get reason() { return this.#reason; }
+        get [Symbol.toStringTag]() { return "CloseEvent"; }
+    }
+
+    class ErrorEvent extends Event {
+        #message;
+        #error;
+        constructor(type, options = {}) {
+            super(type, options);
+            options = options ?? {};
+            this.#message = `${options.message ?? ""}`;
+            this.#error = options.error;
+        }
+        get message() { return this.#message; }
+        get error() { return this.#error; }
+        get filename() { return ""; }
+        get lineno() { return 0; }
+        get colno() { return 0; }
+        get [Symbol.toStringTag]() { return "ErrorEvent"; }
+    }
+
+    class WebSocket extends EventTarget {
+        #url;
+        #origin;
+        #state = CONNECTING;
+        #protocol = "";
+        #binaryType = "blob";
+        #buffered = 0;
+        #native = null;
+        #sending = Promise.resolve();
+        constructor(url, protocols = []) {
+            if (arguments.length === 0) throw new TypeError("The URL is required");
+            super();
+            protocols = typeof protocols === "string" ? [protocols] : Array.from(protocols, String);
+            if (protocols.some((x) => !token.test(x)) || new Set(protocols).size !== protocols.length) {
+                throw named("SyntaxError", "The subprotocols must be distinct tokens");
+            }
+            const { url: href, origin, connected } = connect(`${url}`, protocols);
+            this.#url = href;
+            this.#origin = origin;
+            connected.then((native) => this.#open(native), (e) => this.#fail(e));
+        }
+        get url() { return this.#url; }
+        get readyState() { return this.#state; }
+        get protocol() { return this.#protocol; }
+        get extensions() { return ""; }
+        get bufferedAmount() { return this.#buffered; }
+        get binaryType() { return this.#binaryType; }
+        set binaryType(value) {
+            if (value === "blob" || value === "arraybuffer") this.#binaryType = value;
+        }
+        send(data) {
+            if (this.#state === CONNECTING) throw named("InvalidStateError", "The socket is still connecting");
+            let payload;
+            let length;
+            if (typeof data === "string") {
+                payload = data;
+                length = utf8Length(data);
+            } else if (data instanceof ArrayBuffer) {
+                payload = new Uint8Array(data.slice(0));
+                length = payload.length;
+            } else if (ArrayBuffer.isView(data)) {
+                payload = new Uint8Array(data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength));
+                length = payload.length;
+            } else if (data instanceof Blob) {
+                payload = data.arrayBuffer().then((x) => new Uint8Array(x));
+                length = data.size;
+            } else {
+                payload = `${data}`;
+                length = utf8Length(payload);
+            }
+            this.#buffered += length;
+            // Like browsers the data is dropped once the socket is closing.
+            if (this.#state !== OPEN) return;
+            const native = this.#native;
+            this.#sending = this.#sending.then(async () => {
+                try {
+                    await native.send(await payload);
+                } catch (e) {
+                    this.#fail(e);
+                } finally {
+                    this.#buffered -= length;
+                }
+            });
+        }
+        close(code, reason) {
+            if (code !== undefined) {
+                code = Number(code);
+                if (code !== 1000 && !(Number.isInteger(code) && code >= 3000 && code <= 4999)) {
+                    throw named("InvalidAccessError", "The close code must be 1000 or between 3000 and 4999");
+                }
+            }
+            reason = reason === undefined ? "" : `${reason}`;
+            if (utf8Length(reason) > 123) throw named("SyntaxError", "The close reason must be at most 123 bytes");
+            if (this.#state === CLOSING || this.#state === CLOSED) return;
+            const connecting = this.#state === CONNECTING;
+            this.#state = CLOSING;
+            if (connecting) return;
+            const native = this.#native;
+            this.#sending = this.#sending.then(() => native.close(code, reason)).catch((e) => this.#fail(e));
+        }
+        #open(native) {
+            this.#native = native;
+            if (this.#state !== CONNECTING) {
+                native.close(undefined, "").catch(() => {});
+                this.#fail(new Error("The socket was closed before the connection was established"));
+                return;
+            }
+            this.#protocol = native.protocol;
+            this.#state = OPEN;
+            this.dispatchEvent(new Event("open"));
+            this.#receive();
+        }
+        async #receive() {
+            const native = this.#native;
+            try {
+                for (let data; (data = await native.receive()) !== undefined;) {
+                    if (this.#state !== OPEN) continue;
+                    if (typeof data !== "string") data = this.#binaryType === "arraybuffer" ? data.buffer : new Blob([data]);
+                    this.dispatchEvent(new MessageEvent("message", { data, origin: this.#origin }));
+                }
+            } catch (e) {
+                this.#fail(e);
+                return;
+            }
+            const code = native.closeCode();
+            if (this.#state === CLOSED) return;
+            this.#state = CLOSED;
+            this.dispatchEvent(new CloseEvent("close", { wasClean: code !== 1006, code, reason: native.closeReason() }));
+        }
+        #fail(e) {
+            if (this.#state === CLOSED) return;
+            this.#state = CLOSED;
+            this.dispatchEvent(new ErrorEvent("error", { message: e?.message ?? `${e}`, error: e }));
+            this.dispatchEvent(new CloseEvent("close", { wasClean: false, code: 1006 }));
+        }
+        get [Symbol.toStringTag]() { return "WebSocket"; }
+    }
+    for (const [name, value] of [["CONNECTING", CONNECTING], ["OPEN", OPEN], ["CLOSING", CLOSING], ["CLOSED", CLOSED]]) {
+        Object.defineProperty(WebSocket, name, { value, enumerable: true });
+        Object.defineProperty(WebSocket.prototype, name, { value, enumerable: true });
+    }
+    for (const type of ["open", "message", "error", "close"]) {
+        const handlers = new WeakMap();
+        Object.defineProperty(WebSocket.prototype, `on${type}`, {
+            get() { return handlers.get(this) ?? null; },
+            set(handler) {
+                const previous = handlers.get(this);
+                if (previous !== undefined) this.removeEventListener(type, previous);
+                handlers.delete(this);
+                if (typeof handler === "function") {
+                    handlers.set(this, handler);
+                    this.addEventListener(type, handler);
+                }
+            },
+            enumerable: true,
+            configurable: true,
+        });
+    }
+
+    return { WebSocket, CloseEvent, ErrorEvent };
+}"#;
+
+/// The names exported by the module.
+const EXPORTS: [&str; 3] = ["WebSocket", "CloseEvent", "ErrorEvent"];
+
+/// The close code reported when the connection ended without a close frame.
+const ABNORMAL_CLOSURE: u16 = 1006;
+
+/// The close code reported when the close frame had no code.
+const NO_STATUS: u16 = 1005;
+
+/// A native module giving scripts WebSocket clients, limited to the addresses allowed by a
+/// [`Net`].
+///
+/// The module exports the standard `WebSocket` class, an `EventTarget` connecting when it is
+/// constructed with `new WebSocket(url, protocols)`:
+///
+/// - `open`, `message`, `error` and `close` events, also through the `onopen`, `onmessage`,
+///   `onerror` and `onclose` handlers. Messages are `MessageEvent`s with a string or, depending on
+///   `binaryType`, a `Blob` or an `ArrayBuffer` as their `data`. The `close` event is a
+///   `CloseEvent` with the `code`, `reason` and whether the closure `wasClean`. The `error` event
+///   is an `ErrorEvent` whose `message` tells why the connection failed, for example because the
+///   address is not allowed.
+/// - `send(data)` sends a string as a text message and an `ArrayBuffer`, a view on one or a `Blob`
+///   as a binary message. Messages are sent in order in the background, `bufferedAmount` counts
+///   the bytes not sent yet.
+/// - `close(code, reason)` starts the closing handshake.
+/// - `url`, `readyState` with the `CONNECTING`, `OPEN`, `CLOSING` and `CLOSED` constants,
+///   `protocol` chosen by the server and `extensions`, always empty.
+///
+/// `CloseEvent` and `ErrorEvent` are exported as well. The classes extend the `EventTarget`,
+/// `Event` and `MessageEvent` globals, so [`install`](super::install) has to be called before the
+/// module is declared.
+///
+/// The host and port of a URL must be allowed by the [`Net`] the module was created with, like for
+/// its `connect`. Secure `wss:` URLs require the `websocket-tls` feature, which verifies servers
+/// with rustls and the root certificates of the webpki-roots crate. Without it they throw a
+/// `SyntaxError`.
+///
+/// ```
+/// # use rquickjs::{AsyncRuntime, AsyncContext, async_with, Promise, web::{self, Net, WebSocket}};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let websocket = WebSocket::new(Net::new().with_address("chat.example.com", 80));
+/// let rt = AsyncRuntime::new().unwrap();
+/// let ctx = AsyncContext::full(&rt).await.unwrap();
+/// async_with!(ctx => |ctx| {
+///     web::install(&ctx).unwrap();
+///     websocket.declare(ctx.clone(), "ws").unwrap();
+///     let promise: Promise = rquickjs::Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         r#"
+///         import { WebSocket } from "ws";
+///         const socket = new WebSocket("ws://example.com/");
+///         globalThis.denied = await new Promise((resolve) => {
+///             socket.onerror = (event) => resolve(event.message);
+///         });
+///     "#,
+///     )
+///     .unwrap();
+///     promise.into_future::<()>().await.unwrap();
+///     assert_eq!(
+///         ctx.globals().get::<_, String>("denied").unwrap(),
+///         "IO Error: connecting to example.com:80 is not allowed"
+///     );
+/// })
+/// .await;
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct WebSocket {
+    net: Net,
+}
+
+impl WebSocket {
+    /// Create a module connecting to the addresses allowed by `net`.
+    pub fn new(net: Net) -> Self {
+        WebSocket { net }
+    }
+
+    /// Declare the module under the given name, for example from a module loader.
+    pub fn declare<'js, N>(&self, ctx: Ctx<'js>, name: N) -> Result<Module<'js, Declared>>
+    where
+        N: Into<Vec<u8>>,
+    {
+        let net = Rc::new(self.net.clone());
+        #[cfg(feature = "websocket-tls")]
+        let tls = tls_config()?;
+        let connect = move |ctx: Ctx<'js>, url: StdString, protocols: Vec<StdString>| {
+            let syntax_error = |message: &str| throw_named(&ctx, "SyntaxError", message);
+            let mut request = url
+                .as_str()
+                .into_client_request()
+                .map_err(|_| syntax_error("The URL is invalid"))?;
+            let uri = request.uri();
+            let default_port = match uri.scheme_str() {
+                Some("ws") => 80,
+                #[cfg(feature = "websocket-tls")]
+                Some("wss") => 443,
+                #[cfg(not(feature = "websocket-tls"))]
+                Some("wss") => return Err(syntax_error("wss URLs are not supported")),
+                _ => return Err(syntax_error("The URL must use the ws or wss scheme")),
+            };
+            let host = uri.host().unwrap_or_default().to_string();
+            let port = uri.port_u16().unwrap_or(default_port);
+            let origin = match uri.port() {
+                Some(port) => format!("{}://{host}:{port}", uri.scheme_str().unwrap_or_default()),
+                None => format!("{}://{host}", uri.scheme_str().unwrap_or_default()),
+            };
+            let href = uri.to_string();
+            if !protocols.is_empty() {
+                let protocols = HeaderValue::from_str(&protocols.join(", "))
+                    .map_err(|_| syntax_error("The subprotocols must be distinct tokens"))?;
+                request
+                    .headers_mut()
+                    .insert(SEC_WEBSOCKET_PROTOCOL, protocols);
+            }
+
+            let net = net.clone();
+            #[cfg(feature = "websocket-tls")]
+            let tls = tls.clone();
+            let handle_ctx = ctx.clone();
+            let connected = Promised(async move {
+                let ctx = handle_ctx;
+                let stream =
+                    TcpStream::connect((net.check(&host, port, "connecting to")?, port)).await?;
+                #[cfg(feature = "websocket-tls")]
+                let handshake = client_async_tls_with_config(
+                    request,
+                    stream,
+                    None,
+                    Some(Connector::Rustls(tls)),
+                );
+                #[cfg(not(feature = "websocket-tls"))]
+                let handshake = client_async(request, stream);
+                let (stream, response) = handshake.await.map_err(ws_error)?;
+                let protocol = response
+                    .headers()
+                    .get(SEC_WEBSOCKET_PROTOCOL)
+                    .and_then(|x| x.to_str().ok())
+                    .unwrap_or_default();
+                let handle = Object::new(ctx.clone())?;
+                handle.set("protocol", protocol)?;
+                connection(&ctx, &handle, stream)?;
+                Ok::<_, Error>(handle)
+            });
+
+            let res = Object::new(ctx.clone())?;
+            res.set("url", href)?;
+            res.set("origin", origin)?;
+            res.set("connected", connected)?;
+            Ok::<_, Error>(res)
+        };
+
+        let globals = ctx.globals();
+        let define: Function = ctx.eval(CLASSES)?;
+        let exports: Object = define.call((
+            globals.get::<_, Value>("EventTarget")?,
+            globals.get::<_, Value>("Event")?,
+            globals.get::<_, Value>("MessageEvent")?,
+            globals.get::<_, Value>("Blob")?,
+            Func::from(connect),
+        ))?;
+        Module::declare_value(
+            ctx,
+            name,
+            exports.into_value(),
+            EXPORTS.iter().map(|x| x.to_string()).collect(),
+        )
+    }
+}
+
+/// The configuration verifying servers with the webpki root certificates, using the ring provider
+/// so it doesn't depend on the process default of rustls.
+#[cfg(feature = "websocket-tls")]
+fn tls_config() -> Result<Arc<ClientConfig>> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| Error::Io(io::Error::new(io::ErrorKind::Unsupported, e)))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+#[cfg(feature = "websocket-tls")]
+type Stream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+#[cfg(not(feature = "websocket-tls"))]
+type Stream = WebSocketStream<TcpStream>;
+
+struct Connection {
+    sink: Mutex<SplitSink<Stream, Message>>,
+    stream: Mutex<SplitStream<Stream>>,
+    closed: RefCell<Option<(u16, StdString)>>,
+}
+
+fn ws_error(error: WsError) -> Error {
+    match error {
+        WsError::Io(error) => Error::Io(error),
+        WsError::ConnectionClosed
+        | WsError::AlreadyClosed
+        | WsError::Protocol(ProtocolError::SendAfterClosing) => Error::Io(io::Error::new(
+            io::ErrorKind::NotConnected,
+            "the connection is closed",
+        )),
+        error => Error::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            error.to_string(),
+        )),
+    }
+}
+
+/// Define the methods of the handle of a connected socket.
+fn connection<'js>(ctx: &Ctx<'js>, handle: &Object<'js>, stream: Stream) -> Result<()> {
+    let (sink, stream) = stream.split();
+    let connection = Rc::new(Connection {
+        sink: Mutex::new(sink),
+        stream: Mutex::new(stream),
+        closed: RefCell::new(None),
+    });
+
+    let send = {
+        let connection = connection.clone();
+        move |ctx: Ctx<'js>, data: Value<'js>| {
+            let connection = connection.clone();
+            let message = match data.as_string() {
+                Some(text) => text.to_string().map(Message::text),
+                None => TypedArray::<u8>::from_js(&ctx, data)
+                    .and_then(|x| Ok(Message::binary(array_bytes(&ctx, &x)?.to_vec()))),
+            };
+            async move {
+                let message = message?;
+                let mut sink = connection.sink.lock().await;
+                sink.send(message).await.map_err(ws_error)
+            }
+        }
+    };
+    let receive = {
+        let connection = connection.clone();
+        move |ctx: Ctx<'js>| {
+            let connection = connection.clone();
+            async move {
+                let mut stream = connection.stream.lock().await;
+                loop {
+                    let status = match stream.next().await {
+                        Some(Ok(Message::Text(text))) => return text.as_str().into_js(&ctx),
+                        Some(Ok(Message::Binary(bytes))) => {
+                            return TypedArray::new(ctx, bytes.to_vec()).map(|x| x.into_value())
+                        }
+                        // Pings are answered by the connection itself.
+                        Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {
+                            continue
+                        }
+                        Some(Ok(Message::Close(Some(frame)))) => {
+                            (frame.code.into(), frame.reason.as_str().into())
+                        }
+                        Some(Ok(Message::Close(None))) => (NO_STATUS, StdString::new()),
+                        Some(Err(WsError::ConnectionClosed | WsError::AlreadyClosed)) | None => {
+                            (ABNORMAL_CLOSURE, StdString::new())
+                        }
+                        Some(Err(error)) => return Err(ws_error(error)),
+                    };
+                    connection.closed.borrow_mut().get_or_insert(status);
+                    // Send the reply to a close frame of the server, completing the handshake.
+                    let _ = connection.sink.lock().await.flush().await;
+                    return Ok(Value::new_undefined(ctx));
+                }
+            }
+        }
+    };
+    let close = {
+        let connection = connection.clone();
+        move |code: Option<u16>, reason: StdString| {
+            let connection = connection.clone();
+            async move {
+                let frame = code.map(|code| CloseFrame {
+                    code: CloseCode::from(code),
+                    reason: reason.into(),
+                });
+                let mut sink = connection.sink.lock().await;
+                match sink.send(Message::Close(frame)).await {
+                    Err(
+                        WsError::ConnectionClosed
+                        | WsError::AlreadyClosed
+                        | WsError::Protocol(ProtocolError::SendAfterClosing),
+                    ) => Ok(()),
+                    res => res.map_err(ws_error),
+                }
+            }
+        }
+    };
+    let close_code = {
+        let connection = connection.clone();
+        move || connection.closed.borrow().as_ref().map(|x| x.0)
+    };
+    let close_reason =
+        move || -> Option<StdString> { connection.closed.borrow().as_ref().map(|x| x.1.clone()) };
+
+    handle.set("send", Function::new(ctx.clone(), Async(send))?)?;
+    handle.set("receive", Function::new(ctx.clone(), Async(receive))?)?;
+    handle.set("close", Function::new(ctx.clone(), Async(close))?)?;
+    handle.set("closeCode", Func::from(close_code))?;
+    handle.set("closeReason", Func::from(close_reason))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::{
+        handshake::server::{Request, Response},
+        http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderValue},
+        protocol::CloseFrame,
+        Message,
+    };
+
+    use crate::{
+        web::{Net, WebSocket},
+        *,
+    };
+
+    /// Echo messages until the client says bye.
+    #[allow(clippy::result_large_err)]
+    async fn echo(stream: tokio::net::TcpStream) {
+        let callback = |request: &Request, mut response: Response| {
+            if request.headers().contains_key(SEC_WEBSOCKET_PROTOCOL) {
+                response
+                    .headers_mut()
+                    .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static("chat"));
+            }
+            Ok(response)
+        };
+        // Clients trying TLS fail the handshake.
+        let Ok(mut ws) = tokio_tungstenite::accept_hdr_async(stream, callback).await else {
+            return;
+        };
+        while let Some(Ok(message)) = ws.next().await {
+            match message {
+                Message::Text(text) if text == "bye" => {
+                    let frame = CloseFrame {
+                        code: 4000.into(),
+                        reason: "done".into(),
+                    };
+                    ws.close(Some(frame)).await.unwrap();
+                }
+                Message::Text(_) | Message::Binary(_) => ws.send(message).await.unwrap(),
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn websocket_module() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(echo(stream));
+            }
+        });
+
+        let websocket = WebSocket::new(Net::new().with_host("127.0.0.1"));
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            web::install(&ctx).unwrap();
+            websocket.declare(ctx.clone(), "ws").unwrap();
+            ctx.globals().set("port", port).unwrap();
+            let promise: Promise = Module::evaluate(
+                ctx.clone(),
+                "test",
+                r#"
+                import { WebSocket, CloseEvent } from "ws";
+                const error = (f) => { try { f(); return "returned"; } catch (e) { return e.name; } };
+                const url = `ws://127.0.0.1:${port}/chat`;
+                const events = (socket, onopen = () => {}, onmessage = () => {}) => new Promise((resolve) => {
+                    const log = [];
+                    socket.onopen = () => {
+                        log.push(`open ${socket.readyState} ${socket.protocol}`);
+                        onopen(socket, log);
+                    };
+                    socket.addEventListener("message", (event) => {
+                        const { data } = event;
+                        if (data instanceof ArrayBuffer) log.push(`[${new Uint8Array(data)}]`);
+                        else if (data instanceof Blob) log.push(`blob ${data.size}`);
+                        else log.push(`${data} ${event.origin}`);
+                        onmessage(socket);
+                    });
+                    socket.onerror = (event) => log.push(`error ${event.message}`);
+                    socket.onclose = (event) => {
+                        log.push(`close ${event.code} ${event.reason} ${event.wasClean} ${event instanceof CloseEvent} ${socket.readyState}`);
+                        resolve(log.join("|"));
+                    };
+                });
+
+                const chat = new WebSocket(url, ["chat", "other"]);
+                chat.binaryType = "arraybuffer";
+                chat.binaryType = "other";
+                const state = [chat.readyState, error(() => chat.send("early")), chat.url === url];
+                const chatEvents = events(chat, (socket, log) => {
+                    socket.send("hello");
+                    socket.send(new Uint16Array([0x0201, 0x0403]));
+                    socket.send("bye");
+                    log.push(socket.bufferedAmount);
+                });
+
+                const blob = new WebSocket(url);
+                const blobEvents = events(blob, (socket) => socket.send(new Blob(["ab"])), (socket) => socket.close(3001, "leaving"));
+
+                const early = new WebSocket(url);
+                early.close();
+                const earlyEvents = events(early);
+
+                globalThis.res = [
+                    state.join(),
+                    await chatEvents,
+                    `${chat.binaryType} ${error(() => chat.send("after"))} ${chat.bufferedAmount}`,
+                    await blobEvents,
+                    `${early.readyState} ${await earlyEvents}`,
+                    await events(new WebSocket("ws://localhost:1/")),
+                    `${WebSocket.OPEN} ${chat.CLOSED} ${Object.prototype.toString.call(chat)}`,
+                    error(() => new WebSocket()),
+                    error(() => new WebSocket("http://127.0.0.1/")),
+                    error(() => new WebSocket(url, ["a", "a"])),
+                    error(() => chat.close(1001)),
+                    error(() => chat.close(1000, "x".repeat(124))),
+                ];
+                globalThis.secure = await new Promise((resolve) => {
+                    try {
+                        events(new WebSocket(`wss://127.0.0.1:${port}/`)).then(resolve);
+                    } catch (e) {
+                        resolve(`${e.name} ${e.message}`);
+                    }
+                });
+            "#,
+            )
+            .unwrap();
+            promise.into_future::<()>().await.unwrap();
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "0,InvalidStateError,true".into(),
+                    format!("open 1 chat|12|hello ws://127.0.0.1:{port}|[1,2,3,4]|close 4000 done true true 3"),
+                    "arraybuffer returned 5".into(),
+                    "open 1 |blob 2|close 3001 leaving true true 3".into(),
+                    "3 error The socket was closed before the connection was established|close 1006  false true 3".into(),
+                    "error IO Error: connecting to localhost:1 is not allowed|close 1006  false true 3".into(),
+                    "1 3 [object WebSocket]".into(),
+                    "TypeError".into(),
+                    "SyntaxError".into(),
+                    "SyntaxError".into(),
+                    "InvalidAccessError".into(),
+                    "SyntaxError".into(),
+                ]
+            );
+            let secure: StdString = ctx.globals().get("secure").unwrap();
+            if cfg!(feature = "websocket-tls") {
+                assert!(secure.starts_with("error "), "{secure}");
+                assert!(secure.ends_with("|close 1006  false true 3"), "{secure}");
+            } else {
+                assert_eq!(secure, "SyntaxError wss URLs are not supported");
+            }
+        })
+        .await;
+        server.abort();
+    }
+}
@@ -17,6 +17,7 @@ use crate::{
 #[derive(Debug, Default, Clone)]
 pub(crate) struct ClassConfig {
     pub frozen: bool,
+    pub shared: bool,
     pub crate_: Option<String>,
     pub rename: Option<String>,
     pub rename_all: Option<Case>,
@@ -24,6 +25,7 @@ pub(crate) struct ClassConfig {
 
 pub(crate) enum ClassOption {
     Frozen(FlagOption<kw::frozen>),
+    Shared(FlagOption<kw::shared>),
     Crate(ValueOption<Token![crate], LitStr>),
     Rename(ValueOption<kw::rename, LitStr>),
     RenameAll(ValueOption<kw::rename_all, Case>),
@@ -33,6 +35,8 @@ impl Parse for ClassOption {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         if input.peek(kw::frozen) {
             input.parse().map(Self::Frozen)
+        } else if input.peek(kw::shared) {
+            input.parse().map(Self::Shared)
         } else if input.peek(Token![crate]) {
             input.parse().map(Self::Crate)
         } else if input.peek(kw::rename) {
@@ -51,6 +55,9 @@ impl ClassConfig {
             ClassOption::Frozen(ref x) => {
                 self.frozen = x.is_true();
             }
+            ClassOption::Shared(ref x) => {
+                self.shared = x.is_true();
+            }
             ClassOption::Crate(ref x) => {
                 self.crate_ = Some(x.value.value());
             }
@@ -247,7 +254,11 @@ impl Class {
     }
 
     pub fn mutability(&self) -> TokenStream {
-        if self.config().frozen {
+        if self.config().shared {
+            quote! {
+               Shared
+            }
+        } else if self.config().frozen {
             quote! {
                Readable
             }
@@ -127,6 +127,7 @@ impl<'a> Fold for SelfReplacer<'a> {
 
 pub(crate) mod kw {
     syn::custom_keyword!(frozen);
+    syn::custom_keyword!(shared);
     syn::custom_keyword!(skip_trace);
     syn::custom_keyword!(rename);
     syn::custom_keyword!(rename_all);
@@ -141,4 +142,5 @@ pub(crate) mod kw {
     syn::custom_keyword!(prefix);
     syn::custom_keyword!(declare);
     syn::custom_keyword!(evaluate);
+    syn::custom_keyword!(string);
 }
@@ -0,0 +1,170 @@
+use convert_case::Casing;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned,
+    Attribute, Data, DeriveInput, Error, Fields, GenericParam, LitStr, Result, Token,
+};
+
+use crate::{
+    attrs::{take_attributes, FlagOption, OptionList, ValueOption},
+    common::{add_js_lifetime, crate_ident, kw, Case},
+};
+
+#[derive(Default)]
+pub(crate) struct StructConfig {
+    pub crate_: Option<String>,
+    pub rename_all: Option<Case>,
+}
+
+pub(crate) enum StructOption {
+    Crate(ValueOption<Token![crate], LitStr>),
+    RenameAll(ValueOption<kw::rename_all, Case>),
+}
+
+impl Parse for StructOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![crate]) {
+            input.parse().map(Self::Crate)
+        } else if input.peek(kw::rename_all) {
+            input.parse().map(Self::RenameAll)
+        } else {
+            Err(syn::Error::new(input.span(), "invalid struct attribute"))
+        }
+    }
+}
+
+impl StructConfig {
+    pub fn apply(&mut self, option: &StructOption) {
+        match option {
+            StructOption::Crate(x) => self.crate_ = Some(x.value.value()),
+            StructOption::RenameAll(x) => self.rename_all = Some(x.value),
+        }
+    }
+
+    /// Parse the config from the `qjs` attributes of a struct.
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut config = StructConfig::default();
+        for attr in attrs.iter().filter(|x| x.path().is_ident("qjs")) {
+            let options: OptionList<StructOption> = attr.parse_args()?;
+            options.0.iter().for_each(|x| config.apply(x));
+        }
+        Ok(config)
+    }
+}
+
+pub(crate) enum FieldOption {
+    Rename(ValueOption<kw::rename, LitStr>),
+    Default(FlagOption<Token![default]>),
+}
+
+impl Parse for FieldOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::rename) {
+            input.parse().map(Self::Rename)
+        } else if input.peek(Token![default]) {
+            input.parse().map(Self::Default)
+        } else {
+            Err(syn::Error::new(input.span(), "invalid field attribute"))
+        }
+    }
+}
+
+pub(crate) fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        attrs,
+        ..
+    } = input;
+
+    let config = StructConfig::from_attrs(&attrs)?;
+
+    let Data::Struct(data) = data else {
+        return Err(Error::new(
+            ident.span(),
+            "The FromJs derive macro can only be used on structs",
+        ));
+    };
+    let Fields::Named(fields) = data.fields else {
+        return Err(Error::new(
+            data.fields.span(),
+            "The FromJs derive macro only supports structs with named fields",
+        ));
+    };
+
+    let crate_name = if let Some(x) = config.crate_.as_ref() {
+        format_ident!("{x}")
+    } else {
+        format_ident!("{}", crate_ident()?)
+    };
+
+    let mut reads = Vec::new();
+    for mut field in fields.named {
+        let mut rename = None;
+        let mut default = false;
+        take_attributes(&mut field.attrs, |attr| {
+            if !attr.path().is_ident("qjs") {
+                return Ok(false);
+            }
+            let options: OptionList<FieldOption> = attr.parse_args()?;
+            for option in options.0.iter() {
+                match option {
+                    FieldOption::Rename(x) => rename = Some(x.value.value()),
+                    FieldOption::Default(x) => default = x.is_true(),
+                }
+            }
+            Ok(true)
+        })?;
+
+        let field_ident = field.ident.unwrap();
+        let ty = field.ty;
+        let js_name = rename.unwrap_or_else(|| {
+            let name = field_ident.to_string();
+            match config.rename_all {
+                Some(case) => name.to_case(case.to_convert_case()),
+                None => name,
+            }
+        });
+
+        reads.push(if default {
+            quote! {
+                #field_ident: {
+                    let value: #crate_name::Value<'js> = object.get(#js_name)?;
+                    if value.is_undefined() {
+                        ::std::default::Default::default()
+                    } else {
+                        <#ty as #crate_name::FromJs<'js>>::from_js(ctx, value)?
+                    }
+                }
+            }
+        } else {
+            quote! {
+                #field_ident: object.get::<_, #ty>(#js_name)?
+            }
+        });
+    }
+
+    let mut impl_generics = add_js_lifetime(&generics);
+    for param in impl_generics.params.iter_mut() {
+        if let GenericParam::Type(ty) = param {
+            ty.bounds.push(parse_quote!(#crate_name::FromJs<'js>));
+        }
+    }
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #crate_name::FromJs<'js> for #ident #ty_generics #where_clause {
+            fn from_js(ctx: &#crate_name::Ctx<'js>, value: #crate_name::Value<'js>) -> #crate_name::Result<Self> {
+                let object = <#crate_name::Object<'js> as #crate_name::FromJs<'js>>::from_js(ctx, value)?;
+                Ok(#ident {
+                    #(#reads,)*
+                })
+            }
+        }
+    })
+}
@@ -0,0 +1,229 @@
+use convert_case::Casing;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Attribute, Data, DeriveInput, Error, Fields, LitStr, Result, Token,
+};
+
+use crate::{
+    attrs::{take_attributes, FlagOption, OptionList, ValueOption},
+    common::{crate_ident, kw, Case},
+};
+
+#[derive(Default)]
+pub(crate) struct EnumConfig {
+    pub crate_: Option<String>,
+    pub rename: Option<String>,
+    pub rename_all: Option<Case>,
+    pub string: bool,
+}
+
+pub(crate) enum EnumOption {
+    Crate(ValueOption<Token![crate], LitStr>),
+    Rename(ValueOption<kw::rename, LitStr>),
+    RenameAll(ValueOption<kw::rename_all, Case>),
+    String(FlagOption<kw::string>),
+}
+
+impl Parse for EnumOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![crate]) {
+            input.parse().map(Self::Crate)
+        } else if input.peek(kw::rename_all) {
+            input.parse().map(Self::RenameAll)
+        } else if input.peek(kw::rename) {
+            input.parse().map(Self::Rename)
+        } else if input.peek(kw::string) {
+            input.parse().map(Self::String)
+        } else {
+            Err(syn::Error::new(input.span(), "invalid enum attribute"))
+        }
+    }
+}
+
+impl EnumConfig {
+    pub fn apply(&mut self, option: &EnumOption) {
+        match option {
+            EnumOption::Crate(x) => self.crate_ = Some(x.value.value()),
+            EnumOption::Rename(x) => self.rename = Some(x.value.value()),
+            EnumOption::RenameAll(x) => self.rename_all = Some(x.value),
+            EnumOption::String(x) => self.string = x.is_true(),
+        }
+    }
+
+    /// Parse the config from the `qjs` attributes of an enum.
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut config = EnumConfig::default();
+        for attr in attrs.iter().filter(|x| x.path().is_ident("qjs")) {
+            let options: OptionList<EnumOption> = attr.parse_args()?;
+            options.0.iter().for_each(|x| config.apply(x));
+        }
+        Ok(config)
+    }
+
+    pub fn js_name(&self, ident: &Ident) -> String {
+        self.rename.clone().unwrap_or_else(|| ident.to_string())
+    }
+}
+
+/// Returns if the attributes contain a derive of the `JsEnum` trait.
+pub(crate) fn is_derived(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if !attr.path().is_ident("derive") {
+            return false;
+        }
+        let mut found = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta
+                .path
+                .segments
+                .last()
+                .map(|x| x.ident == "JsEnum")
+                .unwrap_or(false)
+            {
+                found = true;
+            }
+            Ok(())
+        });
+        found
+    })
+}
+
+pub(crate) fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        attrs,
+        ..
+    } = input;
+
+    let config = EnumConfig::from_attrs(&attrs)?;
+
+    if !generics.params.is_empty() {
+        return Err(Error::new(
+            generics.span(),
+            "The JsEnum derive macro does not support generics",
+        ));
+    }
+
+    let Data::Enum(data) = data else {
+        return Err(Error::new(
+            ident.span(),
+            "The JsEnum derive macro can only be used on enums",
+        ));
+    };
+
+    let crate_name = if let Some(x) = config.crate_.as_ref() {
+        format_ident!("{x}")
+    } else {
+        format_ident!("{}", crate_ident()?)
+    };
+
+    let mut variants = Vec::new();
+    for mut variant in data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new(
+                variant.fields.span(),
+                "The JsEnum derive macro only supports variants without fields",
+            ));
+        }
+
+        let mut rename = None;
+        take_attributes(&mut variant.attrs, |attr| {
+            if !attr.path().is_ident("qjs") {
+                return Ok(false);
+            }
+            let options: OptionList<ValueOption<kw::rename, LitStr>> = attr.parse_args()?;
+            if let Some(x) = options.0.last() {
+                rename = Some(x.value.value());
+            }
+            Ok(true)
+        })?;
+
+        let js_name = rename.unwrap_or_else(|| {
+            let name = variant.ident.to_string();
+            match config.rename_all {
+                Some(case) => name.to_case(case.to_convert_case()),
+                None => name,
+            }
+        });
+        variants.push((variant.ident, js_name));
+    }
+
+    let name = config.js_name(&ident);
+    let table = variants
+        .iter()
+        .map(|(variant, js_name)| quote!((#js_name, #ident::#variant)));
+
+    let (into_js, from_js) = if config.string {
+        let into_arms = variants
+            .iter()
+            .map(|(variant, js_name)| quote!(#ident::#variant => #js_name));
+        let from_arms = variants
+            .iter()
+            .map(|(variant, js_name)| quote!(#js_name => Ok(#ident::#variant)));
+        (
+            quote! {
+                let name = match self {
+                    #(#into_arms,)*
+                };
+                #crate_name::IntoJs::into_js(name, ctx)
+            },
+            quote! {
+                let name: ::std::string::String = #crate_name::FromJs::from_js(ctx, value)?;
+                match name.as_str() {
+                    #(#from_arms,)*
+                    _ => Err(#crate_name::Error::new_from_js_message(
+                        "string",
+                        #name,
+                        ::std::format!("`{}` is not a variant", name),
+                    )),
+                }
+            },
+        )
+    } else {
+        let checks = variants.iter().map(|(variant, _)| {
+            quote! {
+                if value == #ident::#variant as i32 {
+                    return Ok(#ident::#variant);
+                }
+            }
+        });
+        (
+            quote! {
+                #crate_name::IntoJs::into_js(self as i32, ctx)
+            },
+            quote! {
+                let value: i32 = #crate_name::FromJs::from_js(ctx, value)?;
+                #(#checks)*
+                Err(#crate_name::Error::new_from_js_message(
+                    "number",
+                    #name,
+                    ::std::format!("`{}` is not a variant", value),
+                ))
+            },
+        )
+    };
+
+    Ok(quote! {
+        impl #crate_name::JsEnum for #ident {
+            const NAME: &'static str = #name;
+            const VARIANTS: &'static [(&'static str, Self)] = &[#(#table,)*];
+        }
+
+        impl<'js> #crate_name::IntoJs<'js> for #ident {
+            fn into_js(self, ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<#crate_name::Value<'js>> {
+                #into_js
+            }
+        }
+
+        impl<'js> #crate_name::FromJs<'js> for #ident {
+            fn from_js(ctx: &#crate_name::Ctx<'js>, value: #crate_name::Value<'js>) -> #crate_name::Result<Self> {
+                #from_js
+            }
+        }
+    })
+}
@@ -20,7 +20,9 @@ mod class;
 mod common;
 mod embed;
 mod fields;
+mod from_js;
 mod function;
+mod js_enum;
 mod js_lifetime;
 mod methods;
 mod module;
@@ -42,6 +44,7 @@ mod trace;
 /// | `rename`     | String    | Changes the name of the implemented class on the JavaScript side.                                                                                                                       |
 /// | `rename_all` | Casing    | Converts the case of all the fields of this struct which have implement accessors. Can be one of `lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`,`snake_case`, or `SCREAMING_SNAKE` |
 /// | `frozen`     | Flag      | Changes the class implementation to only allow borrowing immutably.  Trying to borrow mutably will result in an error.                                                                  |
+/// | `shared`     | Flag      | Stores the class in an `Arc` which can be shared with Rust using `Class::instance_arc` and `Class::arc`. Implies `frozen`.                                                             |
 ///
 /// # Field options
 ///
@@ -52,12 +55,17 @@ mod trace;
 /// | **Option**     | **Value** | **Description**                                                                         |
 /// |----------------|-----------|-----------------------------------------------------------------------------------------|
 /// | `get`          | Flag      | Creates a getter for this field, allowing read access to the field from JavaScript.     |
-/// | `set`          | Flag      | Creates a setter for this field, allowing write access to the field from JavaSccript.   |
+/// | `set`          | Flag      | Creates a setter for this field, allowing write access to the field from JavaScript.    |
 /// | `enumerable`   | Flag      | Makes the field, if it has a getter or setter, enumerable in JavaScript.                |
 /// | `configurable` | Flag      | Makes the field, if it has a getter or setter, configurable in JavaScript.              |
 /// | `skip_trace`   | Flag      | Skips the field deriving the `Trace` trait.                                             |
 /// | `rename`       | String    | Changes the name of the field getter and/or setter to the specified name in JavaScript. |
 ///
+/// The accessors are defined on the prototype of the class and go through the borrow checking of
+/// the class, so a getter fails while the instance is borrowed mutably and a setter fails while it
+/// is borrowed at all, or always if the class is `frozen`. A getter clones the field, which
+/// therefore has to implement `Clone` and `IntoJs`, a setter requires the field to implement
+/// `FromJs`.
 ///
 /// # Example
 /// ```
@@ -128,6 +136,28 @@ pub fn class(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
 /// then when you use closures or the functions for which the proper traits are already
 /// implemented..
 ///
+/// A function can return several values by returning a
+/// [`MultiReturn`](rquickjs_core::function::MultiReturn) of a tuple, which is converted into an
+/// array, or into an object when created with `MultiReturn::named`, so the result can be
+/// destructured in JavaScript:
+///
+/// ```
+/// use rquickjs::{function::MultiReturn, Context, Runtime};
+///
+/// #[rquickjs::function]
+/// fn split_at(text: String, idx: usize) -> MultiReturn<(String, String)> {
+///     let (head, tail) = text.split_at(idx);
+///     MultiReturn::new((head.to_owned(), tail.to_owned()))
+/// }
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     ctx.globals().set("splitAt", js_split_at).unwrap();
+///     let res: String = ctx.eval("const [head, tail] = splitAt('hello', 2); tail + head").unwrap();
+///     assert_eq!(res, "llohe");
+/// });
+/// ```
 #[proc_macro_attribute]
 pub fn function(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
     let options = parse_macro_input!(attr as OptionList<FunctionOption>);
@@ -313,6 +343,8 @@ pub fn methods(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
 ///
 /// - `struct` and `enum` items. These will be exported as JavaScript
 /// classes with their constructor exported as a function from the module.
+/// - `enum` items which derive [`JsEnum`](macro@JsEnum), these will be exported as the frozen object
+///   holding their constants.
 /// - `fn` items, these will be exported as JavaScript functions.
 /// - `use` items, the types which are reexported with `pub` will be handled just like `struct` and
 /// `enum` items defined inside the module. The name of the class can be adjusted by renaming the
@@ -553,3 +585,116 @@ pub fn js_lifetime(stream: TokenStream1) -> TokenStream1 {
         Err(e) => e.into_compile_error().into(),
     }
 }
+
+/// A macro for deriving the `JsEnum`, `IntoJs` and `FromJs` traits for an enum without fields.
+///
+/// The variants convert to JavaScript as their discriminant, or as their name with the `string`
+/// option, and `JsEnum::namespace` creates an object holding every variant as a constant. Inside a
+/// [`module`](macro@module) a public enum deriving `JsEnum` is exported as that object.
+///
+/// # Attribute options
+///
+/// | **Option**   | **Value** | **Description**                                                                                                                                                             |
+/// |--------------|-----------|-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------|
+/// | `crate`      | String    | Changes the name from which the attribute tries to use rquickjs types. Use when the name behind which the rquickjs crate is declared is not properly resolved by the macro. |
+/// | `rename`     | String    | Changes the name of the enum on the JavaScript side.                                                                                                                        |
+/// | `rename_all` | Casing    | Converts the case of the names of all variants. Can be one of `lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`,`snake_case`, or `SCREAMING_SNAKE`                         |
+/// | `string`     | Flag      | Converts variants to and from their name instead of their discriminant.                                                                                                     |
+///
+/// A variant can be renamed with `#[qjs(rename = "name")]`.
+///
+/// # Example
+///
+/// ```
+/// use rquickjs::{Context, JsEnum, Runtime};
+///
+/// #[derive(Clone, Copy, PartialEq, Debug, JsEnum)]
+/// #[qjs(rename_all = "SCREAMING_SNAKE")]
+/// enum Color {
+///     Red,
+///     DarkBlue = 4,
+/// }
+///
+/// #[derive(Clone, Copy, PartialEq, Debug, JsEnum)]
+/// #[qjs(string, rename_all = "lowercase")]
+/// enum Mode {
+///     Read,
+///     #[qjs(rename = "rw")]
+///     ReadWrite,
+/// }
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     ctx.globals().set("Color", Color::namespace(&ctx).unwrap()).unwrap();
+///     ctx.globals().set("Mode", Mode::namespace(&ctx).unwrap()).unwrap();
+///
+///     assert_eq!(ctx.eval::<Color, _>("Color.DARK_BLUE").unwrap(), Color::DarkBlue);
+///     assert_eq!(ctx.eval::<String, _>("Color[4]").unwrap(), "DARK_BLUE");
+///     assert_eq!(ctx.eval::<String, _>("Mode.rw").unwrap(), "rw");
+///     assert_eq!(ctx.eval::<Mode, _>("'read'").unwrap(), Mode::Read);
+///     assert!(ctx.eval::<Color, _>("7").is_err());
+/// });
+/// ```
+#[proc_macro_derive(JsEnum, attributes(qjs))]
+pub fn js_enum(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    match js_enum::expand(derive_input) {
+        Ok(x) => x.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// A macro for deriving the `FromJs` trait for a struct with named fields.
+///
+/// The struct is read from a JavaScript object by looking up a property for every field, which
+/// makes it a good fit for options objects taken with
+/// [`Named`](rquickjs_core::function::Named).
+///
+/// # Attribute options
+///
+/// | **Option**   | **Value** | **Description**                                                                                                                                                             |
+/// |--------------|-----------|-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------|
+/// | `crate`      | String    | Changes the name from which the attribute tries to use rquickjs types. Use when the name behind which the rquickjs crate is declared is not properly resolved by the macro. |
+/// | `rename_all` | Casing    | Converts the case of the property names of all fields. Can be one of `lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`,`snake_case`, or `SCREAMING_SNAKE`                  |
+///
+/// # Field options
+///
+/// | **Option** | **Value** | **Description**                                                                         |
+/// |------------|-----------|-----------------------------------------------------------------------------------------|
+/// | `rename`   | String    | Changes the name of the property the field is read from.                                |
+/// | `default`  | Flag      | Uses the [`Default`] value of the field when the property is missing or `undefined`.    |
+///
+/// # Example
+///
+/// ```
+/// use rquickjs::{Context, FromJs, Runtime};
+///
+/// #[derive(FromJs)]
+/// #[qjs(rename_all = "camelCase")]
+/// struct Options {
+///     line_width: u32,
+///     #[qjs(default)]
+///     indent: usize,
+///     #[qjs(rename = "sep", default)]
+///     separator: Option<String>,
+/// }
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     let opts: Options = ctx.eval("({ lineWidth: 80, sep: ',' })").unwrap();
+///     assert_eq!(opts.line_width, 80);
+///     assert_eq!(opts.indent, 0);
+///     assert_eq!(opts.separator.as_deref(), Some(","));
+///     assert!(ctx.eval::<Options, _>("({ indent: 2 })").is_err());
+/// });
+/// ```
+#[proc_macro_derive(FromJs, attributes(qjs))]
+pub fn from_js(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    match from_js::expand(derive_input) {
+        Ok(x) => x.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
@@ -187,6 +187,9 @@ impl ModuleTypeConfig {
         if self.class.frozen {
             attrs.push(quote!(frozen));
         }
+        if self.class.shared {
+            attrs.push(quote!(shared));
+        }
         if let Some(x) = self.class.crate_.as_ref() {
             attrs.push(quote!(crate = #x));
         }
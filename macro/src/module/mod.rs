@@ -3,6 +3,7 @@ use std::collections::{hash_map::Entry, HashMap};
 use crate::{
     attrs::{take_attributes, OptionList},
     class::Class,
+    js_enum::{self, EnumConfig},
 };
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
@@ -213,6 +214,7 @@ pub(crate) fn expand(options: OptionList<ModuleOption>, mut item: ItemMod) -> Re
     let mut _consts = Vec::new();
     let mut _statics = Vec::new();
     let mut _enums = Vec::new();
+    let mut _js_enums = Vec::new();
     let mut _structs = Vec::new();
     let mut _uses = Vec::new();
     let mut _functions = Vec::new();
@@ -251,6 +253,14 @@ pub(crate) fn expand(options: OptionList<ModuleOption>, mut item: ItemMod) -> Re
                 }
             }
             syn::Item::Enum(i) => {
+                if js_enum::is_derived(&i.attrs) {
+                    let config = EnumConfig::from_attrs(&i.attrs)?;
+                    if let syn::Visibility::Public(_) = i.vis {
+                        _js_enums.push((i, config))
+                    }
+                    continue;
+                }
+
                 let config = parse_type_attrs(&mut i.attrs)?;
                 if config.skip {
                     continue;
@@ -431,6 +441,20 @@ pub(crate) fn expand(options: OptionList<ModuleOption>, mut item: ItemMod) -> Re
         )
     }
 
+    for (e, config) in _js_enums {
+        let ident = &e.ident;
+        let name = config.js_name(ident);
+
+        module.export(
+            name.clone(),
+            ident.span(),
+            quote! {
+                let _namespace = <#mod_name::#ident as #crate_name::JsEnum>::namespace(&_ctx)?;
+                _exports.export(#name,_namespace)?;
+            },
+        )
+    }
+
     for (u, config) in _uses {
         export_use(&u.tree, &mut module, &config)?;
     }
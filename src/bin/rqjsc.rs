@@ -0,0 +1,357 @@
+//! Compiles JavaScript modules to QuickJS bytecode, the companion of the `rquickjs` crate to the
+//! `qjsc` compiler of QuickJS.
+//!
+//! Install it with `cargo install rquickjs --features bin`. Run `rqjsc help` for the usage.
+
+use std::{
+    collections::HashSet,
+    env, fmt, fs,
+    io::{self, Write},
+    process::ExitCode,
+};
+
+use rquickjs::{
+    codegen::Bindings,
+    loader::{
+        bundle::{Bundler, ModuleBundle},
+        FileResolver, ScriptLoader,
+    },
+};
+
+const USAGE: &str = "\
+Usage: rqjsc <command> [options] <entry>
+
+Commands:
+  compile   Write the bytecode bundle of the module graph of <entry>
+  embed     Write Rust source embedding the bytecode bundle of <entry>
+  deps      Print the dependency tree of the modules of <entry>
+  bindings  Write Rust bindings of the TypeScript declarations in the file <entry>
+  help      Print this message
+
+Options:
+  -o, --output <path>     Write to a file instead of the standard output
+  -I, --include <dir>     Add a directory to resolve bare specifiers in, `.` by default
+  -x, --external <name>   Leave the module with the specifier or resolved name out of the bundle
+  -m, --module <path>     Also bundle a module which is only imported dynamically
+  -n, --name <ident>      The name of the function generated by `embed`, `bundle` by default,
+                          or of the struct generated by `bindings`, `Bindings` by default
+
+Bundles are loaded with `rquickjs::loader::bundle::ModuleBundle::from_bytes` by the same version
+of rquickjs, on a target with the same endianness.
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Compile,
+    Embed,
+    Deps,
+    Bindings,
+}
+
+struct Options {
+    command: Command,
+    entry: String,
+    output: Option<String>,
+    includes: Vec<String>,
+    externals: Vec<String>,
+    modules: Vec<String>,
+    name: String,
+}
+
+/// An error with a message for the user.
+struct Failure(String);
+
+impl<E: fmt::Display> From<E> for Failure {
+    fn from(error: E) -> Self {
+        Failure(error.to_string())
+    }
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, Failure> {
+    let command = match args.next().as_deref() {
+        Some("compile") => Command::Compile,
+        Some("embed") => Command::Embed,
+        Some("deps") => Command::Deps,
+        Some("bindings") => Command::Bindings,
+        Some("help" | "-h" | "--help") | None => return Ok(None),
+        Some(other) => return Err(Failure(format!("unknown command `{other}`"))),
+    };
+    let mut options = Options {
+        command,
+        entry: String::new(),
+        output: None,
+        includes: Vec::new(),
+        externals: Vec::new(),
+        modules: Vec::new(),
+        name: if command == Command::Bindings {
+            "Bindings".into()
+        } else {
+            "bundle".into()
+        },
+    };
+    let mut entry = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| Failure(format!("missing value of `{arg}`")))
+        };
+        match arg.as_str() {
+            "-o" | "--output" => options.output = Some(value()?),
+            "-I" | "--include" => options.includes.push(value()?),
+            "-x" | "--external" => options.externals.push(value()?),
+            "-m" | "--module" => options.modules.push(value()?),
+            "-n" | "--name" => options.name = value()?,
+            "-h" | "--help" => return Ok(None),
+            _ if arg.starts_with('-') => return Err(Failure(format!("unknown option `{arg}`"))),
+            _ if entry.is_some() => return Err(Failure("only one entry can be given".into())),
+            _ => entry = Some(arg),
+        }
+    }
+    options.entry = entry.ok_or_else(|| Failure("missing entry module".into()))?;
+    if options.includes.is_empty() {
+        options.includes.push(".".into());
+    }
+    let valid_name = options
+        .name
+        .chars()
+        .next()
+        .filter(|x| x.is_ascii_alphabetic() || *x == '_')
+        .is_some()
+        && options
+            .name
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || x == '_');
+    if !valid_name {
+        return Err(Failure(format!(
+            "`{}` is not a Rust identifier",
+            options.name
+        )));
+    }
+    Ok(Some(options))
+}
+
+fn bundle(options: &Options) -> Result<ModuleBundle, Failure> {
+    let resolver = FileResolver::default().with_paths(options.includes.iter().map(String::as_str));
+    let mut bundler = Bundler::new(resolver, ScriptLoader::default());
+    for external in &options.externals {
+        bundler.add_external(external.as_str());
+    }
+    // The entry is a path, while bare specifiers are resolved in the include directories.
+    let path = |path: &str| {
+        if path.starts_with('/') {
+            Err(Failure(format!(
+                "`{path}` must be relative to the working directory"
+            )))
+        } else if path.starts_with('.') {
+            Ok(path.to_string())
+        } else {
+            Ok(format!("./{path}"))
+        }
+    };
+    for module in &options.modules {
+        bundler.add_module(path(module)?);
+    }
+    Ok(bundler.bundle(&path(&options.entry)?)?)
+}
+
+/// Rust source defining a function which returns the bundle.
+fn embed(bundle: &ModuleBundle, name: &str) -> String {
+    let mut res = String::from("// Generated by rqjsc, do not edit.\n//\n// Modules:\n");
+    for (module, bytecode) in bundle.modules() {
+        res += &format!("// - {module} ({} bytes of bytecode)\n", bytecode.len());
+    }
+    let upper = name.to_ascii_uppercase();
+    res += &format!(
+        "\n/// The module bundle of `{entry}`.\n\
+         ///\n\
+         /// Set it as the resolver and loader of a runtime with `Runtime::set_loader`, then\n\
+         /// evaluate the entry with `ModuleBundle::import`.\n\
+         pub fn {name}() -> rquickjs::loader::bundle::ModuleBundle {{\n    \
+             // The bytes were written by rqjsc with the same version of rquickjs.\n    \
+             unsafe {{ rquickjs::loader::bundle::ModuleBundle::from_bytes({upper}_BYTECODE) }}\n        \
+                 .expect(\"the embedded module bundle is invalid\")\n\
+         }}\n\n\
+         const {upper}_BYTECODE: &[u8] = &[",
+        entry = bundle.entry().escape_debug(),
+    );
+    for (idx, byte) in bundle.to_bytes().iter().enumerate() {
+        res += if idx % 16 == 0 { "\n    " } else { " " };
+        res += &format!("0x{byte:02x},");
+    }
+    res += "\n];\n";
+    res
+}
+
+/// The dependency trees of the entry and the added modules, printing modules already shown once.
+fn deps(bundle: &ModuleBundle) -> String {
+    fn visit(
+        bundle: &ModuleBundle,
+        name: &str,
+        prefix: &str,
+        seen: &mut HashSet<String>,
+        res: &mut String,
+    ) {
+        let children: Vec<_> = bundle
+            .imports()
+            .filter(|(base, _, _)| *base == name)
+            .map(|(_, _, child)| child)
+            .collect();
+        for (idx, child) in children.iter().enumerate() {
+            let last = idx + 1 == children.len();
+            let repeated = !seen.insert(child.to_string());
+            res.push_str(prefix);
+            res.push_str(if last { "└── " } else { "├── " });
+            res.push_str(child);
+            if repeated {
+                res.push_str(" (*)\n");
+                continue;
+            }
+            res.push('\n');
+            let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            visit(bundle, child, &prefix, seen, res);
+        }
+    }
+
+    let mut res = String::new();
+    let mut roots: Vec<_> = bundle
+        .imports()
+        .filter(|(base, _, _)| base.is_empty())
+        .map(|(_, specifier, name)| (specifier != bundle.entry(), name))
+        .collect();
+    if roots.is_empty() {
+        roots.push((false, bundle.entry()));
+    }
+    roots.sort_by_key(|(added, _)| *added);
+    let mut seen = HashSet::new();
+    for (_, root) in roots {
+        if !seen.insert(root.to_string()) {
+            continue;
+        }
+        res.push_str(root);
+        res.push('\n');
+        visit(bundle, root, "", &mut seen, &mut res);
+    }
+    res
+}
+
+fn run(options: &Options) -> Result<(), Failure> {
+    let output = match options.command {
+        Command::Compile => bundle(options)?.to_bytes(),
+        Command::Embed => embed(&bundle(options)?, &options.name).into_bytes(),
+        Command::Deps => deps(&bundle(options)?).into_bytes(),
+        Command::Bindings => {
+            let source = fs::read_to_string(&options.entry)?;
+            let bindings = Bindings::new().with_name(options.name.as_str());
+            let res = bindings
+                .generate(&source)
+                .map_err(|error| Failure(format!("{}: {error}", options.entry)))?;
+            res.into_bytes()
+        }
+    };
+    match &options.output {
+        Some(path) => fs::write(path, output)?,
+        None => io::stdout().write_all(&output)?,
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let res = parse(env::args().skip(1)).and_then(|options| match options {
+        Some(options) => run(&options),
+        None => {
+            print!("{USAGE}");
+            Ok(())
+        }
+    });
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure(message)) => {
+            eprintln!("rqjsc: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rquickjs::loader::{BuiltinLoader, BuiltinResolver};
+
+    fn parse_args(args: &str) -> Result<Option<Options>, String> {
+        parse(args.split_whitespace().map(String::from)).map_err(|Failure(message)| message)
+    }
+
+    #[test]
+    fn parse_options() {
+        assert!(parse_args("").unwrap().is_none());
+        assert!(parse_args("help").unwrap().is_none());
+        assert!(parse_args("compile main.js --help").unwrap().is_none());
+
+        let options =
+            parse_args("embed -o out.rs -I lib -I vendor -x host -m lazy.js -n app main.js")
+                .unwrap()
+                .unwrap();
+        assert_eq!(options.command, Command::Embed);
+        assert_eq!(options.entry, "main.js");
+        assert_eq!(options.output.as_deref(), Some("out.rs"));
+        assert_eq!(options.includes, ["lib", "vendor"]);
+        assert_eq!(options.externals, ["host"]);
+        assert_eq!(options.modules, ["lazy.js"]);
+        assert_eq!(options.name, "app");
+
+        let options = parse_args("compile main.js").unwrap().unwrap();
+        assert_eq!(options.includes, ["."]);
+        assert_eq!(options.name, "bundle");
+        let options = parse_args("bindings lib.d.ts").unwrap().unwrap();
+        assert_eq!(options.name, "Bindings");
+
+        let error = |args| parse_args(args).err().unwrap();
+        assert_eq!(error("run main.js"), "unknown command `run`");
+        assert_eq!(
+            error("compile --verbose main.js"),
+            "unknown option `--verbose`"
+        );
+        assert_eq!(error("compile main.js -o"), "missing value of `-o`");
+        assert_eq!(error("compile a.js b.js"), "only one entry can be given");
+        assert_eq!(error("compile"), "missing entry module");
+        assert_eq!(
+            error("embed -n 1app main.js"),
+            "`1app` is not a Rust identifier"
+        );
+        assert_eq!(
+            error("embed -n my-app main.js"),
+            "`my-app` is not a Rust identifier"
+        );
+    }
+
+    #[test]
+    fn deps_tree() {
+        let bundle = Bundler::new(
+            BuiltinResolver::default()
+                .with_module("main")
+                .with_module("a")
+                .with_module("b")
+                .with_module("c")
+                .with_module("lazy"),
+            BuiltinLoader::default()
+                .with_module("main", "import 'a'; import 'b';")
+                .with_module("a", "import 'c';")
+                .with_module("b", "import 'c';")
+                .with_module("c", "")
+                .with_module("lazy", "import 'a';"),
+        )
+        .with_module("lazy")
+        .bundle("main")
+        .unwrap();
+        assert_eq!(
+            deps(&bundle),
+            "main\n\
+             ├── a\n\
+             │   └── c\n\
+             └── b\n    \
+                 └── c (*)\n\
+             lazy\n\
+             └── a (*)\n"
+        );
+    }
+}
@@ -37,6 +37,10 @@
 //! - `classes` enables support for ES6 classes. Any user-defined Rust
 //! type can be exported to JS as an ES6 class which can be derived and extended by JS.
 //! - `properties` enables support for object properties (`Object.defineProperty`).
+//! - `std` uses the standard library. Without it the crate is `no_std` and only needs `core` and
+//!   `alloc`, leaving out [`Ctx::eval_file`], [`Ctx::eval_timeout`], the [`channel`] module, the
+//!   conversions of `std` types and the features which need `std`, like `loader`, `web-minimal`,
+//!   `futures` and `parallel`. Panics in callbacks can't be caught without it.
 //!
 //! ## Advanced
 //!
@@ -54,10 +58,65 @@
 //! [`Runtime::set_loader`]. The resolvers and loaders can be easily combined via tuples. When the
 //! previous resolver or loader failed the next one will be applied.
 //!
+//! - `bin` builds the `rqjsc` binary, installed with `cargo install rquickjs --features bin`. It
+//!   compiles a module graph to a [`ModuleBundle`](loader::bundle::ModuleBundle), writes Rust
+//!   source embedding the bundle and prints the dependency tree of the modules. It also writes
+//!   the bindings of TypeScript declarations generated by [`codegen`].
+//!
+//! - `codegen` adds [`codegen::Bindings`], generating typed Rust wrappers of JS libraries from
+//!   their TypeScript declarations. It is meant for build scripts and tools, so it isn't enabled
+//!   by `full`.
+//!
 //! - `dyn-load` adds support for loadable native modules (so/dll/dylib).
 //!
 //! - `array-buffer` adds support for [`ArrayBuffer`] and [`TypedArray`].
 //!
+//! - `web-minimal` adds the [`web`] module for installing `console`, timers, `performance`,
+//!   `TextEncoder`, `TextDecoder`, `ReadableStream`, `WritableStream`, `Blob`, `File`, `FormData`,
+//!   `EventTarget`, `MessageChannel`, `atob`, `btoa`, `queueMicrotask` and `structuredClone` on a
+//!   context.
+//!
+//! - `crypto` adds `crypto.subtle` with SHA digests and HMAC signatures and
+//!   `crypto.timingSafeEqual` to the globals installed by the [`web`] module.
+//!
+//! - `fs` adds the [`web::Fs`] module giving scripts access to the files below a directory,
+//!   limited by permissions.
+//!
+//! - `process` adds the [`web::Process`] module giving scripts allow-listed environment
+//!   variables, arguments, platform information, `hrtime` and an `exit` routed to the host.
+//!
+//! - `subprocess` adds the [`web::Subprocess`] module letting scripts run commands on the host,
+//!   each checked by a [`web::CommandPolicy`]. Enables `futures`.
+//!
+//! - `net` adds the [`web::Net`] module giving scripts TCP and UDP sockets to allow-listed
+//!   addresses, bridged to tokio. Enables `futures`.
+//!
+//! - `websocket` adds the [`web::WebSocket`] module giving scripts WebSocket clients to the
+//!   addresses allowed by a [`web::Net`]. Enables `net`.
+//!
+//! - `websocket-tls` adds secure `wss:` URLs to the [`web::WebSocket`] module, verified with
+//!   rustls and the webpki root certificates. Enables `websocket`.
+//!
+//! - `storage` adds the [`web::Storage`] module giving scripts a key-value store with
+//!   transactions and quotas, kept by a [`web::StorageBackend`].
+//!
+//! - `compression` adds the [`web::Compression`] module compressing `Uint8Array`s with gzip and
+//!   brotli, on a separate thread with `futures`.
+//!
+//! - `cbor` adds [`Value::to_cbor`] and [`Value::from_cbor`] for serializing values to CBOR.
+//!
+//! - `msgpack` adds [`Value::to_msgpack`] and [`Value::from_msgpack`] for serializing values to
+//!   MessagePack.
+//!
+//! - `serde` adds [`Ctx::eval_json`] and [`Ctx::eval_into`] for evaluating scripts into
+//!   `serde_json` values and types implementing `Deserialize`.
+//!
+//! - `bench` adds the [`bench`] module measuring the time of scripts, calls and conversions and
+//!   comparing it with a baseline of an earlier run.
+//!
+//! - `testing` adds the [`testing`] module with structural diffs of values, `assert_js_eq` and
+//!   snapshots of values and exceptions.
+//!
 //! - `futures` adds support for async Rust. When enabled the library exports [`AsyncRuntime`] and
 //! [`AsyncContext`]. These are the asynchronous variants of the normal runtime and context. In
 //! order to ensure that QuickJS is used properly the runtime is placed behind a lock. For the
@@ -73,6 +132,11 @@
 //!
 //! - `phf` enables using Perfect Hash Function for builtin modules lookup
 //!
+//! - `metrics` adds [`Metrics::record`](runtime::Metrics::record) for publishing the counters
+//!   collected by a [`Runtime`] through the [`metrics`](https://docs.rs/metrics) crate.
+//! - `tracing` emits [`tracing`](https://docs.rs/tracing) spans for script and module evaluation,
+//!   pending job execution and calls between Rust and JavaScript.
+//!
 //! ## Extra types
 //!
 //! This crate has support for conversion of many Rust types like [`Option`],
@@ -82,7 +146,8 @@
 //! - `either` adds [`FromJs`]/[`IntoJs`] implementations for [`Either`](`either::Either`)
 //!
 //! - `indexmap` adds [`FromJs`]/[`IntoJs`] implementations for [`IndexSet`](`indexmap::IndexSet`)
-//! and [`IndexMap`](`indexmap_rs::IndexMap`)
+//!   and [`IndexMap`](`indexmap_rs::IndexMap`). Unlike `HashMap`, an `IndexMap` keeps the order of
+//!   the properties of an object in both directions, for scripts relying on it.
 //!
 //! ## Bindings
 //!
@@ -106,6 +171,11 @@
 //!
 //! To build the crate for any other target you must enable the `bindgen` feature.
 //!
+//! The `system` feature links against a prebuilt QuickJS library instead of compiling the bundled
+//! sources, see the `rquickjs-sys` crate for the environment variables used to locate it. Since
+//! such a library isn't patched, the APIs which depend on the patches aren't available with it,
+//! see the feature matrix in the README of `rquickjs-sys`.
+//!
 //! ## Experimental
 //!
 //! - `parallel` enables multithreading support.
@@ -129,14 +199,19 @@
 //! - `dump-module-resolve`
 //! - `dump-promise`
 //! - `dump-read-object`
+//!
+//! The `leak-detection` feature records where every handle to a JavaScript value was created and
+//! reports the handles which are still alive when a context is dropped. It also exposes the
+//! reference counts of values and traces the handles to them, see the `leak` module.
 
 #![cfg_attr(feature = "doc-cfg", feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 pub use rquickjs_core::*;
 
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "macro")))]
 #[cfg(feature = "macro")]
-pub use rquickjs_macro::{class, embed, function, methods, module, JsLifetime};
+pub use rquickjs_macro::{class, embed, function, methods, module, FromJs, JsEnum, JsLifetime};
 
 pub mod class {
     //! JavaScript classes defined from Rust.
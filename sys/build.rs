@@ -95,6 +95,7 @@ fn main() {
     let features = [
         "bindgen",
         "update-bindings",
+        "system",
         "dump-bytecode",
         "dump-gc",
         "dump-gc-free",
@@ -156,11 +157,25 @@ fn main() {
 
     let mut bindgen_cflags = vec![];
 
+    if cfg!(feature = "system") {
+        let include_dir = link_system().unwrap_or_else(|e| panic!("{e}"));
+        fs::copy(include_dir.join("quickjs.h"), out_dir.join("quickjs.h"))
+            .expect("Unable to copy quickjs.h from the QuickJS include directory");
+        fs::copy("quickjs.bind.h", out_dir.join("quickjs.bind.h")).expect("Unable to copy source");
+        bindgen(
+            out_dir,
+            out_dir.join("quickjs.bind.h"),
+            &defines,
+            bindgen_cflags,
+        );
+        return;
+    }
+
     if target_os == "windows" {
         if target_env == "msvc" {
-            env::set_var("CFLAGS", "/std:c11 /experimental:c11atomics");
+            configure_msvc(&mut builder);
         } else {
-            env::set_var("CFLAGS", "-std=c11");
+            builder.flag("-std=c11");
         }
     }
 
@@ -172,11 +187,22 @@ fn main() {
         defines.push(("FE_UPWARD".into(), Some("0")));
     }
 
+    if target_os == "none" {
+        // bare metal targets have no threads, the emscripten ifdefs leave out pthreads and atomics
+        defines.push(("EMSCRIPTEN".into(), Some("1")));
+    }
+
     for file in source_files.iter().chain(header_files.iter()) {
-        fs::copy(src_dir.join(file), out_dir.join(file))
-            .expect("Unable to copy source; try 'git submodule update --init'");
+        fs::copy(src_dir.join(file), out_dir.join(file)).unwrap_or_else(|e| {
+            panic!(
+                "Unable to copy {} from {}: {e}; try 'git submodule update --init'",
+                file,
+                src_dir.display()
+            )
+        });
     }
     fs::copy("quickjs.bind.h", out_dir.join("quickjs.bind.h")).expect("Unable to copy source");
+    patch_sources(out_dir);
 
     if target_os == "wasi" {
         let wasi_sdk_path = get_wasi_sdk_path();
@@ -215,6 +241,256 @@ fn main() {
     builder.compile("libquickjs.a");
 }
 
+/// Patch the copied `quickjs.c` and `libregexp.c` with the hooks and exports declared in
+/// `src/hooks.rs`.
+///
+/// The local time zone offset used by `Date` is routed through `rquickjs_timezone_offset`, falling
+/// back to the original implementation when no hook is set. Every garbage collection, including
+/// the ones QuickJS triggers when allocating, calls `rquickjs_gc_run` first. The backtracking loop
+/// of the regular expression engine calls `rquickjs_regexp_interrupt` every
+/// `RQUICKJS_REGEXP_POLL_STEPS` steps, which can abort the match by raising an exception. Functions
+/// of the engine which QuickJS defines but doesn't declare in its header are exported through
+/// wrappers appended to `quickjs.c`.
+fn patch_sources(out_dir: &Path) {
+    const QUICKJS_PATCHES: &[(&str, &str, &str)] = &[
+        (
+            "getTimezoneOffset",
+            "static int getTimezoneOffset(int64_t time) {",
+            "int rquickjs_timezone_offset(int64_t time, int *offset);
+static int getTimezoneOffsetDefault(int64_t time);
+static int getTimezoneOffset(int64_t time) {
+    int offset;
+    if (rquickjs_timezone_offset(time, &offset))
+        return offset;
+    return getTimezoneOffsetDefault(time);
+}
+static int getTimezoneOffsetDefault(int64_t time) {",
+        ),
+        (
+            "JS_RunGC",
+            "void JS_RunGC(JSRuntime *rt)\n{",
+            "void rquickjs_gc_run(JSRuntime *rt, size_t malloc_size);
+void JS_RunGC(JSRuntime *rt)
+{
+    rquickjs_gc_run(rt, rt->malloc_state.malloc_size);",
+        ),
+        (
+            "the regexp callers",
+            "JS_ThrowInternalError(ctx, \"out of memory in regexp execution\");",
+            "if (!JS_HasException(ctx))
+                JS_ThrowInternalError(ctx, \"out of memory in regexp execution\");",
+        ),
+    ];
+    const QUICKJS_EXPORTS: &str = "
+void rquickjs_set_uncatchable_error(JSContext *ctx, JSValue val, BOOL flag)
+{
+    JS_SetUncatchableError(ctx, val, flag);
+}
+
+int rquickjs_global_lexical_count(JSContext *ctx)
+{
+    JSShape *sh = JS_VALUE_GET_OBJ(ctx->global_var_obj)->shape;
+    return sh->prop_count - sh->deleted_prop_count;
+}
+
+BOOL rquickjs_atom_is_array_index(JSContext *ctx, uint32_t *pval, JSAtom atom)
+{
+    return JS_AtomIsArrayIndex(ctx, pval, atom);
+}
+
+JSValue rquickjs_new_string_from_chunks(JSContext *ctx, const char *const *chunks,
+                                        const size_t *lens, size_t count, size_t len)
+{
+    StringBuffer b;
+    JSValue v;
+    size_t i;
+
+    if (len > JS_STRING_LEN_MAX)
+        len = JS_STRING_LEN_MAX;
+    string_buffer_init(ctx, &b, len);
+    for (i = 0; i < count; i++) {
+        v = JS_NewStringLen(ctx, chunks[i], lens[i]);
+        if (JS_IsException(v)) {
+            string_buffer_free(&b);
+            return JS_EXCEPTION;
+        }
+        if (string_buffer_concat_value_free(&b, v))
+            break;
+    }
+    return string_buffer_end(&b);
+}
+
+int rquickjs_regexp_poll(JSContext *ctx, uint64_t steps);
+
+int rquickjs_regexp_interrupt(void *opaque, uint64_t steps)
+{
+    JSContext *ctx = opaque;
+    JSRuntime *rt = ctx->rt;
+    if (rquickjs_regexp_poll(ctx, steps))
+        return 1;
+    if (rt->interrupt_handler && rt->interrupt_handler(rt, rt->interrupt_opaque)) {
+        JS_ThrowInternalError(ctx, \"interrupted\");
+        JS_SetUncatchableError(ctx, rt->current_exception, TRUE);
+        return 1;
+    }
+    return 0;
+}
+";
+    const LIBREGEXP_PATCHES: &[(&str, &str, &str)] = &[
+        (
+            "REExecContext",
+            "    void *opaque; /* used for stack overflow check */\n",
+            "    void *opaque; /* used for stack overflow check */
+    int interrupt_counter;
+    uint64_t steps;
+",
+        ),
+        (
+            "lre_exec_backtrack",
+            "        opcode = *pc++;\n",
+            "        if (unlikely(--s->interrupt_counter <= 0)) {
+            s->interrupt_counter = RQUICKJS_REGEXP_POLL_STEPS;
+            s->steps += RQUICKJS_REGEXP_POLL_STEPS;
+            if (rquickjs_regexp_interrupt(s->opaque, s->steps))
+                return -1;
+        }
+        opcode = *pc++;
+",
+        ),
+        (
+            "lre_exec",
+            "        s->cbuf_type = 2;\n    s->opaque = opaque;\n",
+            "        s->cbuf_type = 2;
+    s->opaque = opaque;
+    s->interrupt_counter = RQUICKJS_REGEXP_POLL_STEPS;
+    s->steps = 0;
+",
+        ),
+        (
+            "libregexp.h include",
+            "#include \"libregexp.h\"\n",
+            "#include \"libregexp.h\"
+
+#define RQUICKJS_REGEXP_POLL_STEPS 10000
+int rquickjs_regexp_interrupt(void *opaque, uint64_t steps);
+",
+        ),
+    ];
+
+    patch_file(&out_dir.join("quickjs.c"), QUICKJS_PATCHES, QUICKJS_EXPORTS);
+    patch_file(&out_dir.join("libregexp.c"), LIBREGEXP_PATCHES, "");
+}
+
+/// Replace every occurrence of the original snippets of a source file and append the exports.
+fn patch_file(path: &Path, patches: &[(&str, &str, &str)], exports: &str) {
+    let file = path.file_name().unwrap().to_string_lossy();
+    let mut source =
+        fs::read_to_string(path).unwrap_or_else(|e| panic!("Unable to read {file}: {e}"));
+    for (name, original, patched) in patches {
+        if !source.contains(original) {
+            panic!("Unable to patch {name} in {file}, the sources don't match");
+        }
+        source = source.replace(original, patched);
+    }
+    source.push_str(exports);
+    fs::write(path, source).unwrap_or_else(|e| panic!("Unable to patch {file}: {e}"));
+}
+
+#[cfg(not(feature = "system"))]
+fn link_system() -> Result<PathBuf, String> {
+    Err("Linking a prebuilt QuickJS library requires the `system` feature".into())
+}
+
+/// Link against a prebuilt QuickJS library instead of compiling the bundled sources.
+///
+/// The library is located with `QUICKJS_LIB_DIR` and `QUICKJS_INCLUDE_DIR`, falling back to
+/// pkg-config, and linked statically if `QUICKJS_STATIC` is set. Returns the include directory.
+#[cfg(feature = "system")]
+fn link_system() -> Result<PathBuf, String> {
+    for var in [
+        "QUICKJS_LIB_DIR",
+        "QUICKJS_INCLUDE_DIR",
+        "QUICKJS_LIB_NAME",
+        "QUICKJS_STATIC",
+    ] {
+        println!("cargo:rerun-if-env-changed={var}");
+    }
+
+    let lib_name = env::var("QUICKJS_LIB_NAME").unwrap_or_else(|_| "qjs".into());
+
+    if let Some(lib_dir) = env::var_os("QUICKJS_LIB_DIR") {
+        let kind = if env::var_os("QUICKJS_STATIC").is_some() {
+            "static"
+        } else {
+            "dylib"
+        };
+        println!(
+            "cargo:rustc-link-search=native={}",
+            Path::new(&lib_dir).display()
+        );
+        println!("cargo:rustc-link-lib={kind}={lib_name}");
+        let include_dir = env::var_os("QUICKJS_INCLUDE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Path::new(&lib_dir).join("..").join("include"));
+        return Ok(include_dir);
+    }
+
+    let library = pkg_config::Config::new()
+        .statik(env::var_os("QUICKJS_STATIC").is_some())
+        .probe(&lib_name)
+        .map_err(|e| {
+            format!("Unable to find QuickJS, set QUICKJS_LIB_DIR or install a pkg-config file: {e}")
+        })?;
+    env::var_os("QUICKJS_INCLUDE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            library
+                .include_paths
+                .into_iter()
+                .find(|path| path.join("quickjs.h").is_file())
+        })
+        .ok_or_else(|| "Unable to find quickjs.h, set QUICKJS_INCLUDE_DIR".to_string())
+}
+
+/// Configure the compiler for MSVC targets.
+///
+/// QuickJS needs C11 atomics which `cl.exe` only supports behind an experimental flag since
+/// Visual Studio 2022 17.5, older versions fall back to `clang-cl` when it can be found.
+fn configure_msvc(builder: &mut cc::Build) {
+    if builder.get_compiler().is_like_clang_cl() {
+        builder.flag("/clang:-std=c11");
+        return;
+    }
+
+    builder.flag("/std:c11");
+    if builder
+        .is_flag_supported("/experimental:c11atomics")
+        .unwrap_or(false)
+    {
+        builder.flag("/experimental:c11atomics");
+        return;
+    }
+
+    let clang_cl = env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .map(|path| path.join("clang-cl.exe"))
+            .find(|path| path.is_file())
+    });
+    match clang_cl {
+        Some(clang_cl) => {
+            println!(
+                "cargo:warning=cl.exe doesn't support C11 atomics, building QuickJS with {}",
+                clang_cl.display()
+            );
+            builder.compiler(clang_cl);
+            builder.flag("/clang:-std=c11");
+        }
+        None => panic!(
+            "Building QuickJS with MSVC requires Visual Studio 2022 17.5 or newer or clang-cl"
+        ),
+    }
+}
+
 fn feature_to_cargo(name: impl AsRef<str>) -> String {
     format!("CARGO_FEATURE_{}", feature_to_define(name))
 }
@@ -294,6 +570,8 @@ where
         .clang_arg("-v")
         .clang_args(cflags)
         .size_t_is_usize(false)
+        .use_core()
+        .ctypes_prefix("core::ffi")
         .header(header_file.display().to_string())
         .allowlist_type("JS.*")
         .allowlist_function("js.*")
@@ -0,0 +1,136 @@
+use core::{
+    mem,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// The function consulted by `Date` for the local time zone offset, see
+/// [`set_timezone_offset_hook`].
+pub type TimezoneOffsetHook = fn(time: i64) -> Option<i32>;
+
+static TIMEZONE_OFFSET_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Set the function supplying the local time zone offset used by `Date`.
+///
+/// The hook receives a time in milliseconds since the epoch and returns the difference between
+/// UTC and local time in minutes, positive west of Greenwich like `Date.prototype.getTimezoneOffset`.
+/// Returning `None`, or passing `None` here, falls back to the C library.
+///
+/// The hook is process-wide and may be called from any thread running a runtime. It must not
+/// unwind.
+pub fn set_timezone_offset_hook(hook: Option<TimezoneOffsetHook>) {
+    let hook = hook.map(|hook| hook as *mut ()).unwrap_or(ptr::null_mut());
+    TIMEZONE_OFFSET_HOOK.store(hook, Ordering::Release);
+}
+
+/// Called by the patched `getTimezoneOffset` in `quickjs.c`.
+#[no_mangle]
+unsafe extern "C" fn rquickjs_timezone_offset(time: i64, offset: *mut c_int) -> c_int {
+    let hook = TIMEZONE_OFFSET_HOOK.load(Ordering::Acquire);
+    if hook.is_null() {
+        return 0;
+    }
+    let hook = mem::transmute::<*mut (), TimezoneOffsetHook>(hook);
+    match hook(time) {
+        Some(value) => {
+            *offset = value as c_int;
+            1
+        }
+        None => 0,
+    }
+}
+
+/// The function called before every garbage collection of any runtime, see [`set_gc_hook`].
+///
+/// Receives the runtime and the number of bytes it has allocated.
+pub type GcHook = unsafe fn(rt: *mut JSRuntime, malloc_size: usize);
+
+static GC_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Set the function called before every garbage collection.
+///
+/// Unlike wrapping `JS_RunGC` this also observes the collections QuickJS triggers by itself when
+/// the allocated memory passes the threshold set with `JS_SetGCThreshold`.
+///
+/// The hook is process-wide and may be called from any thread running a runtime, in the middle
+/// of an allocation. It must not unwind nor call back into the runtime.
+pub fn set_gc_hook(hook: Option<GcHook>) {
+    let hook = hook.map(|hook| hook as *mut ()).unwrap_or(ptr::null_mut());
+    GC_HOOK.store(hook, Ordering::Release);
+}
+
+/// Called by the patched `JS_RunGC` in `quickjs.c`.
+#[no_mangle]
+unsafe extern "C" fn rquickjs_gc_run(rt: *mut JSRuntime, malloc_size: size_t) {
+    let hook = GC_HOOK.load(Ordering::Acquire);
+    if hook.is_null() {
+        return;
+    }
+    let hook = mem::transmute::<*mut (), GcHook>(hook);
+    hook(rt, malloc_size.try_into().unwrap_or(usize::MAX));
+}
+
+/// The function called regularly while a regular expression is executing, see
+/// [`set_regexp_hook`].
+///
+/// Receives the context and the number of steps the current match has taken. Returning `true`
+/// aborts the match, the hook must have thrown an exception in the context then.
+pub type RegexpHook = unsafe fn(ctx: *mut JSContext, steps: u64) -> bool;
+
+static REGEXP_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Set the function called every 10000 steps of the backtracking regular expression engine.
+///
+/// After the hook the interrupt handler of the runtime is polled as well, which QuickJS doesn't
+/// do on its own while a regular expression is executing.
+///
+/// The hook is process-wide and may be called from any thread running a runtime. It must not
+/// unwind.
+pub fn set_regexp_hook(hook: Option<RegexpHook>) {
+    let hook = hook.map(|hook| hook as *mut ()).unwrap_or(ptr::null_mut());
+    REGEXP_HOOK.store(hook, Ordering::Release);
+}
+
+/// Called by the patched `lre_exec_backtrack` in `libregexp.c`.
+#[no_mangle]
+unsafe extern "C" fn rquickjs_regexp_poll(ctx: *mut JSContext, steps: u64) -> c_int {
+    let hook = REGEXP_HOOK.load(Ordering::Acquire);
+    if hook.is_null() {
+        return 0;
+    }
+    let hook = mem::transmute::<*mut (), RegexpHook>(hook);
+    hook(ctx, steps) as c_int
+}
+
+extern "C" {
+    /// Mark an error as uncatchable like the error raised for an interrupt.
+    ///
+    /// Wraps `JS_SetUncatchableError`, which QuickJS defines but doesn't export, in the patched
+    /// `quickjs.c`.
+    pub fn rquickjs_set_uncatchable_error(ctx: *mut JSContext, val: JSValue, flag: c_int);
+
+    /// Returns the number of top-level `let`, `const` and `class` declarations made by the
+    /// global scripts evaluated in a context.
+    ///
+    /// QuickJS keeps them in an internal object which isn't reachable through its API.
+    pub fn rquickjs_global_lexical_count(ctx: *mut JSContext) -> c_int;
+
+    /// Returns if an atom is an array index, a canonical number from `0` to `2^32 - 2`, and
+    /// writes the index to `pval`.
+    ///
+    /// Wraps `JS_AtomIsArrayIndex`, which QuickJS defines but doesn't export, in the patched
+    /// `quickjs.c`.
+    pub fn rquickjs_atom_is_array_index(ctx: *mut JSContext, pval: *mut u32, atom: JSAtom)
+        -> c_int;
+
+    /// Create a string from `count` pieces of UTF-8 which are `len` bytes long together.
+    ///
+    /// The pieces are decoded one after another into a single QuickJS string buffer, so they
+    /// don't have to be joined into one buffer first.
+    pub fn rquickjs_new_string_from_chunks(
+        ctx: *mut JSContext,
+        chunks: *const *const c_char,
+        lens: *const size_t,
+        count: size_t,
+        len: size_t,
+    ) -> JSValue;
+}
@@ -1,4 +1,4 @@
-pub use ::std::os::raw::{c_char, c_int, c_uint, c_void};
+pub use ::core::ffi::{c_char, c_int, c_uint, c_void};
 
 pub type JSValueConst = JSValue;
 
@@ -1,3 +1,4 @@
+#![no_std]
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
@@ -5,7 +6,7 @@
 #![allow(clippy::missing_safety_doc)]
 #![allow(clippy::upper_case_acronyms)]
 
-use std::ptr;
+use core::ptr;
 
 /// Common error message for converting between C `size_t` and Rust `usize`;
 pub const SIZE_T_ERROR: &str =
@@ -23,3 +24,47 @@ include!("inlines/ptr_64.rs");
 include!("inlines/ptr_32_nan_boxing.rs");
 
 include!("inlines/common.rs");
+
+#[cfg(not(feature = "system"))]
+include!("hooks.rs");
+
+/// Returns if an atom is an array index, a canonical number from `0` to `2^32 - 2`, and writes
+/// the index to `pval`.
+///
+/// A prebuilt library doesn't export the check, so with the `system` feature the atom is
+/// converted to its value and string atoms are parsed instead.
+#[cfg(feature = "system")]
+pub unsafe fn rquickjs_atom_is_array_index(
+    ctx: *mut JSContext,
+    pval: *mut u32,
+    atom: JSAtom,
+) -> c_int {
+    *pval = 0;
+    let value = JS_AtomToValue(ctx, atom);
+    if !JS_IsString(value) {
+        JS_FreeValue(ctx, value);
+        return 0;
+    }
+    let mut len = 0;
+    let ptr = JS_ToCStringLen(ctx, &mut len, value);
+    JS_FreeValue(ctx, value);
+    if ptr.is_null() {
+        return 0;
+    }
+    let bytes = core::slice::from_raw_parts(ptr as *const u8, len);
+    let index = match bytes {
+        [b'0'] => Some(0),
+        [b'1'..=b'9', ..] if bytes.len() <= 10 => bytes.iter().try_fold(0u64, |acc, x| {
+            x.is_ascii_digit().then(|| acc * 10 + u64::from(x - b'0'))
+        }),
+        _ => None,
+    };
+    JS_FreeCString(ctx, ptr);
+    match index {
+        Some(index) if index < u64::from(u32::MAX) => {
+            *pval = index as u32;
+            1
+        }
+        _ => 0,
+    }
+}
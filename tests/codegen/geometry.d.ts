@@ -0,0 +1,16 @@
+/** A point. */
+export interface Point {
+    readonly x: number;
+    y: number;
+    label?: string;
+    translate(dx: number, dy?: number): Point;
+}
+export declare class Counter {
+    constructor(start?: number);
+    static zero(): Counter;
+    get value(): number;
+    add(...values: number[]): number;
+}
+export declare function distance(a: Point, b: Point): number;
+export declare function tally(words: string[]): Record<string, number>;
+export declare const ORIGIN: Point;
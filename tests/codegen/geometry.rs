@@ -0,0 +1,54 @@
+// Uses the bindings generated from `geometry.d.ts` next to this file.
+include!("bindings.rs");
+
+fn main() {
+    let rt = rquickjs::Runtime::new().unwrap();
+    let ctx = rquickjs::Context::full(&rt).unwrap();
+    ctx.with(|ctx| {
+        ctx.eval::<(), _>(
+            r#"
+            globalThis.distance = (a, b) => Math.hypot(a.x - b.x, a.y - b.y);
+            globalThis.tally = (words) => {
+                const res = {};
+                for (const word of words) res[word] = (res[word] ?? 0) + 1;
+                return res;
+            };
+            globalThis.ORIGIN = {
+                x: 0,
+                y: 0,
+                translate(dx, dy = 0) { return { ...this, x: this.x + dx, y: this.y + dy }; },
+            };
+            globalThis.Counter = class Counter {
+                #value;
+                constructor(start = 0) { this.#value = start; }
+                static zero() { return new this(); }
+                get value() { return this.#value; }
+                add(...values) { for (const value of values) this.#value += value; return this.#value; }
+            };
+            "#,
+        )
+        .unwrap();
+
+        let geo = Geo::globals(&ctx);
+        let origin = geo.origin().unwrap();
+        let point = origin.translate(3.0, Some(4.0)).unwrap();
+        assert_eq!((point.x().unwrap(), point.y().unwrap()), (3.0, 4.0));
+        assert_eq!(origin.translate(1.0, None).unwrap().y().unwrap(), 0.0);
+        assert_eq!(geo.distance(origin.clone(), point.clone()).unwrap(), 5.0);
+
+        assert_eq!(point.label().unwrap(), None);
+        point.set_label(Some("p".into())).unwrap();
+        point.set_y(1.0).unwrap();
+        assert_eq!(point.label().unwrap().as_deref(), Some("p"));
+        assert_eq!(point.y().unwrap(), 1.0);
+
+        let tally = geo.tally(vec!["a".into(), "b".into(), "a".into()]).unwrap();
+        assert_eq!(tally["a"], 2.0);
+
+        let counter = geo.new_counter(Some(1.0)).unwrap();
+        assert_eq!(counter.add(vec![2.0, 3.0]).unwrap(), 6.0);
+        assert_eq!(counter.value().unwrap(), 6.0);
+        assert_eq!(geo.counter_zero().unwrap().value().unwrap(), 0.0);
+        assert_eq!(geo.new_counter(None).unwrap().add(Vec::new()).unwrap(), 0.0);
+    });
+}
@@ -10,3 +10,67 @@ fn macros() {
     #[cfg(all(feature = "futures", feature = "parallel", feature = "compile-tests"))]
     t.compile_fail("tests/async_parallel_compile_fail/*.rs");
 }
+
+/// Compiles and runs the Rust source written by `rqjsc embed`.
+#[cfg(feature = "bin")]
+#[test]
+fn rqjsc_embed() {
+    use std::{fs, path::Path, process::Command};
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("rqjsc_embed");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("main.js"),
+        "import { add } from './math.js'; globalThis.res = add(1, 2);",
+    )
+    .unwrap();
+    fs::write(dir.join("math.js"), "export const add = (a, b) => a + b;").unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_rqjsc"))
+        .args(["embed", "-n", "app", "-o", "app.rs", "main.js"])
+        .current_dir(&dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::write(
+        dir.join("embed.rs"),
+        r#"include!("app.rs");
+
+fn main() {
+    let rt = rquickjs::Runtime::new().unwrap();
+    let ctx = rquickjs::Context::full(&rt).unwrap();
+    let bundle = app();
+    rt.set_loader(bundle.clone(), bundle.clone());
+    ctx.with(|ctx| {
+        bundle.import(&ctx).unwrap().finish::<()>().unwrap();
+        assert_eq!(ctx.globals().get::<_, i32>("res").unwrap(), 3);
+    });
+}
+"#,
+    )
+    .unwrap();
+
+    let t = trybuild::TestCases::new();
+    t.pass(dir.join("embed.rs"));
+}
+
+/// Compiles and runs a program using the bindings generated from `tests/codegen/geometry.d.ts`.
+#[cfg(feature = "codegen")]
+#[test]
+fn codegen_bindings() {
+    use rquickjs::codegen::Bindings;
+    use std::{fs, path::Path};
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("codegen");
+    fs::create_dir_all(&dir).unwrap();
+    let declarations = fs::read_to_string("tests/codegen/geometry.d.ts").unwrap();
+    let source = Bindings::new()
+        .with_name("Geo")
+        .generate(&declarations)
+        .unwrap();
+    fs::write(dir.join("bindings.rs"), source).unwrap();
+    let program = dir.join("geometry.rs");
+    fs::copy("tests/codegen/geometry.rs", &program).unwrap();
+
+    let t = trybuild::TestCases::new();
+    t.pass(program);
+}
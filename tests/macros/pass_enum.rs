@@ -0,0 +1,84 @@
+use rquickjs::{CatchResultExt, Class, Context, JsEnum, Module, Runtime};
+
+#[derive(rquickjs::class::Trace, rquickjs::JsLifetime)]
+#[rquickjs::class]
+pub struct Pixel {
+    #[qjs(skip_trace)]
+    color: Color,
+}
+
+#[rquickjs::methods]
+impl Pixel {
+    #[qjs(constructor)]
+    pub fn new(color: Color) -> Pixel {
+        Pixel { color }
+    }
+
+    #[qjs(get)]
+    pub fn color(&self) -> Color {
+        self.color
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, JsEnum)]
+#[qjs(rename_all = "SCREAMING_SNAKE")]
+pub enum Color {
+    Red,
+    Green,
+    Blue = 10,
+}
+
+#[rquickjs::module]
+mod test_mod {
+    /// Enums deriving `JsEnum` are exported as their constants.
+    #[derive(Clone, Copy, rquickjs::JsEnum)]
+    #[qjs(rename = "Mode", string, rename_all = "lowercase")]
+    pub enum FileMode {
+        Read,
+        Write,
+    }
+}
+
+fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        // Expose the enum as a static of the class.
+        let constructor = Class::<Pixel>::create_constructor(&ctx).unwrap().unwrap();
+        constructor
+            .set(Color::NAME, Color::namespace(&ctx).unwrap())
+            .unwrap();
+        ctx.globals().set("Pixel", constructor).unwrap();
+
+        Module::declare_def::<js_test_mod, _>(ctx.clone(), "test").unwrap();
+        Module::evaluate(
+            ctx.clone(),
+            "test2",
+            r"
+            import { Mode } from 'test';
+            if (Mode.write !== 'write' || !Object.isFrozen(Mode)){
+                throw new Error(1);
+            }
+            const pixel = new Pixel(Pixel.Color.BLUE);
+            if (pixel.color !== 10 || Pixel.Color[pixel.color] !== 'BLUE'){
+                throw new Error(2);
+            }
+            let failed = false;
+            try {
+                new Pixel(3);
+            } catch {
+                failed = true;
+            }
+            if (!failed){
+                throw new Error(3);
+            }
+        ",
+        )
+        .catch(&ctx)
+        .unwrap()
+        .finish::<()>()
+        .catch(&ctx)
+        .unwrap();
+    })
+}
@@ -0,0 +1,68 @@
+use rquickjs::{
+    function::{Func, Named},
+    CatchResultExt, Context, FromJs, Runtime, Value,
+};
+
+#[derive(Default, FromJs)]
+#[qjs(rename_all = "camelCase")]
+pub struct FormatOptions {
+    #[qjs(default)]
+    indent: usize,
+    #[qjs(default)]
+    line_prefix: String,
+    #[qjs(rename = "sep", default)]
+    separator: Option<String>,
+}
+
+#[derive(FromJs)]
+pub struct Wrapper<'js, T> {
+    inner: T,
+    raw: Value<'js>,
+}
+
+fn format(lines: Vec<String>, Named(opts): Named<FormatOptions>) -> String {
+    let separator = opts.separator.unwrap_or_else(|| "\n".to_string());
+    lines
+        .iter()
+        .map(|line| format!("{}{}{}", opts.line_prefix, " ".repeat(opts.indent), line))
+        .collect::<Vec<_>>()
+        .join(&separator)
+}
+
+fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        ctx.globals().set("format", Func::from(format)).unwrap();
+        ctx.eval::<(), _>(
+            r#"
+            if (format(["a", "b"]) !== "a\nb") {
+                throw new Error(1);
+            }
+            if (format(["a", "b"], { indent: 1, linePrefix: ">", sep: "," }) !== "> a,> b") {
+                throw new Error(2);
+            }
+            if (format(["a"], { indent: undefined, sep: undefined }) !== "a") {
+                throw new Error(3);
+            }
+            let failed = false;
+            try {
+                format(["a"], { indent: "wide" });
+            } catch {
+                failed = true;
+            }
+            if (!failed) {
+                throw new Error(4);
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+
+        let wrapper: Wrapper<u32> = ctx.eval("({ inner: 3, raw: 'x' })").unwrap();
+        assert_eq!(wrapper.inner, 3);
+        assert!(wrapper.raw.is_string());
+        assert!(ctx.eval::<Wrapper<u32>, _>("({ raw: 1 })").is_err());
+    })
+}